@@ -18,10 +18,11 @@ use crate::{
     operation::{self, CallContextField, Operation, RWCounter, StartOp, StorageOp, RW},
     rpc::GethClient,
     state_db::{self, CodeDB, StateDB},
+    trace_provider::WasmTraceProvider,
 };
 pub use access::{Access, AccessSet, AccessValue, CodeSource};
-pub use block::{Block, BlockContext};
-pub use call::{Call, CallContext, CallKind};
+pub use block::{Block, BlockContext, ChainSpec, FeeModel, Fork};
+pub use call::{Call, CallContext, CallKind, FunctionFrame};
 use core::fmt::Debug;
 use eth_types::{
     self,
@@ -236,6 +237,15 @@ impl<'a> CircuitInputBuilder {
     ) -> Result<(), Error> {
         self.handle_block_inner(eth_block, geth_traces, true, true)
     }
+    /// Handle a block with the transaction traces of a [`WasmTraceProvider`].
+    pub fn handle_block_from_provider(
+        &mut self,
+        eth_block: &EthBlock,
+        provider: &impl WasmTraceProvider,
+    ) -> Result<(), Error> {
+        let geth_traces = provider.block_traces(eth_block)?;
+        self.handle_block(eth_block, &geth_traces)
+    }
     /// Handle a block by handling each transaction to generate all the
     /// associated operations.
     pub fn handle_block_inner(
@@ -340,6 +350,10 @@ impl<'a> CircuitInputBuilder {
         log::debug!("memory num: {}", self.block.container.memory.len());
         log::debug!("stack num: {}", self.block.container.stack.len());
         log::debug!("storage num: {}", self.block.container.storage.len());
+        log::debug!(
+            "transient_storage num: {}",
+            self.block.container.transient_storage.len()
+        );
         log::debug!(
             "tx_access_list_account num: {}",
             self.block.container.tx_access_list_account.len()
@@ -359,6 +373,11 @@ impl<'a> CircuitInputBuilder {
         log::debug!("start num: {}", self.block.container.start.len());
     }
 
+    /// Wasm modules executed by calls of the block, see [`wasm_codes`].
+    pub fn wasm_codes(&self) -> Vec<(H256, Vec<u8>)> {
+        wasm_codes(&self.block, &self.code_db)
+    }
+
     /// ..
     pub fn set_end_block(&mut self) -> Result<(), Error> {
         use crate::l2_predeployed::message_queue::{
@@ -453,6 +472,10 @@ impl<'a> CircuitInputBuilder {
         geth_trace: &GethExecTrace,
         is_last_tx: bool,
     ) -> Result<(), Error> {
+        geth_trace
+            .wasm_extension()
+            .validate()
+            .map_err(Error::InvalidWasmTrace)?;
         let mut tx = self.new_tx(eth_tx, !geth_trace.failed)?;
 
         // Sanity check for transaction L1 fee.
@@ -628,6 +651,18 @@ pub fn keccak_inputs(block: &Block, code_db: &CodeDB) -> Result<Vec<Vec<u8>>, Er
     Ok(keccak_inputs)
 }
 
+/// Return the wasm modules executed by calls of the block, keyed by the call's code hash, so
+/// the wasm circuit can be wired without listing modules manually.
+pub fn wasm_codes(block: &Block, code_db: &CodeDB) -> Vec<(H256, Vec<u8>)> {
+    code_db.wasm_codes(
+        block
+            .txs()
+            .iter()
+            .flat_map(|tx| tx.calls())
+            .map(|call| call.code_hash),
+    )
+}
+
 /// Generate the keccak inputs required by the SignVerify Chip from the
 /// signature datas.
 pub fn keccak_inputs_sign_verify(sigs: &[SignData]) -> Vec<Vec<u8>> {
@@ -791,22 +826,35 @@ pub fn keccak_inputs_tx_circuit(
     Ok(inputs)
 }
 
-/// Retrieve the init_code from memory for {CREATE, CREATE2}
-pub fn get_create_init_code<'a>(
-    call_ctx: &'a CallContext,
-    step: &GethExecStep,
-) -> Result<&'a [u8], Error> {
-    let offset = step.stack.nth_last(1)?.low_u64() as usize;
-    let length = step.stack.nth_last(2)?.as_usize();
-
-    let mem_len = call_ctx.memory.0.len();
-    if offset >= mem_len {
-        return Ok(&[]);
-    }
+/// Whether a {CALL, CREATE}* step succeeded, read from the result offset at the step after it.
+/// Calls write a status byte, creates write the created address, zero on failure.
+pub fn call_result_is_success(step: &GethExecStep, next_step: &GethExecStep) -> Result<bool, Error> {
+    let result_offset = step.stack.last()?;
+    Ok(if step.op.is_create() {
+        !next_step.global_memory.read_address(result_offset)?.is_zero()
+    } else {
+        next_step.global_memory.read_u8(result_offset)? != 0u8
+    })
+}
 
-    let offset_end = offset.checked_add(length).unwrap_or(mem_len);
+/// Retrieve the init_code memory offset and length of {CREATE, CREATE2}.
+pub fn get_create_init_code_offset_length(step: &GethExecStep) -> Result<(u64, u64), Error> {
+    // the init code range is pushed before the salt offset of CREATE2 and the result offset
+    let nth = if step.op == OpcodeId::CREATE2 { 2 } else { 1 };
+    let length = step.stack.nth_last(nth)?;
+    if length.is_zero() {
+        Ok((0, 0))
+    } else {
+        Ok((step.stack.nth_last(nth + 1)?.low_u64(), length.low_u64()))
+    }
+}
 
-    Ok(&call_ctx.memory.0[offset..offset_end])
+/// Retrieve the init_code from the linear memory for {CREATE, CREATE2}
+pub fn get_create_init_code(step: &GethExecStep) -> Result<Vec<u8>, Error> {
+    let (offset, length) = get_create_init_code_offset_length(step)?;
+    Ok(step
+        .global_memory
+        .read_chunk(offset.into(), (length as usize).into()))
 }
 
 /// Retrieve the memory offset and length of call.
@@ -4,10 +4,11 @@ use super::{
     execution::ExecState, transaction::Transaction, CircuitsParams, CopyEvent, ExecStep, ExpEvent,
 };
 use crate::{
+    evm::OpcodeId,
     operation::{OperationContainer, RWCounter},
     Error,
 };
-use eth_types::{Address, Hash, ToWord, Word, U256};
+use eth_types::{evm_types::MAX_WASM_MEMORY_PAGES, Address, Hash, ToWord, Word, U256};
 use std::collections::{BTreeMap, HashMap};
 
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
@@ -131,6 +132,121 @@ impl BlockHead {
     }
 }
 
+/// How the end of a transaction rewards the coinbase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeModel {
+    /// Coinbase gets the whole gas price
+    Legacy,
+    /// Coinbase gets the gas price above the base fee, the base fee is burned
+    Eip1559,
+    /// [`FeeModel::Eip1559`] plus the L1 fee of the transaction
+    RollupL1Fee,
+}
+
+impl Default for FeeModel {
+    fn default() -> Self {
+        if cfg!(feature = "scroll") {
+            FeeModel::RollupL1Fee
+        } else {
+            FeeModel::Eip1559
+        }
+    }
+}
+
+impl FeeModel {
+    /// Base fee per gas that is burned instead of paid to the coinbase
+    pub fn burned_base_fee(&self, base_fee: Word) -> Word {
+        match self {
+            FeeModel::Legacy => Word::zero(),
+            FeeModel::Eip1559 | FeeModel::RollupL1Fee => base_fee,
+        }
+    }
+
+    /// Whether the L1 fee of the transaction is paid to the coinbase
+    pub fn pays_l1_fee(&self) -> bool {
+        *self == FeeModel::RollupL1Fee
+    }
+
+    /// Reward of the coinbase for a transaction using `gas_used` gas
+    pub fn coinbase_reward(
+        &self,
+        gas_price: Word,
+        base_fee: Word,
+        gas_used: u64,
+        l1_fee: u64,
+    ) -> Word {
+        let effective_tip = gas_price - self.burned_base_fee(base_fee);
+        let l1_fee = if self.pays_l1_fee() { l1_fee } else { 0 };
+        effective_tip * gas_used + l1_fee
+    }
+}
+
+/// Hard fork deciding which EVM-side host calls exist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Fork {
+    /// No host call introduced after London
+    London,
+    /// Adds `PUSH0`
+    Shanghai,
+    /// Adds `TLOAD`, `TSTORE` and `MCOPY`
+    Cancun,
+}
+
+impl Fork {
+    /// Fork introducing `op`, `None` if it has always been available
+    pub fn introducing(op: OpcodeId) -> Option<Fork> {
+        match op {
+            OpcodeId::PUSH0 => Some(Fork::Shanghai),
+            OpcodeId::TLOAD | OpcodeId::TSTORE | OpcodeId::MCOPY => Some(Fork::Cancun),
+            _ => None,
+        }
+    }
+}
+
+/// Opcodes and wasm proposals enabled on the chain, an opcode that is not enabled halts
+/// with an invalid opcode error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainSpec {
+    /// Active hard fork
+    pub fork: Fork,
+    /// Whether the wasm tail call proposal (`return_call`, `return_call_indirect`) is enabled
+    pub tail_call: bool,
+}
+
+impl Default for ChainSpec {
+    fn default() -> Self {
+        Self {
+            fork: Fork::Cancun,
+            tail_call: true,
+        }
+    }
+}
+
+impl ChainSpec {
+    /// Whether `op` can be executed on the chain
+    pub fn is_opcode_enabled(&self, op: OpcodeId) -> bool {
+        match op {
+            OpcodeId::ReturnCall | OpcodeId::ReturnCallIndirect => self.tail_call,
+            _ => Fork::introducing(op).map_or(true, |fork| fork <= self.fork),
+        }
+    }
+
+    /// Opcodes a wasm trace can contain that are not enabled on the chain. `PUSH0` has no wasm
+    /// encoding (it shares its byte with `unreachable`) so it never shows up here.
+    pub fn disabled_opcodes(&self) -> Vec<OpcodeId> {
+        [
+            OpcodeId::TLOAD,
+            OpcodeId::TSTORE,
+            OpcodeId::MCOPY,
+            OpcodeId::ReturnCall,
+            OpcodeId::ReturnCallIndirect,
+        ]
+        .into_iter()
+        .filter(|op| !self.is_opcode_enabled(*op))
+        .collect()
+    }
+}
+
 /// Circuit Input related to a block.
 #[derive(Debug, Default, Clone)]
 pub struct Block {
@@ -161,6 +277,12 @@ pub struct Block {
     pub circuits_params: CircuitsParams,
     /// chain id
     pub chain_id: Word,
+    /// Fee model of the chain, must match the one the evm circuit is configured with
+    pub fee_model: FeeModel,
+    /// Opcodes enabled on the chain, must match the one of the evm circuit fixed table
+    pub chain_spec: ChainSpec,
+    /// Memory page limit of `memory.grow`, must match the one the evm circuit is configured with
+    pub max_wasm_memory_pages: u64,
 }
 
 impl Block {
@@ -182,6 +304,7 @@ impl Block {
                 .map(|b| (b.number.as_u64(), b.clone()))
                 .collect::<BTreeMap<_, _>>(),
             circuits_params,
+            max_wasm_memory_pages: MAX_WASM_MEMORY_PAGES,
             ..Default::default()
         }
     }
@@ -206,6 +329,7 @@ impl Block {
             exp_events: Vec::new(),
             chain_id,
             circuits_params,
+            max_wasm_memory_pages: MAX_WASM_MEMORY_PAGES,
             ..Default::default()
         };
         let info = BlockHead::new(chain_id, history_hashes, eth_block)?;
@@ -247,3 +371,58 @@ impl Block {
         self.exp_events.push(event);
     }
 }
+
+#[cfg(test)]
+mod fee_model_tests {
+    use super::*;
+
+    #[test]
+    fn coinbase_reward() {
+        let (gas_price, base_fee) = (Word::from(10), Word::from(7));
+        assert_eq!(
+            FeeModel::Legacy.coinbase_reward(gas_price, base_fee, 100, 5),
+            Word::from(1000)
+        );
+        assert_eq!(
+            FeeModel::Eip1559.coinbase_reward(gas_price, base_fee, 100, 5),
+            Word::from(300)
+        );
+        assert_eq!(
+            FeeModel::RollupL1Fee.coinbase_reward(gas_price, base_fee, 100, 5),
+            Word::from(305)
+        );
+    }
+}
+
+#[cfg(test)]
+mod chain_spec_tests {
+    use super::*;
+
+    #[test]
+    fn opcodes_enabled_by_fork() {
+        let london = ChainSpec {
+            fork: Fork::London,
+            tail_call: true,
+        };
+        assert!(!london.is_opcode_enabled(OpcodeId::PUSH0));
+        assert!(london.is_opcode_enabled(OpcodeId::ReturnCall));
+
+        let shanghai = ChainSpec {
+            fork: Fork::Shanghai,
+            tail_call: false,
+        };
+        assert!(shanghai.is_opcode_enabled(OpcodeId::PUSH0));
+        assert!(shanghai.is_opcode_enabled(OpcodeId::SSTORE));
+        assert_eq!(
+            shanghai.disabled_opcodes(),
+            vec![
+                OpcodeId::TLOAD,
+                OpcodeId::TSTORE,
+                OpcodeId::MCOPY,
+                OpcodeId::ReturnCall,
+                OpcodeId::ReturnCallIndirect,
+            ]
+        );
+        assert!(ChainSpec::default().disabled_opcodes().is_empty());
+    }
+}
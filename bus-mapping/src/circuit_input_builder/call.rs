@@ -2,8 +2,9 @@ use super::CodeSource;
 use crate::{exec_trace::OperationRef, Error};
 use eth_types::{
     evm_types::{Memory, OpcodeId},
-    Address, Hash, Word,
+    Address, GethExecTraceFunctionCall, Hash, StackWord, Word,
 };
+use std::collections::HashMap;
 
 /// Type of a *CALL*/CREATE* Function.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -143,6 +144,15 @@ pub struct CallContext {
     pub memory: Memory,
     /// return data buffer
     pub return_data: Vec<u8>,
+    /// WASM functions reported by the tracer for this call
+    pub function_calls: Vec<GethExecTraceFunctionCall>,
+    /// stack of the WASM function frames, the entry function first
+    pub function_frames: Vec<FunctionFrame>,
+    /// WASM globals by index, as of the last global operation of the call
+    pub globals: HashMap<u32, StackWord>,
+    /// WASM table elements by table and element index, as of the last table operation of the
+    /// call
+    pub tables: HashMap<(u32, u32), StackWord>,
 }
 
 impl CallContext {
@@ -150,6 +160,34 @@ impl CallContext {
     pub fn memory_word_size(&self) -> u64 {
         u64::try_from(self.memory.len()).expect("failed to convert usize to u64") / 32
     }
+
+    /// Frame of the WASM function being executed
+    pub fn function_frame(&self) -> FunctionFrame {
+        self.function_frames.last().cloned().unwrap_or_default()
+    }
+}
+
+/// Frame of a WASM function executing in a call.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FunctionFrame {
+    /// Id of the frame: the rw counter of the `call` which pushed it, 0 for the
+    /// entry function. The caller's frame is saved in the call context rows of
+    /// this id.
+    pub frame_id: usize,
+    /// Index of the function
+    pub function_index: u32,
+    /// Max stack height of the function
+    pub max_stack_height: u32,
+    /// Number of locals of the function
+    pub num_locals: u32,
+    /// Program counter of the caller to continue at on return
+    pub return_pc: usize,
+    /// Stack pointer of the caller at the `call`, the arguments are on top of it
+    pub locals_base: usize,
+    /// Number of params of the function
+    pub num_params: usize,
+    /// Number of results of the function, moved to the caller's stack on return
+    pub num_results: usize,
 }
 
 /// A reversion group is the collection of calls and the operations which are
@@ -57,6 +57,11 @@ pub struct ExecStep {
     pub max_stack_height: u32,
     ///
     pub num_locals: u32,
+    /// Id of the WASM function frame, see
+    /// [`FunctionFrame`](crate::circuit_input_builder::FunctionFrame)
+    pub frame_id: usize,
+    /// Immediate operands of the WASM instruction
+    pub params: Vec<u64>,
 }
 
 impl ExecStep {
@@ -68,6 +73,7 @@ impl ExecStep {
         reversible_write_counter: usize,
         log_id: usize,
     ) -> Self {
+        let frame = call_ctx.function_frame();
         ExecStep {
             exec_state: ExecState::Op(step.op),
             pc: step.pc,
@@ -84,9 +90,11 @@ impl ExecStep {
             bus_mapping_instance: Vec::new(),
             copy_rw_counter_delta: 0,
             error: None,
-            function_index: 0,
-            max_stack_height: 0,
-            num_locals: 0,
+            function_index: frame.function_index,
+            max_stack_height: frame.max_stack_height,
+            num_locals: frame.num_locals,
+            frame_id: frame.frame_id,
+            params: step.params.clone(),
         }
     }
 
@@ -125,6 +133,8 @@ impl Default for ExecStep {
             function_index: 0,
             num_locals: 0,
             max_stack_height: 0,
+            frame_id: 0,
+            params: Vec::new(),
         }
     }
 }
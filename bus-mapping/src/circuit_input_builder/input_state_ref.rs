@@ -1,16 +1,16 @@
 //! CircuitInput builder tooling module.
 
 use super::{
-    get_call_memory_offset_length, get_create_init_code, Block, BlockContext, Call, CallContext,
-    CallKind, CodeSource, CopyEvent, ExecState, ExecStep, ExpEvent, Transaction,
-    TransactionContext,
+    call_result_is_success, get_call_memory_offset_length, get_create_init_code, Block,
+    BlockContext, Call, CallContext, CallKind, CodeSource, CopyEvent, ExecState, ExecStep,
+    ExpEvent, Transaction, TransactionContext,
 };
 #[cfg(feature = "scroll")]
 use crate::util::KECCAK_CODE_HASH_ZERO;
 use crate::{
     error::{
         get_step_reported_error, ContractAddressCollisionError, DepthError, ExecError,
-        InsufficientBalanceError, NonceUintOverflowError,
+        InsufficientBalanceError, NonceUintOverflowError, WasmTrapKind,
     },
     exec_trace::OperationRef,
     operation::{
@@ -20,14 +20,15 @@ use crate::{
     },
     precompile::is_precompiled,
     state_db::{CodeDB, StateDB},
+    wasm::WASM_MEMORY_CELL_SIZE,
     Error,
 };
 use eth_types::{evm_types::{
-    Gas, GasCost, MemoryAddress, OpcodeId, StackAddress,
+    Gas, GasCost, MemoryAddress, OpcodeId, StackAddress, MAX_WASM_STACK_HEIGHT,
 }, Address, Bytecode, GethExecStep, ToBigEndian, ToWord, Word, H256, U256, StackWord, ToU256, ToAddress};
 use ethers_core::utils::{get_contract_address, get_create2_address, keccak256};
 use std::cmp::max;
-use crate::operation::GlobalOp;
+use crate::operation::{GlobalOp, TableOp};
 
 /// Reference to the internal state of the CircuitInputBuilder in a particular
 /// [`ExecStep`].
@@ -252,6 +253,30 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(())
     }
 
+    /// Push the read of the wasm memory cell `cell_index` as
+    /// [`WASM_MEMORY_CELL_SIZE`] byte reads starting at the cell boundary
+    pub fn memory_read_cell(
+        &mut self,
+        step: &mut ExecStep,
+        cell_index: u64,
+        cell: u64,
+    ) -> Result<(), Error> {
+        let address = MemoryAddress((cell_index * WASM_MEMORY_CELL_SIZE) as usize);
+        self.memory_read_n(step, address, &cell.to_le_bytes())
+    }
+
+    /// Push the write of the wasm memory cell `cell_index` as
+    /// [`WASM_MEMORY_CELL_SIZE`] byte writes starting at the cell boundary
+    pub fn memory_write_cell(
+        &mut self,
+        step: &mut ExecStep,
+        cell_index: u64,
+        cell: u64,
+    ) -> Result<(), Error> {
+        let address = MemoryAddress((cell_index * WASM_MEMORY_CELL_SIZE) as usize);
+        self.memory_write_n(step, address, &cell.to_le_bytes())
+    }
+
     /// Push a write type [`MemoryOp`] into the
     /// [`OperationContainer`](crate::operation::OperationContainer) with the
     /// next [`RWCounter`](crate::operation::RWCounter) and `call_id`, and then
@@ -269,6 +294,37 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(())
     }
 
+    /// Takes the linear memory of `next_step` as the memory of the current call after a copy
+    /// into it, the bytes written by the host must be the bytes of the copy event
+    pub fn sync_linear_memory_after_copy(
+        &mut self,
+        copy_event: &CopyEvent,
+        next_step: &GethExecStep,
+    ) -> Result<(), Error> {
+        let written = next_step.global_memory.read_chunk(
+            MemoryAddress(copy_event.dst_addr as usize),
+            MemoryAddress(copy_event.bytes.len()),
+        );
+        if copy_event.bytes.iter().map(|(b, _)| *b).ne(written) {
+            return Err(Error::InvalidGethExecTrace(
+                "copied bytes differ from the linear memory",
+            ));
+        }
+        self.call_ctx_mut()?.memory = next_step.global_memory.clone();
+        Ok(())
+    }
+
+    /// Value of the global `global_index` of the current call after the last global operation
+    /// pushed so far, globals start at zero
+    fn global_value(&self, global_index: u32) -> Result<StackWord, Error> {
+        Ok(self
+            .call_ctx()?
+            .globals
+            .get(&global_index)
+            .copied()
+            .unwrap_or_default())
+    }
+
     ///
     pub fn global_write(
         &mut self,
@@ -277,7 +333,13 @@ impl<'a> CircuitInputStateRef<'a> {
         value: StackWord,
     ) -> Result<(), Error> {
         let call_id = self.call()?.call_id;
-        self.push_op(step, RW::WRITE, GlobalOp::new(call_id, global_index, value));
+        let value_prev = self.global_value(global_index)?;
+        self.push_op(
+            step,
+            RW::WRITE,
+            GlobalOp::new(call_id, global_index, value, value_prev),
+        );
+        self.call_ctx_mut()?.globals.insert(global_index, value);
         Ok(())
     }
 
@@ -289,7 +351,60 @@ impl<'a> CircuitInputStateRef<'a> {
         value: StackWord,
     ) -> Result<(), Error> {
         let call_id = self.call()?.call_id;
-        self.push_op(step, RW::READ, GlobalOp::new(call_id, global_index, value));
+        self.push_op(step, RW::READ, GlobalOp::new(call_id, global_index, value, value));
+        self.call_ctx_mut()?.globals.insert(global_index, value);
+        Ok(())
+    }
+
+    /// Funcref held by the element `elem_index` of the table `table_index` of the current call
+    /// after the last table operation pushed so far, elements start as the null reference
+    pub fn table_value(&self, table_index: u32, elem_index: u32) -> Result<StackWord, Error> {
+        Ok(self
+            .call_ctx()?
+            .tables
+            .get(&(table_index, elem_index))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    ///
+    pub fn table_write(
+        &mut self,
+        step: &mut ExecStep,
+        table_index: u32,
+        elem_index: u32,
+        value: StackWord,
+    ) -> Result<(), Error> {
+        let call_id = self.call()?.call_id;
+        let value_prev = self.table_value(table_index, elem_index)?;
+        self.push_op(
+            step,
+            RW::WRITE,
+            TableOp::new(call_id, table_index, elem_index, value, value_prev),
+        );
+        self.call_ctx_mut()?
+            .tables
+            .insert((table_index, elem_index), value);
+        Ok(())
+    }
+
+    ///
+    pub fn table_read(
+        &mut self,
+        step: &mut ExecStep,
+        table_index: u32,
+        elem_index: u32,
+        value: StackWord,
+    ) -> Result<(), Error> {
+        let call_id = self.call()?.call_id;
+        self.push_op(
+            step,
+            RW::READ,
+            TableOp::new(call_id, table_index, elem_index, value, value),
+        );
+        self.call_ctx_mut()?
+            .tables
+            .insert((table_index, elem_index), value);
         Ok(())
     }
 
@@ -846,11 +961,10 @@ impl<'a> CircuitInputStateRef<'a> {
     }
 
     /// Return the contract address of a CREATE2 step.  This is calculated
-    /// deterministically from the arguments in the stack.
+    /// deterministically from the salt and the init code in the linear memory.
     pub(crate) fn create2_address(&self, step: &GethExecStep) -> Result<Address, Error> {
-        let salt = step.stack.nth_last(3)?;
-        let call_ctx = self.call_ctx()?;
-        let init_code = get_create_init_code(call_ctx, step)?.to_vec();
+        let salt = step.global_memory.read_u256(step.stack.nth_last(1)?)?;
+        let init_code = get_create_init_code(step)?;
         let address =
             get_create2_address(self.call()?.address, salt.to_be_bytes().to_vec(), init_code);
         log::trace!(
@@ -888,7 +1002,6 @@ impl<'a> CircuitInputStateRef<'a> {
             .unwrap();
         let kind = CallKind::try_from(step.op)?;
         let caller = self.call()?;
-        let caller_ctx = self.call_ctx()?;
 
         let (caller_address, address, value) = match kind {
             CallKind::Call => {
@@ -910,7 +1023,7 @@ impl<'a> CircuitInputStateRef<'a> {
                 (caller.address, address, Word::zero())
             },
             CallKind::Create => {
-                let value_offset = step.stack.nth_last(2)?;
+                let value_offset = step.stack.nth_last(3)?;
                 let value = step.global_memory.read_u256(value_offset)?;
                 (caller.address, self.create_address()?, value)
             },
@@ -923,7 +1036,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
         let (code_source, code_hash) = match kind {
             CallKind::Create | CallKind::Create2 => {
-                let init_code = get_create_init_code(caller_ctx, step)?.to_vec();
+                let init_code = get_create_init_code(step)?;
                 let code_hash = self.code_db.insert(init_code);
                 (CodeSource::Memory, code_hash)
             }
@@ -999,6 +1112,14 @@ impl<'a> CircuitInputStateRef<'a> {
                     None
                 }
             }
+            OperationRef(Target::TransientStorage, idx) => {
+                let operation = &self.block.container.transient_storage[*idx];
+                if operation.rw().is_write() && operation.reversible() {
+                    Some(OpEnum::TransientStorage(operation.op().reverse()))
+                } else {
+                    None
+                }
+            }
             OperationRef(Target::TxAccessListAccount, idx) => {
                 let operation = &self.block.container.tx_access_list_account[*idx];
                 if operation.rw().is_write() && operation.reversible() {
@@ -1041,6 +1162,10 @@ impl<'a> CircuitInputStateRef<'a> {
             OpEnum::Storage(op) => {
                 self.sdb.set_storage(&op.address, &op.key, &op.value);
             }
+            OpEnum::TransientStorage(op) => {
+                self.sdb
+                    .set_transient_storage(&op.address, &op.key, &op.value);
+            }
             OpEnum::TxAccessListAccount(op) => {
                 if !op.is_warm_prev && op.is_warm {
                     self.sdb.add_account_to_access_list(op.address);
@@ -1169,7 +1294,6 @@ impl<'a> CircuitInputStateRef<'a> {
         };
 
         let call = self.call()?.clone();
-        let call_ctx = self.call_ctx()?;
         let call_success_create: bool =
             call.is_create() && call.is_success && step.op == OpcodeId::RETURN;
 
@@ -1177,9 +1301,14 @@ impl<'a> CircuitInputStateRef<'a> {
         if call_success_create {
             let length = step.stack.nth_last(0)?;
             let offset = step.stack.nth_last(1)?;
-            let code = call_ctx
-                .memory
+            let code = step
+                .global_memory
                 .read_chunk(offset.low_u64().into(), length.low_u64().into());
+            if !CodeDB::is_wasm_module(&code) {
+                return Err(Error::InvalidGethExecTrace(
+                    "deployed code is not a wasm module",
+                ));
+            }
             let keccak_code_hash = H256(keccak256(&code));
             let code_hash = self.code_db.insert(code);
             let (found, callee_account) = self.sdb.get_account_mut(&call.address);
@@ -1365,15 +1494,61 @@ impl<'a> CircuitInputStateRef<'a> {
         self.block.add_exp_event(event)
     }
 
+    /// Whether the next step is the first one of the entry function, right after `BeginTx`
+    pub(crate) fn is_wasm_entry_step(&self) -> bool {
+        matches!(
+            self.tx.steps().last().map(|step| &step.exec_state),
+            Some(ExecState::BeginTx)
+        )
+    }
+
+    /// Number of stack slots `step` takes on top of its stack: the locals of the entry function
+    /// at its first step, the locals of the callee for `call` and one for the opcodes pushing a
+    /// value.
+    pub(crate) fn wasm_stack_growth(&self, step: &GethExecStep) -> Result<u64, Error> {
+        if self.is_wasm_entry_step() {
+            return Ok(self.call_ctx()?.function_frame().num_locals as u64);
+        }
+        Ok(match step.op {
+            OpcodeId::I32Const
+            | OpcodeId::I64Const
+            | OpcodeId::F32Const
+            | OpcodeId::F64Const
+            | OpcodeId::GetLocal
+            | OpcodeId::GetGlobal
+            | OpcodeId::CurrentMemory => 1,
+            OpcodeId::Call => self
+                .call_ctx()?
+                .function_calls
+                .iter()
+                .find(|function_call| function_call.fn_index as u64 == step.params[0])
+                .map(|function_call| function_call.num_locals as u64)
+                .unwrap_or_default(),
+            _ => 0,
+        })
+    }
+
     pub(crate) fn get_step_err(
         &self,
         step: &GethExecStep,
         next_step: Option<&GethExecStep>,
     ) -> Result<Option<ExecError>, Error> {
-        if matches!(step.op, OpcodeId::INVALID(_)) {
+        if matches!(step.op, OpcodeId::INVALID(_))
+            || !self.block.chain_spec.is_opcode_enabled(step.op)
+        {
             return Ok(Some(ExecError::InvalidOpcode));
         }
 
+        if step.op == OpcodeId::Unreachable {
+            return Ok(Some(ExecError::WasmTrap(WasmTrapKind::Unreachable)));
+        }
+
+        // the stack of a step is the one before its opcode, the engine traps when the opcode
+        // grows it above the limit
+        if step.stack.0.len() as u64 + self.wasm_stack_growth(step)? > MAX_WASM_STACK_HEIGHT {
+            return Ok(Some(ExecError::WasmTrap(WasmTrapKind::StackHeightExceeded)));
+        }
+
         if let Some(error) = &step.error {
             return Ok(Some(get_step_reported_error(&step.op, error)));
         }
@@ -1400,21 +1575,21 @@ impl<'a> CircuitInputStateRef<'a> {
 
         let next_depth = next_step.map(|s| s.depth).unwrap_or(0);
         let next_result = next_step
-            .map(|s| {
-                let result_offset = step.stack.last().unwrap_or_else(|_| StackWord::zero());
-                StackWord::from(s.global_memory.read_u8(result_offset).unwrap_or_default())
-            })
+            .map(|s| StackWord::from(call_result_is_success(step, s).unwrap_or_default() as u8))
             .unwrap_or_else(StackWord::zero);
 
-        let call_ctx = self.call_ctx()?;
         // get value first if call/create
         let value = match step.op {
             OpcodeId::CALL | OpcodeId::CALLCODE => {
                 let value_offset = step.stack.nth_last(5)?;
                 step.global_memory.read_u256(value_offset)?
             }
-            OpcodeId::CREATE | OpcodeId::CREATE2 => {
-                let value_offset = step.stack.nth_last(2)?;
+            OpcodeId::CREATE => {
+                let value_offset = step.stack.nth_last(3)?;
+                step.global_memory.read_u256(value_offset)?
+            }
+            OpcodeId::CREATE2 => {
+                let value_offset = step.stack.nth_last(4)?;
                 step.global_memory.read_u256(value_offset)?
             }
             _ => Word::zero(),
@@ -1429,6 +1604,7 @@ impl<'a> CircuitInputStateRef<'a> {
                     OpcodeId::RETURNDATACOPY => Some(ExecError::ReturnDataOutOfBounds),
                     // Break write protection (CALL with value will be handled below)
                     OpcodeId::SSTORE
+                    | OpcodeId::TSTORE
                     | OpcodeId::CREATE
                     | OpcodeId::CREATE2
                     | OpcodeId::SELFDESTRUCT
@@ -1456,13 +1632,15 @@ impl<'a> CircuitInputStateRef<'a> {
             } else {
                 // Return from a {CREATE, CREATE2} with a failure, via RETURN
                 if call.is_create() {
-                    let offset = step.stack.nth_last(0)?;
-                    let length = step.stack.nth_last(1)?;
+                    let length = step.stack.nth_last(0)?;
+                    let offset = step.stack.nth_last(1)?;
                     if length > StackWord::from(0x6000u64) {
                         return Ok(Some(ExecError::MaxCodeSizeExceeded));
                     } else if length > StackWord::zero()
-                        && !call_ctx.memory.is_empty()
-                        && call_ctx.memory.0.get(offset.low_u64() as usize) == Some(&0xef)
+                        && !CodeDB::is_wasm_module(&step.global_memory.read_chunk(
+                            offset.low_u64().into(),
+                            length.low_u64().min(8).into(),
+                        ))
                     {
                         return Ok(Some(ExecError::InvalidCreationCode));
                     } else if StackWord::from(200u64) * length > StackWord::from(step.gas.0) {
@@ -9,7 +9,7 @@ use crate::{
         GETH_ERR_STACK_UNDERFLOW,
     },
     operation::RWCounter,
-    state_db::Account,
+    state_db::{Account, WASM_MAGIC, WASM_VERSION},
 };
 use eth_types::{address, bytecode, evm_types::{stack::Stack, Gas, OpcodeId}, geth_types::GethData, word, Bytecode, Hash, ToAddress, ToWord, Word, StackWord, ToStackWord};
 use lazy_static::lazy_static;
@@ -19,7 +19,8 @@ use mock::{
 };
 use pretty_assertions::assert_eq;
 use std::collections::HashSet;
-use eth_types::evm_types::Memory;
+use eth_types::evm_types::{GasCost, Memory, ProgramCounter, Storage};
+use eth_types::wasm_trace::WASM_TRACE_VERSION;
 
 // Helper struct that contains a CircuitInputBuilder, a particuar tx and a
 // particular execution step so that we can easily get a
@@ -51,6 +52,7 @@ impl CircuitInputBuilderTx {
                 struct_logs: vec![geth_step.clone()],
                 globals: vec![],
                 function_calls: vec![],
+                wasm_trace_version: WASM_TRACE_VERSION,
             },
             false,
         )
@@ -2277,3 +2279,45 @@ fn test_gen_access_trace_create_push_call_stack() {
         }
     )
 }
+
+fn wasm_create_step(op: OpcodeId, stack: Vec<u64>, global_memory: Memory) -> GethExecStep {
+    GethExecStep {
+        pc: ProgramCounter(0),
+        op_family: None,
+        params: vec![],
+        op,
+        gas: Gas(0x1_0000),
+        gas_cost: GasCost(0),
+        refund: Gas(0),
+        depth: 1,
+        error: None,
+        stack: Stack(stack.into_iter().map(StackWord::from).collect()),
+        memory: vec![],
+        global_memory,
+        storage: Storage::default(),
+    }
+}
+
+#[test]
+fn wasm_create_args_from_linear_memory() {
+    let init_code = [WASM_MAGIC.as_slice(), WASM_VERSION.as_slice()].concat();
+    let memory = Memory::from_bytes_with_offset(init_code.clone(), 0x40);
+
+    // value offset, init code offset, init code length, result offset
+    let create = wasm_create_step(OpcodeId::CREATE, vec![0, 0x40, 8, 0x80], memory.clone());
+    assert_eq!(get_create_init_code_offset_length(&create).unwrap(), (0x40, 8));
+    assert_eq!(get_create_init_code(&create).unwrap(), init_code);
+
+    // value offset, init code offset, init code length, salt offset, result offset
+    let create2 = wasm_create_step(OpcodeId::CREATE2, vec![0, 0x40, 8, 0, 0x80], memory.clone());
+    assert_eq!(get_create_init_code_offset_length(&create2).unwrap(), (0x40, 8));
+    assert_eq!(get_create_init_code(&create2).unwrap(), init_code);
+
+    // the created address is written at the result offset, zero on failure
+    let mut next_memory = memory.clone();
+    next_memory.extends_with(&Memory::from_bytes_with_offset(ADDR_B.0.to_vec(), 0x80));
+    let next_step = wasm_create_step(OpcodeId::I32Const, vec![], next_memory);
+    assert!(call_result_is_success(&create, &next_step).unwrap());
+    let failed_step = wasm_create_step(OpcodeId::I32Const, vec![], memory);
+    assert!(!call_result_is_success(&create, &failed_step).unwrap());
+}
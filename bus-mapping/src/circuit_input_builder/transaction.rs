@@ -1,6 +1,6 @@
 //! Transaction & TransactionContext utility module.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
 use eth_types::{
     evm_types::{gas_utils::tx_data_gas_cost, Memory},
@@ -16,7 +16,10 @@ use crate::{
     Error,
 };
 
-use super::{call::ReversionGroup, Call, CallContext, CallKind, CodeSource, ExecStep};
+use super::{
+    call::ReversionGroup, call_result_is_success, Call, CallContext, CallKind, CodeSource,
+    ExecStep,
+};
 
 /// Precision of transaction L1 fee
 pub const TX_L1_FEE_PRECISION: u64 = 1_000_000_000;
@@ -64,13 +67,14 @@ impl TransactionContext {
                     // Emerge from call
                     } else if geth_step.depth - 1 == geth_next_step.depth {
                         let last_call_index = call_indices.pop().unwrap();
-                        let result_offset = geth_trace.struct_logs[last_call_index].stack.last()?;
-                        let is_success = geth_next_step.global_memory.read_u8(result_offset)? != 0u8;
+                        let is_success = call_result_is_success(
+                            &geth_trace.struct_logs[last_call_index],
+                            geth_next_step,
+                        )?;
                         call_is_success_map.insert(last_call_index, is_success);
                     // Callee with empty code
                     } else if CallKind::try_from(geth_step.op).is_ok() {
-                        let result_offset = geth_step.stack.last()?;
-                        let is_success = geth_next_step.global_memory.read_u8(result_offset)? != 0u8;
+                        let is_success = call_result_is_success(geth_step, geth_next_step)?;
                         call_is_success_map.insert(index, is_success);
                     }
                 }
@@ -176,6 +180,10 @@ impl TransactionContext {
             call_data,
             memory: Memory::default(),
             return_data: vec![],
+            function_calls: vec![],
+            function_frames: vec![],
+            globals: HashMap::new(),
+            tables: HashMap::new(),
         });
     }
 
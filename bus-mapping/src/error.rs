@@ -1,13 +1,17 @@
 //! Error module for the bus-mapping crate
 
 use core::fmt::{Display, Formatter, Result as FmtResult};
-use eth_types::{evm_types::OpcodeId, Address, GethExecStep, Word, H256};
+use eth_types::{
+    evm_types::OpcodeId, wasm_trace::WasmTraceError, Address, GethExecStep, Word, H256,
+};
 use ethers_providers::ProviderError;
 use std::error::Error as StdError;
 
 use crate::geth_errors::{
     GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
-    GETH_ERR_STACK_UNDERFLOW,
+    GETH_ERR_STACK_UNDERFLOW, GETH_ERR_WASM_DIVISION_BY_ZERO,
+    GETH_ERR_WASM_INDIRECT_CALL_MISMATCH, GETH_ERR_WASM_MEMORY_OUT_OF_BOUNDS,
+    GETH_ERR_WASM_STACK_HEIGHT_EXCEEDED, GETH_ERR_WASM_UNREACHABLE,
 };
 
 /// Error type for any BusMapping related failure.
@@ -32,6 +36,10 @@ pub enum Error {
     /// Invalid [`eth_types::GethExecTrace`] due to an invalid/unexpected value
     /// in it.
     InvalidGethExecTrace(&'static str),
+    /// Wasm specific fields of a [`eth_types::GethExecTrace`] failed validation
+    InvalidWasmTrace(WasmTraceError),
+    /// Module or instruction not supported by the [`crate::interpreter`]
+    WasmInterpreterError(String),
     /// Invalid [`GethExecStep`] due to an invalid/unexpected value in it.
     InvalidGethExecStep(&'static str, Box<GethExecStep>),
     /// Eth type related error.
@@ -138,6 +146,21 @@ pub enum NonceUintOverflowError {
     Create2,
 }
 
+/// WASM traps by cause.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WasmTrapKind {
+    /// `unreachable` executed
+    Unreachable,
+    /// Load or store outside of the linear memory
+    MemoryOutOfBounds,
+    /// Integer division or remainder by zero
+    DivisionByZero,
+    /// `call_indirect` to a function of another signature
+    IndirectCallMismatch,
+    /// Stack height above [`eth_types::evm_types::MAX_WASM_STACK_HEIGHT`]
+    StackHeightExceeded,
+}
+
 /// EVM Execution Error
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ExecError {
@@ -173,6 +196,8 @@ pub enum ExecError {
     PrecompileFailed,
     /// For CREATE, CREATE2
     NonceUintOverflow(NonceUintOverflowError),
+    /// For WASM opcodes which trap
+    WasmTrap(WasmTrapKind),
 }
 
 // TODO: Move to impl block.
@@ -187,7 +212,8 @@ pub(crate) fn get_step_reported_error(op: &OpcodeId, error: &str) -> ExecError {
             OpcodeId::CALLDATACOPY
             | OpcodeId::CODECOPY
             | OpcodeId::EXTCODECOPY
-            | OpcodeId::RETURNDATACOPY => OogError::MemoryCopy,
+            | OpcodeId::RETURNDATACOPY
+            | OpcodeId::MCOPY => OogError::MemoryCopy,
             OpcodeId::BALANCE | OpcodeId::EXTCODESIZE | OpcodeId::EXTCODEHASH => {
                 OogError::AccountAccess
             }
@@ -209,7 +235,47 @@ pub(crate) fn get_step_reported_error(op: &OpcodeId, error: &str) -> ExecError {
         ExecError::StackOverflow
     } else if error.starts_with(GETH_ERR_STACK_UNDERFLOW) {
         ExecError::StackUnderflow
+    } else if error.starts_with(GETH_ERR_WASM_UNREACHABLE) {
+        ExecError::WasmTrap(WasmTrapKind::Unreachable)
+    } else if error.starts_with(GETH_ERR_WASM_MEMORY_OUT_OF_BOUNDS) {
+        ExecError::WasmTrap(WasmTrapKind::MemoryOutOfBounds)
+    } else if error.starts_with(GETH_ERR_WASM_DIVISION_BY_ZERO) {
+        ExecError::WasmTrap(WasmTrapKind::DivisionByZero)
+    } else if error.starts_with(GETH_ERR_WASM_INDIRECT_CALL_MISMATCH) {
+        ExecError::WasmTrap(WasmTrapKind::IndirectCallMismatch)
+    } else if error.starts_with(GETH_ERR_WASM_STACK_HEIGHT_EXCEEDED) {
+        ExecError::WasmTrap(WasmTrapKind::StackHeightExceeded)
     } else {
         panic!("Unknown GethExecStep.error: {}", error);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wasm_traps_are_reported_by_kind() {
+        for (op, error, kind) in [
+            (OpcodeId::Unreachable, GETH_ERR_WASM_UNREACHABLE, WasmTrapKind::Unreachable),
+            (
+                OpcodeId::I32Load,
+                GETH_ERR_WASM_MEMORY_OUT_OF_BOUNDS,
+                WasmTrapKind::MemoryOutOfBounds,
+            ),
+            (OpcodeId::I64DivU, GETH_ERR_WASM_DIVISION_BY_ZERO, WasmTrapKind::DivisionByZero),
+            (
+                OpcodeId::CallIndirect,
+                GETH_ERR_WASM_INDIRECT_CALL_MISMATCH,
+                WasmTrapKind::IndirectCallMismatch,
+            ),
+            (
+                OpcodeId::I32Const,
+                GETH_ERR_WASM_STACK_HEIGHT_EXCEEDED,
+                WasmTrapKind::StackHeightExceeded,
+            ),
+        ] {
+            assert_eq!(get_step_reported_error(&op, error), ExecError::WasmTrap(kind));
+        }
+    }
+}
@@ -17,7 +17,9 @@ impl fmt::Debug for OperationRef {
                 Target::Memory => "Memory",
                 Target::Stack => "Stack",
                 Target::Global=> "Global",
+                Target::Table => "Table",
                 Target::Storage => "Storage",
+                Target::TransientStorage => "TransientStorage",
                 Target::TxAccessListAccount => "TxAccessListAccount",
                 Target::TxAccessListAccountStorage => "TxAccessListAccountStorage",
                 Target::TxRefund => "TxRefund",
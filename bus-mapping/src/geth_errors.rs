@@ -6,3 +6,13 @@ pub const GETH_ERR_STACK_UNDERFLOW: &str = "stack underflow";
 pub const GETH_ERR_OUT_OF_GAS: &str = "out of gas";
 /// Geth error message for gas uint64 overflow
 pub const GETH_ERR_GAS_UINT_OVERFLOW: &str = "gas uint64 overflow";
+/// WASM trap message for executing `unreachable`
+pub const GETH_ERR_WASM_UNREACHABLE: &str = "unreachable";
+/// WASM trap message for a load or store outside of the linear memory
+pub const GETH_ERR_WASM_MEMORY_OUT_OF_BOUNDS: &str = "out of bounds memory access";
+/// WASM trap message for an integer division or remainder by zero
+pub const GETH_ERR_WASM_DIVISION_BY_ZERO: &str = "integer divide by zero";
+/// WASM trap message for a function exceeding the stack height limit
+pub const GETH_ERR_WASM_STACK_HEIGHT_EXCEEDED: &str = "stack height limit exceeded";
+/// WASM trap message for `call_indirect` with a mismatching function signature
+pub const GETH_ERR_WASM_INDIRECT_CALL_MISMATCH: &str = "indirect call type mismatch";
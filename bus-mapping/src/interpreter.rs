@@ -0,0 +1,1259 @@
+//! Witness-time wasm interpreter. Executes an exported function of a module and records the
+//! execution as a [`WasmiTrace`], so tests and local proving don't need an external tracer.
+//!
+//! Only the integer MVP instructions are executed. Calls of imported (host) functions, floats,
+//! tables and bulk memory fail with [`Error::WasmInterpreterError`].
+
+use std::collections::HashMap;
+
+use eth_types::{
+    evm_types::{ProgramCounter, MAX_WASM_MEMORY_PAGES, MAX_WASM_STACK_HEIGHT, WASM_PAGE_SIZE},
+    Block, GethExecTrace, GethExecTraceFunctionCall, GethExecTraceGlobal, Transaction,
+};
+use wasmparser::{
+    BlockType, ConstExpr, DataKind, ExternalKind, FuncType, MemArg, Operator, Parser, Payload,
+    TypeRef,
+};
+
+use crate::{
+    geth_errors::{
+        GETH_ERR_OUT_OF_GAS, GETH_ERR_WASM_DIVISION_BY_ZERO, GETH_ERR_WASM_MEMORY_OUT_OF_BOUNDS,
+        GETH_ERR_WASM_STACK_HEIGHT_EXCEEDED, GETH_ERR_WASM_UNREACHABLE,
+    },
+    trace_provider::{WasmTraceProvider, WasmiStep, WasmiTrace},
+    Error,
+};
+
+/// Fuel available to a call unless [`WasmInterpreter::with_fuel_limit`] is used
+pub const DEFAULT_FUEL_LIMIT: u64 = 10_000_000;
+
+fn interpreter_error(e: impl std::fmt::Display) -> Error {
+    Error::WasmInterpreterError(e.to_string())
+}
+
+/// Defined function of the module
+#[derive(Clone, Debug)]
+struct Function<'a> {
+    type_index: u32,
+    /// declared locals, params excluded
+    num_locals: u32,
+    /// operators with their offset in the code section
+    body: Vec<(usize, Operator<'a>)>,
+    /// `block`/`loop`/`if` position -> (position of its `else`, position of its `end`), `else`
+    /// position -> (None, position of the `end`)
+    block_ends: HashMap<usize, (Option<usize>, usize)>,
+}
+
+impl<'a> Function<'a> {
+    fn new(type_index: u32, num_locals: u32, body: Vec<(usize, Operator<'a>)>) -> Self {
+        let mut block_ends = HashMap::new();
+        let mut opened: Vec<(usize, Option<usize>)> = vec![];
+        for (position, (_, op)) in body.iter().enumerate() {
+            match op {
+                Operator::Block { .. } | Operator::Loop { .. } | Operator::If { .. } => {
+                    opened.push((position, None))
+                }
+                Operator::Else => {
+                    if let Some(block) = opened.last_mut() {
+                        block.1 = Some(position);
+                    }
+                }
+                Operator::End => {
+                    if let Some((start, else_position)) = opened.pop() {
+                        block_ends.insert(start, (else_position, position));
+                        if let Some(else_position) = else_position {
+                            block_ends.insert(else_position, (None, position));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Self {
+            type_index,
+            num_locals,
+            body,
+            block_ends,
+        }
+    }
+}
+
+/// Result of [`WasmInterpreter::trace_call`]
+#[derive(Clone, Debug, Default)]
+pub struct InterpretedCall {
+    /// Values returned by the function, empty if it trapped
+    pub results: Vec<u64>,
+    /// Recorded execution
+    pub trace: WasmiTrace,
+}
+
+/// Interpreter of a validated wasm module
+#[derive(Clone, Debug)]
+pub struct WasmInterpreter<'a> {
+    types: Vec<FuncType>,
+    num_imported_funcs: u32,
+    funcs: Vec<Function<'a>>,
+    exports: HashMap<String, u32>,
+    globals: Vec<GethExecTraceGlobal>,
+    /// (initial pages, max pages) of the memory
+    memory: Option<(u64, Option<u64>)>,
+    data_segments: Vec<(u32, Vec<u8>)>,
+    fuel_limit: u64,
+}
+
+fn const_value(expr: &ConstExpr) -> Result<(&'static str, u64), Error> {
+    let mut reader = expr.get_operators_reader();
+    match reader.read().map_err(interpreter_error)? {
+        Operator::I32Const { value } => Ok(("i32_const", value as u32 as u64)),
+        Operator::I64Const { value } => Ok(("i64_const", value as u64)),
+        op => Err(interpreter_error(format!(
+            "unsupported init expression {:?}",
+            op
+        ))),
+    }
+}
+
+impl<'a> WasmInterpreter<'a> {
+    /// Validates and decodes `bytes`
+    pub fn new(bytes: &'a [u8]) -> Result<Self, Error> {
+        wasmparser::validate(bytes).map_err(interpreter_error)?;
+
+        let mut interpreter = Self {
+            types: vec![],
+            num_imported_funcs: 0,
+            funcs: vec![],
+            exports: HashMap::new(),
+            globals: vec![],
+            memory: None,
+            data_segments: vec![],
+            fuel_limit: DEFAULT_FUEL_LIMIT,
+        };
+        let mut func_types = vec![];
+        let mut code_start = 0;
+        for payload in Parser::new(0).parse_all(bytes) {
+            match payload.map_err(interpreter_error)? {
+                Payload::TypeSection(reader) => {
+                    for ty in reader {
+                        #[allow(unreachable_patterns)]
+                        match ty.map_err(interpreter_error)? {
+                            wasmparser::Type::Func(func_type) => interpreter.types.push(func_type),
+                            ty => {
+                                return Err(interpreter_error(format!(
+                                    "unsupported type {:?}",
+                                    ty
+                                )))
+                            }
+                        }
+                    }
+                }
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        match import.map_err(interpreter_error)?.ty {
+                            TypeRef::Func(_) => interpreter.num_imported_funcs += 1,
+                            ty => {
+                                return Err(interpreter_error(format!(
+                                    "unsupported import {:?}",
+                                    ty
+                                )))
+                            }
+                        }
+                    }
+                }
+                Payload::FunctionSection(reader) => {
+                    for type_index in reader {
+                        func_types.push(type_index.map_err(interpreter_error)?);
+                    }
+                }
+                Payload::MemorySection(reader) => {
+                    for memory in reader {
+                        let memory = memory.map_err(interpreter_error)?;
+                        interpreter.memory = Some((memory.initial, memory.maximum));
+                    }
+                }
+                Payload::GlobalSection(reader) => {
+                    for (index, global) in reader.into_iter_with_offsets().enumerate() {
+                        let (offset, global) = global.map_err(interpreter_error)?;
+                        let (op, value) = const_value(&global.init_expr)?;
+                        interpreter.globals.push(GethExecTraceGlobal {
+                            pc: ProgramCounter(offset),
+                            index: index as u32,
+                            op: op.to_string(),
+                            value,
+                        });
+                    }
+                }
+                Payload::ExportSection(reader) => {
+                    for export in reader {
+                        let export = export.map_err(interpreter_error)?;
+                        if export.kind == ExternalKind::Func {
+                            interpreter
+                                .exports
+                                .insert(export.name.to_string(), export.index);
+                        }
+                    }
+                }
+                Payload::DataSection(reader) => {
+                    for data in reader {
+                        let data = data.map_err(interpreter_error)?;
+                        if let DataKind::Active { offset_expr, .. } = data.kind {
+                            let (_, offset) = const_value(&offset_expr)?;
+                            interpreter
+                                .data_segments
+                                .push((offset as u32, data.data.to_vec()));
+                        }
+                    }
+                }
+                Payload::CodeSectionStart { range, .. } => code_start = range.start,
+                Payload::CodeSectionEntry(body) => {
+                    let type_index = *func_types
+                        .get(interpreter.funcs.len())
+                        .ok_or_else(|| interpreter_error("function body without declaration"))?;
+                    let mut num_locals = 0;
+                    for locals in body.get_locals_reader().map_err(interpreter_error)? {
+                        num_locals += locals.map_err(interpreter_error)?.0;
+                    }
+                    let mut operators = vec![];
+                    let mut reader = body.get_operators_reader().map_err(interpreter_error)?;
+                    while !reader.eof() {
+                        let (op, offset) = reader.read_with_offset().map_err(interpreter_error)?;
+                        operators.push((offset - code_start, op));
+                    }
+                    interpreter
+                        .funcs
+                        .push(Function::new(type_index, num_locals, operators));
+                }
+                Payload::TableSection(_) | Payload::ElementSection(_) => {
+                    return Err(interpreter_error("tables are not supported"))
+                }
+                _ => {}
+            }
+        }
+        Ok(interpreter)
+    }
+
+    /// Sets the fuel available to a call, every instruction but `end` costs 1
+    pub fn with_fuel_limit(mut self, fuel_limit: u64) -> Self {
+        self.fuel_limit = fuel_limit;
+        self
+    }
+
+    fn func_type(&self, fn_index: u32) -> Result<&FuncType, Error> {
+        let func = fn_index
+            .checked_sub(self.num_imported_funcs)
+            .and_then(|index| self.funcs.get(index as usize))
+            .ok_or_else(|| interpreter_error(format!("no defined function {}", fn_index)))?;
+        Ok(&self.types[func.type_index as usize])
+    }
+
+    /// Calls the exported function `export_name` with `args` (i32 args zero extended) and
+    /// records the execution. A trap ends the trace, it is not an error.
+    pub fn trace_call(&self, export_name: &str, args: &[u64]) -> Result<InterpretedCall, Error> {
+        let fn_index = *self
+            .exports
+            .get(export_name)
+            .ok_or_else(|| interpreter_error(format!("no exported function {}", export_name)))?;
+        if self.func_type(fn_index)?.params().len() != args.len() {
+            return Err(interpreter_error(format!(
+                "{} args given to {}",
+                args.len(),
+                export_name
+            )));
+        }
+
+        let mut memory = vec![];
+        if let Some((initial, _)) = self.memory {
+            memory.resize((initial.min(MAX_WASM_MEMORY_PAGES) * WASM_PAGE_SIZE) as usize, 0);
+        }
+        for (offset, bytes) in self.data_segments.iter() {
+            let start = *offset as usize;
+            memory
+                .get_mut(start..start + bytes.len())
+                .ok_or_else(|| interpreter_error("data segment outside of the memory"))?
+                .copy_from_slice(bytes);
+        }
+
+        let mut execution = Execution {
+            interpreter: self,
+            stack: args.to_vec(),
+            frames: vec![],
+            memory,
+            globals: self.globals.iter().map(|g| g.value).collect(),
+            fuel: self.fuel_limit,
+            steps: vec![],
+            function_calls: vec![],
+        };
+        let trap = execution.run(fn_index)?;
+
+        Ok(InterpretedCall {
+            results: if trap.is_none() {
+                execution.stack.clone()
+            } else {
+                vec![]
+            },
+            trace: WasmiTrace {
+                fuel_consumed: self.fuel_limit - execution.fuel,
+                trap: trap.map(|trap| trap.to_string()),
+                return_data: vec![],
+                data_segments: self.data_segments.clone(),
+                globals: self.globals.clone(),
+                function_calls: execution.function_calls,
+                steps: execution.steps,
+            },
+        })
+    }
+}
+
+/// Reason an instruction didn't complete
+enum Fault {
+    /// Trap with its geth error message, recorded in the trace
+    Trap(&'static str),
+    /// Instruction the interpreter can't execute
+    Unsupported(String),
+}
+
+fn unsupported(e: impl std::fmt::Display) -> Fault {
+    Fault::Unsupported(e.to_string())
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Label {
+    /// values carried by a branch to the label
+    arity: usize,
+    /// stack height below the block params
+    height: usize,
+    /// position a branch to the label continues at
+    target: usize,
+    is_loop: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Frame {
+    fn_index: u32,
+    locals: Vec<u64>,
+    /// labels of the open blocks, the function body first
+    labels: Vec<Label>,
+    pc: usize,
+    /// stack height below the operands of the function
+    stack_base: usize,
+}
+
+struct Execution<'i, 'a> {
+    interpreter: &'i WasmInterpreter<'a>,
+    stack: Vec<u64>,
+    frames: Vec<Frame>,
+    memory: Vec<u8>,
+    globals: Vec<u64>,
+    fuel: u64,
+    steps: Vec<WasmiStep>,
+    function_calls: Vec<GethExecTraceFunctionCall>,
+}
+
+/// Wasm text format name of `op`, derived from its variant name (`I64ExtendI32U` is
+/// `i64.extend_i32_u`)
+fn instr_name(op: &Operator) -> String {
+    let debug = format!("{:?}", op);
+    let variant = debug
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .next()
+        .unwrap_or_default();
+    let mut words: Vec<String> = vec![];
+    for c in variant.chars() {
+        if c.is_ascii_uppercase() || words.is_empty() {
+            words.push(String::new());
+        }
+        if let Some(word) = words.last_mut() {
+            word.push(c.to_ascii_lowercase());
+        }
+    }
+    match words.first().map(|word| word.as_str()) {
+        Some("i32" | "i64" | "f32" | "f64" | "local" | "global" | "memory") if words.len() > 1 => {
+            format!("{}.{}", words[0], words[1..].join("_"))
+        }
+        _ => words.join("_"),
+    }
+}
+
+fn memarg_of(op: &Operator) -> Option<MemArg> {
+    match op {
+        Operator::I32Load { memarg }
+        | Operator::I64Load { memarg }
+        | Operator::I32Load8S { memarg }
+        | Operator::I32Load8U { memarg }
+        | Operator::I32Load16S { memarg }
+        | Operator::I32Load16U { memarg }
+        | Operator::I64Load8S { memarg }
+        | Operator::I64Load8U { memarg }
+        | Operator::I64Load16S { memarg }
+        | Operator::I64Load16U { memarg }
+        | Operator::I64Load32S { memarg }
+        | Operator::I64Load32U { memarg }
+        | Operator::I32Store { memarg }
+        | Operator::I64Store { memarg }
+        | Operator::I32Store8 { memarg }
+        | Operator::I32Store16 { memarg }
+        | Operator::I64Store8 { memarg }
+        | Operator::I64Store16 { memarg }
+        | Operator::I64Store32 { memarg } => Some(*memarg),
+        _ => None,
+    }
+}
+
+/// Immediates of `op` as recorded in [`WasmiStep::params`]
+fn instr_params(op: &Operator) -> Vec<u64> {
+    if let Some(memarg) = memarg_of(op) {
+        return vec![memarg.align as u64, memarg.offset];
+    }
+    match op {
+        Operator::LocalGet { local_index }
+        | Operator::LocalSet { local_index }
+        | Operator::LocalTee { local_index } => vec![*local_index as u64],
+        Operator::GlobalGet { global_index } | Operator::GlobalSet { global_index } => {
+            vec![*global_index as u64]
+        }
+        Operator::Br { relative_depth } | Operator::BrIf { relative_depth } => {
+            vec![*relative_depth as u64]
+        }
+        Operator::BrTable { targets } => targets
+            .targets()
+            .filter_map(Result::ok)
+            .chain(std::iter::once(targets.default()))
+            .map(|depth| depth as u64)
+            .collect(),
+        Operator::Call { function_index } => vec![*function_index as u64],
+        Operator::I32Const { value } => vec![*value as u32 as u64],
+        Operator::I64Const { value } => vec![*value as u64],
+        _ => vec![],
+    }
+}
+
+impl<'i, 'a> Execution<'i, 'a> {
+    /// Runs the function `fn_index` with its args on the stack until it returns or traps
+    fn run(&mut self, fn_index: u32) -> Result<Option<&'static str>, Error> {
+        if let Err(fault) = self.call(fn_index) {
+            return match fault {
+                Fault::Trap(trap) => {
+                    // the locals of the function don't fit, its first instruction traps
+                    let (offset, op) = &self.function(fn_index).body[0];
+                    self.steps.push(WasmiStep {
+                        pc: *offset,
+                        instr: instr_name(op),
+                        params: instr_params(op),
+                        fuel: self.fuel,
+                        fuel_cost: 0,
+                        depth: 1,
+                        stack: self.stack.clone(),
+                        memory_changes: vec![],
+                        trap: Some(trap.to_string()),
+                    });
+                    Ok(Some(trap))
+                }
+                Fault::Unsupported(e) => Err(Error::WasmInterpreterError(e)),
+            };
+        }
+        while let Some(frame) = self.frames.last() {
+            let func = self.function(frame.fn_index);
+            let (offset, op) = func.body[frame.pc].clone();
+            let cost = if matches!(op, Operator::End) { 0 } else { 1 };
+            self.steps.push(WasmiStep {
+                pc: offset,
+                instr: instr_name(&op),
+                params: instr_params(&op),
+                fuel: self.fuel,
+                fuel_cost: cost,
+                depth: self.frames.len() as u16,
+                stack: self.stack.clone(),
+                memory_changes: vec![],
+                trap: None,
+            });
+            let result = if self.fuel < cost {
+                Err(Fault::Trap(GETH_ERR_OUT_OF_GAS))
+            } else {
+                self.fuel -= cost;
+                self.frames.last_mut().expect("frame of the step").pc += 1;
+                self.execute(&op)
+            };
+            let result = result.and_then(|_| {
+                if self.stack.len() as u64 > MAX_WASM_STACK_HEIGHT {
+                    return Err(Fault::Trap(GETH_ERR_WASM_STACK_HEIGHT_EXCEEDED));
+                }
+                Ok(())
+            });
+            match result {
+                Ok(()) => self.update_max_stack_height(),
+                Err(Fault::Trap(trap)) => {
+                    if let Some(step) = self.steps.last_mut() {
+                        step.trap = Some(trap.to_string());
+                    }
+                    return Ok(Some(trap));
+                }
+                Err(Fault::Unsupported(e)) => return Err(Error::WasmInterpreterError(e)),
+            }
+        }
+        Ok(None)
+    }
+
+    fn function(&self, fn_index: u32) -> &'i Function<'a> {
+        &self.interpreter.funcs[(fn_index - self.interpreter.num_imported_funcs) as usize]
+    }
+
+    fn update_max_stack_height(&mut self) {
+        if let Some(frame) = self.frames.last() {
+            let height = self.stack.len().saturating_sub(frame.stack_base) as u32;
+            if let Some(function_call) = self
+                .function_calls
+                .iter_mut()
+                .find(|c| c.fn_index == frame.fn_index)
+            {
+                function_call.max_stack_height = function_call.max_stack_height.max(height);
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Result<u64, Fault> {
+        self.stack
+            .pop()
+            .ok_or_else(|| unsupported("operand stack underflow"))
+    }
+
+    fn pop_n(&mut self, n: usize) -> Result<Vec<u64>, Fault> {
+        let height = self
+            .stack
+            .len()
+            .checked_sub(n)
+            .ok_or_else(|| unsupported("operand stack underflow"))?;
+        Ok(self.stack.split_off(height))
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().expect("frame of the step")
+    }
+
+    fn block_arity(&self, block_type: BlockType) -> (usize, usize) {
+        match block_type {
+            BlockType::Empty => (0, 0),
+            BlockType::Type(_) => (0, 1),
+            BlockType::FuncType(index) => {
+                let func_type = &self.interpreter.types[index as usize];
+                (func_type.params().len(), func_type.results().len())
+            }
+        }
+    }
+
+    fn block_end(&self) -> (Option<usize>, usize) {
+        let frame = self.frames.last().expect("frame of the step");
+        self.function(frame.fn_index).block_ends[&(frame.pc - 1)]
+    }
+
+    fn call(&mut self, fn_index: u32) -> Result<(), Fault> {
+        if fn_index < self.interpreter.num_imported_funcs {
+            return Err(unsupported(format!("call of imported function {}", fn_index)));
+        }
+        let interpreter = self.interpreter;
+        let func = self.function(fn_index);
+        let func_type = &interpreter.types[func.type_index as usize];
+        // the locals of a trapping call are traced too, they are checked against the limit
+        if !self.function_calls.iter().any(|c| c.fn_index == fn_index) {
+            self.function_calls.push(GethExecTraceFunctionCall {
+                fn_index,
+                max_stack_height: 0,
+                num_locals: func.num_locals,
+            });
+        }
+        if (self.stack.len() + func.num_locals as usize) as u64 > MAX_WASM_STACK_HEIGHT {
+            return Err(Fault::Trap(GETH_ERR_WASM_STACK_HEIGHT_EXCEEDED));
+        }
+        let mut locals = self.pop_n(func_type.params().len())?;
+        locals.resize(locals.len() + func.num_locals as usize, 0);
+        self.frames.push(Frame {
+            fn_index,
+            locals,
+            labels: vec![Label {
+                arity: func_type.results().len(),
+                height: self.stack.len(),
+                target: func.body.len(),
+                is_loop: false,
+            }],
+            pc: 0,
+            stack_base: self.stack.len(),
+        });
+        Ok(())
+    }
+
+    fn return_from_function(&mut self) -> Result<(), Fault> {
+        let frame = self.frames.pop().expect("frame of the step");
+        let func = self.function(frame.fn_index);
+        let num_results = self.interpreter.types[func.type_index as usize].results().len();
+        let results = self.pop_n(num_results)?;
+        self.stack.truncate(frame.stack_base);
+        self.stack.extend(results);
+        Ok(())
+    }
+
+    fn branch(&mut self, relative_depth: u32) -> Result<(), Fault> {
+        let position = self
+            .frame()
+            .labels
+            .len()
+            .checked_sub(relative_depth as usize + 1)
+            .ok_or_else(|| unsupported("branch depth above the open blocks"))?;
+        if position == 0 {
+            return self.return_from_function();
+        }
+        let label = self.frame().labels[position];
+        let values = self.pop_n(label.arity)?;
+        self.stack.truncate(label.height);
+        self.stack.extend(values);
+        let frame = self.frame();
+        frame
+            .labels
+            .truncate(if label.is_loop { position + 1 } else { position });
+        frame.pc = label.target;
+        Ok(())
+    }
+
+    fn open_block(&mut self, block_type: BlockType, target: usize, is_loop: bool) {
+        let (num_params, num_results) = self.block_arity(block_type);
+        let height = self.stack.len() - num_params.min(self.stack.len());
+        self.frame().labels.push(Label {
+            arity: if is_loop { num_params } else { num_results },
+            height,
+            target,
+            is_loop,
+        });
+    }
+
+    fn effective_address(&mut self, memarg: MemArg, size: usize) -> Result<usize, Fault> {
+        let address = self.pop()? as u32 as u64 + memarg.offset;
+        if address + size as u64 > self.memory.len() as u64 {
+            return Err(Fault::Trap(GETH_ERR_WASM_MEMORY_OUT_OF_BOUNDS));
+        }
+        Ok(address as usize)
+    }
+
+    fn load(
+        &mut self,
+        memarg: MemArg,
+        size: usize,
+        signed: bool,
+        is_i64: bool,
+    ) -> Result<(), Fault> {
+        let address = self.effective_address(memarg, size)?;
+        let mut bytes = [0u8; 8];
+        bytes[..size].copy_from_slice(&self.memory[address..address + size]);
+        let mut value = u64::from_le_bytes(bytes);
+        if signed {
+            let shift = 64 - 8 * size as u32;
+            value = (((value << shift) as i64) >> shift) as u64;
+        }
+        self.stack
+            .push(if is_i64 { value } else { value as u32 as u64 });
+        Ok(())
+    }
+
+    fn store(&mut self, memarg: MemArg, size: usize) -> Result<(), Fault> {
+        let value = self.pop()?;
+        let address = self.effective_address(memarg, size)?;
+        let bytes = value.to_le_bytes()[..size].to_vec();
+        self.memory[address..address + size].copy_from_slice(&bytes);
+        if let Some(step) = self.steps.last_mut() {
+            step.memory_changes.push((address as u32, bytes));
+        }
+        Ok(())
+    }
+
+    fn unary(&mut self, f: impl FnOnce(u64) -> u64) -> Result<(), Fault> {
+        let a = self.pop()?;
+        self.stack.push(f(a));
+        Ok(())
+    }
+
+    fn binary(&mut self, f: impl FnOnce(u64, u64) -> Result<u64, Fault>) -> Result<(), Fault> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        self.stack.push(f(a, b)?);
+        Ok(())
+    }
+
+    fn binary32(&mut self, f: impl FnOnce(u32, u32) -> Result<u32, Fault>) -> Result<(), Fault> {
+        self.binary(|a, b| f(a as u32, b as u32).map(|v| v as u64))
+    }
+
+    fn execute(&mut self, op: &Operator) -> Result<(), Fault> {
+        let div_by_zero = || Fault::Trap(GETH_ERR_WASM_DIVISION_BY_ZERO);
+        let overflow = || unsupported("integer overflow trap");
+        match *op {
+            Operator::Unreachable => return Err(Fault::Trap(GETH_ERR_WASM_UNREACHABLE)),
+            Operator::Nop => {}
+            Operator::Block { blockty } => {
+                let (_, end) = self.block_end();
+                self.open_block(blockty, end + 1, false);
+            }
+            Operator::Loop { blockty } => {
+                let start = self.frame().pc;
+                self.open_block(blockty, start, true);
+            }
+            Operator::If { blockty } => {
+                let condition = self.pop()?;
+                let (else_position, end) = self.block_end();
+                self.open_block(blockty, end + 1, false);
+                if condition as u32 == 0 {
+                    self.frame().pc = else_position.map(|p| p + 1).unwrap_or(end);
+                }
+            }
+            Operator::Else => {
+                let (_, end) = self.block_end();
+                self.frame().pc = end;
+            }
+            Operator::End => {
+                let frame = self.frame();
+                frame.labels.pop();
+                if frame.labels.is_empty() {
+                    self.return_from_function()?;
+                }
+            }
+            Operator::Br { relative_depth } => self.branch(relative_depth)?,
+            Operator::BrIf { relative_depth } => {
+                if self.pop()? as u32 != 0 {
+                    self.branch(relative_depth)?;
+                }
+            }
+            Operator::BrTable { ref targets } => {
+                let index = self.pop()? as u32;
+                let relative_depth = match targets.targets().nth(index as usize) {
+                    Some(target) => target.map_err(unsupported)?,
+                    None => targets.default(),
+                };
+                self.branch(relative_depth)?;
+            }
+            Operator::Return => self.return_from_function()?,
+            Operator::Call { function_index } => self.call(function_index)?,
+            Operator::Drop => {
+                self.pop()?;
+            }
+            Operator::Select => {
+                let condition = self.pop()?;
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.stack.push(if condition as u32 != 0 { a } else { b });
+            }
+            Operator::LocalGet { local_index } => {
+                let value = self.frame().locals[local_index as usize];
+                self.stack.push(value);
+            }
+            Operator::LocalSet { local_index } => {
+                let value = self.pop()?;
+                self.frame().locals[local_index as usize] = value;
+            }
+            Operator::LocalTee { local_index } => {
+                let value = *self.stack.last().ok_or_else(|| unsupported("empty stack"))?;
+                self.frame().locals[local_index as usize] = value;
+            }
+            Operator::GlobalGet { global_index } => {
+                self.stack.push(self.globals[global_index as usize]);
+            }
+            Operator::GlobalSet { global_index } => {
+                self.globals[global_index as usize] = self.pop()?;
+            }
+
+            Operator::I32Load { memarg } => self.load(memarg, 4, false, false)?,
+            Operator::I64Load { memarg } => self.load(memarg, 8, false, true)?,
+            Operator::I32Load8S { memarg } => self.load(memarg, 1, true, false)?,
+            Operator::I32Load8U { memarg } => self.load(memarg, 1, false, false)?,
+            Operator::I32Load16S { memarg } => self.load(memarg, 2, true, false)?,
+            Operator::I32Load16U { memarg } => self.load(memarg, 2, false, false)?,
+            Operator::I64Load8S { memarg } => self.load(memarg, 1, true, true)?,
+            Operator::I64Load8U { memarg } => self.load(memarg, 1, false, true)?,
+            Operator::I64Load16S { memarg } => self.load(memarg, 2, true, true)?,
+            Operator::I64Load16U { memarg } => self.load(memarg, 2, false, true)?,
+            Operator::I64Load32S { memarg } => self.load(memarg, 4, true, true)?,
+            Operator::I64Load32U { memarg } => self.load(memarg, 4, false, true)?,
+            Operator::I32Store { memarg } => self.store(memarg, 4)?,
+            Operator::I64Store { memarg } => self.store(memarg, 8)?,
+            Operator::I32Store8 { memarg } | Operator::I64Store8 { memarg } => {
+                self.store(memarg, 1)?
+            }
+            Operator::I32Store16 { memarg } | Operator::I64Store16 { memarg } => {
+                self.store(memarg, 2)?
+            }
+            Operator::I64Store32 { memarg } => self.store(memarg, 4)?,
+            Operator::MemorySize { .. } => {
+                self.stack
+                    .push(self.memory.len() as u64 / WASM_PAGE_SIZE);
+            }
+            Operator::MemoryGrow { .. } => {
+                let delta = self.pop()? as u32 as u64;
+                let pages = self.memory.len() as u64 / WASM_PAGE_SIZE;
+                let max_pages = self
+                    .interpreter
+                    .memory
+                    .and_then(|(_, maximum)| maximum)
+                    .unwrap_or(MAX_WASM_MEMORY_PAGES)
+                    .min(MAX_WASM_MEMORY_PAGES);
+                if self.interpreter.memory.is_none() || pages + delta > max_pages {
+                    self.stack.push(u32::MAX as u64);
+                } else {
+                    self.memory
+                        .resize(((pages + delta) * WASM_PAGE_SIZE) as usize, 0);
+                    self.stack.push(pages);
+                }
+            }
+            Operator::I32Const { value } => self.stack.push(value as u32 as u64),
+            Operator::I64Const { value } => self.stack.push(value as u64),
+
+            Operator::I32Eqz => self.unary(|a| (a as u32 == 0) as u64)?,
+            Operator::I32Eq => self.binary32(|a, b| Ok((a == b) as u32))?,
+            Operator::I32Ne => self.binary32(|a, b| Ok((a != b) as u32))?,
+            Operator::I32LtS => self.binary32(|a, b| Ok(((a as i32) < (b as i32)) as u32))?,
+            Operator::I32LtU => self.binary32(|a, b| Ok((a < b) as u32))?,
+            Operator::I32GtS => self.binary32(|a, b| Ok(((a as i32) > (b as i32)) as u32))?,
+            Operator::I32GtU => self.binary32(|a, b| Ok((a > b) as u32))?,
+            Operator::I32LeS => self.binary32(|a, b| Ok(((a as i32) <= (b as i32)) as u32))?,
+            Operator::I32LeU => self.binary32(|a, b| Ok((a <= b) as u32))?,
+            Operator::I32GeS => self.binary32(|a, b| Ok(((a as i32) >= (b as i32)) as u32))?,
+            Operator::I32GeU => self.binary32(|a, b| Ok((a >= b) as u32))?,
+            Operator::I64Eqz => self.unary(|a| (a == 0) as u64)?,
+            Operator::I64Eq => self.binary(|a, b| Ok((a == b) as u64))?,
+            Operator::I64Ne => self.binary(|a, b| Ok((a != b) as u64))?,
+            Operator::I64LtS => self.binary(|a, b| Ok(((a as i64) < (b as i64)) as u64))?,
+            Operator::I64LtU => self.binary(|a, b| Ok((a < b) as u64))?,
+            Operator::I64GtS => self.binary(|a, b| Ok(((a as i64) > (b as i64)) as u64))?,
+            Operator::I64GtU => self.binary(|a, b| Ok((a > b) as u64))?,
+            Operator::I64LeS => self.binary(|a, b| Ok(((a as i64) <= (b as i64)) as u64))?,
+            Operator::I64LeU => self.binary(|a, b| Ok((a <= b) as u64))?,
+            Operator::I64GeS => self.binary(|a, b| Ok(((a as i64) >= (b as i64)) as u64))?,
+            Operator::I64GeU => self.binary(|a, b| Ok((a >= b) as u64))?,
+
+            Operator::I32Clz => self.unary(|a| (a as u32).leading_zeros() as u64)?,
+            Operator::I32Ctz => self.unary(|a| (a as u32).trailing_zeros() as u64)?,
+            Operator::I32Popcnt => self.unary(|a| (a as u32).count_ones() as u64)?,
+            Operator::I32Add => self.binary32(|a, b| Ok(a.wrapping_add(b)))?,
+            Operator::I32Sub => self.binary32(|a, b| Ok(a.wrapping_sub(b)))?,
+            Operator::I32Mul => self.binary32(|a, b| Ok(a.wrapping_mul(b)))?,
+            Operator::I32DivS => self.binary32(|a, b| match (a as i32, b as i32) {
+                (_, 0) => Err(div_by_zero()),
+                (i32::MIN, -1) => Err(overflow()),
+                (a, b) => Ok((a / b) as u32),
+            })?,
+            Operator::I32DivU => self.binary32(|a, b| a.checked_div(b).ok_or_else(div_by_zero))?,
+            Operator::I32RemS => self.binary32(|a, b| match (a as i32, b as i32) {
+                (_, 0) => Err(div_by_zero()),
+                (a, b) => Ok(a.wrapping_rem(b) as u32),
+            })?,
+            Operator::I32RemU => self.binary32(|a, b| a.checked_rem(b).ok_or_else(div_by_zero))?,
+            Operator::I32And => self.binary32(|a, b| Ok(a & b))?,
+            Operator::I32Or => self.binary32(|a, b| Ok(a | b))?,
+            Operator::I32Xor => self.binary32(|a, b| Ok(a ^ b))?,
+            Operator::I32Shl => self.binary32(|a, b| Ok(a.wrapping_shl(b)))?,
+            Operator::I32ShrS => self.binary32(|a, b| Ok((a as i32).wrapping_shr(b) as u32))?,
+            Operator::I32ShrU => self.binary32(|a, b| Ok(a.wrapping_shr(b)))?,
+            Operator::I32Rotl => self.binary32(|a, b| Ok(a.rotate_left(b % 32)))?,
+            Operator::I32Rotr => self.binary32(|a, b| Ok(a.rotate_right(b % 32)))?,
+            Operator::I64Clz => self.unary(|a| a.leading_zeros() as u64)?,
+            Operator::I64Ctz => self.unary(|a| a.trailing_zeros() as u64)?,
+            Operator::I64Popcnt => self.unary(|a| a.count_ones() as u64)?,
+            Operator::I64Add => self.binary(|a, b| Ok(a.wrapping_add(b)))?,
+            Operator::I64Sub => self.binary(|a, b| Ok(a.wrapping_sub(b)))?,
+            Operator::I64Mul => self.binary(|a, b| Ok(a.wrapping_mul(b)))?,
+            Operator::I64DivS => self.binary(|a, b| match (a as i64, b as i64) {
+                (_, 0) => Err(div_by_zero()),
+                (i64::MIN, -1) => Err(overflow()),
+                (a, b) => Ok((a / b) as u64),
+            })?,
+            Operator::I64DivU => self.binary(|a, b| a.checked_div(b).ok_or_else(div_by_zero))?,
+            Operator::I64RemS => self.binary(|a, b| match (a as i64, b as i64) {
+                (_, 0) => Err(div_by_zero()),
+                (a, b) => Ok(a.wrapping_rem(b) as u64),
+            })?,
+            Operator::I64RemU => self.binary(|a, b| a.checked_rem(b).ok_or_else(div_by_zero))?,
+            Operator::I64And => self.binary(|a, b| Ok(a & b))?,
+            Operator::I64Or => self.binary(|a, b| Ok(a | b))?,
+            Operator::I64Xor => self.binary(|a, b| Ok(a ^ b))?,
+            Operator::I64Shl => self.binary(|a, b| Ok(a.wrapping_shl(b as u32)))?,
+            Operator::I64ShrS => self.binary(|a, b| Ok((a as i64).wrapping_shr(b as u32) as u64))?,
+            Operator::I64ShrU => self.binary(|a, b| Ok(a.wrapping_shr(b as u32)))?,
+            Operator::I64Rotl => self.binary(|a, b| Ok(a.rotate_left((b % 64) as u32)))?,
+            Operator::I64Rotr => self.binary(|a, b| Ok(a.rotate_right((b % 64) as u32)))?,
+
+            Operator::I32WrapI64 | Operator::I64ExtendI32U => self.unary(|a| a as u32 as u64)?,
+            Operator::I64ExtendI32S => self.unary(|a| a as u32 as i32 as i64 as u64)?,
+
+            ref op => return Err(unsupported(format!("unsupported instruction {:?}", op))),
+        }
+        Ok(())
+    }
+}
+
+/// Exported function call of a module run by the [`WasmInterpreter`]
+#[derive(Clone, Debug, Default)]
+pub struct InterpreterCall {
+    /// Module bytecode
+    pub code: Vec<u8>,
+    /// Name of the exported function
+    pub function: String,
+    /// Call args
+    pub args: Vec<u64>,
+}
+
+/// Calls run by the [`WasmInterpreter`], one per transaction
+#[derive(Clone, Debug, Default)]
+pub struct InterpreterTraces(pub Vec<InterpreterCall>);
+
+impl WasmTraceProvider for InterpreterTraces {
+    fn block_traces(&self, eth_block: &Block<Transaction>) -> Result<Vec<GethExecTrace>, Error> {
+        if eth_block.transactions.len() != self.0.len() {
+            return Err(Error::InvalidGethExecTrace(
+                "trace count differs from block transaction count",
+            ));
+        }
+        self.0
+            .iter()
+            .map(|call| {
+                WasmInterpreter::new(&call.code)?
+                    .trace_call(&call.function, &call.args)?
+                    .trace
+                    .to_geth_trace()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_encoder::{
+        BlockType, CodeSection, ConstExpr, DataSection, EntityType, ExportKind, ExportSection,
+        Function, FunctionSection, GlobalSection, GlobalType, ImportSection, Instruction, MemArg,
+        MemorySection, MemoryType, Module, TypeSection, ValType,
+    };
+
+    struct TestFunc {
+        params: Vec<ValType>,
+        results: Vec<ValType>,
+        locals: Vec<(u32, ValType)>,
+        body: Vec<Instruction<'static>>,
+    }
+
+    fn i32_func(params: usize, locals: u32, body: Vec<Instruction<'static>>) -> TestFunc {
+        TestFunc {
+            params: vec![ValType::I32; params],
+            results: vec![ValType::I32],
+            locals: if locals > 0 {
+                vec![(locals, ValType::I32)]
+            } else {
+                vec![]
+            },
+            body,
+        }
+    }
+
+    /// Module with one memory page, a mutable i32 global set to 5, "He" at memory offset 16
+    /// and the first function exported as "main"
+    fn module(funcs: &[TestFunc]) -> Vec<u8> {
+        let mut types = TypeSection::new();
+        let mut functions = FunctionSection::new();
+        let mut codes = CodeSection::new();
+        for (index, func) in funcs.iter().enumerate() {
+            types.function(func.params.clone(), func.results.clone());
+            functions.function(index as u32);
+            let mut f = Function::new(func.locals.clone());
+            func.body.iter().for_each(|instruction| {
+                f.instruction(instruction);
+            });
+            codes.function(&f);
+        }
+        let mut memories = MemorySection::new();
+        memories.memory(MemoryType {
+            minimum: 1,
+            maximum: None,
+            memory64: false,
+            shared: false,
+        });
+        let mut globals = GlobalSection::new();
+        globals.global(
+            GlobalType {
+                val_type: ValType::I32,
+                mutable: true,
+            },
+            &ConstExpr::i32_const(5),
+        );
+        let mut exports = ExportSection::new();
+        exports.export("main", ExportKind::Func, 0);
+        let mut data = DataSection::new();
+        data.active(0, &ConstExpr::i32_const(16), vec![72, 101]);
+
+        let mut module = Module::new();
+        module.section(&types);
+        module.section(&functions);
+        module.section(&memories);
+        module.section(&globals);
+        module.section(&exports);
+        module.section(&codes);
+        module.section(&data);
+        module.finish()
+    }
+
+    fn instrs(trace: &WasmiTrace) -> Vec<&str> {
+        trace.steps.iter().map(|step| step.instr.as_str()).collect()
+    }
+
+    fn call_store_module() -> Vec<u8> {
+        module(&[
+            i32_func(
+                1,
+                1,
+                vec![
+                    Instruction::I32Const(0),
+                    Instruction::LocalGet(0),
+                    Instruction::I64ExtendI32U,
+                    Instruction::I64Store(MemArg {
+                        offset: 0,
+                        align: 3,
+                        memory_index: 0,
+                    }),
+                    Instruction::LocalGet(0),
+                    Instruction::Call(1),
+                    Instruction::GlobalGet(0),
+                    Instruction::I32Add,
+                    Instruction::End,
+                ],
+            ),
+            i32_func(
+                1,
+                0,
+                vec![
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(2),
+                    Instruction::I32Mul,
+                    Instruction::End,
+                ],
+            ),
+        ])
+    }
+
+    #[test]
+    fn call_and_store_are_traced() {
+        let code = call_store_module();
+        let call = WasmInterpreter::new(&code)
+            .unwrap()
+            .trace_call("main", &[7])
+            .unwrap();
+        assert_eq!(call.results, vec![19]);
+        let trace = call.trace;
+        assert_eq!(
+            instrs(&trace),
+            vec![
+                "i32.const",
+                "local.get",
+                "i64.extend_i32_u",
+                "i64.store",
+                "local.get",
+                "call",
+                "local.get",
+                "i32.const",
+                "i32.mul",
+                "end",
+                "global.get",
+                "i32.add",
+                "end"
+            ]
+        );
+        assert_eq!(trace.trap, None);
+        assert_eq!(trace.fuel_consumed, 11);
+        assert_eq!(trace.steps[3].params, vec![3, 0]);
+        assert_eq!(trace.steps[3].stack, vec![0, 7]);
+        assert_eq!(trace.steps[3].memory_changes, vec![(0, 7u64.to_le_bytes().to_vec())]);
+        assert_eq!(trace.steps[5].depth, 1);
+        assert_eq!(trace.steps[6].depth, 2);
+        assert_eq!(trace.steps[10].stack, vec![14]);
+        assert_eq!(
+            trace.function_calls,
+            vec![
+                GethExecTraceFunctionCall {
+                    fn_index: 0,
+                    max_stack_height: 2,
+                    num_locals: 1,
+                },
+                GethExecTraceFunctionCall {
+                    fn_index: 1,
+                    max_stack_height: 2,
+                    num_locals: 0,
+                },
+            ]
+        );
+        assert_eq!(trace.globals.len(), 1);
+        assert_eq!(trace.globals[0].value, 5);
+        assert_eq!(trace.data_segments, vec![(16, vec![72, 101])]);
+
+        let geth_trace = trace.to_geth_trace().unwrap();
+        assert_eq!(geth_trace.global_memory.0[16..18], [72, 101]);
+        assert_eq!(geth_trace.struct_logs[4].global_memory.0[0..8], 7u64.to_le_bytes());
+        assert_eq!(geth_trace.wasm_extension().validate(), Ok(()));
+    }
+
+    #[test]
+    fn loop_with_branches() {
+        // sums 1..=n
+        let code = module(&[i32_func(
+            1,
+            1,
+            vec![
+                Instruction::Block(BlockType::Empty),
+                Instruction::Loop(BlockType::Empty),
+                Instruction::LocalGet(0),
+                Instruction::I32Eqz,
+                Instruction::BrIf(1),
+                Instruction::LocalGet(1),
+                Instruction::LocalGet(0),
+                Instruction::I32Add,
+                Instruction::LocalSet(1),
+                Instruction::LocalGet(0),
+                Instruction::I32Const(1),
+                Instruction::I32Sub,
+                Instruction::LocalSet(0),
+                Instruction::Br(0),
+                Instruction::End,
+                Instruction::End,
+                Instruction::LocalGet(1),
+                Instruction::End,
+            ],
+        )]);
+        let interpreter = WasmInterpreter::new(&code).unwrap();
+        let call = interpreter.trace_call("main", &[4]).unwrap();
+        assert_eq!(call.results, vec![10]);
+        let instrs = instrs(&call.trace);
+        assert_eq!(instrs.iter().filter(|i| **i == "loop").count(), 1);
+        assert_eq!(instrs.iter().filter(|i| **i == "br").count(), 4);
+        // br_if 1 leaves the block past its `end`
+        assert_eq!(instrs[instrs.len() - 3..], ["br_if", "local.get", "end"]);
+        assert_eq!(interpreter.trace_call("main", &[0]).unwrap().results, vec![0]);
+    }
+
+    #[test]
+    fn traps_end_the_trace() {
+        let code = module(&[i32_func(
+            1,
+            0,
+            vec![
+                Instruction::I32Const(1),
+                Instruction::LocalGet(0),
+                Instruction::I32DivU,
+                Instruction::End,
+            ],
+        )]);
+        let interpreter = WasmInterpreter::new(&code).unwrap();
+        assert_eq!(interpreter.trace_call("main", &[1]).unwrap().results, vec![1]);
+
+        let call = interpreter.trace_call("main", &[0]).unwrap();
+        assert!(call.results.is_empty());
+        assert_eq!(call.trace.steps.len(), 3);
+        assert_eq!(
+            call.trace.trap.as_deref(),
+            Some(GETH_ERR_WASM_DIVISION_BY_ZERO)
+        );
+        let geth_trace = call.trace.to_geth_trace().unwrap();
+        assert!(geth_trace.failed);
+        assert_eq!(
+            geth_trace.struct_logs[2].error.as_deref(),
+            Some(GETH_ERR_WASM_DIVISION_BY_ZERO)
+        );
+    }
+
+    #[test]
+    fn fuel_runs_out() {
+        let code = module(&[TestFunc {
+            params: vec![],
+            results: vec![],
+            locals: vec![],
+            body: vec![
+                Instruction::Loop(BlockType::Empty),
+                Instruction::Br(0),
+                Instruction::End,
+                Instruction::End,
+            ],
+        }]);
+        let call = WasmInterpreter::new(&code)
+            .unwrap()
+            .with_fuel_limit(10)
+            .trace_call("main", &[])
+            .unwrap();
+        assert_eq!(call.trace.trap.as_deref(), Some(GETH_ERR_OUT_OF_GAS));
+        assert_eq!(call.trace.fuel_consumed, 10);
+        assert_eq!(call.trace.steps.len(), 11);
+    }
+
+    #[test]
+    fn entry_locals_above_stack_height_trap_at_the_first_instruction() {
+        let code = module(&[i32_func(
+            0,
+            MAX_WASM_STACK_HEIGHT as u32 + 1,
+            vec![Instruction::I32Const(1), Instruction::End],
+        )]);
+        let call = WasmInterpreter::new(&code)
+            .unwrap()
+            .trace_call("main", &[])
+            .unwrap();
+        assert_eq!(call.trace.trap.as_deref(), Some(GETH_ERR_WASM_STACK_HEIGHT_EXCEEDED));
+        assert_eq!(instrs(&call.trace), vec!["i32.const"]);
+        assert_eq!(
+            call.trace.steps[0].trap.as_deref(),
+            Some(GETH_ERR_WASM_STACK_HEIGHT_EXCEEDED)
+        );
+        assert_eq!(call.trace.function_calls[0].num_locals, MAX_WASM_STACK_HEIGHT as u32 + 1);
+    }
+
+    #[test]
+    fn imported_function_call_is_unsupported() {
+        let mut types = TypeSection::new();
+        types.function(vec![], vec![]);
+        let mut imports = ImportSection::new();
+        imports.import("env", "host", EntityType::Function(0));
+        let mut functions = FunctionSection::new();
+        functions.function(0);
+        let mut exports = ExportSection::new();
+        exports.export("main", ExportKind::Func, 1);
+        let mut codes = CodeSection::new();
+        let mut f = Function::new(vec![]);
+        f.instruction(&Instruction::Call(0));
+        f.instruction(&Instruction::End);
+        codes.function(&f);
+        let mut module = Module::new();
+        module.section(&types);
+        module.section(&imports);
+        module.section(&functions);
+        module.section(&exports);
+        module.section(&codes);
+        let code = module.finish();
+
+        let interpreter = WasmInterpreter::new(&code).unwrap();
+        assert!(matches!(
+            interpreter.trace_call("main", &[]),
+            Err(Error::WasmInterpreterError(_))
+        ));
+        assert!(matches!(
+            interpreter.trace_call("missing", &[]),
+            Err(Error::WasmInterpreterError(_))
+        ));
+    }
+
+    #[test]
+    fn interpreter_traces_provider() {
+        let code = call_store_module();
+        let expected = WasmInterpreter::new(&code)
+            .unwrap()
+            .trace_call("main", &[7])
+            .unwrap()
+            .trace
+            .to_geth_trace()
+            .unwrap();
+        let traces = InterpreterTraces(vec![InterpreterCall {
+            code,
+            function: "main".to_string(),
+            args: vec![7],
+        }]);
+        let block = Block {
+            transactions: vec![Transaction::default()],
+            ..Default::default()
+        };
+        assert_eq!(traces.block_traces(&block).unwrap(), vec![expected]);
+    }
+}
@@ -234,12 +234,15 @@ pub mod circuit_input_builder;
 pub mod error;
 pub mod exec_trace;
 pub(crate) mod geth_errors;
+pub mod interpreter;
 pub mod l2_predeployed;
 pub mod mock;
 pub mod operation;
 pub mod precompile;
 pub mod rpc;
 pub mod state_db;
+pub mod trace_export;
+pub mod trace_provider;
 pub mod util;
 
 pub use error::Error;
@@ -92,8 +92,12 @@ pub enum Target {
     Stack,
     /// Means that target of the operation is the Global.
     Global,
+    /// Means that target of the operation is a wasm Table.
+    Table,
     /// Means the target of the operation is the Storage.
     Storage,
+    /// Means the target of the operation is the TransientStorage.
+    TransientStorage,
     /// Means the target of the operation is the TxAccessListAccount.
     TxAccessListAccount,
     /// Means the target of the operation is the TxAccessListAccountStorage.
@@ -292,37 +296,46 @@ impl Ord for StackOp {
     }
 }
 
-/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the stack implied
-/// by an specific [`OpcodeId`](eth_types::evm_types::opcode_ids::OpcodeId) of
-/// the [`ExecStep`](crate::circuit_input_builder::ExecStep).
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into a wasm global
+/// implied by an specific
+/// [`OpcodeId`](eth_types::evm_types::opcode_ids::OpcodeId) of the
+/// [`ExecStep`](crate::circuit_input_builder::ExecStep).
 #[derive(Clone, PartialEq, Eq)]
 pub struct GlobalOp {
     /// Call ID
     pub call_id: usize,
     /// Global index
     pub global_index: u32,
-    /// Value
+    /// Value after the operation
     pub value: StackWord,
+    /// Value before the operation
+    pub value_prev: StackWord,
 }
 
 impl Debug for GlobalOp {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str("GlobalOp { ")?;
         f.write_fmt(format_args!(
-            "call_id: {:?}, index: {:?}, val: 0x{:x}",
-            self.call_id, self.global_index, self.value
+            "call_id: {:?}, index: {:?}, val_prev: 0x{:x}, val: 0x{:x}",
+            self.call_id, self.global_index, self.value_prev, self.value
         ))?;
         f.write_str(" }")
     }
 }
 
 impl GlobalOp {
-    /// Create a new instance of a `StackOp` from it's components.
-    pub const fn new(call_id: usize, global_index: u32, value: StackWord) -> GlobalOp {
+    /// Create a new instance of a `GlobalOp` from it's components.
+    pub const fn new(
+        call_id: usize,
+        global_index: u32,
+        value: StackWord,
+        value_prev: StackWord,
+    ) -> GlobalOp {
         GlobalOp {
             call_id,
             global_index,
             value,
+            value_prev,
         }
     }
 
@@ -345,6 +358,11 @@ impl GlobalOp {
     pub const fn value(&self) -> &StackWord {
         &self.value
     }
+
+    /// Returns the [`Word`] held by the global before this operation.
+    pub const fn value_prev(&self) -> &StackWord {
+        &self.value_prev
+    }
 }
 
 impl Op for GlobalOp {
@@ -353,7 +371,9 @@ impl Op for GlobalOp {
     }
 
     fn reverse(&self) -> Self {
-        unreachable!("GlobalOp can't be reverted")
+        let mut rev = self.clone();
+        swap(&mut rev.value, &mut rev.value_prev);
+        rev
     }
 }
 
@@ -369,6 +389,115 @@ impl Ord for GlobalOp {
     }
 }
 
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) of an element of a
+/// wasm table implied by an specific
+/// [`OpcodeId`](eth_types::evm_types::opcode_ids::OpcodeId) of the
+/// [`ExecStep`](crate::circuit_input_builder::ExecStep).
+///
+/// The funcref value is the referenced function index plus one, 0 is the null
+/// reference every element starts with.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TableOp {
+    /// Call ID
+    pub call_id: usize,
+    /// Table index
+    pub table_index: u32,
+    /// Element index
+    pub elem_index: u32,
+    /// Funcref value after the operation
+    pub value: StackWord,
+    /// Funcref value before the operation
+    pub value_prev: StackWord,
+}
+
+impl Debug for TableOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TableOp { ")?;
+        f.write_fmt(format_args!(
+            "call_id: {:?}, table: {:?}, elem: {:?}, val_prev: 0x{:x}, val: 0x{:x}",
+            self.call_id, self.table_index, self.elem_index, self.value_prev, self.value
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl TableOp {
+    /// Create a new instance of a `TableOp` from it's components.
+    pub const fn new(
+        call_id: usize,
+        table_index: u32,
+        elem_index: u32,
+        value: StackWord,
+        value_prev: StackWord,
+    ) -> TableOp {
+        TableOp {
+            call_id,
+            table_index,
+            elem_index,
+            value,
+            value_prev,
+        }
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::Table
+    }
+
+    /// Returns the call id associated to this Operation.
+    pub const fn call_id(&self) -> usize {
+        self.call_id
+    }
+
+    /// Returns the table index associated to this Operation.
+    pub const fn table_index(&self) -> u32 {
+        self.table_index
+    }
+
+    /// Returns the element index associated to this Operation.
+    pub const fn elem_index(&self) -> u32 {
+        self.elem_index
+    }
+
+    /// Returns the funcref read or written by this operation.
+    pub const fn value(&self) -> &StackWord {
+        &self.value
+    }
+
+    /// Returns the funcref held by the element before this operation.
+    pub const fn value_prev(&self) -> &StackWord {
+        &self.value_prev
+    }
+}
+
+impl Op for TableOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::Table(self)
+    }
+
+    fn reverse(&self) -> Self {
+        let mut rev = self.clone();
+        swap(&mut rev.value, &mut rev.value_prev);
+        rev
+    }
+}
+
+impl PartialOrd for TableOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TableOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.call_id, &self.table_index, &self.elem_index).cmp(&(
+            &other.call_id,
+            &other.table_index,
+            &other.elem_index,
+        ))
+    }
+}
+
 /// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the storage
 /// implied by an specific
 /// [`OpcodeId`](eth_types::evm_types::opcode_ids::OpcodeId) of
@@ -470,6 +599,77 @@ impl Ord for StorageOp {
     }
 }
 
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the EIP-1153 transient storage
+/// implied by a `TLOAD` or `TSTORE` step of the
+/// [`ExecStep`](crate::circuit_input_builder::ExecStep).
+#[derive(Clone, PartialEq, Eq)]
+pub struct TransientStorageOp {
+    /// Transaction ID: Transaction index in the block starting at 1.
+    pub tx_id: usize,
+    /// Account Address
+    pub address: Address,
+    /// Transient Storage Key
+    pub key: Word,
+    /// Transient Storage Value after the operation
+    pub value: Word,
+    /// Transient Storage Value before the operation
+    pub value_prev: Word,
+}
+
+impl fmt::Debug for TransientStorageOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TransientStorageOp { ")?;
+        f.write_fmt(format_args!(
+            "tx_id: {:?}, addr: {:?}, key: {:?}, val_prev: 0x{:x}, val: 0x{:x}",
+            self.tx_id, self.address, self.key, self.value_prev, self.value
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl TransientStorageOp {
+    /// Create a new instance of a `TransientStorageOp` from it's components.
+    pub const fn new(
+        tx_id: usize,
+        address: Address,
+        key: Word,
+        value: Word,
+        value_prev: Word,
+    ) -> TransientStorageOp {
+        TransientStorageOp {
+            tx_id,
+            address,
+            key,
+            value,
+            value_prev,
+        }
+    }
+}
+
+impl Op for TransientStorageOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::TransientStorage(self)
+    }
+
+    fn reverse(&self) -> Self {
+        let mut rev = self.clone();
+        swap(&mut rev.value, &mut rev.value_prev);
+        rev
+    }
+}
+
+impl PartialOrd for TransientStorageOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TransientStorageOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.tx_id, &self.address, &self.key).cmp(&(&other.tx_id, &other.address, &other.key))
+    }
+}
+
 /// Represents a change in the Account AccessList implied by a `BeginTx`,
 /// `EXTCODECOPY`, `EXTCODESIZE`, `EXTCODEHASH` `BALANCE`, `SELFDESTRUCT`,
 /// `*CALL`* or `CREATE*` step.
@@ -711,6 +911,22 @@ pub enum CallContextField {
     RwCounterEndOfReversion,
     /// InternalFunctionId
     InternalFunctionId,
+    /// NumLocals
+    NumLocals,
+    /// FrameId
+    FrameId,
+    /// CallerFrameId
+    CallerFrameId,
+    /// CallerFunctionIndex
+    CallerFunctionIndex,
+    /// ReturnProgramCounter
+    ReturnProgramCounter,
+    /// LocalsBase
+    LocalsBase,
+    /// NumParams
+    NumParams,
+    /// NumResults
+    NumResults,
     /// CallerId
     CallerId,
     /// TxId
@@ -1009,10 +1225,14 @@ pub enum OpEnum {
     Stack(StackOp),
     /// Global
     Global(GlobalOp),
+    /// Table
+    Table(TableOp),
     /// Memory
     Memory(MemoryOp),
     /// Storage
     Storage(StorageOp),
+    /// TransientStorage
+    TransientStorage(TransientStorageOp),
     /// TxAccessListAccount
     TxAccessListAccount(TxAccessListAccountOp),
     /// TxAccessListAccountStorage
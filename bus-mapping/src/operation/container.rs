@@ -1,11 +1,11 @@
 use super::{
     AccountOp, CallContextOp, MemoryOp, Op, OpEnum, Operation, RWCounter, StackOp, StartOp,
-    StorageOp, Target, TxAccessListAccountOp, TxAccessListAccountStorageOp, TxLogOp, TxReceiptOp,
+    StorageOp, Target, TransientStorageOp, TxAccessListAccountOp, TxAccessListAccountStorageOp, TxLogOp, TxReceiptOp,
     TxRefundOp, RW,
 };
 use crate::exec_trace::OperationRef;
 use itertools::Itertools;
-use crate::operation::GlobalOp;
+use crate::operation::{GlobalOp, TableOp};
 
 /// The `OperationContainer` is meant to store all of the [`Operation`]s that an
 /// [`ExecStep`](crate::circuit_input_builder::ExecStep) performs during its
@@ -29,8 +29,12 @@ pub struct OperationContainer {
     pub stack: Vec<Operation<StackOp>>,
     /// Operations of GlobalOp
     pub globals: Vec<Operation<GlobalOp>>,
+    /// Operations of TableOp
+    pub tables: Vec<Operation<TableOp>>,
     /// Operations of StorageOp
     pub storage: Vec<Operation<StorageOp>>,
+    /// Operations of TransientStorageOp
+    pub transient_storage: Vec<Operation<TransientStorageOp>>,
     /// Operations of TxAccessListAccountOp
     pub tx_access_list_account: Vec<Operation<TxAccessListAccountOp>>,
     /// Operations of TxAccessListAccountStorageOp
@@ -63,7 +67,9 @@ impl OperationContainer {
             memory: Vec::new(),
             stack: Vec::new(),
             globals: Vec::new(),
+            tables: Vec::new(),
             storage: Vec::new(),
+            transient_storage: Vec::new(),
             tx_access_list_account: Vec::new(),
             tx_access_list_account_storage: Vec::new(),
             tx_refund: Vec::new(),
@@ -110,6 +116,10 @@ impl OperationContainer {
                 self.globals.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::Global, self.globals.len() - 1))
             },
+            OpEnum::Table(op) => {
+                self.tables.push(Operation::new(rwc, rw, op));
+                OperationRef::from((Target::Table, self.tables.len() - 1))
+            }
             OpEnum::Storage(op) => {
                 self.storage.push(if reversible {
                     Operation::new_reversible(rwc, rw, op)
@@ -118,6 +128,17 @@ impl OperationContainer {
                 });
                 OperationRef::from((Target::Storage, self.storage.len() - 1))
             }
+            OpEnum::TransientStorage(op) => {
+                self.transient_storage.push(if reversible {
+                    Operation::new_reversible(rwc, rw, op)
+                } else {
+                    Operation::new(rwc, rw, op)
+                });
+                OperationRef::from((
+                    Target::TransientStorage,
+                    self.transient_storage.len() - 1,
+                ))
+            }
             OpEnum::TxAccessListAccount(op) => {
                 self.tx_access_list_account.push(if reversible {
                     Operation::new_reversible(rwc, rw, op)
@@ -3,7 +3,7 @@
 
 use crate::{
     precompile::is_precompiled,
-    util::{hash_code, KECCAK_CODE_HASH_ZERO},
+    util::{hash_code, hash_code_poseidon, KECCAK_CODE_HASH_ZERO},
 };
 use eth_types::{Address, Hash, Word, H256, U256};
 use lazy_static::lazy_static;
@@ -22,6 +22,12 @@ lazy_static! {
 
 const VALUE_ZERO: Word = Word::zero();
 
+/// Magic prefix of wasm modules
+pub const WASM_MAGIC: &[u8; 4] = b"\0asm";
+
+/// Binary format version of wasm modules, following the magic
+pub const WASM_VERSION: &[u8; 4] = &[0x01, 0x00, 0x00, 0x00];
+
 /// Memory storage for contract code by code hash.
 #[derive(Debug)]
 pub struct CodeDB(pub HashMap<Hash, Vec<u8>>);
@@ -59,6 +65,41 @@ impl CodeDB {
     pub fn hash(code: &[u8]) -> Hash {
         H256(hash_code(code).into())
     }
+
+    /// Compute poseidon hash of given code, regardless of the default code hash.
+    pub fn hash_poseidon(code: &[u8]) -> Hash {
+        hash_code_poseidon(code)
+    }
+
+    /// Return if code is a wasm module (starts with the wasm magic).
+    pub fn is_wasm(code: &[u8]) -> bool {
+        code.starts_with(WASM_MAGIC)
+    }
+
+    /// Return if code can be deployed as a wasm module (starts with the wasm magic and
+    /// version).
+    pub fn is_wasm_module(code: &[u8]) -> bool {
+        Self::is_wasm(code) && code.get(WASM_MAGIC.len()..8) == Some(WASM_VERSION.as_slice())
+    }
+
+    /// Return wasm codes of given code hashes, deduplicated and in first occurrence order.
+    /// Hashes missing from the db or pointing to non wasm code are skipped.
+    pub fn wasm_codes(
+        &self,
+        code_hashes: impl IntoIterator<Item = Hash>,
+    ) -> Vec<(Hash, Vec<u8>)> {
+        let mut seen = HashSet::new();
+        code_hashes
+            .into_iter()
+            .filter(|code_hash| seen.insert(*code_hash))
+            .filter_map(|code_hash| {
+                self.0
+                    .get(&code_hash)
+                    .filter(|code| Self::is_wasm(code))
+                    .map(|code| (code_hash, code.clone()))
+            })
+            .collect()
+    }
 }
 
 /// Account of the Ethereum State Trie, which contains an in-memory key-value
@@ -115,6 +156,8 @@ pub struct StateDB {
     // state before current transaction, to calculate gas cost for some opcodes like sstore.
     // So both dirty storage and committed storage are needed.
     dirty_storage: HashMap<(Address, Word), Word>,
+    // EIP-1153 transient storage, discarded when current transaction finishes.
+    transient_storage: HashMap<(Address, Word), Word>,
     // Accounts that have been through `SELFDESTRUCT` under the situation that `is_persistent` is
     // `true`. These accounts will be reset once `commit_tx` is called.
     destructed_account: HashSet<Address>,
@@ -259,6 +302,19 @@ impl StateDB {
         debug_assert!(exist);
     }
 
+    /// Get transient storage value at `(addr, key)`, zero if it was not written in the current
+    /// transaction.
+    pub fn get_transient_storage(&self, addr: &Address, key: &Word) -> &Word {
+        self.transient_storage
+            .get(&(*addr, *key))
+            .unwrap_or(&VALUE_ZERO)
+    }
+
+    /// Set transient storage value at `(addr, key)`.
+    pub fn set_transient_storage(&mut self, addr: &Address, key: &Word, value: &Word) {
+        self.transient_storage.insert((*addr, *key), *value);
+    }
+
     /// Set account as self destructed.
     pub fn destruct_account(&mut self, addr: Address) {
         self.state.insert(addr, Account::zero());
@@ -275,7 +331,7 @@ impl StateDB {
         self.refund = value;
     }
 
-    /// Clear access list and refund, and commit dirty storage.
+    /// Clear access list, transient storage and refund, and commit dirty storage.
     /// It should be invoked before processing
     /// with new transaction with the same [`StateDB`].
     pub fn commit_tx(&mut self) {
@@ -286,6 +342,7 @@ impl StateDB {
             *ptr = value;
         }
         self.dirty_storage = HashMap::new();
+        self.transient_storage = HashMap::new();
         for addr in self.destructed_account.clone() {
             let (_, account) = self.get_account_mut(&addr);
             *account = ACCOUNT_ZERO.clone();
@@ -353,4 +410,38 @@ mod statedb_tests {
         assert!(found);
         assert_eq!(value, &Word::from(102));
     }
+
+    #[test]
+    fn statedb_transient_storage() {
+        let addr = address!("0x0000000000000000000000000000000000000001");
+        let mut statedb = StateDB::new();
+        assert_eq!(statedb.get_transient_storage(&addr, &Word::from(1)), &Word::zero());
+
+        statedb.set_transient_storage(&addr, &Word::from(1), &Word::from(7));
+        assert_eq!(statedb.get_transient_storage(&addr, &Word::from(1)), &Word::from(7));
+        // transient storage does not reach the account storage
+        assert!(!statedb.get_storage(&addr, &Word::from(1)).0);
+
+        statedb.commit_tx();
+        assert_eq!(statedb.get_transient_storage(&addr, &Word::from(1)), &Word::zero());
+    }
+
+    #[test]
+    fn code_db_wasm_codes() {
+        let mut code_db = CodeDB::new();
+        let wasm = [WASM_MAGIC.as_slice(), WASM_VERSION.as_slice()].concat();
+        let wasm_hash = code_db.insert(wasm.clone());
+        let evm_hash = code_db.insert(vec![0x60, 0x00]);
+        let missing_hash = CodeDB::hash(&[0x00]);
+
+        assert!(CodeDB::is_wasm(&wasm));
+        assert!(!CodeDB::is_wasm(&[0x60, 0x00]));
+        assert!(CodeDB::is_wasm_module(&wasm));
+        assert!(!CodeDB::is_wasm_module(WASM_MAGIC));
+        assert!(!CodeDB::is_wasm_module(b"\0asm\x02\0\0\0"));
+        assert_eq!(
+            code_db.wasm_codes([evm_hash, wasm_hash, missing_hash, wasm_hash]),
+            vec![(wasm_hash, wasm)]
+        );
+    }
 }
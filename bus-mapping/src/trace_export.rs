@@ -0,0 +1,361 @@
+//! Stable binary format of wasm execution traces ([`GethExecTrace`]), so traces can be
+//! consumed by other provers and debuggers without the geth JSON.
+//!
+//! All integers are little-endian, `str` is a `u32` byte length followed by utf-8 bytes,
+//! `mem` is a `u32` offset, `u32` length and the bytes, `vec<T>` is a `u32` count followed by
+//! the items.
+//!
+//! ```text
+//! trace:    magic "WTRC" | version u16 | l1_fee u64 | gas u64 | failed u8
+//!           | internal_error str | return_value str | global_memory mem
+//!           | globals vec<global> | function_calls vec<call> | steps vec<step>
+//! global:   pc u64 | index u32 | op str | value u64
+//! call:     fn_index u32 | max_stack_height u32 | num_locals u32
+//! step:     pc u64 | op_family u8 | op u8 | gas u64 | gas_cost u64 | refund u64 | depth u16
+//!           | error_flag u8 [| error str] | params vec<u64> | stack vec<u64>
+//!           | memory_changes vec<mem> | storage vec<key [u8; 32] | value [u8; 32]>
+//! ```
+//!
+//! `op_family` is 0 for none, 1 unknown, 2 wasm, 3 evm. `op` is [`OpcodeId::as_u8`], which is not
+//! injective for every evm opcode (e.g. `CREATE2` shares the `BASEFEE` byte), wasm opcodes round
+//! trip exactly. Storage words are big-endian, entries are sorted by key. The global memory
+//! snapshot of every step is not stored, the reader rebuilds it from the initial global memory
+//! and memory changes the same way the geth JSON deserialization does.
+
+use eth_types::{
+    evm_types::{Gas, GasCost, Memory, OpcodeId, ProgramCounter, Stack, Storage},
+    wasm_trace::WASM_TRACE_VERSION,
+    GethExecStep, GethExecStepFamily, GethExecTrace, GethExecTraceFunctionCall,
+    GethExecTraceGlobal, StackWord, Word,
+};
+
+use crate::Error;
+
+/// Magic prefix of an exported trace
+pub const TRACE_MAGIC: &[u8; 4] = b"WTRC";
+/// Version of the exported trace format
+pub const TRACE_VERSION: u16 = 1;
+
+struct TraceWriter {
+    bytes: Vec<u8>,
+}
+
+impl TraceWriter {
+    fn u8(&mut self, v: u8) {
+        self.bytes.push(v);
+    }
+    fn u16(&mut self, v: u16) {
+        self.bytes.extend(v.to_le_bytes());
+    }
+    fn u32(&mut self, v: u32) {
+        self.bytes.extend(v.to_le_bytes());
+    }
+    fn u64(&mut self, v: u64) {
+        self.bytes.extend(v.to_le_bytes());
+    }
+    fn len(&mut self, len: usize) {
+        self.u32(len as u32);
+    }
+    fn str(&mut self, s: &str) {
+        self.len(s.len());
+        self.bytes.extend(s.as_bytes());
+    }
+    fn mem(&mut self, memory: &Memory) {
+        self.u32(memory.1);
+        self.len(memory.0.len());
+        self.bytes.extend(&memory.0);
+    }
+    fn word(&mut self, word: &Word) {
+        let mut bytes = [0u8; 32];
+        word.to_big_endian(&mut bytes);
+        self.bytes.extend(bytes);
+    }
+
+    fn step(&mut self, step: &GethExecStep) {
+        self.u64(step.pc.0 as u64);
+        self.u8(match step.op_family {
+            None => 0,
+            Some(GethExecStepFamily::Unknown) => 1,
+            Some(GethExecStepFamily::WebAssembly) => 2,
+            Some(GethExecStepFamily::Evm) => 3,
+        });
+        self.u8(step.op.as_u8());
+        self.u64(step.gas.0);
+        self.u64(step.gas_cost.0);
+        self.u64(step.refund.0);
+        self.u16(step.depth);
+        match &step.error {
+            Some(error) => {
+                self.u8(1);
+                self.str(error);
+            }
+            None => self.u8(0),
+        }
+        self.len(step.params.len());
+        step.params.iter().for_each(|p| self.u64(*p));
+        self.len(step.stack.0.len());
+        step.stack.0.iter().for_each(|v| self.u64(v.as_u64()));
+        self.len(step.memory.len());
+        step.memory.iter().for_each(|m| self.mem(m));
+        let mut storage = step.storage.0.iter().collect::<Vec<_>>();
+        storage.sort_by_key(|(key, _)| **key);
+        self.len(storage.len());
+        for (key, value) in storage {
+            self.word(key);
+            self.word(value);
+        }
+    }
+}
+
+/// Encodes `trace` into the exported trace format
+pub fn export_trace(trace: &GethExecTrace) -> Vec<u8> {
+    let mut w = TraceWriter {
+        bytes: TRACE_MAGIC.to_vec(),
+    };
+    w.u16(TRACE_VERSION);
+    w.u64(trace.l1_fee);
+    w.u64(trace.gas.0);
+    w.u8(trace.failed as u8);
+    w.str(&trace.internal_error);
+    w.str(&trace.return_value);
+    w.mem(&trace.global_memory);
+    w.len(trace.globals.len());
+    for global in &trace.globals {
+        w.u64(global.pc.0 as u64);
+        w.u32(global.index);
+        w.str(&global.op);
+        w.u64(global.value);
+    }
+    w.len(trace.function_calls.len());
+    for call in &trace.function_calls {
+        w.u32(call.fn_index);
+        w.u32(call.max_stack_height);
+        w.u32(call.num_locals);
+    }
+    w.len(trace.struct_logs.len());
+    trace.struct_logs.iter().for_each(|step| w.step(step));
+    w.bytes
+}
+
+struct TraceReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> TraceReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(Error::InvalidGethExecTrace("exported trace is truncated"))?;
+        let bytes = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+    fn len(&mut self) -> Result<usize, Error> {
+        Ok(self.u32()? as usize)
+    }
+    fn str(&mut self) -> Result<String, Error> {
+        let len = self.len()?;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| Error::InvalidGethExecTrace("exported trace string is not utf-8"))
+    }
+    fn mem(&mut self) -> Result<Memory, Error> {
+        let offset = self.u32()?;
+        let len = self.len()?;
+        Ok(Memory::from_bytes_with_offset(self.take(len)?.to_vec(), offset))
+    }
+    fn word(&mut self) -> Result<Word, Error> {
+        Ok(Word::from_big_endian(self.take(32)?))
+    }
+    fn vec<T>(&mut self, f: impl Fn(&mut Self) -> Result<T, Error>) -> Result<Vec<T>, Error> {
+        let len = self.len()?;
+        // every item takes at least one byte, don't trust the count for allocation
+        let mut items = Vec::with_capacity(len.min(self.bytes.len() - self.offset));
+        for _ in 0..len {
+            items.push(f(self)?);
+        }
+        Ok(items)
+    }
+
+    fn step(&mut self, global_memory: &mut Memory) -> Result<GethExecStep, Error> {
+        let pc = ProgramCounter(self.u64()? as usize);
+        let op_family = match self.u8()? {
+            0 => None,
+            1 => Some(GethExecStepFamily::Unknown),
+            2 => Some(GethExecStepFamily::WebAssembly),
+            3 => Some(GethExecStepFamily::Evm),
+            _ => return Err(Error::InvalidGethExecTrace("exported trace has bad op family")),
+        };
+        let op = OpcodeId::from(self.u8()?);
+        let gas = Gas(self.u64()?);
+        let gas_cost = GasCost(self.u64()?);
+        let refund = Gas(self.u64()?);
+        let depth = self.u16()?;
+        let error = match self.u8()? {
+            0 => None,
+            1 => Some(self.str()?),
+            _ => return Err(Error::InvalidGethExecTrace("exported trace has bad error flag")),
+        };
+        let params = self.vec(|r| r.u64())?;
+        let stack = Stack(self.vec(|r| Ok(StackWord::from(r.u64()?)))?);
+        let memory = self.vec(|r| r.mem())?;
+        let storage = Storage(
+            self.vec(|r| Ok((r.word()?, r.word()?)))?
+                .into_iter()
+                .collect(),
+        );
+        memory.iter().for_each(|m| global_memory.extends_with(m));
+
+        Ok(GethExecStep {
+            pc,
+            op_family,
+            params,
+            op,
+            gas,
+            gas_cost,
+            refund,
+            depth,
+            error,
+            stack,
+            memory,
+            global_memory: global_memory.clone(),
+            storage,
+        })
+    }
+}
+
+/// Decodes a trace produced by [`export_trace`]
+pub fn read_trace(bytes: &[u8]) -> Result<GethExecTrace, Error> {
+    let mut r = TraceReader { bytes, offset: 0 };
+    if r.take(TRACE_MAGIC.len())? != TRACE_MAGIC {
+        return Err(Error::InvalidGethExecTrace("exported trace has bad magic"));
+    }
+    if r.u16()? != TRACE_VERSION {
+        return Err(Error::InvalidGethExecTrace("exported trace has unsupported version"));
+    }
+    let l1_fee = r.u64()?;
+    let gas = Gas(r.u64()?);
+    let failed = r.u8()? != 0;
+    let internal_error = r.str()?;
+    let return_value = r.str()?;
+    let global_memory = r.mem()?;
+    let globals = r.vec(|r| {
+        Ok(GethExecTraceGlobal {
+            pc: ProgramCounter(r.u64()? as usize),
+            index: r.u32()?,
+            op: r.str()?,
+            value: r.u64()?,
+        })
+    })?;
+    let function_calls = r.vec(|r| {
+        Ok(GethExecTraceFunctionCall {
+            fn_index: r.u32()?,
+            max_stack_height: r.u32()?,
+            num_locals: r.u32()?,
+        })
+    })?;
+    let mut step_global_memory = global_memory.clone();
+    let struct_logs = r.vec(|r| r.step(&mut step_global_memory))?;
+    if r.offset != bytes.len() {
+        return Err(Error::InvalidGethExecTrace("exported trace has trailing bytes"));
+    }
+
+    Ok(GethExecTrace {
+        l1_fee,
+        gas,
+        internal_error,
+        failed,
+        global_memory,
+        return_value,
+        struct_logs,
+        globals,
+        function_calls,
+        // version 1 of the format carries version 1 of the wasm fields
+        wasm_trace_version: WASM_TRACE_VERSION,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WASM_TRACE_JSON: &str = r#"
+{
+    "gas": 92024,
+    "failed": false,
+    "returnValue": "",
+    "globalMemory": { "1048576": "0x48656c6c6f" },
+    "structLogs":
+    [
+        {
+            "pc": 0,
+            "opcodeFamily": "WASM",
+            "params": [1048576],
+            "op": "i32_const",
+            "gas": 9942176,
+            "gasCost": 1,
+            "depth": 1,
+            "stack": ["0x0"]
+        },
+        {
+            "pc": 1,
+            "opcodeFamily": "WASM",
+            "params": [],
+            "op": "i32_store",
+            "gas": 9942175,
+            "gasCost": 1,
+            "depth": 1,
+            "stack": ["0x0", "0x100000"],
+            "memoryChanges": { "1048580": "0x2c20" },
+            "storage": { "0x1": "0x2a" }
+        },
+        {
+            "pc": 18446744073709551615,
+            "opcodeFamily": "EVM",
+            "params": [],
+            "op": "evm_return",
+            "gas": 9942176,
+            "gasCost": 0,
+            "depth": 1,
+            "error": "out of gas",
+            "stack": ["0xab", "0x100000"]
+        }
+    ],
+    "globals": [{ "pc": 1, "index": 0, "op": "global_get", "value": 7 }],
+    "functionCalls": [{ "fnIndex": 1, "maxStackHeight": 2, "numLocals": 0 }]
+}
+    "#;
+
+    #[test]
+    fn export_read_round_trip() {
+        let trace: GethExecTrace = serde_json::from_str(WASM_TRACE_JSON).unwrap();
+        let bytes = export_trace(&trace);
+        assert_eq!(&bytes[..4], TRACE_MAGIC);
+        assert_eq!(read_trace(&bytes).unwrap(), trace);
+    }
+
+    #[test]
+    fn read_malformed_trace_fails() {
+        let trace: GethExecTrace = serde_json::from_str(WASM_TRACE_JSON).unwrap();
+        let bytes = export_trace(&trace);
+        assert!(read_trace(&bytes[..bytes.len() - 1]).is_err());
+        assert!(read_trace(&[bytes.as_slice(), &[0]].concat()).is_err());
+        let mut bad_magic = bytes;
+        bad_magic[0] = 0;
+        assert!(read_trace(&bad_magic).is_err());
+    }
+}
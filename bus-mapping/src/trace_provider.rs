@@ -0,0 +1,272 @@
+//! Sources of wasm execution traces. The [`CircuitInputBuilder`] consumes [`GethExecTrace`]s,
+//! a [`WasmTraceProvider`] turns the traces of an engine into them.
+//!
+//! [`CircuitInputBuilder`]: crate::circuit_input_builder::CircuitInputBuilder
+
+use std::str::FromStr;
+
+use eth_types::{
+    evm_types::{Gas, GasCost, Memory, OpcodeId, ProgramCounter, Stack, Storage},
+    wasm_trace::WASM_TRACE_VERSION,
+    Block, GethExecStep, GethExecStepFamily, GethExecTrace, GethExecTraceFunctionCall,
+    GethExecTraceGlobal, StackWord, Transaction,
+};
+use serde::Deserialize;
+
+use crate::{trace_export::read_trace, Error};
+
+/// Source of the execution traces of a block
+pub trait WasmTraceProvider {
+    /// Traces of the block transactions, in transaction order
+    fn block_traces(&self, eth_block: &Block<Transaction>) -> Result<Vec<GethExecTrace>, Error>;
+}
+
+fn check_trace_count(eth_block: &Block<Transaction>, count: usize) -> Result<(), Error> {
+    if eth_block.transactions.len() != count {
+        return Err(Error::InvalidGethExecTrace(
+            "trace count differs from block transaction count",
+        ));
+    }
+    Ok(())
+}
+
+/// Traces in the geth `debug_trace*` JSON shape, one per transaction
+#[derive(Clone, Debug, Default)]
+pub struct GethJsonTraces(pub Vec<String>);
+
+impl WasmTraceProvider for GethJsonTraces {
+    fn block_traces(&self, eth_block: &Block<Transaction>) -> Result<Vec<GethExecTrace>, Error> {
+        check_trace_count(eth_block, self.0.len())?;
+        self.0
+            .iter()
+            .map(|json| serde_json::from_str(json).map_err(Error::SerdeError))
+            .collect()
+    }
+}
+
+/// Traces in the binary format of [`crate::trace_export`], one per transaction
+#[derive(Clone, Debug, Default)]
+pub struct ExportedTraces(pub Vec<Vec<u8>>);
+
+impl WasmTraceProvider for ExportedTraces {
+    fn block_traces(&self, eth_block: &Block<Transaction>) -> Result<Vec<GethExecTrace>, Error> {
+        check_trace_count(eth_block, self.0.len())?;
+        self.0.iter().map(|bytes| read_trace(bytes)).collect()
+    }
+}
+
+/// Instruction executed by wasmi
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct WasmiStep {
+    /// Offset of the instruction in the code section
+    pub pc: usize,
+    /// Instruction name in the wasm text format (`local.get`, `i32.add`), or the name of the
+    /// called host function (`evm_sstore`)
+    pub instr: String,
+    /// Immediates of the instruction
+    #[serde(default)]
+    pub params: Vec<u64>,
+    /// Fuel left before the instruction
+    pub fuel: u64,
+    /// Fuel charged for the instruction
+    pub fuel_cost: u64,
+    /// Call depth, 1 for the entry call
+    pub depth: u16,
+    /// Value stack before the instruction, bottom first
+    pub stack: Vec<u64>,
+    /// Linear memory written by the instruction as (offset, bytes)
+    #[serde(default)]
+    pub memory_changes: Vec<(u32, Vec<u8>)>,
+    /// Trap raised by the instruction
+    #[serde(default)]
+    pub trap: Option<String>,
+}
+
+/// Execution of a transaction recorded by a wasmi tracer
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct WasmiTrace {
+    /// Fuel consumed by the execution
+    pub fuel_consumed: u64,
+    /// Trap that aborted the execution
+    #[serde(default)]
+    pub trap: Option<String>,
+    /// Data returned by the execution
+    #[serde(default)]
+    pub return_data: Vec<u8>,
+    /// Active data segments as (offset, bytes)
+    #[serde(default)]
+    pub data_segments: Vec<(u32, Vec<u8>)>,
+    /// Globals
+    #[serde(default)]
+    pub globals: Vec<GethExecTraceGlobal>,
+    /// Function calls, the entry function first
+    pub function_calls: Vec<GethExecTraceFunctionCall>,
+    /// Executed instructions
+    pub steps: Vec<WasmiStep>,
+}
+
+/// Maps a wasm text format instruction name to the name used by geth traces.
+fn geth_op_name(instr: &str) -> String {
+    match instr {
+        "local.get" => return "get_local".to_string(),
+        "local.set" => return "set_local".to_string(),
+        "local.tee" => return "tee_local".to_string(),
+        "global.get" => return "get_global".to_string(),
+        "global.set" => return "set_global".to_string(),
+        "memory.size" => return "current_memory".to_string(),
+        "memory.grow" => return "grow_memory".to_string(),
+        _ => {}
+    }
+    let mut parts = instr.split(|c| c == '.' || c == '_').collect::<Vec<_>>();
+    // conversions put the signedness before the source type (`i64.extend_i32_s` is
+    // `i64_extend_s_i32`)
+    if parts.len() == 4 && (parts[3] == "s" || parts[3] == "u") {
+        parts.swap(2, 3);
+    }
+    parts.join("_")
+}
+
+impl WasmiTrace {
+    /// Converts the trace into the geth trace shape
+    pub fn to_geth_trace(&self) -> Result<GethExecTrace, Error> {
+        let mut global_memory = Memory::new();
+        for (offset, bytes) in self.data_segments.iter() {
+            global_memory.extends_with(&Memory::from_bytes_with_offset(bytes.clone(), *offset));
+        }
+        let init_memory = global_memory.clone();
+
+        let mut struct_logs = Vec::with_capacity(self.steps.len());
+        for step in self.steps.iter() {
+            let op_name = geth_op_name(&step.instr);
+            let op = OpcodeId::from_str(&op_name)?;
+            let memory = step
+                .memory_changes
+                .iter()
+                .map(|(offset, bytes)| Memory::from_bytes_with_offset(bytes.clone(), *offset))
+                .collect::<Vec<_>>();
+            memory.iter().for_each(|m| global_memory.extends_with(m));
+            struct_logs.push(GethExecStep {
+                pc: ProgramCounter(step.pc),
+                op_family: Some(if op_name.starts_with("evm_") {
+                    GethExecStepFamily::Evm
+                } else {
+                    GethExecStepFamily::WebAssembly
+                }),
+                params: step.params.clone(),
+                op,
+                gas: Gas(step.fuel),
+                gas_cost: GasCost(step.fuel_cost),
+                refund: Gas(0),
+                depth: step.depth,
+                error: step.trap.clone(),
+                stack: Stack(step.stack.iter().map(|v| StackWord::from(*v)).collect()),
+                memory,
+                global_memory: global_memory.clone(),
+                storage: Storage::default(),
+            });
+        }
+
+        Ok(GethExecTrace {
+            l1_fee: 0,
+            gas: Gas(self.fuel_consumed),
+            internal_error: self.trap.clone().unwrap_or_default(),
+            failed: self.trap.is_some(),
+            global_memory: init_memory,
+            return_value: hex::encode(&self.return_data),
+            struct_logs,
+            globals: self.globals.clone(),
+            function_calls: self.function_calls.clone(),
+            wasm_trace_version: WASM_TRACE_VERSION,
+        })
+    }
+}
+
+/// Traces recorded by wasmi, one per transaction
+#[derive(Clone, Debug, Default)]
+pub struct WasmiTraces(pub Vec<WasmiTrace>);
+
+impl WasmTraceProvider for WasmiTraces {
+    fn block_traces(&self, eth_block: &Block<Transaction>) -> Result<Vec<GethExecTrace>, Error> {
+        check_trace_count(eth_block, self.0.len())?;
+        self.0.iter().map(|trace| trace.to_geth_trace()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace_export::export_trace;
+
+    const WASMI_TRACE_JSON: &str = r#"
+{
+    "fuel_consumed": 3,
+    "data_segments": [[1048576, [72, 101]]],
+    "function_calls": [{ "fnIndex": 0, "maxStackHeight": 1, "numLocals": 1 }],
+    "steps": [
+        { "pc": 0, "instr": "local.get", "params": [0], "fuel": 10, "fuel_cost": 1, "depth": 1, "stack": [] },
+        { "pc": 2, "instr": "i64.extend_i32_u", "fuel": 9, "fuel_cost": 1, "depth": 1, "stack": [7] },
+        { "pc": 3, "instr": "i64.store", "params": [3, 0], "fuel": 8, "fuel_cost": 1, "depth": 1,
+          "stack": [0, 7], "memory_changes": [[1048578, [1, 2]]] },
+        { "pc": 6, "instr": "end", "fuel": 7, "fuel_cost": 0, "depth": 1, "stack": [] }
+    ]
+}
+    "#;
+
+    fn block_with_txs(count: usize) -> Block<Transaction> {
+        Block {
+            transactions: vec![Transaction::default(); count],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn wasm_text_names_map_to_geth_names() {
+        assert_eq!(geth_op_name("local.get"), "get_local");
+        assert_eq!(geth_op_name("i32.add"), "i32_add");
+        assert_eq!(geth_op_name("i32.lt_s"), "i32_lt_s");
+        assert_eq!(geth_op_name("i32.load8_u"), "i32_load8_u");
+        assert_eq!(geth_op_name("i32.trunc_f64_s"), "i32_trunc_s_f64");
+        assert_eq!(geth_op_name("f32.convert_i64_u"), "f32_convert_u_i64");
+        assert_eq!(geth_op_name("i32.wrap_i64"), "i32_wrap_i64");
+        assert_eq!(geth_op_name("evm_sstore"), "evm_sstore");
+    }
+
+    #[test]
+    fn wasmi_trace_to_geth_trace() {
+        let wasmi_trace: WasmiTrace = serde_json::from_str(WASMI_TRACE_JSON).unwrap();
+        let trace = wasmi_trace.to_geth_trace().unwrap();
+        let ops = trace.struct_logs.iter().map(|s| s.op).collect::<Vec<_>>();
+        assert_eq!(
+            ops,
+            vec![
+                OpcodeId::GetLocal,
+                OpcodeId::I64ExtendUI32,
+                OpcodeId::I64Store,
+                OpcodeId::End
+            ]
+        );
+        assert_eq!(trace.global_memory.0[1048576..1048578], [72, 101]);
+        assert_eq!(trace.struct_logs[3].global_memory.0[1048578..1048580], [1, 2]);
+        assert_eq!(trace.wasm_extension().validate(), Ok(()));
+    }
+
+    #[test]
+    fn providers_agree() {
+        let wasmi_trace: WasmiTrace = serde_json::from_str(WASMI_TRACE_JSON).unwrap();
+        let trace = wasmi_trace.to_geth_trace().unwrap();
+        let block = block_with_txs(1);
+
+        let exported = ExportedTraces(vec![export_trace(&trace)]);
+        assert_eq!(exported.block_traces(&block).unwrap(), vec![trace.clone()]);
+        let wasmi = WasmiTraces(vec![wasmi_trace]);
+        assert_eq!(wasmi.block_traces(&block).unwrap(), vec![trace]);
+    }
+
+    #[test]
+    fn trace_count_mismatch_fails() {
+        let wasmi_trace: WasmiTrace = serde_json::from_str(WASMI_TRACE_JSON).unwrap();
+        let wasmi = WasmiTraces(vec![wasmi_trace]);
+        assert!(wasmi.block_traces(&block_with_txs(2)).is_err());
+        assert!(GethJsonTraces(vec![]).block_traces(&block_with_txs(1)).is_err());
+    }
+}
@@ -1,8 +1,18 @@
 //! Evm types needed for parsing instruction sets as well
 
+pub mod gas_schedule;
+pub mod memory;
+pub mod memory_tree;
+pub(crate) mod data_segments;
+pub(crate) mod elem_segments;
+pub(crate) mod function_types;
 pub(crate) mod opcodes;
+pub(crate) mod sections;
 
 pub use eth_types::evm_types::opcode_ids::OpcodeId;
+pub use gas_schedule::{WasmGasSchedule, WASM_GAS_SCHEDULE};
+pub use memory::{WasmMemory, WasmMemoryAccess, WASM_MEMORY_CELL_SIZE};
+pub use memory_tree::{WasmMemoryTree, WasmMemoryTreeAccess, WASM_MEMORY_TREE_DEPTH};
 pub use opcodes::Opcode;
 
 #[cfg(any(feature = "test", test))]
@@ -0,0 +1,106 @@
+//! Active data segments of a WASM module, which initialize the linear memory at the start of a
+//! transaction.
+
+use std::ops::Range;
+
+use crate::Error;
+
+use super::sections::{read_leb128, section_body, WASM_DATA_SECTION_ID};
+
+const WASM_I32_CONST: u8 = 0x41;
+const WASM_END: u8 = 0x0b;
+
+/// Active data segment: the bytes at `bytes` in the code are copied to the linear memory at
+/// `memory_offset`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DataSegment {
+    /// destination in the linear memory
+    pub memory_offset: u64,
+    /// source range in the code
+    pub bytes: Range<usize>,
+}
+
+/// Active data segments of the module `code`, in section order. Passive segments are skipped,
+/// offsets must be a single `i32.const`.
+pub(crate) fn active_data_segments(code: &[u8]) -> Result<Vec<DataSegment>, Error> {
+    let body = match section_body(code, WASM_DATA_SECTION_ID)? {
+        Some(body) => body,
+        None => return Ok(vec![]),
+    };
+    let mut offset = body.start;
+    let segments_count = read_leb128(code, &mut offset, false)?;
+    let mut segments = Vec::with_capacity(segments_count as usize);
+    for _ in 0..segments_count {
+        let flags = read_leb128(code, &mut offset, false)?;
+        let memory_offset = match flags {
+            0 | 2 => {
+                if flags == 2 {
+                    // memory index, only memory 0 exists
+                    read_leb128(code, &mut offset, false)?;
+                }
+                if code.get(offset) != Some(&WASM_I32_CONST) {
+                    return Err(Error::InternalError("data segment offset is not i32.const"));
+                }
+                offset += 1;
+                let memory_offset = read_leb128(code, &mut offset, true)? as u32;
+                if code.get(offset) != Some(&WASM_END) {
+                    return Err(Error::InternalError("data segment offset is not i32.const"));
+                }
+                offset += 1;
+                Some(memory_offset as u64)
+            }
+            1 => None,
+            _ => return Err(Error::InternalError("invalid data segment flags")),
+        };
+        let bytes_len = read_leb128(code, &mut offset, false)? as usize;
+        let bytes = offset..offset + bytes_len;
+        if bytes.end > code.len() {
+            return Err(Error::InternalError("data segment out of code bounds"));
+        }
+        offset = bytes.end;
+        if let Some(memory_offset) = memory_offset {
+            segments.push(DataSegment {
+                memory_offset,
+                bytes,
+            });
+        }
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wasm::sections::tests::module;
+
+    use super::*;
+
+    #[test]
+    fn module_without_data_section() {
+        let code = module(&[(5, vec![0x01, 0x00, 0x01])]);
+        assert_eq!(active_data_segments(&code).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn active_and_passive_segments() {
+        let data = vec![
+            0x03, // segments count
+            0x00, WASM_I32_CONST, 0x80, 0x01, WASM_END, 0x02, 0xaa, 0xbb, // active at 128
+            0x01, 0x01, 0xcc, // passive
+            0x02, 0x00, WASM_I32_CONST, 0x04, WASM_END, 0x01, 0xdd, // active memory 0 at 4
+        ];
+        let code = module(&[(5, vec![0x01, 0x00, 0x01]), (WASM_DATA_SECTION_ID, data)]);
+        let segments = active_data_segments(&code).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].memory_offset, 128);
+        assert_eq!(code[segments[0].bytes.clone()], [0xaa, 0xbb]);
+        assert_eq!(segments[1].memory_offset, 4);
+        assert_eq!(code[segments[1].bytes.clone()], [0xdd]);
+    }
+
+    #[test]
+    fn global_get_offset_is_rejected() {
+        let data = vec![0x01, 0x00, 0x23, 0x00, WASM_END, 0x01, 0xaa];
+        let code = module(&[(WASM_DATA_SECTION_ID, data)]);
+        assert!(active_data_segments(&code).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Active element segments of a WASM module, which initialize the tables at the start of a
+//! transaction.
+
+use crate::Error;
+
+use super::sections::{read_leb128, section_body, WASM_ELEM_SECTION_ID};
+
+const WASM_I32_CONST: u8 = 0x41;
+const WASM_END: u8 = 0x0b;
+const WASM_ELEM_KIND_FUNCREF: u8 = 0x00;
+
+/// Active element segment: the functions `function_indexes` are stored in the table
+/// `table_index` starting at the element `table_offset`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ElemSegment {
+    /// destination table
+    pub table_index: u32,
+    /// first destination element
+    pub table_offset: u32,
+    /// referenced functions
+    pub function_indexes: Vec<u32>,
+}
+
+fn read_function_indexes(code: &[u8], offset: &mut usize) -> Result<Vec<u32>, Error> {
+    let count = read_leb128(code, offset, false)?;
+    (0..count)
+        .map(|_| read_leb128(code, offset, false).map(|index| index as u32))
+        .collect()
+}
+
+/// Active element segments of the module `code`, in section order. Passive and declarative
+/// segments are skipped, offsets must be a single `i32.const` and elements function indexes.
+pub(crate) fn active_elem_segments(code: &[u8]) -> Result<Vec<ElemSegment>, Error> {
+    let body = match section_body(code, WASM_ELEM_SECTION_ID)? {
+        Some(body) => body,
+        None => return Ok(vec![]),
+    };
+    let mut offset = body.start;
+    let segments_count = read_leb128(code, &mut offset, false)?;
+    let mut segments = Vec::with_capacity(segments_count as usize);
+    for _ in 0..segments_count {
+        let flags = read_leb128(code, &mut offset, false)?;
+        match flags {
+            0 | 2 => {
+                let table_index = if flags == 2 {
+                    read_leb128(code, &mut offset, false)? as u32
+                } else {
+                    0
+                };
+                if code.get(offset) != Some(&WASM_I32_CONST) {
+                    return Err(Error::InternalError("elem segment offset is not i32.const"));
+                }
+                offset += 1;
+                let table_offset = read_leb128(code, &mut offset, true)? as u32;
+                if code.get(offset) != Some(&WASM_END) {
+                    return Err(Error::InternalError("elem segment offset is not i32.const"));
+                }
+                offset += 1;
+                if flags == 2 {
+                    if code.get(offset) != Some(&WASM_ELEM_KIND_FUNCREF) {
+                        return Err(Error::InternalError("invalid elem segment kind"));
+                    }
+                    offset += 1;
+                }
+                segments.push(ElemSegment {
+                    table_index,
+                    table_offset,
+                    function_indexes: read_function_indexes(code, &mut offset)?,
+                });
+            }
+            1 | 3 => {
+                if code.get(offset) != Some(&WASM_ELEM_KIND_FUNCREF) {
+                    return Err(Error::InternalError("invalid elem segment kind"));
+                }
+                offset += 1;
+                read_function_indexes(code, &mut offset)?;
+            }
+            _ => return Err(Error::InternalError("unsupported elem segment flags")),
+        }
+        if offset > body.end {
+            return Err(Error::InternalError("elem segment out of section bounds"));
+        }
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wasm::sections::tests::module;
+
+    use super::*;
+
+    #[test]
+    fn module_without_elem_section() {
+        let code = module(&[(4, vec![0x01, 0x70, 0x00, 0x02])]);
+        assert_eq!(active_elem_segments(&code).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn active_and_passive_segments() {
+        let elems = vec![
+            0x03, // segments count
+            0x00, WASM_I32_CONST, 0x01, WASM_END, 0x02, 0x05, 0x07, // active table 0 at 1
+            0x01, WASM_ELEM_KIND_FUNCREF, 0x01, 0x03, // passive
+            0x02, 0x01, WASM_I32_CONST, 0x00, WASM_END, WASM_ELEM_KIND_FUNCREF, 0x01, 0x02,
+        ];
+        let code = module(&[(WASM_ELEM_SECTION_ID, elems)]);
+        assert_eq!(
+            active_elem_segments(&code).unwrap(),
+            vec![
+                ElemSegment { table_index: 0, table_offset: 1, function_indexes: vec![5, 7] },
+                ElemSegment { table_index: 1, table_offset: 0, function_indexes: vec![2] },
+            ]
+        );
+    }
+
+    #[test]
+    fn expression_elements_are_rejected() {
+        let elems = vec![0x01, 0x04, WASM_I32_CONST, 0x00, WASM_END, 0x00];
+        let code = module(&[(WASM_ELEM_SECTION_ID, elems)]);
+        assert!(active_elem_segments(&code).is_err());
+    }
+}
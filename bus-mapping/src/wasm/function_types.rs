@@ -0,0 +1,159 @@
+//! Types of the functions of a WASM module.
+
+use crate::Error;
+
+use super::sections::{
+    read_leb128, section_body, WASM_FUNCTION_SECTION_ID, WASM_IMPORT_SECTION_ID,
+    WASM_TYPE_SECTION_ID,
+};
+
+const WASM_FUNC_TYPE: u8 = 0x60;
+const WASM_IMPORT_KIND_FUNC: u8 = 0;
+const WASM_IMPORT_KIND_TABLE: u8 = 1;
+const WASM_IMPORT_KIND_MEMORY: u8 = 2;
+const WASM_IMPORT_KIND_GLOBAL: u8 = 3;
+
+/// Arity of a function type, results can be more than one with multi-value
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct FunctionType {
+    /// number of params
+    pub params: usize,
+    /// number of results
+    pub results: usize,
+}
+
+fn skip_limits(code: &[u8], offset: &mut usize) -> Result<(), Error> {
+    let flags = read_leb128(code, offset, false)?;
+    read_leb128(code, offset, false)?;
+    if flags & 1 != 0 {
+        read_leb128(code, offset, false)?;
+    }
+    Ok(())
+}
+
+/// Type indexes of the imported functions, which come first in the function index space
+fn imported_function_types(code: &[u8]) -> Result<Vec<u32>, Error> {
+    let body = match section_body(code, WASM_IMPORT_SECTION_ID)? {
+        Some(body) => body,
+        None => return Ok(vec![]),
+    };
+    let mut offset = body.start;
+    let mut type_indexes = vec![];
+    for _ in 0..read_leb128(code, &mut offset, false)? {
+        // module and field names
+        for _ in 0..2 {
+            let len = read_leb128(code, &mut offset, false)? as usize;
+            offset += len;
+        }
+        let kind = *code
+            .get(offset)
+            .ok_or(Error::InternalError("import out of code bounds"))?;
+        offset += 1;
+        match kind {
+            WASM_IMPORT_KIND_FUNC => {
+                type_indexes.push(read_leb128(code, &mut offset, false)? as u32);
+            }
+            WASM_IMPORT_KIND_TABLE => {
+                // reference type
+                offset += 1;
+                skip_limits(code, &mut offset)?;
+            }
+            WASM_IMPORT_KIND_MEMORY => skip_limits(code, &mut offset)?,
+            // value type and mutability
+            WASM_IMPORT_KIND_GLOBAL => offset += 2,
+            _ => return Err(Error::InternalError("invalid import kind")),
+        }
+    }
+    Ok(type_indexes)
+}
+
+/// Type of the function `function_index` of the module `code`
+pub(crate) fn function_type(code: &[u8], function_index: u32) -> Result<FunctionType, Error> {
+    let imported = imported_function_types(code)?;
+    let type_index = match imported.get(function_index as usize) {
+        Some(type_index) => *type_index,
+        None => {
+            let body = section_body(code, WASM_FUNCTION_SECTION_ID)?
+                .ok_or(Error::InternalError("function section is missing"))?;
+            let mut offset = body.start;
+            let index = function_index as usize - imported.len();
+            if index as i64 >= read_leb128(code, &mut offset, false)? {
+                return Err(Error::InternalError("function index out of bounds"));
+            }
+            for _ in 0..index {
+                read_leb128(code, &mut offset, false)?;
+            }
+            read_leb128(code, &mut offset, false)? as u32
+        }
+    };
+
+    let body = section_body(code, WASM_TYPE_SECTION_ID)?
+        .ok_or(Error::InternalError("type section is missing"))?;
+    let mut offset = body.start;
+    if type_index as i64 >= read_leb128(code, &mut offset, false)? {
+        return Err(Error::InternalError("type index out of bounds"));
+    }
+    for index in 0..=type_index {
+        if code.get(offset) != Some(&WASM_FUNC_TYPE) {
+            return Err(Error::InternalError("invalid function type"));
+        }
+        offset += 1;
+        let params = read_leb128(code, &mut offset, false)? as usize;
+        offset += params;
+        let results = read_leb128(code, &mut offset, false)? as usize;
+        offset += results;
+        if index == type_index {
+            return Ok(FunctionType { params, results });
+        }
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wasm::sections::tests::module;
+
+    use super::*;
+
+    const I32: u8 = 0x7f;
+    const I64: u8 = 0x7e;
+
+    fn types() -> (u8, Vec<u8>) {
+        (
+            WASM_TYPE_SECTION_ID,
+            vec![
+                0x02, // types count
+                WASM_FUNC_TYPE, 0x00, 0x00, // [] -> []
+                WASM_FUNC_TYPE, 0x01, I32, 0x03, I32, I64, I32, // [i32] -> [i32, i64, i32]
+            ],
+        )
+    }
+
+    #[test]
+    fn multi_value_results() {
+        let code = module(&[types(), (WASM_FUNCTION_SECTION_ID, vec![0x02, 0x01, 0x00])]);
+        assert_eq!(
+            function_type(&code, 0).unwrap(),
+            FunctionType { params: 1, results: 3 }
+        );
+        assert_eq!(function_type(&code, 1).unwrap(), FunctionType::default());
+        assert!(function_type(&code, 2).is_err());
+    }
+
+    #[test]
+    fn imported_functions_come_first() {
+        let imports = vec![
+            0x03, // imports count
+            0x01, b'e', 0x01, b'm', WASM_IMPORT_KIND_MEMORY, 0x00, 0x01,
+            0x01, b'e', 0x01, b'f', WASM_IMPORT_KIND_FUNC, 0x01,
+            0x01, b'e', 0x01, b'g', WASM_IMPORT_KIND_GLOBAL, I32, 0x00,
+        ];
+        let code = module(&[
+            types(),
+            (WASM_IMPORT_SECTION_ID, imports),
+            (WASM_FUNCTION_SECTION_ID, vec![0x01, 0x00]),
+        ]);
+        assert_eq!(function_type(&code, 0).unwrap().results, 3);
+        assert_eq!(function_type(&code, 1).unwrap().results, 0);
+    }
+}
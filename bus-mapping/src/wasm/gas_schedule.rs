@@ -0,0 +1,195 @@
+//! Constant gas costs of the WASM opcodes.
+
+use eth_types::evm_types::{GasCost, OpcodeId};
+
+/// Constant gas cost of every WASM opcode, grouped by the opcode families the
+/// execution gadgets are built around. The same schedule must be used by the
+/// tracer, the opcode handlers and the EVM circuit, otherwise the `gas_left`
+/// transitions of the steps do not match the trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WasmGasSchedule {
+    /// `unreachable`, `nop`, blocks, branches and `return`
+    pub control: GasCost,
+    /// `call`, `call_indirect` and their tail-call variants
+    pub call: GasCost,
+    /// `drop` and `select`
+    pub parametric: GasCost,
+    /// local and global get/set/tee
+    pub variable: GasCost,
+    /// loads, stores, `memory.size` and `memory.grow`
+    pub memory: GasCost,
+    /// constants, tests, comparisons, unary, binary and conversion opcodes
+    pub numeric: GasCost,
+}
+
+/// Schedule used by the opcode handlers and the EVM circuit gadgets.
+pub const WASM_GAS_SCHEDULE: WasmGasSchedule = WasmGasSchedule::uniform(GasCost::ZERO);
+
+impl Default for WasmGasSchedule {
+    fn default() -> Self {
+        WASM_GAS_SCHEDULE
+    }
+}
+
+impl WasmGasSchedule {
+    /// Schedule charging `cost` for every WASM opcode
+    pub const fn uniform(cost: GasCost) -> Self {
+        Self {
+            control: cost,
+            call: cost,
+            parametric: cost,
+            variable: cost,
+            memory: cost,
+            numeric: cost,
+        }
+    }
+
+    /// Constant gas cost of `opcode`, `None` if it is not a WASM opcode
+    pub fn cost(&self, opcode: OpcodeId) -> Option<GasCost> {
+        let cost = match opcode {
+            OpcodeId::Unreachable
+            | OpcodeId::Nop
+            | OpcodeId::Block
+            | OpcodeId::Loop
+            | OpcodeId::If
+            | OpcodeId::Else
+            | OpcodeId::End
+            | OpcodeId::Br
+            | OpcodeId::BrIf
+            | OpcodeId::BrTable
+            | OpcodeId::Return => self.control,
+            OpcodeId::Call
+            | OpcodeId::CallIndirect
+            | OpcodeId::ReturnCall
+            | OpcodeId::ReturnCallIndirect => self.call,
+            OpcodeId::Drop | OpcodeId::Select => self.parametric,
+            OpcodeId::GetLocal
+            | OpcodeId::SetLocal
+            | OpcodeId::TeeLocal
+            | OpcodeId::GetGlobal
+            | OpcodeId::SetGlobal => self.variable,
+            OpcodeId::I32Load
+            | OpcodeId::I64Load
+            | OpcodeId::F32Load
+            | OpcodeId::F64Load
+            | OpcodeId::I32Load8S
+            | OpcodeId::I32Load8U
+            | OpcodeId::I32Load16S
+            | OpcodeId::I32Load16U
+            | OpcodeId::I64Load8S
+            | OpcodeId::I64Load8U
+            | OpcodeId::I64Load16S
+            | OpcodeId::I64Load16U
+            | OpcodeId::I64Load32S
+            | OpcodeId::I64Load32U
+            | OpcodeId::I32Store
+            | OpcodeId::I64Store
+            | OpcodeId::F32Store
+            | OpcodeId::F64Store
+            | OpcodeId::I32Store8
+            | OpcodeId::I32Store16
+            | OpcodeId::I64Store8
+            | OpcodeId::I64Store16
+            | OpcodeId::I64Store32
+            | OpcodeId::CurrentMemory
+            | OpcodeId::GrowMemory => self.memory,
+            _ if Self::is_numeric(opcode) => self.numeric,
+            _ => return None,
+        };
+        Some(cost)
+    }
+
+    /// Numeric opcodes take the contiguous `i32.const..=f64.reinterpret_i64`
+    /// range of the WASM encoding, EVM opcodes sharing these bytes are skipped
+    fn is_numeric(opcode: OpcodeId) -> bool {
+        let byte = opcode.as_u8();
+        (OpcodeId::I32Const.as_u8()..=OpcodeId::F64ReinterpretI64.as_u8()).contains(&byte)
+            && OpcodeId::from(byte) == opcode
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::TestContext;
+
+    use crate::{circuit_input_builder::ExecState, mock::BlockData};
+
+    use super::*;
+
+    #[test]
+    fn default_schedule_matches_circuit() {
+        for opcode in [
+            OpcodeId::I32Const,
+            OpcodeId::I64Add,
+            OpcodeId::I32Eqz,
+            OpcodeId::I32WrapI64,
+            OpcodeId::Drop,
+            OpcodeId::Select,
+            OpcodeId::GetLocal,
+            OpcodeId::SetGlobal,
+            OpcodeId::I32Load,
+            OpcodeId::I64Store8,
+            OpcodeId::Call,
+            OpcodeId::BrIf,
+        ] {
+            assert_eq!(WasmGasSchedule::default().cost(opcode), Some(GasCost::ZERO));
+        }
+    }
+
+    #[test]
+    fn evm_opcodes_are_not_scheduled() {
+        for opcode in [OpcodeId::SHA3, OpcodeId::CALL, OpcodeId::ADD, OpcodeId::PUSH1] {
+            assert_eq!(WASM_GAS_SCHEDULE.cost(opcode), None);
+        }
+    }
+
+    #[test]
+    fn families_are_configurable() {
+        let schedule = WasmGasSchedule {
+            numeric: GasCost::QUICK,
+            memory: GasCost::FASTEST,
+            ..WasmGasSchedule::uniform(GasCost(1))
+        };
+        assert_eq!(schedule.cost(OpcodeId::I32Add), Some(GasCost::QUICK));
+        assert_eq!(schedule.cost(OpcodeId::F64ReinterpretI64), Some(GasCost::QUICK));
+        assert_eq!(schedule.cost(OpcodeId::GrowMemory), Some(GasCost::FASTEST));
+        assert_eq!(schedule.cost(OpcodeId::I32Store), Some(GasCost::FASTEST));
+        assert_eq!(schedule.cost(OpcodeId::Return), Some(GasCost(1)));
+        assert_eq!(schedule.cost(OpcodeId::TeeLocal), Some(GasCost(1)));
+    }
+
+    #[test]
+    fn trace_gas_matches_schedule() {
+        let code = bytecode! {
+            I32Const[7]
+            I32Const[5]
+            I32Add
+            I32Eqz
+            I32Const[1]
+            I32Const[2]
+            I32Const[0]
+            Select
+            Drop
+            Drop
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let mut checked = 0;
+        for step in builder.block.txs()[0].steps() {
+            if let ExecState::Op(opcode) = step.exec_state {
+                if let Some(gas_cost) = WASM_GAS_SCHEDULE.cost(opcode) {
+                    assert_eq!(step.gas_cost, gas_cost, "gas cost of {:?}", opcode);
+                    checked += 1;
+                }
+            }
+        }
+        assert!(checked >= 10);
+    }
+}
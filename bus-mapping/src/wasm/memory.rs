@@ -0,0 +1,137 @@
+//! Wasm linear memory viewed as 8-byte cells, the unit wasm load/store gadgets work with
+
+use eth_types::evm_types::Memory;
+
+/// Size in bytes of a wasm memory cell
+pub const WASM_MEMORY_CELL_SIZE: u64 = 8;
+
+/// Cells touched by a wasm memory access, an access of at most [`WASM_MEMORY_CELL_SIZE`] bytes
+/// spans one or two cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WasmMemoryAccess {
+    /// index of the cell holding the first accessed byte
+    pub start_cell_index: u64,
+    /// offset of the first accessed byte inside its cell
+    pub start_inner_offset: u64,
+    /// index of the cell holding the last accessed byte
+    pub end_cell_index: u64,
+    /// offset of the last accessed byte inside its cell
+    pub end_inner_offset: u64,
+}
+
+impl WasmMemoryAccess {
+    /// Access of `len` bytes (`1..=8`) starting at `address`
+    pub fn new(address: u64, len: u64) -> Self {
+        debug_assert!((1..=WASM_MEMORY_CELL_SIZE).contains(&len));
+        let end_address = address + len - 1;
+        Self {
+            start_cell_index: address / WASM_MEMORY_CELL_SIZE,
+            start_inner_offset: address % WASM_MEMORY_CELL_SIZE,
+            end_cell_index: end_address / WASM_MEMORY_CELL_SIZE,
+            end_inner_offset: end_address % WASM_MEMORY_CELL_SIZE,
+        }
+    }
+
+    /// Indexes of the touched cells in ascending order
+    pub fn cell_indexes(&self) -> Vec<u64> {
+        (self.start_cell_index..=self.end_cell_index).collect()
+    }
+}
+
+/// Linear memory of a wasm call as little-endian 8-byte cells, cells past the end read as zero
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WasmMemory(pub Vec<u64>);
+
+impl WasmMemory {
+    /// Value of the cell `index`
+    pub fn cell(&self, index: u64) -> u64 {
+        self.0.get(index as usize).copied().unwrap_or_default()
+    }
+
+    /// Little-endian value of `len` bytes starting at `address`
+    pub fn read(&self, address: u64, len: u64) -> u64 {
+        (0..len).fold(0, |acc, i| acc | (self.read_u8(address + i) as u64) << (8 * i))
+    }
+
+    /// Writes the `len` low bytes of `value` (little-endian) starting at `address`
+    pub fn write(&mut self, address: u64, len: u64, value: u64) {
+        for i in 0..len {
+            self.write_u8(address + i, (value >> (8 * i)) as u8);
+        }
+    }
+
+    /// Bytes of the memory, its length is a multiple of [`WASM_MEMORY_CELL_SIZE`]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|cell| cell.to_le_bytes()).collect()
+    }
+
+    fn read_u8(&self, address: u64) -> u8 {
+        (self.cell(address / WASM_MEMORY_CELL_SIZE) >> (8 * (address % WASM_MEMORY_CELL_SIZE))) as u8
+    }
+
+    fn write_u8(&mut self, address: u64, byte: u8) {
+        let index = (address / WASM_MEMORY_CELL_SIZE) as usize;
+        if self.0.len() <= index {
+            self.0.resize(index + 1, 0);
+        }
+        let shift = 8 * (address % WASM_MEMORY_CELL_SIZE);
+        self.0[index] = self.0[index] & !(0xff << shift) | (byte as u64) << shift;
+    }
+}
+
+impl From<&Memory> for WasmMemory {
+    fn from(memory: &Memory) -> Self {
+        Self(
+            memory
+                .0
+                .chunks(WASM_MEMORY_CELL_SIZE as usize)
+                .map(|chunk| {
+                    let mut cell = [0u8; WASM_MEMORY_CELL_SIZE as usize];
+                    cell[..chunk.len()].copy_from_slice(chunk);
+                    u64::from_le_bytes(cell)
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod wasm_memory_tests {
+    use eth_types::evm_types::Memory;
+
+    use super::*;
+
+    #[test]
+    fn access_within_one_cell() {
+        let access = WasmMemoryAccess::new(9, 4);
+        assert_eq!((access.start_cell_index, access.start_inner_offset), (1, 1));
+        assert_eq!((access.end_cell_index, access.end_inner_offset), (1, 4));
+        assert_eq!(access.cell_indexes(), vec![1]);
+    }
+
+    #[test]
+    fn access_across_two_cells() {
+        let access = WasmMemoryAccess::new(6, 8);
+        assert_eq!((access.start_cell_index, access.start_inner_offset), (0, 6));
+        assert_eq!((access.end_cell_index, access.end_inner_offset), (1, 5));
+        assert_eq!(access.cell_indexes(), vec![0, 1]);
+    }
+
+    #[test]
+    fn read_write_match_byte_memory() {
+        let bytes: Vec<u8> = (1..=20).collect();
+        let mut memory = WasmMemory::from(&Memory::from_bytes_with_offset(bytes.clone(), 0));
+        assert_eq!(memory.0.len(), 3);
+        assert_eq!(memory.read(6, 4), u32::from_le_bytes([7, 8, 9, 10]) as u64);
+
+        memory.write(14, 4, 0xaabbccdd);
+        assert_eq!(memory.read(14, 4), 0xaabbccdd);
+        assert_eq!(memory.read(13, 1), 14);
+        assert_eq!(memory.read(18, 2), u16::from_le_bytes([19, 20]) as u64);
+        assert_eq!(memory.to_bytes()[..13], bytes[..13]);
+
+        memory.write(30, 2, 0xffff);
+        assert_eq!(memory.0.len(), 4);
+        assert_eq!(memory.cell(3), 0xffff << 48);
+    }
+}
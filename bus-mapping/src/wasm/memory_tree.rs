@@ -0,0 +1,220 @@
+//! Sparse binary Merkle tree over the 8-byte cells of a wasm linear memory. An opening or an
+//! update witnesses only the touched cell and its path instead of every memory byte. Wasm
+//! loads and stores do not use it yet: their memory ops are still pushed byte by byte.
+
+use std::collections::HashMap;
+
+use eth_types::Field;
+use poseidon_circuit::hash::MessageHashable;
+
+use super::memory::WasmMemory;
+
+/// Levels above the leaves, enough for the 2^29 cells of a 4GiB wasm32 memory
+pub const WASM_MEMORY_TREE_DEPTH: usize = 29;
+
+/// Poseidon capacity of node hashes, code hashes use multiples of the domain spec
+pub const WASM_MEMORY_TREE_NODE_DOMAIN: u128 = 1;
+
+/// Hash of the node whose children are `left` and `right`
+pub fn hash_memory_tree_node<F: Field>(left: F, right: F) -> F {
+    F::hash_msg(&[left, right], Some(WASM_MEMORY_TREE_NODE_DOMAIN))
+}
+
+/// Opening of a memory cell against the root, a write also moves the root to `new_root`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmMemoryTreeAccess<F> {
+    /// index of the opened cell
+    pub cell_index: u64,
+    /// whether the cell is updated
+    pub is_write: bool,
+    /// value of the cell before the access
+    pub old_value: u64,
+    /// value of the cell after the access, `old_value` for reads
+    pub new_value: u64,
+    /// siblings of the nodes on the path of the cell, leaf level first
+    pub siblings: Vec<F>,
+    /// root before the access
+    pub old_root: F,
+    /// root after the access
+    pub new_root: F,
+}
+
+impl<F: Field> WasmMemoryTreeAccess<F> {
+    /// Nodes on the path of the cell holding `value`, from the leaf to the root
+    pub fn path(&self, value: u64) -> Vec<F> {
+        let mut node = F::from(value);
+        let mut path = vec![node];
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            node = if (self.cell_index >> level) & 1 == 0 {
+                hash_memory_tree_node(node, *sibling)
+            } else {
+                hash_memory_tree_node(*sibling, node)
+            };
+            path.push(node);
+        }
+        path
+    }
+}
+
+/// Memory tree of a wasm call, nodes missing from the tree are roots of all-zero subtrees
+#[derive(Debug, Clone)]
+pub struct WasmMemoryTree<F> {
+    /// non-zero cells by index
+    cells: HashMap<u64, u64>,
+    /// nodes of the levels above the leaves by index, `nodes[0]` is unused
+    nodes: Vec<HashMap<u64, F>>,
+    /// root of an all-zero subtree of every level, leaf level first
+    empty_nodes: Vec<F>,
+}
+
+impl<F: Field> Default for WasmMemoryTree<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Field> WasmMemoryTree<F> {
+    /// Tree of an all-zero memory
+    pub fn new() -> Self {
+        let mut empty_nodes = vec![F::zero()];
+        for level in 0..WASM_MEMORY_TREE_DEPTH {
+            empty_nodes.push(hash_memory_tree_node(empty_nodes[level], empty_nodes[level]));
+        }
+        Self {
+            cells: HashMap::new(),
+            nodes: vec![HashMap::new(); WASM_MEMORY_TREE_DEPTH + 1],
+            empty_nodes,
+        }
+    }
+
+    /// Tree of the cells of `memory`
+    pub fn from_memory(memory: &WasmMemory) -> Self {
+        let mut tree = Self::new();
+        for (cell_index, cell) in memory.0.iter().enumerate() {
+            if *cell != 0 {
+                tree.set(cell_index as u64, *cell);
+            }
+        }
+        tree
+    }
+
+    /// Root of the tree
+    pub fn root(&self) -> F {
+        self.node(WASM_MEMORY_TREE_DEPTH, 0)
+    }
+
+    /// Value of the cell `cell_index`
+    pub fn cell(&self, cell_index: u64) -> u64 {
+        self.cells.get(&cell_index).copied().unwrap_or_default()
+    }
+
+    /// Opens the cell `cell_index`
+    pub fn read_cell(&self, cell_index: u64) -> WasmMemoryTreeAccess<F> {
+        let value = self.cell(cell_index);
+        let root = self.root();
+        WasmMemoryTreeAccess {
+            cell_index,
+            is_write: false,
+            old_value: value,
+            new_value: value,
+            siblings: self.siblings(cell_index),
+            old_root: root,
+            new_root: root,
+        }
+    }
+
+    /// Opens the cell `cell_index` and sets it to `value`
+    pub fn write_cell(&mut self, cell_index: u64, value: u64) -> WasmMemoryTreeAccess<F> {
+        let read = self.read_cell(cell_index);
+        self.set(cell_index, value);
+        WasmMemoryTreeAccess {
+            is_write: true,
+            new_value: value,
+            new_root: self.root(),
+            ..read
+        }
+    }
+
+    fn node(&self, level: usize, index: u64) -> F {
+        if level == 0 {
+            return F::from(self.cell(index));
+        }
+        self.nodes[level]
+            .get(&index)
+            .copied()
+            .unwrap_or(self.empty_nodes[level])
+    }
+
+    fn siblings(&self, cell_index: u64) -> Vec<F> {
+        debug_assert!(cell_index < 1 << WASM_MEMORY_TREE_DEPTH);
+        (0..WASM_MEMORY_TREE_DEPTH)
+            .map(|level| self.node(level, (cell_index >> level) ^ 1))
+            .collect()
+    }
+
+    fn set(&mut self, cell_index: u64, value: u64) {
+        debug_assert!(cell_index < 1 << WASM_MEMORY_TREE_DEPTH);
+        self.cells.insert(cell_index, value);
+        for level in 1..=WASM_MEMORY_TREE_DEPTH {
+            let index = cell_index >> level;
+            let node = hash_memory_tree_node(
+                self.node(level - 1, index << 1),
+                self.node(level - 1, index << 1 | 1),
+            );
+            self.nodes[level].insert(index, node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod wasm_memory_tree_tests {
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    use super::*;
+
+    #[test]
+    fn empty_tree_opens_zero_cells() {
+        let tree = WasmMemoryTree::<Fr>::new();
+        let root = (0..WASM_MEMORY_TREE_DEPTH)
+            .fold(Fr::zero(), |node, _| hash_memory_tree_node(node, node));
+        assert_eq!(tree.root(), root);
+
+        let access = tree.read_cell(1000);
+        assert_eq!((access.old_value, access.new_value), (0, 0));
+        assert_eq!(access.siblings.len(), WASM_MEMORY_TREE_DEPTH);
+        assert_eq!(access.path(0).last(), Some(&root));
+    }
+
+    #[test]
+    fn write_moves_the_root() {
+        let mut tree = WasmMemoryTree::<Fr>::new();
+        tree.write_cell(3, 7);
+        let access = tree.write_cell(2, u64::MAX);
+        assert!(access.is_write);
+        assert_eq!((access.old_value, access.new_value), (0, u64::MAX));
+        assert_ne!(access.old_root, access.new_root);
+        assert_eq!(access.path(0).last(), Some(&access.old_root));
+        assert_eq!(access.path(u64::MAX).last(), Some(&access.new_root));
+        assert_eq!(tree.root(), access.new_root);
+        assert_eq!(access.siblings[0], Fr::from(7));
+
+        let read = tree.read_cell(3);
+        assert_eq!((read.old_value, read.new_value), (7, 7));
+        assert_eq!(read.old_root, read.new_root);
+        assert_eq!(read.path(7).last(), Some(&tree.root()));
+    }
+
+    #[test]
+    fn tree_of_memory_matches_writes() {
+        let memory = WasmMemory(vec![1, 0, 3]);
+        let mut tree = WasmMemoryTree::<Fr>::new();
+        tree.write_cell(0, 1);
+        tree.write_cell(2, 3);
+        assert_eq!(WasmMemoryTree::from_memory(&memory).root(), tree.root());
+
+        let last_cell = (1 << WASM_MEMORY_TREE_DEPTH) - 1;
+        let access = tree.write_cell(last_cell, 5);
+        assert_eq!(access.path(5).last(), Some(&tree.root()));
+        assert_eq!(tree.cell(last_cell), 5);
+    }
+}
@@ -13,17 +13,20 @@ use callop::CallOpcode;
 use callvalue::Callvalue;
 use codecopy::Codecopy;
 use codesize::Codesize;
+use error_indirect_call_mismatch::ErrorIndirectCallMismatch;
 use error_invalid_jump::InvalidJump;
 use error_oog_call::OOGCall;
 use error_oog_log::ErrorOOGLog;
 use error_oog_sload_sstore::OOGSloadSstore;
 use error_return_data_outofbound::ErrorReturnDataOutOfBound;
+use error_stack_height_exceeded::ErrorStackHeightExceeded;
 use error_write_protection::ErrorWriteProtection;
-use eth_types::{evm_types::{GasCost, MAX_REFUND_QUOTIENT_OF_GAS_USED}, evm_unimplemented, GethExecStep, GethExecTrace, StackWord, ToAddress, ToWord, Word};
-use eth_types::evm_types::MemoryAddress;
+use eth_types::{evm_types::{GasCost, MAX_REFUND_QUOTIENT_OF_GAS_USED}, evm_unimplemented, Bytecode, GethExecStep, GethExecTrace, StackWord, ToAddress, ToWord, Word, H256};
+use eth_types::evm_types::MAX_WASM_STACK_HEIGHT;
 use extcodecopy::Extcodecopy;
 use extcodesize::Extcodesize;
 use gasprice::GasPrice;
+use mcopy::Mcopy;
 use number::Number;
 use origin::Origin;
 use return_revert::ReturnRevert;
@@ -36,11 +39,18 @@ use stop::Stop;
 use wasm_break::WasmBreakOpcode;
 use wasm_call::WasmCallOpcode;
 use wasm_global::WasmGlobalOpcode;
+use wasm_load_store::{WasmLoadOpcode, WasmStoreOpcode};
 use wasm_local::WasmLocalOpcode;
+use wasm_memory::WasmMemoryOpcode;
+use wasm_parametric::{WasmDropOpcode, WasmSelectOpcode};
+#[cfg(feature = "tail-call")]
+use wasm_return_call::WasmReturnCallOpcode;
 
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
-    error::{ExecError, OogError},
+    circuit_input_builder::{
+        CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, FunctionFrame, NumberOrHash,
+    },
+    error::{ExecError, OogError, WasmTrapKind},
     Error,
     evm::OpcodeId,
     operation::{
@@ -54,6 +64,9 @@ use crate::evm::opcodes::extcodehash::Extcodehash;
 use crate::precompile::is_precompiled;
 use crate::state_db::CodeDB;
 use crate::util::CHECK_MEM_STRICT;
+use crate::wasm::data_segments::active_data_segments;
+use crate::wasm::elem_segments::active_elem_segments;
+use crate::wasm::gas_schedule::WASM_GAS_SCHEDULE;
 use crate::wasm::opcodes::error_codestore::ErrorCodeStore;
 use crate::wasm::opcodes::error_invalid_creation_code::ErrorCreationCode;
 use crate::wasm::opcodes::error_oog_account_access::ErrorOOGAccountAccess;
@@ -64,6 +77,8 @@ use crate::wasm::opcodes::logs::Log;
 use crate::wasm::opcodes::sha3::Sha3;
 use crate::wasm::opcodes::sload::Sload;
 use crate::wasm::opcodes::sstore::Sstore;
+use crate::wasm::opcodes::tload::Tload;
+use crate::wasm::opcodes::tstore::Tstore;
 
 #[cfg(any(feature = "test", test))]
 pub use self::sha3::sha3_tests::{gen_sha3_code, MemoryKind};
@@ -84,6 +99,7 @@ mod extcodehash;
 mod extcodesize;
 mod gasprice;
 mod logs;
+mod mcopy;
 mod number;
 mod origin;
 mod precompiles;
@@ -97,9 +113,12 @@ mod sstore;
 mod stackonlyop;
 mod stacktomemoryop;
 mod stop;
+mod tload;
+mod tstore;
 
 mod error_codestore;
 mod error_contract_address_collision;
+mod error_indirect_call_mismatch;
 mod error_invalid_creation_code;
 mod error_invalid_jump;
 mod error_oog_account_access;
@@ -110,6 +129,7 @@ mod error_oog_memory_copy;
 mod error_oog_sload_sstore;
 mod error_precompile_failed;
 mod error_return_data_outofbound;
+mod error_stack_height_exceeded;
 mod error_write_protection;
 
 #[cfg(test)]
@@ -119,8 +139,13 @@ pub use callop::tests::PrecompileCallArgs;
 
 mod wasm_call;
 mod wasm_global;
+mod wasm_load_store;
 mod wasm_local;
+mod wasm_memory;
+mod wasm_parametric;
 mod wasm_break;
+#[cfg(feature = "tail-call")]
+mod wasm_return_call;
 
 /// Generic opcode trait which defines the logic of the
 /// [`Operation`](crate::operation::Operation) that should be generated for one
@@ -158,7 +183,7 @@ type FnGenAssociatedOps = fn(
 fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
     match opcode_id {
         // WASM opcodes
-        OpcodeId::Unreachable => Stop::gen_associated_ops,
+        // OpcodeId::Unreachable always traps, see `ExecError::WasmTrap`
         // OpcodeId::Nop => Dummy::gen_associated_ops,
         // OpcodeId::Block => Dummy::gen_associated_ops,
         // OpcodeId::Loop => Dummy::gen_associated_ops,
@@ -201,8 +226,6 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         // OpcodeId::I64Store8 => Dummy::gen_associated_ops,
         // OpcodeId::I64Store16 => Dummy::gen_associated_ops,
         // OpcodeId::I64Store32 => Dummy::gen_associated_ops,
-        // OpcodeId::CurrentMemory => Dummy::gen_associated_ops,
-        // OpcodeId::GrowMemory => Dummy::gen_associated_ops,
         OpcodeId::I32Const |
         OpcodeId::I64Const => StackOnlyOpcode::<0, 1>::gen_associated_ops,
         // WASM binary opcodes
@@ -276,7 +299,14 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::I64Load16S |
         OpcodeId::I64Load16U |
         OpcodeId::I64Load32S |
-        OpcodeId::I64Load32U => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        OpcodeId::I64Load32U => WasmLoadOpcode::gen_associated_ops,
+        OpcodeId::I32Store |
+        OpcodeId::I32Store8 |
+        OpcodeId::I32Store16 |
+        OpcodeId::I64Store |
+        OpcodeId::I64Store8 |
+        OpcodeId::I64Store16 |
+        OpcodeId::I64Store32 => WasmStoreOpcode::gen_associated_ops,
 
         // WASM unary opcodes
         OpcodeId::I64ExtendUI32 |
@@ -296,9 +326,16 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::SetLocal |
         OpcodeId::GetLocal |
         OpcodeId::TeeLocal => WasmLocalOpcode::gen_associated_ops,
+        // WASM memory opcodes
+        OpcodeId::CurrentMemory |
+        OpcodeId::GrowMemory => WasmMemoryOpcode::gen_associated_ops,
         // call opcodes
         OpcodeId::Call |
         OpcodeId::CallIndirect => WasmCallOpcode::gen_associated_ops,
+        // tail-call opcodes (`tail-call` feature)
+        #[cfg(feature = "tail-call")]
+        OpcodeId::ReturnCall |
+        OpcodeId::ReturnCallIndirect => WasmReturnCallOpcode::gen_associated_ops,
         // control flow opcodes (PC)
         OpcodeId::Return |
         OpcodeId::Br |
@@ -306,21 +343,142 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::BrTable => WasmBreakOpcode::gen_associated_ops,
 
         // WASM select like opcodes.
-        OpcodeId::Select => StackOnlyOpcode::<3, 1>::gen_associated_ops,
-
-        // WASM store like ops.
-        OpcodeId::I32Store |
-        OpcodeId::I32Store8 |
-        OpcodeId::I32Store16 |
-        OpcodeId::I64Store |
-        OpcodeId::I64Store8 |
-        OpcodeId::I64Store16 |
-        OpcodeId::I64Store32 => StackOnlyOpcode::<2, 0>::gen_associated_ops,
+        OpcodeId::Select => WasmSelectOpcode::gen_associated_ops,
 
         // WASM test opcodes
         OpcodeId::I32Eqz | OpcodeId::I64Eqz => StackOnlyOpcode::<1, 1>::gen_associated_ops,
 
-        OpcodeId::Drop => StackOnlyOpcode::<1, 0>::gen_associated_ops,
+        OpcodeId::Drop => WasmDropOpcode::gen_associated_ops,
+
+        // WASM floating point opcodes (`fp` feature)
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Const => StackOnlyOpcode::<0, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Const => StackOnlyOpcode::<0, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Eq => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Ne => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Lt => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Gt => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Le => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Ge => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Eq => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Ne => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Lt => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Gt => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Le => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Ge => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Add => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Sub => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Mul => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Div => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Min => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Max => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Copysign => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Add => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Sub => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Mul => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Div => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Min => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Max => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Copysign => StackOnlyOpcode::<2, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Abs => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Neg => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Ceil => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Floor => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Trunc => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Nearest => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32Sqrt => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Abs => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Neg => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Ceil => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Floor => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Trunc => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Nearest => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64Sqrt => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::I32TruncSF32 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::I32TruncUF32 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::I32TruncSF64 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::I32TruncUF64 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::I64TruncSF32 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::I64TruncUF32 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::I64TruncSF64 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::I64TruncUF64 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32ConvertSI32 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32ConvertUI32 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32ConvertSI64 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32ConvertUI64 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32DemoteF64 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64ConvertSI32 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64ConvertUI32 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64ConvertSI64 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64ConvertUI64 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64PromoteF32 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::I32ReinterpretF32 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::I64ReinterpretF64 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F32ReinterpretI32 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        #[cfg(feature = "fp")]
+        OpcodeId::F64ReinterpretI64 => StackOnlyOpcode::<1, 1>::gen_associated_ops,
 
         // EVM opcodes
         OpcodeId::STOP => Stop::gen_associated_ops,
@@ -340,6 +498,7 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::EXTCODECOPY => Extcodecopy::gen_associated_ops,
         OpcodeId::RETURNDATASIZE => Returndatasize::gen_associated_ops,
         OpcodeId::RETURNDATACOPY => Returndatacopy::gen_associated_ops,
+        OpcodeId::MCOPY => Mcopy::gen_associated_ops,
         OpcodeId::EXTCODEHASH => Extcodehash::gen_associated_ops,
         OpcodeId::BLOCKHASH => StackToMemoryOpcode::<1, STACK_TO_MEMORY_TYPE_U256>::gen_associated_ops,
         OpcodeId::COINBASE => StackToMemoryOpcode::<0>::gen_associated_ops,
@@ -352,6 +511,8 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::BASEFEE => StackToMemoryOpcode::<0>::gen_associated_ops,
         OpcodeId::SLOAD => Sload::gen_associated_ops,
         OpcodeId::SSTORE => Sstore::gen_associated_ops,
+        OpcodeId::TLOAD => Tload::gen_associated_ops,
+        OpcodeId::TSTORE => Tstore::gen_associated_ops,
         OpcodeId::PC => StackToMemoryOpcode::<0, STACK_TO_MEMORY_TYPE_U64>::gen_associated_ops,
         OpcodeId::MSIZE => StackToMemoryOpcode::<0, STACK_TO_MEMORY_TYPE_U64>::gen_associated_ops,
         OpcodeId::GAS => StackToMemoryOpcode::<0, STACK_TO_MEMORY_TYPE_U64>::gen_associated_ops,
@@ -368,14 +529,8 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
             evm_unimplemented!("Using dummy gen_selfdestruct_ops for opcode SELFDESTRUCT");
             DummySelfDestruct::gen_associated_ops
         }
-        // OpcodeId::CREATE => {
-        //     evm_unimplemented!("Using dummy gen_create_ops for opcode {:?}", opcode_id);
-        //     DummyCreate::<false>::gen_associated_ops
-        // }
-        // OpcodeId::CREATE2 => {
-        //     evm_unimplemented!("Using dummy gen_create_ops for opcode {:?}", opcode_id);
-        //     DummyCreate::<true>::gen_associated_ops
-        // }
+        OpcodeId::CREATE => Create::<false>::gen_associated_ops,
+        OpcodeId::CREATE2 => Create::<true>::gen_associated_ops,
         _ => {
             evm_unimplemented!("Using dummy gen_associated_ops for opcode {:?}", opcode_id);
             Dummy::gen_associated_ops
@@ -460,6 +615,29 @@ fn fn_gen_error_state_associated_ops(
             Some(Create::<true>::gen_associated_ops)
         }
         ExecError::InvalidCreationCode => Some(ErrorCreationCode::gen_associated_ops),
+        // wasm traps pop the operands of the trapping opcode
+        ExecError::WasmTrap(WasmTrapKind::Unreachable) => {
+            Some(StackOnlyOpcode::<0, 0, true>::gen_associated_ops)
+        }
+        ExecError::WasmTrap(WasmTrapKind::StackHeightExceeded) => {
+            Some(ErrorStackHeightExceeded::gen_associated_ops)
+        }
+        ExecError::WasmTrap(WasmTrapKind::DivisionByZero) => {
+            Some(StackOnlyOpcode::<2, 0, true>::gen_associated_ops)
+        }
+        ExecError::WasmTrap(WasmTrapKind::IndirectCallMismatch) => {
+            Some(ErrorIndirectCallMismatch::gen_associated_ops)
+        }
+        ExecError::WasmTrap(WasmTrapKind::MemoryOutOfBounds) => match geth_step.op {
+            OpcodeId::I32Store
+            | OpcodeId::I64Store
+            | OpcodeId::I32Store8
+            | OpcodeId::I32Store16
+            | OpcodeId::I64Store8
+            | OpcodeId::I64Store16
+            | OpcodeId::I64Store32 => Some(StackOnlyOpcode::<2, 0, true>::gen_associated_ops),
+            _ => Some(StackOnlyOpcode::<1, 0, true>::gen_associated_ops),
+        },
         // more future errors place here
         _ => {
             evm_unimplemented!("TODO: error state {:?} not implemented", error);
@@ -563,6 +741,17 @@ pub fn gen_associated_ops(
     // if no errors, continue as normal
     let fn_gen_associated_ops = fn_gen_associated_ops(opcode_id);
     let res = fn_gen_associated_ops(state, geth_steps)?;
+    if let Some(gas_cost) = WASM_GAS_SCHEDULE.cost(*opcode_id) {
+        if geth_step.gas_cost != gas_cost {
+            log::error!(
+                "gas cost of {:?} at pc {:?} is {:?} in trace, {:?} in wasm gas schedule",
+                opcode_id,
+                geth_step.pc,
+                geth_step.gas_cost,
+                gas_cost,
+            );
+        }
+    }
     // copy global memory dump into call context
     if state.has_call() {
         let call_ctx = state.call_ctx_mut()?;
@@ -808,35 +997,65 @@ pub fn gen_begin_tx_ops(
     };
 
     // Initialize WASM global memory and global variables section
-    for (i, byte) in geth_trace.global_memory.0.iter().enumerate() {
-        // TODO: "I think there is easier way to proof init memory"
-        state.memory_write(&mut exec_step, MemoryAddress::from(i), *byte)?;
+    if !geth_trace.global_memory.0.is_empty() {
+        gen_data_segment_copy_events(
+            state,
+            &mut exec_step,
+            call.code_hash,
+            &geth_trace.global_memory.0,
+        )?;
     }
     for global in &geth_trace.globals {
-        // TODO: "proof const evaluation"
+        // init values are proven against the global section by the wasm global table of the
+        // circuits, the EVM circuit still has to look these writes up in it
         state.global_write(&mut exec_step, global.index, StackWord::from(global.value))?;
     }
+    gen_elem_segment_table_writes(state, &mut exec_step, call.code_hash)?;
+
+    // the entry function of code that runs is looked up in the code section by the circuit, a
+    // trace without it can't be proven
+    let is_code_run = call.is_create() || (!is_precompile && !is_empty_code_hash);
+    if is_code_run && geth_trace.function_calls.is_empty() {
+        return Err(Error::InvalidGethExecTrace(
+            "wasm trace of executed code has no function calls",
+        ));
+    }
+    if let Some(first_function_call) = geth_trace.function_calls.first() {
+        // the entry function runs in frame 0, a `call` saves it as the caller frame
+        state.call_context_write(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::InternalFunctionId,
+            first_function_call.fn_index.into(),
+        );
 
-    let first_function_call = geth_trace.function_calls.first().unwrap();
-    // state.call_context_write(
-    //     &mut exec_step,
-    //     state.call()?.call_id,
-    //     CallContextField::InternalFunctionId,
-    //     U256::from(first_function_call.fn_index),
-    // );
-
-    for i in 0..first_function_call.num_locals {
-        // TODO: "function body can be empty"
-        state.stack_write(&mut exec_step, geth_trace.struct_logs[0].stack.nth_last_filled((first_function_call.num_locals - i - 1) as usize), StackWord::zero())?;
+        // Locals are reserved on top of the stack in one go, the slots are not written: a read
+        // before the first write is a first access in the state circuit and must return zero.
+        state.call_context_write(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::NumLocals,
+            first_function_call.num_locals.into(),
+        );
+        exec_step.function_index = first_function_call.fn_index;
+        exec_step.max_stack_height = first_function_call.max_stack_height;
+        exec_step.num_locals = first_function_call.num_locals;
+        // increase reserved stack size with num locals, locals above the stack height limit are
+        // not reserved, the first step of the entry function traps
+        if first_function_call.num_locals as u64 <= MAX_WASM_STACK_HEIGHT {
+            exec_step.stack_size += first_function_call.num_locals as usize;
+        }
     }
-    exec_step.function_index = first_function_call.fn_index;
-    exec_step.max_stack_height = first_function_call.max_stack_height;
-    exec_step.num_locals = first_function_call.num_locals;
-    // increase reserved stack size with num locals
-    exec_step.stack_size += first_function_call.num_locals as usize;
 
     let mut call_ctx = state.call_ctx_mut()?;
     call_ctx.memory = geth_trace.global_memory.clone();
+    call_ctx.function_calls = geth_trace.function_calls.clone();
+    call_ctx.function_frames = vec![FunctionFrame {
+        function_index: exec_step.function_index,
+        max_stack_height: exec_step.max_stack_height,
+        num_locals: exec_step.num_locals,
+        ..Default::default()
+    }];
 
     log::trace!("begin_tx_step: {:?}", exec_step);
     state.tx.steps_mut().push(exec_step);
@@ -853,6 +1072,82 @@ pub fn gen_begin_tx_ops(
     Ok(())
 }
 
+/// Store the functions of every active element segment of the code in its table, one table write
+/// per element holding the function index plus one.
+fn gen_elem_segment_table_writes(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    code_hash: H256,
+) -> Result<(), Error> {
+    let code = state.code(code_hash)?;
+    for segment in active_elem_segments(&code)? {
+        for (i, function_index) in segment.function_indexes.into_iter().enumerate() {
+            state.table_write(
+                exec_step,
+                segment.table_index,
+                segment.table_offset + i as u32,
+                StackWord::from(function_index as u64 + 1),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy every active data segment of the code to the linear memory, one copy event per segment
+/// sourced from the bytecode, instead of writing the initial memory byte by byte.
+fn gen_data_segment_copy_events(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    code_hash: H256,
+    global_memory: &[u8],
+) -> Result<(), Error> {
+    let code = state.code(code_hash)?;
+    let segments = active_data_segments(&code)?;
+
+    let mut memory = vec![0u8; global_memory.len()];
+    for segment in &segments {
+        let dst_start = segment.memory_offset as usize;
+        memory
+            .get_mut(dst_start..dst_start + segment.bytes.len())
+            .ok_or(Error::InvalidGethExecTrace("data segment out of the initial memory"))?
+            .copy_from_slice(&code[segment.bytes.clone()]);
+    }
+    if memory != global_memory {
+        return Err(Error::InvalidGethExecTrace(
+            "data segments don't match the initial memory",
+        ));
+    }
+
+    let bytecode: Bytecode = code.into();
+    for segment in segments {
+        let rw_counter_start = state.block_ctx.rwc;
+        let src_addr = segment.bytes.start as u64;
+        let src_addr_end = segment.bytes.end as u64;
+        let bytes = state.gen_copy_steps_for_bytecode(
+            exec_step,
+            &bytecode,
+            src_addr,
+            segment.memory_offset,
+            src_addr_end,
+            src_addr_end - src_addr,
+        )?;
+        let copy_event = CopyEvent {
+            src_type: CopyDataType::Bytecode,
+            src_id: NumberOrHash::Hash(code_hash),
+            src_addr,
+            src_addr_end,
+            dst_type: CopyDataType::Memory,
+            dst_id: NumberOrHash::Number(state.call()?.call_id),
+            dst_addr: segment.memory_offset,
+            log_id: None,
+            rw_counter_start,
+            bytes,
+        };
+        state.push_copy(exec_step, copy_event);
+    }
+    Ok(())
+}
+
 pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error> {
     let mut exec_step = state.new_end_tx_step();
     let call = state.tx.calls()[0].clone();
@@ -904,13 +1199,19 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         .get(&state.tx.block_num)
         .unwrap()
         .clone();
-    let effective_tip = state.tx.gas_price - block_info.base_fee;
+    let fee_model = state.block.fee_model;
     let gas_cost = state.tx.gas - exec_step.gas_left.0 - effective_refund;
-    let coinbase_reward = effective_tip * gas_cost + state.tx_ctx.l1_fee;
-    log::trace!(
-        "coinbase reward = ({} - {}) * ({} - {} - {}) = {}",
+    let coinbase_reward = fee_model.coinbase_reward(
         state.tx.gas_price,
         block_info.base_fee,
+        gas_cost,
+        state.tx_ctx.l1_fee,
+    );
+    log::trace!(
+        "coinbase reward ({:?}) = ({} - {}) * ({} - {} - {}) = {}",
+        fee_model,
+        state.tx.gas_price,
+        fee_model.burned_base_fee(block_info.base_fee),
         state.tx.gas,
         exec_step.gas_left.0,
         effective_refund,
@@ -19,16 +19,9 @@ impl Opcode for Calldatacopy {
         let geth_step = &geth_steps[0];
         let mut exec_steps = vec![gen_calldatacopy_step(state, geth_step)?];
 
-        // reconstruction
-        let length = geth_step.stack.nth_last(0)?;
-        let data_offset = geth_step.stack.nth_last(1)?;
-        let memory_offset = geth_step.stack.nth_last(2)?;
-        let call_ctx = state.call_ctx_mut()?;
-        let memory = &mut call_ctx.memory;
-
-        memory.copy_from(memory_offset, data_offset, length, &call_ctx.call_data);
-
+        // the host copies the call data into the linear memory, the copy circuit proves it
         let copy_event = gen_copy_event(state, geth_step)?;
+        state.sync_linear_memory_after_copy(&copy_event, &geth_steps[1])?;
         state.push_copy(&mut exec_steps[0], copy_event);
         Ok(exec_steps)
     }
@@ -39,21 +32,21 @@ fn gen_calldatacopy_step(
     geth_step: &GethExecStep,
 ) -> Result<ExecStep, Error> {
     let mut exec_step = state.new_step(geth_step)?;
-    let memory_offset = geth_step.stack.nth_last(0)?;
+    let length = geth_step.stack.nth_last(0)?;
     let data_offset = geth_step.stack.nth_last(1)?;
-    let length = geth_step.stack.nth_last(2)?;
+    let memory_offset = geth_step.stack.nth_last(2)?;
 
+    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), length)?;
     state.stack_read(
         &mut exec_step,
-        geth_step.stack.nth_last_filled(0),
-        memory_offset,
+        geth_step.stack.nth_last_filled(1),
+        data_offset,
     )?;
     state.stack_read(
         &mut exec_step,
-        geth_step.stack.nth_last_filled(1),
-        data_offset,
+        geth_step.stack.nth_last_filled(2),
+        memory_offset,
     )?;
-    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), length)?;
 
     if state.call()?.is_root {
         state.call_context_read(
@@ -34,11 +34,12 @@ impl<const WITH_VALUE: bool> Opcode for CallOpcode<WITH_VALUE> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
 
+        // wasm pushes the args in order, the status offset is the last one
         let status_offset = geth_step.stack.nth_last(0)?.low_u64() as usize;
-        let ret_offset = geth_step.stack.nth_last(1)?.low_u64() as usize;
-        let ret_length = geth_step.stack.nth_last(2)?.as_usize();
-        let args_offset = geth_step.stack.nth_last(3)?.low_u64() as usize;
-        let args_length = geth_step.stack.nth_last(4)?.as_usize();
+        let ret_length = geth_step.stack.nth_last(1)?.as_usize();
+        let ret_offset = geth_step.stack.nth_last(2)?.low_u64() as usize;
+        let args_length = geth_step.stack.nth_last(3)?.as_usize();
+        let args_offset = geth_step.stack.nth_last(4)?.low_u64() as usize;
 
         // we need to keep the memory until parse_call complete
         state.call_expand_memory(args_offset, args_length, ret_offset, ret_length)?;
@@ -277,18 +278,13 @@ impl<const WITH_VALUE: bool> Opcode for CallOpcode<WITH_VALUE> {
                 let code_address = code_address.unwrap();
                 let precompile_call: PrecompileCalls = code_address.0[19].into();
 
-                // get the result of the precompile call.
-                let caller_ctx = state.caller_ctx()?;
-                let caller_memory = caller_ctx.memory.0.clone();
-                let (result, contract_gas_cost) = execute_precompiled(
-                    &code_address,
-                    if args_length != 0 {
-                        &caller_memory[args_offset..args_offset + args_length]
-                    } else {
-                        &[]
-                    },
-                    callee_gas_left,
-                );
+                // get the result of the precompile call, the input is in the caller's linear
+                // memory at the time of the call.
+                let input = geth_step
+                    .global_memory
+                    .read_chunk(MemoryAddress(args_offset), MemoryAddress(args_length));
+                let (result, contract_gas_cost) =
+                    execute_precompiled(&code_address, &input, callee_gas_left);
 
                 log::trace!(
                     "precompile returned data len {} gas {}",
@@ -296,9 +292,10 @@ impl<const WITH_VALUE: bool> Opcode for CallOpcode<WITH_VALUE> {
                     contract_gas_cost
                 );
 
-                // mutate the caller memory.
+                // write the output back to the caller's linear memory.
                 let caller_ctx_mut = state.caller_ctx_mut()?;
                 caller_ctx_mut.return_data = result.clone();
+                caller_ctx_mut.memory = geth_step.global_memory.clone();
                 let length = min(result.len(), ret_length);
                 if length > 0 {
                     caller_ctx_mut.memory.extend_at_least(ret_offset + length);
@@ -368,12 +365,7 @@ impl<const WITH_VALUE: bool> Opcode for CallOpcode<WITH_VALUE> {
                 // insert a copy event (input) for this step
                 let rw_counter_start = state.block_ctx.rwc;
                 if call.call_data_length > 0 {
-                    let bytes: Vec<(u8, bool)> = caller_memory
-                        .iter()
-                        .skip(call.call_data_offset as usize)
-                        .take(call.call_data_length as usize)
-                        .map(|b| (*b, false))
-                        .collect();
+                    let bytes: Vec<(u8, bool)> = input.iter().map(|b| (*b, false)).collect();
                     for (i, &(byte, _is_code)) in bytes.iter().enumerate() {
                         // push caller memory read
                         state.push_op(
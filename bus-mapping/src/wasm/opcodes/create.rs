@@ -1,6 +1,7 @@
 use crate::{
     circuit_input_builder::{
-        CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+        get_create_init_code_offset_length, CircuitInputStateRef, CopyDataType, CopyEvent,
+        ExecStep, NumberOrHash,
     },
     error::{ContractAddressCollisionError, ExecError},
     evm::{Opcode, OpcodeId},
@@ -8,7 +9,10 @@ use crate::{
     state_db::CodeDB,
     Error,
 };
-use eth_types::{Bytecode, GethExecStep, ToBigEndian, ToWord, Word, H160, H256};
+use eth_types::{
+    evm_types::MemoryAddress, Address, Bytecode, GethExecStep, ToBigEndian, ToWord, Word, H160,
+    H256,
+};
 use ethers_core::utils::{get_create2_address, keccak256, rlp};
 
 #[derive(Debug, Copy, Clone)]
@@ -22,16 +26,9 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
 
-        // Get low Uint64 of offset.
-        let offset = geth_step.stack.nth_last(1)?.low_u64() as usize;
-        let length = geth_step.stack.nth_last(2)?.as_usize();
-
-        if length != 0 {
-            state
-                .call_ctx_mut()?
-                .memory
-                .extend_at_least(offset + length);
-        }
+        // the init code is read from the linear memory, which the host does not expand
+        let (offset, length) = get_create_init_code_offset_length(geth_step)?;
+        let (offset, length) = (offset as usize, length as usize);
         let next_memory_word_size = state.call_ctx()?.memory_word_size();
 
         let callee = state.parse_call(geth_step)?;
@@ -43,7 +40,7 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
             Word::from(state.call()?.is_static as u8),
         );
 
-        let n_pop = if IS_CREATE2 { 4 } else { 3 };
+        let n_pop = if IS_CREATE2 { 5 } else { 4 };
         for i in 0..n_pop {
             state.stack_read(
                 &mut exec_step,
@@ -64,18 +61,21 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
             state.sdb.get_account_mut(&address).1.storage.clear();
         }
 
-        // state.stack_write(
-        //     &mut exec_step,
-        //     geth_step.stack.nth_last_filled(n_pop - 1),
-        //     if callee.is_success {
-        //         address.to_word()
-        //     } else {
-        //         Word::zero()
-        //     },
-        // )?;
+        // wasm pushes the args in order, the created address is written at the last one
+        let result_offset = geth_step.stack.nth_last(0)?;
+        state.memory_write_n(
+            &mut exec_step,
+            MemoryAddress::try_from(result_offset)?,
+            &if callee.is_success {
+                address
+            } else {
+                Address::zero()
+            }
+            .to_fixed_bytes(),
+        )?;
 
         let (initialization_code, keccak_code_hash, code_hash) = if length > 0 {
-            handle_copy(state, &mut exec_step, state.call()?.call_id, offset, length)?
+            handle_copy(state, geth_step, &mut exec_step, state.call()?.call_id, offset, length)?
         } else {
             (vec![], H256(keccak256([])), CodeDB::empty_code_hash())
         };
@@ -274,7 +274,7 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
         }
 
         let keccak_input = if IS_CREATE2 {
-            let salt = geth_step.stack.nth_last(3)?;
+            let salt = geth_step.global_memory.read_u256(geth_step.stack.nth_last(1)?)?;
             assert_eq!(
                 address,
                 get_create2_address(
@@ -321,12 +321,15 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
 
 fn handle_copy(
     state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
     step: &mut ExecStep,
     callee_id: usize,
     offset: usize,
     length: usize,
 ) -> Result<(Vec<u8>, H256, H256), Error> {
-    let initialization_bytes = state.call_ctx()?.memory.0[offset..offset + length].to_vec();
+    let initialization_bytes = geth_step
+        .global_memory
+        .read_chunk(offset.into(), length.into());
     let keccak_code_hash = H256(keccak256(&initialization_bytes));
     let code_hash = CodeDB::hash(&initialization_bytes);
     let bytes: Vec<_> = Bytecode::from(initialization_bytes.clone())
@@ -0,0 +1,36 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    evm::Opcode,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Trap of a `call_indirect` to a function of another type. The element index is popped and the
+/// element is read from the table 0, the circuit compares the type of its function with the type
+/// immediate of the opcode.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorIndirectCallMismatch;
+
+impl Opcode for ErrorIndirectCallMismatch {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = state.get_step_err(geth_step, geth_steps.get(1))?;
+
+        let elem_index = geth_step.stack.nth_last(0)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), elem_index)?;
+        // only the table 0 exists without the reference types proposal
+        let elem_index = elem_index.low_u64() as u32;
+        let funcref = state.table_value(0, elem_index)?;
+        if funcref.is_zero() {
+            return Err(Error::InvalidGethExecTrace("call_indirect of a null table element"));
+        }
+        state.table_read(&mut exec_step, 0, elem_index, funcref)?;
+
+        state.handle_return(&mut exec_step, geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
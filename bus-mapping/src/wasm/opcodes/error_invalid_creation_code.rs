@@ -2,6 +2,7 @@ use crate::{
     circuit_input_builder::{CircuitInputStateRef, ExecStep},
     error::ExecError,
     evm::Opcode,
+    state_db::CodeDB,
     Error,
 };
 use eth_types::{GethExecStep, StackWord};
@@ -19,10 +20,10 @@ impl Opcode for ErrorCreationCode {
 
         exec_step.error = Some(ExecError::InvalidCreationCode);
 
-        let offset = geth_step.stack.nth_last(0)?;
-        let length = geth_step.stack.nth_last(1)?;
-        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), offset)?;
-        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), length)?;
+        let length = geth_step.stack.nth_last(0)?;
+        let offset = geth_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), length)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), offset)?;
 
         // in create context
         let call = state.call()?;
@@ -32,11 +33,13 @@ impl Opcode for ErrorCreationCode {
 
         assert!(length > StackWord::zero());
 
-        // read first byte and assert it is 0xef
-        let byte = state.call_ctx()?.memory.0[offset.as_usize()];
-        assert!(byte == 0xef);
+        // the deployed code does not start with the wasm magic and version
+        let header = geth_step
+            .global_memory
+            .read_chunk(offset.low_u64().into(), length.low_u64().min(8).into());
+        assert!(!CodeDB::is_wasm_module(&header));
 
-        state.memory_read(&mut exec_step, offset.try_into()?, byte)?;
+        state.memory_read(&mut exec_step, offset.try_into()?, header[0])?;
 
         // refer to return_revert Case C
         state.handle_return(&mut exec_step, geth_steps, true)?;
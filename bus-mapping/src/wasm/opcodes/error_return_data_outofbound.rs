@@ -25,21 +25,21 @@ impl Opcode for ErrorReturnDataOutOfBound {
             Some(ExecError::ReturnDataOutOfBounds)
         );
 
-        let memory_offset = geth_step.stack.nth_last(0)?;
+        let length = geth_step.stack.nth_last(0)?;
         let data_offset = geth_step.stack.nth_last(1)?;
-        let length = geth_step.stack.nth_last(2)?;
+        let memory_offset = geth_step.stack.nth_last(2)?;
 
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), length)?;
         state.stack_read(
             &mut exec_step,
-            geth_step.stack.nth_last_filled(0),
-            memory_offset,
+            geth_step.stack.nth_last_filled(1),
+            data_offset,
         )?;
         state.stack_read(
             &mut exec_step,
-            geth_step.stack.nth_last_filled(1),
-            data_offset,
+            geth_step.stack.nth_last_filled(2),
+            memory_offset,
         )?;
-        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), length)?;
 
         let call_id = state.call()?.call_id;
         let call_ctx = state.call_ctx()?;
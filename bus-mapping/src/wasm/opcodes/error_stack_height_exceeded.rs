@@ -0,0 +1,38 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    evm::Opcode,
+    operation::CallContextField,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Trap of a step growing the stack above the height limit, see
+/// [`CircuitInputStateRef::wasm_stack_growth`]. Nothing is popped, the first step of the entry
+/// function reads the number of locals which don't fit.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorStackHeightExceeded;
+
+impl Opcode for ErrorStackHeightExceeded {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = state.get_step_err(geth_step, geth_steps.get(1))?;
+
+        if state.is_wasm_entry_step() {
+            let call_id = state.call()?.call_id;
+            let num_locals = state.call_ctx()?.function_frame().num_locals;
+            state.call_context_read(
+                &mut exec_step,
+                call_id,
+                CallContextField::NumLocals,
+                num_locals.into(),
+            );
+        }
+
+        state.handle_return(&mut exec_step, geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -26,21 +26,6 @@ impl<const N_LOGS: usize> Opcode for Log<N_LOGS> {
             state.tx_ctx.log_id += 1;
         }
 
-        // reconstruction
-        let length = geth_step.stack.nth_last(2 + N_LOGS - 2)?.as_u64();
-        let offset = geth_step.stack.nth_last(2 + N_LOGS - 1)?;
-
-        if length != 0 {
-            // Offset should be within range of Uint64 if length is non-zero.
-            let memory_length = offset
-                .as_u64()
-                .checked_add(length)
-                .and_then(|val| usize::try_from(val).ok())
-                .unwrap();
-
-            state.call_ctx_mut()?.memory.extend_at_least(memory_length);
-        }
-
         Ok(vec![exec_step])
     }
 }
@@ -88,16 +73,26 @@ fn gen_log_step<const N_LOGS: usize>(
         )?;
     }
 
+    // linear memory is not expanded by the host, the topics and the data must be allocated
+    let memory_size = state.call_ctx()?.memory.len() as u64;
+    let is_allocated = |offset: u64, length: u64| {
+        length == 0 || offset.checked_add(length).map_or(false, |end| end <= memory_size)
+    };
+
     for i in 0..N_LOGS {
         let topic_offset = geth_step.stack.nth_last(i)?;
+        if !is_allocated(topic_offset.as_u64(), 32) {
+            return Err(Error::InvalidGethExecTrace("log topic above the allocated pages"));
+        }
         let topic = geth_step.global_memory.read_u256(topic_offset)?;
         state.stack_read(
             &mut exec_step,
             geth_step.stack.nth_last_filled(i),
             topic_offset,
         )?;
+        // the topic is read from the linear memory at the offset on the stack
         let topic_bytes = topic.to_be_bytes();
-        state.memory_write_n(
+        state.memory_read_n(
             &mut exec_step,
             MemoryAddress::from(topic_offset.as_u64()),
             &topic_bytes,
@@ -117,6 +112,9 @@ fn gen_log_step<const N_LOGS: usize>(
 
     let mstart = geth_step.stack.nth_last(1 + N_LOGS)?;
     let msize = geth_step.stack.nth_last(N_LOGS)?;
+    if !is_allocated(mstart.low_u64(), msize.low_u64()) {
+        return Err(Error::InvalidGethExecTrace("log data above the allocated pages"));
+    }
 
     state.stack_read(
         &mut exec_step,
@@ -168,10 +166,18 @@ fn gen_copy_event<const N_LOGS: usize>(
 mod log_tests {
     use crate::{
         circuit_input_builder::{CopyDataType, ExecState, NumberOrHash},
+        exec_trace::OperationRef,
         mock::BlockData,
-        operation::{CallContextField, CallContextOp, MemoryOp, StackOp, TxLogField, TxLogOp, RW},
+        operation::{
+            CallContextField, CallContextOp, MemoryOp, StackOp, Target, TxLogField, TxLogOp, RW,
+        },
+    };
+    use eth_types::{
+        bytecode_internal,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        Bytecode, StackWord, ToBigEndian, ToWord, Word,
     };
-    use eth_types::{bytecode, evm_types::{OpcodeId, StackAddress}, geth_types::GethData, Bytecode, ToWord, Word, StackWord};
 
     use mock::test_ctx::{helpers::*, TestContext};
     use pretty_assertions::assert_eq;
@@ -207,44 +213,30 @@ mod log_tests {
         let topic_count = topics.len();
         let cur_op_code = log_codes[topic_count];
 
-        let mstart = 0x00usize;
-        let msize = 0x40usize;
+        // data and topics are in the linear memory, the stack holds their offsets
+        let data = hex::decode("1234567890abcdef1234567890abcdef").unwrap();
+        let msize = data.len();
         let mut code = Bytecode::default();
-        // make dynamic topics push operations
-        for topic in topics {
-            code.push(32, *topic);
+        let mstart = code.fill_default_global_data(data.clone()) as usize;
+        let topic_offsets = topics
+            .iter()
+            .map(|topic| code.fill_default_global_data(topic.to_be_bytes().to_vec()) as usize)
+            .collect::<Vec<_>>();
+        bytecode_internal! {code,
+            I32Const[mstart]
+            I32Const[msize]
+        }
+        for offset in topic_offsets.iter() {
+            bytecode_internal! {code,
+                I32Const[*offset]
+            }
         }
-
-        code.push(32, Word::from(msize));
-        code.push(32, Word::from(mstart));
         code.write_op(cur_op_code);
-        code.op_stop();
-
-        // prepare memory data
-        let pushdata = hex::decode("1234567890abcdef1234567890abcdef").unwrap();
-        let mut memory_data = std::iter::repeat(0)
-            .take(16)
-            .chain(pushdata.clone())
-            .collect::<Vec<u8>>();
-        // construct 64 bytes
-        memory_data.append(&mut memory_data.clone());
-
-        let mut code_prepare: Bytecode = bytecode! {
-            // populate memory.
-            PUSH16(Word::from_big_endian(&pushdata))
-            PUSH1(0x00) // offset
-            MSTORE
-            PUSH16(Word::from_big_endian(&pushdata))
-            PUSH1(0x20) // offset
-            MSTORE
-        };
-
-        code_prepare.append(&code);
 
         // Get the execution steps from the external tracer
         let block: GethData = TestContext::<2, 1>::new(
             None,
-            account_0_code_account_1_no_code(code_prepare),
+            account_0_code_account_1_no_code(code),
             tx_from_1_to_0,
             |block, _tx| block.number(0xcafeu64),
         )
@@ -256,68 +248,87 @@ mod log_tests {
             .handle_block(&block.eth_block, &block.geth_traces)
             .unwrap();
 
-        let is_persistent = builder.block.txs()[0].calls()[0].is_persistent;
-        let callee_address = builder.block.txs()[0].to;
-
         let step = builder.block.txs()[0]
             .steps()
             .iter()
             .find(|step| step.exec_state == ExecState::Op(cur_op_code))
             .unwrap();
+        let call_id = builder.block.txs()[0].calls()[step.call_index].call_id;
+        let is_persistent = builder.block.txs()[0].calls()[step.call_index].is_persistent;
+        let callee_address = builder.block.txs()[0].to;
 
-        let expected_call_id = builder.block.txs()[0].calls()[step.call_index].call_id;
-
-        assert_eq!(
-            [0, 1]
-                .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
-                .map(|operation| (operation.rw(), operation.op())),
-            [
-                (
-                    RW::READ,
-                    &StackOp::new(1, StackAddress::from((1022 - topic_count) as u32), StackWord::from(mstart))
-                ),
+        // the topic offsets are popped first, the last pushed one is topic 0, then msize and
+        // mstart
+        let mut expected_stack_reads = topic_offsets
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, offset)| {
                 (
                     RW::READ,
-                    &StackOp::new(1, StackAddress::from((1023 - topic_count) as u32), StackWord::from(msize))
+                    StackOp::new(
+                        call_id,
+                        StackAddress::from(1022 - topic_count + i),
+                        StackWord::from(*offset),
+                    ),
                 )
-            ]
+            })
+            .collect::<Vec<_>>();
+        expected_stack_reads.push((
+            RW::READ,
+            StackOp::new(call_id, StackAddress::from(1022), StackWord::from(msize)),
+        ));
+        expected_stack_reads.push((
+            RW::READ,
+            StackOp::new(call_id, StackAddress::from(1023), StackWord::from(mstart)),
+        ));
+        assert_eq!(
+            step.bus_mapping_instance
+                .iter()
+                .filter(|op_ref| op_ref.0 == Target::Stack)
+                .map(|OperationRef(_, idx)| &builder.block.container.stack[*idx])
+                .map(|op| (op.rw(), op.op().clone()))
+                .collect::<Vec<(RW, StackOp)>>(),
+            expected_stack_reads,
         );
 
         // assert call context is right
         assert_eq!(
-            [2, 3, 4, 5]
-                .map(|idx| &builder.block.container.call_context
-                    [step.bus_mapping_instance[idx].as_usize()])
-                .map(|operation| (operation.rw(), operation.op())),
-            [
+            step.bus_mapping_instance
+                .iter()
+                .filter(|op_ref| op_ref.0 == Target::CallContext)
+                .map(|OperationRef(_, idx)| &builder.block.container.call_context[*idx])
+                .map(|op| (op.rw(), op.op().clone()))
+                .collect::<Vec<(RW, CallContextOp)>>(),
+            vec![
                 (
                     RW::READ,
-                    &CallContextOp {
-                        call_id: 1,
+                    CallContextOp {
+                        call_id,
                         field: CallContextField::TxId,
                         value: Word::from(1),
                     },
                 ),
                 (
                     RW::READ,
-                    &CallContextOp {
-                        call_id: 1,
+                    CallContextOp {
+                        call_id,
                         field: CallContextField::IsStatic,
                         value: Word::from(0),
                     },
                 ),
                 (
                     RW::READ,
-                    &CallContextOp {
-                        call_id: 1,
+                    CallContextOp {
+                        call_id,
                         field: CallContextField::CalleeAddress,
                         value: callee_address.to_word(),
                     },
                 ),
                 (
                     RW::READ,
-                    &CallContextOp {
-                        call_id: 1,
+                    CallContextOp {
+                        call_id,
                         field: CallContextField::IsPersistent,
                         value: Word::from(1),
                     },
@@ -325,98 +336,84 @@ mod log_tests {
             ]
         );
 
-        // TODO: handle is_persistent = false conditions
-        if is_persistent {
-            assert_eq!(
-                [6].map(|idx| &builder.block.container.tx_log
-                    [step.bus_mapping_instance[idx].as_usize()])
-                    .map(|operation| (operation.rw(), operation.op())),
-                [(
-                    RW::WRITE,
-                    &TxLogOp {
-                        tx_id: 1,
-                        log_id: step.log_id + 1,
-                        field: TxLogField::Address,
-                        index: 0,
-                        value: callee_address.to_word(),
-                    }
-                ),]
-            );
-        }
+        // the address is written to the log before the topics
+        assert!(is_persistent);
+        assert_eq!(
+            step.bus_mapping_instance
+                .iter()
+                .find(|op_ref| op_ref.0 == Target::TxLog)
+                .map(|OperationRef(_, idx)| &builder.block.container.tx_log[*idx])
+                .map(|op| (op.rw(), op.op().clone())),
+            Some((
+                RW::WRITE,
+                TxLogOp {
+                    tx_id: 1,
+                    log_id: step.log_id + 1,
+                    field: TxLogField::Address,
+                    index: 0,
+                    value: callee_address.to_word(),
+                }
+            )),
+        );
 
-        // log topic writes
-        let mut log_topic_ops = Vec::with_capacity(topic_count);
-        for (idx, topic) in topics.iter().rev().enumerate() {
-            log_topic_ops.push((
+        // topics are read from the linear memory before the data, the last pushed topic is
+        // topic 0
+        let topic_reads = step
+            .bus_mapping_instance
+            .iter()
+            .filter(|op_ref| op_ref.0 == Target::Memory)
+            .take(32 * topic_count)
+            .map(|OperationRef(_, idx)| &builder.block.container.memory[*idx])
+            .map(|op| (op.rw(), op.op().clone()))
+            .collect::<Vec<(RW, MemoryOp)>>();
+        let mut expected_topic_reads = Vec::with_capacity(32 * topic_count);
+        let mut expected_topic_writes = Vec::with_capacity(topic_count);
+        for (idx, (topic, offset)) in topics.iter().zip(topic_offsets.iter()).rev().enumerate() {
+            for (i, byte) in topic.to_be_bytes().iter().enumerate() {
+                expected_topic_reads.push((RW::READ, MemoryOp::new(call_id, (offset + i).into(), *byte)));
+            }
+            expected_topic_writes.push((
                 RW::WRITE,
                 TxLogOp::new(1, step.log_id + 1, TxLogField::Topic, idx, *topic),
             ));
         }
+        assert_eq!(topic_reads, expected_topic_reads);
         assert_eq!(
             (1..1 + topic_count)
                 .map(|idx| &builder.block.container.tx_log[idx])
                 .map(|op| (op.rw(), op.op().clone()))
                 .collect::<Vec<(RW, TxLogOp)>>(),
-            { log_topic_ops },
-        );
-
-        // memory reads.
-        let mut log_data_ops = Vec::with_capacity(msize);
-        assert_eq!(
-            // skip first 32 writes of MSTORE ops
-            (mstart + 64..(mstart + 64 + msize))
-                .map(|idx| &builder.block.container.memory[idx])
-                .map(|op| (op.rw(), op.op().clone()))
-                .collect::<Vec<(RW, MemoryOp)>>(),
-            {
-                let mut memory_ops = Vec::with_capacity(msize);
-                (mstart..msize).for_each(|idx| {
-                    memory_ops.push((
-                        RW::READ,
-                        MemoryOp::new(1, (mstart + idx).into(), memory_data[mstart + idx]),
-                    ));
-                    // tx log addition
-                    log_data_ops.push((
-                        RW::WRITE,
-                        TxLogOp::new(
-                            1,
-                            step.log_id + 1, // because it is in next CopyToLog step
-                            TxLogField::Data,
-                            idx - mstart,
-                            Word::from(memory_data[mstart + idx]),
-                        ),
-                    ));
-                });
-
-                memory_ops
-            },
-        );
-        assert_eq!(
-            ((1 + topic_count)..msize + 1 + topic_count)
-                .map(|idx| &builder.block.container.tx_log[idx])
-                .map(|op| (op.rw(), op.op().clone()))
-                .collect::<Vec<(RW, TxLogOp)>>(),
-            { log_data_ops },
+            expected_topic_writes,
         );
 
+        // the data is copied from the linear memory into the log
         let copy_events = builder.block.copy_events.clone();
         assert_eq!(copy_events.len(), 1);
-        assert_eq!(copy_events[0].bytes.len(), msize);
         assert_eq!(copy_events[0].src_type, CopyDataType::Memory);
-        assert_eq!(
-            copy_events[0].src_id,
-            NumberOrHash::Number(expected_call_id)
-        );
+        assert_eq!(copy_events[0].src_id, NumberOrHash::Number(call_id));
         assert_eq!(copy_events[0].src_addr as usize, mstart);
         assert_eq!(copy_events[0].src_addr_end as usize, mstart + msize);
         assert_eq!(copy_events[0].dst_type, CopyDataType::TxLog);
         assert_eq!(copy_events[0].dst_id, NumberOrHash::Number(1)); // tx_id
-        assert_eq!(copy_events[0].dst_addr as usize, 0);
         assert_eq!(copy_events[0].log_id, Some(step.log_id as u64 + 1));
-
-        for (idx, (byte, is_code)) in copy_events[0].bytes.iter().enumerate() {
-            assert_eq!(Some(byte), memory_data.get(mstart + idx));
-            assert!(!*is_code);
-        }
+        assert_eq!(copy_events[0].dst_addr as usize, 0);
+        assert_eq!(copy_events[0].bytes.len(), msize);
+        assert_eq!(
+            copy_events[0].bytes,
+            data.iter().map(|byte| (*byte, false)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            (1 + topic_count..1 + topic_count + msize)
+                .map(|idx| &builder.block.container.tx_log[idx])
+                .map(|op| (op.rw(), op.op().clone()))
+                .collect::<Vec<(RW, TxLogOp)>>(),
+            data.iter()
+                .enumerate()
+                .map(|(idx, byte)| (
+                    RW::WRITE,
+                    TxLogOp::new(1, step.log_id + 1, TxLogField::Data, idx, Word::from(*byte)),
+                ))
+                .collect::<Vec<_>>()
+        );
     }
 }
@@ -0,0 +1,207 @@
+use crate::{
+    circuit_input_builder::{
+        CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+    },
+    evm::Opcode,
+    Error,
+};
+use eth_types::{evm_types::MemoryAddress, GethExecStep};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::MCOPY`](crate::evm::OpcodeId::MCOPY)
+/// `OpcodeId`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Mcopy;
+
+impl Opcode for Mcopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let length = geth_step.stack.nth_last(0)?;
+        let src_offset = geth_step.stack.nth_last(1)?;
+        let dst_offset = geth_step.stack.nth_last(2)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), length)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), src_offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), dst_offset)?;
+
+        // the host moves the bytes inside the linear memory, the copy circuit proves it
+        let copy_event = gen_copy_event(state, geth_step)?;
+        state.sync_linear_memory_after_copy(&copy_event, &geth_steps[1])?;
+        state.push_copy(&mut exec_step, copy_event);
+        Ok(vec![exec_step])
+    }
+}
+
+fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
+) -> Result<CopyEvent, Error> {
+    let length = geth_step.stack.nth_last(0)?.as_u64();
+    let src_addr = geth_step.stack.nth_last(1)?.as_u64();
+    let dst_addr = geth_step.stack.nth_last(2)?.as_u64();
+
+    // the copy circuit reads and writes byte by byte from the lowest address, a forward
+    // overlapping move would read bytes it already overwrote
+    if dst_addr > src_addr && dst_addr < src_addr + length {
+        return Err(Error::InvalidGethExecTrace(
+            "overlapping forward memory copy is not supported",
+        ));
+    }
+
+    let bytes = geth_step
+        .global_memory
+        .read_chunk(MemoryAddress(src_addr as usize), MemoryAddress(length as usize));
+
+    let rw_counter_start = state.block_ctx.rwc;
+    let mut exec_step = state.new_step(geth_step)?;
+    let mut copy_steps = Vec::with_capacity(bytes.len());
+    for (idx, byte) in bytes.into_iter().enumerate() {
+        state.memory_read(&mut exec_step, (src_addr + idx as u64).into(), byte)?;
+        state.memory_write(&mut exec_step, (dst_addr + idx as u64).into(), byte)?;
+        copy_steps.push((byte, false));
+    }
+
+    let call_id = state.call()?.call_id;
+    Ok(CopyEvent {
+        src_type: CopyDataType::Memory,
+        src_id: NumberOrHash::Number(call_id),
+        src_addr,
+        src_addr_end: src_addr + length,
+        dst_type: CopyDataType::Memory,
+        dst_id: NumberOrHash::Number(call_id),
+        dst_addr,
+        log_id: None,
+        rw_counter_start,
+        bytes: copy_steps,
+    })
+}
+
+#[cfg(test)]
+mod mcopy_tests {
+    use crate::{
+        circuit_input_builder::{CopyDataType, NumberOrHash},
+        mock::BlockData,
+        operation::RW,
+        trace_provider::{WasmiStep, WasmiTrace, WasmiTraces},
+        Error,
+    };
+    use eth_types::{bytecode, geth_types::GethData, GethExecTraceFunctionCall};
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+    use pretty_assertions::assert_eq;
+
+    const DATA: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    fn step(pc: usize, instr: &str, params: Vec<u64>, stack: Vec<u64>) -> WasmiStep {
+        WasmiStep {
+            pc,
+            instr: instr.to_string(),
+            params,
+            fuel: 90_000 - pc as u64,
+            fuel_cost: 1,
+            depth: 1,
+            stack,
+            ..Default::default()
+        }
+    }
+
+    /// Moves `DATA`, placed at offset 0 by a data segment, from `src` to `dst`
+    fn mcopy_trace(dst: u64, src: u64) -> WasmiTrace {
+        let mut moved = vec![0u8; (src + 8) as usize];
+        moved[..8].copy_from_slice(&DATA);
+        let moved = moved[src as usize..].to_vec();
+        let steps = vec![
+            step(0, "i32.const", vec![dst], vec![]),
+            step(1, "i32.const", vec![src], vec![dst]),
+            step(2, "i32.const", vec![8], vec![dst, src]),
+            WasmiStep {
+                memory_changes: vec![(dst as u32, moved)],
+                ..step(3, "evm_mcopy", vec![], vec![dst, src, 8])
+            },
+            step(4, "end", vec![], vec![]),
+        ];
+        WasmiTrace {
+            fuel_consumed: steps.len() as u64,
+            data_segments: vec![(0, DATA.to_vec())],
+            function_calls: vec![GethExecTraceFunctionCall {
+                fn_index: 0,
+                max_stack_height: 3,
+                num_locals: 0,
+            }],
+            steps,
+            ..Default::default()
+        }
+    }
+
+    fn handle_trace(trace: WasmiTrace) -> Result<crate::circuit_input_builder::Block, Error> {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! {}),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.handle_block_from_provider(&block.eth_block, &WasmiTraces(vec![trace]))?;
+        Ok(builder.block)
+    }
+
+    #[test]
+    fn mcopy_moves_linear_memory() {
+        let block = handle_trace(mcopy_trace(32, 0)).unwrap();
+
+        let copy_event = &block.copy_events[0];
+        assert_eq!(copy_event.src_type, CopyDataType::Memory);
+        assert_eq!(copy_event.dst_type, CopyDataType::Memory);
+        assert_eq!(copy_event.src_id, NumberOrHash::Number(1));
+        assert_eq!(copy_event.dst_id, NumberOrHash::Number(1));
+        assert_eq!((copy_event.src_addr, copy_event.src_addr_end), (0, 8));
+        assert_eq!(copy_event.dst_addr, 32);
+        assert_eq!(
+            copy_event.bytes,
+            DATA.iter().map(|b| (*b, false)).collect::<Vec<_>>()
+        );
+
+        // the copy reads and writes are proven by the copy circuit, not by the step
+        let memory_ops = block
+            .container
+            .memory
+            .iter()
+            .filter(|op| op.rwc() >= copy_event.rw_counter_start)
+            .map(|op| (op.rw(), op.op().address().0, op.op().value()))
+            .collect::<Vec<_>>();
+        let expected = DATA
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, b)| [(RW::READ, idx, *b), (RW::WRITE, 32 + idx, *b)])
+            .collect::<Vec<_>>();
+        assert_eq!(memory_ops, expected);
+    }
+
+    #[test]
+    fn mcopy_backward_overlap() {
+        let block = handle_trace(mcopy_trace(0, 4)).unwrap();
+        assert_eq!(
+            block.copy_events[0].bytes,
+            [5, 6, 7, 8, 0, 0, 0, 0]
+                .iter()
+                .map(|b| (*b, false))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn mcopy_forward_overlap_fails() {
+        assert!(matches!(
+            handle_trace(mcopy_trace(4, 0)),
+            Err(Error::InvalidGethExecTrace(_))
+        ));
+    }
+}
@@ -50,6 +50,7 @@ impl Opcode for ReturnRevert {
             // copy event.
             let code_info = handle_create(
                 state,
+                step,
                 &mut exec_step,
                 Source {
                     id: call.call_id,
@@ -228,10 +229,14 @@ struct AccountCodeInfo {
 
 fn handle_create(
     state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
     step: &mut ExecStep,
     source: Source,
 ) -> Result<AccountCodeInfo, Error> {
-    let values = state.call_ctx()?.memory.0[source.offset..source.offset + source.length].to_vec();
+    // the deployed code is taken from the linear memory, handle_return checks it is a wasm module
+    let values = geth_step
+        .global_memory
+        .read_chunk(source.offset.into(), source.length.into());
     let keccak_hash = H256(keccak256(&values));
     let code_hash = CodeDB::hash(&values);
     let size = values.len();
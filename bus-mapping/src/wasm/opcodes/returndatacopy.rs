@@ -19,20 +19,10 @@ impl Opcode for Returndatacopy {
         let geth_step = &geth_steps[0];
         let mut exec_steps = vec![gen_returndatacopy_step(state, geth_step)?];
 
-        // reconstruction
-        let geth_step = &geth_steps[0];
-        let dest_offset = geth_step.stack.nth_last(0)?;
-        let offset = geth_step.stack.nth_last(1)?;
-        let size = geth_step.stack.nth_last(2)?;
-
-        // can we reduce this clone?
-        let return_data = state.call_ctx()?.return_data.clone();
-
-        let call_ctx = state.call_ctx_mut()?;
-        let memory = &mut call_ctx.memory;
-        memory.copy_from(dest_offset, offset, size, &return_data);
-
+        // the host copies the return data into the linear memory, the copy circuit proves it.
+        // An out of bound copy traps and is handled by `ErrorReturnDataOutOfBound`.
         let copy_event = gen_copy_event(state, geth_step)?;
+        state.sync_linear_memory_after_copy(&copy_event, &geth_steps[1])?;
         state.push_copy(&mut exec_steps[0], copy_event);
         Ok(exec_steps)
     }
@@ -43,21 +33,21 @@ fn gen_returndatacopy_step(
     geth_step: &GethExecStep,
 ) -> Result<ExecStep, Error> {
     let mut exec_step = state.new_step(geth_step)?;
-    let memory_offset = geth_step.stack.nth_last(0)?;
+    let length = geth_step.stack.nth_last(0)?;
     let data_offset = geth_step.stack.nth_last(1)?;
-    let length = geth_step.stack.nth_last(2)?;
+    let memory_offset = geth_step.stack.nth_last(2)?;
 
+    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), length)?;
     state.stack_read(
         &mut exec_step,
-        geth_step.stack.nth_last_filled(0),
-        memory_offset,
+        geth_step.stack.nth_last_filled(1),
+        data_offset,
     )?;
     state.stack_read(
         &mut exec_step,
-        geth_step.stack.nth_last_filled(1),
-        data_offset,
+        geth_step.stack.nth_last_filled(2),
+        memory_offset,
     )?;
-    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), length)?;
 
     let call_id = state.call()?.call_id;
     let call_ctx = state.call_ctx()?;
@@ -123,9 +113,9 @@ fn gen_copy_event(
     state: &mut CircuitInputStateRef,
     geth_step: &GethExecStep,
 ) -> Result<CopyEvent, Error> {
-    let dst_addr = geth_step.stack.nth_last(0)?.as_u64();
+    let length = geth_step.stack.nth_last(0)?.as_u64();
     let data_offset = geth_step.stack.nth_last(1)?.as_u64();
-    let length = geth_step.stack.nth_last(2)?.as_u64();
+    let dst_addr = geth_step.stack.nth_last(2)?.as_u64();
 
     let last_callee_return_data_offset = state.call()?.last_callee_return_data_offset;
     let last_callee_return_data_length = state.call()?.last_callee_return_data_length;
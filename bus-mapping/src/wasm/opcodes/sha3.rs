@@ -4,7 +4,7 @@ use crate::{
     },
     Error,
 };
-use eth_types::{GethExecStep, Word, U256, StackWord};
+use eth_types::{GethExecStep, Word, U256};
 use ethers_core::utils::keccak256;
 use eth_types::evm_types::MemoryAddress;
 
@@ -35,13 +35,7 @@ impl Opcode for Sha3 {
 
         let expected_sha3 = geth_steps[1].global_memory.read_u256(dest)?;
 
-        if size.gt(&StackWord::zero()) {
-            state
-                .call_ctx_mut()?
-                .memory
-                .extend_at_least(offset.as_usize() + size.as_usize());
-        }
-
+        // the hashed range is read from the linear memory, which the host does not expand
         let memory = geth_steps[0].global_memory.read_chunk(offset.low_u64().into(), size.as_usize().into());
 
         // keccak-256 hash of the given data in memory.
@@ -53,12 +47,12 @@ impl Opcode for Sha3 {
             &sha3,
         )?;
 
-        // Memory read operations
+        // Memory read operations of the copy event
         let rw_counter_start = state.block_ctx.rwc;
+        let mut copy_step = state.new_step(geth_step)?;
         let mut steps = Vec::with_capacity(size.as_usize());
         for (i, byte) in memory.iter().enumerate() {
-            // Read step
-            state.memory_read(&mut exec_step, (offset.as_usize() + i).into(), *byte)?;
+            state.memory_read(&mut copy_step, (offset.as_usize() + i).into(), *byte)?;
             steps.push((*byte, false));
         }
         state.block.sha3_inputs.push(memory);
@@ -233,6 +227,7 @@ pub mod sha3_tests {
             assert_eq!(Some(value), memory_view.get(idx));
             assert!(!is_code);
         }
+        assert!(builder.block.sha3_inputs.contains(&memory_view));
     }
 
     #[test]
@@ -0,0 +1,70 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{CallContextField, TransientStorageOp, RW},
+    Error,
+};
+use eth_types::{evm_types::MemoryAddress, GethExecStep, ToBigEndian, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::TLOAD`](crate::evm::OpcodeId::TLOAD)
+/// `OpcodeId`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Tload;
+
+impl Opcode for Tload {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let call_id = state.call()?.call_id;
+        let contract_addr = state.call()?.address;
+
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::TxId,
+            Word::from(state.tx_ctx.id()),
+        );
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::CalleeAddress,
+            contract_addr.to_word(),
+        );
+
+        let value_offset = geth_step.stack.nth_last(0)?;
+        let key_offset = geth_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), value_offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), key_offset)?;
+
+        let key = geth_step.global_memory.read_u256(key_offset)?;
+        let value = *state.sdb.get_transient_storage(&contract_addr, &key);
+        debug_assert_eq!(
+            geth_steps[1].global_memory.read_u256(value_offset)?,
+            value,
+            "tload values mismatch"
+        );
+
+        state.memory_read_n(
+            &mut exec_step,
+            MemoryAddress::from(key_offset.as_u64()),
+            &key.to_be_bytes(),
+        )?;
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            TransientStorageOp::new(state.tx_ctx.id(), contract_addr, key, value, value),
+        );
+        state.memory_write_n(
+            &mut exec_step,
+            MemoryAddress::from(value_offset.as_u64()),
+            &value.to_be_bytes(),
+        )?;
+
+        Ok(vec![exec_step])
+    }
+}
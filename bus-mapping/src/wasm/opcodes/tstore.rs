@@ -0,0 +1,240 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{CallContextField, TransientStorageOp},
+    Error,
+};
+use eth_types::{evm_types::MemoryAddress, GethExecStep, ToBigEndian, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::TSTORE`](crate::evm::OpcodeId::TSTORE)
+/// `OpcodeId`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Tstore;
+
+impl Opcode for Tstore {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let call = state.call()?.clone();
+        let contract_addr = call.address;
+
+        for (field, value) in [
+            (CallContextField::TxId, Word::from(state.tx_ctx.id())),
+            (CallContextField::IsStatic, Word::from(call.is_static as u8)),
+            (
+                CallContextField::RwCounterEndOfReversion,
+                Word::from(call.rw_counter_end_of_reversion),
+            ),
+            (CallContextField::IsPersistent, Word::from(call.is_persistent as u8)),
+            (CallContextField::CalleeAddress, contract_addr.to_word()),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value);
+        }
+
+        let value_offset = geth_step.stack.nth_last(0)?;
+        let key_offset = geth_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), value_offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), key_offset)?;
+
+        let key = geth_step.global_memory.read_u256(key_offset)?;
+        let value = geth_step.global_memory.read_u256(value_offset)?;
+        state.memory_read_n(
+            &mut exec_step,
+            MemoryAddress::from(key_offset.as_u64()),
+            &key.to_be_bytes(),
+        )?;
+        state.memory_read_n(
+            &mut exec_step,
+            MemoryAddress::from(value_offset.as_u64()),
+            &value.to_be_bytes(),
+        )?;
+
+        // reverted with the other reversible writes of the call, see `handle_reversion`
+        let value_prev = *state.sdb.get_transient_storage(&contract_addr, &key);
+        state.push_op_reversible(
+            &mut exec_step,
+            TransientStorageOp::new(state.tx_ctx.id(), contract_addr, key, value, value_prev),
+        )?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod tstore_tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::{ChainSpec, ExecState, Fork},
+        error::ExecError,
+        mock::BlockData,
+        operation::RW,
+        trace_provider::{WasmiStep, WasmiTrace, WasmiTraces},
+    };
+    use eth_types::{
+        bytecode, evm_types::OpcodeId, geth_types::GethData, GethExecTraceFunctionCall,
+    };
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext, MOCK_ACCOUNTS,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn step(pc: usize, instr: &str, params: Vec<u64>, stack: Vec<u64>) -> WasmiStep {
+        WasmiStep {
+            pc,
+            instr: instr.to_string(),
+            params,
+            fuel: 90_000 - pc as u64,
+            fuel_cost: 1,
+            depth: 1,
+            stack,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tstore_then_tload() {
+        let (key_offset, value_offset, loaded_offset) = (0u64, 32u64, 64u64);
+        let mut loaded = vec![0u8; 32];
+        loaded[31] = 0x2a;
+        // key 0 is left in the zeroed memory, the value is 0x2a
+        let steps = vec![
+            step(0, "i32.const", vec![value_offset + 31], vec![]),
+            step(1, "i32.const", vec![0x2a], vec![value_offset + 31]),
+            WasmiStep {
+                memory_changes: vec![(value_offset as u32 + 31, vec![0x2a])],
+                ..step(2, "i32.store8", vec![0, 0], vec![value_offset + 31, 0x2a])
+            },
+            step(3, "i32.const", vec![key_offset], vec![]),
+            step(4, "i32.const", vec![value_offset], vec![key_offset]),
+            step(5, "evm_tstore", vec![], vec![key_offset, value_offset]),
+            step(6, "i32.const", vec![key_offset], vec![]),
+            step(7, "i32.const", vec![loaded_offset], vec![key_offset]),
+            WasmiStep {
+                memory_changes: vec![(loaded_offset as u32, loaded)],
+                ..step(8, "evm_tload", vec![], vec![key_offset, loaded_offset])
+            },
+            step(9, "end", vec![], vec![]),
+        ];
+        let trace = WasmiTrace {
+            fuel_consumed: steps.len() as u64,
+            function_calls: vec![GethExecTraceFunctionCall {
+                fn_index: 0,
+                max_stack_height: 2,
+                num_locals: 0,
+            }],
+            steps,
+            ..Default::default()
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! {}),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block_from_provider(&block.eth_block, &WasmiTraces(vec![trace]))
+            .unwrap();
+
+        let container = &builder.block.container;
+        let expected_op = |rw, value_prev: u64| {
+            (
+                rw,
+                TransientStorageOp::new(
+                    1,
+                    MOCK_ACCOUNTS[0],
+                    Word::zero(),
+                    Word::from(0x2a),
+                    Word::from(value_prev),
+                ),
+            )
+        };
+        let transient_ops = container
+            .transient_storage
+            .iter()
+            .map(|op| (op.rw(), op.op().clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            transient_ops,
+            vec![expected_op(RW::WRITE, 0), expected_op(RW::READ, 0x2a)]
+        );
+
+        let tload_step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::TLOAD))
+            .unwrap();
+        let written = tload_step
+            .bus_mapping_instance
+            .iter()
+            .filter(|op_ref| op_ref.target() == crate::operation::Target::Memory)
+            .map(|op_ref| &container.memory[op_ref.as_usize()])
+            .filter(|op| op.rw() == RW::WRITE)
+            .map(|op| op.op().value())
+            .collect::<Vec<_>>();
+        assert_eq!(written, [0u8; 31].into_iter().chain([0x2a]).collect::<Vec<_>>());
+
+        // transient storage is dropped with the transaction
+        assert_eq!(
+            builder.sdb.get_transient_storage(&MOCK_ACCOUNTS[0], &Word::zero()),
+            &Word::zero()
+        );
+    }
+
+    #[test]
+    fn tstore_disabled_before_cancun() {
+        let steps = vec![
+            step(0, "i32.const", vec![0], vec![]),
+            step(1, "i32.const", vec![32], vec![0]),
+            WasmiStep {
+                trap: Some("invalid opcode".to_string()),
+                ..step(2, "evm_tstore", vec![], vec![0, 32])
+            },
+        ];
+        let trace = WasmiTrace {
+            fuel_consumed: 90_000,
+            trap: Some("invalid opcode".to_string()),
+            function_calls: vec![GethExecTraceFunctionCall {
+                fn_index: 0,
+                max_stack_height: 2,
+                num_locals: 0,
+            }],
+            steps,
+            ..Default::default()
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! {}),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.chain_spec = ChainSpec {
+            fork: Fork::Shanghai,
+            ..Default::default()
+        };
+        builder
+            .handle_block_from_provider(&block.eth_block, &WasmiTraces(vec![trace]))
+            .unwrap();
+
+        let tstore_step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::TSTORE))
+            .unwrap();
+        assert_eq!(tstore_step.error, Some(ExecError::InvalidOpcode));
+        assert!(builder.block.container.transient_storage.is_empty());
+    }
+}
@@ -1,8 +1,9 @@
 use eth_types::{GethExecStep};
-use eth_types::evm_types::OpcodeId;
+use eth_types::evm_types::{OpcodeId, StackAddress};
 
 use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
 use crate::Error;
+use crate::operation::CallContextField;
 
 use super::Opcode;
 
@@ -15,13 +16,62 @@ impl Opcode for WasmBreakOpcode {
         state: &mut CircuitInputStateRef,
         geth_steps: &[GethExecStep],
     ) -> Result<Vec<ExecStep>, Error> {
+        // a `return` ending the entry function can be the last step of the trace
         let current_step = &geth_steps[0];
-        let _next_step = &geth_steps[1];
 
-        let exec_step = state.new_step(current_step)?;
+        let mut exec_step = state.new_step(current_step)?;
 
         match current_step.op {
             OpcodeId::Return => {
+                let call_id = state.call()?.call_id;
+                let frame = state.call_ctx()?.function_frame();
+                state.call_context_read(
+                    &mut exec_step,
+                    call_id,
+                    CallContextField::FrameId,
+                    frame.frame_id.into(),
+                );
+                // returning from a called function restores the caller's frame, the entry
+                // function has no caller frame
+                if frame.frame_id != 0 {
+                    let call_ctx = state.call_ctx_mut()?;
+                    call_ctx.function_frames.pop();
+                    let caller = call_ctx.function_frame();
+                    for (field, value) in [
+                        (CallContextField::CallerFrameId, caller.frame_id.into()),
+                        (CallContextField::CallerFunctionIndex, caller.function_index.into()),
+                        (CallContextField::ReturnProgramCounter, frame.return_pc.into()),
+                        (CallContextField::LocalsBase, frame.locals_base.into()),
+                        (CallContextField::NumParams, frame.num_params.into()),
+                        (CallContextField::NumResults, frame.num_results.into()),
+                    ] {
+                        state.call_context_read(&mut exec_step, frame.frame_id, field, value);
+                    }
+                    state.call_context_write(
+                        &mut exec_step,
+                        call_id,
+                        CallContextField::FrameId,
+                        caller.frame_id.into(),
+                    );
+                    state.call_context_write(
+                        &mut exec_step,
+                        call_id,
+                        CallContextField::InternalFunctionId,
+                        caller.function_index.into(),
+                    );
+
+                    // the results replace the params on the caller's stack
+                    let results = (0..frame.num_results)
+                        .map(|i| current_step.stack.nth_last(i))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    for (i, value) in results.iter().enumerate() {
+                        state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(i), *value)?;
+                    }
+                    let stack_pointer = frame.locals_base + frame.num_params - frame.num_results;
+                    for (i, value) in results.into_iter().enumerate() {
+                        state.stack_write(&mut exec_step, StackAddress(stack_pointer + i), value)?;
+                    }
+                }
             }
             OpcodeId::Br => {
             }
@@ -35,3 +85,47 @@ impl Opcode for WasmBreakOpcode {
         Ok(vec![exec_step])
     }
 }
+
+#[cfg(test)]
+mod wasm_break_tests {
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Bytecode};
+    use mock::TestContext;
+    use wasm_encoder::ValType;
+
+    use crate::{
+        circuit_input_builder::{ExecState, ExecStep},
+        mock::BlockData,
+    };
+
+    fn handle_entry_function(code: Bytecode) -> Vec<ExecStep> {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        builder.block.txs()[0].steps().to_vec()
+    }
+
+    #[test]
+    fn entry_function_with_empty_body() {
+        let mut code = bytecode! {};
+        code.with_main_locals(vec![(2, ValType::I32)]);
+        let steps = handle_entry_function(code);
+        // begin tx, the end of the body and end tx
+        assert_eq!(steps.len(), 3);
+        assert_eq!(steps[0].exec_state, ExecState::BeginTx);
+        assert_eq!(steps[0].num_locals, 2);
+        assert_eq!(steps[2].exec_state, ExecState::EndTx);
+    }
+
+    #[test]
+    fn entry_function_return_is_last_step() {
+        let steps = handle_entry_function(bytecode! {
+            Return
+        });
+        assert_eq!(steps[1].exec_state, ExecState::Op(OpcodeId::Return));
+        assert_eq!(steps.last().unwrap().exec_state, ExecState::EndTx);
+    }
+}
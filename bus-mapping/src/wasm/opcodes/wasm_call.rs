@@ -1,13 +1,16 @@
-    use eth_types::{GethExecStep, ToU256, ToWord};
-use eth_types::evm_types::OpcodeId;
+use eth_types::GethExecStep;
+use eth_types::evm_types::{OpcodeId, MAX_WASM_RESULTS};
 
-use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep, FunctionFrame};
 use crate::Error;
 use crate::operation::CallContextField;
+use crate::wasm::function_types::function_type;
 
 use super::Opcode;
 
-///
+/// Pushes the frame of the called function. The frame id is the rw counter of the `call`, the
+/// caller's frame is saved in the call context rows of this id and restored by `return`.
+/// `call_indirect` reads the called function from the element of the table 0 it pops.
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct WasmCallOpcode;
 
@@ -21,29 +24,218 @@ impl Opcode for WasmCallOpcode {
 
         let mut exec_step = state.new_step(current_step)?;
 
-        match current_step.op {
-            OpcodeId::Call => {
-                let call_index = current_step.params[0];
-                let pc = next_step.pc;
-                state.call_context_write(
-                    &mut exec_step,
-                    state.call()?.call_id,
-                    CallContextField::InternalFunctionId,
-                    call_index.to_word(),
-                );
-                state.call_context_write(
-                    &mut exec_step,
-                    state.call()?.call_id,
-                    CallContextField::ProgramCounter,
-                    pc.0.to_u256(),
-                );
-            }
+        let (function_index, locals_base) = match current_step.op {
+            OpcodeId::Call => (current_step.params[0] as u32, current_step.stack.last_filled().0),
             OpcodeId::CallIndirect => {
-
+                // only the table 0 exists without the reference types proposal
+                let elem_index = current_step.stack.nth_last(0)?;
+                state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(0), elem_index)?;
+                let funcref = state.table_value(0, elem_index.low_u64() as u32)?;
+                state.table_read(&mut exec_step, 0, elem_index.low_u64() as u32, funcref)?;
+                if funcref.is_zero() {
+                    return Err(Error::InvalidGethExecTrace("call_indirect of a null table element"));
+                }
+                (
+                    (funcref.low_u64() - 1) as u32,
+                    current_step.stack.nth_last_filled(1).0,
+                )
             }
             _ => unreachable!("not supported opcode: {:?}", current_step.op)
         };
 
+        let call_id = state.call()?.call_id;
+        let caller = state.call_ctx()?.function_frame();
+        let (max_stack_height, num_locals) = state
+            .call_ctx()?
+            .function_calls
+            .iter()
+            .find(|function_call| function_call.fn_index == function_index)
+            .map(|function_call| (function_call.max_stack_height, function_call.num_locals))
+            .unwrap_or_default();
+        let code = state.code(state.call()?.code_hash)?;
+        let function_type = function_type(&code, function_index)?;
+        if function_type.results > MAX_WASM_RESULTS {
+            return Err(Error::InternalError("too many results of a wasm function"));
+        }
+        let frame = FunctionFrame {
+            frame_id: exec_step.rwc.0,
+            function_index,
+            max_stack_height,
+            num_locals,
+            return_pc: current_step.pc.0 + 1,
+            locals_base,
+            num_params: function_type.params,
+            num_results: function_type.results,
+        };
+
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::FrameId,
+            caller.frame_id.into(),
+        );
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::InternalFunctionId,
+            caller.function_index.into(),
+        );
+        for (field, value) in [
+            (CallContextField::CallerFrameId, caller.frame_id.into()),
+            (CallContextField::CallerFunctionIndex, caller.function_index.into()),
+            (CallContextField::ReturnProgramCounter, frame.return_pc.into()),
+            (CallContextField::LocalsBase, frame.locals_base.into()),
+            (CallContextField::NumParams, frame.num_params.into()),
+            (CallContextField::NumResults, frame.num_results.into()),
+            (CallContextField::NumLocals, frame.num_locals.into()),
+        ] {
+            state.call_context_write(&mut exec_step, frame.frame_id, field, value);
+        }
+        state.call_context_write(
+            &mut exec_step,
+            call_id,
+            CallContextField::FrameId,
+            frame.frame_id.into(),
+        );
+        state.call_context_write(
+            &mut exec_step,
+            call_id,
+            CallContextField::InternalFunctionId,
+            function_index.into(),
+        );
+        state.call_context_write(
+            &mut exec_step,
+            call_id,
+            CallContextField::ProgramCounter,
+            next_step.pc.0.into(),
+        );
+
+        state.call_ctx_mut()?.function_frames.push(frame);
+
         Ok(vec![exec_step])
     }
 }
+
+#[cfg(test)]
+mod wasm_call_tests {
+    use eth_types::{
+        bytecode, evm_types::OpcodeId, geth_types::GethData, GethExecTraceFunctionCall,
+    };
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+    use wasm_encoder::ValType;
+
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::{ExecError, WasmTrapKind},
+        geth_errors::GETH_ERR_WASM_STACK_HEIGHT_EXCEEDED,
+        mock::BlockData,
+        trace_provider::{WasmiStep, WasmiTrace, WasmiTraces},
+    };
+
+    #[test]
+    fn nested_calls_push_and_pop_frames() {
+        let mut code = bytecode! {
+            Call[0]
+        };
+        code.new_function(vec![], vec![], bytecode! {
+            Call[1]
+            Return
+        }, vec![]);
+        code.new_function(vec![], vec![], bytecode! {
+            Return
+        }, vec![]);
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let steps = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .filter(|step| {
+                matches!(step.exec_state, ExecState::Op(OpcodeId::Call | OpcodeId::Return))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(steps.len(), 4);
+        let (outer_frame_id, inner_frame_id) = (steps[0].rwc.0, steps[1].rwc.0);
+        assert_eq!(steps[0].frame_id, 0);
+        assert_eq!((steps[1].frame_id, steps[1].function_index), (outer_frame_id, 0));
+        assert_eq!((steps[2].frame_id, steps[2].function_index), (inner_frame_id, 1));
+        assert_eq!((steps[3].frame_id, steps[3].function_index), (outer_frame_id, 0));
+    }
+
+    #[test]
+    fn callee_locals_above_stack_height() {
+        let mut code = bytecode! {
+            I32Const[0]
+            Call[0]
+        };
+        code.new_function(vec![], vec![], bytecode! {}, vec![(1024, ValType::I64)]);
+        let step = |pc: usize, instr: &str, params: Vec<u64>, stack: Vec<u64>| WasmiStep {
+            pc,
+            instr: instr.to_string(),
+            params,
+            fuel: 90_000 - pc as u64,
+            fuel_cost: 1,
+            depth: 1,
+            stack,
+            ..Default::default()
+        };
+        // the caller's operand and the 1024 locals of the callee don't fit into the stack, the
+        // `call` traps
+        let steps = vec![
+            step(0, "i32.const", vec![0], vec![]),
+            WasmiStep {
+                trap: Some(GETH_ERR_WASM_STACK_HEIGHT_EXCEEDED.to_string()),
+                ..step(1, "call", vec![0], vec![0])
+            },
+        ];
+        let trace = WasmiTrace {
+            fuel_consumed: steps.len() as u64,
+            trap: Some(GETH_ERR_WASM_STACK_HEIGHT_EXCEEDED.to_string()),
+            function_calls: vec![
+                GethExecTraceFunctionCall {
+                    fn_index: 1,
+                    max_stack_height: 1,
+                    num_locals: 0,
+                },
+                GethExecTraceFunctionCall {
+                    fn_index: 0,
+                    max_stack_height: 0,
+                    num_locals: 1024,
+                },
+            ],
+            steps,
+            ..Default::default()
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block_from_provider(&block.eth_block, &WasmiTraces(vec![trace]))
+            .unwrap();
+
+        let call_step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| matches!(step.exec_state, ExecState::Op(OpcodeId::Call)))
+            .unwrap();
+        assert_eq!(
+            call_step.error,
+            Some(ExecError::WasmTrap(WasmTrapKind::StackHeightExceeded))
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use eth_types::evm_types::OpcodeId;
+use eth_types::GethExecStep;
+
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::wasm::memory::{WasmMemory, WasmMemoryAccess, WASM_MEMORY_CELL_SIZE};
+use crate::Error;
+
+use super::Opcode;
+
+/// Number of memory bytes accessed by a wasm load/store
+fn access_len(op: OpcodeId) -> u64 {
+    match op {
+        OpcodeId::I32Load8S
+        | OpcodeId::I32Load8U
+        | OpcodeId::I64Load8S
+        | OpcodeId::I64Load8U
+        | OpcodeId::I32Store8
+        | OpcodeId::I64Store8 => 1,
+        OpcodeId::I32Load16S
+        | OpcodeId::I32Load16U
+        | OpcodeId::I64Load16S
+        | OpcodeId::I64Load16U
+        | OpcodeId::I32Store16
+        | OpcodeId::I64Store16 => 2,
+        OpcodeId::I32Load
+        | OpcodeId::I64Load32S
+        | OpcodeId::I64Load32U
+        | OpcodeId::I32Store
+        | OpcodeId::I64Store32 => 4,
+        OpcodeId::I64Load | OpcodeId::I64Store => 8,
+        _ => unreachable!("not supported opcode: {:?}", op),
+    }
+}
+
+/// Memory access of `step`, the effective address is the popped address plus the memarg offset
+fn memory_access(step: &GethExecStep, raw_address: u64) -> (u64, WasmMemoryAccess) {
+    let address = raw_address + step.params.first().copied().unwrap_or_default();
+    (address, WasmMemoryAccess::new(address, access_len(step.op)))
+}
+
+/// Wasm loads read the one or two memory cells the loaded bytes span
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WasmLoadOpcode;
+
+impl Opcode for WasmLoadOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let current_step = &geth_steps[0];
+        let next_step = &geth_steps[1];
+
+        let mut exec_step = state.new_step(current_step)?;
+
+        let raw_address = current_step.stack.nth_last(0)?;
+        state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(0), raw_address)?;
+
+        let (_, access) = memory_access(current_step, raw_address.low_u64());
+        let memory = WasmMemory::from(&state.call_ctx()?.memory);
+        for cell_index in access.cell_indexes() {
+            state.memory_read_cell(&mut exec_step, cell_index, memory.cell(cell_index))?;
+        }
+
+        let value = next_step.stack.nth_last(0)?;
+        state.stack_write(&mut exec_step, next_step.stack.nth_last_filled(0), value)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+/// Wasm stores read the one or two memory cells the stored bytes span and write them back
+/// updated
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WasmStoreOpcode;
+
+impl Opcode for WasmStoreOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let current_step = &geth_steps[0];
+
+        let mut exec_step = state.new_step(current_step)?;
+
+        let value = current_step.stack.nth_last(0)?;
+        state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(0), value)?;
+        let raw_address = current_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(1), raw_address)?;
+
+        let (address, access) = memory_access(current_step, raw_address.low_u64());
+        let mut memory = WasmMemory::from(&state.call_ctx()?.memory);
+        for cell_index in access.cell_indexes() {
+            state.memory_read_cell(&mut exec_step, cell_index, memory.cell(cell_index))?;
+        }
+        memory.write(address, access_len(current_step.op), value.low_u64());
+        for cell_index in access.cell_indexes() {
+            state.memory_write_cell(&mut exec_step, cell_index, memory.cell(cell_index))?;
+        }
+
+        // keep the byte memory of the call in sync with the written cells
+        let start = (access.start_cell_index * WASM_MEMORY_CELL_SIZE) as usize;
+        let end = ((access.end_cell_index + 1) * WASM_MEMORY_CELL_SIZE) as usize;
+        let call_memory = &mut state.call_ctx_mut()?.memory;
+        call_memory.extend_at_least(end);
+        call_memory.0[start..end].copy_from_slice(&memory.to_bytes()[start..end]);
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod wasm_load_store_tests {
+    use super::*;
+
+    #[test]
+    fn access_len_matches_opcode_width() {
+        assert_eq!(access_len(OpcodeId::I32Load8U), 1);
+        assert_eq!(access_len(OpcodeId::I64Store16), 2);
+        assert_eq!(access_len(OpcodeId::I64Load32S), 4);
+        assert_eq!(access_len(OpcodeId::I64Store), 8);
+    }
+}
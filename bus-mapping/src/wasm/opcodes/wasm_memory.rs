@@ -0,0 +1,101 @@
+use eth_types::{GethExecStep, StackWord};
+use eth_types::evm_types::{OpcodeId, WASM_PAGE_SIZE};
+
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::Error;
+
+use super::Opcode;
+
+/// `memory.size` pushes the number of pages of the linear memory, `memory.grow` pops a number of
+/// pages and pushes the previous size, or `-1` if the memory would exceed the page limit of the
+/// block.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WasmMemoryOpcode;
+
+impl Opcode for WasmMemoryOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let current_step = &geth_steps[0];
+        let next_step = &geth_steps[1];
+
+        let mut exec_step = state.new_step(current_step)?;
+        let pages = state.call_ctx()?.memory.len() as u64 / WASM_PAGE_SIZE;
+
+        let result = match current_step.op {
+            OpcodeId::CurrentMemory => pages,
+            OpcodeId::GrowMemory => {
+                let delta = current_step.stack.nth_last(0)?;
+                state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(0), delta)?;
+                let max_pages = state.block.max_wasm_memory_pages;
+                match pages.checked_add(delta.as_u64()) {
+                    Some(new_pages) if new_pages <= max_pages => pages,
+                    _ => u32::MAX as u64,
+                }
+            }
+            _ => unreachable!("not supported opcode: {:?}", current_step.op)
+        };
+        let value = next_step.stack.nth_last(0)?;
+        if value != StackWord::from(result) {
+            return Err(Error::InvalidGethExecTrace("memory pages don't match the memory page limit"));
+        }
+        state.stack_write(&mut exec_step, next_step.stack.nth_last_filled(0), value)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod wasm_memory_tests {
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, MAX_WASM_MEMORY_PAGES},
+        geth_types::GethData,
+        Bytecode, StackWord,
+    };
+    use mock::TestContext;
+
+    use crate::{circuit_input_builder::ExecState, mock::BlockData, operation::RW};
+
+    use super::*;
+
+    fn grow_result(code: Bytecode) -> StackWord {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| matches!(step.exec_state, ExecState::Op(OpcodeId::GrowMemory)))
+            .unwrap();
+        let push = &builder.block.container.stack[step.bus_mapping_instance[1].as_usize()];
+        assert_eq!(push.rw(), RW::WRITE);
+        *push.op().value()
+    }
+
+    #[test]
+    fn grow_within_limit_returns_previous_size() {
+        let code = bytecode! {
+            I32Const[1]
+            GrowMemory
+            Drop
+        };
+        assert_eq!(grow_result(code), StackWord::from(1));
+    }
+
+    #[test]
+    fn grow_above_limit_fails() {
+        let code = bytecode! {
+            I32Const[MAX_WASM_MEMORY_PAGES]
+            GrowMemory
+            Drop
+        };
+        assert_eq!(grow_result(code), StackWord::from(u32::MAX));
+    }
+}
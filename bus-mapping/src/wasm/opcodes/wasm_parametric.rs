@@ -0,0 +1,100 @@
+use eth_types::GethExecStep;
+
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::Error;
+
+use super::Opcode;
+
+/// `drop` only moves the stack pointer, the dropped slot is never read again before it is
+/// overwritten, so no stack operation is needed.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WasmDropOpcode;
+
+impl Opcode for WasmDropOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        Ok(vec![state.new_step(&geth_steps[0])?])
+    }
+}
+
+/// `select` reads its condition only. The first value already sits in the result slot and is
+/// left there when the condition is non-zero, otherwise the second value is read and copied into
+/// the result slot.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WasmSelectOpcode;
+
+impl Opcode for WasmSelectOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let current_step = &geth_steps[0];
+        let next_step = &geth_steps[1];
+
+        let mut exec_step = state.new_step(current_step)?;
+
+        let cond = current_step.stack.nth_last(0)?;
+        state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(0), cond)?;
+        if cond.is_zero() {
+            let value = current_step.stack.nth_last(1)?;
+            state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(1), value)?;
+            state.stack_write(
+                &mut exec_step,
+                next_step.stack.nth_last_filled(0),
+                next_step.stack.nth_last(0)?,
+            )?;
+        }
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod wasm_parametric_tests {
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Bytecode};
+    use mock::TestContext;
+
+    use crate::{circuit_input_builder::ExecState, mock::BlockData};
+
+    fn rw_counts(code: Bytecode, op: OpcodeId) -> Vec<usize> {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        builder.block.txs()[0]
+            .steps()
+            .iter()
+            .filter(|step| step.exec_state == ExecState::Op(op))
+            .map(|step| step.bus_mapping_instance.len())
+            .collect()
+    }
+
+    #[test]
+    fn drop_has_no_rw_operations() {
+        let code = bytecode! {
+            I32Const[1]
+            Drop
+        };
+        assert_eq!(rw_counts(code, OpcodeId::Drop), vec![0]);
+    }
+
+    #[test]
+    fn select_rw_operations_depend_on_condition() {
+        let code = bytecode! {
+            I32Const[1]
+            I32Const[2]
+            I32Const[1]
+            Select
+            I32Const[3]
+            I32Const[0]
+            Select
+            Drop
+        };
+        assert_eq!(rw_counts(code, OpcodeId::Select), vec![1, 3]);
+    }
+}
@@ -0,0 +1,170 @@
+use eth_types::GethExecStep;
+use eth_types::evm_types::{OpcodeId, StackAddress, MAX_WASM_TAIL_CALL_PARAMS};
+
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep, FunctionFrame};
+use crate::Error;
+use crate::operation::CallContextField;
+use crate::wasm::function_types::function_type;
+
+use super::Opcode;
+
+/// Replaces the frame of the current function by the one of the tail-called function. The frame
+/// id and the saved caller's frame are kept, the params of the callee are moved in place of the
+/// params of the replaced function so that `return` moves the results to the caller's stack.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WasmReturnCallOpcode;
+
+impl Opcode for WasmReturnCallOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let current_step = &geth_steps[0];
+        let next_step = &geth_steps[1];
+
+        let mut exec_step = state.new_step(current_step)?;
+
+        match current_step.op {
+            OpcodeId::ReturnCall => {
+                let call_id = state.call()?.call_id;
+                let function_index = current_step.params[0] as u32;
+                let replaced = state.call_ctx()?.function_frame();
+                let (max_stack_height, num_locals) = state
+                    .call_ctx()?
+                    .function_calls
+                    .iter()
+                    .find(|function_call| function_call.fn_index == function_index)
+                    .map(|function_call| (function_call.max_stack_height, function_call.num_locals))
+                    .unwrap_or_default();
+                let code = state.code(state.call()?.code_hash)?;
+                let function_type = function_type(&code, function_index)?;
+                if function_type.params > MAX_WASM_TAIL_CALL_PARAMS {
+                    return Err(Error::InternalError("too many params of a tail-called wasm function"));
+                }
+                // the entry function has no caller's stack to move the params to
+                let locals_base = if replaced.frame_id != 0 {
+                    if function_type.results != replaced.num_results {
+                        return Err(Error::InternalError("tail-called wasm function results mismatch"));
+                    }
+                    replaced.locals_base + replaced.num_params - function_type.params
+                } else {
+                    current_step.stack.last_filled().0
+                };
+                let frame = FunctionFrame {
+                    function_index,
+                    max_stack_height,
+                    num_locals,
+                    locals_base,
+                    num_params: function_type.params,
+                    num_results: function_type.results,
+                    ..replaced.clone()
+                };
+
+                state.call_context_read(
+                    &mut exec_step,
+                    call_id,
+                    CallContextField::FrameId,
+                    frame.frame_id.into(),
+                );
+                if frame.frame_id != 0 {
+                    for (field, value) in [
+                        (CallContextField::LocalsBase, replaced.locals_base.into()),
+                        (CallContextField::NumParams, replaced.num_params.into()),
+                        (CallContextField::NumResults, replaced.num_results.into()),
+                    ] {
+                        state.call_context_read(&mut exec_step, frame.frame_id, field, value);
+                    }
+                    for (field, value) in [
+                        (CallContextField::LocalsBase, frame.locals_base.into()),
+                        (CallContextField::NumParams, frame.num_params.into()),
+                    ] {
+                        state.call_context_write(&mut exec_step, frame.frame_id, field, value);
+                    }
+                }
+                state.call_context_write(
+                    &mut exec_step,
+                    call_id,
+                    CallContextField::InternalFunctionId,
+                    function_index.into(),
+                );
+                state.call_context_write(
+                    &mut exec_step,
+                    call_id,
+                    CallContextField::ProgramCounter,
+                    next_step.pc.0.into(),
+                );
+
+                if frame.frame_id != 0 {
+                    let params = (0..frame.num_params)
+                        .map(|i| current_step.stack.nth_last(i))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    for (i, value) in params.iter().enumerate() {
+                        state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(i), *value)?;
+                    }
+                    for (i, value) in params.into_iter().enumerate() {
+                        state.stack_write(&mut exec_step, StackAddress(frame.locals_base + i), value)?;
+                    }
+                }
+
+                let call_ctx = state.call_ctx_mut()?;
+                call_ctx.function_frames.pop();
+                call_ctx.function_frames.push(frame);
+            }
+            OpcodeId::ReturnCallIndirect => {
+
+            }
+            _ => unreachable!("not supported opcode: {:?}", current_step.op)
+        };
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod wasm_return_call_tests {
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData};
+    use mock::TestContext;
+    use wasm_encoder::ValType;
+
+    use crate::{circuit_input_builder::ExecState, mock::BlockData};
+
+    #[test]
+    fn tail_call_replaces_frame() {
+        let mut code = bytecode! {
+            Call[0]
+            Drop
+        };
+        code.new_function(vec![], vec![ValType::I32], bytecode! {
+            I32Const[3]
+            ReturnCall[1]
+        }, vec![]);
+        code.new_function(vec![ValType::I32], vec![ValType::I32], bytecode! {
+            GetLocal[0]
+            Return
+        }, vec![]);
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let steps = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .filter(|step| {
+                matches!(
+                    step.exec_state,
+                    ExecState::Op(OpcodeId::Call | OpcodeId::ReturnCall | OpcodeId::Return)
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(steps.len(), 3);
+        let frame_id = steps[0].rwc.0;
+        assert_eq!((steps[1].frame_id, steps[1].function_index), (frame_id, 0));
+        assert_eq!((steps[2].frame_id, steps[2].function_index), (frame_id, 1));
+        // the param is moved in place of the replaced function's stack
+        assert_eq!(steps[1].bus_mapping_instance.len(), 1 + 3 + 2 + 2 + 2);
+    }
+}
@@ -0,0 +1,90 @@
+//! Sections of a WASM module, read from the code of a call.
+
+use std::ops::Range;
+
+use crate::Error;
+
+const WASM_PREAMBLE_LEN: usize = 8;
+
+pub(crate) const WASM_TYPE_SECTION_ID: u8 = 1;
+pub(crate) const WASM_IMPORT_SECTION_ID: u8 = 2;
+pub(crate) const WASM_FUNCTION_SECTION_ID: u8 = 3;
+pub(crate) const WASM_ELEM_SECTION_ID: u8 = 9;
+pub(crate) const WASM_DATA_SECTION_ID: u8 = 11;
+
+/// Reads the LEB128 number at `offset` and moves `offset` past it.
+pub(crate) fn read_leb128(code: &[u8], offset: &mut usize, is_signed: bool) -> Result<i64, Error> {
+    let mut value = 0i64;
+    let mut shift = 0;
+    loop {
+        let byte = *code
+            .get(*offset)
+            .ok_or(Error::InternalError("leb128 out of code bounds"))?;
+        *offset += 1;
+        if shift >= 64 {
+            return Err(Error::InternalError("leb128 too long"));
+        }
+        value |= ((byte & 0x7f) as i64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            if is_signed && shift < 64 && byte & 0x40 != 0 {
+                value |= -1 << shift;
+            }
+            return Ok(value);
+        }
+    }
+}
+
+/// Range of the body of the section `section_id` in `code`, `None` if the module has no such
+/// section.
+pub(crate) fn section_body(code: &[u8], section_id: u8) -> Result<Option<Range<usize>>, Error> {
+    let mut offset = WASM_PREAMBLE_LEN;
+    while offset < code.len() {
+        let id = code[offset];
+        offset += 1;
+        let len = read_leb128(code, &mut offset, false)? as usize;
+        let body = offset..offset + len;
+        if body.end > code.len() {
+            return Err(Error::InternalError("section out of code bounds"));
+        }
+        if id == section_id {
+            return Ok(Some(body));
+        }
+        offset = body.end;
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Module made of `sections`, bodies must be shorter than 128 bytes
+    pub(crate) fn module(sections: &[(u8, Vec<u8>)]) -> Vec<u8> {
+        let mut code = b"\0asm\x01\0\0\0".to_vec();
+        for (id, body) in sections {
+            code.push(*id);
+            code.push(body.len() as u8);
+            code.extend_from_slice(body);
+        }
+        code
+    }
+
+    #[test]
+    fn signed_and_unsigned_leb128() {
+        let code = [0xe5, 0x8e, 0x26, 0x7f, 0x80, 0x7f];
+        let mut offset = 0;
+        assert_eq!(read_leb128(&code, &mut offset, false).unwrap(), 624485);
+        assert_eq!(read_leb128(&code, &mut offset, true).unwrap(), -1);
+        assert_eq!(read_leb128(&code, &mut offset, true).unwrap(), -128);
+        assert_eq!(offset, code.len());
+        assert!(read_leb128(&code, &mut offset, false).is_err());
+    }
+
+    #[test]
+    fn section_out_of_code_bounds() {
+        let mut code = module(&[(WASM_TYPE_SECTION_ID, vec![0x00])]);
+        code.pop();
+        assert!(section_body(&code, WASM_DATA_SECTION_ID).is_err());
+    }
+}
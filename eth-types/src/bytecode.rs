@@ -377,6 +377,9 @@ impl Bytecode {
             OpcodeId::BASEFEE => ("_evm_basefee", 1),
             OpcodeId::SLOAD => ("_evm_sload", 2),
             OpcodeId::SSTORE => ("_evm_sstore", 2),
+            OpcodeId::TLOAD => ("_evm_tload", 2),
+            OpcodeId::TSTORE => ("_evm_tstore", 2),
+            OpcodeId::MCOPY => ("_evm_mcopy", 3),
             OpcodeId::GAS => ("_evm_gas", 1),
             OpcodeId::PC => ("_evm_pc", 1),
             OpcodeId::MSIZE => ("_evm_msize", 1),
@@ -385,7 +388,7 @@ impl Bytecode {
             OpcodeId::LOG2 => ("_evm_log2", 4),
             OpcodeId::LOG3 => ("_evm_log3", 5),
             OpcodeId::LOG4 => ("_evm_log4", 6),
-            OpcodeId::CREATE => ("_evm_create", 3),
+            OpcodeId::CREATE => ("_evm_create", 4),
             OpcodeId::CALL => ("_evm_call", 8),
             OpcodeId::CALLCODE => ("_evm_callcode", 8),
             OpcodeId::DELEGATECALL => ("_evm_delegatecall", 7),
@@ -471,8 +474,8 @@ impl Bytecode {
             OpcodeId::I64Store32 => Instruction::I64Store32,
 */
 
-            //OpcodeId::GrowMemory => Instruction::MemoryGrow,
-            //OpcodeId::CurrentMemory => Instruction::MemorySize,
+            OpcodeId::GrowMemory => Instruction::MemoryGrow(0),
+            OpcodeId::CurrentMemory => Instruction::MemorySize(0),
 
             OpcodeId::I32Add => Instruction::I32Add,
             OpcodeId::I64Add => Instruction::I64Add,
@@ -587,6 +590,7 @@ impl Bytecode {
             OpcodeId::SetLocal => Instruction::LocalSet(val as u32),
             OpcodeId::TeeLocal => Instruction::LocalTee(val as u32),
             OpcodeId::Call => Instruction::Call(val as u32),
+            OpcodeId::ReturnCall => Instruction::ReturnCall(val as u32),
             OpcodeId::Br => Instruction::Br(val as u32),
             OpcodeId::BrIf => Instruction::BrIf(val as u32),
             _ => {
@@ -1038,6 +1042,9 @@ impl_other_opcodes! {
     (op_mstore8, MSTORE8, offset: O, value: V),
     (op_sload, SLOAD, offset: O),
     (op_sstore, SSTORE, offset: O, value: V),
+    (op_tload, TLOAD, offset: O),
+    (op_tstore, TSTORE, offset: O, value: V),
+    (op_mcopy, MCOPY, dest_offset: D, offset: B, size: C),
     (op_jump, JUMP, counter: C),
     (op_jumpi, JUMPI, counter: C), // branch not included
     (op_pc, PC),
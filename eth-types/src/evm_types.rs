@@ -63,6 +63,19 @@ impl fmt::Debug for Gas {
 
 /// Maximum bytecode size to permit for a contract.
 pub const MAX_CODE_SIZE: u64 = 24576;
+/// Maximum stack height (locals and operands) of a WASM function, the engine traps above it. It is
+/// one below the 1024 stack slots of the circuits: a full stack still has a slot for the value of
+/// the opcode trapping on it.
+pub const MAX_WASM_STACK_HEIGHT: u64 = 1023;
+/// Size of a page of the WASM linear memory.
+pub const WASM_PAGE_SIZE: u64 = 0x10000;
+/// Maximum number of pages of the WASM linear memory: modules can't declare more, `memory.grow`
+/// above it fails and accesses out of the allocated pages trap.
+pub const MAX_WASM_MEMORY_PAGES: u64 = 256;
+/// Maximum number of results (multi-value) of a WASM function returning to a caller frame.
+pub const MAX_WASM_RESULTS: usize = 8;
+/// Maximum number of params of a WASM function moved in place of the caller's frame by a tail call.
+pub const MAX_WASM_TAIL_CALL_PARAMS: usize = 8;
 /// This constant ((2^32 - 1) * 32) is the highest number that can be used without overflowing the
 /// square operation of gas calculation.
 /// <https://github.com/ethereum/go-ethereum/blob/e6b6a8b738069ad0579f6798ee59fde93ed13b43/core/vm/gas_table.go#L38>
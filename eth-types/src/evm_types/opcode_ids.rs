@@ -47,6 +47,9 @@ pub enum OpcodeId {
     BASEFEE,
     SLOAD,
     SSTORE,
+    TLOAD,
+    TSTORE,
+    MCOPY,
     GAS,
 
     LOG0,
@@ -77,6 +80,8 @@ pub enum OpcodeId {
     Return,
     Call,
     CallIndirect,
+    ReturnCall,
+    ReturnCallIndirect,
     Drop,
     Select,
     GetLocal,
@@ -358,6 +363,7 @@ impl OpcodeId {
             OpcodeId::RETURNDATASIZE | OpcodeId::RETURNDATACOPY | OpcodeId::BLOCKHASH | OpcodeId::COINBASE |
             OpcodeId::TIMESTAMP | OpcodeId::NUMBER | OpcodeId::DIFFICULTY | OpcodeId::GASLIMIT |
             OpcodeId::CHAINID | OpcodeId::BASEFEE | OpcodeId::SLOAD | OpcodeId::SSTORE | OpcodeId::GAS |
+            OpcodeId::TLOAD | OpcodeId::TSTORE | OpcodeId::MCOPY |
             OpcodeId::PC | OpcodeId::MSIZE | OpcodeId::LOG0 | OpcodeId::LOG1 | OpcodeId::LOG2 |
             OpcodeId::LOG3 | OpcodeId::LOG4 | OpcodeId::CREATE | OpcodeId::CALL | OpcodeId::CALLCODE |
             OpcodeId::DELEGATECALL | OpcodeId::CREATE2 | OpcodeId::STATICCALL | OpcodeId::REVERT |
@@ -453,6 +459,8 @@ impl OpcodeId {
             OpcodeId::Return => 0x0f,
             OpcodeId::Call => 0x10,
             OpcodeId::CallIndirect => 0x11,
+            OpcodeId::ReturnCall => 0x12,
+            OpcodeId::ReturnCallIndirect => 0x13,
             OpcodeId::Drop => 0x1a,
             OpcodeId::Select => 0x1b,
             OpcodeId::GetLocal => 0x20,
@@ -658,6 +666,9 @@ impl OpcodeId {
             OpcodeId::DELEGATECALL => 0xec,
             OpcodeId::STATICCALL => 0xed,
             OpcodeId::SELFDESTRUCT => 0xef,
+            OpcodeId::TLOAD => 0xf0,
+            OpcodeId::TSTORE => 0xf1,
+            OpcodeId::MCOPY => 0xf2,
             _ => 0x00,
         }
     }
@@ -698,6 +709,9 @@ impl OpcodeId {
             OpcodeId::BASEFEE => GasCost::QUICK,
             OpcodeId::SLOAD => GasCost::ZERO,
             OpcodeId::SSTORE => GasCost::ZERO,
+            OpcodeId::TLOAD => GasCost::WARM_ACCESS,
+            OpcodeId::TSTORE => GasCost::WARM_ACCESS,
+            OpcodeId::MCOPY => GasCost::FASTEST,
             OpcodeId::PC => GasCost::QUICK,
             OpcodeId::MSIZE => GasCost::QUICK,
             OpcodeId::GAS => GasCost::QUICK,
@@ -789,6 +803,9 @@ impl OpcodeId {
             OpcodeId::BASEFEE => (1, 1024),
             OpcodeId::SLOAD => (0, 1023),
             OpcodeId::SSTORE => (0, 1022),
+            OpcodeId::TLOAD => (0, 1022),
+            OpcodeId::TSTORE => (0, 1022),
+            OpcodeId::MCOPY => (0, 1021),
             OpcodeId::PC => (1, 1024),
             OpcodeId::MSIZE => (1, 1024),
             OpcodeId::GAS => (1, 1024),
@@ -828,6 +845,7 @@ impl OpcodeId {
                 | OpcodeId::RETURNDATACOPY
                 | OpcodeId::CODECOPY
                 | OpcodeId::EXTCODECOPY
+                | OpcodeId::MCOPY
         )
     }
 
@@ -918,6 +936,8 @@ impl From<u8> for OpcodeId {
             0x0f => OpcodeId::Return,
             0x10 => OpcodeId::Call,
             0x11 => OpcodeId::CallIndirect,
+            0x12 => OpcodeId::ReturnCall,
+            0x13 => OpcodeId::ReturnCallIndirect,
             0x1a => OpcodeId::Drop,
             0x1b => OpcodeId::Select,
             0x20 => OpcodeId::GetLocal,
@@ -1124,6 +1144,9 @@ impl From<u8> for OpcodeId {
             0xee => OpcodeId::STATICCALL,
             #[cfg(not(feature = "scroll"))]
             0xef => OpcodeId::SELFDESTRUCT,
+            0xf0 => OpcodeId::TLOAD,
+            0xf1 => OpcodeId::TSTORE,
+            0xf2 => OpcodeId::MCOPY,
             // invalid opcode
             _ => OpcodeId::INVALID(value)
         }
@@ -1152,6 +1175,8 @@ impl FromStr for OpcodeId {
             "return" => OpcodeId::Return,
             "call" => OpcodeId::Call,
             "call_indirect" => OpcodeId::CallIndirect,
+            "return_call" => OpcodeId::ReturnCall,
+            "return_call_indirect" => OpcodeId::ReturnCallIndirect,
             "drop" => OpcodeId::Drop,
             "select" => OpcodeId::Select,
             "get_local" => OpcodeId::GetLocal,
@@ -1343,6 +1368,9 @@ impl FromStr for OpcodeId {
             "evm_basefee" => OpcodeId::BASEFEE,
             "evm_sload" => OpcodeId::SLOAD,
             "evm_sstore" => OpcodeId::SSTORE,
+            "evm_tload" => OpcodeId::TLOAD,
+            "evm_tstore" => OpcodeId::TSTORE,
+            "evm_mcopy" => OpcodeId::MCOPY,
             "evm_gas" => OpcodeId::GAS,
             "evm_msize" => OpcodeId::MSIZE,
             "evm_pc" => OpcodeId::PC,
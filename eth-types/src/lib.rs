@@ -21,6 +21,7 @@ pub mod bytecode;
 pub mod evm_types;
 pub mod geth_types;
 pub mod sign_types;
+pub mod wasm_trace;
 
 pub use bytecode::Bytecode;
 pub use error::Error;
@@ -684,6 +685,9 @@ pub struct GethExecTrace {
     /// Globals.
     #[serde(rename = "functionCalls")]
     pub function_calls: Vec<GethExecTraceFunctionCall>,
+    /// Version of the wasm specific fields
+    #[serde(rename = "wasmTraceVersion")]
+    pub wasm_trace_version: u32,
 }
 
 #[derive(Deserialize)]
@@ -717,6 +721,14 @@ pub struct GethExecTraceInternal {
     /// Globals.
     #[serde(rename = "functionCalls")]
     pub function_calls: Vec<GethExecTraceFunctionCall>,
+    /// Version of the wasm specific fields
+    #[serde(rename = "wasmTraceVersion")]
+    #[serde(default = "default_wasm_trace_version")]
+    pub wasm_trace_version: u32,
+}
+
+fn default_wasm_trace_version() -> u32 {
+    wasm_trace::WASM_TRACE_VERSION
 }
 
 impl<'de> Deserialize<'de> for GethExecTrace {
@@ -753,6 +765,7 @@ impl<'de> Deserialize<'de> for GethExecTrace {
             struct_logs: s.struct_logs,
             globals: s.globals,
             function_calls: s.function_calls,
+            wasm_trace_version: s.wasm_trace_version,
         })
     }
 }
@@ -878,6 +891,7 @@ mod tests {
                 global_memory: Memory::new(),
                 globals: Vec::new(),
                 function_calls: Vec::new(),
+                wasm_trace_version: wasm_trace::WASM_TRACE_VERSION,
                 struct_logs: vec![
                     GethExecStep {
                         pc: ProgramCounter(0),
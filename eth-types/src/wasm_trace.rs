@@ -0,0 +1,191 @@
+//! Wasm specific fields of an execution trace and their validation.
+
+use std::collections::HashSet;
+
+use crate::{
+    evm_types::{Memory, MAX_WASM_MEMORY_PAGES, WASM_PAGE_SIZE},
+    GethExecStep, GethExecTrace, GethExecTraceFunctionCall, GethExecTraceGlobal,
+};
+
+/// Version of the wasm trace fields produced by the tracer. Traces without a version are
+/// version 1.
+pub const WASM_TRACE_VERSION: u32 = 1;
+
+/// Failure of [`WasmTraceExtension::validate`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WasmTraceError {
+    /// The trace was produced for another version of the wasm fields
+    UnsupportedVersion(u32),
+    /// Initial global memory ends above the max wasm memory
+    GlobalMemoryOutOfBounds {
+        /// Offset of the memory
+        offset: u32,
+        /// Length of the memory
+        len: usize,
+    },
+    /// Memory change of a step ends above the max wasm memory
+    StepMemoryOutOfBounds {
+        /// Position of the step in the trace
+        step: usize,
+        /// Offset of the memory change
+        offset: u32,
+        /// Length of the memory change
+        len: usize,
+    },
+    /// Global index listed more than once
+    DuplicateGlobal(u32),
+    /// Global recorded at a lower pc than the one listed before it
+    GlobalPcNotMonotonic {
+        /// Position of the global in the trace
+        position: usize,
+    },
+    /// Steps were executed without any function call
+    MissingFunctionCalls,
+}
+
+/// Wasm specific fields of a [`GethExecTrace`]
+#[derive(Clone, Copy, Debug)]
+pub struct WasmTraceExtension<'a> {
+    /// Version of the fields
+    pub version: u32,
+    /// Memory initialized by the data segments
+    pub global_memory: &'a Memory,
+    /// Globals
+    pub globals: &'a [GethExecTraceGlobal],
+    /// Function calls, the entry function first
+    pub function_calls: &'a [GethExecTraceFunctionCall],
+    /// Steps, their memory changes are checked against the max wasm memory
+    pub struct_logs: &'a [GethExecStep],
+}
+
+impl<'a> WasmTraceExtension<'a> {
+    /// Checks the wasm fields before circuit inputs are built from them
+    pub fn validate(&self) -> Result<(), WasmTraceError> {
+        if self.version != WASM_TRACE_VERSION {
+            return Err(WasmTraceError::UnsupportedVersion(self.version));
+        }
+        if !in_wasm_memory(self.global_memory) {
+            return Err(WasmTraceError::GlobalMemoryOutOfBounds {
+                offset: self.global_memory.1,
+                len: self.global_memory.0.len(),
+            });
+        }
+        for (step, geth_step) in self.struct_logs.iter().enumerate() {
+            if let Some(memory) = geth_step.memory.iter().find(|m| !in_wasm_memory(m)) {
+                return Err(WasmTraceError::StepMemoryOutOfBounds {
+                    step,
+                    offset: memory.1,
+                    len: memory.0.len(),
+                });
+            }
+        }
+
+        let mut global_indexes = HashSet::new();
+        for (position, global) in self.globals.iter().enumerate() {
+            if !global_indexes.insert(global.index) {
+                return Err(WasmTraceError::DuplicateGlobal(global.index));
+            }
+            if position > 0 && global.pc.0 < self.globals[position - 1].pc.0 {
+                return Err(WasmTraceError::GlobalPcNotMonotonic { position });
+            }
+        }
+
+        if !self.struct_logs.is_empty() && self.function_calls.is_empty() {
+            return Err(WasmTraceError::MissingFunctionCalls);
+        }
+        Ok(())
+    }
+}
+
+fn in_wasm_memory(memory: &Memory) -> bool {
+    memory.1 as u64 + memory.0.len() as u64 <= MAX_WASM_MEMORY_PAGES * WASM_PAGE_SIZE
+}
+
+impl GethExecTrace {
+    /// Wasm specific fields of the trace
+    pub fn wasm_extension(&self) -> WasmTraceExtension<'_> {
+        WasmTraceExtension {
+            version: self.wasm_trace_version,
+            global_memory: &self.global_memory,
+            globals: &self.globals,
+            function_calls: &self.function_calls,
+            struct_logs: &self.struct_logs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod wasm_trace_tests {
+    use super::*;
+    use crate::evm_types::ProgramCounter;
+
+    fn global(pc: usize, index: u32) -> GethExecTraceGlobal {
+        GethExecTraceGlobal {
+            pc: ProgramCounter(pc),
+            index,
+            op: "global_get".to_string(),
+            value: 0,
+        }
+    }
+
+    fn extension<'a>(
+        global_memory: &'a Memory,
+        globals: &'a [GethExecTraceGlobal],
+    ) -> WasmTraceExtension<'a> {
+        WasmTraceExtension {
+            version: WASM_TRACE_VERSION,
+            global_memory,
+            globals,
+            function_calls: &[],
+            struct_logs: &[],
+        }
+    }
+
+    #[test]
+    fn valid_extension() {
+        let memory = Memory::from_bytes_with_offset(vec![1, 2, 3], 0x100000);
+        let globals = [global(1, 0), global(1, 1), global(5, 2)];
+        assert_eq!(extension(&memory, &globals).validate(), Ok(()));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let memory = Memory::new();
+        let mut extension = extension(&memory, &[]);
+        extension.version = WASM_TRACE_VERSION + 1;
+        assert_eq!(
+            extension.validate(),
+            Err(WasmTraceError::UnsupportedVersion(WASM_TRACE_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn global_memory_above_max_memory() {
+        let offset = (MAX_WASM_MEMORY_PAGES * WASM_PAGE_SIZE - 1) as u32;
+        let memory = Memory::from_bytes_with_offset(vec![1, 2], offset);
+        assert_eq!(
+            extension(&memory, &[]).validate(),
+            Err(WasmTraceError::GlobalMemoryOutOfBounds { offset, len: 2 })
+        );
+    }
+
+    #[test]
+    fn duplicate_global() {
+        let memory = Memory::new();
+        let globals = [global(1, 0), global(2, 0)];
+        assert_eq!(
+            extension(&memory, &globals).validate(),
+            Err(WasmTraceError::DuplicateGlobal(0))
+        );
+    }
+
+    #[test]
+    fn global_pc_not_monotonic() {
+        let memory = Memory::new();
+        let globals = [global(2, 0), global(1, 1)];
+        assert_eq!(
+            extension(&memory, &globals).validate(),
+            Err(WasmTraceError::GlobalPcNotMonotonic { position: 1 })
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use std::ops::Range;
+
+use ethers_core::types::U256 as Word;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "circuit")]
+use bus_mapping::state_db::CodeDB;
+#[cfg(feature = "circuit")]
+use eth_types::ToWord;
+
+use crate::{
+    error::{error_index_out_of_bounds, remap_error_to_compute_value_at, Error},
+    leb128::helpers::leb128_compute_sn,
+    types::{ImportDescType, LimitType, Mutability, WasmSection},
+    validation::{types::SectionReport, validator::module_sections},
+};
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WasmBytecode {
+    pub bytes: Vec<u8>,
+    pub code_hash: Word,
+    /// sections layout, computed once at construction (up to the first structural error)
+    pub sections: Vec<SectionReport>,
+    /// body ranges (locals + instructions, without the body size prefix) of the code section
+    pub function_bodies: Vec<Range<usize>>,
+}
+
+impl WasmBytecode {
+    /// Construct from bytecode bytes hashed the way the `CodeDB` does, available with the
+    /// `circuit` feature
+    #[cfg(feature = "circuit")]
+    pub fn new(bytes: Vec<u8>) -> Self {
+        let code_hash = CodeDB::hash(&bytes).to_word();
+        Self::with_code_hash(bytes, code_hash)
+    }
+
+    /// Construct from bytecode bytes and their code hash, as computed by the caller
+    pub fn with_code_hash(bytes: Vec<u8>, code_hash: Word) -> Self {
+        let sections = module_sections(&bytes);
+        let function_bodies = sections
+            .iter()
+            .find(|s| s.section == WasmSection::Code)
+            .and_then(|s| compute_function_bodies(&bytes, s).ok())
+            .unwrap_or_default();
+        Self {
+            code_hash,
+            bytes,
+            sections,
+            function_bodies,
+        }
+    }
+
+    /// Construct from a textual module, available with the `wat` feature. Identifiers (`$name`)
+    /// are encoded into a `name` custom section, which the circuit does not support.
+    #[cfg(all(feature = "wat", feature = "circuit"))]
+    pub fn from_wat(wat: &str) -> Result<Self, wat::Error> {
+        Ok(Self::new(wat::parse_str(wat)?))
+    }
+
+    /// Poseidon hash of the bytecode, independent of the default code hash
+    #[cfg(feature = "circuit")]
+    pub fn poseidon_code_hash(&self) -> Word {
+        CodeDB::hash_poseidon(&self.bytes).to_word()
+    }
+
+    /// get byte value
+    pub fn get(&self, idx: usize) -> Option<&u8> {
+        self.bytes.get(idx)
+    }
+
+    /// sections in bytecode order
+    pub fn sections(&self) -> &[SectionReport] {
+        &self.sections
+    }
+
+    /// byte range of the whole section (id, length and body)
+    pub fn section(&self, section: WasmSection) -> Option<Range<usize>> {
+        self.section_report(section)
+            .map(|s| s.section_start_offset..s.section_end_offset + 1)
+    }
+
+    /// byte range of the section body
+    pub fn section_body(&self, section: WasmSection) -> Option<Range<usize>> {
+        self.section_report(section)
+            .map(|s| s.section_body_start_offset..s.section_end_offset + 1)
+    }
+
+    /// byte ranges of function bodies from the code section
+    pub fn function_bodies(&self) -> &[Range<usize>] {
+        &self.function_bodies
+    }
+
+    /// init values of the globals defined by the global section, in section order
+    pub fn global_init_values(&self) -> Result<Vec<u64>, Error> {
+        match self.section_body(WasmSection::Global) {
+            Some(body) => decode_global_init_values(&self.bytes, body.start),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// whether the globals defined by the global section are mutable, in section order
+    pub fn global_mutabilities(&self) -> Result<Vec<bool>, Error> {
+        match self.section_body(WasmSection::Global) {
+            Some(body) => decode_global_mutabilities(&self.bytes, body.start),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// `(function index, locals count)` of the functions defined by the code section. Function
+    /// indexes start after the imported functions, locals do not include params.
+    pub fn function_locals(&self) -> Result<Vec<(u64, u64)>, Error> {
+        let imported_funcs_count = self.imported_funcs_count()?;
+        match self.section_body(WasmSection::Code) {
+            Some(body) => decode_function_locals(&self.bytes, body.start, imported_funcs_count),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// `(function index, type index)` of the functions declared by the function section. Function
+    /// indexes start after the imported functions.
+    pub fn function_type_indexes(&self) -> Result<Vec<(u64, u64)>, Error> {
+        let imported_funcs_count = self.imported_funcs_count()?;
+        match self.section_body(WasmSection::Function) {
+            Some(body) => {
+                decode_function_type_indexes(&self.bytes, body.start, imported_funcs_count)
+            }
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Type index of the function `function_index`, `None` for imported or undefined functions
+    pub fn function_type_index(&self, function_index: u64) -> Result<Option<u64>, Error> {
+        Ok(self
+            .function_type_indexes()?
+            .into_iter()
+            .find(|(index, _)| *index == function_index)
+            .map(|(_, type_index)| type_index))
+    }
+
+    fn imported_funcs_count(&self) -> Result<u64, Error> {
+        match self.section_body(WasmSection::Import) {
+            Some(body) => decode_imported_funcs_count(&self.bytes, body.start),
+            None => Ok(0),
+        }
+    }
+
+    fn section_report(&self, section: WasmSection) -> Option<&SectionReport> {
+        self.sections.iter().find(|s| s.section == section)
+    }
+}
+
+fn compute_function_bodies(
+    bytes: &[u8],
+    code_section: &SectionReport,
+) -> Result<Vec<Range<usize>>, Error> {
+    let mut offset = code_section.section_body_start_offset;
+    let (funcs_count, last_byte_offset) = leb128_compute_sn(bytes, false, offset)
+        .map_err(remap_error_to_compute_value_at(offset))?;
+    offset = last_byte_offset + 1;
+    let mut function_bodies = Vec::with_capacity(funcs_count as usize);
+    for _ in 0..funcs_count {
+        let (body_len, last_byte_offset) = leb128_compute_sn(bytes, false, offset)
+            .map_err(remap_error_to_compute_value_at(offset))?;
+        let body_start_offset = last_byte_offset + 1;
+        let body_end_offset = body_start_offset + body_len as usize;
+        if body_end_offset > code_section.section_end_offset + 1 {
+            return Err(error_index_out_of_bounds(body_end_offset));
+        }
+        function_bodies.push(body_start_offset..body_end_offset);
+        offset = body_end_offset;
+    }
+    Ok(function_bodies)
+}
+
+/// Init values of the global section body starting at `body_start_offset`. Init expressions are a
+/// single `i32.const`/`i64.const` whose immediate is decoded as unsigned LEB128, the way the
+/// global section chip decodes it.
+pub fn decode_global_init_values(
+    bytes: &[u8],
+    body_start_offset: usize,
+) -> Result<Vec<u64>, Error> {
+    let (items_count, last_byte_offset) = leb128_compute_sn(bytes, false, body_start_offset)
+        .map_err(remap_error_to_compute_value_at(body_start_offset))?;
+    let mut offset = last_byte_offset + 1;
+    let mut init_values = Vec::with_capacity(items_count as usize);
+    for _ in 0..items_count {
+        // global type, mutability and init opcode
+        offset += 3;
+        let (init_value, last_byte_offset) = leb128_compute_sn(bytes, false, offset)
+            .map_err(remap_error_to_compute_value_at(offset))?;
+        init_values.push(init_value);
+        // init value and expression delimiter
+        offset = last_byte_offset + 2;
+    }
+    Ok(init_values)
+}
+
+/// Mutabilities of the global section body starting at `body_start_offset`, `true` for `var`
+pub fn decode_global_mutabilities(
+    bytes: &[u8],
+    body_start_offset: usize,
+) -> Result<Vec<bool>, Error> {
+    let (items_count, last_byte_offset) = leb128_compute_sn(bytes, false, body_start_offset)
+        .map_err(remap_error_to_compute_value_at(body_start_offset))?;
+    let mut offset = last_byte_offset + 1;
+    let mut mutabilities = Vec::with_capacity(items_count as usize);
+    for _ in 0..items_count {
+        let mutability = bytes
+            .get(offset + 1)
+            .copied()
+            .ok_or_else(|| error_index_out_of_bounds(offset + 1))?;
+        mutabilities.push(mutability == Mutability::Var as u8);
+        // global type, mutability and init opcode
+        offset += 3;
+        let (_, last_byte_offset) = leb128_compute_sn(bytes, false, offset)
+            .map_err(remap_error_to_compute_value_at(offset))?;
+        // init value and expression delimiter
+        offset = last_byte_offset + 2;
+    }
+    Ok(mutabilities)
+}
+
+/// Number of function imports of the import section body starting at `body_start_offset`
+fn decode_imported_funcs_count(bytes: &[u8], body_start_offset: usize) -> Result<u64, Error> {
+    let leb128_end = |offset: usize| -> Result<usize, Error> {
+        leb128_compute_sn(bytes, false, offset)
+            .map(|(_, last_byte_offset)| last_byte_offset + 1)
+            .map_err(remap_error_to_compute_value_at(offset))
+    };
+    let byte_at = |offset: usize| -> Result<u8, Error> {
+        bytes
+            .get(offset)
+            .copied()
+            .ok_or_else(|| error_index_out_of_bounds(offset))
+    };
+    let limits_end = |offset: usize| -> Result<usize, Error> {
+        let min_end = leb128_end(offset + 1)?;
+        if LimitType::try_from(byte_at(offset)?)?.has_max() {
+            leb128_end(min_end)
+        } else {
+            Ok(min_end)
+        }
+    };
+    let (items_count, last_byte_offset) = leb128_compute_sn(bytes, false, body_start_offset)
+        .map_err(remap_error_to_compute_value_at(body_start_offset))?;
+    let mut offset = last_byte_offset + 1;
+    let mut funcs_count = 0;
+    for _ in 0..items_count {
+        // module name and field name
+        for _ in 0..2 {
+            let (name_len, last_byte_offset) = leb128_compute_sn(bytes, false, offset)
+                .map_err(remap_error_to_compute_value_at(offset))?;
+            offset = last_byte_offset + 1 + name_len as usize;
+        }
+        let importdesc_type = ImportDescType::try_from(byte_at(offset)?)?;
+        offset += 1;
+        offset = match importdesc_type {
+            ImportDescType::Typeidx => {
+                funcs_count += 1;
+                leb128_end(offset)?
+            }
+            // reftype precedes the limits
+            ImportDescType::TableType => limits_end(offset + 1)?,
+            ImportDescType::MemType => limits_end(offset)?,
+            // valtype and mutability
+            ImportDescType::GlobalType => offset + 2,
+        };
+    }
+    Ok(funcs_count)
+}
+
+/// `(function index, locals count)` of the func bodies of the code section body starting at
+/// `body_start_offset`, locals count is the sum of the local repetition counts of a body
+pub fn decode_function_locals(
+    bytes: &[u8],
+    body_start_offset: usize,
+    imported_funcs_count: u64,
+) -> Result<Vec<(u64, u64)>, Error> {
+    let (funcs_count, last_byte_offset) = leb128_compute_sn(bytes, false, body_start_offset)
+        .map_err(remap_error_to_compute_value_at(body_start_offset))?;
+    let mut offset = last_byte_offset + 1;
+    let mut function_locals = Vec::with_capacity(funcs_count as usize);
+    for body_index in 0..funcs_count {
+        let (body_len, last_byte_offset) = leb128_compute_sn(bytes, false, offset)
+            .map_err(remap_error_to_compute_value_at(offset))?;
+        let body_end_offset = last_byte_offset + 1 + body_len as usize;
+        let (transitions_count, last_byte_offset) =
+            leb128_compute_sn(bytes, false, last_byte_offset + 1)
+                .map_err(remap_error_to_compute_value_at(last_byte_offset + 1))?;
+        offset = last_byte_offset + 1;
+        let mut locals_count = 0;
+        for _ in 0..transitions_count {
+            let (repetition_count, last_byte_offset) = leb128_compute_sn(bytes, false, offset)
+                .map_err(remap_error_to_compute_value_at(offset))?;
+            locals_count += repetition_count;
+            // local type
+            offset = last_byte_offset + 2;
+        }
+        function_locals.push((imported_funcs_count + body_index, locals_count));
+        offset = body_end_offset;
+    }
+    Ok(function_locals)
+}
+
+/// `(function index, type index)` of the typeidx items of the function section body starting at
+/// `body_start_offset`
+pub fn decode_function_type_indexes(
+    bytes: &[u8],
+    body_start_offset: usize,
+    imported_funcs_count: u64,
+) -> Result<Vec<(u64, u64)>, Error> {
+    let (items_count, last_byte_offset) = leb128_compute_sn(bytes, false, body_start_offset)
+        .map_err(remap_error_to_compute_value_at(body_start_offset))?;
+    let mut offset = last_byte_offset + 1;
+    let mut type_indexes = Vec::with_capacity(items_count as usize);
+    for item_index in 0..items_count {
+        let (type_index, last_byte_offset) = leb128_compute_sn(bytes, false, offset)
+            .map_err(remap_error_to_compute_value_at(offset))?;
+        type_indexes.push((imported_funcs_count + item_index, type_index));
+        offset = last_byte_offset + 1;
+    }
+    Ok(type_indexes)
+}
+
+#[cfg(feature = "circuit")]
+impl From<&eth_types::bytecode::Bytecode> for WasmBytecode {
+    fn from(b: &eth_types::bytecode::Bytecode) -> Self {
+        WasmBytecode::new(b.to_vec())
+    }
+}
@@ -0,0 +1,139 @@
+use num_traits::checked_pow;
+use wasmparser::{ImportSectionReader, TypeRef};
+
+use crate::{
+    consts::{
+        MAX_LEB128_BYTES, PASS_THROUGH_CUSTOM_SECTION_NAMES,
+        PASS_THROUGH_CUSTOM_SECTION_NAME_PREFIX, PRODUCERS_FIELD_NAMES,
+    },
+    error::{remap_error_to_compute_value_at, remap_error_to_invalid_byte_value_at, Error},
+    leb128::helpers::leb128_compute_sn,
+    types::{
+        Leb128BytesCountType, NewWbOffsetType, ProducersField, ProducersSection,
+        SectionLengthType, WasmSection,
+    },
+};
+
+pub fn digit_char_to_number(ch: &char) -> u8 {
+    *ch as u8 - 48
+}
+
+pub fn wasm_compute_section_len(
+    wb: &[u8],
+    len_start_index: usize,
+) -> Result<(SectionLengthType, Leb128BytesCountType), Error> {
+    let mut section_len: usize = 0;
+    let mut i = len_start_index;
+    loop {
+        let byte = wb.get(i).ok_or(Error::IndexOutOfBoundsSimple)?;
+        let mut byte_val: u32 = (byte & 0b1111111) as u32;
+        let pow = checked_pow(0b10000000, i - len_start_index).ok_or(Error::ComputationFailed)?;
+        byte_val = byte_val * pow;
+        section_len += byte_val as usize;
+        if byte & 0b10000000 == 0 {
+            break;
+        }
+        i += 1;
+        if i - len_start_index >= MAX_LEB128_BYTES {
+            return Err(Error::Leb128MaxBytes);
+        }
+    }
+    Ok((section_len, (i - len_start_index + 1) as u8))
+}
+
+/// Name (len followed by the name bytes) starting at `wb_offset` and the offset after it
+pub fn wasm_name(wb: &[u8], wb_offset: usize) -> Result<(&[u8], NewWbOffsetType), Error> {
+    let (name_len, name_len_leb_bytes_count) = wasm_compute_section_len(wb, wb_offset)?;
+    let name_start_offset = wb_offset + name_len_leb_bytes_count as usize;
+    let name = wb
+        .get(name_start_offset..name_start_offset + name_len)
+        .ok_or(Error::IndexOutOfBoundsSimple)?;
+    Ok((name, name_start_offset + name_len))
+}
+
+/// Name of the custom section which body starts at `section_body_start_offset`
+pub fn wasm_custom_section_name(
+    wb: &[u8],
+    section_body_start_offset: usize,
+) -> Result<&[u8], Error> {
+    Ok(wasm_name(wb, section_body_start_offset)?.0)
+}
+
+fn wasm_utf8_name(wb: &[u8], wb_offset: usize) -> Result<(String, NewWbOffsetType), Error> {
+    let (name, new_wb_offset) =
+        wasm_name(wb, wb_offset).map_err(remap_error_to_compute_value_at(wb_offset))?;
+    let name =
+        std::str::from_utf8(name).map_err(remap_error_to_invalid_byte_value_at(wb_offset))?;
+    Ok((name.to_string(), new_wb_offset))
+}
+
+/// Parses the `producers` custom section which body starts at `section_body_start_offset` and
+/// ends with `wb`: a vec of fields, each of them a known and unique field name followed by a vec
+/// of `(name, version)` values
+pub fn wasm_producers_section(
+    wb: &[u8],
+    section_body_start_offset: usize,
+) -> Result<ProducersSection, Error> {
+    let (_, mut offset) = wasm_name(wb, section_body_start_offset)
+        .map_err(remap_error_to_compute_value_at(section_body_start_offset))?;
+    let (fields_count, fields_count_leb_len) =
+        leb128_compute_sn(wb, false, offset).map_err(remap_error_to_compute_value_at(offset))?;
+    offset += fields_count_leb_len;
+
+    let mut producers = ProducersSection::default();
+    for _ in 0..fields_count {
+        let field_name_offset = offset;
+        let (name, new_offset) = wasm_utf8_name(wb, offset)?;
+        if !PRODUCERS_FIELD_NAMES.contains(&name.as_str())
+            || producers.fields.iter().any(|field| field.name == name)
+        {
+            return Err(Error::InvalidByteValueAt(field_name_offset));
+        }
+        offset = new_offset;
+        let (values_count, values_count_leb_len) = leb128_compute_sn(wb, false, offset)
+            .map_err(remap_error_to_compute_value_at(offset))?;
+        offset += values_count_leb_len;
+
+        let mut values = vec![];
+        for _ in 0..values_count {
+            let (value_name, new_offset) = wasm_utf8_name(wb, offset)?;
+            let (version, new_offset) = wasm_utf8_name(wb, new_offset)?;
+            values.push((value_name, version));
+            offset = new_offset;
+        }
+        producers.fields.push(ProducersField { name, values });
+    }
+    if offset != wb.len() {
+        return Err(Error::InvalidByteValueAt(offset));
+    }
+
+    Ok(producers)
+}
+
+pub fn is_pass_through_custom_section_name(name: &[u8]) -> bool {
+    name.starts_with(PASS_THROUGH_CUSTOM_SECTION_NAME_PREFIX)
+        || PASS_THROUGH_CUSTOM_SECTION_NAMES.contains(&name)
+}
+
+fn import_section_error(e: wasmparser::BinaryReaderError) -> Error {
+    Error::FatalInvalidArgumentValue(format!("import section: {}", e))
+}
+
+/// Functions declared by a section body: imported functions for the import section, bodies for
+/// the code section
+pub fn section_func_count(section: WasmSection, body: &[u8]) -> Result<usize, Error> {
+    match section {
+        WasmSection::Import => {
+            let reader = ImportSectionReader::new(body, 0).map_err(import_section_error)?;
+            let mut func_count = 0;
+            for import in reader {
+                if let TypeRef::Func(_) = import.map_err(import_section_error)?.ty {
+                    func_count += 1;
+                }
+            }
+            Ok(func_count)
+        }
+        WasmSection::Code => Ok(leb128_compute_sn(body, false, 0)?.0 as usize),
+        _ => Ok(0),
+    }
+}
@@ -0,0 +1,47 @@
+use crate::types::WasmSection;
+
+pub const MAX_LEB128_BYTES: usize = 5;
+pub static WASM_MAGIC_PREFIX: &'static str = "\0asm";
+pub static WASM_MAGIC_PREFIX_LEN: usize = WASM_MAGIC_PREFIX.len();
+pub static WASM_MAGIC_PREFIX_START_INDEX: usize = 0;
+pub static WASM_MAGIC_PREFIX_END_INDEX: usize = WASM_MAGIC_PREFIX_LEN - 1;
+pub static WASM_VERSION_PREFIX: &'static str = "1000";
+pub static WASM_VERSION_PREFIX_LEN: usize = WASM_VERSION_PREFIX.len();
+pub static WASM_VERSION_PREFIX_START_INDEX: usize = WASM_MAGIC_PREFIX_END_INDEX + 1;
+pub static WASM_VERSION_PREFIX_END_INDEX: usize =
+    WASM_VERSION_PREFIX_START_INDEX + WASM_VERSION_PREFIX_LEN - 1;
+pub static WASM_SECTIONS_START_INDEX: usize = WASM_VERSION_PREFIX_END_INDEX + 1;
+pub static WASM_BLOCK_END: u8 = 0xB;
+pub static WASM_BLOCKTYPE_DELIMITER: i32 = 0x40;
+/// Prefix of the SIMD (v128) instructions, followed by a leb sub-opcode and its immediates
+pub static WASM_SIMD_INSTRUCTION_PREFIX: u8 = 0xFD;
+/// Default cap of the code section block_level (a function body itself is level 1)
+pub const MAX_BLOCK_LEVEL: u64 = 256;
+/// Default cap of a code section func body declared size (in bytes)
+pub const MAX_FUNC_BODY_SIZE: u64 = 128 * 1024;
+/// Default cap of the number of locals declared by a code section func body
+pub const MAX_FUNC_LOCALS: u64 = 50_000;
+/// Default cap of a data section segment len (in bytes)
+pub const MAX_DATA_SEGMENT_SIZE: u64 = 128 * 1024;
+/// Default cap of the total len of the data section segments (in bytes)
+pub const MAX_DATA_SIZE: u64 = 1024 * 1024;
+/// Default cap of the number of params of a type section functype
+pub const MAX_FUNC_PARAMS: u64 = 1000;
+/// Default cap of the module name length of an import section item (in bytes)
+pub const MAX_IMPORT_MOD_NAME_LEN: u64 = 256;
+/// Default cap of the field name length of an import section item (in bytes)
+pub const MAX_IMPORT_NAME_LEN: u64 = 256;
+pub const WASM_SECTION_ID_MAX: usize = WasmSection::DataCount as usize;
+
+// TODO make it differ from custom section id (which is 0 too)
+pub const SECTION_ID_DEFAULT: i32 = 0;
+
+/// Custom sections skipped without parsing their bodies: DWARF debug info and source maps
+pub const PASS_THROUGH_CUSTOM_SECTION_NAME_PREFIX: &[u8] = b".debug_";
+pub const PASS_THROUGH_CUSTOM_SECTION_NAMES: &[&[u8]] = &[b"sourceMappingURL"];
+/// Custom section parsed and validated as toolchain metadata
+pub const PRODUCERS_CUSTOM_SECTION_NAME: &[u8] = b"producers";
+pub const PRODUCERS_FIELD_NAMES: &[&str] = &["language", "processed-by", "sdk"];
+
+/// Instance values exposed per bytecode: bytecode_number, code_hash, error_code
+pub const WASM_MODULE_OUTPUT_LEN: usize = 3;
@@ -0,0 +1,153 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use wasmparser::{
+    BinaryReaderError, CodeSectionReader, DataSectionReader, ElementSectionReader,
+    ExportSectionReader, FunctionSectionReader, GlobalSectionReader, ImportSectionReader,
+    MemorySectionReader, Parser, Payload, SectionLimited, TableSectionReader, TypeSectionReader,
+};
+
+use crate::{
+    common::is_pass_through_custom_section_name,
+    consts::{PRODUCERS_CUSTOM_SECTION_NAME, WASM_SECTIONS_START_INDEX},
+    error::Error,
+    types::WasmSection,
+};
+
+/// Section as decoded by wasmparser. Ranges are bytecode offsets with exclusive ends.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceSection {
+    pub section: WasmSection,
+    /// whole section: id, length and body
+    pub range: Range<usize>,
+    pub body: Range<usize>,
+    /// items of the body after the items count. Code section items are function bodies without
+    /// their size prefix, start section has no items.
+    pub items: Vec<Range<usize>>,
+}
+
+/// Structured view of a module produced by wasmparser, independent of the hand-rolled offset
+/// walking done by the chips
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceModule {
+    pub sections: Vec<ReferenceSection>,
+}
+
+impl ReferenceModule {
+    /// Section starting (section id byte) at `offset`
+    pub fn section_at(&self, offset: usize) -> Option<&ReferenceSection> {
+        self.sections.iter().find(|s| s.range.start == offset)
+    }
+}
+
+fn decode_error(e: impl std::fmt::Display) -> Error {
+    Error::FatalInvalidArgumentValue(format!("reference decoder: {}", e))
+}
+
+/// Malformed item of a section body, at the offset the reader failed at
+fn item_error(e: BinaryReaderError) -> Error {
+    Error::InvalidByteValueAt(e.offset())
+}
+
+fn items_of<'a, T: wasmparser::FromReader<'a>>(
+    reader: Result<SectionLimited<'a, T>, BinaryReaderError>,
+) -> Result<Vec<Range<usize>>, Error> {
+    let reader = reader.map_err(item_error)?;
+    let end = reader.range().end;
+    let starts = reader
+        .into_iter_with_offsets()
+        .map(|item| item.map(|(offset, _)| offset))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(item_error)?;
+    Ok(starts
+        .iter()
+        .enumerate()
+        .map(|(idx, start)| *start..starts.get(idx + 1).copied().unwrap_or(end))
+        .collect())
+}
+
+/// Items of the section `body` of `section`, see [`ReferenceSection::items`]. Both
+/// [`decode_module`] and [`validate_module`](crate::validation::validate_module)
+/// walk the section bodies with it, a malformed item fails with the offset it was read at.
+pub fn decode_section_items(
+    bytes: &[u8],
+    section: WasmSection,
+    body: Range<usize>,
+) -> Result<Vec<Range<usize>>, Error> {
+    let data = bytes
+        .get(body.clone())
+        .ok_or_else(|| decode_error("section body out of bytecode"))?;
+    let offset = body.start;
+    match section {
+        WasmSection::Type => items_of(TypeSectionReader::new(data, offset)),
+        WasmSection::Import => items_of(ImportSectionReader::new(data, offset)),
+        WasmSection::Function => items_of(FunctionSectionReader::new(data, offset)),
+        WasmSection::Table => items_of(TableSectionReader::new(data, offset)),
+        WasmSection::Memory => items_of(MemorySectionReader::new(data, offset)),
+        WasmSection::Global => items_of(GlobalSectionReader::new(data, offset)),
+        WasmSection::Export => items_of(ExportSectionReader::new(data, offset)),
+        WasmSection::Element => items_of(ElementSectionReader::new(data, offset)),
+        WasmSection::Code => CodeSectionReader::new(data, offset)
+            .and_then(|reader| {
+                reader
+                    .into_iter()
+                    .map(|body| body.map(|body| body.range()))
+                    .collect()
+            })
+            .map_err(item_error),
+        WasmSection::Data => items_of(DataSectionReader::new(data, offset)),
+        WasmSection::Start | WasmSection::Custom => Ok(vec![]),
+        _ => Err(Error::FatalUnsupportedValue(format!(
+            "reference decoder: unsupported section {:?}",
+            section
+        ))),
+    }
+}
+
+/// Decodes `bytes` with wasmparser (without validation). Fails on malformed modules and on
+/// sections the circuit doesn't support.
+pub fn decode_module(bytes: &[u8]) -> Result<ReferenceModule, Error> {
+    let mut module = ReferenceModule::default();
+    let mut section_start = WASM_SECTIONS_START_INDEX;
+    for payload in Parser::new(0).parse_all(bytes) {
+        let payload = payload.map_err(decode_error)?;
+        let section = match &payload {
+            Payload::TypeSection(_) => WasmSection::Type,
+            Payload::ImportSection(_) => WasmSection::Import,
+            Payload::FunctionSection(_) => WasmSection::Function,
+            Payload::TableSection(_) => WasmSection::Table,
+            Payload::MemorySection(_) => WasmSection::Memory,
+            Payload::GlobalSection(_) => WasmSection::Global,
+            Payload::ExportSection(_) => WasmSection::Export,
+            Payload::StartSection { .. } => WasmSection::Start,
+            Payload::ElementSection(_) => WasmSection::Element,
+            Payload::CodeSectionStart { .. } => WasmSection::Code,
+            Payload::DataSection(_) => WasmSection::Data,
+            Payload::CustomSection(r)
+                if is_pass_through_custom_section_name(r.name().as_bytes())
+                    || r.name().as_bytes() == PRODUCERS_CUSTOM_SECTION_NAME =>
+            {
+                WasmSection::Custom
+            }
+            // the code section items are walked with the section
+            Payload::CodeSectionEntry(_) | Payload::Version { .. } | Payload::End(_) => continue,
+            _ => {
+                return Err(Error::FatalUnsupportedValue(format!(
+                    "reference decoder: unsupported payload {:?}",
+                    payload
+                )))
+            }
+        };
+        let (_, body) = payload
+            .as_section()
+            .ok_or_else(|| decode_error("section payload without range"))?;
+        module.sections.push(ReferenceSection {
+            section,
+            range: section_start..body.end,
+            body: body.clone(),
+            items: decode_section_items(bytes, section, body.clone())?,
+        });
+        section_start = body.end;
+    }
+    Ok(module)
+}
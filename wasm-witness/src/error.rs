@@ -0,0 +1,189 @@
+use crate::{
+    bytecode::WasmBytecode,
+    types::{AssignOffsetType, ErrorCode, ErrorReason, WasmSection},
+};
+use strum_macros::EnumIter;
+
+#[derive(Debug, Clone, EnumIter, PartialEq)]
+pub enum Error {
+    IndexOutOfBoundsAt(AssignOffsetType),
+    AssignAt(AssignOffsetType),
+    InvalidByteValueAt(AssignOffsetType),
+    ParseOpcodeFailedAt(AssignOffsetType),
+    InvalidEnumValueAt(AssignOffsetType),
+    ComputeValueAt(AssignOffsetType),
+    /// function import missing from the configured import whitelist
+    ImportNotWhitelistedAt(AssignOffsetType),
+    /// memory limit above the configured max memory pages
+    MemoryLimitExceededAt(AssignOffsetType),
+    /// code section blocks nested above the configured max block level
+    BlockLevelExceededAt(AssignOffsetType),
+    /// br/br_if label index not below the current block level
+    BranchDepthExceededAt(AssignOffsetType),
+    /// code section func body declared size above the configured max func body size
+    FuncBodySizeExceededAt(AssignOffsetType),
+    /// code section func body locals above the configured max func locals
+    FuncLocalsExceededAt(AssignOffsetType),
+    /// code section SIMD instruction while SIMD instructions are not skipped
+    SimdInstructionAt(AssignOffsetType),
+    /// type section functype params above the configured max func params
+    FuncParamsExceededAt(AssignOffsetType),
+    /// type section functype results above the configured max func results (1 without
+    /// multi-value)
+    FuncResultsExceededAt(AssignOffsetType),
+    /// import section module or field name longer than the configured max name length
+    ImportNameLenExceededAt(AssignOffsetType),
+    /// export of a mutable global while exported mutable globals are rejected
+    ExportedMutableGlobalAt(AssignOffsetType),
+    /// data section segment len above the configured max data segment size or total segments
+    /// len above the configured max data size
+    DataSizeExceededAt(AssignOffsetType),
+    /// section declared size above the number of bytes remaining in the bytecode
+    SectionLenExceededAt(AssignOffsetType),
+
+    InvalidEnumValue,
+    IndexOutOfBoundsSimple,
+    Leb128Encode,
+    Leb128EncodeSigned,
+    Leb128EncodeUnsigned,
+    Leb128MaxBytes,
+    ComputationFailed,
+
+    FatalAssignExternalChip,
+    FatalUnknownAssignTypeUsed(String),
+    FatalUnsupportedValue(String),
+    FatalUnsupportedTypeValue(String),
+    FatalInvalidArgumentValue(String),
+
+    FatalLeb128Overflow(String),
+    FatalLeb128AlignOverflow(String),
+    FatalLeb128ThresholdOverflow(String),
+    FatalLeb128InvalidArgumentValue(String),
+
+    FatalRecoverableButNotProcessed(String),
+    /// bytecodes need more rows than the circuit has, detected before assignment starts
+    FatalCapacityExceeded {
+        required_rows: usize,
+        available_rows: usize,
+    },
+    /// witness generation walked the bytecode differently than the reference decoder
+    FatalReferenceDecoderMismatch(String),
+
+    FatalUnknown(String),
+}
+pub fn is_recoverable_error(e: &Error) -> bool {
+    if error_code_at(e).is_some() {
+        return true;
+    }
+    return match e {
+        Error::IndexOutOfBoundsSimple
+        | Error::Leb128Encode
+        | Error::Leb128EncodeSigned
+        | Error::Leb128EncodeUnsigned
+        | Error::Leb128MaxBytes
+        | Error::InvalidEnumValue
+        | Error::ComputationFailed => true,
+
+        _ => false,
+    };
+}
+pub fn is_fatal_error(e: &Error) -> bool {
+    return match e {
+        Error::FatalAssignExternalChip
+        | Error::FatalUnknownAssignTypeUsed(_)
+        | Error::FatalUnsupportedValue(_)
+        | Error::FatalUnsupportedTypeValue(_)
+        | Error::FatalInvalidArgumentValue(_)
+        | Error::FatalLeb128Overflow(_)
+        | Error::FatalLeb128AlignOverflow(_)
+        | Error::FatalLeb128ThresholdOverflow(_)
+        | Error::FatalLeb128InvalidArgumentValue(_)
+        | Error::FatalRecoverableButNotProcessed(_)
+        | Error::FatalCapacityExceeded { .. }
+        | Error::FatalReferenceDecoderMismatch(_)
+        | Error::FatalUnknown(_) => true,
+
+        _ => false,
+    };
+}
+
+/// Structured code and bytecode offset of the errors carrying an offset, the only place error
+/// codes are mapped to errors
+fn error_code_at(e: &Error) -> Option<(ErrorCode, AssignOffsetType)> {
+    let (error_code, offset) = match e {
+        Error::IndexOutOfBoundsAt(offset) => (ErrorCode::IndexOutOfBounds, offset),
+        Error::AssignAt(offset) => (ErrorCode::Assign, offset),
+        Error::InvalidByteValueAt(offset) => (ErrorCode::InvalidByteValue, offset),
+        Error::ParseOpcodeFailedAt(offset) => (ErrorCode::ParseOpcodeFailed, offset),
+        Error::InvalidEnumValueAt(offset) => (ErrorCode::InvalidEnumValue, offset),
+        Error::ComputeValueAt(offset) => (ErrorCode::ComputeValue, offset),
+        Error::ImportNotWhitelistedAt(offset) => (ErrorCode::ImportNotWhitelisted, offset),
+        Error::MemoryLimitExceededAt(offset) => (ErrorCode::MemoryLimitExceeded, offset),
+        Error::BlockLevelExceededAt(offset) => (ErrorCode::BlockLevelExceeded, offset),
+        Error::BranchDepthExceededAt(offset) => (ErrorCode::BranchDepthExceeded, offset),
+        Error::FuncBodySizeExceededAt(offset) => (ErrorCode::FuncBodySizeExceeded, offset),
+        Error::FuncLocalsExceededAt(offset) => (ErrorCode::FuncLocalsExceeded, offset),
+        Error::SimdInstructionAt(offset) => (ErrorCode::SimdInstruction, offset),
+        Error::FuncParamsExceededAt(offset) => (ErrorCode::FuncParamsExceeded, offset),
+        Error::FuncResultsExceededAt(offset) => (ErrorCode::FuncResultsExceeded, offset),
+        Error::ImportNameLenExceededAt(offset) => (ErrorCode::ImportNameLenExceeded, offset),
+        Error::ExportedMutableGlobalAt(offset) => (ErrorCode::ExportedMutableGlobal, offset),
+        Error::DataSizeExceededAt(offset) => (ErrorCode::DataSizeExceeded, offset),
+        Error::SectionLenExceededAt(offset) => (ErrorCode::SectionLenExceeded, offset),
+
+        _ => return None,
+    };
+    Some((error_code, *offset))
+}
+
+/// Bytecode offset carried by the error
+pub fn error_offset(e: &Error) -> Option<AssignOffsetType> {
+    error_code_at(e).map(|(_, offset)| offset)
+}
+
+/// Structured code of the error, [`ErrorCode::Error`] for errors without a dedicated one
+pub fn error_code_of(e: &Error) -> ErrorCode {
+    error_code_at(e).map_or(ErrorCode::Error, |(error_code, _)| error_code)
+}
+
+pub fn error_reason(e: &Error, section: Option<WasmSection>) -> ErrorReason {
+    ErrorReason {
+        error_code: error_code_of(e),
+        section,
+        wb_offset: error_offset(e),
+    }
+}
+
+pub fn error_index_out_of_bounds(assign_offset: usize) -> Error {
+    Error::IndexOutOfBoundsAt(assign_offset)
+}
+
+pub fn validate_wb_offset(wb: &WasmBytecode, assign_offset: usize) -> Result<(), Error> {
+    if assign_offset >= wb.bytes.len() {
+        return Err(error_index_out_of_bounds(assign_offset));
+    }
+    Ok(())
+}
+
+pub fn remap_error_to_index_out_of_bounds_at<E>(assign_offset: usize) -> impl FnOnce(E) -> Error {
+    move |_| Error::IndexOutOfBoundsAt(assign_offset)
+}
+pub fn remap_error_to_assign_at<E>(assign_offset: usize) -> impl FnOnce(E) -> Error {
+    move |_| Error::AssignAt(assign_offset)
+}
+pub fn remap_error_to_invalid_byte_value_at<E>(assign_offset: usize) -> impl FnOnce(E) -> Error {
+    move |_| Error::InvalidByteValueAt(assign_offset)
+}
+pub fn remap_error_to_parse_opcode_failed_at<E>(assign_offset: usize) -> impl FnOnce(E) -> Error {
+    move |_| Error::ParseOpcodeFailedAt(assign_offset)
+}
+pub fn remap_error_to_invalid_enum_value_at<E>(assign_offset: usize) -> impl FnOnce(E) -> Error {
+    move |_| Error::InvalidEnumValueAt(assign_offset)
+}
+pub fn remap_error_to_compute_value_at<E>(assign_offset: usize) -> impl FnOnce(E) -> Error {
+    move |_| Error::ComputeValueAt(assign_offset)
+}
+
+pub fn remap_error<E>(to: Error) -> impl FnOnce(E) -> Error {
+    |_| to
+}
@@ -0,0 +1,24 @@
+//! `Expr` of the enums encoded into the circuit cells
+
+use gadgets::impl_expr;
+use halo2_proofs::plonk::Expression;
+
+use crate::types::{
+    ControlInstruction, ExportDescType, ImportDescType, LimitType, MemSegmentType, Mutability,
+    NumType, NumericInstruction, ParametricInstruction, RefType, ReferenceInstruction,
+    VariableInstruction, WasmSection,
+};
+
+impl_expr!(WasmSection);
+impl_expr!(NumType);
+impl_expr!(RefType);
+impl_expr!(LimitType);
+impl_expr!(MemSegmentType);
+impl_expr!(ImportDescType);
+impl_expr!(ExportDescType);
+impl_expr!(Mutability);
+impl_expr!(NumericInstruction);
+impl_expr!(VariableInstruction);
+impl_expr!(ControlInstruction);
+impl_expr!(ParametricInstruction);
+impl_expr!(ReferenceInstruction);
@@ -1,7 +1,5 @@
-use num_traits::pow;
-
-use crate::wasm_circuit::error::{Error, remap_error};
-use crate::wasm_circuit::leb128::consts::{EIGHT_MS_BIT_MASK, LEB128_MAX_BYTES_COUNT};
+use crate::error::{Error, remap_error};
+use crate::leb128::consts::{EIGHT_MS_BIT_MASK, LEB128_MAX_BYTES_COUNT};
 
 pub fn leb128_compute_sn_recovered_at_position(
     sn_recovered_at_prev_pos: u64,
@@ -14,13 +12,17 @@ pub fn leb128_compute_sn_recovered_at_position(
     let is_byte_has_cb = byte_rel_offset < last_byte_rel_offset;
     let is_consider_byte = byte_rel_offset <= last_byte_rel_offset;
     let mut sn_recovered_at_pos = 0;
+    // arithmetic is mod 2^64: the 10th byte of i64::MIN (and of other 64 bit values) overflows
+    // intermediate values while the final SN still fits
     if is_consider_byte {
-        let leb_byte_mul: u64 = pow(0b10000000, byte_rel_offset);
-        sn_recovered_at_pos = sn_recovered_at_prev_pos + (byte_val as u64 - if is_byte_has_cb { 0b10000000 } else { 0 }) * leb_byte_mul;
+        let leb_byte_mul: u64 = 0b10000000u64.wrapping_pow(byte_rel_offset as u32);
+        sn_recovered_at_pos = sn_recovered_at_prev_pos.wrapping_add(
+            (byte_val as u64 - if is_byte_has_cb { 0b10000000 } else { 0 }).wrapping_mul(leb_byte_mul),
+        );
     }
     if is_signed && is_last_leb_byte {
-        let number_for_signed_revert = pow(0b10000000, byte_rel_offset + 1) - 1;
-        sn_recovered_at_pos = number_for_signed_revert - (sn_recovered_at_pos - 1);
+        let number_for_signed_revert = 0b10000000u64.wrapping_pow(byte_rel_offset as u32 + 1).wrapping_sub(1);
+        sn_recovered_at_pos = number_for_signed_revert.wrapping_sub(sn_recovered_at_pos.wrapping_sub(1));
     }
 
     sn_recovered_at_pos
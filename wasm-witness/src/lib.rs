@@ -0,0 +1,21 @@
+//! # wasm-witness
+//!
+//! Witness generation inputs of the wasm circuit usable without the prover: bytecode decoding,
+//! validation, shared state and the section assignment plans. Nothing here depends on halo2
+//! unless the `circuit` feature is enabled, light clients and indexers depend on this crate only.
+
+// We want to have UPPERCASE idents sometimes.
+#![allow(clippy::upper_case_acronyms)]
+// Catch documentation errors caused by code changes.
+#![deny(rustdoc::broken_intra_doc_links)]
+
+pub mod bytecode;
+pub mod common;
+pub mod consts;
+pub mod decoder;
+pub mod error;
+#[cfg(feature = "circuit")]
+mod expr;
+pub mod leb128;
+pub mod types;
+pub mod validation;
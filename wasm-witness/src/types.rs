@@ -0,0 +1,1087 @@
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+use ethers_core::types::U256 as Word;
+
+use crate::{
+    bytecode::WasmBytecode,
+    error::Error,
+    leb128::helpers::{leb128_compute_last_byte_offset, leb128_compute_sn},
+};
+
+pub type AssignOffsetType = usize;
+pub type AssignDeltaType = usize;
+pub type AssignValueType = u64;
+pub type OffsetType = usize;
+pub type NewOffsetType = usize;
+pub type WbOffsetType = usize;
+pub type NewWbOffsetType = usize;
+pub type Sn = u64;
+pub type Leb128LengthType = usize;
+pub type Leb128BytesCountType = u8;
+pub type SectionLengthType = usize;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AssignType {
+    Unknown,
+    QFirst,
+    QLast,
+    IsSectionId,
+    IsSectionLen,
+    IsSectionBody,
+
+    BodyByteRevIndexL1,
+    BytecodeRevIndex,
+
+    ErrorCode,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ErrorCode {
+    Ok = 0,
+    /// code of the errors without a dedicated one
+    Error = 1,
+
+    IndexOutOfBounds = 2,
+    Assign = 3,
+    InvalidByteValue = 4,
+    ParseOpcodeFailed = 5,
+    InvalidEnumValue = 6,
+    ComputeValue = 7,
+    ImportNotWhitelisted = 8,
+    MemoryLimitExceeded = 9,
+    BlockLevelExceeded = 10,
+    BranchDepthExceeded = 11,
+    FuncBodySizeExceeded = 12,
+    FuncLocalsExceeded = 13,
+    SimdInstruction = 14,
+    FuncParamsExceeded = 15,
+    FuncResultsExceeded = 16,
+    ImportNameLenExceeded = 17,
+    ExportedMutableGlobal = 18,
+    DataSizeExceeded = 19,
+    SectionLenExceeded = 20,
+}
+
+/// Why the error code of a bytecode was turned on
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ErrorReason {
+    pub error_code: ErrorCode,
+    /// section being assigned when the error happened, `None` outside of section bodies
+    pub section: Option<WasmSection>,
+    pub wb_offset: Option<WbOffsetType>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum WasmSection {
+    Custom = 0,
+    Type = 1,
+    Import = 2,
+    Function = 3,
+    Table = 4,
+    Memory = 5,
+    Global = 6,
+    Export = 7,
+    Start = 8,
+    Element = 9,
+    Code = 10,
+    Data = 11,
+    DataCount = 12,
+}
+
+pub const WASM_SECTION_VALUES: &[WasmSection] = &[
+    WasmSection::Custom,
+    WasmSection::Type,
+    WasmSection::Import,
+    WasmSection::Function,
+    WasmSection::Table,
+    WasmSection::Memory,
+    WasmSection::Global,
+    WasmSection::Export,
+    WasmSection::Start,
+    WasmSection::Element,
+    WasmSection::Code,
+    WasmSection::Data,
+    WasmSection::DataCount,
+];
+
+impl TryFrom<i32> for WasmSection {
+    type Error = Error;
+
+    fn try_from(v: i32) -> Result<Self, Self::Error> {
+        for instr in WASM_SECTION_VALUES {
+            if v == *instr as i32 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+/// https://webassembly.github.io/spec/core/binary/types.html#number-types
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NumType {
+    I32 = 0x7F,
+    I64 = 0x7E,
+    // not supported yet
+    // F32 = 0x7D,
+    // F64 = 0x7C,
+}
+
+pub const NUM_TYPE_VALUES: &[NumType] = &[
+    NumType::I32,
+    NumType::I64,
+    // NumType::F32,
+    // NumType::F64,
+];
+
+impl TryFrom<u8> for NumType {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in NUM_TYPE_VALUES {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<NumType> for usize {
+    fn from(t: NumType) -> Self {
+        t as usize
+    }
+}
+
+/// https://webassembly.github.io/spec/core/binary/types.html#reference-types
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum RefType {
+    FuncRef = 0x70,
+    ExternRef = 0x71,
+}
+
+pub const REF_TYPE_VALUES: &[RefType] = &[RefType::FuncRef, RefType::ExternRef];
+
+impl TryFrom<u8> for RefType {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in REF_TYPE_VALUES {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<RefType> for usize {
+    fn from(t: RefType) -> Self {
+        t as usize
+    }
+}
+
+/// https://webassembly.github.io/spec/core/binary/types.html#limits
+/// Bit 1 marks a shared memory (threads proposal), shared memories must declare a max so `0x2` is
+/// never valid.
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LimitType {
+    MinOnly = 0x0,
+    MinMax = 0x1,
+    SharedMinMax = 0x3,
+}
+
+/// shared memories come with the threads proposal
+pub const LIMIT_TYPE_VALUES: &[LimitType] = &[
+    LimitType::MinOnly,
+    LimitType::MinMax,
+    #[cfg(feature = "threads")]
+    LimitType::SharedMinMax,
+];
+
+impl LimitType {
+    pub fn has_max(&self) -> bool {
+        matches!(self, LimitType::MinMax | LimitType::SharedMinMax)
+    }
+}
+
+impl TryFrom<u8> for LimitType {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in LIMIT_TYPE_VALUES {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<LimitType> for usize {
+    fn from(t: LimitType) -> Self {
+        t as usize
+    }
+}
+
+/// https://webassembly.github.io/spec/core/binary/modules.html#data-section
+/// Bit 0 indicates a passive segment, bit 1 indicates the presence of an explicit memory index for
+/// an active segment.
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemSegmentType {
+    Active = 0x0,
+    Passive = 0x1,
+    ActiveVariadic = 0x2,
+}
+
+pub const MEM_SEGMENT_TYPE_VALUES: &[MemSegmentType] = &[
+    MemSegmentType::Active,
+    MemSegmentType::Passive,
+    MemSegmentType::ActiveVariadic,
+];
+
+impl TryFrom<u8> for MemSegmentType {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in MEM_SEGMENT_TYPE_VALUES {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<MemSegmentType> for usize {
+    fn from(t: MemSegmentType) -> Self {
+        t as usize
+    }
+}
+
+/// https://webassembly.github.io/spec/core/binary/modules.html#binary-importdesc
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ImportDescType {
+    Typeidx = 0x0,
+    TableType = 0x1,
+    MemType = 0x2,
+    GlobalType = 0x3,
+}
+
+pub const IMPORT_DESC_TYPE_VALUES: &[ImportDescType] = &[
+    ImportDescType::Typeidx,
+    ImportDescType::TableType,
+    ImportDescType::MemType,
+    ImportDescType::GlobalType,
+];
+
+impl TryFrom<u8> for ImportDescType {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in IMPORT_DESC_TYPE_VALUES {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<ImportDescType> for usize {
+    fn from(t: ImportDescType) -> Self {
+        t as usize
+    }
+}
+
+/// https://webassembly.github.io/spec/core/binary/modules.html#export-section
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExportDescType {
+    Funcidx = 0x0,
+    Tableidx = 0x1,
+    Memidx = 0x2,
+    Globalidx = 0x3,
+}
+
+pub const EXPORT_DESC_TYPE_VALUES: &[ExportDescType] = &[
+    ExportDescType::Funcidx,
+    ExportDescType::Tableidx,
+    ExportDescType::Memidx,
+    ExportDescType::Globalidx,
+];
+
+impl TryFrom<u8> for ExportDescType {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in EXPORT_DESC_TYPE_VALUES {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<ExportDescType> for usize {
+    fn from(t: ExportDescType) -> Self {
+        t as usize
+    }
+}
+
+/// https://webassembly.github.io/spec/core/binary/types.html#global-types
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mutability {
+    Const = 0x0,
+    Var = 0x1,
+}
+
+pub const MUTABILITY_VALUES: &[Mutability] = &[Mutability::Const, Mutability::Var];
+
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NumericInstruction {
+    I32Const = 0x41,
+    I64Const = 0x42,
+    F32Const = 0x43,
+    F64Const = 0x44,
+
+    I32Eqz = 0x45,
+    I32Eq = 0x46,
+    I32Ne = 0x47,
+    I32LtS = 0x48,
+    I32LtU = 0x49,
+    I32GtS = 0x4a,
+    I32GtU = 0x4b,
+    I32LeS = 0x4c,
+    I32LeU = 0x4d,
+    I32GeS = 0x4e,
+    I32GeU = 0x4f,
+
+    I64Eqz = 0x50,
+    I64Eq = 0x51,
+    I64Ne = 0x52,
+    I64LtS = 0x53,
+    I64LtU = 0x54,
+    I64GtS = 0x55,
+    I64GtU = 0x56,
+    I64LeS = 0x57,
+    I64LeU = 0x58,
+    I64GeS = 0x59,
+    I64GeU = 0x5a,
+
+    F32Eq = 0x5b,
+    F32Ne = 0x5c,
+    F32Lt = 0x5d,
+    F32Gt = 0x5e,
+    F32Le = 0x5f,
+    F32Ge = 0x60,
+
+    F64Eq = 0x61,
+    F64Ne = 0x62,
+    F64Lt = 0x63,
+    F64Gt = 0x64,
+    F64Le = 0x65,
+    F64Ge = 0x66,
+
+    I32Clz = 0x67,
+    I32Ctz = 0x68,
+    I32Popcnt = 0x69,
+    I32Add = 0x6a,
+    I32Sub = 0x6b,
+    I32Mul = 0x6c,
+    I32DivS = 0x6d,
+    I32DivU = 0x6e,
+    I32RemS = 0x6f,
+    I32RemU = 0x70,
+    I32And = 0x71,
+    I32Or = 0x72,
+    I32Xor = 0x73,
+    I32Shl = 0x74,
+    I32ShrS = 0x75,
+    I32ShrU = 0x76,
+    I32Rotl = 0x77,
+    I32Rotr = 0x78,
+
+    I64Clz = 0x79,
+    I64Ctz = 0x7a,
+    I64Popcnt = 0x7b,
+    I64Add = 0x7c,
+    I64Sub = 0x7d,
+    I64Mul = 0x7e,
+    I64DivS = 0x7f,
+    I64DivU = 0x80,
+    I64RemS = 0x81,
+    I64RemU = 0x82,
+    I64And = 0x83,
+    I64Or = 0x84,
+    I64Xor = 0x85,
+    I64Shl = 0x86,
+    I64ShrS = 0x87,
+    I64ShrU = 0x88,
+    I64Rotl = 0x89,
+    I64Rotr = 0x8a,
+
+    F32Abs = 0x8b,
+    F32Neg = 0x8c,
+    F32Ceil = 0x8d,
+    F32Floor = 0x8e,
+    F32Trunc = 0x8f,
+    F32Nearest = 0x90,
+    F32Sqrt = 0x91,
+    F32Add = 0x92,
+    F32Sub = 0x93,
+    F32Mul = 0x94,
+    F32Div = 0x95,
+    F32Min = 0x96,
+    F32Max = 0x97,
+    F32Copysign = 0x98,
+
+    F64Abs = 0x99,
+    F64Neg = 0x9a,
+    F64Ceil = 0x9b,
+    F64Floor = 0x9c,
+    F64Trunc = 0x9d,
+    F64Nearest = 0x9e,
+    F64Sqrt = 0x9f,
+    F64Add = 0xa0,
+    F64Sub = 0xa1,
+    F64Mul = 0xa2,
+    F64Div = 0xa3,
+    F64Min = 0xa4,
+    F64Max = 0xa5,
+    F64Copysign = 0xa6,
+    I32WrapI64 = 0xa7,
+    I32TruncSF32 = 0xa8,
+    I32TruncUF32 = 0xa9,
+    I32TruncSF64 = 0xaa,
+    I32TruncUF64 = 0xab,
+    I64ExtendSI32 = 0xac,
+    I64ExtendUI32 = 0xad,
+    I64TruncSF32 = 0xae,
+    I64TruncUF32 = 0xaf,
+    I64TruncSF64 = 0xb0,
+    I64TruncUF64 = 0xb1,
+    F32ConvertSI32 = 0xb2,
+    F32ConvertUI32 = 0xb3,
+    F32ConvertSI64 = 0xb4,
+    F32ConvertUI64 = 0xb5,
+    F32DemoteF64 = 0xb6,
+    F64ConvertSI32 = 0xb7,
+    F64ConvertUI32 = 0xb8,
+    F64ConvertSI64 = 0xb9,
+    F64ConvertUI64 = 0xba,
+    F64PromoteF32 = 0xbb,
+    I32ReinterpretF32 = 0xbc,
+    I64ReinterpretF64 = 0xbd,
+    F32ReinterpretI32 = 0xbe,
+    F64ReinterpretI64 = 0xbf,
+
+    I32extend8S = 0xc0,
+    I32extend16S = 0xc1,
+    I64extend8S = 0xc2,
+    I64extend16S = 0xc3,
+    I64extend32S = 0xc4,
+}
+
+/// The `fp` feature extends the whitelist. `f32.const`/`f64.const` are not there since
+/// their immediates are fixed width, not LEB128.
+pub const NUMERIC_INSTRUCTIONS_WITHOUT_ARGS: &[NumericInstruction] = &[
+    NumericInstruction::I32Add,
+    NumericInstruction::I64Add,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Eq,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Ne,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Lt,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Gt,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Le,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Ge,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Eq,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Ne,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Lt,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Gt,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Le,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Ge,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Abs,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Neg,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Ceil,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Floor,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Trunc,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Nearest,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Sqrt,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Add,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Sub,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Mul,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Div,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Min,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Max,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32Copysign,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Abs,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Neg,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Ceil,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Floor,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Trunc,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Nearest,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Sqrt,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Add,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Sub,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Mul,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Div,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Min,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Max,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64Copysign,
+    #[cfg(feature = "fp")]
+    NumericInstruction::I32TruncSF32,
+    #[cfg(feature = "fp")]
+    NumericInstruction::I32TruncUF32,
+    #[cfg(feature = "fp")]
+    NumericInstruction::I32TruncSF64,
+    #[cfg(feature = "fp")]
+    NumericInstruction::I32TruncUF64,
+    #[cfg(feature = "fp")]
+    NumericInstruction::I64TruncSF32,
+    #[cfg(feature = "fp")]
+    NumericInstruction::I64TruncUF32,
+    #[cfg(feature = "fp")]
+    NumericInstruction::I64TruncSF64,
+    #[cfg(feature = "fp")]
+    NumericInstruction::I64TruncUF64,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32ConvertSI32,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32ConvertUI32,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32ConvertSI64,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32ConvertUI64,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32DemoteF64,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64ConvertSI32,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64ConvertUI32,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64ConvertSI64,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64ConvertUI64,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64PromoteF32,
+    #[cfg(feature = "fp")]
+    NumericInstruction::I32ReinterpretF32,
+    #[cfg(feature = "fp")]
+    NumericInstruction::I64ReinterpretF64,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F32ReinterpretI32,
+    #[cfg(feature = "fp")]
+    NumericInstruction::F64ReinterpretI64,
+];
+pub const NUMERIC_INSTRUCTION_WITH_LEB_ARG: &[NumericInstruction] =
+    &[NumericInstruction::I32Const, NumericInstruction::I64Const];
+
+impl TryFrom<u8> for NumericInstruction {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in NUMERIC_INSTRUCTION_WITH_LEB_ARG {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        for instr in NUMERIC_INSTRUCTIONS_WITHOUT_ARGS {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<NumericInstruction> for usize {
+    fn from(t: NumericInstruction) -> Self {
+        t as usize
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VariableInstruction {
+    LocalGet = 0x20,
+    LocalSet = 0x21,
+    LocalTee = 0x22,
+    GlobalGet = 0x23,
+    GlobalSet = 0x24,
+}
+
+pub const VARIABLE_INSTRUCTION_WITH_LEB_ARG: &[VariableInstruction] = &[
+    VariableInstruction::LocalGet,
+    VariableInstruction::LocalSet,
+    VariableInstruction::LocalTee,
+    VariableInstruction::GlobalGet,
+    VariableInstruction::GlobalSet,
+];
+
+impl TryFrom<u8> for VariableInstruction {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in VARIABLE_INSTRUCTION_WITH_LEB_ARG {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<VariableInstruction> for usize {
+    fn from(t: VariableInstruction) -> Self {
+        t as usize
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ControlInstruction {
+    Unreachable = 0x00,
+    Nop = 0x01,
+    Block = 0x02,
+    Loop = 0x03,
+    If = 0x04,
+    Else = 0x05,
+    Br = 0x0C,
+    BrIf = 0x0D,
+    BrTable = 0x0E,
+    Return = 0x0F,
+    Call = 0x10,
+    CallIndirect = 0x11,
+    ReturnCall = 0x12,
+    ReturnCallIndirect = 0x13,
+}
+
+pub const CONTROL_INSTRUCTION_WITHOUT_ARGS: &[ControlInstruction] =
+    &[ControlInstruction::Unreachable, ControlInstruction::Else];
+pub const CONTROL_INSTRUCTION_WITH_LEB_ARG: &[ControlInstruction] = &[
+    ControlInstruction::Br,
+    ControlInstruction::BrIf,
+    ControlInstruction::Call,
+    #[cfg(feature = "tail-call")]
+    ControlInstruction::ReturnCall,
+];
+pub const CONTROL_INSTRUCTION_BLOCK: &[ControlInstruction] = &[
+    ControlInstruction::Block,
+    ControlInstruction::Loop,
+    ControlInstruction::If,
+];
+
+impl TryFrom<u8> for ControlInstruction {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in CONTROL_INSTRUCTION_WITH_LEB_ARG {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        for instr in CONTROL_INSTRUCTION_WITHOUT_ARGS {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        for instr in CONTROL_INSTRUCTION_BLOCK {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<ControlInstruction> for usize {
+    fn from(t: ControlInstruction) -> Self {
+        t as usize
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ParametricInstruction {
+    Drop = 0x1A,
+    Select = 0x1B,
+    // SelectT = 0x1C,
+}
+
+pub const PARAMETRIC_INSTRUCTIONS_WITHOUT_ARGS: &[ParametricInstruction] =
+    &[ParametricInstruction::Drop, ParametricInstruction::Select];
+
+impl TryFrom<u8> for ParametricInstruction {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in PARAMETRIC_INSTRUCTIONS_WITHOUT_ARGS {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<ParametricInstruction> for usize {
+    fn from(t: ParametricInstruction) -> Self {
+        t as usize
+    }
+}
+
+/// https://webassembly.github.io/spec/core/binary/instructions.html#vector-instructions
+/// Immediates following the sub-opcode of a SIMD instruction. SIMD semantics are not proven, the
+/// layouts are only used to skip the instructions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SimdImmediate {
+    None,
+    Memarg,
+    MemargLaneIdx,
+    LaneIdx,
+    /// `v128.const` value or `i8x16.shuffle` lane indexes
+    Bytes16,
+}
+
+/// Sub-opcodes below `0x100` not assigned by the SIMD proposal
+pub const SIMD_UNASSIGNED_SUB_OPCODES: &[u64] = &[
+    0x9A, 0xA2, 0xA5, 0xA6, 0xAF, 0xB0, 0xB2, 0xB3, 0xB4, 0xBB, 0xC2, 0xC5, 0xC6, 0xCF, 0xD0, 0xD2,
+    0xD3, 0xD4,
+];
+
+impl TryFrom<u64> for SimdImmediate {
+    type Error = Error;
+
+    fn try_from(sub_opcode: u64) -> Result<Self, Self::Error> {
+        if SIMD_UNASSIGNED_SUB_OPCODES.contains(&sub_opcode) {
+            return Err(Error::InvalidEnumValue);
+        }
+        match sub_opcode {
+            0x00..=0x0B | 0x5C | 0x5D => Ok(Self::Memarg),
+            0x0C | 0x0D => Ok(Self::Bytes16),
+            0x15..=0x22 => Ok(Self::LaneIdx),
+            0x54..=0x5B => Ok(Self::MemargLaneIdx),
+            0x0E..=0xFF => Ok(Self::None),
+            _ => Err(Error::InvalidEnumValue),
+        }
+    }
+}
+
+/// Length (prefix included) of the SIMD instruction starting at `offset`
+pub fn simd_instruction_len(bytes: &[u8], offset: usize) -> Result<usize, Error> {
+    let (sub_opcode, sub_opcode_last_byte_offset) = leb128_compute_sn(bytes, false, offset + 1)?;
+    let immediate = SimdImmediate::try_from(sub_opcode)?;
+    let mut end_offset = sub_opcode_last_byte_offset + 1;
+    if [SimdImmediate::Memarg, SimdImmediate::MemargLaneIdx].contains(&immediate) {
+        // memarg: align and offset
+        for _ in 0..2 {
+            end_offset = leb128_compute_last_byte_offset(bytes, end_offset)? + 1;
+        }
+    }
+    end_offset += match immediate {
+        SimdImmediate::MemargLaneIdx | SimdImmediate::LaneIdx => 1,
+        SimdImmediate::Bytes16 => 16,
+        SimdImmediate::None | SimdImmediate::Memarg => 0,
+    };
+    if end_offset > bytes.len() {
+        return Err(Error::IndexOutOfBoundsSimple);
+    }
+    Ok(end_offset - offset)
+}
+
+/// https://webassembly.github.io/spec/core/binary/instructions.html#reference-instructions
+/// Only used by the constant expressions of element segments.
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReferenceInstruction {
+    RefNull = 0xD0,
+    RefFunc = 0xD2,
+}
+
+pub const REFERENCE_INSTRUCTIONS: &[ReferenceInstruction] =
+    &[ReferenceInstruction::RefNull, ReferenceInstruction::RefFunc];
+
+impl TryFrom<u8> for ReferenceInstruction {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in REFERENCE_INSTRUCTIONS {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<ReferenceInstruction> for usize {
+    fn from(t: ReferenceInstruction) -> Self {
+        t as usize
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SharedState {
+    pub bytecode_number: u64,
+    pub dynamic_indexes_offset: usize,
+    pub func_count: usize,
+    pub block_level: usize,
+
+    pub error_processing_enabled: bool,
+    /// cross-check section walking of witness generation against wasmparser (see
+    /// [`decode_module`](crate::decoder::decode_module))
+    pub reference_decoder_enabled: bool,
+    pub error_code: u64,
+    /// first failure of the current bytecode, kept once the error code is turned on
+    pub error_reason: Option<ErrorReason>,
+}
+
+impl SharedState {
+    pub fn reset(&mut self) {
+        self.bytecode_number = 1;
+        self.dynamic_indexes_offset = 0;
+        self.func_count = 0;
+        self.block_level = 0;
+
+        // self.error_processing_enabled = true;
+        self.error_code = 0;
+        self.error_reason = None;
+    }
+
+    pub fn bytecode_number_inc(&mut self) {
+        self.bytecode_number += 1;
+    }
+    pub fn bytecode_number_reset(&mut self) {
+        self.bytecode_number = 1;
+    }
+    pub fn dynamic_indexes_offset_reset(&mut self) {
+        self.dynamic_indexes_offset = 0;
+    }
+    /// turns the error code on with the code of the reason, the reason of an earlier failure of
+    /// the bytecode is kept along with its code
+    pub fn error_code_turn_on(&mut self, error_reason: ErrorReason) {
+        self.error_code = self.error_reason.get_or_insert(error_reason).error_code as u64;
+    }
+    pub fn error_code_reset(&mut self) {
+        self.error_code = 0;
+        self.error_reason = None;
+    }
+    pub fn block_level_inc(&mut self) {
+        self.block_level += 1;
+    }
+    pub fn block_level_reset(&mut self) {
+        self.block_level = 0;
+    }
+    pub fn block_level_dec(&mut self) {
+        self.block_level -= 1;
+    }
+
+    /// starts the assignment of the bytecode `bytecode_number`, counters of the previous bytecode
+    /// are dropped (the circuit requires func_count=0 at q_first and block_level=0 at the code
+    /// section boundaries)
+    pub fn module_begin(&mut self) {
+        self.func_count = 0;
+        self.block_level = 0;
+        self.error_code_reset();
+    }
+    /// ends the assignment of the current bytecode, the next one gets the next bytecode_number
+    pub fn module_end(&mut self) {
+        self.bytecode_number_inc();
+    }
+
+    pub fn snapshot(&self) -> SharedStateSnapshot {
+        SharedStateSnapshot {
+            dynamic_indexes_offset: self.dynamic_indexes_offset,
+            func_count: self.func_count,
+            block_level: self.block_level,
+        }
+    }
+    /// rolls the counters back to `snapshot`, used when a section assignment fails midway
+    pub fn restore(&mut self, snapshot: SharedStateSnapshot) {
+        self.dynamic_indexes_offset = snapshot.dynamic_indexes_offset;
+        self.func_count = snapshot.func_count;
+        self.block_level = snapshot.block_level;
+    }
+}
+
+/// Counters of the [`SharedState`] updated by the section chips while assigning
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SharedStateSnapshot {
+    pub dynamic_indexes_offset: usize,
+    pub func_count: usize,
+    pub block_level: usize,
+}
+
+/// Handle to the [`SharedState`] of the chips assigning the same bytecodes. Clones share the
+/// state, the handle is `Send + Sync` so assignment can be moved onto worker threads.
+#[derive(Clone, Debug, Default)]
+pub struct SharedStateRef(Arc<RwLock<SharedState>>);
+
+impl SharedStateRef {
+    pub fn new(shared_state: SharedState) -> Self {
+        Self(Arc::new(RwLock::new(shared_state)))
+    }
+
+    /// Read guard of the state, released when dropped
+    pub fn read(&self) -> RwLockReadGuard<'_, SharedState> {
+        self.0.read().expect("shared state lock is poisoned")
+    }
+
+    /// Write guard of the state, released when dropped. Keep its scope short: chips of other
+    /// threads block until it is dropped.
+    pub fn write(&self) -> RwLockWriteGuard<'_, SharedState> {
+        self.0.write().expect("shared state lock is poisoned")
+    }
+}
+
+/// Byte range of a single section, recorded while the section is being assigned
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WasmSectionAssignment {
+    pub bytecode_number: u64,
+    pub section: WasmSection,
+    pub section_start_offset: WbOffsetType,
+    pub section_body_start_offset: WbOffsetType,
+    pub section_end_offset: WbOffsetType,
+    pub assign_delta: AssignDeltaType,
+}
+
+/// Public output of a single bytecode in a multi-bytecode batch. Error code of one bytecode never
+/// leaks into the next one since error code is reset at each bytecode start.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WasmModuleOutput {
+    pub bytecode_number: u64,
+    pub code_hash: Word,
+    pub error_code: u64,
+    /// witness side reason of a set `error_code`, not part of the public outputs
+    pub error_reason: Option<ErrorReason>,
+    /// offset of the bytecode last row (marked by q_last), outputs are read from it
+    pub q_last_assign_offset: AssignOffsetType,
+}
+
+/// Position of the next bytecode in a region holding several bytecodes one after another, each of
+/// them preceded by a zero row when the bytecode table has one
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct RegionCursor {
+    assign_delta: AssignDeltaType,
+    zero_row_enabled: bool,
+}
+
+impl RegionCursor {
+    pub fn new(assign_delta_base: AssignDeltaType, zero_row_enabled: bool) -> Self {
+        Self {
+            assign_delta: assign_delta_base,
+            zero_row_enabled,
+        }
+    }
+
+    /// Assign delta of the next bytecode (its zero row included)
+    pub fn assign_delta(&self) -> AssignDeltaType {
+        self.assign_delta
+    }
+
+    /// Assign delta of the first byte of the next bytecode
+    pub fn bytecode_assign_delta(&self) -> AssignDeltaType {
+        self.assign_delta + if self.zero_row_enabled { 1 } else { 0 }
+    }
+
+    /// Moves past `wb` assigned at the cursor
+    pub fn advance(&mut self, wb: &WasmBytecode) {
+        self.assign_delta = self.bytecode_assign_delta() + wb.bytes.len();
+    }
+}
+
+/// Everything needed to repeat the witness assignment of a batch of bytecodes elsewhere (e.g. on a
+/// dedicated proving machine)
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WasmAssignmentPlan {
+    pub wbs: Vec<WasmBytecode>,
+    pub assign_delta_base: AssignDeltaType,
+    pub shared_state: SharedState,
+    pub section_assignments: Vec<WasmSectionAssignment>,
+}
+
+impl WasmAssignmentPlan {
+    /// Plan computed without assigning the circuit: section ranges come from the validator and
+    /// bytecodes are laid out like `WasmCircuit` does it (a zero row before each of them). Shared
+    /// state is the initial one. Matches the plan recorded by the circuit for valid bytecodes,
+    /// sections of an invalid bytecode stop at its first structural error.
+    pub fn new(wbs: Vec<WasmBytecode>, assign_delta_base: AssignDeltaType) -> Self {
+        let mut shared_state = SharedState::default();
+        shared_state.reset();
+        let mut section_assignments = vec![];
+        let mut cursor = RegionCursor::new(assign_delta_base, true);
+        for (idx, wb) in wbs.iter().enumerate() {
+            let assign_delta = cursor.bytecode_assign_delta();
+            for report in wb.sections() {
+                section_assignments.push(WasmSectionAssignment {
+                    bytecode_number: shared_state.bytecode_number + idx as u64,
+                    section: report.section,
+                    section_start_offset: report.section_start_offset,
+                    section_body_start_offset: report.section_body_start_offset,
+                    section_end_offset: report.section_end_offset,
+                    assign_delta,
+                });
+            }
+            cursor.advance(wb);
+        }
+        Self {
+            wbs,
+            assign_delta_base,
+            shared_state,
+            section_assignments,
+        }
+    }
+}
+
+/// Toolchain metadata of the `producers` custom section
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ProducersSection {
+    pub fields: Vec<ProducersField>,
+}
+
+/// Field of the `producers` section (`language`, `processed-by` or `sdk`) and its
+/// `(name, version)` values
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ProducersField {
+    pub name: String,
+    pub values: Vec<(String, String)>,
+}
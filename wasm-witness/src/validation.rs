@@ -0,0 +1,4 @@
+pub mod types;
+pub mod validator;
+
+pub use validator::validate_module;
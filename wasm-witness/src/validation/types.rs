@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Error,
+    types::{ProducersSection, Sn, WasmSection, WbOffsetType},
+};
+
+/// Offsets are bytecode offsets, end offsets are inclusive (same as in `WasmChip::assign_auto`)
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SectionReport {
+    pub section: WasmSection,
+    pub section_start_offset: WbOffsetType,
+    pub section_body_start_offset: WbOffsetType,
+    pub section_end_offset: WbOffsetType,
+    /// count prefix of the section body (func index for the start section), if section has one
+    pub items_count: Option<Sn>,
+}
+
+impl SectionReport {
+    pub fn section_body_len(&self) -> usize {
+        self.section_end_offset + 1 - self.section_body_start_offset
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidationReport {
+    pub sections: Vec<SectionReport>,
+    /// toolchain metadata, if the module has a `producers` custom section
+    pub producers: Option<ProducersSection>,
+    /// first error found, validation stops on it
+    pub error: Option<Error>,
+    /// section of the error if it is a malformed item of the section body
+    pub error_section: Option<WasmSection>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    pub fn section(&self, section: WasmSection) -> Option<&SectionReport> {
+        self.sections.iter().find(|s| s.section == section)
+    }
+}
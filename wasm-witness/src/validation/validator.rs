@@ -0,0 +1,145 @@
+use crate::{
+    common::{
+        digit_char_to_number, is_pass_through_custom_section_name, wasm_compute_section_len,
+        wasm_custom_section_name, wasm_producers_section,
+    },
+    consts::{
+        PRODUCERS_CUSTOM_SECTION_NAME, SECTION_ID_DEFAULT, WASM_MAGIC_PREFIX,
+        WASM_MAGIC_PREFIX_START_INDEX, WASM_SECTIONS_START_INDEX, WASM_VERSION_PREFIX,
+        WASM_VERSION_PREFIX_START_INDEX,
+    },
+    error::{
+        error_index_out_of_bounds, remap_error_to_compute_value_at,
+        remap_error_to_invalid_enum_value_at, Error,
+    },
+    decoder::decode_section_items,
+    leb128::helpers::leb128_compute_sn,
+    types::{Sn, WasmSection},
+    validation::types::{SectionReport, ValidationReport},
+};
+
+/// Checks the module layout without any halo2 machinery: magic prefix, version, section ids and
+/// their order, section lengths, the count prefix of the section bodies and the custom sections.
+/// The items of the section bodies are walked like the reference decoder does, see
+/// [`decode_section_items`], the section of a malformed item is reported with the error. The
+/// instructions of the function bodies are not decoded, so a module passing the check can still
+/// fail in `WasmChip::assign_auto`. Useful as a cheap preflight check before proving.
+pub fn validate_module(bytes: &[u8]) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    if let Err(e) = validate_module_internal(bytes, &mut report, true) {
+        report.error = Some(e);
+    }
+    report
+}
+
+/// Sections of the module up to the first framing error, their items are not walked. The
+/// assignment is planned from them, a malformed item is rejected by the chip of its section.
+pub fn module_sections(bytes: &[u8]) -> Vec<SectionReport> {
+    let mut report = ValidationReport::default();
+    validate_module_internal(bytes, &mut report, false).ok();
+    report.sections
+}
+
+fn validate_module_internal(
+    bytes: &[u8],
+    report: &mut ValidationReport,
+    walk_items: bool,
+) -> Result<(), Error> {
+    for (idx, ch) in WASM_MAGIC_PREFIX.chars().enumerate() {
+        let offset = WASM_MAGIC_PREFIX_START_INDEX + idx;
+        let byte_val = *bytes.get(offset).ok_or(error_index_out_of_bounds(offset))?;
+        if byte_val != (ch as u8) {
+            return Err(Error::InvalidByteValueAt(WASM_MAGIC_PREFIX_START_INDEX));
+        }
+    }
+    for (idx, ch) in WASM_VERSION_PREFIX.chars().enumerate() {
+        let offset = WASM_VERSION_PREFIX_START_INDEX + idx;
+        let byte_val = *bytes.get(offset).ok_or(error_index_out_of_bounds(offset))?;
+        if byte_val != digit_char_to_number(&ch) {
+            return Err(Error::InvalidByteValueAt(WASM_VERSION_PREFIX_START_INDEX));
+        }
+    }
+
+    let mut offset = WASM_SECTIONS_START_INDEX;
+    let mut section_id_prev = SECTION_ID_DEFAULT;
+    let mut is_custom_section_seen = false;
+    while offset < bytes.len() {
+        let section_start_offset = offset;
+        let section_id = bytes[offset] as i32;
+        let section: WasmSection = section_id
+            .try_into()
+            .map_err(remap_error_to_invalid_enum_value_at(offset))?;
+        // custom sections are accepted only after all the other sections
+        if section == WasmSection::Custom {
+            is_custom_section_seen = true;
+        } else if section_id < section_id_prev || is_custom_section_seen {
+            return Err(Error::InvalidByteValueAt(offset));
+        }
+        section_id_prev = section_id;
+        offset += 1;
+
+        let (section_len, section_len_leb_bytes_count) = wasm_compute_section_len(bytes, offset)
+            .map_err(remap_error_to_compute_value_at(offset))?;
+        let section_body_start_offset = offset + section_len_leb_bytes_count as usize;
+        if section_body_start_offset + section_len > bytes.len() {
+            return Err(Error::SectionLenExceededAt(section_body_start_offset - 1));
+        }
+        if section_len == 0 {
+            return Err(error_index_out_of_bounds(offset));
+        }
+        let section_end_offset = section_body_start_offset + section_len - 1;
+
+        let items_count = match section {
+            WasmSection::Custom => {
+                let name = wasm_custom_section_name(
+                    &bytes[..=section_end_offset],
+                    section_body_start_offset,
+                )
+                .map_err(remap_error_to_compute_value_at(section_body_start_offset))?;
+                if name == PRODUCERS_CUSTOM_SECTION_NAME {
+                    report.producers = Some(wasm_producers_section(
+                        &bytes[..=section_end_offset],
+                        section_body_start_offset,
+                    )?);
+                } else if !is_pass_through_custom_section_name(name) {
+                    return Err(Error::FatalUnsupportedValue(format!(
+                        "unsupported custom section '{}'",
+                        String::from_utf8_lossy(name)
+                    )));
+                }
+                None
+            }
+            WasmSection::DataCount => {
+                return Err(Error::FatalUnsupportedValue(format!(
+                    "unsupported section value '{:x?}'",
+                    section
+                )))
+            }
+            _ => Some(section_items_count(bytes, section_body_start_offset)?),
+        };
+        if walk_items {
+            let body = section_body_start_offset..section_end_offset + 1;
+            if let Err(e) = decode_section_items(bytes, section, body) {
+                report.error_section = Some(section);
+                return Err(e);
+            }
+        }
+
+        report.sections.push(SectionReport {
+            section,
+            section_start_offset,
+            section_body_start_offset,
+            section_end_offset,
+            items_count,
+        });
+        offset = section_end_offset + 1;
+    }
+
+    Ok(())
+}
+
+fn section_items_count(bytes: &[u8], section_body_start_offset: usize) -> Result<Sn, Error> {
+    let (items_count, _) = leb128_compute_sn(bytes, false, section_body_start_offset)
+        .map_err(remap_error_to_compute_value_at(section_body_start_offset))?;
+    Ok(items_count)
+}
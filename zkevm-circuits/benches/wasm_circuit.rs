@@ -0,0 +1,146 @@
+//! Witness generation and proving benchmarks of the WASM circuit.
+//!
+//! Run with `cargo bench -p zkevm-circuits --bench wasm_circuit`, add
+//! `--features prover-thread-pool` to compare proving on dedicated thread pools.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use rand::rngs::OsRng;
+use wabt::wat2wasm;
+
+use zkevm_circuits::wasm_circuit::{
+    bytecode::bytecode::WasmBytecode,
+    circuit::WasmCircuit,
+    common::SectionBodyChip,
+    prover::prover::{Kzg, WasmProver},
+    sections::{
+        code::body::circuit::WasmCodeSectionBodyChip,
+        data::body::circuit::WasmDataSectionBodyChip,
+        export::body::circuit::WasmExportSectionBodyChip,
+        function::body::circuit::WasmFunctionSectionBodyChip,
+        global::body::circuit::WasmGlobalSectionBodyChip,
+        harness::circuit::SectionTestCircuit,
+        import::body::circuit::WasmImportSectionBodyChip,
+        r#type::body::circuit::WasmTypeSectionBodyChip,
+    },
+};
+
+const PATHS: [&str; 3] = [
+    "./test_files/cc1.wat",
+    "./test_files/cc2.wat",
+    "./test_files/cc3.wat",
+];
+
+fn read_module(path: &str) -> Vec<u8> {
+    let data: Vec<u8> = std::fs::read(path).unwrap();
+    wat2wasm(data).unwrap()
+}
+
+/// (name, bytecodes, minimal k) for small (single file), medium (all files) and large (all files
+/// several times) batches
+fn module_sets() -> Vec<(&'static str, Vec<WasmBytecode>, u32)> {
+    let all = PATHS
+        .iter()
+        .map(|path| WasmBytecode::new(read_module(path)))
+        .collect::<Vec<_>>();
+    vec![
+        ("small", all[..1].to_vec(), 9),
+        ("medium", all.clone(), 13),
+        ("large", all.repeat(4), 15),
+    ]
+}
+
+fn bench_section<C: SectionBodyChip<Fr>>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group("wasm_section_assign_auto");
+    for path in PATHS {
+        let circuit = match SectionTestCircuit::<Fr, C>::from_module(read_module(path)) {
+            Some(circuit) => circuit,
+            None => continue,
+        };
+        group.throughput(Throughput::Bytes(circuit.bytecode.len() as u64));
+        group.bench_with_input(BenchmarkId::new(name, path), &circuit, |b, circuit| {
+            b.iter(|| MockProver::run(8, circuit, vec![]).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_sections(c: &mut Criterion) {
+    bench_section::<WasmTypeSectionBodyChip<Fr>>(c, "type");
+    bench_section::<WasmImportSectionBodyChip<Fr>>(c, "import");
+    bench_section::<WasmFunctionSectionBodyChip<Fr>>(c, "function");
+    bench_section::<WasmGlobalSectionBodyChip<Fr>>(c, "global");
+    bench_section::<WasmExportSectionBodyChip<Fr>>(c, "export");
+    bench_section::<WasmCodeSectionBodyChip<Fr>>(c, "code");
+    bench_section::<WasmDataSectionBodyChip<Fr>>(c, "data");
+}
+
+fn bench_synthesize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wasm_circuit_synthesize");
+    group.sample_size(10);
+    for (name, wbs, k) in module_sets() {
+        let bytes_len = wbs.iter().map(|wb| wb.bytes.len()).sum::<usize>();
+        group.throughput(Throughput::Bytes(bytes_len as u64));
+        let circuit = WasmCircuit::<Fr>::new(wbs);
+        group.bench_with_input(BenchmarkId::new(name, k), &circuit, |b, circuit| {
+            b.iter(|| circuit.compute_module_outputs(k).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_prove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wasm_circuit_prove");
+    group.sample_size(10);
+    for (name, wbs, k_min) in module_sets() {
+        for k in [k_min, k_min + 1] {
+            let mut prover = WasmProver::<Kzg>::setup(k, OsRng);
+            // keygen is not a part of the measurement
+            prover.proving_key(&wbs).unwrap();
+            group.bench_with_input(BenchmarkId::new(name, k), &wbs, |b, wbs| {
+                b.iter(|| prover.prove(wbs).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Proving on the global pool against dedicated pools of growing size
+#[cfg(feature = "prover-thread-pool")]
+fn bench_prove_on_pool(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wasm_circuit_prove_on_pool");
+    group.sample_size(10);
+    let (_, wbs, k) = module_sets().swap_remove(1);
+    let mut prover = WasmProver::<Kzg>::setup(k, OsRng);
+    prover.proving_key(&wbs).unwrap();
+    group.bench_with_input(BenchmarkId::new("default", k), &wbs, |b, wbs| {
+        b.iter(|| prover.prove(wbs).unwrap())
+    });
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut num_threads = 1;
+    while num_threads <= max_threads {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap();
+        group.bench_with_input(
+            BenchmarkId::new(format!("threads_{}", num_threads), k),
+            &wbs,
+            |b, wbs| b.iter(|| prover.prove_on_pool(wbs, &pool).unwrap()),
+        );
+        num_threads *= 2;
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "prover-thread-pool"))]
+criterion_group!(benches, bench_sections, bench_synthesize, bench_prove);
+#[cfg(feature = "prover-thread-pool")]
+criterion_group!(
+    benches,
+    bench_sections,
+    bench_synthesize,
+    bench_prove,
+    bench_prove_on_pool
+);
+criterion_main!(benches);
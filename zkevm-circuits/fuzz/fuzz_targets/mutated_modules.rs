@@ -0,0 +1,68 @@
+//! Mutates bytes of a known-good module and runs witness generation and `MockProver::verify`.
+//! The circuit must be satisfied for any bytes, and structurally broken bytecode must end up on
+//! the error code path.
+#![no_main]
+
+use arbitrary::{Arbitrary, Unstructured};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use libfuzzer_sys::fuzz_target;
+use once_cell::sync::Lazy;
+use wabt::wat2wasm;
+use zkevm_circuits::wasm_circuit::{
+    bytecode::bytecode::WasmBytecode, circuit::WasmCircuit, validation::validate_module,
+};
+
+const K: u32 = 14;
+
+/// Same module the circuit tests start from
+static SEED_MODULE: Lazy<Vec<u8>> =
+    Lazy::new(|| wat2wasm(include_str!("../../test_files/cc1.wat")).unwrap());
+
+#[derive(Arbitrary, Debug)]
+enum Mutation {
+    Set { index: usize, value: u8 },
+    Remove { index: usize },
+    Insert { index: usize, value: u8 },
+    Truncate { len: usize },
+}
+
+impl Mutation {
+    fn apply(&self, bytes: &mut Vec<u8>) {
+        match *self {
+            Mutation::Set { index, value } => {
+                let len = bytes.len();
+                bytes[index % len] = value;
+            }
+            Mutation::Remove { index } => {
+                let len = bytes.len();
+                bytes.remove(index % len);
+            }
+            Mutation::Insert { index, value } => bytes.insert(index % (bytes.len() + 1), value),
+            Mutation::Truncate { len } => bytes.truncate(len % bytes.len()),
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut u = Unstructured::new(data);
+    let mut bytes = SEED_MODULE.to_vec();
+    while let Ok(mutation) = Mutation::arbitrary(&mut u) {
+        if bytes.is_empty() || u.is_empty() {
+            break;
+        }
+        mutation.apply(&mut bytes);
+    }
+    if bytes.is_empty() {
+        return;
+    }
+
+    let circuit = WasmCircuit::<Fr>::new(vec![WasmBytecode::new(bytes.clone())]);
+    let outputs = circuit.compute_module_outputs(K).unwrap();
+    let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+    let prover = MockProver::run(K, &circuit, instance).unwrap();
+    prover.verify().unwrap();
+
+    if !validate_module(&bytes).is_ok() {
+        assert_eq!(outputs[0].error_code, 1, "broken bytecode accepted: {:?}", bytes);
+    }
+});
@@ -0,0 +1,72 @@
+//! Generates valid modules restricted to the MVP subset handled by the circuit, runs witness
+//! generation and `MockProver::verify`. The circuit must be satisfied for every module, and a
+//! module reported with zero error code must be valid according to wasmparser.
+#![no_main]
+
+use arbitrary::{Result, Unstructured};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use libfuzzer_sys::fuzz_target;
+use wasm_smith::{Config, Module};
+use zkevm_circuits::wasm_circuit::{bytecode::bytecode::WasmBytecode, circuit::WasmCircuit};
+
+const K: u32 = 14;
+const MAX_BYTECODE_LEN: usize = 2048;
+
+#[derive(Clone, Debug)]
+struct CircuitSubsetConfig;
+
+impl Config for CircuitSubsetConfig {
+    fn max_funcs(&self) -> usize {
+        8
+    }
+    fn max_instructions(&self) -> usize {
+        32
+    }
+    fn max_memories(&self) -> usize {
+        1
+    }
+    fn max_tables(&self) -> usize {
+        1
+    }
+    fn max_data_segments(&self) -> usize {
+        4
+    }
+    fn max_elements(&self) -> usize {
+        4
+    }
+    fn bulk_memory_enabled(&self) -> bool {
+        false
+    }
+    fn reference_types_enabled(&self) -> bool {
+        false
+    }
+    fn simd_enabled(&self) -> bool {
+        false
+    }
+    fn multi_value_enabled(&self) -> bool {
+        false
+    }
+}
+
+fn run(data: &[u8]) -> Result<()> {
+    let mut u = Unstructured::new(data);
+    let bytes = Module::new(CircuitSubsetConfig, &mut u)?.to_bytes();
+    if bytes.len() > MAX_BYTECODE_LEN {
+        return Ok(());
+    }
+
+    let circuit = WasmCircuit::<Fr>::new(vec![WasmBytecode::new(bytes.clone())]);
+    let outputs = circuit.compute_module_outputs(K).unwrap();
+    let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+    let prover = MockProver::run(K, &circuit, instance).unwrap();
+    prover.verify().unwrap();
+
+    if outputs[0].error_code == 0 {
+        wasmparser::validate(&bytes).unwrap();
+    }
+    Ok(())
+}
+
+fuzz_target!(|data: &[u8]| {
+    let _ = run(data);
+});
@@ -30,10 +30,15 @@ use crate::{
     evm_circuit::param::{MAX_STEP_HEIGHT, STEP_STATE_HEIGHT},
     table::{
         BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, LookupTable, RwTable, TxTable,
+        WasmFunctionTable,
     },
     util::{SubCircuit, SubCircuitConfig},
+    wasm_circuit::{builder::WasmCircuitOptions, bytecode::bytecode::WasmBytecode},
+};
+use bus_mapping::{
+    circuit_input_builder::{ChainSpec, FeeModel},
+    evm::OpcodeId,
 };
-use bus_mapping::evm::OpcodeId;
 use eth_types::Field;
 use execution::ExecutionConfig;
 use itertools::Itertools;
@@ -55,6 +60,7 @@ pub struct EvmCircuitConfig<F> {
     copy_table: CopyTable,
     keccak_table: KeccakTable,
     exp_table: ExpTable,
+    wasm_function_table: WasmFunctionTable,
 }
 
 /// Circuit configuration arguments
@@ -75,6 +81,13 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub keccak_table: KeccakTable,
     /// ExpTable
     pub exp_table: ExpTable,
+    /// WasmFunctionTable
+    pub wasm_function_table: WasmFunctionTable,
+    /// Fee model of the end tx gadget, must match the one of the circuit input builder
+    pub fee_model: FeeModel,
+    /// Memory page limit of the wasm memory gadget, must match the one of the circuit input
+    /// builder
+    pub max_memory_pages: u64,
 }
 
 /// Circuit exported cells after synthesis, used for subcircuit
@@ -100,6 +113,9 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            wasm_function_table,
+            fee_model,
+            max_memory_pages,
         }: Self::ConfigArgs,
     ) -> Self {
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
@@ -116,6 +132,9 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             &copy_table,
             &keccak_table,
             &exp_table,
+            &wasm_function_table,
+            fee_model,
+            max_memory_pages,
         ));
 
         meta.annotate_lookup_any_column(byte_table[0], || "byte_range");
@@ -129,6 +148,7 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
         copy_table.annotate_columns(meta);
         keccak_table.annotate_columns(meta);
         exp_table.annotate_columns(meta);
+        wasm_function_table.annotate_columns(meta);
 
         Self {
             fixed_table,
@@ -141,6 +161,7 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            wasm_function_table,
         }
     }
 }
@@ -151,12 +172,13 @@ impl<F: Field> EvmCircuitConfig<F> {
         &self,
         layouter: &mut impl Layouter<F>,
         fixed_table_tags: Vec<FixedTableTag>,
+        chain_spec: &ChainSpec,
     ) -> Result<(), Error> {
         layouter.assign_region(
             || "fixed table",
             |mut region| {
                 for (offset, row) in std::iter::once([F::zero(); 4])
-                    .chain(fixed_table_tags.iter().flat_map(|tag| tag.build()))
+                    .chain(fixed_table_tags.iter().flat_map(|tag| tag.build(chain_spec)))
                     .enumerate()
                 {
                     for (column, value) in self.fixed_table.iter().zip_eq(row) {
@@ -280,7 +302,7 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
             Self::get_num_rows_required_no_padding(block);
         let num_rows_required_for_fixed_table: usize = detect_fixed_table_tags(block)
             .iter()
-            .map(|tag| tag.build::<F>().count())
+            .map(|tag| tag.build::<F>(&block.chain_spec).count())
             .sum();
         (
             num_rows_required_for_execution_steps,
@@ -303,7 +325,7 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
     ) -> Result<(), Error> {
         let block = self.block.as_ref().unwrap();
 
-        config.load_fixed_table(layouter, self.fixed_table_tags.clone())?;
+        config.load_fixed_table(layouter, self.fixed_table_tags.clone(), &block.chain_spec)?;
         config.load_byte_table(layouter)?;
         let export = config.execution.assign_block(layouter, block, challenges)?;
         self.exports.borrow_mut().replace(export);
@@ -417,6 +439,7 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
         let copy_table = CopyTable::construct(meta, q_copy_table);
         let keccak_table = KeccakTable::construct(meta);
         let exp_table = ExpTable::construct(meta);
+        let wasm_function_table = WasmFunctionTable::construct(meta);
         (
             EvmCircuitConfig::new(
                 meta,
@@ -429,6 +452,9 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
                     copy_table,
                     keccak_table,
                     exp_table,
+                    wasm_function_table,
+                    fee_model: FeeModel::default(),
+                    max_memory_pages: WasmCircuitOptions::default().max_memory_pages(),
                 },
             ),
             challenges,
@@ -473,6 +499,14 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
             .keccak_table
             .dev_load(&mut layouter, &block.sha3_inputs, &challenges)?;
         config.exp_table.dev_load(&mut layouter, block)?;
+        let wasm_bytecodes = block
+            .bytecodes
+            .values()
+            .map(|bytecode| WasmBytecode::new(bytecode.bytes.clone()))
+            .collect_vec();
+        config
+            .wasm_function_table
+            .dev_load(&mut layouter, &wasm_bytecodes, &challenges)?;
 
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
@@ -654,7 +688,9 @@ mod evm_circuit_stats {
             keccak_table,
             LOOKUP_CONFIG[6].1,
             exp_table,
-            LOOKUP_CONFIG[7].1
+            LOOKUP_CONFIG[7].1,
+            wasm_function_table,
+            LOOKUP_CONFIG[8].1
         );
     }
 
@@ -54,7 +54,10 @@ pub enum ExecutionState {
     WASM_GLOBAL,
     WASM_LOAD,
     WASM_LOCAL,
+    WASM_MEMORY,
     WASM_REL,
+    WASM_RETURN,
+    WASM_RETURN_CALL,
     WASM_SELECT,
     WASM_STORE,
     WASM_TEST,
@@ -103,6 +106,9 @@ pub enum ExecutionState {
     MEMORY, // MLOAD, MSTORE, MSTORE8
     SLOAD,
     SSTORE,
+    TLOAD,
+    TSTORE,
+    MCOPY,
     JUMP,
     JUMPI,
     PC,
@@ -139,6 +145,12 @@ pub enum ExecutionState {
     ErrorOutOfGasSloadSstore,
     ErrorOutOfGasCREATE,
     ErrorOutOfGasSELFDESTRUCT,
+    // WASM traps
+    ErrorWasmUnreachable,
+    ErrorWasmMemoryOutOfBounds,
+    ErrorWasmDivisionByZero,
+    ErrorWasmIndirectCallMismatch,
+    ErrorWasmStackHeightExceeded,
     // Precompiles
     PrecompileEcRecover,
     PrecompileSha256,
@@ -225,6 +237,11 @@ impl ExecutionState {
                 | Self::ErrorOutOfGasSloadSstore
                 | Self::ErrorOutOfGasCREATE
                 | Self::ErrorOutOfGasSELFDESTRUCT
+                | Self::ErrorWasmUnreachable
+                | Self::ErrorWasmMemoryOutOfBounds
+                | Self::ErrorWasmDivisionByZero
+                | Self::ErrorWasmIndirectCallMismatch
+                | Self::ErrorWasmStackHeightExceeded
         )
     }
 
@@ -264,7 +281,6 @@ impl ExecutionState {
                 OpcodeId::I64RemU,
             ],
             Self::WASM_BREAK => vec![
-                OpcodeId::Return,
                 OpcodeId::Br,
                 OpcodeId::BrIf,
                 OpcodeId::BrTable,
@@ -312,6 +328,15 @@ impl ExecutionState {
                 OpcodeId::SetLocal,
                 OpcodeId::TeeLocal,
             ],
+            Self::WASM_MEMORY => vec![
+                OpcodeId::CurrentMemory,
+                OpcodeId::GrowMemory,
+            ],
+            Self::WASM_RETURN => vec![OpcodeId::Return],
+            Self::WASM_RETURN_CALL => vec![
+                OpcodeId::ReturnCall,
+                OpcodeId::ReturnCallIndirect,
+            ],
             Self::WASM_END => vec![OpcodeId::End],
             // EVM opcodes
             Self::STOP => vec![OpcodeId::STOP],
@@ -358,6 +383,9 @@ impl ExecutionState {
             }
             Self::SLOAD => vec![OpcodeId::SLOAD],
             Self::SSTORE => vec![OpcodeId::SSTORE],
+            Self::TLOAD => vec![OpcodeId::TLOAD],
+            Self::TSTORE => vec![OpcodeId::TSTORE],
+            Self::MCOPY => vec![OpcodeId::MCOPY],
             Self::JUMP => vec![OpcodeId::JUMP],
             Self::JUMPI => vec![OpcodeId::JUMPI],
             Self::PC => vec![OpcodeId::PC],
@@ -382,6 +410,39 @@ impl ExecutionState {
             Self::RETURN_REVERT => vec![OpcodeId::RETURN, OpcodeId::REVERT],
             Self::SELFDESTRUCT => vec![OpcodeId::SELFDESTRUCT],
             Self::ErrorInvalidOpcode => OpcodeId::invalid_opcodes(),
+            Self::ErrorWasmUnreachable => vec![OpcodeId::Unreachable],
+            Self::ErrorWasmMemoryOutOfBounds => vec![
+                OpcodeId::I32Load,
+                OpcodeId::I64Load,
+                OpcodeId::I32Load8S,
+                OpcodeId::I32Load8U,
+                OpcodeId::I32Load16S,
+                OpcodeId::I32Load16U,
+                OpcodeId::I64Load8S,
+                OpcodeId::I64Load8U,
+                OpcodeId::I64Load16S,
+                OpcodeId::I64Load16U,
+                OpcodeId::I64Load32S,
+                OpcodeId::I64Load32U,
+                OpcodeId::I32Store,
+                OpcodeId::I64Store,
+                OpcodeId::I32Store8,
+                OpcodeId::I32Store16,
+                OpcodeId::I64Store8,
+                OpcodeId::I64Store16,
+                OpcodeId::I64Store32,
+            ],
+            Self::ErrorWasmDivisionByZero => vec![
+                OpcodeId::I32DivS,
+                OpcodeId::I32DivU,
+                OpcodeId::I32RemS,
+                OpcodeId::I32RemU,
+                OpcodeId::I64DivS,
+                OpcodeId::I64DivU,
+                OpcodeId::I64RemS,
+                OpcodeId::I64RemU,
+            ],
+            Self::ErrorWasmIndirectCallMismatch => vec![OpcodeId::CallIndirect],
             _ => vec![],
         }
             .into_iter()
@@ -3,7 +3,9 @@ use crate::{
     evm_circuit::step::{ExecutionState, ResponsibleOp},
     impl_expr,
 };
-use bus_mapping::{evm::OpcodeId, precompile::PrecompileCalls};
+use bus_mapping::{
+    circuit_input_builder::ChainSpec, evm::OpcodeId, precompile::PrecompileCalls,
+};
 use eth_types::Field;
 use gadgets::util::Expr;
 use halo2_proofs::plonk::Expression;
@@ -40,7 +42,7 @@ pub enum FixedTableTag {
 impl_expr!(FixedTableTag);
 
 impl FixedTableTag {
-    pub fn build<F: Field>(&self) -> Box<dyn Iterator<Item = [F; 4]>> {
+    pub fn build<F: Field>(&self, chain_spec: &ChainSpec) -> Box<dyn Iterator<Item = [F; 4]>> {
         let tag = F::from(*self as u64);
         match self {
             Self::Zero => Box::new((0..1).map(move |_| [tag, F::zero(), F::zero(), F::zero()])),
@@ -89,24 +91,39 @@ impl FixedTableTag {
                 (0..256).map(move |rhs| [tag, F::from(lhs), F::from(rhs), F::from(lhs ^ rhs)])
             })),
             Self::ResponsibleOpcode => {
-                Box::new(ExecutionState::iter().flat_map(move |execution_state| {
-                    execution_state.responsible_opcodes().into_iter().map(
-                        move |responsible_opcode| {
-                            let (op, aux) = match responsible_opcode {
-                                ResponsibleOp::Op(op) => (op, F::zero()),
-                                ResponsibleOp::InvalidStackPtr(op, stack_ptr) => {
-                                    (op, F::from(u64::from(stack_ptr)))
-                                }
-                            };
+                // opcodes disabled by the chain spec can only halt with an invalid opcode error
+                let chain_spec = *chain_spec;
+                let disabled = chain_spec
+                    .disabled_opcodes()
+                    .into_iter()
+                    .map(|op| (ExecutionState::ErrorInvalidOpcode, op, F::zero()));
+                Box::new(
+                    ExecutionState::iter()
+                        .flat_map(move |execution_state| {
+                            execution_state.responsible_opcodes().into_iter().filter_map(
+                                move |responsible_opcode| {
+                                    let (op, aux) = match responsible_opcode {
+                                        ResponsibleOp::Op(op) => (op, F::zero()),
+                                        ResponsibleOp::InvalidStackPtr(op, stack_ptr) => {
+                                            (op, F::from(u64::from(stack_ptr)))
+                                        }
+                                    };
+                                    chain_spec
+                                        .is_opcode_enabled(op)
+                                        .then_some((execution_state, op, aux))
+                                },
+                            )
+                        })
+                        .chain(disabled)
+                        .map(move |(execution_state, op, aux)| {
                             [
                                 tag,
                                 F::from(execution_state.as_u64()),
                                 F::from(op.as_u64()),
                                 aux,
                             ]
-                        },
-                    )
-                }))
+                        }),
+                )
             }
             Self::Pow2 => Box::new((0..256).map(move |value| {
                 let (pow_lo, pow_hi) = if value < 128 {
@@ -211,6 +228,7 @@ pub(crate) enum Table {
     Copy,
     Keccak,
     Exp,
+    WasmFunction,
 }
 
 #[derive(Clone, Debug)]
@@ -360,6 +378,17 @@ pub(crate) enum Lookup<F> {
         exponent_lo_hi: [Expression<F>; 2],
         exponentiation_lo_hi: [Expression<F>; 2],
     },
+    /// Lookup to wasm function table.
+    WasmFunctionTable {
+        /// Hash of the code defining the function.
+        code_hash: Expression<F>,
+        /// Index of the function.
+        function_index: Expression<F>,
+        /// Number of locals declared by the function.
+        num_locals: Expression<F>,
+        /// Type index of the function.
+        type_index: Expression<F>,
+    },
     /// Conditional lookup enabled by the first element.
     Conditional(Expression<F>, Box<Lookup<F>>),
 }
@@ -379,6 +408,7 @@ impl<F: Field> Lookup<F> {
             Self::CopyTable { .. } => Table::Copy,
             Self::KeccakTable { .. } => Table::Keccak,
             Self::ExpTable { .. } => Table::Exp,
+            Self::WasmFunctionTable { .. } => Table::WasmFunction,
             Self::Conditional(_, lookup) => lookup.table(),
         }
     }
@@ -501,6 +531,18 @@ impl<F: Field> Lookup<F> {
                 exponentiation_lo_hi[0].clone(),
                 exponentiation_lo_hi[1].clone(),
             ],
+            Self::WasmFunctionTable {
+                code_hash,
+                function_index,
+                num_locals,
+                type_index,
+            } => vec![
+                1.expr(), // q_enable
+                code_hash.clone(),
+                function_index.clone(),
+                num_locals.clone(),
+                type_index.clone(),
+            ],
             Self::Conditional(condition, lookup) => lookup
                 .input_exprs()
                 .into_iter()
@@ -1,11 +1,14 @@
 #![allow(unused_imports)]
 pub use super::EvmCircuit;
-use crate::evm_circuit::{detect_fixed_table_tags, witness::Block};
+use crate::{
+    evm_circuit::{detect_fixed_table_tags, witness::Block},
+    test_rng::test_rng,
+};
 
 use eth_types::{Field, Word};
 use rand::{
     distributions::uniform::{SampleRange, SampleUniform},
-    random, thread_rng, Rng,
+    Rng,
 };
 
 pub(crate) fn rand_range<T, R>(range: R) -> T
@@ -13,15 +16,15 @@ where
     T: SampleUniform,
     R: SampleRange<T>,
 {
-    thread_rng().gen_range(range)
+    test_rng().gen_range(range)
 }
 
 pub(crate) fn rand_bytes(n: usize) -> Vec<u8> {
-    (0..n).map(|_| random()).collect()
+    (0..n).map(|_| test_rng().gen()).collect()
 }
 
 pub(crate) fn rand_bytes_array<const N: usize>() -> [u8; N] {
-    [(); N].map(|_| random())
+    [(); N].map(|_| test_rng().gen())
 }
 
 pub(crate) fn rand_word() -> Word {
@@ -12,10 +12,11 @@ use crate::{
     util::{build_tx_log_expression, Challenges, Expr},
 };
 use bus_mapping::{
+    circuit_input_builder::FeeModel,
     state_db::EMPTY_CODE_HASH_LE,
     util::{KECCAK_CODE_HASH_ZERO, POSEIDON_CODE_HASH_ZERO},
 };
-use eth_types::{Field, ToLittleEndian, ToScalar, ToWord};
+use eth_types::{evm_types::MAX_WASM_MEMORY_PAGES, Field, ToLittleEndian, ToScalar, ToWord};
 use gadgets::util::{and, not};
 use halo2_proofs::{
     circuit::Value,
@@ -306,6 +307,8 @@ pub(crate) struct EVMConstraintBuilder<'a, F> {
     constraints_location: ConstraintLocation,
     stored_expressions: Vec<StoredExpression<F>>,
     pub(crate) max_inner_degree: (&'static str, usize),
+    pub(crate) fee_model: FeeModel,
+    pub(crate) max_memory_pages: u64,
 }
 
 impl<'a, F: Field> ConstrainBuilderCommon<F> for EVMConstraintBuilder<'a, F> {
@@ -349,6 +352,8 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
             constraints_location: ConstraintLocation::Step,
             stored_expressions: Vec::new(),
             max_inner_degree: ("", 0),
+            fee_model: FeeModel::default(),
+            max_memory_pages: MAX_WASM_MEMORY_PAGES,
         }
     }
 
@@ -1098,6 +1103,56 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    pub(crate) fn transient_storage_read(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        key: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "TransientStorage read",
+            false.expr(),
+            RwTableTag::TransientStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                key,
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
+    pub(crate) fn transient_storage_write(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        key: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "TransientStorage write",
+            RwTableTag::TransientStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                key,
+                value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ),
+            reversion_info,
+        );
+    }
+
     // Call context
 
     pub(crate) fn call_context(
@@ -1190,15 +1245,26 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
 
     // Global
 
-    pub(crate) fn global_write(&mut self, index: Expression<F>, value: Expression<F>) {
-        self.global_lookup(1.expr(), index, value)
+    pub(crate) fn global_write(
+        &mut self,
+        index: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+    ) {
+        self.global_lookup(1.expr(), index, value, value_prev)
     }
 
     pub(crate) fn global_read(&mut self, index: Expression<F>, value: Expression<F>) {
-        self.global_lookup(0.expr(), index, value)
+        self.global_lookup(0.expr(), index, value.clone(), value)
     }
 
-    pub(crate) fn global_lookup(&mut self, is_write: Expression<F>, index: Expression<F>, value: Expression<F>) {
+    pub(crate) fn global_lookup(
+        &mut self,
+        is_write: Expression<F>,
+        index: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+    ) {
         self.rw_lookup(
             "Global lookup",
             is_write,
@@ -1209,7 +1275,32 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
                 0.expr(),
                 0.expr(),
                 value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
+    // Table
+
+    pub(crate) fn table_read(
+        &mut self,
+        table_index: Expression<F>,
+        elem_index: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "Table lookup",
+            0.expr(),
+            RwTableTag::Table,
+            RwValues::new(
+                self.curr.state.call_id.expr(),
+                elem_index,
+                table_index,
                 0.expr(),
+                value.clone(),
+                value,
                 0.expr(),
                 0.expr(),
             ),
@@ -1453,6 +1544,26 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // Wasm Function Table
+
+    pub(crate) fn wasm_function_table_lookup(
+        &mut self,
+        code_hash: Expression<F>,
+        function_index: Expression<F>,
+        num_locals: Expression<F>,
+        type_index: Expression<F>,
+    ) {
+        self.add_lookup(
+            "wasm function lookup",
+            Lookup::WasmFunctionTable {
+                code_hash,
+                function_index,
+                num_locals,
+                type_index,
+            },
+        );
+    }
+
     // Keccak Table
 
     pub(crate) fn keccak_table_lookup(
@@ -103,6 +103,9 @@ impl Instrument {
                     CellType::Lookup(Table::Exp) => {
                         report.exp_table = data_entry;
                     }
+                    CellType::Lookup(Table::WasmFunction) => {
+                        report.wasm_function_table = data_entry;
+                    }
                 }
             }
             report_collection.push(report);
@@ -129,6 +132,7 @@ pub(crate) struct ExecStateReport {
     pub(crate) copy_table: StateReportRow,
     pub(crate) keccak_table: StateReportRow,
     pub(crate) exp_table: StateReportRow,
+    pub(crate) wasm_function_table: StateReportRow,
 }
 
 impl From<ExecutionState> for ExecStateReport {
@@ -21,6 +21,7 @@ use crate::{
     util::{query_expression, Challenges, Expr},
 };
 
+use bus_mapping::circuit_input_builder::FeeModel;
 use eth_types::{evm_unimplemented, Field, ToLittleEndian};
 use gadgets::util::not;
 use halo2_proofs::{
@@ -51,6 +52,7 @@ mod error_oog_dynamic_memory;
 mod error_oog_memory_copy;
 mod error_oog_sha3;
 mod error_precompile_failed;
+mod precompiles;
 
 mod common_begin_tx;
 mod common_block_ctx;
@@ -67,6 +69,7 @@ mod error_oog_sload_sstore;
 mod error_oog_static_memory;
 mod error_return_data_oo_bound;
 mod error_stack;
+mod error_wasm_trap;
 mod error_write_protection;
 mod evm_address;
 mod evm_balance;
@@ -87,6 +90,7 @@ mod evm_gas;
 mod evm_gasprice;
 mod evm_keccak256;
 mod evm_log;
+mod evm_mcopy;
 mod evm_msize;
 mod evm_origin;
 mod evm_pc;
@@ -96,6 +100,8 @@ mod evm_returndatasize;
 mod evm_selfbalance;
 mod evm_sload;
 mod evm_sstore;
+mod evm_tload;
+mod evm_tstore;
 mod evm_stop;
 mod wasm_bin;
 mod wasm_break;
@@ -107,7 +113,11 @@ mod wasm_end;
 mod wasm_global;
 // mod wasm_load;
 mod wasm_local;
+mod wasm_memory;
 mod wasm_rel;
+mod wasm_return;
+#[cfg(feature = "tail-call")]
+mod wasm_return_call;
 mod wasm_select;
 // mod wasm_store;
 mod wasm_test;
@@ -128,6 +138,7 @@ use error_oog_sload_sstore::ErrorOOGSloadSstoreGadget;
 // use error_oog_static_memory::ErrorOOGStaticMemoryGadget;
 use error_return_data_oo_bound::ErrorReturnDataOutOfBoundGadget;
 use error_stack::ErrorStackGadget;
+use error_wasm_trap::ErrorWasmTrapGadget;
 use error_write_protection::ErrorWriteProtectionGadget;
 use evm_address::EvmAddressGadget;
 use evm_balance::EvmBalanceGadget;
@@ -148,6 +159,7 @@ use evm_gas::EvmGasGadget;
 use evm_gasprice::EvmGasPriceGadget;
 use evm_keccak256::EvmKeccak256Gadget;
 use evm_log::EvmLogGadget;
+use evm_mcopy::EvmMcopyGadget;
 use evm_msize::EvmMsizeGadget;
 use evm_origin::EvmOriginGadget;
 use evm_pc::EvmPcGadget;
@@ -157,6 +169,8 @@ use evm_returndatasize::EvmReturnDataSizeGadget;
 use evm_selfbalance::EvmSelfBalanceGadget;
 use evm_sload::EvmSloadGadget;
 use evm_sstore::EvmSstoreGadget;
+use evm_tload::EvmTloadGadget;
+use evm_tstore::EvmTstoreGadget;
 use evm_stop::EvmStopGadget;
 use wasm_bin::WasmBinGadget;
 use wasm_break::WasmBreakGadget;
@@ -168,7 +182,11 @@ use wasm_end::WasmEndGadget;
 use wasm_global::WasmGlobalGadget;
 // use wasm_load::WasmLoadGadget;
 use wasm_local::WasmLocalGadget;
+use wasm_memory::WasmMemoryGadget;
 use wasm_rel::WasmRelGadget;
+use wasm_return::WasmReturnGadget;
+#[cfg(feature = "tail-call")]
+use wasm_return_call::WasmReturnCallGadget;
 use wasm_select::WasmSelectGadget;
 // use wasm_store::WasmStoreGadget;
 use wasm_test::WasmTestGadget;
@@ -178,6 +196,7 @@ use crate::evm_circuit::wasm::end_inner_block::EndInnerBlockGadget;
 use crate::evm_circuit::wasm::error_code_store::ErrorCodeStoreGadget;
 use crate::evm_circuit::wasm::error_invalid_creation_code::ErrorInvalidCreationCodeGadget;
 use crate::evm_circuit::wasm::error_precompile_failed::ErrorPrecompileFailedGadget;
+use crate::evm_circuit::wasm::precompiles::{BasePrecompileGadget, IdentityGadget};
 use crate::evm_circuit::wasm::error_oog_account_access::ErrorOOGAccountAccessGadget;
 use crate::table::{RwTableTag, TxReceiptFieldTag};
 
@@ -258,7 +277,26 @@ pub(crate) struct ExecutionConfig<F> {
     error_invalid_opcode: Box<ErrorInvalidOpcodeGadget<F>>,
     error_invalid_creation_code: Box<ErrorInvalidCreationCodeGadget<F>>,
     error_precompile_failed: Box<ErrorPrecompileFailedGadget<F>>,
+    // precompile calls
+    precompile_ecrecover_gadget:
+        Box<BasePrecompileGadget<F, { ExecutionState::PrecompileEcRecover }>>,
+    precompile_sha2_gadget: Box<BasePrecompileGadget<F, { ExecutionState::PrecompileSha256 }>>,
+    precompile_ripemd_gadget: Box<BasePrecompileGadget<F, { ExecutionState::PrecompileRipemd160 }>>,
+    precompile_identity_gadget: Box<IdentityGadget<F>>,
+    precompile_modexp_gadget: Box<BasePrecompileGadget<F, { ExecutionState::PrecompileBigModExp }>>,
+    precompile_bn128add_gadget:
+        Box<BasePrecompileGadget<F, { ExecutionState::PrecompileBn256Add }>>,
+    precompile_bn128mul_gadget:
+        Box<BasePrecompileGadget<F, { ExecutionState::PrecompileBn256ScalarMul }>>,
+    precompile_bn128pairing_gadget:
+        Box<BasePrecompileGadget<F, { ExecutionState::PrecompileBn256Pairing }>>,
+    precompile_blake2f_gadget: Box<BasePrecompileGadget<F, { ExecutionState::PrecompileBlake2f }>>,
     error_return_data_out_of_bound: Box<ErrorReturnDataOutOfBoundGadget<F>>,
+    error_wasm_unreachable: Box<ErrorWasmTrapGadget<F, { ExecutionState::ErrorWasmUnreachable }>>,
+    error_wasm_memory_out_of_bounds: Box<ErrorWasmTrapGadget<F, { ExecutionState::ErrorWasmMemoryOutOfBounds }>>,
+    error_wasm_division_by_zero: Box<ErrorWasmTrapGadget<F, { ExecutionState::ErrorWasmDivisionByZero }>>,
+    error_wasm_indirect_call_mismatch: Box<ErrorWasmTrapGadget<F, { ExecutionState::ErrorWasmIndirectCallMismatch }>>,
+    error_wasm_stack_height_exceeded: Box<ErrorWasmTrapGadget<F, { ExecutionState::ErrorWasmStackHeightExceeded }>>,
 
     // EVM Gadgets
     evm_address: Box<EvmAddressGadget<F>>,
@@ -289,6 +327,9 @@ pub(crate) struct ExecutionConfig<F> {
     evm_selfbalance: Box<EvmSelfBalanceGadget<F>>,
     evm_sload: Box<EvmSloadGadget<F>>,
     evm_sstore: Box<EvmSstoreGadget<F>>,
+    evm_tload: Box<EvmTloadGadget<F>>,
+    evm_tstore: Box<EvmTstoreGadget<F>>,
+    evm_mcopy: Box<EvmMcopyGadget<F>>,
     evm_stop: Box<EvmStopGadget<F>>,
 
     // WASM Gadgets
@@ -302,7 +343,11 @@ pub(crate) struct ExecutionConfig<F> {
     wasm_global: Box<WasmGlobalGadget<F>>,
     // wasm_load: Box<WasmLoadGadget<F>>,
     wasm_local: Box<WasmLocalGadget<F>>,
+    wasm_memory: Box<WasmMemoryGadget<F>>,
     wasm_rel: Box<WasmRelGadget<F>>,
+    wasm_return: Box<WasmReturnGadget<F>>,
+    #[cfg(feature = "tail-call")]
+    wasm_return_call: Box<WasmReturnCallGadget<F>>,
     wasm_select: Box<WasmSelectGadget<F>>,
     // wasm_store: Box<WasmStoreGadget<F>>,
     wasm_test: Box<WasmTestGadget<F>>,
@@ -324,6 +369,9 @@ impl<F: Field> ExecutionConfig<F> {
         copy_table: &dyn LookupTable<F>,
         keccak_table: &dyn LookupTable<F>,
         exp_table: &dyn LookupTable<F>,
+        wasm_function_table: &dyn LookupTable<F>,
+        fee_model: FeeModel,
+        max_memory_pages: u64,
     ) -> Self {
         let mut instrument = Instrument::default();
         let q_usable = meta.complex_selector();
@@ -466,6 +514,8 @@ impl<F: Field> ExecutionConfig<F> {
                         &mut height_map,
                         &mut stored_expressions_map,
                         &mut instrument,
+                        fee_model,
+                        max_memory_pages,
                     ))
                 })()
             };
@@ -510,6 +560,21 @@ impl<F: Field> ExecutionConfig<F> {
             error_invalid_creation_code: configure_gadget!(),
             error_return_data_out_of_bound: configure_gadget!(),
             error_precompile_failed: configure_gadget!(),
+            // precompile calls
+            precompile_ecrecover_gadget: configure_gadget!(),
+            precompile_sha2_gadget: configure_gadget!(),
+            precompile_ripemd_gadget: configure_gadget!(),
+            precompile_identity_gadget: configure_gadget!(),
+            precompile_modexp_gadget: configure_gadget!(),
+            precompile_bn128add_gadget: configure_gadget!(),
+            precompile_bn128mul_gadget: configure_gadget!(),
+            precompile_bn128pairing_gadget: configure_gadget!(),
+            precompile_blake2f_gadget: configure_gadget!(),
+            error_wasm_unreachable: configure_gadget!(),
+            error_wasm_memory_out_of_bounds: configure_gadget!(),
+            error_wasm_division_by_zero: configure_gadget!(),
+            error_wasm_indirect_call_mismatch: configure_gadget!(),
+            error_wasm_stack_height_exceeded: configure_gadget!(),
             evm_address: configure_gadget!(),
             evm_balance: configure_gadget!(),
             evm_blockhash: configure_gadget!(),
@@ -538,6 +603,9 @@ impl<F: Field> ExecutionConfig<F> {
             evm_selfbalance: configure_gadget!(),
             evm_sload: configure_gadget!(),
             evm_sstore: configure_gadget!(),
+            evm_tload: configure_gadget!(),
+            evm_tstore: configure_gadget!(),
+            evm_mcopy: configure_gadget!(),
             evm_stop: configure_gadget!(),
             wasm_bin: configure_gadget!(),
             wasm_break: configure_gadget!(),
@@ -549,7 +617,11 @@ impl<F: Field> ExecutionConfig<F> {
             wasm_global: configure_gadget!(),
             // wasm_load: configure_gadget!(),
             wasm_local: configure_gadget!(),
+            wasm_memory: configure_gadget!(),
             wasm_rel: configure_gadget!(),
+            wasm_return: configure_gadget!(),
+            #[cfg(feature = "tail-call")]
+            wasm_return_call: configure_gadget!(),
             wasm_select: configure_gadget!(),
             // wasm_store: configure_gadget!(),
             wasm_test: configure_gadget!(),
@@ -573,6 +645,7 @@ impl<F: Field> ExecutionConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            wasm_function_table,
             &challenges,
             &cell_manager,
         );
@@ -597,6 +670,8 @@ impl<F: Field> ExecutionConfig<F> {
         height_map: &mut HashMap<ExecutionState, usize>,
         stored_expressions_map: &mut HashMap<ExecutionState, Vec<StoredExpression<F>>>,
         instrument: &mut Instrument,
+        fee_model: FeeModel,
+        max_memory_pages: u64,
     ) -> G {
         // Configure the gadget with the max height first so we can find out the actual
         // height
@@ -608,6 +683,8 @@ impl<F: Field> ExecutionConfig<F> {
                 challenges,
                 G::EXECUTION_STATE,
             );
+            cb.fee_model = fee_model;
+            cb.max_memory_pages = max_memory_pages;
             G::configure(&mut cb);
             let (_, _, height) = cb.build();
             height
@@ -621,6 +698,8 @@ impl<F: Field> ExecutionConfig<F> {
             challenges,
             G::EXECUTION_STATE,
         );
+        cb.fee_model = fee_model;
+        cb.max_memory_pages = max_memory_pages;
 
         let gadget = G::configure(&mut cb);
 
@@ -828,6 +907,7 @@ impl<F: Field> ExecutionConfig<F> {
         copy_table: &dyn LookupTable<F>,
         keccak_table: &dyn LookupTable<F>,
         exp_table: &dyn LookupTable<F>,
+        wasm_function_table: &dyn LookupTable<F>,
         challenges: &Challenges<Expression<F>>,
         cell_manager: &CellManager<F>,
     ) {
@@ -844,6 +924,7 @@ impl<F: Field> ExecutionConfig<F> {
                         Table::Copy => copy_table,
                         Table::Keccak => keccak_table,
                         Table::Exp => exp_table,
+                        Table::WasmFunction => wasm_function_table,
                     }
                     .table_exprs(meta);
                     vec![(
@@ -1294,12 +1375,16 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::WASM_DROP => assign_exec_step!(self.wasm_drop),
             ExecutionState::WASM_GLOBAL => assign_exec_step!(self.wasm_global),
             ExecutionState::WASM_LOCAL => assign_exec_step!(self.wasm_local),
+            ExecutionState::WASM_MEMORY => assign_exec_step!(self.wasm_memory),
             ExecutionState::WASM_UNARY => assign_exec_step!(self.wasm_unary),
             ExecutionState::WASM_CONVERSION => assign_exec_step!(self.wasm_conversion),
             ExecutionState::WASM_REL => assign_exec_step!(self.wasm_rel),
             ExecutionState::WASM_END => assign_exec_step!(self.wasm_end),
             ExecutionState::WASM_BREAK => assign_exec_step!(self.wasm_break),
             ExecutionState::WASM_CALL => assign_exec_step!(self.wasm_call),
+            ExecutionState::WASM_RETURN => assign_exec_step!(self.wasm_return),
+            #[cfg(feature = "tail-call")]
+            ExecutionState::WASM_RETURN_CALL => assign_exec_step!(self.wasm_return_call),
             // opcode
             ExecutionState::SHA3 => assign_exec_step!(self.evm_keccak256),
             ExecutionState::ADDRESS => assign_exec_step!(self.evm_address),
@@ -1321,6 +1406,9 @@ impl<F: Field> ExecutionConfig<F> {
             // ExecutionState::MEMORY => assign_exec_step!(self.memory_gadget),
             ExecutionState::SLOAD => assign_exec_step!(self.evm_sload),
             ExecutionState::SSTORE => assign_exec_step!(self.evm_sstore),
+            ExecutionState::TLOAD => assign_exec_step!(self.evm_tload),
+            ExecutionState::TSTORE => assign_exec_step!(self.evm_tstore),
+            ExecutionState::MCOPY => assign_exec_step!(self.evm_mcopy),
             ExecutionState::MSIZE => assign_exec_step!(self.evm_msize),
             ExecutionState::ORIGIN => assign_exec_step!(self.evm_origin),
             ExecutionState::PC => assign_exec_step!(self.evm_pc),
@@ -1407,6 +1495,48 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ErrorPrecompileFailed => {
                 assign_exec_step!(self.error_precompile_failed)
             }
+            ExecutionState::PrecompileEcRecover => {
+                assign_exec_step!(self.precompile_ecrecover_gadget)
+            }
+            ExecutionState::PrecompileSha256 => {
+                assign_exec_step!(self.precompile_sha2_gadget)
+            }
+            ExecutionState::PrecompileRipemd160 => {
+                assign_exec_step!(self.precompile_ripemd_gadget)
+            }
+            ExecutionState::PrecompileIdentity => {
+                assign_exec_step!(self.precompile_identity_gadget)
+            }
+            ExecutionState::PrecompileBigModExp => {
+                assign_exec_step!(self.precompile_modexp_gadget)
+            }
+            ExecutionState::PrecompileBn256Add => {
+                assign_exec_step!(self.precompile_bn128add_gadget)
+            }
+            ExecutionState::PrecompileBn256ScalarMul => {
+                assign_exec_step!(self.precompile_bn128mul_gadget)
+            }
+            ExecutionState::PrecompileBn256Pairing => {
+                assign_exec_step!(self.precompile_bn128pairing_gadget)
+            }
+            ExecutionState::PrecompileBlake2f => {
+                assign_exec_step!(self.precompile_blake2f_gadget)
+            }
+            ExecutionState::ErrorWasmUnreachable => {
+                assign_exec_step!(self.error_wasm_unreachable)
+            }
+            ExecutionState::ErrorWasmMemoryOutOfBounds => {
+                assign_exec_step!(self.error_wasm_memory_out_of_bounds)
+            }
+            ExecutionState::ErrorWasmDivisionByZero => {
+                assign_exec_step!(self.error_wasm_division_by_zero)
+            }
+            ExecutionState::ErrorWasmIndirectCallMismatch => {
+                assign_exec_step!(self.error_wasm_indirect_call_mismatch)
+            }
+            ExecutionState::ErrorWasmStackHeightExceeded => {
+                assign_exec_step!(self.error_wasm_stack_height_exceeded)
+            }
 
             _ => evm_unimplemented!("unimplemented ExecutionState: {:?}", step.execution_state),
         }
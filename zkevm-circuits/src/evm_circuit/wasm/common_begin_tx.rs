@@ -22,8 +22,9 @@ use crate::{
     table::{
         AccountFieldTag, BlockContextFieldTag, CallContextFieldTag, TxFieldTag as TxContextFieldTag,
     },
+    wasm_circuit::bytecode::bytecode::WasmBytecode,
 };
-use eth_types::{Address, Field, ToLittleEndian, ToScalar};
+use eth_types::{evm_types::MAX_WASM_STACK_HEIGHT, Address, Field, ToLittleEndian, ToScalar};
 use ethers_core::utils::{get_contract_address, keccak256, rlp::RlpStream};
 use gadgets::util::{expr_from_bytes, not, or, Expr};
 use halo2_proofs::{circuit::Value, plonk::Error};
@@ -41,6 +42,9 @@ use gadgets::util::select;
 pub(crate) struct CommonBeginTxGadget<F> {
     tx_id: Cell<F>,
     num_locals: Cell<F>,
+    function_index: Cell<F>,
+    type_index: Cell<F>,
+    locals_in_stack: LtGadget<F, 4>,
     tx_nonce: Cell<F>,
     tx_gas: Cell<F>,
     tx_gas_price: Word<F>,
@@ -296,6 +300,25 @@ impl<F: Field> ExecutionGadget<F> for CommonBeginTxGadget<F> {
             );
         });
 
+        // Locals of the entry function are reserved in one go: only their number is written, a
+        // read of a local before its first write is a stack first access which is zero.
+        let num_locals = cb.query_cell();
+        // the entry function runs in frame 0, see `WasmCallGadget`
+        let function_index = cb.query_cell();
+        let type_index = cb.query_cell();
+        // both are only written when code runs, they have to match the function defined by it
+        cb.condition(
+            or::expr([tx_is_create.expr(), not::expr(no_callee_code.expr())]),
+            |cb| {
+                cb.wasm_function_table_lookup(
+                    cb.curr.state.code_hash.expr(),
+                    function_index.expr(),
+                    num_locals.expr(),
+                    type_index.expr(),
+                );
+            },
+        );
+
         // 1. Handle contract creation transaction.
         cb.condition(tx_is_create.expr(), |cb| {
             let output_rlc = cb.word_rlc::<N_BYTES_WORD>(
@@ -338,12 +361,14 @@ impl<F: Field> ExecutionGadget<F> for CommonBeginTxGadget<F> {
                     CallContextFieldTag::CodeHash,
                     cb.curr.state.code_hash.expr(),
                 ),
+                (CallContextFieldTag::InternalFunctionId, function_index.expr()),
+                (CallContextFieldTag::NumLocals, num_locals.expr()),
             ] {
                 cb.call_context_lookup(true.expr(), Some(call_id.expr()), field_tag, value);
             }
 
             cb.require_step_state_transition(StepStateTransition {
-                // 21 + a reads and writes:
+                // 23 + a reads and writes:
                 //   - Write CallContext TxId
                 //   - Write CallContext RwCounterEndOfReversion
                 //   - Write CallContext IsPersistent
@@ -367,8 +392,10 @@ impl<F: Field> ExecutionGadget<F> for CommonBeginTxGadget<F> {
                 //   - Write CallContext IsRoot
                 //   - Write CallContext IsCreate
                 //   - Write CallContext CodeHash
+                //   - Write CallContext InternalFunctionId
+                //   - Write CallContext NumLocals
                 rw_counter: Delta(
-                    21.expr() + transfer_with_gas_fee.rw_delta() + SHANGHAI_RW_DELTA.expr(),
+                    23.expr() + transfer_with_gas_fee.rw_delta() + SHANGHAI_RW_DELTA.expr(),
                 ),
                 call_id: To(call_id.expr()),
                 is_root: To(true.expr()),
@@ -475,7 +502,7 @@ impl<F: Field> ExecutionGadget<F> for CommonBeginTxGadget<F> {
 
         // 4. Call to account with non-empty code.
         cb.condition(
-            and::expr([not::expr(tx_is_create.expr()), not::expr(no_callee_code)]),
+            and::expr([not::expr(tx_is_create.expr()), not::expr(no_callee_code.expr())]),
             |cb| {
                 // Setup first call's context.
                 for (field_tag, value) in [
@@ -498,12 +525,14 @@ impl<F: Field> ExecutionGadget<F> for CommonBeginTxGadget<F> {
                     (CallContextFieldTag::IsRoot, 1.expr()),
                     (CallContextFieldTag::IsCreate, tx_is_create.expr()),
                     (CallContextFieldTag::CodeHash, phase2_code_hash.expr()),
+                    (CallContextFieldTag::InternalFunctionId, function_index.expr()),
+                    (CallContextFieldTag::NumLocals, num_locals.expr()),
                 ] {
                     cb.call_context_lookup(true.expr(), Some(call_id.expr()), field_tag, value);
                 }
 
                 cb.require_step_state_transition(StepStateTransition {
-                    // 21 reads and writes:
+                    // 23 reads and writes:
                     //   - Write CallContext TxId
                     //   - Write CallContext RwCounterEndOfReversion
                     //   - Write CallContext IsPersistent
@@ -527,8 +556,10 @@ impl<F: Field> ExecutionGadget<F> for CommonBeginTxGadget<F> {
                     //   - Write CallContext IsRoot
                     //   - Write CallContext IsCreate
                     //   - Write CallContext CodeHash
+                    //   - Write CallContext InternalFunctionId
+                    //   - Write CallContext NumLocals
                     rw_counter: Delta(
-                        21.expr() + transfer_with_gas_fee.rw_delta() + SHANGHAI_RW_DELTA.expr(),
+                        23.expr() + transfer_with_gas_fee.rw_delta() + SHANGHAI_RW_DELTA.expr(),
                     ),
                     call_id: To(call_id.expr()),
                     is_root: To(true.expr()),
@@ -542,19 +573,29 @@ impl<F: Field> ExecutionGadget<F> for CommonBeginTxGadget<F> {
             },
         );
 
-        // cb.call_context_lookup(
-        //     1.expr(),
-        //     Some(call_id.expr()),
-        //     CallContextFieldTag::InternalFunctionId,
-        //     0.expr(),
-        // );
-
-        let num_locals = cb.query_cell();
         cb.register_local_variables(num_locals.expr());
 
+        // the locals of the entry function are bound to its body by the function lookup, when
+        // they don't fit into the stack its first step traps
+        let locals_in_stack = LtGadget::construct(
+            cb,
+            num_locals.expr(),
+            (MAX_WASM_STACK_HEIGHT + 1).expr(),
+        );
+        cb.condition(
+            and::expr([
+                or::expr([tx_is_create.expr(), not::expr(no_callee_code.expr())]),
+                not::expr(locals_in_stack.expr()),
+            ]),
+            |cb| cb.require_next_state(ExecutionState::ErrorWasmStackHeightExceeded),
+        );
+
         Self {
             tx_id,
             num_locals,
+            function_index,
+            type_index,
+            locals_in_stack,
             tx_nonce,
             tx_gas,
             tx_gas_price,
@@ -777,6 +818,27 @@ impl<F: Field> ExecutionGadget<F> for CommonBeginTxGadget<F> {
             .assign(region, offset, Value::known(F::from(is_coinbase_warm)))?;
 
         self.num_locals.assign(region, offset, Value::known(F::from(step.num_locals as u64)))?;
+        self.function_index
+            .assign(region, offset, Value::known(F::from(step.function_index as u64)))?;
+        // transactions without code have no function, the lookup is disabled
+        let type_index = block
+            .bytecodes
+            .get(&call.code_hash)
+            .and_then(|code| {
+                WasmBytecode::new(code.bytes.clone())
+                    .function_type_index(step.function_index as u64)
+                    .ok()
+                    .flatten()
+            })
+            .unwrap_or_default();
+        self.type_index
+            .assign(region, offset, Value::known(F::from(type_index)))?;
+        self.locals_in_stack.assign(
+            region,
+            offset,
+            F::from(step.num_locals as u64),
+            F::from(MAX_WASM_STACK_HEIGHT + 1),
+        )?;
 
         Ok(())
     }
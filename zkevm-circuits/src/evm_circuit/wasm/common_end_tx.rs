@@ -22,6 +22,7 @@ use crate::{
     },
     util::Expr,
 };
+use bus_mapping::circuit_input_builder::FeeModel;
 use eth_types::{evm_types::MAX_REFUND_QUOTIENT_OF_GAS_USED, Field, ToLittleEndian, ToScalar};
 use halo2_proofs::{circuit::Value, plonk::Error};
 use strum::EnumCount;
@@ -44,6 +45,7 @@ pub(crate) struct CommonEndTxGadget<F> {
     current_cumulative_gas_used: Cell<F>,
     is_first_tx: IsEqualGadget<F>,
     is_persistent: Cell<F>,
+    fee_model: FeeModel,
 }
 
 impl<F: Field> ExecutionGadget<F> for CommonEndTxGadget<F> {
@@ -85,17 +87,26 @@ impl<F: Field> ExecutionGadget<F> for CommonEndTxGadget<F> {
         );
 
         // Add gas_used * effective_tip to coinbase's balance
+        let fee_model = cb.fee_model;
         let coinbase = cb.query_cell();
-        let base_fee = cb.query_word_rlc();
-        for (tag, value) in [
-            (BlockContextFieldTag::Coinbase, coinbase.expr()),
-            (BlockContextFieldTag::BaseFee, base_fee.expr()),
-        ] {
-            cb.block_lookup(tag.expr(), cb.curr.state.block_number.expr(), value);
+        cb.block_lookup(
+            BlockContextFieldTag::Coinbase.expr(),
+            cb.curr.state.block_number.expr(),
+            coinbase.expr(),
+        );
+        let burned_base_fee = cb.query_word_rlc();
+        if fee_model == FeeModel::Legacy {
+            cb.require_zero("legacy fee model burns no base fee", burned_base_fee.expr());
+        } else {
+            cb.block_lookup(
+                BlockContextFieldTag::BaseFee.expr(),
+                cb.curr.state.block_number.expr(),
+                burned_base_fee.expr(),
+            );
         }
         let effective_tip = cb.query_word_rlc();
         let sub_gas_price_by_base_fee =
-            AddWordsGadget::construct(cb, [effective_tip.clone(), base_fee], tx_gas_price);
+            AddWordsGadget::construct(cb, [effective_tip.clone(), burned_base_fee], tx_gas_price);
         let mul_effective_tip_by_gas_used = MulWordByU64Gadget::construct(
             cb,
             effective_tip,
@@ -104,12 +115,13 @@ impl<F: Field> ExecutionGadget<F> for CommonEndTxGadget<F> {
 
         let effective_fee = cb.query_word_rlc();
         // TODO: contraint l1 fee
-        #[cfg(not(feature = "scroll"))]
-        cb.require_equal(
-            "tx_fee == l1_fee + l2_fee, l1_fee == 0",
-            mul_effective_tip_by_gas_used.product().expr(),
-            effective_fee.expr(),
-        );
+        if !fee_model.pays_l1_fee() {
+            cb.require_equal(
+                "tx_fee == l1_fee + l2_fee, l1_fee == 0",
+                mul_effective_tip_by_gas_used.product().expr(),
+                effective_fee.expr(),
+            );
+        }
 
         let coinbase_reward =
             UpdateBalanceGadget::construct(cb, coinbase.expr(), vec![effective_fee.clone()], None);
@@ -201,6 +213,7 @@ impl<F: Field> ExecutionGadget<F> for CommonEndTxGadget<F> {
             current_cumulative_gas_used,
             is_first_tx,
             is_persistent,
+            fee_model,
         }
     }
 
@@ -257,11 +270,12 @@ impl<F: Field> ExecutionGadget<F> for CommonEndTxGadget<F> {
             caller_balance,
         )?;
         let context = &block.context.ctxs[&tx.block_number];
-        let effective_tip = tx.gas_price - context.base_fee;
+        let burned_base_fee = self.fee_model.burned_base_fee(context.base_fee);
+        let effective_tip = tx.gas_price - burned_base_fee;
         self.sub_gas_price_by_base_fee.assign(
             region,
             offset,
-            [effective_tip, context.base_fee],
+            [effective_tip, burned_base_fee],
             tx.gas_price,
         )?;
         let coinbase_reward = effective_tip * (gas_used - effective_refund);
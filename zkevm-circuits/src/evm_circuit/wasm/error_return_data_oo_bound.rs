@@ -53,10 +53,10 @@ impl<F: Field> ExecutionGadget<F> for ErrorReturnDataOutOfBoundGadget<F> {
             OpcodeId::RETURNDATACOPY.expr(),
         );
 
-        // Pop memory_offset, offset, size from stack
-        cb.stack_pop(memory_offset.expr());
-        cb.stack_pop(data_offset.expr());
+        // Pop size, offset, memory_offset from stack, wasm pushes memory_offset first
         cb.stack_pop(size.expr());
+        cb.stack_pop(data_offset.expr());
+        cb.stack_pop(memory_offset.expr());
 
         // Read last callee return data length
         cb.call_context_lookup(
@@ -129,7 +129,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorReturnDataOutOfBoundGadget<F> {
         self.opcode
             .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
 
-        let [dest_offset, data_offset, size] =
+        let [size, data_offset, dest_offset] =
             [0, 1, 2].map(|i| block.rws[step.rw_indices[i as usize]].stack_value());
 
         self.memory_offset
@@ -181,7 +181,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorReturnDataOutOfBoundGadget<F> {
 #[cfg(test)]
 mod test {
     use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
-    use eth_types::{bytecode, ToWord, Word};
+    use eth_types::{bytecode, bytecode_internal, evm_types::OpcodeId, Bytecode, ToWord, Word};
     use mock::test_ctx::TestContext;
 
     fn test_ok(
@@ -299,4 +299,60 @@ mod test {
         test_ok(0, 0x10, 0x20, 0x10.into(), 0x10, false);
         test_ok(0, 0x10, 0x20, 1.into(), 0xff, true);
     }
+
+    fn test_wasm_trap(return_data_size: usize, dest_offset: usize, offset: usize, size: usize) {
+        let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
+
+        let mut code_b = Bytecode::default();
+        code_b.fill_default_global_data(rand_bytes(return_data_size));
+        bytecode_internal!(code_b,
+            I32Const[0x00]
+            I32Const[return_data_size]
+            RETURN
+        );
+
+        // the copy into the linear memory of A reads past the return data and traps
+        let mut code_a = Bytecode::default();
+        code_a.alloc_default_global_data((dest_offset + size) as u32);
+        code_a.emit_evm_call(
+            OpcodeId::CALL,
+            0x1_0000,
+            addr_b,
+            Word::zero(),
+            0x00,
+            0x00,
+            0x00,
+            return_data_size as u64,
+        );
+        bytecode_internal!(code_a,
+            I32Const[dest_offset]
+            I32Const[offset]
+            I32Const[size]
+            RETURNDATACOPY
+        );
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_a).code(code_a);
+                accs[1].address(addr_b).code(code_b);
+                accs[2]
+                    .address(mock::MOCK_ACCOUNTS[2])
+                    .balance(Word::from(1u64 << 30));
+            },
+            |mut txs, accs| {
+                txs[0].to(accs[0].address).from(accs[2].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn test_return_data_oo_bound_wasm_trap() {
+        test_wasm_trap(0x10, 0x20, 0x10, 0x10);
+        test_wasm_trap(0x10, 0x20, 0x00, 0x11);
+    }
 }
@@ -0,0 +1,913 @@
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Error, Expression},
+};
+
+use bus_mapping::evm::OpcodeId;
+use eth_types::{evm_types::MAX_WASM_STACK_HEIGHT, Field};
+use gadgets::util::{sum, Expr};
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{PAGE_SIZE, STACK_CAPACITY},
+        step::ExecutionState,
+        table::{FixedTableTag, Lookup},
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            math_gadget::{IsEqualGadget, LtGadget},
+            CachedRegion, Cell,
+        },
+        witness::{Block, Bytecode, Call, ExecStep, Rw, Transaction},
+    },
+    table::CallContextFieldTag,
+    wasm_circuit::bytecode::bytecode::WasmBytecode,
+};
+
+/// Max length of the LEB128 encoded u32 immediates: memory offsets of wasm32 loads and stores,
+/// function indexes of calls
+const MAX_LEB_BYTES: usize = 5;
+
+const LOAD_OPCODES: [OpcodeId; 12] = [
+    OpcodeId::I32Load,
+    OpcodeId::I64Load,
+    OpcodeId::I32Load8S,
+    OpcodeId::I32Load8U,
+    OpcodeId::I32Load16S,
+    OpcodeId::I32Load16U,
+    OpcodeId::I64Load8S,
+    OpcodeId::I64Load8U,
+    OpcodeId::I64Load16S,
+    OpcodeId::I64Load16U,
+    OpcodeId::I64Load32S,
+    OpcodeId::I64Load32U,
+];
+
+const STORE_OPCODES: [OpcodeId; 7] = [
+    OpcodeId::I32Store,
+    OpcodeId::I64Store,
+    OpcodeId::I32Store8,
+    OpcodeId::I32Store16,
+    OpcodeId::I64Store8,
+    OpcodeId::I64Store16,
+    OpcodeId::I64Store32,
+];
+
+/// Number of bytes accessed by a load or a store
+fn access_size(opcode: OpcodeId) -> u64 {
+    match opcode {
+        OpcodeId::I32Load8S
+        | OpcodeId::I32Load8U
+        | OpcodeId::I64Load8S
+        | OpcodeId::I64Load8U
+        | OpcodeId::I32Store8
+        | OpcodeId::I64Store8 => 1,
+        OpcodeId::I32Load16S
+        | OpcodeId::I32Load16U
+        | OpcodeId::I64Load16S
+        | OpcodeId::I64Load16U
+        | OpcodeId::I32Store16
+        | OpcodeId::I64Store16 => 2,
+        OpcodeId::I64Load | OpcodeId::I64Store => 8,
+        _ => 4,
+    }
+}
+
+/// Unsigned LEB128 immediate of the opcode at the current program counter, starting `start` bytes
+/// after the opcode. Every byte of it is looked up in the bytecode table.
+#[derive(Clone, Debug)]
+struct LebImmediateGadget<F> {
+    /// encoded bytes, zero past the last one
+    bytes: [Cell<F>; MAX_LEB_BYTES],
+    is_last: [Cell<F>; MAX_LEB_BYTES],
+    /// is_code flags of the bytes in the bytecode table
+    is_code: [Cell<F>; MAX_LEB_BYTES],
+    start: usize,
+}
+
+impl<F: Field> LebImmediateGadget<F> {
+    fn construct(cb: &mut EVMConstraintBuilder<F>, start: usize) -> Self {
+        let bytes = [(); MAX_LEB_BYTES].map(|_| cb.query_cell());
+        let is_last = [(); MAX_LEB_BYTES].map(|_| cb.query_bool());
+        let is_code = [(); MAX_LEB_BYTES].map(|_| cb.query_cell());
+
+        let code_hash = cb.curr.state.code_hash.expr();
+        let program_counter = cb.curr.state.program_counter.expr();
+        cb.require_equal(
+            "immediate has a last byte",
+            sum::expr(is_last.iter().map(|is_last| is_last.expr())),
+            1.expr(),
+        );
+        for idx in 0..MAX_LEB_BYTES {
+            let is_leb_byte = Self::is_leb_byte(&is_last, idx);
+            cb.condition(is_leb_byte.clone(), |cb| {
+                cb.bytecode_lookup(
+                    code_hash.clone(),
+                    program_counter.clone() + (start + idx).expr(),
+                    is_code[idx].expr(),
+                    bytes[idx].expr(),
+                );
+            });
+            cb.condition(1.expr() - is_leb_byte, |cb| {
+                cb.require_zero("no byte past the last one", bytes[idx].expr());
+            });
+            cb.range_lookup(Self::payload(&bytes, &is_last, idx), 128);
+        }
+
+        Self {
+            bytes,
+            is_last,
+            is_code,
+            start,
+        }
+    }
+
+    /// Whether byte `idx` belongs to the immediate, no byte before it was the last one
+    fn is_leb_byte(is_last: &[Cell<F>], idx: usize) -> Expression<F> {
+        1.expr() - sum::expr(is_last[..idx].iter().map(|is_last| is_last.expr()))
+    }
+
+    /// Low 7 bits of byte `idx`, all the bytes but the last one have the continuation bit set
+    fn payload(bytes: &[Cell<F>], is_last: &[Cell<F>], idx: usize) -> Expression<F> {
+        let has_continuation_bit = Self::is_leb_byte(is_last, idx) - is_last[idx].expr();
+        bytes[idx].expr() - has_continuation_bit * 128.expr()
+    }
+
+    fn expr(&self) -> Expression<F> {
+        sum::expr((0..MAX_LEB_BYTES).map(|idx| {
+            Self::payload(&self.bytes, &self.is_last, idx) * (1u64 << (7 * idx)).expr()
+        }))
+    }
+
+    /// Assigns the immediate of the opcode at `program_counter`, returns its value
+    fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        code: &Bytecode,
+        program_counter: usize,
+    ) -> Result<u64, Error> {
+        let mut value = 0;
+        let mut is_leb_byte = true;
+        for idx in 0..MAX_LEB_BYTES {
+            let [byte, is_code] = if is_leb_byte {
+                code.get(program_counter + self.start + idx)
+            } else {
+                [0, 0]
+            };
+            let is_last = is_leb_byte && byte & 0x80 == 0;
+            value |= ((byte & 0x7f) as u64) << (7 * idx);
+            self.bytes[idx].assign(region, offset, Value::known(F::from(byte as u64)))?;
+            self.is_last[idx].assign(region, offset, Value::known(F::from(is_last as u64)))?;
+            self.is_code[idx].assign(region, offset, Value::known(F::from(is_code as u64)))?;
+            is_leb_byte &= !is_last;
+        }
+
+        Ok(value)
+    }
+}
+
+/// Offset immediate of the load or store at the current program counter. The align immediate
+/// comes first, it is below 4 for valid code so it takes a single byte. The offset follows.
+#[derive(Clone, Debug)]
+struct MemoryOffsetGadget<F> {
+    align: Cell<F>,
+    /// is_code flag of the align byte in the bytecode table
+    align_is_code: Cell<F>,
+    offset: LebImmediateGadget<F>,
+}
+
+impl<F: Field> MemoryOffsetGadget<F> {
+    fn construct(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let align = cb.query_cell();
+        let align_is_code = cb.query_cell();
+        cb.bytecode_lookup(
+            cb.curr.state.code_hash.expr(),
+            cb.curr.state.program_counter.expr() + 1.expr(),
+            align_is_code.expr(),
+            align.expr(),
+        );
+        cb.require_in_set(
+            "align immediate is a single byte",
+            align.expr(),
+            (0..4u64).map(|align| align.expr()).collect(),
+        );
+        let offset = LebImmediateGadget::construct(cb, 2);
+
+        Self {
+            align,
+            align_is_code,
+            offset,
+        }
+    }
+
+    fn expr(&self) -> Expression<F> {
+        self.offset.expr()
+    }
+
+    /// Assigns the immediates of the opcode at `program_counter`, returns the offset
+    fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        code: &Bytecode,
+        program_counter: usize,
+    ) -> Result<u64, Error> {
+        let [align, align_is_code] = code.get(program_counter + 1);
+        self.align
+            .assign(region, offset, Value::known(F::from(align as u64)))?;
+        self.align_is_code
+            .assign(region, offset, Value::known(F::from(align_is_code as u64)))?;
+        self.offset.assign(region, offset, code, program_counter)
+    }
+}
+
+/// Opcodes pushing one value on the stack
+const PUSH_OPCODES: [OpcodeId; 7] = [
+    OpcodeId::I32Const,
+    OpcodeId::I64Const,
+    OpcodeId::F32Const,
+    OpcodeId::F64Const,
+    OpcodeId::GetLocal,
+    OpcodeId::GetGlobal,
+    OpcodeId::CurrentMemory,
+];
+
+/// Growth of the stack by the trapping step, it must end above [`MAX_WASM_STACK_HEIGHT`]. The
+/// stack height is `STACK_CAPACITY - stack_pointer`:
+/// - an opcode pushing a value grows it by one
+/// - `call` grows it by the locals of the callee, the function index is the immediate
+/// - the first step of the entry function grows the empty stack by the locals of the function,
+///   `BeginTx` only goes to this state when they don't fit
+#[derive(Clone, Debug)]
+struct StackGrowthGadget<F> {
+    is_call: Cell<F>,
+    is_entry: Cell<F>,
+    function_index: LebImmediateGadget<F>,
+    num_locals: Cell<F>,
+    type_index: Cell<F>,
+    height_exceeded: LtGadget<F, 5>,
+}
+
+impl<F: Field> StackGrowthGadget<F> {
+    fn construct(cb: &mut EVMConstraintBuilder<F>, opcode: Expression<F>) -> Self {
+        let is_call = cb.query_bool();
+        let is_entry = cb.query_bool();
+        let is_push = 1.expr() - is_call.expr() - is_entry.expr();
+        cb.require_boolean("is_call and is_entry are exclusive", is_push.clone());
+        let num_locals = cb.query_cell();
+        let type_index = cb.query_cell();
+        cb.condition(is_push.clone(), |cb| {
+            cb.require_in_set(
+                "is_push=1 -> opcode pushes a value",
+                opcode.clone(),
+                PUSH_OPCODES.iter().map(|op| op.expr()).collect(),
+            );
+            cb.require_zero("is_push=1 -> no locals", num_locals.expr());
+        });
+        let function_index = cb.condition(is_call.expr(), |cb| {
+            cb.require_equal("is_call=1 -> opcode is call", opcode, OpcodeId::Call.expr());
+            let function_index = LebImmediateGadget::construct(cb, 1);
+            cb.wasm_function_table_lookup(
+                cb.curr.state.code_hash.expr(),
+                function_index.expr(),
+                num_locals.expr(),
+                type_index.expr(),
+            );
+            function_index
+        });
+        cb.condition(is_entry.expr(), |cb| {
+            cb.require_equal(
+                "is_entry=1 -> stack is empty",
+                cb.curr.state.stack_pointer.expr(),
+                STACK_CAPACITY.expr(),
+            );
+            cb.call_context_lookup(
+                false.expr(),
+                None,
+                CallContextFieldTag::NumLocals,
+                num_locals.expr(),
+            );
+        });
+
+        let height_exceeded = LtGadget::construct(
+            cb,
+            MAX_WASM_STACK_HEIGHT.expr(),
+            STACK_CAPACITY.expr() - cb.curr.state.stack_pointer.expr()
+                + is_push
+                + num_locals.expr(),
+        );
+        cb.require_equal(
+            "stack height is above the limit",
+            height_exceeded.expr(),
+            1.expr(),
+        );
+
+        Self {
+            is_call,
+            is_entry,
+            function_index,
+            num_locals,
+            type_index,
+            height_exceeded,
+        }
+    }
+
+    /// Returns the number of rws read, the locals of the entry function
+    fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        code: &Bytecode,
+        opcode: OpcodeId,
+        step: &ExecStep,
+    ) -> Result<usize, Error> {
+        // the first step of the entry function reads its locals before anything else
+        let entry_locals = step.rw_indices.first().and_then(|rw_index| {
+            match block.rws[*rw_index] {
+                Rw::CallContext {
+                    field_tag: CallContextFieldTag::NumLocals,
+                    value,
+                    ..
+                } => Some(value.low_u64()),
+                _ => None,
+            }
+        });
+        let is_entry = entry_locals.is_some();
+        let is_call = !is_entry && opcode == OpcodeId::Call;
+        self.is_call
+            .assign(region, offset, Value::known(F::from(is_call as u64)))?;
+        self.is_entry
+            .assign(region, offset, Value::known(F::from(is_entry as u64)))?;
+
+        let (num_locals, type_index) = if is_call {
+            let function_index =
+                self.function_index
+                    .assign(region, offset, code, step.program_counter as usize)?;
+            function_locals_and_type(code, function_index)
+        } else {
+            (entry_locals.unwrap_or_default(), 0)
+        };
+        self.num_locals
+            .assign(region, offset, Value::known(F::from(num_locals)))?;
+        self.type_index
+            .assign(region, offset, Value::known(F::from(type_index)))?;
+
+        let is_push = !is_call && !is_entry;
+        self.height_exceeded.assign(
+            region,
+            offset,
+            F::from(MAX_WASM_STACK_HEIGHT),
+            F::from((STACK_CAPACITY - step.stack_pointer) as u64 + is_push as u64 + num_locals),
+        )?;
+
+        Ok(is_entry as usize)
+    }
+}
+
+/// `(locals count, type index)` of the function `function_index` defined by the code
+fn function_locals_and_type(code: &Bytecode, function_index: u64) -> (u64, u64) {
+    let wasm_bytecode = WasmBytecode::new(code.bytes.clone());
+    let num_locals = wasm_bytecode
+        .function_locals()
+        .expect("code of a step has a valid code section")
+        .into_iter()
+        .find(|(index, _)| *index == function_index)
+        .map(|(_, num_locals)| num_locals)
+        .expect("called function is defined by the code");
+    let type_index = wasm_bytecode
+        .function_type_index(function_index)
+        .expect("code of a step has a valid function section")
+        .expect("called function is defined by the code");
+    (num_locals, type_index)
+}
+
+/// Type check of the `call_indirect` at the current program counter. The element of the table 0
+/// popped from the stack holds the function index plus one, a null element traps differently.
+/// The type of the function must differ from the type immediate of the opcode. Types are
+/// compared by index, not by signature: a module declaring one signature twice traps on calls
+/// through the other index.
+#[derive(Clone, Debug)]
+struct IndirectCallTypeGadget<F> {
+    funcref: Cell<F>,
+    num_locals: Cell<F>,
+    type_index: Cell<F>,
+    expected_type_index: LebImmediateGadget<F>,
+    is_same_type: IsEqualGadget<F>,
+}
+
+impl<F: Field> IndirectCallTypeGadget<F> {
+    fn construct(cb: &mut EVMConstraintBuilder<F>, elem_index: Expression<F>) -> Self {
+        let funcref = cb.query_cell();
+        // only the table 0 exists without the reference types proposal
+        cb.table_read(0.expr(), elem_index, funcref.expr());
+        let num_locals = cb.query_cell();
+        let type_index = cb.query_cell();
+        cb.wasm_function_table_lookup(
+            cb.curr.state.code_hash.expr(),
+            funcref.expr() - 1.expr(),
+            num_locals.expr(),
+            type_index.expr(),
+        );
+        // the type immediate comes first, the table immediate follows
+        let expected_type_index = LebImmediateGadget::construct(cb, 1);
+        let is_same_type =
+            IsEqualGadget::construct(cb, type_index.expr(), expected_type_index.expr());
+        cb.require_zero(
+            "type of the function differs from the type immediate",
+            is_same_type.expr(),
+        );
+
+        Self {
+            funcref,
+            num_locals,
+            type_index,
+            expected_type_index,
+            is_same_type,
+        }
+    }
+
+    /// Returns the number of rws read after the operand, the table element
+    fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        code: &Bytecode,
+        step: &ExecStep,
+    ) -> Result<usize, Error> {
+        let funcref = block.rws[step.rw_indices[1]].table_value_pair().0.low_u64();
+        self.funcref
+            .assign(region, offset, Value::known(F::from(funcref)))?;
+        let (num_locals, type_index) = function_locals_and_type(code, funcref - 1);
+        self.num_locals
+            .assign(region, offset, Value::known(F::from(num_locals)))?;
+        self.type_index
+            .assign(region, offset, Value::known(F::from(type_index)))?;
+        let expected_type_index =
+            self.expected_type_index
+                .assign(region, offset, code, step.program_counter as usize)?;
+        self.is_same_type.assign(
+            region,
+            offset,
+            F::from(type_index),
+            F::from(expected_type_index),
+        )?;
+
+        Ok(1)
+    }
+}
+
+/// Gadget for the WASM traps, one execution state per [`bus_mapping::error::WasmTrapKind`].
+/// The trapping opcode is checked by the ResponsibleOpcode lookup, its operands are popped and
+/// the call halts like for other exceptions:
+/// - `ErrorWasmUnreachable` pops nothing
+/// - `ErrorWasmDivisionByZero` pops the divisor (which must be zero) and the dividend
+/// - `ErrorWasmIndirectCallMismatch` pops the element index and reads the element from the
+///   table, see [`IndirectCallTypeGadget`]
+/// - `ErrorWasmMemoryOutOfBounds` pops the address of loads, the value and the address of stores,
+///   the accessed bytes (from the address plus the offset immediate of the opcode) must end above
+///   the allocated pages
+/// - `ErrorWasmStackHeightExceeded` pops nothing, the step grows the stack above
+///   [`MAX_WASM_STACK_HEIGHT`], see [`StackGrowthGadget`]
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorWasmTrapGadget<F, const S: ExecutionState> {
+    opcode: Cell<F>,
+    is_store: Cell<F>,
+    operands: [Cell<F>; 2],
+    /// memory offset of the opcode, access size and the out of bounds check
+    memory_out_of_bounds: Option<(MemoryOffsetGadget<F>, Cell<F>, LtGadget<F, 5>)>,
+    indirect_call_type: Option<IndirectCallTypeGadget<F>>,
+    stack_height_exceeded: Option<StackGrowthGadget<F>>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field, const S: ExecutionState> ErrorWasmTrapGadget<F, S> {
+    fn operand_count(opcode: OpcodeId) -> usize {
+        match S {
+            ExecutionState::ErrorWasmDivisionByZero => 2,
+            ExecutionState::ErrorWasmIndirectCallMismatch => 1,
+            ExecutionState::ErrorWasmMemoryOutOfBounds => {
+                1 + STORE_OPCODES.contains(&opcode) as usize
+            }
+            _ => 0,
+        }
+    }
+}
+
+impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for ErrorWasmTrapGadget<F, S> {
+    const NAME: &'static str = "ErrorWasmTrap";
+
+    const EXECUTION_STATE: ExecutionState = S;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        if !matches!(S, ExecutionState::ErrorWasmStackHeightExceeded) {
+            cb.add_lookup(
+                "Responsible opcode lookup",
+                Lookup::Fixed {
+                    tag: FixedTableTag::ResponsibleOpcode.expr(),
+                    values: [
+                        Self::EXECUTION_STATE.as_u64().expr(),
+                        opcode.expr(),
+                        0.expr(),
+                    ],
+                },
+            );
+        }
+
+        let is_store = cb.query_bool();
+        let operands = [cb.query_cell(), cb.query_cell()];
+        let mut memory_out_of_bounds = None;
+        let mut indirect_call_type = None;
+        match S {
+            ExecutionState::ErrorWasmDivisionByZero => {
+                cb.stack_pop(operands[0].expr());
+                cb.stack_pop(operands[1].expr());
+                cb.require_zero("divisor is zero", operands[0].expr());
+            }
+            ExecutionState::ErrorWasmIndirectCallMismatch => {
+                cb.stack_pop(operands[0].expr());
+                indirect_call_type =
+                    Some(IndirectCallTypeGadget::construct(cb, operands[0].expr()));
+            }
+            ExecutionState::ErrorWasmMemoryOutOfBounds => {
+                cb.require_in_set(
+                    "is_store=1 -> opcode is a store",
+                    is_store.expr() * opcode.expr(),
+                    STORE_OPCODES.iter().map(|op| is_store.expr() * op.expr()).collect(),
+                );
+                cb.require_in_set(
+                    "is_store=0 -> opcode is a load",
+                    (1.expr() - is_store.expr()) * opcode.expr(),
+                    LOAD_OPCODES
+                        .iter()
+                        .map(|op| (1.expr() - is_store.expr()) * op.expr())
+                        .collect(),
+                );
+                cb.stack_pop(operands[0].expr());
+                cb.condition(is_store.expr(), |cb| {
+                    cb.stack_pop(operands[1].expr());
+                });
+
+                let memory_offset = MemoryOffsetGadget::construct(cb);
+                let size = cb.query_cell();
+                cb.require_in_set(
+                    "access size of the opcode",
+                    opcode.expr() * 256.expr() + size.expr(),
+                    LOAD_OPCODES
+                        .iter()
+                        .chain(STORE_OPCODES.iter())
+                        .map(|op| (op.as_u64() * 256 + access_size(*op)).expr())
+                        .collect(),
+                );
+                // the address is below the value for stores
+                let address = is_store.expr() * operands[1].expr()
+                    + (1.expr() - is_store.expr()) * operands[0].expr();
+                let out_of_bounds = LtGadget::construct(
+                    cb,
+                    cb.curr.state.memory_word_size.expr() * PAGE_SIZE.expr(),
+                    address + memory_offset.expr() + size.expr(),
+                );
+                cb.require_equal(
+                    "access ends above the memory size",
+                    out_of_bounds.expr(),
+                    1.expr(),
+                );
+                memory_out_of_bounds = Some((memory_offset, size, out_of_bounds));
+            }
+            _ => {}
+        }
+        let stack_height_exceeded = matches!(S, ExecutionState::ErrorWasmStackHeightExceeded)
+            .then(|| StackGrowthGadget::construct(cb, opcode.expr()));
+        if !matches!(S, ExecutionState::ErrorWasmMemoryOutOfBounds) {
+            cb.require_zero("is_store is only used by memory traps", is_store.expr());
+        }
+
+        let rw_counter_delta = cb.rw_counter_offset() + 2.expr();
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), rw_counter_delta);
+
+        Self {
+            opcode,
+            is_store,
+            operands,
+            memory_out_of_bounds,
+            indirect_call_type,
+            stack_height_exceeded,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let operand_count = Self::operand_count(opcode);
+        let is_store =
+            matches!(S, ExecutionState::ErrorWasmMemoryOutOfBounds) && operand_count == 2;
+        self.is_store
+            .assign(region, offset, Value::known(F::from(is_store as u64)))?;
+        let operands = (0..operand_count)
+            .map(|idx| block.rws[step.rw_indices[idx]].stack_value().as_u64())
+            .collect::<Vec<_>>();
+        for (operand, value) in self.operands.iter().zip(operands.iter()) {
+            operand.assign(region, offset, Value::known(F::from(*value)))?;
+        }
+
+        let code = block
+            .bytecodes
+            .get(&call.code_hash)
+            .expect("could not find current environment's bytecode");
+        if let Some((memory_offset_gadget, access_size_cell, out_of_bounds)) =
+            &self.memory_out_of_bounds
+        {
+            let address = operands[operand_count - 1];
+            let memory_offset = memory_offset_gadget.assign(
+                region,
+                offset,
+                code,
+                step.program_counter as usize,
+            )?;
+            let size = access_size(opcode);
+            access_size_cell.assign(region, offset, Value::known(F::from(size)))?;
+            out_of_bounds.assign(
+                region,
+                offset,
+                F::from(step.memory_word_size() * PAGE_SIZE as u64),
+                F::from(address + memory_offset + size),
+            )?;
+        }
+
+        let mut rw_offset = 2 + operand_count;
+        if let Some(indirect_call_type) = &self.indirect_call_type {
+            rw_offset += indirect_call_type.assign(region, offset, block, code, step)?;
+        }
+        if let Some(stack_height_exceeded) = &self.stack_height_exceeded {
+            rw_offset += stack_height_exceeded.assign(region, offset, block, code, opcode, step)?;
+        }
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, rw_offset)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bus_mapping::{circuit_input_builder::CircuitsParams, evm::OpcodeId};
+    use eth_types::{
+        bytecode,
+        bytecode::UncheckedWasmBinary,
+        evm_types::MAX_WASM_STACK_HEIGHT,
+        Bytecode, StackWord,
+    };
+    use mock::TestContext;
+    use wasm_encoder::{
+        CodeSection, ConstExpr, ElementSection, Elements, ExportKind, ExportSection, Function,
+        FunctionSection, Instruction, MemorySection, MemoryType, Module, TableSection, TableType,
+        TypeSection, ValType,
+    };
+
+    use crate::{
+        evm_circuit::{param::PAGE_SIZE, step::ExecutionState},
+        test_util::CircuitTestBuilder,
+        witness::Rw,
+    };
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    #[test]
+    fn test_unreachable() {
+        run_test(bytecode! {
+            I32Const[1]
+            Drop
+            Unreachable
+        });
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        run_test(bytecode! {
+            I32Const[100]
+            I32Const[0]
+            I32DivU
+            Drop
+        });
+        run_test(bytecode! {
+            I64Const[100]
+            I64Const[0]
+            I64RemS
+            Drop
+        });
+    }
+
+    // the memory of the test code is a single page
+
+    #[test]
+    fn test_load_out_of_bounds() {
+        run_test(bytecode! {
+            I32Const[PAGE_SIZE - 3]
+            .write_memarg(OpcodeId::I32Load, 0, 2, 0)
+            Drop
+        });
+        run_test(bytecode! {
+            I32Const[PAGE_SIZE - 4]
+            .write_memarg(OpcodeId::I64Load, 2, 3, 0)
+            Drop
+        });
+    }
+
+    #[test]
+    fn test_load_out_of_bounds_by_offset() {
+        run_test(bytecode! {
+            I32Const[0]
+            .write_memarg(OpcodeId::I32Load8U, PAGE_SIZE as u64, 0, 0)
+            Drop
+        });
+    }
+
+    #[test]
+    fn test_store_out_of_bounds() {
+        run_test(bytecode! {
+            I32Const[PAGE_SIZE - 1]
+            I64Const[7]
+            .write_memarg(OpcodeId::I64Store16, 0, 1, 0)
+        });
+        run_test(bytecode! {
+            I32Const[PAGE_SIZE - 6]
+            I64Const[7]
+            .write_memarg(OpcodeId::I64Store, 0, 3, 0)
+        });
+    }
+
+    #[test]
+    fn test_store_out_of_bounds_by_offset() {
+        run_test(bytecode! {
+            I32Const[0]
+            I32Const[7]
+            .write_memarg(OpcodeId::I32Store, PAGE_SIZE as u64 - 2, 2, 0)
+        });
+    }
+
+    /// Module whose entry function calls the element 0 of its table with the type 1, `() -> i32`.
+    /// The elements 0 and 1 hold the functions 0 of type 0, `() -> ()`, and 1 of type 1.
+    fn call_indirect_code() -> UncheckedWasmBinary {
+        let mut types = TypeSection::new();
+        types.function(vec![], vec![]);
+        types.function(vec![], vec![ValType::I32]);
+        let mut functions = FunctionSection::new();
+        functions.function(0);
+        functions.function(1);
+        functions.function(0);
+        let mut tables = TableSection::new();
+        tables.table(TableType {
+            element_type: ValType::FuncRef,
+            minimum: 2,
+            maximum: None,
+        });
+        let mut memories = MemorySection::new();
+        memories.memory(MemoryType {
+            minimum: 1,
+            maximum: None,
+            memory64: false,
+            shared: false,
+        });
+        let mut exports = ExportSection::new();
+        exports.export("main", ExportKind::Func, 2);
+        exports.export("memory", ExportKind::Memory, 0);
+        let mut elements = ElementSection::new();
+        elements.active(
+            Some(0),
+            &ConstExpr::i32_const(0),
+            ValType::FuncRef,
+            Elements::Functions(&[0, 1]),
+        );
+        let mut codes = CodeSection::new();
+        let mut callee = Function::new(vec![]);
+        callee.instruction(&Instruction::End);
+        codes.function(&callee);
+        let mut callee = Function::new(vec![]);
+        callee.instruction(&Instruction::I32Const(1));
+        callee.instruction(&Instruction::End);
+        codes.function(&callee);
+        let mut main = Function::new(vec![]);
+        main.instruction(&Instruction::I32Const(0));
+        main.instruction(&Instruction::CallIndirect { ty: 1, table: 0 });
+        main.instruction(&Instruction::Drop);
+        main.instruction(&Instruction::End);
+        codes.function(&main);
+
+        let mut module = Module::new();
+        module.section(&types);
+        module.section(&functions);
+        module.section(&tables);
+        module.section(&memories);
+        module.section(&exports);
+        module.section(&elements);
+        module.section(&codes);
+        UncheckedWasmBinary::from(module.finish())
+    }
+
+    #[test]
+    fn test_indirect_call_mismatch() {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(call_indirect_code()).unwrap(),
+        )
+        .run()
+    }
+
+    #[test]
+    fn test_indirect_call_mismatch_of_matching_element() {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(call_indirect_code()).unwrap(),
+        )
+        .block_modifier(Box::new(|block| {
+            // the trap reads the element 1 holding the function 1 of the expected type
+            let step = block.txs[0]
+                .steps
+                .iter()
+                .find(|step| step.execution_state == ExecutionState::ErrorWasmIndirectCallMismatch)
+                .expect("call_indirect traps");
+            let (tag, index) = step.rw_indices[1];
+            match &mut block.rws.0.get_mut(&tag).unwrap()[index] {
+                Rw::Table {
+                    value, value_prev, ..
+                } => {
+                    *value = StackWord::from(2);
+                    *value_prev = StackWord::from(2);
+                }
+                rw => unreachable!("{:?}", rw),
+            }
+        }))
+        .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+            assert!(prover
+                .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                .is_err())
+        }))
+        // the read doesn't match the element written by the begin tx either
+        .state_checks(Box::new(|prover, gate_rows, lookup_rows| {
+            assert!(prover
+                .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                .is_err())
+        }))
+        .run()
+    }
+
+    #[test]
+    fn test_stack_height_exceeded_by_push() {
+        let mut code = Bytecode::default();
+        for _ in 0..=MAX_WASM_STACK_HEIGHT {
+            code.write_postfix(OpcodeId::I32Const, 1);
+        }
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .params(CircuitsParams {
+            max_bytecode: 4096,
+            ..Default::default()
+        })
+        .run()
+    }
+
+    #[test]
+    fn test_stack_height_exceeded_by_callee_locals() {
+        let mut code = bytecode! {
+            I32Const[1]
+            Call[0]
+            Drop
+        };
+        code.new_function(
+            vec![],
+            vec![],
+            bytecode! {},
+            vec![(MAX_WASM_STACK_HEIGHT as u32, ValType::I64)],
+        );
+        run_test(code);
+    }
+
+    #[test]
+    fn test_stack_height_exceeded_by_entry_locals() {
+        let mut code = bytecode! {
+            I32Const[1]
+            Drop
+        };
+        code.with_main_locals(vec![(MAX_WASM_STACK_HEIGHT as u32 + 1, ValType::I32)]);
+        run_test(code);
+    }
+}
@@ -248,7 +248,7 @@ impl<F: Field> ExecutionGadget<F> for EvmCallDataCopyGadget<F> {
 mod test {
     use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
     use bus_mapping::circuit_input_builder::CircuitsParams;
-    use eth_types::{bytecode, StackWord, Word};
+    use eth_types::{bytecode, evm_types::OpcodeId, Bytecode, Word};
     use mock::test_ctx::{helpers::*, TestContext};
 
     fn test_ok_root(
@@ -299,29 +299,26 @@ mod test {
 
         // code B gets called by code A, so the call is an internal call.
         let code_b = bytecode! {
-            I32Const[length]     // size
-            I32Const[offset]     // offset
             I32Const[dst_offset] // dst_offset
+            I32Const[offset]     // offset
+            I32Const[length]     // size
+            #[start]
             CALLDATACOPY
         };
 
-        // code A calls code B.
-        let pushdata = rand_bytes(8);
-        let code_a = bytecode! {
-            // populate memory in A's context.
-            I32Const[StackWord::from_big_endian(&pushdata).as_u64()]
-            I32Const[0x00] // offset
-            MSTORE
-            // call ADDR_B.
-            I32Const[0x00] // retLength
-            I32Const[0x00] // retOffset
-            I32Const[call_data_length] // argsLength
-            I32Const[call_data_offset] // argsOffset
-            I32Const[0x00] // value
-            // I32Const[addr_b.to_word()] // addr
-            I32Const[0x1_0000] // gas
-            CALL
-        };
+        // code A calls code B with call data from its linear memory.
+        let mut code_a = Bytecode::default();
+        code_a.fill_default_global_data(rand_bytes(call_data_offset + call_data_length));
+        code_a.emit_evm_call(
+            OpcodeId::CALL,
+            0x1_0000,
+            addr_b,
+            Word::zero(),
+            call_data_offset as u64,
+            call_data_length as u64,
+            0x00,
+            0x00,
+        );
 
         let ctx = TestContext::<3, 1>::new(
             None,
@@ -345,24 +342,24 @@ mod test {
     #[test]
     fn calldatacopy_gadget_simple() {
         test_ok_root(0x40, 0x40, 0x00, 10);
-        // test_ok_internal(0x40, 0x40, 0xA0, 0x10, 10);
+        test_ok_internal(0x40, 0x40, 0xA0, 0x10, 10);
     }
 
     #[test]
     fn calldatacopy_gadget_large() {
         test_ok_root(0x204, 0x103, 0x102, 0x101);
-        // test_ok_internal(0x30, 0x204, 0x103, 0x102, 0x101);
+        test_ok_internal(0x30, 0x204, 0x103, 0x102, 0x101);
     }
 
     #[test]
     fn calldatacopy_gadget_out_of_bound() {
         test_ok_root(0x40, 0x40, 0x20, 40);
-        // test_ok_internal(0x40, 0x20, 0xA0, 0x28, 10);
+        test_ok_internal(0x40, 0x20, 0xA0, 0x28, 10);
     }
 
     #[test]
     fn calldatacopy_gadget_zero_length() {
         test_ok_root(0x40, 0x40, 0x00, 0);
-        // test_ok_internal(0x40, 0x40, 0xA0, 0x10, 0);
+        test_ok_internal(0x40, 0x40, 0xA0, 0x10, 0);
     }
 }
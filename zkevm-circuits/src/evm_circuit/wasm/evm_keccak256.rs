@@ -5,17 +5,15 @@ use halo2_proofs::{circuit::Value, plonk::Error};
 use itertools::Itertools;
 
 use crate::evm_circuit::{
-    param::N_BYTES_MEMORY_WORD_SIZE,
+    param::PAGE_SIZE,
     step::ExecutionState,
     util::{
         common_gadget::SameContextGadget,
         constraint_builder::{
             ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition, Transition,
         },
-        memory_gadget::{
-            CommonMemoryAddressGadget, MemoryCopierGasGadget,
-            MemoryExpansionGadget,
-        },
+        math_gadget::LtGadget,
+        memory_gadget::{CommonMemoryAddressGadget, MemoryCopierGasGadget},
         rlc, CachedRegion, Cell, Word,
     },
     witness::{Block, Call, ExecStep, Transaction},
@@ -32,7 +30,8 @@ pub(crate) struct EvmKeccak256Gadget<F> {
     copy_rwc_inc: Cell<F>,
     rlc_acc: Cell<F>,
     dest_offset: Cell<F>,
-    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    // the hashed range ends above the allocated pages
+    out_of_bounds: LtGadget<F, 5>,
     memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY_SHA3 }>,
 }
 
@@ -81,18 +80,25 @@ impl<F: Field> ExecutionGadget<F> for EvmKeccak256Gadget<F> {
         });
         cb.keccak_table_lookup(rlc_acc.expr(), memory_address.length(), sha3_rlc.expr());
 
-        let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
-        let memory_copier_gas = MemoryCopierGasGadget::construct(
+        // linear memory is not expanded by the host, the hashed range must be allocated
+        let out_of_bounds = LtGadget::construct(
             cb,
-            memory_address.length(),
-            memory_expansion.gas_cost(),
+            cb.curr.state.memory_word_size.expr() * PAGE_SIZE.expr(),
+            memory_address.address(),
         );
+        cb.condition(memory_address.has_length(), |cb| {
+            cb.require_zero(
+                "hashed range ends within the allocated pages",
+                out_of_bounds.expr(),
+            );
+        });
+        let memory_copier_gas =
+            MemoryCopierGasGadget::construct(cb, memory_address.length(), 0.expr());
 
         let step_state_transition = StepStateTransition {
             rw_counter: Transition::Delta(cb.rw_counter_offset()),
             program_counter: Transition::Delta(1.expr()),
             stack_pointer: Transition::Delta(1.expr()),
-            memory_word_size: Transition::To(memory_expansion.next_memory_word_size()),
             gas_left: Transition::Delta(
                 -(OpcodeId::SHA3.constant_gas_cost().expr() + memory_copier_gas.gas_cost()),
             ),
@@ -107,7 +113,7 @@ impl<F: Field> ExecutionGadget<F> for EvmKeccak256Gadget<F> {
             copy_rwc_inc,
             rlc_acc,
             dest_offset,
-            memory_expansion,
+            out_of_bounds,
             memory_copier_gas,
         }
     }
@@ -128,8 +134,7 @@ impl<F: Field> ExecutionGadget<F> for EvmKeccak256Gadget<F> {
                 .map(|idx| block.rws[idx].stack_value());
         let sha3_bytes = (3..35).map(|i| block.rws[step.rw_indices[i]].memory_value()).collect_vec();
         let sha3_word = eth_types::Word::from_big_endian(sha3_bytes.as_slice());
-        let memory_address = self
-            .memory_address
+        self.memory_address
             .assign(region, offset, memory_offset, size)?;
         self.dest_offset.assign(region, offset, Value::known(F::from(dest_offset.as_u64())))?;
         self.sha3_rlc
@@ -144,9 +149,23 @@ impl<F: Field> ExecutionGadget<F> for EvmKeccak256Gadget<F> {
             ),
         )?;
 
-        let values: Vec<u8> = (35..35 + (size.low_u64() as usize))
-            .map(|i| block.rws[step.rw_indices[i]].memory_value())
-            .collect();
+        // the hashed bytes are the bytes of the copy event starting after the sha3 write
+        let values: Vec<u8> = if size.is_zero() {
+            vec![]
+        } else {
+            block
+                .copy_events
+                .iter()
+                .find(|event| {
+                    event.dst_type == CopyDataType::RlcAcc
+                        && event.rw_counter_start.0 == step.rw_counter + 35
+                })
+                .expect("sha3 step without copy event")
+                .bytes
+                .iter()
+                .map(|(byte, _)| *byte)
+                .collect()
+        };
 
         let rlc_acc = region
             .challenges()
@@ -154,15 +173,14 @@ impl<F: Field> ExecutionGadget<F> for EvmKeccak256Gadget<F> {
             .map(|randomness| rlc::value(values.iter().rev(), randomness));
         self.rlc_acc.assign(region, offset, rlc_acc)?;
 
-        // Memory expansion and dynamic gas cost for reading it.
-        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+        self.out_of_bounds.assign(
             region,
             offset,
-            step.memory_word_size(),
-            [memory_address],
+            F::from(step.memory_word_size() * PAGE_SIZE as u64),
+            F::from(memory_offset.as_u64() + size.as_u64()),
         )?;
         self.memory_copier_gas
-            .assign(region, offset, size.as_u64(), memory_expansion_gas_cost)?;
+            .assign(region, offset, size.as_u64(), 0)?;
 
         Ok(())
     }
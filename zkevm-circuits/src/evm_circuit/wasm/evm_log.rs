@@ -1,17 +1,16 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64},
+        param::{N_BYTES_U64, PAGE_SIZE},
         step::ExecutionState,
         util::{
             common_gadget::{SameContextGadget, WordByteRangeGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
-                Transition::{Delta, To},
-            },
-            memory_gadget::{
-                CommonMemoryAddressGadget, MemoryAddressGadget, MemoryExpansionGadget,
+                Transition::Delta,
             },
+            math_gadget::LtGadget,
+            memory_gadget::{CommonMemoryAddressGadget, MemoryAddressGadget},
             not, sum, CachedRegion, Cell,
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -35,13 +34,16 @@ pub(crate) struct EvmLogGadget<F> {
     topics_log_index: [Cell<F>; 4],
     topic_selectors: [Cell<F>; 4],
     topics_rlc: [Word<F>; 4],
+    // the topic ends above the allocated pages
+    topics_out_of_bounds: [LtGadget<F, 5>; 4],
 
     contract_address: Cell<F>,
     is_static_call: Cell<F>,
     is_persistent: Cell<F>,
     tx_id: Cell<F>,
     copy_rwc_inc: Cell<F>,
-    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    // the data ends above the allocated pages
+    out_of_bounds: LtGadget<F, 5>,
 }
 
 impl<F: Field> ExecutionGadget<F> for EvmLogGadget<F> {
@@ -81,10 +83,24 @@ impl<F: Field> ExecutionGadget<F> for EvmLogGadget<F> {
         let topics_rlc: [Word<F>; 4] = array_init(|_| cb.query_word_rlc());
         let topics_log_index: [Cell<F>; 4] = array_init(|_| cb.query_cell());
 
+        // linear memory is not expanded by the host, the topics must be allocated
+        let topics_out_of_bounds: [LtGadget<F, 5>; 4] = array_init(|idx| {
+            LtGadget::construct(
+                cb,
+                cb.curr.state.memory_word_size.expr() * PAGE_SIZE.expr(),
+                topics_offsets[idx].expr() + 32.expr(),
+            )
+        });
+        for (topic_selector, out_of_bounds) in topic_selectors.iter().zip(&topics_out_of_bounds) {
+            cb.condition(topic_selector.expr(), |cb| {
+                cb.require_zero("topic ends within the allocated pages", out_of_bounds.expr());
+            });
+        }
+
         for (idx, (topics_offsets,  topic_rlc)) in topics_offsets.iter().zip(topics_rlc.iter()).enumerate().rev() {
             cb.condition(topic_selectors[idx].expr(), |cb| {
                 cb.stack_pop(topics_offsets.expr());
-                cb.memory_rlc_lookup(1.expr(), &topics_offsets, &topic_rlc);
+                cb.memory_rlc_lookup(0.expr(), &topics_offsets, &topic_rlc);
             });
             cb.condition(topic_selectors[idx].expr() * is_persistent.expr(), |cb| {
                 cb.tx_log_lookup(
@@ -127,9 +143,18 @@ impl<F: Field> ExecutionGadget<F> for EvmLogGadget<F> {
         // check memory copy
         let memory_address = MemoryAddress64Gadget::construct(cb, mstart, msize);
 
-        // Calculate the next memory size and the gas cost for this memory
-        // access
-        let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
+        // linear memory is not expanded by the host, the data must be allocated
+        let out_of_bounds = LtGadget::construct(
+            cb,
+            cb.curr.state.memory_word_size.expr() * PAGE_SIZE.expr(),
+            memory_address.address(),
+        );
+        cb.condition(memory_address.has_length(), |cb| {
+            cb.require_zero(
+                "data ends within the allocated pages",
+                out_of_bounds.expr(),
+            );
+        });
 
         let copy_rwc_inc = cb.query_cell();
         let dst_addr = build_tx_log_expression(
@@ -161,15 +186,13 @@ impl<F: Field> ExecutionGadget<F> for EvmLogGadget<F> {
 
         let gas_cost = GasCost::LOG.as_u64().expr()
             + GasCost::LOG.as_u64().expr() * topic_count.clone()
-            + 8.expr() * memory_address.length()
-            + memory_expansion.gas_cost();
+            + 8.expr() * memory_address.length();
         // State transition
 
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(cb.rw_counter_offset()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(2.expr() + topic_count),
-            memory_word_size: To(memory_expansion.next_memory_word_size()),
             log_id: Delta(is_persistent.expr()),
             gas_left: Delta(-gas_cost),
             ..Default::default()
@@ -188,8 +211,9 @@ impl<F: Field> ExecutionGadget<F> for EvmLogGadget<F> {
             is_persistent,
             tx_id,
             copy_rwc_inc,
-            memory_expansion,
+            out_of_bounds,
             topics_rlc,
+            topics_out_of_bounds,
         }
     }
 
@@ -216,9 +240,13 @@ impl<F: Field> ExecutionGadget<F> for EvmLogGadget<F> {
             (RwTableTag::Stack, 0usize)
         };
 
+        let memory_size = F::from(step.memory_word_size() * PAGE_SIZE as u64);
+        // each topic is a stack read, 32 memory reads and a log write when persistent
+        let topic_rw_count = 33 + is_persistent as usize;
         for i in 0..4 {
             if i < topic_count {
-                let topic_vec = step.rw_indices[5 + call.is_persistent as usize + 34 * i..6 + 34 * i + 32].iter().map(|&b|
+                let topic_start = 5 + is_persistent as usize + topic_rw_count * i;
+                let topic_vec = step.rw_indices[topic_start..topic_start + 32].iter().map(|&b|
                     block.rws[b].memory_value()
                 ).collect::<Vec<u8>>();
                 let topic = U256::from_big_endian(topic_vec.as_slice());
@@ -229,10 +257,17 @@ impl<F: Field> ExecutionGadget<F> for EvmLogGadget<F> {
                 topic_stack_entry.1 += 1;
                 self.topics_rlc[topic_count - 1 - i].assign(region, offset, Some(topic.to_le_bytes()))?;
                 self.topics_offsets[topic_count - 1 - i].assign(region, offset, Value::<F>::known(topic_offset.to_scalar().unwrap()))?;
+                self.topics_out_of_bounds[topic_count - 1 - i].assign(
+                    region,
+                    offset,
+                    memory_size,
+                    F::from(topic_offset.as_u64() + 32),
+                )?;
             } else {
                 self.topic_selectors[i].assign(region, offset, Value::known(F::zero()))?;
                 self.topics_rlc[i].assign(region, offset, Some(U256::zero().to_le_bytes()))?;
                 self.topics_offsets[i].assign(region, offset, Value::<F>::known(StackWord::zero().to_scalar().unwrap()))?;
+                self.topics_out_of_bounds[i].assign(region, offset, memory_size, F::from(32))?;
             }
         }
 
@@ -242,13 +277,14 @@ impl<F: Field> ExecutionGadget<F> for EvmLogGadget<F> {
                 block.rws[idx].stack_value()
             });
 
-        let memory_address = self
-            .memory_address
+        self.memory_address
             .assign(region, offset, memory_start, msize)?;
-
-        // Memory expansion
-        self.memory_expansion
-            .assign(region, offset, step.memory_word_size(), [memory_address])?;
+        self.out_of_bounds.assign(
+            region,
+            offset,
+            memory_size,
+            F::from(memory_start.low_u64().wrapping_add(msize.low_u64())),
+        )?;
 
         self.contract_address.assign(
             region,
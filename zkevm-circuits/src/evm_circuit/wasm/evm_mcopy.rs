@@ -0,0 +1,152 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_MEMORY_WORD_SIZE,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            memory_gadget::{
+                CommonMemoryAddressGadget, MemoryAddress64Gadget, MemoryCopierGasGadget,
+                MemoryExpansionGadget,
+            },
+            not, CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use eth_types::{evm_types::GasCost, Field};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct EvmMcopyGadget<F> {
+    same_context: SameContextGadget<F>,
+    src_address: MemoryAddress64Gadget<F>,
+    dst_address: MemoryAddress64Gadget<F>,
+    copy_rwc_inc: Cell<F>,
+    memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
+}
+
+impl<F: Field> ExecutionGadget<F> for EvmMcopyGadget<F> {
+    const NAME: &'static str = "MCOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::MCOPY;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let dst_offset = cb.query_cell_phase2();
+        let src_offset = cb.query_cell_phase2();
+        let length = cb.query_cell();
+
+        // Pop dst_offset, src_offset, length from stack
+        cb.stack_pop(length.expr());
+        cb.stack_pop(src_offset.expr());
+        cb.stack_pop(dst_offset.expr());
+
+        let src_address = MemoryAddress64Gadget::construct(cb, src_offset, length.clone());
+        let dst_address = MemoryAddress64Gadget::construct(cb, dst_offset, length);
+
+        // Both the source and the destination range can expand the memory
+        let memory_expansion =
+            MemoryExpansionGadget::construct(cb, [src_address.address(), dst_address.address()]);
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            dst_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        let copy_rwc_inc = cb.query_cell();
+        cb.condition(dst_address.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                src_address.offset(),
+                src_address.address(),
+                dst_address.offset(),
+                dst_address.length(),
+                0.expr(),
+                copy_rwc_inc.expr(),
+            );
+        });
+        cb.condition(not::expr(dst_address.has_length()), |cb| {
+            cb.require_zero(
+                "if no bytes to copy, copy table rwc inc == 0",
+                copy_rwc_inc.expr(),
+            );
+        });
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(3.expr()),
+            gas_left: Delta(
+                -(OpcodeId::MCOPY.constant_gas_cost().expr() + memory_copier_gas.gas_cost()),
+            ),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            src_address,
+            dst_address,
+            copy_rwc_inc,
+            memory_expansion,
+            memory_copier_gas,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [length, src_offset, dst_offset] =
+            [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2]]
+                .map(|idx| block.rws[idx].stack_value());
+        let src_address = self
+            .src_address
+            .assign(region, offset, src_offset, length)?;
+        let dst_address = self
+            .dst_address
+            .assign(region, offset, dst_offset, length)?;
+
+        // every copied byte is one memory read and one memory write
+        self.copy_rwc_inc.assign(
+            region,
+            offset,
+            Value::known(F::from(2 * length.as_u64())),
+        )?;
+
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [src_address, dst_address],
+        )?;
+        self.memory_copier_gas.assign(
+            region,
+            offset,
+            length.as_u64(),
+            memory_expansion_gas_cost as u64,
+        )?;
+
+        Ok(())
+    }
+}
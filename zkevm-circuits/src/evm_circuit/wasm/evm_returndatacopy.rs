@@ -66,10 +66,10 @@ impl<F: Field> ExecutionGadget<F> for EvmReturnDataCopyGadget<F> {
         let data_offset = cb.query_word_rlc();
         let size = cb.query_word_rlc();
 
-        // 1. Pop dest_offset, offset, length from stack
-        cb.stack_pop(dest_offset.expr());
-        cb.stack_pop(data_offset.expr());
+        // 1. Pop size, offset, dest_offset from stack, wasm pushes dest_offset first
         cb.stack_pop(size.expr());
+        cb.stack_pop(data_offset.expr());
+        cb.stack_pop(dest_offset.expr());
 
         // 2. Add lookup constraint in the call context for the returndatacopy field.
         let last_callee_id = cb.query_cell();
@@ -177,7 +177,7 @@ impl<F: Field> ExecutionGadget<F> for EvmReturnDataCopyGadget<F> {
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
 
-        let [dest_offset, data_offset, size] =
+        let [size, data_offset, dest_offset] =
             [0, 1, 2].map(|i| block.rws[step.rw_indices[i as usize]].stack_value());
 
         self.data_offset.assign(
@@ -271,7 +271,7 @@ impl<F: Field> ExecutionGadget<F> for EvmReturnDataCopyGadget<F> {
 mod test {
     use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
     use bus_mapping::circuit_input_builder::CircuitsParams;
-    use eth_types::{bytecode, ToWord, Word};
+    use eth_types::{bytecode_internal, evm_types::OpcodeId, Bytecode, Word};
     use mock::test_ctx::TestContext;
 
     fn test_ok_internal(
@@ -283,37 +283,34 @@ mod test {
     ) {
         let (addr_a, addr_b) = (mock::MOCK_ACCOUNTS[0], mock::MOCK_ACCOUNTS[1]);
 
-        let pushdata = rand_bytes(32);
-        let return_offset =
-            std::cmp::max((return_data_offset + return_data_size) as i64 - 32, 0) as usize;
-        let code_b = bytecode! {
-            PUSH32(Word::from_big_endian(&pushdata))
-            PUSH32(return_offset)
-            MSTORE
-
-            PUSH32(return_data_size)
-            PUSH32(return_data_offset)
+        // code B returns `return_data_size` bytes of its linear memory.
+        let mut code_b = Bytecode::default();
+        code_b.fill_default_global_data(rand_bytes(return_data_offset + return_data_size));
+        bytecode_internal!(code_b,
+            I32Const[return_data_offset]
+            I32Const[return_data_size]
             RETURN
-            STOP
-        };
+        );
 
-        // code A calls code B.
-        let code_a = bytecode! {
-            // call ADDR_B.
-            PUSH32(return_data_size) // retLength
-            PUSH32(return_data_offset) // retOffset
-            PUSH1(0x00) // argsLength
-            PUSH1(0x00) // argsOffset
-            PUSH1(0x00) // value
-            PUSH32(addr_b.to_word()) // addr
-            PUSH32(0x1_0000) // gas
-            CALL
-            PUSH32(size) // size
-            PUSH32(offset) // offset
-            PUSH32(dest_offset) // dest_offset
+        // code A calls code B and copies the return data into its linear memory.
+        let mut code_a = Bytecode::default();
+        code_a.alloc_default_global_data((dest_offset + size) as u32);
+        code_a.emit_evm_call(
+            OpcodeId::CALL,
+            0x1_0000,
+            addr_b,
+            Word::zero(),
+            0x00,
+            0x00,
+            return_data_offset as u64,
+            return_data_size as u64,
+        );
+        bytecode_internal!(code_a,
+            I32Const[dest_offset]
+            I32Const[offset]
+            I32Const[size]
             RETURNDATACOPY
-            STOP
-        };
+        );
 
         let ctx = TestContext::<3, 1>::new(
             None,
@@ -0,0 +1,112 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{EVMConstraintBuilder, StepStateTransition, Transition::Delta},
+            CachedRegion, Cell, RandomLinearCombination,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{Field, ToLittleEndian, ToScalar};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct EvmTloadGadget<F> {
+    same_context: SameContextGadget<F>,
+    tx_id: Cell<F>,
+    callee_address: Cell<F>,
+    key_offset: Cell<F>,
+    key: RandomLinearCombination<F, 32>,
+    value_offset: Cell<F>,
+    value: RandomLinearCombination<F, 32>,
+}
+
+impl<F: Field> ExecutionGadget<F> for EvmTloadGadget<F> {
+    const NAME: &'static str = "TLOAD";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::TLOAD;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+
+        let value_offset = cb.query_cell();
+        let key_offset = cb.query_cell();
+        cb.stack_pop(value_offset.expr());
+        cb.stack_pop(key_offset.expr());
+
+        let key = cb.query_word_rlc();
+        cb.memory_rlc_lookup(0.expr(), &key_offset, &key);
+
+        let value = cb.query_word_rlc();
+        cb.transient_storage_read(tx_id.expr(), callee_address.expr(), key.expr(), value.expr());
+        cb.memory_rlc_lookup(1.expr(), &value_offset, &value);
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(69.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(2.expr()),
+            gas_left: Delta(-OpcodeId::TLOAD.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            tx_id,
+            callee_address,
+            key_offset,
+            key,
+            value_offset,
+            value,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(
+                call.callee_address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+
+        let [value_offset, key_offset] =
+            [step.rw_indices[2], step.rw_indices[3]].map(|idx| block.rws[idx].stack_value());
+        self.value_offset.assign(region, offset, Value::known(F::from(value_offset.as_u64())))?;
+        self.key_offset.assign(region, offset, Value::known(F::from(key_offset.as_u64())))?;
+
+        let transient_storage = &block.rws[step.rw_indices[4 + 32]];
+        let (value, _) = transient_storage.transient_storage_value_pair();
+        self.key.assign(
+            region,
+            offset,
+            Some(transient_storage.storage_key().unwrap().to_le_bytes()),
+        )?;
+        self.value.assign(region, offset, Some(value.to_le_bytes()))?;
+
+        Ok(())
+    }
+}
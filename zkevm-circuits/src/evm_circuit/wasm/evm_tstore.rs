@@ -0,0 +1,146 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::Delta,
+            },
+            CachedRegion, Cell, RandomLinearCombination,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{Field, ToLittleEndian, ToScalar};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct EvmTstoreGadget<F> {
+    same_context: SameContextGadget<F>,
+    tx_id: Cell<F>,
+    is_static: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    callee_address: Cell<F>,
+    key_offset: Cell<F>,
+    key: RandomLinearCombination<F, 32>,
+    value_offset: Cell<F>,
+    value: RandomLinearCombination<F, 32>,
+    value_prev: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for EvmTstoreGadget<F> {
+    const NAME: &'static str = "TSTORE";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::TSTORE;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+
+        // constrain not in static call
+        let is_static = cb.call_context(None, CallContextFieldTag::IsStatic);
+        cb.require_zero("is_static is false", is_static.expr());
+
+        let mut reversion_info = cb.reversion_info_read(None);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+
+        let value_offset = cb.query_cell();
+        let key_offset = cb.query_cell();
+        cb.stack_pop(value_offset.expr());
+        cb.stack_pop(key_offset.expr());
+
+        let key = cb.query_word_rlc();
+        let value = cb.query_word_rlc();
+        cb.memory_rlc_lookup(0.expr(), &key_offset, &key);
+        cb.memory_rlc_lookup(0.expr(), &value_offset, &value);
+
+        let value_prev = cb.query_cell_phase2();
+        cb.transient_storage_write(
+            tx_id.expr(),
+            callee_address.expr(),
+            key.expr(),
+            value.expr(),
+            value_prev.expr(),
+            Some(&mut reversion_info),
+        );
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(72.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(2.expr()),
+            reversible_write_counter: Delta(1.expr()),
+            gas_left: Delta(-OpcodeId::TSTORE.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            tx_id,
+            is_static,
+            reversion_info,
+            callee_address,
+            key_offset,
+            key,
+            value_offset,
+            value,
+            value_prev,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.is_static
+            .assign(region, offset, Value::known(F::from(call.is_static as u64)))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(
+                call.callee_address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+
+        let [value_offset, key_offset] =
+            [step.rw_indices[5], step.rw_indices[6]].map(|idx| block.rws[idx].stack_value());
+        self.value_offset.assign(region, offset, Value::known(F::from(value_offset.as_u64())))?;
+        self.key_offset.assign(region, offset, Value::known(F::from(key_offset.as_u64())))?;
+
+        let transient_storage = &block.rws[step.rw_indices[7 + 32 + 32]];
+        let (value, value_prev) = transient_storage.transient_storage_value_pair();
+        self.key.assign(
+            region,
+            offset,
+            Some(transient_storage.storage_key().unwrap().to_le_bytes()),
+        )?;
+        self.value.assign(region, offset, Some(value.to_le_bytes()))?;
+        self.value_prev
+            .assign(region, offset, region.word_rlc(value_prev))?;
+
+        Ok(())
+    }
+}
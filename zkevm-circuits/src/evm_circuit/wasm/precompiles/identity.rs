@@ -0,0 +1,171 @@
+use eth_types::{Field, ToScalar};
+use gadgets::util::Expr;
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget, constraint_builder::EVMConstraintBuilder,
+            CachedRegion, Cell,
+        },
+    },
+    table::CallContextFieldTag,
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+#[derive(Clone, Debug)]
+pub(crate) struct IdentityGadget<F> {
+    is_success: Cell<F>,
+    callee_address: Cell<F>,
+    caller_id: Cell<F>,
+    call_data_offset: Cell<F>,
+    call_data_length: Cell<F>,
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for IdentityGadget<F> {
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileIdentity;
+
+    const NAME: &'static str = "IDENTITY";
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let [is_success, callee_address, caller_id, call_data_offset, call_data_length, return_data_offset, return_data_length] =
+            [
+                CallContextFieldTag::IsSuccess,
+                CallContextFieldTag::CalleeAddress,
+                CallContextFieldTag::CallerId,
+                CallContextFieldTag::CallDataOffset,
+                CallContextFieldTag::CallDataLength,
+                CallContextFieldTag::ReturnDataOffset,
+                CallContextFieldTag::ReturnDataLength,
+            ]
+            .map(|tag| cb.call_context(None, tag));
+
+        cb.precompile_info_lookup(
+            cb.execution_state().as_u64().expr(),
+            callee_address.expr(),
+            cb.execution_state().precompile_base_gas_cost().expr(),
+        );
+
+        let restore_context = RestoreContextGadget::construct(
+            cb,
+            is_success.expr(),
+            0.expr(),
+            0.expr(),
+            0.expr(),
+            0.expr(),
+            0.expr(),
+        );
+
+        Self {
+            is_success,
+            callee_address,
+            caller_id,
+            call_data_offset,
+            call_data_length,
+            return_data_offset,
+            return_data_length,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.is_success.assign(
+            region,
+            offset,
+            Value::known(F::from(u64::from(call.is_success))),
+        )?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(call.code_address.unwrap().to_scalar().unwrap()),
+        )?;
+        self.caller_id
+            .assign(region, offset, Value::known(F::from(call.caller_id as u64)))?;
+        self.call_data_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(call.call_data_offset)),
+        )?;
+        self.call_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(call.call_data_length)),
+        )?;
+        self.return_data_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(call.return_data_offset)),
+        )?;
+        self.return_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(call.return_data_length)),
+        )?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step, 7)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bus_mapping::{circuit_input_builder::CircuitsParams, precompile::PrecompileCalls};
+    use eth_types::{evm_types::OpcodeId, Address, Bytecode, Word};
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn test_ok(opcode: OpcodeId, input: Vec<u8>, ret_length: u64) {
+        let mut code = Bytecode::default();
+        let input_length = input.len() as u64;
+        let input_offset = code.fill_default_global_data(input);
+        let ret_offset = code.alloc_default_global_data(ret_length as u32);
+        code.emit_evm_call(
+            opcode,
+            1000,
+            Address::from_low_u64_be(PrecompileCalls::Identity.address()),
+            Word::zero(),
+            input_offset as u64,
+            input_length,
+            ret_offset as u64,
+            ret_length,
+        );
+
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .params(CircuitsParams {
+            max_rws: 1000,
+            max_copy_rows: 1000,
+            ..Default::default()
+        })
+        .run();
+    }
+
+    #[test]
+    fn precompile_identity_single_byte() {
+        test_ok(OpcodeId::CALL, vec![0xff], 1);
+        test_ok(OpcodeId::STATICCALL, vec![0xff], 1);
+    }
+
+    #[test]
+    fn precompile_identity_multi_bytes() {
+        let input = (0..0x3fu8).collect::<Vec<_>>();
+        // return only the first 35 bytes
+        test_ok(OpcodeId::CALL, input.clone(), 0x23);
+        test_ok(OpcodeId::STATICCALL, input, 0x23);
+    }
+}
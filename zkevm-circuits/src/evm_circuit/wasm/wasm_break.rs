@@ -1,6 +1,6 @@
 use halo2_proofs::plonk::Error;
 
-use bus_mapping::evm::OpcodeId;
+use bus_mapping::wasm::WASM_GAS_SCHEDULE;
 use eth_types::Field;
 
 use crate::{
@@ -37,7 +37,7 @@ impl<F: Field> ExecutionGadget<F> for WasmBreakGadget<F> {
             rw_counter: Delta(2.expr()),
             program_counter: To(program_counter.expr()),
             stack_pointer: Delta(0.expr()),
-            gas_left: Delta(-OpcodeId::Call.constant_gas_cost().expr()),
+            gas_left: Delta(-WASM_GAS_SCHEDULE.control.expr()),
             ..Default::default()
         };
 
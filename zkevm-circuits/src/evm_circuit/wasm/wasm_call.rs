@@ -1,30 +1,47 @@
 use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::Error;
 
-use bus_mapping::evm::OpcodeId;
-use eth_types::Field;
+use bus_mapping::wasm::WASM_GAS_SCHEDULE;
+use eth_types::{evm_types::MAX_WASM_STACK_HEIGHT, Field};
 
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
+        param::STACK_CAPACITY,
         step::ExecutionState,
         util::{
             CachedRegion,
             common_gadget::SameContextGadget,
-            constraint_builder::{StepStateTransition, Transition::Delta, Transition::To},
+            constraint_builder::{
+                ConstrainBuilderCommon, StepStateTransition, Transition::Delta, Transition::To,
+            },
+            math_gadget::LtGadget,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
+    table::CallContextFieldTag,
     util::Expr,
+    wasm_circuit::bytecode::bytecode::WasmBytecode,
 };
 use crate::evm_circuit::util::Cell;
 use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
 
+/// Gadget for `call`. The callee's frame id is the rw counter of this step, the caller's frame id,
+/// function index and return program counter are saved under it with the arity of the callee and
+/// restored by [`WasmReturnGadget`](super::wasm_return::WasmReturnGadget). The locals of the
+/// callee are reserved below the stack pointer and must fit into the stack.
 #[derive(Clone, Debug)]
 pub(crate) struct WasmCallGadget<F> {
     same_context: SameContextGadget<F>,
     program_counter: Cell<F>,
     function_index: Cell<F>,
+    caller_frame_id: Cell<F>,
+    caller_function_index: Cell<F>,
+    num_params: Cell<F>,
+    num_results: Cell<F>,
+    num_locals: Cell<F>,
+    type_index: Cell<F>,
+    locals_in_stack: LtGadget<F, 4>,
 }
 
 impl<F: Field> ExecutionGadget<F> for WasmCallGadget<F> {
@@ -35,25 +52,60 @@ impl<F: Field> ExecutionGadget<F> for WasmCallGadget<F> {
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let function_index = cb.query_cell();
         let program_counter = cb.query_cell();
-
-        // cb.call_context_lookup(
-        //     1.expr(),
-        //     None,
-        //     CallContextFieldTag::InternalFunctionId,
-        //     function_index.expr(),
-        // );
-        // cb.call_context_lookup(
-        //     1.expr(),
-        //     None,
-        //     CallContextFieldTag::ProgramCounter,
-        //     program_counter.expr(),
-        // );
+        // TODO: check the arity against the type section of the code
+        let num_params = cb.query_cell();
+        let num_results = cb.query_cell();
+        let num_locals = cb.query_cell();
+        let type_index = cb.query_cell();
+        cb.wasm_function_table_lookup(
+            cb.curr.state.code_hash.expr(),
+            function_index.expr(),
+            num_locals.expr(),
+            type_index.expr(),
+        );
+        // stack height is `STACK_CAPACITY - stack_pointer`, the locals can't spill out of it
+        let locals_in_stack = LtGadget::construct(
+            cb,
+            num_locals.expr() + (STACK_CAPACITY as u64 - MAX_WASM_STACK_HEIGHT).expr(),
+            cb.curr.state.stack_pointer.expr() + 1.expr(),
+        );
+        cb.require_equal(
+            "stack height + num_locals <= MAX_WASM_STACK_HEIGHT",
+            locals_in_stack.expr(),
+            1.expr(),
+        );
+
+        let caller_frame_id = cb.call_context(None, CallContextFieldTag::FrameId);
+        let caller_function_index = cb.call_context(None, CallContextFieldTag::InternalFunctionId);
+
+        let frame_id = cb.curr.state.rw_counter.expr();
+        for (field_tag, value) in [
+            (CallContextFieldTag::CallerFrameId, caller_frame_id.expr()),
+            (CallContextFieldTag::CallerFunctionIndex, caller_function_index.expr()),
+            (
+                CallContextFieldTag::ReturnProgramCounter,
+                cb.curr.state.program_counter.expr() + 1.expr(),
+            ),
+            (CallContextFieldTag::LocalsBase, cb.curr.state.stack_pointer.expr()),
+            (CallContextFieldTag::NumParams, num_params.expr()),
+            (CallContextFieldTag::NumResults, num_results.expr()),
+            (CallContextFieldTag::NumLocals, num_locals.expr()),
+        ] {
+            cb.call_context_lookup(true.expr(), Some(frame_id.clone()), field_tag, value);
+        }
+        for (field_tag, value) in [
+            (CallContextFieldTag::FrameId, frame_id),
+            (CallContextFieldTag::InternalFunctionId, function_index.expr()),
+            (CallContextFieldTag::ProgramCounter, program_counter.expr()),
+        ] {
+            cb.call_context_lookup(true.expr(), None, field_tag, value);
+        }
 
         let step_state_transition = StepStateTransition {
-            rw_counter: Delta(2.expr()),
+            rw_counter: Delta(12.expr()),
             program_counter: To(program_counter.expr()),
             stack_pointer: Delta(0.expr()),
-            gas_left: Delta(-OpcodeId::Call.constant_gas_cost().expr()),
+            gas_left: Delta(-WASM_GAS_SCHEDULE.call.expr()),
             ..Default::default()
         };
 
@@ -64,6 +116,13 @@ impl<F: Field> ExecutionGadget<F> for WasmCallGadget<F> {
             same_context,
             program_counter,
             function_index,
+            caller_frame_id,
+            caller_function_index,
+            num_params,
+            num_results,
+            num_locals,
+            type_index,
+            locals_in_stack,
         }
     }
 
@@ -73,15 +132,43 @@ impl<F: Field> ExecutionGadget<F> for WasmCallGadget<F> {
         offset: usize,
         block: &Block<F>,
         _: &Transaction,
-        _call: &Call,
+        call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
 
-        let function_index = block.rws[step.rw_indices[0]].call_context_value();
-        self.function_index.assign(region, offset, Value::known(F::from(function_index.low_u64())))?;
-        let program_counter = block.rws[step.rw_indices[1]].call_context_value();
-        self.program_counter.assign(region, offset, Value::known(F::from(program_counter.low_u64())))?;
+        for (cell, rw_index) in [
+            (&self.caller_frame_id, 0),
+            (&self.caller_function_index, 1),
+            (&self.num_params, 6),
+            (&self.num_results, 7),
+            (&self.num_locals, 8),
+            (&self.function_index, 10),
+            (&self.program_counter, 11),
+        ] {
+            let value = block.rws[step.rw_indices[rw_index]].call_context_value();
+            cell.assign(region, offset, Value::known(F::from(value.low_u64())))?;
+        }
+
+        let function_index = block.rws[step.rw_indices[10]].call_context_value().low_u64();
+        let code = block
+            .bytecodes
+            .get(&call.code_hash)
+            .expect("could not find current environment's bytecode");
+        let type_index = WasmBytecode::new(code.bytes.clone())
+            .function_type_index(function_index)
+            .expect("code of a step has a valid function section")
+            .expect("called function is defined by the code");
+        self.type_index
+            .assign(region, offset, Value::known(F::from(type_index)))?;
+
+        let num_locals = block.rws[step.rw_indices[8]].call_context_value().low_u64();
+        self.locals_in_stack.assign(
+            region,
+            offset,
+            F::from(num_locals + (STACK_CAPACITY as u64 - MAX_WASM_STACK_HEIGHT)),
+            F::from(step.stack_pointer as u64 + 1),
+        )?;
 
         Ok(())
     }
@@ -1,6 +1,7 @@
 use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::Error;
 
+use bus_mapping::wasm::WASM_GAS_SCHEDULE;
 use eth_types::{evm_types::OpcodeId, Field, ToScalar};
 
 use crate::{
@@ -42,7 +43,7 @@ impl<F: Field> ExecutionGadget<F> for WasmConstGadget<F> {
             rw_counter: Delta(1.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta((-1).expr()),
-            gas_left: Delta(-OpcodeId::I32Const.constant_gas_cost().expr()),
+            gas_left: Delta(-WASM_GAS_SCHEDULE.numeric.expr()),
             ..Default::default()
         };
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
@@ -2,6 +2,7 @@ use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::{Error};
 
 use bus_mapping::evm::OpcodeId;
+use bus_mapping::wasm::WASM_GAS_SCHEDULE;
 use eth_types::{Field, ToScalar};
 
 use crate::{
@@ -115,7 +116,7 @@ impl<F: Field> ExecutionGadget<F> for WasmConversionGadget<F> {
             rw_counter: Delta(2.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(0.expr()),
-            gas_left: Delta(-OpcodeId::I32WrapI64.constant_gas_cost().expr()),
+            gas_left: Delta(-WASM_GAS_SCHEDULE.numeric.expr()),
             ..StepStateTransition::default()
         };
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
@@ -1,8 +1,7 @@
-use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::Error;
 
-use bus_mapping::evm::OpcodeId;
-use eth_types::{Field, ToScalar};
+use bus_mapping::wasm::WASM_GAS_SCHEDULE;
+use eth_types::Field;
 
 use crate::{
     evm_circuit::{
@@ -10,7 +9,6 @@ use crate::{
         step::ExecutionState,
         util::{
             CachedRegion,
-            Cell,
             common_gadget::SameContextGadget, constraint_builder::{StepStateTransition, Transition::Delta},
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -19,10 +17,10 @@ use crate::{
 };
 use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
 
+/// `drop` is a pure stack pointer increment, the dropped value is never looked up.
 #[derive(Clone, Debug)]
 pub(crate) struct WasmDropGadget<F> {
     same_context: SameContextGadget<F>,
-    phase2_value: Cell<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for WasmDropGadget<F> {
@@ -31,42 +29,30 @@ impl<F: Field> ExecutionGadget<F> for WasmDropGadget<F> {
     const EXECUTION_STATE: ExecutionState = ExecutionState::WASM_DROP;
 
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
-        let phase2_value = cb.query_cell_phase2();
-
-        // Pop the value from the stack
-        cb.stack_pop(phase2_value.expr());
-
         // State transition
         let step_state_transition = StepStateTransition {
-            rw_counter: Delta(1.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(1.expr()),
-            gas_left: Delta(-OpcodeId::POP.constant_gas_cost().expr()),
+            gas_left: Delta(-WASM_GAS_SCHEDULE.parametric.expr()),
             ..Default::default()
         };
         let opcode = cb.query_cell();
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
 
-        Self {
-            same_context,
-            phase2_value,
-        }
+        Self { same_context }
     }
 
     fn assign_exec_step(
         &self,
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
-        block: &Block<F>,
+        _: &Block<F>,
         _: &Transaction,
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
 
-        let value = block.rws[step.rw_indices[0]].stack_value();
-        self.phase2_value.assign(region, offset, Value::known(value.to_scalar().unwrap()))?;
-
         Ok(())
     }
 }
@@ -136,6 +136,7 @@ impl<F: Field> ExecutionGadget<F> for WasmEndGadget<F> {
 mod test {
     use crate::test_util::CircuitTestBuilder;
     use eth_types::{bytecode, Bytecode};
+    use wasm_encoder::ValType;
 
     use mock::TestContext;
 
@@ -152,4 +153,11 @@ mod test {
         };
         run_test(code);
     }
+
+    #[test]
+    fn test_end_of_empty_body_with_locals() {
+        let mut code = bytecode! {};
+        code.with_main_locals(vec![(2, ValType::I32), (1, ValType::I64)]);
+        run_test(code);
+    }
 }
@@ -2,6 +2,7 @@ use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::Error;
 
 use bus_mapping::evm::OpcodeId;
+use bus_mapping::wasm::WASM_GAS_SCHEDULE;
 use eth_types::{Field, ToScalar};
 
 use crate::{
@@ -27,6 +28,7 @@ pub(crate) struct WasmGlobalGadget<F> {
     is_set_global: Cell<F>,
     index: Cell<F>,
     value: Cell<F>,
+    value_prev: Cell<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for WasmGlobalGadget<F> {
@@ -48,10 +50,11 @@ impl<F: Field> ExecutionGadget<F> for WasmGlobalGadget<F> {
 
         let index = cb.query_cell();
         let value = cb.query_cell();
+        let value_prev = cb.query_cell();
 
         cb.condition(is_set_global.expr(), |cb| {
             cb.stack_pop(value.expr());
-            cb.global_write(index.expr(), value.expr());
+            cb.global_write(index.expr(), value.expr(), value_prev.expr());
         });
 
         cb.condition(is_get_global.expr(), |cb| {
@@ -65,7 +68,7 @@ impl<F: Field> ExecutionGadget<F> for WasmGlobalGadget<F> {
             rw_counter: Delta(2.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(sp),
-            gas_left: Delta(-OpcodeId::GetGlobal.constant_gas_cost().expr()),
+            gas_left: Delta(-WASM_GAS_SCHEDULE.variable.expr()),
             ..Default::default()
         };
 
@@ -77,6 +80,7 @@ impl<F: Field> ExecutionGadget<F> for WasmGlobalGadget<F> {
             is_get_global,
             index,
             value,
+            value_prev,
         }
     }
 
@@ -95,7 +99,9 @@ impl<F: Field> ExecutionGadget<F> for WasmGlobalGadget<F> {
             OpcodeId::SetGlobal => {
                 self.is_set_global.assign(region, offset, Value::known(F::one()))?;
                 let (value, index) = block.rws[step.rw_indices[1]].global_value();
+                let (_, value_prev) = block.rws[step.rw_indices[1]].global_value_pair();
                 self.value.assign(region, offset, Value::<F>::known(value.to_scalar().unwrap()))?;
+                self.value_prev.assign(region, offset, Value::<F>::known(value_prev.to_scalar().unwrap()))?;
                 self.index.assign(region, offset, Value::<F>::known(index.to_scalar().unwrap()))?;
             },
             OpcodeId::GetGlobal => {
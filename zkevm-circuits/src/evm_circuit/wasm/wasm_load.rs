@@ -2,6 +2,7 @@ use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::{Error, Expression};
 
 use bus_mapping::evm::OpcodeId;
+use bus_mapping::wasm::WASM_GAS_SCHEDULE;
 use eth_types::{Field, ToScalar};
 
 use crate::{
@@ -239,7 +240,7 @@ impl<F: Field> ExecutionGadget<F> for WasmLoadGadget<F> {
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(0.expr()),
             // TODO: Change opcode.
-            gas_left: Delta(-OpcodeId::I32Eqz.constant_gas_cost().expr()),
+            gas_left: Delta(-WASM_GAS_SCHEDULE.memory.expr()),
             ..StepStateTransition::default()
         };
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
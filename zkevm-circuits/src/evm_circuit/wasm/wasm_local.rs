@@ -2,6 +2,7 @@ use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::Error;
 
 use bus_mapping::evm::OpcodeId;
+use bus_mapping::wasm::WASM_GAS_SCHEDULE;
 use eth_types::{Field, ToScalar};
 
 use crate::{
@@ -72,7 +73,7 @@ impl<F: Field> ExecutionGadget<F> for WasmLocalGadget<F> {
             program_counter: Delta(1.expr()),
             stack_pointer: Delta((-1).expr()),
             // stack_pointer: Delta(is_tee_local.expr() * 2.expr() + (1.expr() - is_tee_local.expr()) * 1.expr()),
-            gas_left: Delta(-OpcodeId::GetLocal.constant_gas_cost().expr()),
+            gas_left: Delta(-WASM_GAS_SCHEDULE.variable.expr()),
             ..Default::default()
         };
 
@@ -197,4 +198,23 @@ mod test {
         code.with_main_locals(vec![(3, ValType::I32)]);
         run_test(code);
     }
+
+    #[test]
+    fn test_unused_locals() {
+        let mut code = bytecode! {
+            GetLocal[3]
+            Drop
+            I32Const[5]
+            SetLocal[0]
+        };
+        code.with_main_locals(vec![(2, ValType::I32), (2, ValType::I64)]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_locals_with_empty_body() {
+        let mut code = bytecode! {};
+        code.with_main_locals(vec![(2, ValType::I64)]);
+        run_test(code);
+    }
 }
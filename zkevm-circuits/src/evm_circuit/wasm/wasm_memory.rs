@@ -0,0 +1,165 @@
+use halo2_proofs::circuit::Value;
+use halo2_proofs::plonk::Error;
+
+use bus_mapping::evm::OpcodeId;
+use bus_mapping::wasm::WASM_GAS_SCHEDULE;
+use eth_types::{Field, ToScalar};
+use gadgets::util::not;
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            CachedRegion,
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstrainBuilderCommon, StepStateTransition, Transition::Delta, Transition::To,
+            },
+            math_gadget::LtGadget,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use crate::evm_circuit::util::Cell;
+use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
+
+/// Gadget for `memory.size` and `memory.grow`. The memory size in pages is the
+/// `memory_word_size` of the step state. Growing above the configured page limit leaves the
+/// memory as is and pushes `-1`.
+#[derive(Clone, Debug)]
+pub(crate) struct WasmMemoryGadget<F> {
+    same_context: SameContextGadget<F>,
+    delta: Cell<F>,
+    result: Cell<F>,
+    exceeds_limit: LtGadget<F, 5>,
+    max_memory_pages: u64,
+}
+
+impl<F: Field> ExecutionGadget<F> for WasmMemoryGadget<F> {
+    const NAME: &'static str = "WASM_MEMORY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::WASM_MEMORY;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let delta = cb.query_cell();
+        let result = cb.query_cell();
+
+        // `memory.grow` directly follows `memory.size` in the encoding
+        let is_grow = opcode.expr() - OpcodeId::CurrentMemory.expr();
+        cb.require_boolean("opcode is memory.size or memory.grow", is_grow.clone());
+
+        let max_memory_pages = cb.max_memory_pages;
+        let pages = cb.curr.state.memory_word_size.expr();
+        let exceeds_limit =
+            LtGadget::construct(cb, max_memory_pages.expr(), pages.clone() + delta.expr());
+
+        cb.condition(is_grow.clone(), |cb| {
+            cb.stack_lookup(false.expr(), 0.expr(), delta.expr());
+        });
+        cb.condition(not::expr(is_grow.clone()), |cb| {
+            cb.require_zero("memory.size has no delta", delta.expr());
+        });
+        cb.require_equal(
+            "result is the previous size or -1",
+            result.expr(),
+            pages.clone() + exceeds_limit.expr() * (u32::MAX.expr() - pages.clone()),
+        );
+        // `memory.size` pushes, `memory.grow` replaces the delta
+        cb.stack_lookup(true.expr(), is_grow.clone() - 1.expr(), result.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(1.expr() + is_grow.clone()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(is_grow.clone() - 1.expr()),
+            gas_left: Delta(-WASM_GAS_SCHEDULE.memory.expr()),
+            memory_word_size: To(
+                pages + is_grow * not::expr(exceeds_limit.expr()) * delta.expr(),
+            ),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            delta,
+            result,
+            exceeds_limit,
+            max_memory_pages,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let is_grow = step.opcode.unwrap() == OpcodeId::GrowMemory;
+        let delta = if is_grow {
+            block.rws[step.rw_indices[0]].stack_value().as_u64()
+        } else {
+            0
+        };
+        self.delta.assign(region, offset, Value::known(F::from(delta)))?;
+        self.exceeds_limit.assign(
+            region,
+            offset,
+            F::from(self.max_memory_pages),
+            F::from(step.memory_word_size() + delta),
+        )?;
+        let result = block.rws[step.rw_indices[is_grow as usize]].stack_value();
+        self.result.assign(region, offset, Value::known(result.to_scalar().unwrap()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use eth_types::{bytecode, evm_types::MAX_WASM_MEMORY_PAGES, Bytecode};
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    #[test]
+    fn test_memory_size() {
+        run_test(bytecode! {
+            CurrentMemory
+            Drop
+        });
+    }
+
+    #[test]
+    fn test_memory_grow() {
+        run_test(bytecode! {
+            I32Const[2]
+            GrowMemory
+            Drop
+            CurrentMemory
+            Drop
+        });
+    }
+
+    #[test]
+    fn test_memory_grow_above_limit() {
+        run_test(bytecode! {
+            I32Const[MAX_WASM_MEMORY_PAGES]
+            GrowMemory
+            Drop
+        });
+    }
+}
@@ -0,0 +1,242 @@
+use array_init::array_init;
+use halo2_proofs::circuit::Value;
+use halo2_proofs::plonk::Error;
+
+use bus_mapping::wasm::WASM_GAS_SCHEDULE;
+use eth_types::{evm_types::MAX_WASM_RESULTS, Field, ToScalar};
+use gadgets::util::{not, sum};
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            CachedRegion,
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstrainBuilderCommon, StepStateTransition, Transition::Delta, Transition::To,
+            },
+            math_gadget::IsZeroGadget,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+};
+use crate::evm_circuit::util::Cell;
+use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
+
+/// Gadget for `return`. Returning from a called function restores the caller's frame saved by
+/// [`WasmCallGadget`](super::wasm_call::WasmCallGadget), moves the results (up to
+/// [`MAX_WASM_RESULTS`] with multi-value) in place of the params on the caller's stack and continues
+/// at the return program counter. The entry function runs in frame 0 and has no caller frame.
+#[derive(Clone, Debug)]
+pub(crate) struct WasmReturnGadget<F> {
+    same_context: SameContextGadget<F>,
+    program_counter: Cell<F>,
+    frame_id: Cell<F>,
+    is_entry_frame: IsZeroGadget<F>,
+    caller_frame_id: Cell<F>,
+    caller_function_index: Cell<F>,
+    locals_base: Cell<F>,
+    num_params: Cell<F>,
+    num_results: Cell<F>,
+    is_result: [Cell<F>; MAX_WASM_RESULTS],
+    results: [Cell<F>; MAX_WASM_RESULTS],
+}
+
+impl<F: Field> ExecutionGadget<F> for WasmReturnGadget<F> {
+    const NAME: &'static str = "WASM_RETURN";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::WASM_RETURN;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let program_counter = cb.query_cell();
+        let caller_frame_id = cb.query_cell();
+        let caller_function_index = cb.query_cell();
+        let locals_base = cb.query_cell();
+        let num_params = cb.query_cell();
+        let num_results = cb.query_cell();
+        let is_result = array_init(|_| cb.query_bool());
+        let results = array_init(|_| cb.query_cell());
+
+        let frame_id = cb.call_context(None, CallContextFieldTag::FrameId);
+        let is_entry_frame = IsZeroGadget::construct(cb, frame_id.expr());
+        let not_entry_frame = not::expr(is_entry_frame.expr());
+
+        // results are a prefix of the slots
+        for i in 1..MAX_WASM_RESULTS {
+            cb.require_zero(
+                "is_result[i] -> is_result[i-1]",
+                is_result[i].expr() * not::expr(is_result[i - 1].expr()),
+            );
+        }
+        let results_count = sum::expr(&is_result);
+
+        // stack pointer of the caller once the params are replaced by the results
+        let caller_stack_pointer = locals_base.expr() + num_params.expr() - num_results.expr();
+        cb.condition(not_entry_frame.clone(), |cb| {
+            for (field_tag, value) in [
+                (CallContextFieldTag::CallerFrameId, caller_frame_id.expr()),
+                (CallContextFieldTag::CallerFunctionIndex, caller_function_index.expr()),
+                (CallContextFieldTag::ReturnProgramCounter, program_counter.expr()),
+                (CallContextFieldTag::LocalsBase, locals_base.expr()),
+                (CallContextFieldTag::NumParams, num_params.expr()),
+                (CallContextFieldTag::NumResults, num_results.expr()),
+            ] {
+                cb.call_context_lookup(false.expr(), Some(frame_id.expr()), field_tag, value);
+            }
+            for (field_tag, value) in [
+                (CallContextFieldTag::FrameId, caller_frame_id.expr()),
+                (CallContextFieldTag::InternalFunctionId, caller_function_index.expr()),
+            ] {
+                cb.call_context_lookup(true.expr(), None, field_tag, value);
+            }
+
+            cb.require_equal("results count", results_count.clone(), num_results.expr());
+            for (i, (is_result, value)) in is_result.iter().zip(results.iter()).enumerate() {
+                cb.condition(is_result.expr(), |cb| {
+                    cb.stack_lookup(false.expr(), i.expr(), value.expr());
+                });
+            }
+            for (i, (is_result, value)) in is_result.iter().zip(results.iter()).enumerate() {
+                cb.condition(is_result.expr(), |cb| {
+                    cb.stack_lookup(
+                        true.expr(),
+                        caller_stack_pointer.clone() + i.expr() - cb.curr.state.stack_pointer.expr(),
+                        value.expr(),
+                    );
+                });
+            }
+        });
+        cb.condition(is_entry_frame.expr(), |cb| {
+            cb.require_zero("entry function results are not moved", results_count.clone());
+        });
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(
+                1.expr() + not_entry_frame.clone() * 8.expr() + results_count * 2.expr(),
+            ),
+            // TODO: constrain the program counter after returning from the entry function
+            program_counter: To(program_counter.expr()),
+            stack_pointer: To(
+                not_entry_frame * caller_stack_pointer
+                    + is_entry_frame.expr() * cb.curr.state.stack_pointer.expr(),
+            ),
+            gas_left: Delta(-WASM_GAS_SCHEDULE.control.expr()),
+            ..Default::default()
+        };
+
+        let opcode = cb.query_cell();
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            program_counter,
+            frame_id,
+            is_entry_frame,
+            caller_frame_id,
+            caller_function_index,
+            locals_base,
+            num_params,
+            num_results,
+            is_result,
+            results,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let frame_id = block.rws[step.rw_indices[0]].call_context_value().low_u64();
+        self.frame_id.assign(region, offset, Value::known(F::from(frame_id)))?;
+        self.is_entry_frame.assign(region, offset, F::from(frame_id))?;
+        if frame_id == 0 {
+            return Ok(());
+        }
+
+        for (cell, rw_index) in [
+            (&self.caller_frame_id, 1),
+            (&self.caller_function_index, 2),
+            (&self.program_counter, 3),
+            (&self.locals_base, 4),
+            (&self.num_params, 5),
+            (&self.num_results, 6),
+        ] {
+            let value = block.rws[step.rw_indices[rw_index]].call_context_value();
+            cell.assign(region, offset, Value::known(F::from(value.low_u64())))?;
+        }
+        let num_results = block.rws[step.rw_indices[6]].call_context_value().as_usize();
+        for (i, (is_result, value)) in self.is_result.iter().zip(self.results.iter()).enumerate() {
+            is_result.assign(region, offset, Value::known(F::from((i < num_results) as u64)))?;
+            if i < num_results {
+                let result = block.rws[step.rw_indices[9 + i]].stack_value();
+                value.assign(region, offset, Value::known(result.to_scalar().unwrap()))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use wasm_encoder::ValType;
+
+    use eth_types::{bytecode, Bytecode};
+    use mock::test_ctx::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    #[test]
+    fn test_nested_calls() {
+        let mut code = bytecode! {
+            Call[0]
+            Drop
+        };
+        code.new_function(vec![], vec![ValType::I32], bytecode! {
+            Call[1]
+            I32Const[1]
+            I32Add
+            Return
+        }, vec![]);
+        code.new_function(vec![], vec![ValType::I32], bytecode! {
+            I32Const[2]
+            Return
+        }, vec![]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_multi_value_return() {
+        let mut code = bytecode! {
+            I32Const[7]
+            I64Const[8]
+            Call[0]
+            Drop
+            Drop
+            Drop
+        };
+        code.new_function(vec![ValType::I32, ValType::I64], vec![ValType::I64, ValType::I32, ValType::I64], bytecode! {
+            GetLocal[1]
+            GetLocal[0]
+            I64Const[9]
+            Return
+        }, vec![]);
+        run_test(code);
+    }
+}
@@ -0,0 +1,249 @@
+use array_init::array_init;
+use halo2_proofs::circuit::Value;
+use halo2_proofs::plonk::Error;
+
+use bus_mapping::wasm::WASM_GAS_SCHEDULE;
+use eth_types::{evm_types::MAX_WASM_TAIL_CALL_PARAMS, Field, ToScalar};
+use gadgets::util::{not, sum};
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            CachedRegion,
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstrainBuilderCommon, StepStateTransition, Transition::Delta, Transition::To,
+            },
+            math_gadget::IsZeroGadget,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+};
+use crate::evm_circuit::util::Cell;
+use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
+
+/// Gadget for `return_call`. The frame of the current function is replaced instead of pushing a
+/// new one: the frame id and the saved caller's frame are kept, the params of the callee (up to
+/// [`MAX_WASM_TAIL_CALL_PARAMS`]) are moved in place of the params of the replaced function. A
+/// tail call from the entry function keeps frame 0 and the stack as is.
+#[derive(Clone, Debug)]
+pub(crate) struct WasmReturnCallGadget<F> {
+    same_context: SameContextGadget<F>,
+    program_counter: Cell<F>,
+    function_index: Cell<F>,
+    frame_id: Cell<F>,
+    is_entry_frame: IsZeroGadget<F>,
+    locals_base: Cell<F>,
+    replaced_num_params: Cell<F>,
+    num_params: Cell<F>,
+    num_results: Cell<F>,
+    is_param: [Cell<F>; MAX_WASM_TAIL_CALL_PARAMS],
+    params: [Cell<F>; MAX_WASM_TAIL_CALL_PARAMS],
+}
+
+impl<F: Field> ExecutionGadget<F> for WasmReturnCallGadget<F> {
+    const NAME: &'static str = "WASM_RETURN_CALL";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::WASM_RETURN_CALL;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let function_index = cb.query_cell();
+        let program_counter = cb.query_cell();
+        let locals_base = cb.query_cell();
+        let replaced_num_params = cb.query_cell();
+        // TODO: check the arity against the type section of the code
+        let num_params = cb.query_cell();
+        let num_results = cb.query_cell();
+        let is_param = array_init(|_| cb.query_bool());
+        let params = array_init(|_| cb.query_cell());
+
+        let frame_id = cb.call_context(None, CallContextFieldTag::FrameId);
+        let is_entry_frame = IsZeroGadget::construct(cb, frame_id.expr());
+        let not_entry_frame = not::expr(is_entry_frame.expr());
+
+        // params are a prefix of the slots
+        for i in 1..MAX_WASM_TAIL_CALL_PARAMS {
+            cb.require_zero(
+                "is_param[i] -> is_param[i-1]",
+                is_param[i].expr() * not::expr(is_param[i - 1].expr()),
+            );
+        }
+        let params_count = sum::expr(&is_param);
+
+        // the callee's params end where the params of the replaced function ended
+        let callee_locals_base =
+            locals_base.expr() + replaced_num_params.expr() - num_params.expr();
+        cb.condition(not_entry_frame.clone(), |cb| {
+            for (field_tag, value) in [
+                (CallContextFieldTag::LocalsBase, locals_base.expr()),
+                (CallContextFieldTag::NumParams, replaced_num_params.expr()),
+                (CallContextFieldTag::NumResults, num_results.expr()),
+            ] {
+                cb.call_context_lookup(false.expr(), Some(frame_id.expr()), field_tag, value);
+            }
+            for (field_tag, value) in [
+                (CallContextFieldTag::LocalsBase, callee_locals_base.clone()),
+                (CallContextFieldTag::NumParams, num_params.expr()),
+            ] {
+                cb.call_context_lookup(true.expr(), Some(frame_id.expr()), field_tag, value);
+            }
+        });
+        for (field_tag, value) in [
+            (CallContextFieldTag::InternalFunctionId, function_index.expr()),
+            (CallContextFieldTag::ProgramCounter, program_counter.expr()),
+        ] {
+            cb.call_context_lookup(true.expr(), None, field_tag, value);
+        }
+        cb.condition(not_entry_frame.clone(), |cb| {
+            cb.require_equal("params count", params_count.clone(), num_params.expr());
+            for (i, (is_param, value)) in is_param.iter().zip(params.iter()).enumerate() {
+                cb.condition(is_param.expr(), |cb| {
+                    cb.stack_lookup(false.expr(), i.expr(), value.expr());
+                });
+            }
+            for (i, (is_param, value)) in is_param.iter().zip(params.iter()).enumerate() {
+                cb.condition(is_param.expr(), |cb| {
+                    cb.stack_lookup(
+                        true.expr(),
+                        callee_locals_base.clone() + i.expr() - cb.curr.state.stack_pointer.expr(),
+                        value.expr(),
+                    );
+                });
+            }
+        });
+        cb.condition(is_entry_frame.expr(), |cb| {
+            cb.require_zero("entry function params are not moved", params_count.clone());
+        });
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(
+                3.expr() + not_entry_frame.clone() * 5.expr() + params_count * 2.expr(),
+            ),
+            program_counter: To(program_counter.expr()),
+            stack_pointer: To(
+                not_entry_frame * callee_locals_base
+                    + is_entry_frame.expr() * cb.curr.state.stack_pointer.expr(),
+            ),
+            gas_left: Delta(-WASM_GAS_SCHEDULE.call.expr()),
+            ..Default::default()
+        };
+
+        let opcode = cb.query_cell();
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            program_counter,
+            function_index,
+            frame_id,
+            is_entry_frame,
+            locals_base,
+            replaced_num_params,
+            num_params,
+            num_results,
+            is_param,
+            params,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let frame_id = block.rws[step.rw_indices[0]].call_context_value().low_u64();
+        self.frame_id.assign(region, offset, Value::known(F::from(frame_id)))?;
+        self.is_entry_frame.assign(region, offset, F::from(frame_id))?;
+
+        let mut cells = vec![];
+        if frame_id != 0 {
+            cells.extend([
+                (&self.locals_base, 1),
+                (&self.replaced_num_params, 2),
+                (&self.num_results, 3),
+                (&self.num_params, 5),
+            ]);
+        }
+        let rw_offset = if frame_id != 0 { 6 } else { 1 };
+        cells.extend([
+            (&self.function_index, rw_offset),
+            (&self.program_counter, rw_offset + 1),
+        ]);
+        for (cell, rw_index) in cells {
+            let value = block.rws[step.rw_indices[rw_index]].call_context_value();
+            cell.assign(region, offset, Value::known(F::from(value.low_u64())))?;
+        }
+        if frame_id == 0 {
+            return Ok(());
+        }
+
+        let num_params = block.rws[step.rw_indices[5]].call_context_value().as_usize();
+        for (i, (is_param, value)) in self.is_param.iter().zip(self.params.iter()).enumerate() {
+            is_param.assign(region, offset, Value::known(F::from((i < num_params) as u64)))?;
+            if i < num_params {
+                let param = block.rws[step.rw_indices[8 + i]].stack_value();
+                value.assign(region, offset, Value::known(param.to_scalar().unwrap()))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use wasm_encoder::ValType;
+
+    use eth_types::{bytecode, Bytecode};
+    use mock::test_ctx::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    #[test]
+    fn test_tail_call_from_entry_function() {
+        let mut code = bytecode! {
+            ReturnCall[0]
+        };
+        code.new_function(vec![], vec![], bytecode! {
+            Return
+        }, vec![]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_tail_call_moves_params() {
+        let mut code = bytecode! {
+            I32Const[5]
+            Call[0]
+            Drop
+        };
+        code.new_function(vec![ValType::I32], vec![ValType::I32], bytecode! {
+            GetLocal[0]
+            I32Const[1]
+            ReturnCall[1]
+        }, vec![]);
+        code.new_function(vec![ValType::I32, ValType::I32], vec![ValType::I32], bytecode! {
+            GetLocal[0]
+            GetLocal[1]
+            I32Add
+            Return
+        }, vec![]);
+        run_test(code);
+    }
+}
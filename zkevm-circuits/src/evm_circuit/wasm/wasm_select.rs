@@ -1,7 +1,8 @@
-use halo2_proofs::plonk::{Error};
+use halo2_proofs::circuit::Value;
+use halo2_proofs::plonk::Error;
 
-use bus_mapping::evm::OpcodeId;
-use eth_types::{Field};
+use bus_mapping::wasm::WASM_GAS_SCHEDULE;
+use eth_types::{Field, ToScalar};
 
 use crate::{
     evm_circuit::{
@@ -11,6 +12,7 @@ use crate::{
             CachedRegion,
             common_gadget::SameContextGadget,
             constraint_builder::{StepStateTransition, Transition::Delta},
+            math_gadget::IsZeroGadget,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -19,15 +21,14 @@ use crate::{
 use crate::evm_circuit::util::Cell;
 use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
 
+/// `select` pops the condition only. When it is non-zero the first value already sits in the
+/// result slot, otherwise the second value is read and written over it.
 #[derive(Clone, Debug)]
 pub(crate) struct WasmSelectGadget<F> {
     same_context: SameContextGadget<F>,
     cond: Cell<F>,
-    cond_inv: Cell<F>,
-    val1: Cell<F>,
+    cond_is_zero: IsZeroGadget<F>,
     val2: Cell<F>,
-    res: Cell<F>,
-    vtype: Cell<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for WasmSelectGadget<F> {
@@ -37,34 +38,24 @@ impl<F: Field> ExecutionGadget<F> for WasmSelectGadget<F> {
 
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let cond = cb.alloc_u64_on_u8();
-        let cond_inv = cb.alloc_unlimited_value();
-        let val1 = cb.alloc_u64();
         let val2 = cb.alloc_u64();
-        let res = cb.alloc_u64();
-        let vtype = cb.alloc_common_range_value();
 
         cb.stack_pop(cond.expr());
-        cb.stack_pop(val2.expr());
-        cb.stack_pop(val1.expr());
-        cb.stack_push(res.expr());
+        let cond_is_zero = IsZeroGadget::construct(cb, cond.expr());
 
-        cb.require_zeros("op_select: cond is zero", vec![
-            (1.expr() - cond.expr() * cond_inv.expr())
-                * (res.expr() - val2.expr()),
-        ]);
-
-        cb.require_zeros("op_select: cond is not zero", vec![
-            cond.expr() * (res.expr() - val1.expr())
-        ]);
+        cb.condition(cond_is_zero.expr(), |cb| {
+            cb.stack_lookup(false.expr(), 1.expr(), val2.expr());
+            cb.stack_lookup(true.expr(), 2.expr(), val2.expr());
+        });
 
         let opcode = cb.query_cell();
 
         // State transition
         let step_state_transition = StepStateTransition {
-            rw_counter: Delta(4.expr()),
+            rw_counter: Delta(cb.rw_counter_offset()),
             program_counter: Delta(1.expr()),
-            stack_pointer: Delta(0.expr()),
-            gas_left: Delta(-OpcodeId::Select.constant_gas_cost().expr()),
+            stack_pointer: Delta(2.expr()),
+            gas_left: Delta(-WASM_GAS_SCHEDULE.parametric.expr()),
             ..StepStateTransition::default()
         };
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
@@ -72,11 +63,8 @@ impl<F: Field> ExecutionGadget<F> for WasmSelectGadget<F> {
         Self {
             same_context,
             cond,
-            cond_inv,
-            val1,
+            cond_is_zero,
             val2,
-            res,
-            vtype,
         }
     }
 
@@ -89,44 +77,16 @@ impl<F: Field> ExecutionGadget<F> for WasmSelectGadget<F> {
         _call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
-        use eth_types::ToScalar;
-        use halo2_proofs::circuit::Value;
-
         self.same_context.assign_exec_step(region, offset, step)?;
 
-        let opcode = step.opcode.unwrap();
-
-        let [cond, val2, val1, res] = [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2], step.rw_indices[3]]
-            .map(|idx| block.rws[idx].stack_value());
-
-        self.cond.assign(region, offset, Value::known(cond.to_scalar().unwrap()))?;
-        self.cond_inv.assign(region, offset, Value::known(F::from(cond.as_u64()).invert().unwrap_or(F::zero())))?;
-        self.val2.assign(region, offset, Value::known(val2.to_scalar().unwrap()))?;
-        self.val1.assign(region, offset, Value::known(val1.to_scalar().unwrap()))?;
-        self.res.assign(region, offset, Value::known(res.to_scalar().unwrap()))?;
-
-/*
-        self.value.assign(region, offset, Value::known(value.to_scalar().unwrap()))?;
-        self.value_inv.assign(region, offset, Value::known(F::from(value.as_u64()).invert().unwrap_or(F::zero())))?;
-        self.res.assign(region, offset, Value::known(res.to_scalar().unwrap()))?;
-
-        match opcode {
-            OpcodeId::I64Eqz => {
-                let zero_or_one = (value.as_u64() == 0) as u64;
-                self.res.assign(region, offset, Value::known(F::from(zero_or_one)))?;
-            }
-            OpcodeId::I32Eqz => {
-                let zero_or_one = (value.as_u32() == 0) as u64;
-                self.res.assign(region, offset, Value::known(F::from(zero_or_one)))?;
-            }
-            _ => unreachable!("not supported opcode: {:?}", opcode),
-        };
- 
-        let is_i64 = matches!(opcode,
-            OpcodeId::I64Eqz
-        );
-        self.is_i64.assign(region, offset, Value::known(F::from(is_i64 as u64)))?;
-*/
+        let cond = block.rws[step.rw_indices[0]].stack_value();
+        let cond = cond.to_scalar().unwrap();
+        self.cond.assign(region, offset, Value::known(cond))?;
+        self.cond_is_zero.assign(region, offset, cond)?;
+        if cond.is_zero_vartime() {
+            let val2 = block.rws[step.rw_indices[1]].stack_value();
+            self.val2.assign(region, offset, Value::known(val2.to_scalar().unwrap()))?;
+        }
 
         Ok(())
     }
@@ -156,6 +116,17 @@ mod test {
         });
     }
 
+    #[test]
+    fn test_select_nonzero_cond() {
+        run_test(bytecode! {
+            I32Const[1]
+            I32Const[2]
+            I32Const[7]
+            Select
+            Drop
+        });
+    }
+
     #[test]
     fn test_select_i64() {
         run_test(bytecode! {
@@ -166,4 +137,22 @@ mod test {
             Drop
         });
     }
+
+    /// `gas_left` of every step is constrained by `WASM_GAS_SCHEDULE`, so the trace has to charge
+    /// the same costs for all opcode families
+    #[test]
+    fn test_gas_schedule_matches_trace() {
+        run_test(bytecode! {
+            I32Const[7]
+            I32Const[5]
+            I32Add
+            I32Eqz
+            I32Const[1]
+            I32Const[2]
+            I32Const[0]
+            Select
+            Drop
+            Drop
+        });
+    }
 }
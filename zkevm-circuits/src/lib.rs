@@ -42,6 +42,8 @@ pub mod state_circuit;
 pub mod super_circuit;
 pub mod table;
 
+#[cfg(any(feature = "test", test))]
+pub mod test_rng;
 #[cfg(any(feature = "test", test))]
 pub mod test_util;
 
@@ -18,8 +18,14 @@ use self::{
     lexicographic_ordering::LimbIndex,
 };
 use crate::{
-    evm_circuit::{param::N_BYTES_WORD, util::rlc},
-    table::{AccountFieldTag, LookupTable, MPTProofType, MptTable, RwTable, RwTableTag},
+    evm_circuit::{
+        param::{N_BYTES_WORD, STACK_CAPACITY},
+        util::rlc,
+    },
+    table::{
+        AccountFieldTag, CallContextFieldTag, LookupTable, MPTProofType, MptTable, RwTable,
+        RwTableTag,
+    },
     util::{Challenges, Expr, SubCircuit, SubCircuitConfig},
     witness::{self, MptUpdates, Rw, RwMap},
 };
@@ -39,16 +45,13 @@ use lookups::{Chip as LookupsChip, Config as LookupsConfig, Queries as LookupsQu
 use multiple_precision_integer::{Chip as MpiChip, Config as MpiConfig, Queries as MpiQueries};
 use param::*;
 use random_linear_combination::{Chip as RlcChip, Config as RlcConfig, Queries as RlcQueries};
-use std::marker::PhantomData;
+use std::{collections::HashMap, marker::PhantomData};
 
 #[cfg(feature = "onephase")]
 use halo2_proofs::plonk::FirstPhase as SecondPhase;
 #[cfg(not(feature = "onephase"))]
 use halo2_proofs::plonk::SecondPhase;
 
-#[cfg(any(feature = "test", test, feature = "test-circuits"))]
-use std::collections::HashMap;
-
 /// Config for StateCircuit
 #[derive(Clone)]
 pub struct StateCircuitConfig<F> {
@@ -67,6 +70,11 @@ pub struct StateCircuitConfig<F> {
     is_non_exist: BatchedIsZeroConfig,
     // Intermediary witness used to reduce mpt lookup expression degree
     mpt_proof_type: Column<Advice>,
+    // For Rw::Stack, 1 if the address is a local of the entry function of the call. BeginTx
+    // reserves the locals without writing them, so they can be read before they are written.
+    is_local: Column<Advice>,
+    // For local Rw::Stack rows, the NumLocals of the call, 0 otherwise.
+    num_locals: Column<Advice>,
     state_root: Column<Advice>,
     lexicographic_ordering: LexicographicOrderingConfig,
     not_first_access: Column<Advice>,
@@ -168,6 +176,8 @@ impl<F: Field> SubCircuitConfig<F> for StateCircuitConfig<F> {
             initial_value,
             is_non_exist,
             mpt_proof_type,
+            is_local: meta.advice_column(),
+            num_locals: meta.advice_column(),
             state_root,
             lexicographic_ordering,
             not_first_access: meta.advice_column(),
@@ -233,6 +243,18 @@ impl<F: Field> StateCircuitConfig<F> {
             padding_length
         );
         let rows_len = rows.len();
+        let num_locals: HashMap<_, _> = rows
+            .iter()
+            .filter_map(|row| match row {
+                Rw::CallContext {
+                    call_id,
+                    field_tag: CallContextFieldTag::NumLocals,
+                    value,
+                    ..
+                } => Some((*call_id, value.low_u64())),
+                _ => None,
+            })
+            .collect();
 
         let mut state_root =
             randomness.map(|randomness| rlc::value(&updates.old_root().to_le_bytes(), randomness));
@@ -371,6 +393,34 @@ impl<F: Field> StateCircuitConfig<F> {
                 || mpt_proof_type,
             )?;
 
+            // The locals of the entry function are the NumLocals slots at the bottom of the stack.
+            let local_num_locals = match row {
+                Rw::Stack {
+                    call_id,
+                    stack_pointer,
+                    ..
+                } => num_locals
+                    .get(call_id)
+                    .copied()
+                    .filter(|num_locals| {
+                        (*stack_pointer as u64) + num_locals >= STACK_CAPACITY as u64
+                    })
+                    .unwrap_or_default(),
+                _ => 0,
+            };
+            region.assign_advice(
+                || "is_local",
+                self.is_local,
+                offset,
+                || Value::known(F::from((local_num_locals != 0) as u64)),
+            )?;
+            region.assign_advice(
+                || "num_locals",
+                self.num_locals,
+                offset,
+                || Value::known(F::from(local_num_locals)),
+            )?;
+
             // TODO: Switch from Rw::Start -> Rw::Padding to simplify this logic.
             // State root assignment is at previous row (offset - 1) because the state root
             // changes on the last access row.
@@ -428,6 +478,8 @@ impl<F: Field> StateCircuitConfig<F> {
         region.name_column(|| "STATE_not_first_access", self.not_first_access);
         region.name_column(|| "STATE_phase2_initial_value", self.initial_value);
         region.name_column(|| "STATE_phase2_mpt_proof_type", self.mpt_proof_type);
+        region.name_column(|| "STATE_is_local", self.is_local);
+        region.name_column(|| "STATE_num_locals", self.num_locals);
         region.name_column(|| "STATE_phase2_state_root", self.state_root);
     }
 }
@@ -600,8 +652,6 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
 
 fn queries<F: Field>(meta: &mut VirtualCells<'_, F>, c: &StateCircuitConfig<F>) -> Queries<F> {
     let first_different_limb = c.lexicographic_ordering.first_different_limb;
-    let final_bits_sum = meta.query_advice(first_different_limb.bits[3], Rotation::cur())
-        + meta.query_advice(first_different_limb.bits[4], Rotation::cur());
 
     Queries {
         selector: meta.query_fixed(c.selector, Rotation::cur()),
@@ -640,21 +690,19 @@ fn queries<F: Field>(meta: &mut VirtualCells<'_, F>, c: &StateCircuitConfig<F>)
             .bits
             .map(|bit| meta.query_advice(bit, Rotation::cur())),
         id: MpiQueries::new(meta, c.sort_keys.id),
-        // this isn't binary! only 0 if most significant 3 bits are all 0 and at most 1 of the two
-        // least significant bits is 1.
-        // TODO: this can mask off just the top 3 bits if you want, since the 4th limb index is
-        // Address9, which is always 0 for Rw::Stack rows.
-        is_tag_and_id_unchanged: 4.expr()
-            * (meta.query_advice(first_different_limb.bits[0], Rotation::cur())
-                + meta.query_advice(first_different_limb.bits[1], Rotation::cur())
-                + meta.query_advice(first_different_limb.bits[2], Rotation::cur()))
-            + final_bits_sum.clone() * (1.expr() - final_bits_sum),
+        // this isn't binary! only 0 if the most significant 3 bits are all 0, i.e. the first
+        // different limb is Tag, Id1, Id0 or Address9. Address9 is always 0 for Rw::Stack rows.
+        is_tag_and_id_unchanged: meta.query_advice(first_different_limb.bits[0], Rotation::cur())
+            + meta.query_advice(first_different_limb.bits[1], Rotation::cur())
+            + meta.query_advice(first_different_limb.bits[2], Rotation::cur()),
         address: MpiQueries::new(meta, c.sort_keys.address),
         storage_key: RlcQueries::new(meta, c.sort_keys.storage_key),
         initial_value: meta.query_advice(c.initial_value, Rotation::cur()),
         initial_value_prev: meta.query_advice(c.initial_value, Rotation::prev()),
         is_non_exist: meta.query_advice(c.is_non_exist.is_zero, Rotation::cur()),
         mpt_proof_type: meta.query_advice(c.mpt_proof_type, Rotation::cur()),
+        is_local: meta.query_advice(c.is_local, Rotation::cur()),
+        num_locals: meta.query_advice(c.num_locals, Rotation::cur()),
         lookups: LookupsQueries::new(meta, c.lookups),
         power_of_randomness: c.power_of_randomness.clone(),
         first_different_limb: [0, 1, 2, 3]
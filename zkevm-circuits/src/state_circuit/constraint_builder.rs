@@ -3,8 +3,11 @@ use super::{
     param::*, random_linear_combination::Queries as RlcQueries,
 };
 use crate::{
-    evm_circuit::{param::N_BYTES_WORD, util::not},
-    table::{MPTProofType as ProofType, RwTableTag},
+    evm_circuit::{
+        param::{N_BYTES_WORD, STACK_CAPACITY},
+        util::not,
+    },
+    table::{CallContextFieldTag, MPTProofType as ProofType, RwTableTag},
     util::Expr,
 };
 use eth_types::Field;
@@ -58,6 +61,8 @@ pub struct Queries<F: Field> {
     pub initial_value_prev: Expression<F>,
     pub is_non_exist: Expression<F>,
     pub mpt_proof_type: Expression<F>,
+    pub is_local: Expression<F>,
+    pub num_locals: Expression<F>,
     pub lookups: LookupsQueries<F>,
     pub power_of_randomness: [Expression<F>; N_BYTES_WORD - 1],
     pub first_different_limb: [Expression<F>; 4],
@@ -108,8 +113,13 @@ impl<F: Field> ConstraintBuilder<F> {
         self.condition(q.tag_matches(RwTableTag::Stack), |cb| {
             cb.build_stack_constraints(q)
         });
+        // is_local only relaxes the Stack constraints, so binding it doesn't depend on the tag
+        self.condition(q.is_local(), |cb| cb.build_stack_local_lookups(q));
         self.condition(q.tag_matches(RwTableTag::Global), |cb| {
-            cb.build_memory_constraints(q)
+            cb.build_global_constraints(q)
+        });
+        self.condition(q.tag_matches(RwTableTag::Table), |cb| {
+            cb.build_table_constraints(q)
         });
         self.condition(q.tag_matches(RwTableTag::AccountStorage), |cb| {
             cb.build_account_storage_constraints(q)
@@ -133,6 +143,9 @@ impl<F: Field> ConstraintBuilder<F> {
         self.condition(q.tag_matches(RwTableTag::TxLog), |cb| {
             cb.build_tx_log_constraints(q)
         });
+        self.condition(q.tag_matches(RwTableTag::TransientStorage), |cb| {
+            cb.build_transient_storage_constraints(q)
+        });
     }
 
     fn build_general_constraints(&mut self, q: &Queries<F>) {
@@ -258,18 +271,23 @@ impl<F: Field> ConstraintBuilder<F> {
         // 3.0. Unused keys are 0
         self.require_zero("field_tag is 0 for Stack", q.field_tag());
         self.require_zero("storage_key is 0 for Stack", q.rw_table.storage_key.clone());
-        // 3.1. First access for a set of all keys
+        // 3.1. First access for a set of all keys. The locals of the entry function are reserved
+        // by BeginTx without writing their slots, the first access to one of them can be a read,
+        // which returns the initial value 0 by 3.4.
+        self.require_boolean("is_local is boolean", q.is_local());
         self.require_zero(
             "first access to new stack address is a write",
-            q.first_access() * (1.expr() - q.is_write()),
+            q.first_access() * q.is_read() * (1.expr() - q.is_local()),
         );
         // 3.2. stack_ptr in range
         self.add_lookup(
             "stack address fits into 10 bits",
             vec![(q.rw_table.address.clone(), q.lookups.u10.clone())],
         );
-        // 3.3. stack_ptr only increases by 0 or 1
-        self.condition(q.is_tag_and_id_unchanged.clone(), |cb| {
+        // 3.3. stack_ptr only increases by 0 or 1, except for locals: the locals which are never
+        // accessed leave gaps at the bottom of the stack.
+        let is_not_local = 1.expr() - q.is_local();
+        self.condition(q.is_tag_and_id_unchanged.clone() * is_not_local, |cb| {
             cb.require_boolean(
                 "if previous row is also Stack with unchanged call id, address change is 0 or 1",
                 q.address_change(),
@@ -290,6 +308,81 @@ impl<F: Field> ConstraintBuilder<F> {
         );
     }
 
+    fn build_stack_local_lookups(&mut self, q: &Queries<F>) {
+        // 3.6. A local is one of the NumLocals slots at the bottom of the stack of the call
+        self.add_lookup(
+            "local is in the NumLocals of the call",
+            vec![
+                (RwTableTag::CallContext.expr(), q.rw_table.tag.clone()),
+                (q.rw_table.id.clone(), q.rw_table.id.clone()),
+                (
+                    CallContextFieldTag::NumLocals.expr(),
+                    q.rw_table.field_tag.clone(),
+                ),
+                (q.num_locals(), q.rw_table.value.clone()),
+            ],
+        );
+        self.add_lookup(
+            "local address is at the bottom of the stack",
+            vec![(
+                q.rw_table.address.clone() + q.num_locals() - STACK_CAPACITY.expr(),
+                q.lookups.u10.clone(),
+            )],
+        );
+    }
+
+    fn build_global_constraints(&mut self, q: &Queries<F>) {
+        // 3a.0. Unused keys are 0
+        self.require_zero("field_tag is 0 for Global", q.field_tag());
+        self.require_zero("storage_key is 0 for Global", q.rw_table.storage_key.clone());
+        // 3a.1. global index in range
+        for limb in &q.address.limbs[2..] {
+            self.require_zero("global index fits into 2 limbs", limb.clone());
+        }
+        // 3a.2. Global initial value is 0, BeginTx writes the init values of the global section
+        self.require_zero("initial Global value is 0", q.initial_value());
+        // 3a.3. state root does not change
+        self.require_equal(
+            "state_root is unchanged for Global",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+        // 3a.4. value_prev is the value of the previous access to the same global
+        self.condition(q.not_first_access.clone(), |cb| {
+            cb.require_equal(
+                "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+                q.rw_table.value_prev.clone(),
+                q.value_prev_column(),
+            );
+        });
+    }
+
+    fn build_table_constraints(&mut self, q: &Queries<F>) {
+        // 3b.0. Unused keys are 0, field_tag is the table index
+        self.require_zero("storage_key is 0 for Table", q.rw_table.storage_key.clone());
+        // 3b.1. element index in range
+        for limb in &q.address.limbs[2..] {
+            self.require_zero("table element index fits into 2 limbs", limb.clone());
+        }
+        // 3b.2. Table elements start as the null reference, BeginTx writes the active element
+        // segments
+        self.require_zero("initial Table value is 0", q.initial_value());
+        // 3b.3. state root does not change
+        self.require_equal(
+            "state_root is unchanged for Table",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+        // 3b.4. value_prev is the value of the previous access to the same element
+        self.condition(q.not_first_access.clone(), |cb| {
+            cb.require_equal(
+                "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+                q.rw_table.value_prev.clone(),
+                q.value_prev_column(),
+            );
+        });
+    }
+
     fn build_account_storage_constraints(&mut self, q: &Queries<F>) {
         // TODO: cold VS warm
         // ref. spec 4.0. Unused keys are 0
@@ -416,6 +509,25 @@ impl<F: Field> ConstraintBuilder<F> {
         self.require_zero("initial TxRefund value is 0", q.initial_value());
     }
 
+    fn build_transient_storage_constraints(&mut self, q: &Queries<F>) {
+        self.require_zero("field_tag is 0 for TransientStorage", q.field_tag());
+        // transient storage is not part of the state trie
+        self.require_zero("initial TransientStorage value is 0", q.initial_value());
+        self.require_equal(
+            "state_root is unchanged for TransientStorage",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+
+        self.condition(q.not_first_access.clone(), |cb| {
+            cb.require_equal(
+                "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+                q.rw_table.value_prev.clone(),
+                q.value_prev_column(),
+            );
+        });
+    }
+
     fn build_account_constraints(&mut self, q: &Queries<F>) {
         // ref. spec 6.0. Unused keys are 0
         self.require_zero("id is 0 for Account", q.id());
@@ -616,6 +728,14 @@ impl<F: Field> Queries<F> {
         self.mpt_proof_type.clone()
     }
 
+    fn is_local(&self) -> Expression<F> {
+        self.is_local.clone()
+    }
+
+    fn num_locals(&self) -> Expression<F> {
+        self.num_locals.clone()
+    }
+
     fn tag_matches(&self, tag: RwTableTag) -> Expression<F> {
         BinaryNumberConfig::<RwTableTag, 4>::value_equals_expr(tag, self.tag_bits.clone())
     }
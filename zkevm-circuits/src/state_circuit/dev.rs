@@ -87,6 +87,8 @@ pub enum AdviceColumn {
     // NonEmptyWitness is the BatchedIsZero chip witness that contains the
     // inverse of the non-zero value if any in [committed_value, value]
     NonEmptyWitness,
+    IsLocal,
+    NumLocals,
 }
 
 impl AdviceColumn {
@@ -117,6 +119,8 @@ impl AdviceColumn {
             Self::InitialValue => config.initial_value,
             Self::IsZero => config.is_non_exist.is_zero,
             Self::NonEmptyWitness => config.is_non_exist.nonempty_witness,
+            Self::IsLocal => config.is_local,
+            Self::NumLocals => config.num_locals,
         }
     }
 }
@@ -783,6 +783,130 @@ fn stack_read_before_write() {
     assert_error_matches(verify(rows), "first access to new stack address is a write");
 }
 
+fn num_locals_row(call_id: usize, num_locals: u64) -> Rw {
+    Rw::CallContext {
+        rw_counter: 1,
+        is_write: true,
+        call_id,
+        field_tag: CallContextFieldTag::NumLocals,
+        value: U256::from(num_locals),
+    }
+}
+
+#[test]
+fn local_read_before_write() {
+    let rows = vec![
+        num_locals_row(3, 2),
+        Rw::Stack {
+            rw_counter: 9,
+            is_write: false,
+            call_id: 3,
+            stack_pointer: 1022,
+            value: U64::zero(),
+            local_index: 1,
+        },
+    ];
+
+    assert_eq!(verify(rows), Ok(()));
+}
+
+#[test]
+fn nonzero_local_read_before_write() {
+    let rows = vec![
+        num_locals_row(3, 2),
+        Rw::Stack {
+            rw_counter: 9,
+            is_write: false,
+            call_id: 3,
+            stack_pointer: 1022,
+            value: U64::from(10),
+            local_index: 1,
+        },
+    ];
+
+    assert_error_matches(verify(rows), "first access reads don't change value");
+}
+
+#[test]
+fn stack_read_before_write_below_locals() {
+    let rows = vec![
+        num_locals_row(3, 2),
+        Rw::Stack {
+            rw_counter: 9,
+            is_write: false,
+            call_id: 3,
+            stack_pointer: 1021,
+            value: U64::zero(),
+            local_index: 0,
+        },
+    ];
+
+    assert_error_matches(verify(rows), "first access to new stack address is a write");
+}
+
+#[test]
+fn local_read_before_write_of_another_call() {
+    let rows = vec![
+        num_locals_row(2, 2),
+        Rw::Stack {
+            rw_counter: 9,
+            is_write: false,
+            call_id: 3,
+            stack_pointer: 1022,
+            value: U64::zero(),
+            local_index: 1,
+        },
+    ];
+
+    assert_error_matches(verify(rows), "first access to new stack address is a write");
+}
+
+#[test]
+fn claimed_local_below_locals() {
+    let rows = vec![
+        num_locals_row(3, 2),
+        Rw::Stack {
+            rw_counter: 9,
+            is_write: true,
+            call_id: 3,
+            stack_pointer: 1021,
+            value: U64::zero(),
+            local_index: 0,
+        },
+    ];
+    let overrides = HashMap::from([
+        ((AdviceColumn::IsLocal, 0), Fr::one()),
+        ((AdviceColumn::NumLocals, 0), Fr::from(2)),
+    ]);
+
+    let result = verify_with_overrides(rows, overrides);
+
+    assert_error_matches(result, "local address is at the bottom of the stack");
+}
+
+#[test]
+fn claimed_local_with_wrong_num_locals() {
+    let rows = vec![
+        num_locals_row(3, 2),
+        Rw::Stack {
+            rw_counter: 9,
+            is_write: true,
+            call_id: 3,
+            stack_pointer: 1021,
+            value: U64::zero(),
+            local_index: 0,
+        },
+    ];
+    let overrides = HashMap::from([
+        ((AdviceColumn::IsLocal, 0), Fr::one()),
+        ((AdviceColumn::NumLocals, 0), Fr::from(3)),
+    ]);
+
+    let result = verify_with_overrides(rows, overrides);
+
+    assert_error_matches(result, "local is in the NumLocals of the call");
+}
+
 #[test]
 fn invalid_stack_address() {
     let rows = vec![Rw::Stack {
@@ -824,6 +948,153 @@ fn invalid_stack_address_change() {
     );
 }
 
+#[test]
+fn stack_address_gap_for_unused_locals() {
+    let rows = vec![
+        num_locals_row(3, 4),
+        Rw::Stack {
+            rw_counter: 9,
+            is_write: true,
+            call_id: 3,
+            stack_pointer: 1019,
+            value: U64::from(10),
+            local_index: 0,
+        },
+        Rw::Stack {
+            rw_counter: 13,
+            is_write: false,
+            call_id: 3,
+            stack_pointer: 1021,
+            value: U64::zero(),
+            local_index: 1,
+        },
+        Rw::Stack {
+            rw_counter: 14,
+            is_write: true,
+            call_id: 3,
+            stack_pointer: 1023,
+            value: U64::from(20),
+            local_index: 3,
+        },
+    ];
+
+    assert_eq!(verify(rows), Ok(()));
+}
+
+#[test]
+fn global_write_then_read() {
+    let rows = vec![
+        Rw::Global {
+            rw_counter: 9,
+            is_write: true,
+            call_id: 3,
+            global_index: 1,
+            value: U64::from(0x1234),
+            value_prev: U64::zero(),
+        },
+        Rw::Global {
+            rw_counter: 13,
+            is_write: true,
+            call_id: 3,
+            global_index: 1,
+            value: U64::from(7),
+            value_prev: U64::from(0x1234),
+        },
+        Rw::Global {
+            rw_counter: 14,
+            is_write: false,
+            call_id: 3,
+            global_index: 1,
+            value: U64::from(7),
+            value_prev: U64::from(7),
+        },
+    ];
+
+    assert_eq!(verify(rows), Ok(()));
+}
+
+#[test]
+fn global_write_with_wrong_value_prev() {
+    let rows = vec![
+        Rw::Global {
+            rw_counter: 9,
+            is_write: true,
+            call_id: 3,
+            global_index: 1,
+            value: U64::from(10),
+            value_prev: U64::zero(),
+        },
+        Rw::Global {
+            rw_counter: 13,
+            is_write: true,
+            call_id: 3,
+            global_index: 1,
+            value: U64::from(7),
+            value_prev: U64::from(11),
+        },
+    ];
+
+    assert_error_matches(
+        verify(rows),
+        "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+    );
+}
+
+#[test]
+fn nonzero_global_read_before_write() {
+    let rows = vec![Rw::Global {
+        rw_counter: 9,
+        is_write: false,
+        call_id: 3,
+        global_index: 1,
+        value: U64::from(10),
+        value_prev: U64::from(10),
+    }];
+
+    assert_error_matches(verify(rows), "first access reads don't change value");
+}
+
+#[test]
+fn table_write_then_read() {
+    let rows = vec![
+        Rw::Table {
+            rw_counter: 9,
+            is_write: true,
+            call_id: 3,
+            table_index: 0,
+            elem_index: 2,
+            value: U64::from(6),
+            value_prev: U64::zero(),
+        },
+        Rw::Table {
+            rw_counter: 13,
+            is_write: false,
+            call_id: 3,
+            table_index: 0,
+            elem_index: 2,
+            value: U64::from(6),
+            value_prev: U64::from(6),
+        },
+    ];
+
+    assert_eq!(verify(rows), Ok(()));
+}
+
+#[test]
+fn nonnull_table_read_before_write() {
+    let rows = vec![Rw::Table {
+        rw_counter: 9,
+        is_write: false,
+        call_id: 3,
+        table_index: 0,
+        elem_index: 2,
+        value: U64::from(6),
+        value_prev: U64::from(6),
+    }];
+
+    assert_error_matches(verify(rows), "first access reads don't change value");
+}
+
 #[test]
 fn invalid_tags() {
     let first_row_offset = -isize::try_from(N_ROWS).unwrap();
@@ -16,6 +16,7 @@
 //! - [ ] Keccak Circuit
 //! - [ ] MPT Circuit
 //! - [x] PublicInputs Circuit
+//! - [x] Wasm Circuit
 //!
 //! And the following shared tables, with the circuits that use them:
 //!
@@ -49,6 +50,9 @@
 //!   - [x] Bytecode Circuit
 //!   - [x] Tx Circuit
 //!   - [ ] MPT Circuit
+//! - [x] Wasm Function Table
+//!   - [x] Wasm Circuit
+//!   - [x] EVM Circuit
 
 #[cfg(any(feature = "test", test))]
 pub(crate) mod test;
@@ -83,13 +87,17 @@ use crate::{
     state_circuit::{StateCircuit, StateCircuitConfig, StateCircuitConfigArgs},
     table::{
         BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, MptTable, PoseidonTable,
-        RlpFsmRlpTable as RlpTable, RwTable, TxTable,
+        RlpFsmRlpTable as RlpTable, RwTable, TxTable, WasmFunctionTable,
+    },
+    wasm_circuit::{
+        builder::WasmCircuitOptions,
+        circuit::{WasmCircuit, WasmCircuitConfig, WasmCircuitConfigArgs},
     },
 };
 
 use crate::util::circuit_stats;
 use bus_mapping::{
-    circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
+    circuit_input_builder::{CircuitInputBuilder, CircuitsParams, FeeModel},
     mock::BlockData,
 };
 use eth_types::{geth_types::GethData, Field};
@@ -127,6 +135,7 @@ pub struct SuperCircuitConfig<F: Field> {
     pi_circuit: PiCircuitConfig<F>,
     exp_circuit: ExpCircuitConfig<F>,
     rlp_circuit: RlpCircuitConfig<F>,
+    wasm_circuit: WasmCircuitConfig<F>,
     /// Mpt Circuit
     #[cfg(feature = "zktrie")]
     mpt_circuit: MptCircuitConfig,
@@ -187,6 +196,8 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
         log_circuit_info(meta, "rlp table");
         let keccak_table = KeccakTable::construct(meta);
         log_circuit_info(meta, "keccak table");
+        let wasm_function_table = WasmFunctionTable::construct(meta);
+        log_circuit_info(meta, "wasm function table");
 
         let keccak_circuit = KeccakCircuitConfig::new(
             meta,
@@ -309,10 +320,21 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                 copy_table,
                 keccak_table,
                 exp_table,
+                wasm_function_table,
+                fee_model: FeeModel::default(),
+                max_memory_pages: WasmCircuitOptions::default().max_memory_pages(),
             },
         );
         log_circuit_info(meta, "evm circuit");
 
+        let wasm_circuit = WasmCircuitConfig::new(
+            meta,
+            WasmCircuitConfigArgs {
+                wasm_function_table: Some(wasm_function_table),
+            },
+        );
+        log_circuit_info(meta, "wasm circuit");
+
         #[cfg(feature = "onephase")]
         if meta.max_phase() != 0 {
             log::warn!("max_phase: {}", meta.max_phase());
@@ -334,6 +356,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             rlp_circuit,
             tx_circuit,
             exp_circuit,
+            wasm_circuit,
             #[cfg(feature = "zktrie")]
             mpt_circuit,
         }
@@ -369,6 +392,8 @@ pub struct SuperCircuit<
     pub poseidon_circuit: PoseidonCircuit<F>,
     /// Rlp Circuit
     pub rlp_circuit: RlpCircuit<F, Transaction>,
+    /// Wasm Circuit
+    pub wasm_circuit: WasmCircuit<F>,
     /// Mpt Circuit
     #[cfg(feature = "zktrie")]
     pub mpt_circuit: MptCircuit<F>,
@@ -402,6 +427,7 @@ impl<
         let exp = ExpCircuit::min_num_rows_block(block);
         let pi = PiCircuit::min_num_rows_block(block);
         let poseidon = (0, 0); //PoseidonCircuit::min_num_rows_block(block);
+        let wasm = WasmCircuit::min_num_rows_block(block);
         #[cfg(feature = "zktrie")]
         let mpt = (0, 0); //MptCircuit::min_num_rows_block(block);
 
@@ -416,6 +442,7 @@ impl<
             exp,
             pi,
             poseidon,
+            wasm,
             #[cfg(feature = "zktrie")]
             mpt,
         ];
@@ -453,6 +480,7 @@ impl<
             CopyCircuit::<F>::unusable_rows(),
             ExpCircuit::<F>::unusable_rows(),
             KeccakCircuit::<F>::unusable_rows(),
+            WasmCircuit::<F>::unusable_rows(),
         ])
         .unwrap()
     }
@@ -468,6 +496,7 @@ impl<
         let keccak_circuit = KeccakCircuit::new_from_block(block);
         let poseidon_circuit = PoseidonCircuit::new_from_block(block);
         let rlp_circuit = RlpCircuit::new_from_block(block);
+        let wasm_circuit = SubCircuit::new_from_block(block);
         #[cfg(feature = "zktrie")]
         let mpt_circuit = MptCircuit::new_from_block(block);
         SuperCircuit::<_, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS> {
@@ -481,6 +510,7 @@ impl<
             keccak_circuit,
             poseidon_circuit,
             rlp_circuit,
+            wasm_circuit,
             #[cfg(feature = "zktrie")]
             mpt_circuit,
         }
@@ -497,6 +527,7 @@ impl<
         instance.extend_from_slice(&self.state_circuit.instance());
         instance.extend_from_slice(&self.exp_circuit.instance());
         instance.extend_from_slice(&self.evm_circuit.instance());
+        instance.extend_from_slice(&self.wasm_circuit.instance());
 
         instance
     }
@@ -547,6 +578,8 @@ impl<
 
         self.rlp_circuit
             .synthesize_sub(&config.rlp_circuit, challenges, layouter)?;
+        self.wasm_circuit
+            .synthesize_sub(&config.wasm_circuit, challenges, layouter)?;
         // load both poseidon table and zktrie table
         #[cfg(feature = "zktrie")]
         self.mpt_circuit
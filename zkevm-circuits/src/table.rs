@@ -17,10 +17,10 @@ use itertools::Itertools;
 use strum_macros::{EnumCount, EnumIter};
 
 use bus_mapping::circuit_input_builder::{CopyDataType, CopyEvent, CopyStep, ExpEvent};
-use eth_types::{Field, ToLittleEndian, ToScalar, ToWord, Word, U256};
+use eth_types::{Field, ToLittleEndian, ToScalar, ToWord, Word, H256, U256};
 use gadgets::{
     binary_number::{BinaryNumberChip, BinaryNumberConfig},
-    util::{split_u256, split_u256_limb64},
+    util::{split_u256, split_u256_limb64, Expr},
 };
 use keccak256::plain::Keccak;
 
@@ -30,6 +30,14 @@ use crate::{
     exp_circuit::param::{OFFSET_INCREMENT, ROWS_PER_STEP},
     impl_expr,
     util::{build_tx_log_address, Challenges},
+    wasm_circuit::{
+        bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+        sections::{
+            code::body::circuit::WasmCodeSectionBodyConfig,
+            function::body::circuit::WasmFunctionSectionBodyConfig,
+            global::body::circuit::WasmGlobalSectionBodyConfig,
+        },
+    },
     witness::{
         Block, BlockContext, BlockContexts, Bytecode, MptUpdateRow, MptUpdates, RlpFsmWitnessGen,
         Rw, RwMap, RwRow, Transaction,
@@ -370,6 +378,8 @@ pub enum RwTableTag {
     Stack,
     /// Global operation
     Global,
+    /// Wasm table operation
+    Table,
     /// Memory operation
     Memory,
     /// Account Storage operation
@@ -388,6 +398,8 @@ pub enum RwTableTag {
     TxLog,
     /// Tx Receipt operation
     TxReceipt,
+    /// Transient Storage operation
+    TransientStorage,
 }
 impl_expr!(RwTableTag);
 
@@ -401,6 +413,7 @@ impl RwTableTag {
                 | RwTableTag::TxRefund
                 | RwTableTag::Account
                 | RwTableTag::AccountStorage
+                | RwTableTag::TransientStorage
         )
     }
 }
@@ -512,6 +525,22 @@ pub enum CallContextFieldTag {
     MemorySize,
     /// ReversibleWriteCounter
     ReversibleWriteCounter,
+    /// NumLocals
+    NumLocals,
+    /// FrameId
+    FrameId,
+    /// CallerFrameId
+    CallerFrameId,
+    /// CallerFunctionIndex
+    CallerFunctionIndex,
+    /// ReturnProgramCounter
+    ReturnProgramCounter,
+    /// LocalsBase
+    LocalsBase,
+    /// NumParams
+    NumParams,
+    /// NumResults
+    NumResults,
 }
 impl_expr!(CallContextFieldTag);
 
@@ -1061,6 +1090,18 @@ impl PoseidonTable {
         region: &mut Region<F>,
         inputs: impl IntoIterator<Item = &'a Vec<u8>> + Clone,
         assign_delta: usize,
+    ) -> Result<(), Error> {
+        self.dev_load2_with_hash(region, inputs, assign_delta, bus_mapping::state_db::CodeDB::hash)
+    }
+
+    /// Same as `dev_load2` with hash ids of inputs computed by `hash` instead of the default
+    /// code hash
+    pub fn dev_load2_with_hash<'a, F: Field>(
+        &self,
+        region: &mut Region<F>,
+        inputs: impl IntoIterator<Item = &'a Vec<u8>> + Clone,
+        assign_delta: usize,
+        hash: impl Fn(&[u8]) -> H256,
     ) -> Result<(), Error> {
         use crate::bytecode_circuit::bytecode_unroller::{
             unroll_to_hash_input_default, HASHBLOCK_BYTES_IN_FIELD,
@@ -1108,12 +1149,7 @@ impl PoseidonTable {
         for input in inputs.clone() {
             let mut control_len = input.len();
             let mut first_row = true;
-            let ref_hash = Value::known(
-                CodeDB::hash(input.as_slice())
-                    .to_word()
-                    .to_scalar()
-                    .unwrap(),
-            );
+            let ref_hash = Value::known(hash(input.as_slice()).to_word().to_scalar().unwrap());
             for row in unroll_to_hash_input_default::<F>(input.iter().copied()) {
                 assert_ne!(
                     control_len,
@@ -1290,6 +1326,497 @@ impl<F: Field> LookupTable<F> for BytecodeTable {
     }
 }
 
+/// Kind of code anchored in the [`UnifiedBytecodeTable`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+pub enum BytecodeKind {
+    /// EVM bytecode
+    Evm = 1,
+    /// WASM bytecode
+    Wasm = 2,
+}
+impl_expr!(BytecodeKind);
+
+/// Byte rows of both EVM and WASM code tagged with their [`BytecodeKind`], so calls mixing EVM
+/// and WASM contracts in one block anchor their code in a single table. Code hash keeps the
+/// encoding of the kind's own table (RLC for EVM, field element for WASM).
+#[derive(Clone, Debug)]
+pub struct UnifiedBytecodeTable {
+    /// Is Enabled
+    pub q_enable: Column<Fixed>,
+    /// Kind
+    pub kind: Column<Advice>,
+    /// Code Hash
+    pub code_hash: Column<Advice>,
+    /// Index
+    pub index: Column<Advice>,
+    /// Value
+    pub value: Column<Advice>,
+}
+
+impl UnifiedBytecodeTable {
+    /// Construct a new UnifiedBytecodeTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        let [kind, index, value] = array::from_fn(|_| meta.advice_column());
+        let code_hash = meta.advice_column_in(SecondPhase);
+        Self {
+            q_enable: meta.fixed_column(),
+            kind,
+            code_hash,
+            index,
+            value,
+        }
+    }
+
+    /// Constrains every byte row of the EVM `bytecode_table` to be present in this table.
+    pub fn configure_evm_lookup<F: Field>(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        bytecode_table: &BytecodeTable,
+    ) {
+        meta.lookup_any("evm bytecode in unified bytecode table", |meta| {
+            // header tag is 0 and byte tag is 1, so tag works as a byte row flag
+            let condition = meta.query_fixed(bytecode_table.q_enable, Rotation::cur())
+                * meta.query_advice(bytecode_table.tag, Rotation::cur());
+            [
+                BytecodeKind::Evm.expr(),
+                meta.query_advice(bytecode_table.code_hash, Rotation::cur()),
+                meta.query_advice(bytecode_table.index, Rotation::cur()),
+                meta.query_advice(bytecode_table.value, Rotation::cur()),
+            ]
+            .into_iter()
+            .zip(self.table_exprs(meta).into_iter().skip(1))
+            .map(|(input, table)| (condition.clone() * input, table))
+            .collect()
+        });
+    }
+
+    /// Assign the `UnifiedBytecodeTable` from EVM and WASM bytecodes
+    pub fn dev_load<'a, F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        evm_bytecodes: impl IntoIterator<Item = &'a Bytecode> + Clone,
+        wasm_bytecodes: impl IntoIterator<Item = &'a WasmBytecode> + Clone,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "unified bytecode table",
+            |mut region| {
+                let mut rows = vec![[Value::known(F::zero()); 4]];
+                for bytecode in evm_bytecodes.clone() {
+                    // first row is the header
+                    for [hash, _tag, index, _is_code, value] in
+                        bytecode.table_assignments(challenges).into_iter().skip(1)
+                    {
+                        rows.push([
+                            Value::known(F::from(BytecodeKind::Evm as u64)),
+                            hash,
+                            index,
+                            value,
+                        ]);
+                    }
+                }
+                for wb in wasm_bytecodes.clone() {
+                    for [index, value, hash] in wb.table_assignments::<F>() {
+                        rows.push([
+                            Value::known(F::from(BytecodeKind::Wasm as u64)),
+                            hash,
+                            index,
+                            value,
+                        ]);
+                    }
+                }
+
+                let columns = <UnifiedBytecodeTable as LookupTable<F>>::advice_columns(self);
+                for (offset, row) in rows.into_iter().enumerate() {
+                    region.assign_fixed(
+                        || format!("unified bytecode table row {}", offset),
+                        self.q_enable,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    for (&column, value) in columns.iter().zip_eq(row) {
+                        region.assign_advice(
+                            || format!("unified bytecode table row {}", offset),
+                            column,
+                            offset,
+                            || value,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: Field> LookupTable<F> for UnifiedBytecodeTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.kind.into(),
+            self.code_hash.into(),
+            self.index.into(),
+            self.value.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("kind"),
+            String::from("code_hash"),
+            String::from("index"),
+            String::from("value"),
+        ]
+    }
+}
+
+/// Init values of the WASM globals by code hash (as in [`WasmBytecodeTable`]) and position in the
+/// global section. Rows are proven against the global section of the code, so the initial global
+/// values written at the start of a transaction can be looked up instead of trusted from the
+/// trace.
+#[derive(Clone, Debug)]
+pub struct WasmGlobalTable {
+    /// Is Enabled
+    pub q_enable: Column<Fixed>,
+    /// Code Hash
+    pub code_hash: Column<Advice>,
+    /// Position of the global in the global section
+    pub index: Column<Advice>,
+    /// Init value
+    pub value: Column<Advice>,
+}
+
+impl WasmGlobalTable {
+    /// Construct a new WasmGlobalTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        let [code_hash, index, value] = array::from_fn(|_| meta.advice_column());
+        Self {
+            q_enable: meta.fixed_column(),
+            code_hash,
+            index,
+            value,
+        }
+    }
+
+    /// Constrains every row of this table to be the init value of a global in the global section
+    /// of the code, as decoded by the section chip.
+    pub fn configure_global_section_lookup<F: Field>(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        wb_table: &WasmBytecodeTable,
+        global_section: &WasmGlobalSectionBodyConfig<F>,
+    ) {
+        meta.lookup_any("wasm global init value in global section", |meta| {
+            let condition = meta.query_fixed(self.q_enable, Rotation::cur());
+            // the decoded value is complete at the last byte of the init value
+            let is_init_value = meta.query_fixed(global_section.q_enable, Rotation::cur())
+                * meta.query_fixed(global_section.is_init_val, Rotation::cur())
+                * meta.query_fixed(
+                    global_section.leb128_chip.config.is_last_byte,
+                    Rotation::cur(),
+                );
+            [
+                (self.code_hash, wb_table.code_hash),
+                (self.index, global_section.global_index),
+                (self.value, global_section.leb128_chip.config.sn),
+            ]
+            .into_iter()
+            .map(|(input, table)| {
+                (
+                    condition.clone() * meta.query_advice(input, Rotation::cur()),
+                    is_init_value.clone() * meta.query_advice(table, Rotation::cur()),
+                )
+            })
+            .collect()
+        });
+    }
+
+    /// Assign the `WasmGlobalTable` from `(code_hash, index, value)` rows
+    pub fn load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        rows: impl IntoIterator<Item = (Word, u64, u64)> + Clone,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "wasm global table",
+            |mut region| {
+                let columns = <WasmGlobalTable as LookupTable<F>>::advice_columns(self);
+                for (offset, (code_hash, index, value)) in rows.clone().into_iter().enumerate() {
+                    region.assign_fixed(
+                        || format!("wasm global table row {}", offset),
+                        self.q_enable,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    let row = [code_hash.to_scalar().unwrap(), F::from(index), F::from(value)];
+                    for (&column, value) in columns.iter().zip_eq(row) {
+                        region.assign_advice(
+                            || format!("wasm global table row {}", offset),
+                            column,
+                            offset,
+                            || Value::known(value),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assign the `WasmGlobalTable` from the global sections of WASM bytecodes
+    pub fn dev_load<'a, F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        wasm_bytecodes: impl IntoIterator<Item = &'a WasmBytecode> + Clone,
+    ) -> Result<(), Error> {
+        let mut rows = vec![];
+        for wb in wasm_bytecodes {
+            let init_values = wb
+                .global_init_values()
+                .map_err(|_| Error::Synthesis)?;
+            rows.extend(
+                init_values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, value)| (wb.code_hash, index as u64, value)),
+            );
+        }
+        self.load(layouter, rows)
+    }
+}
+
+impl<F: Field> LookupTable<F> for WasmGlobalTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.code_hash.into(),
+            self.index.into(),
+            self.value.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("code_hash"),
+            String::from("index"),
+            String::from("value"),
+        ]
+    }
+}
+
+/// Lookup table of the functions defined by the code and function sections of WASM bytecodes
+#[derive(Clone, Copy, Debug)]
+pub struct WasmFunctionTable {
+    /// Is Enabled
+    pub q_enable: Column<Fixed>,
+    /// Code Hash, same encoding as in the `BytecodeTable`
+    pub code_hash: Column<Advice>,
+    /// Function index, imported functions included
+    pub function_index: Column<Advice>,
+    /// Number of locals declared by the function body
+    pub num_locals: Column<Advice>,
+    /// Type index of the function declared by the function section
+    pub type_index: Column<Advice>,
+}
+
+impl WasmFunctionTable {
+    /// Construct a new WasmFunctionTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        let [function_index, num_locals, type_index] = array::from_fn(|_| meta.advice_column());
+        let code_hash = meta.advice_column_in(SecondPhase);
+        Self {
+            q_enable: meta.fixed_column(),
+            code_hash,
+            function_index,
+            num_locals,
+            type_index,
+        }
+    }
+
+    /// Constrains every row of this table to be a function body of the code section of the code,
+    /// as decoded by the section chip. The code hash encodings of both tables only agree with
+    /// poseidon code hashes.
+    pub fn configure_code_section_lookup<F: Field>(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        wb_table: &WasmBytecodeTable,
+        code_section: &WasmCodeSectionBodyConfig<F>,
+    ) {
+        meta.lookup_any("wasm function locals in code section", |meta| {
+            let condition = meta.query_fixed(self.q_enable, Rotation::cur());
+            // locals_count is final on the instructions of a body, which always ends with a
+            // block end
+            let is_block_end = meta.query_fixed(code_section.selectors.q_enable, Rotation::cur())
+                * meta.query_fixed(code_section.is_block_end, Rotation::cur());
+            // func_count already includes the declared bodies, see the func index lookup of the
+            // code section in `WasmChip`
+            let function_index = meta.query_advice(code_section.func_count, Rotation::cur())
+                - 1.expr()
+                - meta.query_advice(code_section.body_item_rev_count, Rotation::cur());
+            [
+                (
+                    meta.query_advice(self.code_hash, Rotation::cur()),
+                    meta.query_advice(wb_table.code_hash, Rotation::cur()),
+                ),
+                (
+                    meta.query_advice(self.function_index, Rotation::cur()),
+                    function_index,
+                ),
+                (
+                    meta.query_advice(self.num_locals, Rotation::cur()),
+                    meta.query_advice(code_section.locals_count, Rotation::cur()),
+                ),
+            ]
+            .into_iter()
+            .map(|(input, table)| (condition.clone() * input, is_block_end.clone() * table))
+            .collect()
+        });
+    }
+
+    /// Constrains every row of this table to be a typeidx of the function section of the code,
+    /// as decoded by the section chip. The code hash encodings of both tables only agree with
+    /// poseidon code hashes.
+    pub fn configure_function_section_lookup<F: Field>(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        wb_table: &WasmBytecodeTable,
+        function_section: &WasmFunctionSectionBodyConfig<F>,
+    ) {
+        meta.lookup_any("wasm function type in function section", |meta| {
+            let condition = meta.query_fixed(self.q_enable, Rotation::cur());
+            let is_typeidx = meta
+                .query_fixed(function_section.selectors.q_enable, Rotation::cur())
+                * meta.query_fixed(function_section.is_typeidx, Rotation::cur())
+                * meta.query_fixed(
+                    function_section.leb128_chip.config.is_last_byte,
+                    Rotation::cur(),
+                );
+            // func_count only counts the imported functions in the function section, the item
+            // with body_item_rev_count N is the func index func_count+items_count-1-N
+            let function_index = meta.query_advice(function_section.func_count, Rotation::cur())
+                + meta.query_advice(function_section.items_count, Rotation::cur())
+                - 1.expr()
+                - meta.query_advice(function_section.body_item_rev_count, Rotation::cur());
+            [
+                (
+                    meta.query_advice(self.code_hash, Rotation::cur()),
+                    meta.query_advice(wb_table.code_hash, Rotation::cur()),
+                ),
+                (
+                    meta.query_advice(self.function_index, Rotation::cur()),
+                    function_index,
+                ),
+                (
+                    meta.query_advice(self.type_index, Rotation::cur()),
+                    meta.query_advice(function_section.leb128_chip.config.sn, Rotation::cur()),
+                ),
+            ]
+            .into_iter()
+            .map(|(input, table)| (condition.clone() * input, is_typeidx.clone() * table))
+            .collect()
+        });
+    }
+
+    /// Assign the `WasmFunctionTable` from `(code_hash, function_index, num_locals, type_index)`
+    /// rows
+    pub fn load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        rows: impl IntoIterator<Item = (Value<F>, u64, u64, u64)> + Clone,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "wasm function table",
+            |mut region| {
+                let columns = <WasmFunctionTable as LookupTable<F>>::advice_columns(self);
+                for (offset, (code_hash, function_index, num_locals, type_index)) in
+                    rows.clone().into_iter().enumerate()
+                {
+                    region.assign_fixed(
+                        || format!("wasm function table row {}", offset),
+                        self.q_enable,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    let row = [
+                        code_hash,
+                        Value::known(F::from(function_index)),
+                        Value::known(F::from(num_locals)),
+                        Value::known(F::from(type_index)),
+                    ];
+                    for (&column, value) in columns.iter().zip_eq(row) {
+                        region.assign_advice(
+                            || format!("wasm function table row {}", offset),
+                            column,
+                            offset,
+                            || value,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assign the `WasmFunctionTable` from the code sections of WASM bytecodes, bytecodes that
+    /// are not valid WASM are skipped
+    pub fn dev_load<'a, F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        wasm_bytecodes: impl IntoIterator<Item = &'a WasmBytecode> + Clone,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        let mut rows = vec![];
+        for wb in wasm_bytecodes {
+            let (function_locals, function_type_indexes) =
+                match (wb.function_locals(), wb.function_type_indexes()) {
+                    (Ok(function_locals), Ok(function_type_indexes)) => {
+                        (function_locals, function_type_indexes)
+                    }
+                    _ => continue,
+                };
+            let code_hash = if cfg!(feature = "poseidon-codehash") {
+                challenges
+                    .evm_word()
+                    .map(|_| rlc::value(&wb.code_hash.to_le_bytes(), F::from(256u64)))
+            } else {
+                challenges
+                    .evm_word()
+                    .map(|challenge| rlc::value(&wb.code_hash.to_le_bytes(), challenge))
+            };
+            rows.extend(function_locals.into_iter().zip(function_type_indexes).map(
+                |((function_index, num_locals), (_, type_index))| {
+                    (code_hash, function_index, num_locals, type_index)
+                },
+            ));
+        }
+        self.load(layouter, rows)
+    }
+}
+
+impl<F: Field> LookupTable<F> for WasmFunctionTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.code_hash.into(),
+            self.function_index.into(),
+            self.num_locals.into(),
+            self.type_index.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("code_hash"),
+            String::from("function_index"),
+            String::from("num_locals"),
+            String::from("type_index"),
+        ]
+    }
+}
+
 /// Tag to identify the field in a Block Table row
 // Keep the sequence consistent with OpcodeId for scalar
 #[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
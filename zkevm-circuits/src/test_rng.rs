@@ -0,0 +1,135 @@
+//! Deterministic RNG for randomized tests.
+//!
+//! Every test thread gets its own ChaCha RNG seeded from the test name (libtest names test threads
+//! after the test), so a failing run can be reproduced. The seed is printed on first use; test
+//! output is only shown for failed tests. To replay a seed set `ZKEVM_TEST_SEED=<seed>` (decimal
+//! or `0x` prefixed hex) or call [`replay_seed`] at the start of the test.
+
+use std::cell::RefCell;
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Env variable overriding seeds of all tests
+pub const TEST_SEED_ENV: &str = "ZKEVM_TEST_SEED";
+
+struct SeededRng {
+    seed: u64,
+    rng: ChaCha20Rng,
+}
+
+impl SeededRng {
+    fn new(seed: u64) -> Self {
+        println!("test rng seed {} (replay with {}={})", seed, TEST_SEED_ENV, seed);
+        Self {
+            seed,
+            rng: ChaCha20Rng::seed_from_u64(seed),
+        }
+    }
+}
+
+thread_local! {
+    static RNG: RefCell<Option<SeededRng>> = RefCell::new(None);
+}
+
+fn parse_seed(value: &str) -> Option<u64> {
+    let value = value.trim();
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+/// FNV-1a, stable across platforms and compiler versions (unlike `DefaultHasher`)
+fn hash_name(name: &str) -> u64 {
+    name.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Seed of a test: [`TEST_SEED_ENV`] if set, hash of `test_name` otherwise
+pub fn seed_for(test_name: &str) -> u64 {
+    std::env::var(TEST_SEED_ENV)
+        .ok()
+        .and_then(|v| parse_seed(&v))
+        .unwrap_or_else(|| hash_name(test_name))
+}
+
+fn with_rng<R>(f: impl FnOnce(&mut SeededRng) -> R) -> R {
+    RNG.with(|rng| {
+        let mut rng = rng.borrow_mut();
+        let rng = rng.get_or_insert_with(|| {
+            let thread = std::thread::current();
+            SeededRng::new(seed_for(thread.name().unwrap_or_default()))
+        });
+        f(rng)
+    })
+}
+
+/// Seed of the current test thread RNG
+pub fn seed() -> u64 {
+    with_rng(|rng| rng.seed)
+}
+
+/// Restarts the current test thread RNG from `seed`
+pub fn replay_seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = Some(SeededRng::new(seed)));
+}
+
+/// Handle to the current test thread RNG, drop-in replacement for `rand::thread_rng()`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TestRng;
+
+pub fn test_rng() -> TestRng {
+    TestRng
+}
+
+impl RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        with_rng(|rng| rng.rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        with_rng(|rng| rng.rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        with_rng(|rng| rng.rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        with_rng(|rng| rng.rng.try_fill_bytes(dest))
+    }
+}
+
+#[cfg(test)]
+mod test_rng_tests {
+    use rand::Rng;
+
+    use super::{parse_seed, replay_seed, seed, seed_for, test_rng};
+
+    #[test]
+    fn replay_gives_same_values() {
+        let seed = seed();
+        let first = (0..16).map(|_| test_rng().gen::<u64>()).collect::<Vec<_>>();
+        replay_seed(seed);
+        let replayed = (0..16).map(|_| test_rng().gen::<u64>()).collect::<Vec<_>>();
+        assert_eq!(first, replayed);
+    }
+
+    #[test]
+    fn seeds_differ_between_tests() {
+        if std::env::var(super::TEST_SEED_ENV).is_ok() {
+            return;
+        }
+        assert_ne!(seed_for("a::test_one"), seed_for("a::test_two"));
+        assert_eq!(seed_for("a::test_one"), seed_for("a::test_one"));
+    }
+
+    #[test]
+    fn seed_parsing() {
+        assert_eq!(parse_seed("42"), Some(42));
+        assert_eq!(parse_seed("0x2a"), Some(42));
+        assert_eq!(parse_seed("nope"), None);
+    }
+}
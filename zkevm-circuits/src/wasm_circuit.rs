@@ -1,5 +1,6 @@
+pub mod builder;
 pub mod circuit;
-pub mod consts;
+pub use wasm_witness::consts;
 pub mod bytecode;
 #[cfg(any(feature = "test", test))]
 pub mod tests;
@@ -8,10 +9,22 @@ pub mod tests_parsers;
 #[cfg(any(feature = "test", test))]
 mod error_tests;
 pub mod leb128;
+#[cfg(any(feature = "test", test))]
+pub mod module_builder;
 pub mod tables;
 pub mod common;
+pub mod corpus;
+pub mod cost;
+pub mod decoder;
 pub mod sections;
-pub mod error;
+pub use wasm_witness::error;
+pub mod memory_tree;
+pub mod prover;
+pub mod recorder;
+pub mod rwasm;
 pub mod utf8;
-pub mod types;
+pub use wasm_witness::types;
+pub mod validation;
+pub mod witness;
+#[cfg(any(feature = "test", test))]
 mod tests_helpers;
@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+
+use eth_types::evm_types::{MAX_WASM_MEMORY_PAGES, MAX_WASM_RESULTS};
+
+use crate::wasm_circuit::{
+    consts::{
+        MAX_BLOCK_LEVEL, MAX_DATA_SEGMENT_SIZE, MAX_DATA_SIZE, MAX_FUNC_BODY_SIZE,
+        MAX_FUNC_LOCALS, MAX_FUNC_PARAMS, MAX_IMPORT_MOD_NAME_LEN, MAX_IMPORT_NAME_LEN,
+    },
+    tables::import_whitelist::types::ImportWhitelist,
+    types::{WasmSection, WASM_SECTION_VALUES},
+};
+
+/// Sections whose body chips can be left out of the circuit
+pub const WASM_SECTIONS_WITH_BODY_CHIP: &[WasmSection] = &[
+    WasmSection::Type,
+    WasmSection::Import,
+    WasmSection::Function,
+    WasmSection::Table,
+    WasmSection::Memory,
+    WasmSection::Global,
+    WasmSection::Export,
+    WasmSection::Start,
+    WasmSection::Element,
+    WasmSection::Code,
+    WasmSection::Data,
+];
+
+/// Shape of the circuit. Disabled section chips allocate no columns, gates or lookups, so the
+/// options are part of the constraint system (and therefore of the verifying key). A bytecode
+/// containing a disabled section is processed as an invalid one (error code is set).
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WasmCircuitOptions {
+    enabled_sections: Vec<WasmSection>,
+    /// function imports missing from the whitelist set the error code
+    import_whitelist: Option<ImportWhitelist>,
+    /// type section functypes declaring more params set the error code
+    max_func_params: u64,
+    /// type section functypes declaring more results set the error code, applies with
+    /// `multi_value` only, otherwise at most 1 result is accepted
+    max_func_results: u64,
+    multi_value: bool,
+    /// import module names declared longer set the error code
+    max_import_mod_name_len: u64,
+    /// import field names declared longer set the error code
+    max_import_name_len: u64,
+    /// memory limits above it set the error code
+    max_memory_pages: u64,
+    /// code section blocks nested deeper set the error code
+    max_block_level: u64,
+    /// code section func bodies declared bigger set the error code
+    max_func_body_size: u64,
+    /// code section func bodies declaring more locals set the error code
+    max_func_locals: u64,
+    /// code section SIMD instructions are skipped without proving their semantics
+    /// (validation-only), otherwise they set the error code
+    skip_simd_instructions: bool,
+    /// exports of mutable globals set the error code
+    reject_exported_mutable_globals: bool,
+    /// data section segments declared longer set the error code
+    max_data_segment_size: u64,
+    /// data section segments declaring more bytes in total set the error code
+    max_data_size: u64,
+}
+
+impl Default for WasmCircuitOptions {
+    fn default() -> Self {
+        Self {
+            enabled_sections: WASM_SECTIONS_WITH_BODY_CHIP.to_vec(),
+            import_whitelist: None,
+            max_func_params: MAX_FUNC_PARAMS,
+            max_func_results: MAX_WASM_RESULTS as u64,
+            multi_value: false,
+            max_import_mod_name_len: MAX_IMPORT_MOD_NAME_LEN,
+            max_import_name_len: MAX_IMPORT_NAME_LEN,
+            max_memory_pages: MAX_WASM_MEMORY_PAGES,
+            max_block_level: MAX_BLOCK_LEVEL,
+            max_func_body_size: MAX_FUNC_BODY_SIZE,
+            max_func_locals: MAX_FUNC_LOCALS,
+            skip_simd_instructions: false,
+            reject_exported_mutable_globals: false,
+            max_data_segment_size: MAX_DATA_SEGMENT_SIZE,
+            max_data_size: MAX_DATA_SIZE,
+        }
+    }
+}
+
+impl WasmCircuitOptions {
+    pub fn is_section_enabled(&self, section: WasmSection) -> bool {
+        self.enabled_sections.contains(&section)
+    }
+
+    pub fn enabled_sections(&self) -> &[WasmSection] {
+        &self.enabled_sections
+    }
+
+    pub fn import_whitelist(&self) -> Option<&ImportWhitelist> {
+        self.import_whitelist.as_ref()
+    }
+
+    pub fn max_func_params(&self) -> u64 {
+        self.max_func_params
+    }
+
+    /// Max results of a functype, 1 without multi-value
+    pub fn max_func_results(&self) -> u64 {
+        if self.multi_value {
+            self.max_func_results
+        } else {
+            1
+        }
+    }
+
+    pub fn multi_value(&self) -> bool {
+        self.multi_value
+    }
+
+    pub fn max_import_mod_name_len(&self) -> u64 {
+        self.max_import_mod_name_len
+    }
+
+    pub fn max_import_name_len(&self) -> u64 {
+        self.max_import_name_len
+    }
+
+    pub fn max_memory_pages(&self) -> u64 {
+        self.max_memory_pages
+    }
+
+    pub fn max_block_level(&self) -> u64 {
+        self.max_block_level
+    }
+
+    pub fn max_func_body_size(&self) -> u64 {
+        self.max_func_body_size
+    }
+
+    pub fn max_func_locals(&self) -> u64 {
+        self.max_func_locals
+    }
+
+    pub fn skip_simd_instructions(&self) -> bool {
+        self.skip_simd_instructions
+    }
+
+    pub fn reject_exported_mutable_globals(&self) -> bool {
+        self.reject_exported_mutable_globals
+    }
+
+    pub fn max_data_segment_size(&self) -> u64 {
+        self.max_data_segment_size
+    }
+
+    pub fn max_data_size(&self) -> u64 {
+        self.max_data_size
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct WasmCircuitBuilder {
+    options: WasmCircuitOptions,
+}
+
+impl WasmCircuitBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_section(mut self, section: WasmSection) -> Self {
+        if WASM_SECTIONS_WITH_BODY_CHIP.contains(&section)
+            && !self.options.enabled_sections.contains(&section)
+        {
+            self.options.enabled_sections.push(section);
+            // keep order stable so equal option sets compare (and serialize) equally
+            self.options.enabled_sections.sort_by_key(|s| {
+                WASM_SECTION_VALUES
+                    .iter()
+                    .position(|v| v == s)
+                    .unwrap_or_default()
+            });
+        }
+        self
+    }
+
+    pub fn without_section(mut self, section: WasmSection) -> Self {
+        self.options.enabled_sections.retain(|s| *s != section);
+        self
+    }
+
+    pub fn with_import_whitelist(mut self, import_whitelist: ImportWhitelist) -> Self {
+        self.options.import_whitelist = Some(import_whitelist);
+        self
+    }
+
+    pub fn with_max_func_params(mut self, max_func_params: u64) -> Self {
+        self.options.max_func_params = max_func_params;
+        self
+    }
+
+    /// Accepts functypes with up to `max_func_results` results
+    pub fn with_multi_value(mut self, max_func_results: u64) -> Self {
+        self.options.multi_value = true;
+        self.options.max_func_results = max_func_results;
+        self
+    }
+
+    pub fn with_max_import_mod_name_len(mut self, max_import_mod_name_len: u64) -> Self {
+        self.options.max_import_mod_name_len = max_import_mod_name_len;
+        self
+    }
+
+    pub fn with_max_import_name_len(mut self, max_import_name_len: u64) -> Self {
+        self.options.max_import_name_len = max_import_name_len;
+        self
+    }
+
+    pub fn with_max_memory_pages(mut self, max_memory_pages: u64) -> Self {
+        self.options.max_memory_pages = max_memory_pages;
+        self
+    }
+
+    pub fn with_max_block_level(mut self, max_block_level: u64) -> Self {
+        self.options.max_block_level = max_block_level;
+        self
+    }
+
+    pub fn with_max_func_body_size(mut self, max_func_body_size: u64) -> Self {
+        self.options.max_func_body_size = max_func_body_size;
+        self
+    }
+
+    pub fn with_max_func_locals(mut self, max_func_locals: u64) -> Self {
+        self.options.max_func_locals = max_func_locals;
+        self
+    }
+
+    pub fn with_simd_instructions_skipped(mut self) -> Self {
+        self.options.skip_simd_instructions = true;
+        self
+    }
+
+    pub fn with_exported_mutable_globals_rejected(mut self) -> Self {
+        self.options.reject_exported_mutable_globals = true;
+        self
+    }
+
+    pub fn with_max_data_segment_size(mut self, max_data_segment_size: u64) -> Self {
+        self.options.max_data_segment_size = max_data_segment_size;
+        self
+    }
+
+    pub fn with_max_data_size(mut self, max_data_size: u64) -> Self {
+        self.options.max_data_size = max_data_size;
+        self
+    }
+
+    pub fn build(self) -> WasmCircuitOptions {
+        self.options
+    }
+}
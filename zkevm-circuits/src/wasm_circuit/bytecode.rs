@@ -1,2 +1,4 @@
 pub mod bytecode_table;
-pub mod bytecode;
+pub use wasm_witness::bytecode;
+#[cfg(any(feature = "test", test))]
+pub mod tests;
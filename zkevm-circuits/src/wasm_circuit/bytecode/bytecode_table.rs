@@ -7,7 +7,7 @@ use halo2_proofs::{
 use itertools::Itertools;
 use log::debug;
 
-use eth_types::Field;
+use eth_types::{Field, ToScalar};
 
 use crate::{
     table::LookupTable,
@@ -19,6 +19,9 @@ pub struct WasmBytecodeTable {
     pub index: Column<Advice>,
     pub value: Column<Advice>,
     pub code_hash: Column<Advice>,
+    /// poseidon commitment of the bytecode, cheap to verify inside the field (e.g. by
+    /// aggregation circuits) whatever hash `code_hash` uses. Not part of lookup columns.
+    pub poseidon_code_hash: Option<Column<Advice>>,
 
     pub zero_row_enabled: bool,
 }
@@ -30,10 +33,22 @@ impl WasmBytecodeTable {
             index,
             value,
             code_hash,
+            poseidon_code_hash: None,
             zero_row_enabled,
         }
     }
 
+    /// Same as [`Self::construct`] with the poseidon code hash column
+    pub fn construct_with_poseidon_code_hash<F: Field>(
+        cs: &mut ConstraintSystem<F>,
+        zero_row_enabled: bool,
+    ) -> Self {
+        Self {
+            poseidon_code_hash: Some(cs.advice_column()),
+            ..Self::construct(cs, zero_row_enabled)
+        }
+    }
+
     pub fn load<'a, F: Field>(
         &self,
         region: &mut Region<F>,
@@ -64,10 +79,39 @@ impl WasmBytecodeTable {
                     || Value::known(F::from(value)),
                 )?;
             }
+            if let Some(column) = self.poseidon_code_hash {
+                region.assign_advice(
+                    || {
+                        format!(
+                            "assign at {} wasm_bytecode_table poseidon_code_hash",
+                            assign_offset
+                        )
+                    },
+                    column,
+                    assign_offset,
+                    || Value::known(F::zero()),
+                )?;
+            }
             assign_offset += 1;
         }
 
-        for (offset, &row) in wb.table_assignments::<F>().iter().enumerate() {
+        let poseidon_code_hash = self
+            .poseidon_code_hash
+            .map(|column| (column, wb.poseidon_code_hash().to_scalar().unwrap()));
+        for (offset, &row) in table_assignments::<F>(wb).iter().enumerate() {
+            if let Some((column, poseidon_code_hash)) = poseidon_code_hash {
+                region.assign_advice(
+                    || {
+                        format!(
+                            "assign at {} wasm_bytecode_table poseidon_code_hash",
+                            assign_offset
+                        )
+                    },
+                    column,
+                    assign_offset,
+                    || Value::known(poseidon_code_hash),
+                )?;
+            }
             for (&column, value) in bytecode_table_columns.iter().zip_eq(row) {
                 debug!(
                     "assign at {} column.index {} wasm_bytecode_table val {:?}",
@@ -91,6 +135,20 @@ impl WasmBytecodeTable {
     }
 }
 
+/// Assignments for bytecode table
+fn table_assignments<F: Field>(wb: &WasmBytecode) -> Vec<[Value<F>; 3]> {
+    let code_hash_val = Value::known(wb.code_hash.to_scalar().unwrap());
+    wb.bytes
+        .iter()
+        .enumerate()
+        .map(|(idx, byte)| {
+            let idx_val = Value::known(F::from(idx as u64));
+            let byte_val = Value::known(F::from(*byte as u64));
+            [idx_val, byte_val, code_hash_val]
+        })
+        .collect()
+}
+
 impl<F: Field> LookupTable<F> for WasmBytecodeTable {
     fn columns(&self) -> Vec<Column<Any>> {
         vec![self.index.into(), self.value.into(), self.code_hash.into()]
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod wasm_bytecode_tests {
+    use wabt::wat2wasm;
+    use wasmparser::{Parser, Payload};
+
+    use crate::wasm_circuit::{bytecode::bytecode::WasmBytecode, types::WasmSection};
+
+    fn wat_file_to_wb(path: &str) -> WasmBytecode {
+        let data: Vec<u8> = std::fs::read(path).unwrap();
+        WasmBytecode::new(wat2wasm(data).unwrap())
+    }
+
+    #[test]
+    pub fn file1_sections_match_wasmparser() {
+        let wb = wat_file_to_wb("./test_files/cc1.wat");
+
+        let mut expected_sections = vec![];
+        let mut expected_function_bodies = vec![];
+        for payload in Parser::new(0).parse_all(&wb.bytes) {
+            let payload = payload.unwrap();
+            if let Payload::CodeSectionEntry(body) = &payload {
+                expected_function_bodies.push(body.range());
+            }
+            if let Some((id, range)) = payload.as_section() {
+                expected_sections.push((id as i32, range));
+            }
+        }
+        let actual_sections = wb
+            .sections()
+            .iter()
+            .map(|s| (s.section as i32, wb.section_body(s.section).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(expected_sections, actual_sections);
+        assert_eq!(expected_function_bodies, wb.function_bodies());
+    }
+
+    #[test]
+    pub fn file2_section_query_ok() {
+        let wb = wat_file_to_wb("./test_files/cc2.wat");
+
+        let start_section = wb.section(WasmSection::Start).unwrap();
+        assert_eq!(wb.bytes[start_section.start], WasmSection::Start as u8);
+        assert_eq!(wb.section_body(WasmSection::Start).unwrap().len(), 1);
+        assert!(wb.section(WasmSection::DataCount).is_none());
+        assert!(!wb.function_bodies().is_empty());
+    }
+
+    #[test]
+    pub fn truncated_bytecode_keeps_parsed_sections() {
+        let mut bytes = wat2wasm(std::fs::read("./test_files/cc1.wat").unwrap()).unwrap();
+        let full_wb = WasmBytecode::new(bytes.clone());
+        let code_section = full_wb.section(WasmSection::Code).unwrap();
+        bytes.truncate(code_section.start + 2);
+
+        let wb = WasmBytecode::new(bytes);
+        assert!(wb.section(WasmSection::Code).is_none());
+        assert!(wb.function_bodies().is_empty());
+        assert_eq!(
+            wb.sections(),
+            &full_wb.sections()[..wb.sections().len()]
+        );
+        assert!(wb.section(WasmSection::Type).is_some());
+    }
+
+    #[cfg(feature = "wat")]
+    #[test]
+    pub fn from_wat_ok() {
+        let wat = r#"
+            (module
+              (type (func (param i32 i32)))
+              (import "env" "_evm_return" (func (type 0)))
+              (func (export "main")
+                i32.const 0
+                i32.const 2
+                call 0))
+        "#;
+        let wb = WasmBytecode::from_wat(wat).unwrap();
+        assert_eq!(wb.bytes, wat2wasm(wat).unwrap());
+        assert!(wb.section(WasmSection::Code).is_some());
+        assert_eq!(wb.function_bodies().len(), 1);
+        assert!(WasmBytecode::from_wat("(module (func").is_err());
+    }
+}
@@ -1,13 +1,16 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{cell::RefCell, marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
-    circuit::{Chip, Layouter, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Fixed},
+    circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Fixed, Instance},
     poly::Rotation,
 };
 use log::debug;
+use snark_verifier_sdk::CircuitExt;
 
-use eth_types::Field;
+use bus_mapping::{circuit_input_builder, state_db::CodeDB};
+use eth_types::{Field, ToScalar};
 use gadgets::{
     is_zero::{IsZeroChip, IsZeroInstruction},
     less_than::{LtChip, LtInstruction},
@@ -16,27 +19,37 @@ use gadgets::{
 
 use crate::{
     evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
-    table::PoseidonTable,
+    table::{BytecodeKind, PoseidonTable, UnifiedBytecodeTable, WasmFunctionTable},
+    util::{log2_ceil, unusable_rows, Challenges, SubCircuit, SubCircuitConfig},
     wasm_circuit::{
+        builder::WasmCircuitOptions,
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
             configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            digit_char_to_number, wasm_compute_section_len, WasmAssignAwareChip,
-            WasmBytecodeNumberAwareChip, WasmErrorAwareChip, WasmFuncCountAwareChip,
-            WasmLenPrefixedBytesSpanAwareChip, WasmMarkupLeb128SectionAwareChip,
-            WasmSharedStateAwareChip,
+            digit_char_to_number, is_pass_through_custom_section_name, section_func_count,
+            wasm_compute_section_len, wasm_custom_section_name, wasm_producers_section,
+            ErrorCodeColumns, WasmAssignAwareChip, WasmBytecodeNumberAwareChip, WasmErrorAwareChip,
+            WasmFuncCountAwareChip, WasmLenPrefixedBytesSpanAwareChip,
+            WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
         },
         consts::{
-            SECTION_ID_DEFAULT, WASM_MAGIC_PREFIX, WASM_MAGIC_PREFIX_LEN,
-            WASM_MAGIC_PREFIX_START_INDEX, WASM_SECTIONS_START_INDEX, WASM_SECTION_ID_MAX,
-            WASM_VERSION_PREFIX, WASM_VERSION_PREFIX_END_INDEX, WASM_VERSION_PREFIX_LEN,
-            WASM_VERSION_PREFIX_START_INDEX,
+            PRODUCERS_CUSTOM_SECTION_NAME, SECTION_ID_DEFAULT, WASM_MAGIC_PREFIX, WASM_MAGIC_PREFIX_LEN,
+            WASM_MAGIC_PREFIX_START_INDEX, WASM_MODULE_OUTPUT_LEN, WASM_SECTIONS_START_INDEX,
+            WASM_SECTION_ID_MAX, WASM_VERSION_PREFIX, WASM_VERSION_PREFIX_END_INDEX,
+            WASM_VERSION_PREFIX_LEN, WASM_VERSION_PREFIX_START_INDEX,
         },
+        cost::{
+            cost::CostReport,
+            layout::{CircuitLayout, ColumnKind, InstanceLayout},
+        },
+        decoder::decoder::{decode_module, ReferenceModule, ReferenceSection},
         error::{
-            error_index_out_of_bounds, is_recoverable_error, remap_error_to_assign_at,
-            remap_error_to_compute_value_at, remap_error_to_invalid_enum_value_at, Error,
+            error_index_out_of_bounds, error_offset, error_reason, is_recoverable_error,
+            remap_error_to_assign_at, remap_error_to_compute_value_at,
+            remap_error_to_invalid_enum_value_at, Error,
         },
         leb128::{circuit::LEB128Chip, helpers::leb128_compute_last_byte_offset},
+        recorder::journal::{with_journal, AssignJournal},
         sections::{
             code::body::circuit::WasmCodeSectionBodyChip,
             consts::LebParams,
@@ -54,19 +67,24 @@ use crate::{
             table::body::circuit::WasmTableSectionBodyChip,
         },
         tables::{
+            byte_set::{config::ByteSetTableConfig, types::byte_set_rows},
             dynamic_indexes::{
                 circuit::DynamicIndexesChip,
                 types::{LookupArgsParams, Tag},
             },
             fixed_range::config::RangeTableConfig,
+            opcode::{config::OpcodeTableConfig, types::opcode_rows},
+            utf8_transition::{config::Utf8TransitionTableConfig, types::utf8_transitions},
         },
         types::{
-            AssignDeltaType, AssignType, AssignValueType, ControlInstruction, ErrorCode,
-            ExportDescType, ImportDescType, NewOffsetType, NewWbOffsetType, OffsetType,
-            SharedState, WasmSection,
+            AssignDeltaType, AssignType, AssignValueType, ControlInstruction, ExportDescType,
+            ImportDescType, Mutability, NewOffsetType, NewWbOffsetType, OffsetType, RegionCursor,
+            SharedStateRef, WasmAssignmentPlan, WasmModuleOutput, WasmSection,
+            WasmSectionAssignment,
         },
         utf8::circuit::UTF8Chip,
     },
+    witness,
 };
 
 pub struct WasmSectionConfig<F: Field> {
@@ -75,9 +93,10 @@ pub struct WasmSectionConfig<F: Field> {
 
 #[derive(Debug, Clone)]
 pub struct WasmConfig<F: Field> {
-    pub wb_table: Rc<WasmBytecodeTable>,
+    pub wb_table: Arc<WasmBytecodeTable>,
 
-    pub shared_state: Rc<RefCell<SharedState>>,
+    pub shared_state: SharedStateRef,
+    pub options: WasmCircuitOptions,
 
     bytecode_number: Column<Advice>,
 
@@ -90,28 +109,34 @@ pub struct WasmConfig<F: Field> {
 
     section_id: Column<Advice>,
 
-    leb128_chip: Rc<LEB128Chip<F>>,
-    utf8_chip: Rc<UTF8Chip<F>>,
-    wasm_type_section_item_chip: Rc<WasmTypeSectionItemChip<F>>,
-    wasm_type_section_body_chip: Rc<WasmTypeSectionBodyChip<F>>,
-    wasm_import_section_body_chip: Rc<WasmImportSectionBodyChip<F>>,
-    wasm_function_section_body_chip: Rc<WasmFunctionSectionBodyChip<F>>,
-    wasm_memory_section_body_chip: Rc<WasmMemorySectionBodyChip<F>>,
-    wasm_export_section_body_chip: Rc<WasmExportSectionBodyChip<F>>,
-    wasm_data_section_body_chip: Rc<WasmDataSectionBodyChip<F>>,
-    wasm_global_section_body_chip: Rc<WasmGlobalSectionBodyChip<F>>,
-    wasm_code_section_body_chip: Rc<WasmCodeSectionBodyChip<F>>,
-    wasm_start_section_body_chip: Rc<WasmStartSectionBodyChip<F>>,
-    wasm_table_section_body_chip: Rc<WasmTableSectionBodyChip<F>>,
-    wasm_element_section_body_chip: Rc<WasmElementSectionBodyChip<F>>,
+    leb128_chip: Arc<LEB128Chip<F>>,
+    utf8_chip: Arc<UTF8Chip<F>>,
+    wasm_type_section_item_chip: Option<Arc<WasmTypeSectionItemChip<F>>>,
+    wasm_type_section_body_chip: Option<Arc<WasmTypeSectionBodyChip<F>>>,
+    wasm_import_section_body_chip: Option<Arc<WasmImportSectionBodyChip<F>>>,
+    wasm_function_section_body_chip: Option<Arc<WasmFunctionSectionBodyChip<F>>>,
+    wasm_memory_section_body_chip: Option<Arc<WasmMemorySectionBodyChip<F>>>,
+    wasm_export_section_body_chip: Option<Arc<WasmExportSectionBodyChip<F>>>,
+    wasm_data_section_body_chip: Option<Arc<WasmDataSectionBodyChip<F>>>,
+    wasm_global_section_body_chip: Option<Arc<WasmGlobalSectionBodyChip<F>>>,
+    wasm_code_section_body_chip: Option<Arc<WasmCodeSectionBodyChip<F>>>,
+    wasm_start_section_body_chip: Option<Arc<WasmStartSectionBodyChip<F>>>,
+    wasm_table_section_body_chip: Option<Arc<WasmTableSectionBodyChip<F>>>,
+    wasm_element_section_body_chip: Option<Arc<WasmElementSectionBodyChip<F>>>,
     section_id_lt_chip: LtChip<F, 1>,
-    dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+    /// section len must not be above the bytes remaining after it
+    section_len_lt_chip: LtChip<F, 4>,
+    /// custom sections have no body chip, their bodies are only accounted by the section length
+    section_id_is_custom_chip: IsZeroChip<F>,
+    dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
     magic_prefix_count: usize,
     index_at_magic_prefix: Vec<IsZeroChip<F>>,
     poseidon_table: PoseidonTable,
     range_table_config_0_256: RangeTableConfig<F, 0, 256>,
     section_id_range_table_config: RangeTableConfig<F, 0, { WASM_SECTION_ID_MAX + 1 }>,
-    range_table_config_0_128: Rc<RangeTableConfig<F, 0, 128>>,
+    utf8_transition_table_config: Arc<Utf8TransitionTableConfig<F>>,
+    byte_set_table_config: Arc<ByteSetTableConfig<F>>,
+    opcode_table_config: Arc<OpcodeTableConfig<F>>,
 
     func_count: Column<Advice>,
     block_depth_level: Column<Advice>,
@@ -119,8 +144,13 @@ pub struct WasmConfig<F: Field> {
     body_byte_rev_index_l2: Column<Advice>,
     body_item_rev_count_l1: Column<Advice>,
     body_item_rev_count_l2: Column<Advice>,
+    /// number of bytecode bytes after the current one
+    bytecode_rev_index: Column<Advice>,
+
+    error_code: ErrorCodeColumns,
 
-    error_code: Column<Advice>,
+    /// columns, gates and lookups allocated by each chip during configure
+    pub cost_report: CostReport,
 
     _marker: PhantomData<F>,
 }
@@ -130,6 +160,8 @@ impl<F: Field> WasmConfig<F> {}
 #[derive(Debug, Clone)]
 pub struct WasmChip<F: Field> {
     pub config: WasmConfig<F>,
+    pub section_assignments: Vec<WasmSectionAssignment>,
+    pub module_outputs: Vec<WasmModuleOutput>,
     _marker: PhantomData<F>,
 }
 
@@ -144,13 +176,13 @@ impl<F: Field> WasmBytecodeNumberAwareChip<F> for WasmChip<F> {
 }
 
 impl<F: Field> WasmErrorAwareChip<F> for WasmChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
@@ -288,6 +320,21 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmChip<F> {
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
+                AssignType::BytecodeRevIndex => {
+                    region
+                        .assign_advice(
+                            || {
+                                format!(
+                                    "assign 'bytecode_rev_index' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.bytecode_rev_index,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
                 AssignType::ErrorCode => {
                     self.assign_error_code(region, assign_offset, None)?;
                 }
@@ -324,10 +371,22 @@ impl<F: Field> WasmChip<F> {
             } else {
                 0
             };
-        self.config
-            .poseidon_table
-            .dev_load2(region, &[wb.bytes.clone()], assign_delta)
-            .unwrap();
+        if self.config.wb_table.poseidon_code_hash.is_some() {
+            self.config
+                .poseidon_table
+                .dev_load2_with_hash(
+                    region,
+                    &[wb.bytes.clone()],
+                    assign_delta,
+                    CodeDB::hash_poseidon,
+                )
+                .unwrap();
+        } else {
+            self.config
+                .poseidon_table
+                .dev_load2(region, &[wb.bytes.clone()], assign_delta)
+                .unwrap();
+        }
 
         Ok(new_assign_offset)
     }
@@ -337,17 +396,39 @@ impl<F: Field> WasmChip<F> {
             .section_id_range_table_config
             .load(layouter)
             .unwrap();
-        self.config.range_table_config_0_128.load(layouter).unwrap();
+        self.config.utf8_transition_table_config.load(layouter).unwrap();
+        self.config.byte_set_table_config.load(layouter).unwrap();
+        self.config.opcode_table_config.load(layouter).unwrap();
+        if let Some(fields) = self
+            .config
+            .wasm_import_section_body_chip
+            .as_ref()
+            .and_then(|chip| chip.config.import_whitelist_fields.as_ref())
+        {
+            fields.table.load(layouter).unwrap();
+        }
 
         Ok(())
     }
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        wb_table: Rc<WasmBytecodeTable>,
-        shared_state: Rc<RefCell<SharedState>>,
+        wb_table: Arc<WasmBytecodeTable>,
+        shared_state: SharedStateRef,
+    ) -> WasmConfig<F> {
+        Self::configure_with_options(cs, wb_table, shared_state, WasmCircuitOptions::default())
+    }
+
+    /// Use [`WasmCircuitBuilder`](crate::wasm_circuit::builder::WasmCircuitBuilder) to build
+    /// `options`
+    pub fn configure_with_options(
+        cs: &mut ConstraintSystem<F>,
+        wb_table: Arc<WasmBytecodeTable>,
+        shared_state: SharedStateRef,
+        options: WasmCircuitOptions,
     ) -> WasmConfig<F> {
         let magic_prefix_count = WASM_MAGIC_PREFIX_LEN + WASM_VERSION_PREFIX_LEN;
+        let mut cost_report = CostReport::new(cs);
 
         let bytecode_number = cs.advice_column();
 
@@ -365,170 +446,265 @@ impl<F: Field> WasmChip<F> {
         let body_byte_rev_index_l2 = cs.advice_column();
         let body_item_rev_count_l1 = cs.advice_column();
         let body_item_rev_count_l2 = cs.advice_column();
+        let bytecode_rev_index = cs.advice_column();
 
-        let error_code = cs.advice_column();
+        let error_code = ErrorCodeColumns::configure(cs);
+        cost_report.track("WasmChip", cs);
 
         let range_table_config_0_256 = RangeTableConfig::configure(cs);
         let section_id_range_table_config = RangeTableConfig::configure(cs);
-        let range_table_config_0_128 = Rc::new(RangeTableConfig::configure(cs));
+        cost_report.track("RangeTableConfig", cs);
+        let utf8_transition_table_config = Arc::new(Utf8TransitionTableConfig::configure(cs));
+        cost_report.track("Utf8TransitionTableConfig", cs);
+        let byte_set_table_config = Arc::new(ByteSetTableConfig::configure(cs));
+        cost_report.track("ByteSetTableConfig", cs);
+        let opcode_table_config = Arc::new(OpcodeTableConfig::configure(cs));
+        cost_report.track("OpcodeTableConfig", cs);
         let poseidon_table = PoseidonTable::dev_construct(cs);
+        cost_report.track("PoseidonTable", cs);
 
         let leb128_config = LEB128Chip::configure(cs, &wb_table.value);
-        let mut leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
+        let mut leb128_chip = Arc::new(LEB128Chip::construct(leb128_config));
+        cost_report.track("LEB128Chip", cs);
 
         let utf8_config =
-            UTF8Chip::configure(cs, range_table_config_0_128.clone(), &wb_table.value);
-        let mut utf8_chip = Rc::new(UTF8Chip::construct(utf8_config));
+            UTF8Chip::configure(cs, utf8_transition_table_config.clone(), &wb_table.value);
+        let mut utf8_chip = Arc::new(UTF8Chip::construct(utf8_config));
+        cost_report.track("UTF8Chip", cs);
 
         let config = DynamicIndexesChip::configure(cs, shared_state.clone());
-        let dynamic_indexes_chip = Rc::new(DynamicIndexesChip::construct(config));
+        let dynamic_indexes_chip = Arc::new(DynamicIndexesChip::construct(config));
+        cost_report.track("DynamicIndexesChip", cs);
 
-        let config = WasmTypeSectionItemChip::configure(
-            cs,
-            wb_table.clone(),
-            leb128_chip.clone(),
-            func_count,
-            shared_state.clone(),
-            body_item_rev_count_l2,
-            error_code,
-        );
-        let wasm_type_section_item_chip = Rc::new(WasmTypeSectionItemChip::construct(config));
-        let config = WasmTypeSectionBodyChip::configure(
-            cs,
-            wb_table.clone(),
-            leb128_chip.clone(),
-            wasm_type_section_item_chip.clone(),
-            dynamic_indexes_chip.clone(),
-            func_count,
-            shared_state.clone(),
-            body_item_rev_count_l1,
-            error_code,
-        );
-        let wasm_type_section_body_chip = Rc::new(WasmTypeSectionBodyChip::construct(config));
+        let wasm_type_section_item_chip = if options.is_section_enabled(WasmSection::Type) {
+            let config = WasmTypeSectionItemChip::configure(
+                cs,
+                wb_table.clone(),
+                leb128_chip.clone(),
+                &byte_set_table_config,
+                func_count,
+                shared_state.clone(),
+                body_item_rev_count_l2,
+                error_code,
+                options.max_func_params(),
+                options.max_func_results(),
+            );
+            Some(Arc::new(WasmTypeSectionItemChip::construct(config)))
+        } else {
+            None
+        };
+        cost_report.track("WasmTypeSectionItemChip", cs);
 
-        let config = WasmImportSectionBodyChip::configure(
-            cs,
-            wb_table.clone(),
-            leb128_chip.clone(),
-            utf8_chip.clone(),
-            dynamic_indexes_chip.clone(),
-            func_count,
-            shared_state.clone(),
-            body_byte_rev_index_l2,
-            body_item_rev_count_l1,
-            error_code,
-        );
-        let wasm_import_section_body_chip = Rc::new(WasmImportSectionBodyChip::construct(config));
+        let wasm_type_section_body_chip = if options.is_section_enabled(WasmSection::Type) {
+            let config = WasmTypeSectionBodyChip::configure(
+                cs,
+                wb_table.clone(),
+                leb128_chip.clone(),
+                wasm_type_section_item_chip.clone().unwrap(),
+                dynamic_indexes_chip.clone(),
+                func_count,
+                shared_state.clone(),
+                body_item_rev_count_l1,
+                error_code,
+            );
+            Some(Arc::new(WasmTypeSectionBodyChip::construct(config)))
+        } else {
+            None
+        };
+        cost_report.track("WasmTypeSectionBodyChip", cs);
 
-        let config = WasmFunctionSectionBodyChip::configure(
-            cs,
-            wb_table.clone(),
-            leb128_chip.clone(),
-            func_count,
-            shared_state.clone(),
-            body_item_rev_count_l1,
-            error_code,
-        );
-        let wasm_function_section_body_chip =
-            Rc::new(WasmFunctionSectionBodyChip::construct(config));
+        let wasm_import_section_body_chip = if options.is_section_enabled(WasmSection::Import) {
+            let config = WasmImportSectionBodyChip::configure(
+                cs,
+                wb_table.clone(),
+                leb128_chip.clone(),
+                &byte_set_table_config,
+                utf8_chip.clone(),
+                dynamic_indexes_chip.clone(),
+                func_count,
+                shared_state.clone(),
+                body_byte_rev_index_l2,
+                body_item_rev_count_l1,
+                error_code,
+                options.import_whitelist().cloned(),
+                options.max_import_mod_name_len(),
+                options.max_import_name_len(),
+            );
+            Some(Arc::new(WasmImportSectionBodyChip::construct(config)))
+        } else {
+            None
+        };
+        cost_report.track("WasmImportSectionBodyChip", cs);
 
-        let config = WasmMemorySectionBodyChip::configure(
-            cs,
-            wb_table.clone(),
-            leb128_chip.clone(),
-            dynamic_indexes_chip.clone(),
-            func_count,
-            shared_state.clone(),
-            body_item_rev_count_l1,
-            error_code,
-            bytecode_number,
-        );
-        let wasm_memory_section_body_chip = Rc::new(WasmMemorySectionBodyChip::construct(config));
+        let wasm_function_section_body_chip = if options.is_section_enabled(WasmSection::Function) {
+            let config = WasmFunctionSectionBodyChip::configure(
+                cs,
+                wb_table.clone(),
+                leb128_chip.clone(),
+                func_count,
+                shared_state.clone(),
+                body_item_rev_count_l1,
+                error_code,
+            );
+            Some(Arc::new(WasmFunctionSectionBodyChip::construct(config)))
+        } else {
+            None
+        };
+        cost_report.track("WasmFunctionSectionBodyChip", cs);
 
-        let config = WasmExportSectionBodyChip::configure(
-            cs,
-            wb_table.clone(),
-            leb128_chip.clone(),
-            func_count,
-            shared_state.clone(),
-            body_byte_rev_index_l2,
-            body_item_rev_count_l1,
-            error_code,
-        );
-        let wasm_export_section_body_chip = Rc::new(WasmExportSectionBodyChip::construct(config));
+        let wasm_memory_section_body_chip = if options.is_section_enabled(WasmSection::Memory) {
+            let config = WasmMemorySectionBodyChip::configure(
+                cs,
+                wb_table.clone(),
+                leb128_chip.clone(),
+                &byte_set_table_config,
+                dynamic_indexes_chip.clone(),
+                func_count,
+                shared_state.clone(),
+                body_item_rev_count_l1,
+                error_code,
+                bytecode_number,
+                options.max_memory_pages(),
+            );
+            Some(Arc::new(WasmMemorySectionBodyChip::construct(config)))
+        } else {
+            None
+        };
+        cost_report.track("WasmMemorySectionBodyChip", cs);
 
-        let config = WasmDataSectionBodyChip::configure(
-            cs,
-            wb_table.clone(),
-            leb128_chip.clone(),
-            dynamic_indexes_chip.clone(),
-            func_count,
-            shared_state.clone(),
-            body_byte_rev_index_l2,
-            body_item_rev_count_l1,
-            error_code,
-            bytecode_number,
-        );
-        let wasm_data_section_body_chip = Rc::new(WasmDataSectionBodyChip::construct(config));
+        let wasm_export_section_body_chip = if options.is_section_enabled(WasmSection::Export) {
+            let config = WasmExportSectionBodyChip::configure(
+                cs,
+                wb_table.clone(),
+                leb128_chip.clone(),
+                &byte_set_table_config,
+                utf8_chip.clone(),
+                func_count,
+                shared_state.clone(),
+                body_byte_rev_index_l2,
+                body_item_rev_count_l1,
+                error_code,
+                options.reject_exported_mutable_globals(),
+            );
+            Some(Arc::new(WasmExportSectionBodyChip::construct(config)))
+        } else {
+            None
+        };
+        cost_report.track("WasmExportSectionBodyChip", cs);
 
-        let config = WasmGlobalSectionBodyChip::configure(
-            cs,
-            wb_table.clone(),
-            leb128_chip.clone(),
-            dynamic_indexes_chip.clone(),
-            func_count,
-            shared_state.clone(),
-            body_item_rev_count_l1,
-            error_code,
-            bytecode_number,
-        );
-        let wasm_global_section_body_chip = Rc::new(WasmGlobalSectionBodyChip::construct(config));
+        let wasm_data_section_body_chip = if options.is_section_enabled(WasmSection::Data) {
+            let config = WasmDataSectionBodyChip::configure(
+                cs,
+                wb_table.clone(),
+                leb128_chip.clone(),
+                &byte_set_table_config,
+                dynamic_indexes_chip.clone(),
+                func_count,
+                shared_state.clone(),
+                body_byte_rev_index_l2,
+                body_item_rev_count_l1,
+                error_code,
+                bytecode_number,
+                options.max_data_segment_size(),
+                options.max_data_size(),
+            );
+            Some(Arc::new(WasmDataSectionBodyChip::construct(config)))
+        } else {
+            None
+        };
+        cost_report.track("WasmDataSectionBodyChip", cs);
 
-        let config = WasmCodeSectionBodyChip::configure(
-            cs,
-            wb_table.clone(),
-            leb128_chip.clone(),
-            dynamic_indexes_chip.clone(),
-            func_count,
-            shared_state.clone(),
-            body_byte_rev_index_l2,
-            body_item_rev_count_l1,
-            error_code,
-            bytecode_number,
-        );
-        let wasm_code_section_body_chip = Rc::new(WasmCodeSectionBodyChip::construct(config));
+        let wasm_global_section_body_chip = if options.is_section_enabled(WasmSection::Global) {
+            let config = WasmGlobalSectionBodyChip::configure(
+                cs,
+                wb_table.clone(),
+                leb128_chip.clone(),
+                &byte_set_table_config,
+                dynamic_indexes_chip.clone(),
+                func_count,
+                shared_state.clone(),
+                body_item_rev_count_l1,
+                error_code,
+                bytecode_number,
+            );
+            Some(Arc::new(WasmGlobalSectionBodyChip::construct(config)))
+        } else {
+            None
+        };
+        cost_report.track("WasmGlobalSectionBodyChip", cs);
 
-        let config = WasmStartSectionBodyChip::configure(
-            cs,
-            wb_table.clone(),
-            leb128_chip.clone(),
-            func_count,
-            shared_state.clone(),
-            error_code,
-        );
-        let wasm_start_section_body_chip = Rc::new(WasmStartSectionBodyChip::construct(config));
+        let wasm_code_section_body_chip = if options.is_section_enabled(WasmSection::Code) {
+            let config = WasmCodeSectionBodyChip::configure(
+                cs,
+                wb_table.clone(),
+                leb128_chip.clone(),
+                &opcode_table_config,
+                dynamic_indexes_chip.clone(),
+                func_count,
+                shared_state.clone(),
+                body_byte_rev_index_l2,
+                body_item_rev_count_l1,
+                error_code,
+                bytecode_number,
+                options.max_block_level(),
+                options.max_func_body_size(),
+                options.max_func_locals(),
+                options.skip_simd_instructions(),
+            );
+            Some(Arc::new(WasmCodeSectionBodyChip::construct(config)))
+        } else {
+            None
+        };
+        cost_report.track("WasmCodeSectionBodyChip", cs);
 
-        let config = WasmElementSectionBodyChip::configure(
-            cs,
-            wb_table.clone(),
-            leb128_chip.clone(),
-            func_count,
-            shared_state.clone(),
-            body_item_rev_count_l1,
-            error_code,
-        );
-        let wasm_element_section_body_chip = Rc::new(WasmElementSectionBodyChip::construct(config));
+        let wasm_start_section_body_chip = if options.is_section_enabled(WasmSection::Start) {
+            let config = WasmStartSectionBodyChip::configure(
+                cs,
+                wb_table.clone(),
+                leb128_chip.clone(),
+                func_count,
+                shared_state.clone(),
+                error_code,
+            );
+            Some(Arc::new(WasmStartSectionBodyChip::construct(config)))
+        } else {
+            None
+        };
+        cost_report.track("WasmStartSectionBodyChip", cs);
 
-        let config = WasmTableSectionBodyChip::configure(
-            cs,
-            wb_table.clone(),
-            leb128_chip.clone(),
-            dynamic_indexes_chip.clone(),
-            func_count,
-            error_code,
-            shared_state.clone(),
-        );
-        let wasm_table_section_body_chip = Rc::new(WasmTableSectionBodyChip::construct(config));
+        let wasm_element_section_body_chip = if options.is_section_enabled(WasmSection::Element) {
+            let config = WasmElementSectionBodyChip::configure(
+                cs,
+                wb_table.clone(),
+                leb128_chip.clone(),
+                &byte_set_table_config,
+                func_count,
+                shared_state.clone(),
+                body_item_rev_count_l1,
+                error_code,
+            );
+            Some(Arc::new(WasmElementSectionBodyChip::construct(config)))
+        } else {
+            None
+        };
+        cost_report.track("WasmElementSectionBodyChip", cs);
+
+        let wasm_table_section_body_chip = if options.is_section_enabled(WasmSection::Table) {
+            let config = WasmTableSectionBodyChip::configure(
+                cs,
+                wb_table.clone(),
+                leb128_chip.clone(),
+                &byte_set_table_config,
+                dynamic_indexes_chip.clone(),
+                func_count,
+                error_code,
+                shared_state.clone(),
+            );
+            Some(Arc::new(WasmTableSectionBodyChip::construct(config)))
+        } else {
+            None
+        };
+        cost_report.track("WasmTableSectionBodyChip", cs);
 
         let mut index_at_magic_prefix: Vec<IsZeroChip<F>> = Vec::new();
         for index in 0..magic_prefix_count {
@@ -548,6 +724,8 @@ impl<F: Field> WasmChip<F> {
             index_at_magic_prefix.push(chip);
         }
 
+        cost_report.track("IsZeroChip", cs);
+
         Self::configure_len_prefixed_bytes_span_checks(
             cs,
             leb128_chip.as_ref(),
@@ -579,12 +757,46 @@ impl<F: Field> WasmChip<F> {
         Self::configure_error_code(cs, q_enable, q_first, q_last, error_code);
 
         Self::configure_bytecode_number(cs, q_enable, q_first, q_last, bytecode_number);
+        if wb_table.zero_row_enabled {
+            // bytecodes are separated by a zero row, so the 'q_last && next.q_first' check of the
+            // bytecode_number gate never fires
+            cs.create_gate("bytecode_number grows across the zero row", |vc| {
+                let mut cb = BaseConstraintBuilder::default();
+
+                let q_enable_expr = vc.query_fixed(q_enable, Rotation::cur());
+                let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
+                let q_last_before_zero_row_expr = vc.query_fixed(q_last, Rotation(-2));
+                let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+                let bytecode_number_before_zero_row_expr =
+                    vc.query_advice(bytecode_number, Rotation(-2));
+
+                cb.condition(and::expr([q_first_expr, q_last_before_zero_row_expr]), |cb| {
+                    cb.require_equal(
+                        "q_first && prev.prev.q_last => bytecode_number=prev.prev.bytecode_number+1",
+                        bytecode_number_expr,
+                        bytecode_number_before_zero_row_expr + 1.expr(),
+                    );
+                });
+
+                cb.gate(q_enable_expr)
+            });
+        }
+
+        if let Some(poseidon_code_hash) = wb_table.poseidon_code_hash {
+            Self::configure_poseidon_code_hash(
+                cs,
+                q_enable,
+                q_first,
+                poseidon_code_hash,
+                &poseidon_table,
+            );
+        }
 
         cs.lookup("all bytecode values are byte values", |vc| {
             let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                 vc,
                 q_enable,
-                &shared_state.borrow(),
+                &shared_state.read(),
                 error_code,
             );
 
@@ -601,7 +813,7 @@ impl<F: Field> WasmChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let q_enable_expr =
@@ -632,7 +844,7 @@ impl<F: Field> WasmChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
@@ -653,13 +865,15 @@ impl<F: Field> WasmChip<F> {
             });
         }
 
+        cost_report.track("WasmChip", cs);
+
         let section_id_lt_chip_config = LtChip::configure(
             cs,
             |vc| {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
@@ -671,6 +885,61 @@ impl<F: Field> WasmChip<F> {
             |vc| vc.query_advice(section_id, Rotation::cur()),
         );
         let section_id_lt_chip = LtChip::construct(section_id_lt_chip_config);
+        cost_report.track("LtChip", cs);
+
+        let section_len_lt_chip_config = LtChip::configure(
+            cs,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                );
+
+                and::expr([
+                    q_enable_expr,
+                    vc.query_fixed(is_section_len, Rotation::cur()),
+                    vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur()),
+                ])
+            },
+            |vc| vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
+            |vc| vc.query_advice(bytecode_rev_index, Rotation::cur()) + 1.expr(),
+        );
+        let section_len_lt_chip = LtChip::construct(section_len_lt_chip_config);
+        cost_report.track("LtChip", cs);
+
+        let value_inv = cs.advice_column();
+        let section_id_is_custom_config = IsZeroChip::configure(
+            cs,
+            |vc| {
+                vc.query_fixed(q_enable, Rotation::cur())
+                    * (vc.query_fixed(is_section_id, Rotation::cur())
+                        + vc.query_fixed(is_section_len, Rotation::cur())
+                        + vc.query_fixed(is_section_body, Rotation::cur()))
+            },
+            |vc| vc.query_advice(section_id, Rotation::cur()),
+            value_inv,
+        );
+        let section_id_is_custom_chip = IsZeroChip::construct(section_id_is_custom_config);
+        cost_report.track("IsZeroChip", cs);
+
+        let section_body_chips_q_enable: Vec<Column<Fixed>> = [
+            wasm_type_section_body_chip.as_ref().map(|c| c.config.selectors.q_enable),
+            wasm_import_section_body_chip.as_ref().map(|c| c.config.selectors.q_enable),
+            wasm_function_section_body_chip.as_ref().map(|c| c.config.selectors.q_enable),
+            wasm_memory_section_body_chip.as_ref().map(|c| c.config.selectors.q_enable),
+            wasm_export_section_body_chip.as_ref().map(|c| c.config.selectors.q_enable),
+            wasm_data_section_body_chip.as_ref().map(|c| c.config.selectors.q_enable),
+            wasm_global_section_body_chip.as_ref().map(|c| c.config.selectors.q_enable),
+            wasm_code_section_body_chip.as_ref().map(|c| c.config.selectors.q_enable),
+            wasm_start_section_body_chip.as_ref().map(|c| c.config.selectors.q_enable),
+            wasm_table_section_body_chip.as_ref().map(|c| c.config.selectors.q_enable),
+            wasm_element_section_body_chip.as_ref().map(|c| c.config.selectors.q_enable),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
 
         cs.create_gate("WasmCircuit gate", |vc| {
             let mut cb = BaseConstraintBuilder::default();
@@ -678,7 +947,7 @@ impl<F: Field> WasmChip<F> {
             let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                 vc,
                 q_enable,
-                &shared_state.borrow(),
+                &shared_state.read(),
                 error_code,
             );
             let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
@@ -702,11 +971,20 @@ impl<F: Field> WasmChip<F> {
 
             let section_id_expr = vc.query_advice(section_id, Rotation::cur());
             let section_id_prev_expr = vc.query_advice(section_id, Rotation::prev());
+            let section_id_is_custom_expr = section_id_is_custom_chip.config().expr();
+            let section_id_is_custom_inv_prev_expr =
+                vc.query_advice(section_id_is_custom_chip.config().value_inv, Rotation::prev());
+            let section_id_prev_is_custom_expr =
+                1.expr() - section_id_prev_expr.clone() * section_id_is_custom_inv_prev_expr;
 
             let leb128_is_last_byte_expr =
                 vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
 
-            let wb_table_code_hash = vc.query_advice(wb_table.code_hash, Rotation::cur());
+            // poseidon table ids are poseidon code hashes when the column is configured
+            let wb_table_code_hash = vc.query_advice(
+                wb_table.poseidon_code_hash.unwrap_or(wb_table.code_hash),
+                Rotation::cur(),
+            );
             let poseidon_table_hash_id = vc.query_advice(poseidon_table.hash_id, Rotation::cur());
 
             cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
@@ -738,6 +1016,21 @@ impl<F: Field> WasmChip<F> {
                 and::expr([q_first_expr.clone(), index_val_expr.clone()]),
             );
 
+            let bytecode_rev_index_expr = vc.query_advice(bytecode_rev_index, Rotation::cur());
+            cb.condition(q_last_expr.clone(), |cb| {
+                cb.require_zero("q_last => bytecode_rev_index=0", bytecode_rev_index_expr.clone());
+            });
+            cb.condition(
+                and::expr([not_q_first_expr.clone(), not_q_last_expr.clone()]),
+                |cb| {
+                    cb.require_equal(
+                        "not_q_first && not_q_last => bytecode_rev_index=next.bytecode_rev_index+1",
+                        bytecode_rev_index_expr.clone(),
+                        vc.query_advice(bytecode_rev_index, Rotation::next()) + 1.expr(),
+                    );
+                },
+            );
+
             let mut is_index_at_magic_prefix_expr = index_at_magic_prefix.iter()
                 .fold(0.expr(), |acc, x| { acc.clone() + x.config().expr() });
 
@@ -788,49 +1081,14 @@ impl<F: Field> WasmChip<F> {
             cb.condition(is_section_body_expr.clone(), |cb| {
                 cb.require_equal(
                     "is_section_body -> exactly one section chip is enabled",
-                    vc.query_fixed(wasm_type_section_body_chip.config.q_enable, Rotation::cur())
-                        + vc.query_fixed(
-                        wasm_import_section_body_chip.config.q_enable,
-                        Rotation::cur(),
-                    )
-                        + vc.query_fixed(
-                        wasm_function_section_body_chip.config.q_enable,
-                        Rotation::cur(),
-                    )
-                        + vc.query_fixed(
-                        wasm_memory_section_body_chip.config.q_enable,
-                        Rotation::cur(),
-                    )
-                        + vc.query_fixed(
-                        wasm_export_section_body_chip.config.q_enable,
-                        Rotation::cur(),
-                    )
-                        + vc.query_fixed(
-                        wasm_data_section_body_chip.config.q_enable,
-                        Rotation::cur(),
-                    )
-                        + vc.query_fixed(
-                        wasm_global_section_body_chip.config.q_enable,
-                        Rotation::cur(),
-                    )
-                        + vc.query_fixed(
-                        wasm_code_section_body_chip.config.q_enable,
-                        Rotation::cur(),
-                    )
-                        + vc.query_fixed(
-                        wasm_start_section_body_chip.config.q_enable,
-                        Rotation::cur(),
-                    )
-                        + vc.query_fixed(
-                        wasm_table_section_body_chip.config.q_enable,
-                        Rotation::cur(),
-                    )
-                        + vc.query_fixed(
-                        wasm_element_section_body_chip.config.q_enable,
-                        Rotation::cur(),
-                    )
+                    section_body_chips_q_enable
+                        .iter()
+                        .fold(0.expr(), |acc, q_enable| {
+                            acc + vc.query_fixed(*q_enable, Rotation::cur())
+                        })
                         + is_section_id_expr.clone()
-                        + is_section_len_expr.clone(),
+                        + is_section_len_expr.clone()
+                        + section_id_is_custom_expr.clone(),
                     1.expr(),
                 );
             });
@@ -838,19 +1096,26 @@ impl<F: Field> WasmChip<F> {
             cb.condition(q_first_expr.clone(), |cb| {
                 cb.require_zero("q_first => func_count=0", func_count_expr.clone());
             });
-            let importdesc_type_is_typeidx_expr = and::expr([
-                vc.query_fixed(
-                    wasm_import_section_body_chip.config.is_importdesc_type,
-                    Rotation::cur(),
-                ),
-                wasm_import_section_body_chip
-                    .config
-                    .importdesc_type_chip
-                    .config
-                    .value_equals(ImportDescType::Typeidx, Rotation::cur())(vc),
-            ]);
-            let wasm_code_section_q_first_expr =
-                vc.query_fixed(wasm_code_section_body_chip.config.q_first, Rotation::cur());
+            let importdesc_type_is_typeidx_expr = match &wasm_import_section_body_chip {
+                Some(wasm_import_section_body_chip) => and::expr([
+                    vc.query_fixed(
+                        wasm_import_section_body_chip.config.is_importdesc_type,
+                        Rotation::cur(),
+                    ),
+                    wasm_import_section_body_chip
+                        .config
+                        .importdesc_type_chip
+                        .config
+                        .value_equals(ImportDescType::Typeidx, Rotation::cur())(vc),
+                ]),
+                None => 0.expr(),
+            };
+            let wasm_code_section_q_first_expr = match &wasm_code_section_body_chip {
+                Some(wasm_code_section_body_chip) => {
+                    vc.query_fixed(wasm_code_section_body_chip.config.selectors.q_first, Rotation::cur())
+                }
+                None => 0.expr(),
+            };
             let not_func_count_inc_expr = and::expr([
                 not::expr(importdesc_type_is_typeidx_expr.clone()),
                 not::expr(wasm_code_section_q_first_expr.clone()),
@@ -874,18 +1139,20 @@ impl<F: Field> WasmChip<F> {
                     func_count_expr.clone(),
                 );
             });
-            cb.condition(wasm_code_section_q_first_expr.clone(), |cb| {
-                let func_count_prev_expr = vc.query_advice(func_count, Rotation::prev());
-                let wasm_code_section_leb128_sn_expr = vc.query_advice(
-                    wasm_code_section_body_chip.config.leb128_chip.config.sn,
-                    Rotation::cur(),
-                );
-                cb.require_equal(
-                    "wasm_code_section_q_first => func_count grew by specific number",
-                    func_count_prev_expr.clone() + wasm_code_section_leb128_sn_expr.clone(),
-                    func_count_expr.clone(),
-                );
-            });
+            if let Some(wasm_code_section_body_chip) = &wasm_code_section_body_chip {
+                cb.condition(wasm_code_section_q_first_expr.clone(), |cb| {
+                    let func_count_prev_expr = vc.query_advice(func_count, Rotation::prev());
+                    let wasm_code_section_leb128_sn_expr = vc.query_advice(
+                        wasm_code_section_body_chip.config.leb128_chip.config.sn,
+                        Rotation::cur(),
+                    );
+                    cb.require_equal(
+                        "wasm_code_section_q_first => func_count grew by specific number",
+                        func_count_prev_expr.clone() + wasm_code_section_leb128_sn_expr.clone(),
+                        func_count_expr.clone(),
+                    );
+                });
+            }
 
             // wasm section layout check
             cb.condition(
@@ -899,6 +1166,16 @@ impl<F: Field> WasmChip<F> {
                     )
                 }
             );
+            cb.condition(
+                and::expr([is_section_len_expr.clone(), leb128_is_last_byte_expr.clone()]),
+                |cb| {
+                    cb.require_equal(
+                        "is_section_len && leb128_is_last_byte => section_len<=bytecode_rev_index",
+                        section_len_lt_chip.config().is_lt(vc, None),
+                        1.expr(),
+                    );
+                },
+            );
             // section+(is_section_id{1} -> is_section_len+ -> is_section_body+)
             configure_transition_check(
                 &mut cb,
@@ -962,13 +1239,29 @@ impl<F: Field> WasmChip<F> {
                 );
             }
 
-            cb.condition(not_q_first_expr.clone(), |cb| {
-                cb.require_zero(
-                    "prev.section_id <= cur.section_id",
-                    (section_id_lt_chip.config().is_lt(vc, None) - 1.expr())
-                        * (section_id_expr.clone() - section_id_prev_expr.clone()),
-                );
-            });
+            cb.condition(
+                and::expr([not_q_first_expr.clone(), not::expr(section_id_is_custom_expr.clone())]),
+                |cb| {
+                    cb.require_zero(
+                        "!is_custom_section -> prev.section_id <= cur.section_id",
+                        (section_id_lt_chip.config().is_lt(vc, None) - 1.expr())
+                            * (section_id_expr.clone() - section_id_prev_expr.clone()),
+                    );
+                },
+            );
+            cb.condition(
+                and::expr([
+                    not_q_first_expr.clone(),
+                    vc.query_fixed(is_section_body, Rotation::prev()),
+                    section_id_prev_is_custom_expr.clone(),
+                ]),
+                |cb| {
+                    cb.require_zero(
+                        "prev.is_section_body && prev.is_custom_section -> cur.is_custom_section",
+                        section_id_expr.clone(),
+                    );
+                },
+            );
 
             // code_hash check
             // TODO refactor
@@ -985,7 +1278,7 @@ impl<F: Field> WasmChip<F> {
             let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                 vc,
                 q_enable,
-                &shared_state.borrow(),
+                &shared_state.read(),
                 error_code,
             );
 
@@ -998,224 +1291,294 @@ impl<F: Field> WasmChip<F> {
         });
 
         // start section crosschecks
-        dynamic_indexes_chip.lookup_args("start section: func index refs are valid", cs, |vc| {
-            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
-                vc,
-                q_enable,
-                &shared_state.borrow(),
-                error_code,
-            );
-            let cond = vc.query_fixed(
-                wasm_start_section_body_chip.config.is_func_index,
-                Rotation::cur(),
-            ) * q_enable_expr;
-            let sn_expr = vc.query_advice(
-                wasm_start_section_body_chip.config.leb128_chip.config.sn,
-                Rotation::cur(),
-            );
-            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
-
-            LookupArgsParams {
-                cond,
-                bytecode_number: bytecode_number_expr,
-                index: sn_expr,
-                tag: Tag::FuncIndex.expr(),
-                is_terminator: false.expr(),
-            }
-        });
-        // import section crosschecks
-        dynamic_indexes_chip.lookup_args("import section: typeidx refs are valid", cs, |vc| {
-            let cond = and::expr([
-                vc.query_fixed(
-                    wasm_import_section_body_chip.config.is_importdesc_type,
-                    Rotation::cur(),
-                ),
-                wasm_import_section_body_chip
-                    .config
-                    .importdesc_type_chip
-                    .config
-                    .value_equals(ImportDescType::Typeidx, Rotation::cur())(vc),
-            ]);
-            let cond = cond
-                * Self::get_selector_expr_enriched_with_error_processing(
+        cost_report.track("WasmChip", cs);
+
+        if let Some(wasm_start_section_body_chip) = &wasm_start_section_body_chip {
+            dynamic_indexes_chip.lookup_args("start section: func index refs are valid", cs, |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
-            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
-
-            LookupArgsParams {
-                cond,
-                bytecode_number: bytecode_number_expr,
-                index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
-                tag: Tag::TypeIndex.expr(),
-                is_terminator: false.expr(),
-            }
-        });
-        // export section crosschecks
-        dynamic_indexes_chip.lookup_args("export section: funcidx refs are valid", cs, |vc| {
-            let cond = and::expr([
-                vc.query_fixed(
-                    wasm_export_section_body_chip.config.is_exportdesc_type,
+                let cond = vc.query_fixed(
+                    wasm_start_section_body_chip.config.is_func_index,
                     Rotation::cur(),
-                ),
-                wasm_export_section_body_chip
-                    .config
-                    .exportdesc_type_chip
-                    .config
-                    .value_equals(ExportDescType::Funcidx, Rotation::cur())(vc),
-            ]);
-            let cond = cond
-                * Self::get_selector_expr_enriched_with_error_processing(
-                    vc,
-                    q_enable,
-                    &shared_state.borrow(),
-                    error_code,
-                );
-            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
-
-            LookupArgsParams {
-                cond,
-                bytecode_number: bytecode_number_expr,
-                index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
-                tag: Tag::TypeIndex.expr(),
-                is_terminator: false.expr(),
-            }
-        });
-        dynamic_indexes_chip.lookup_args("export section: tableidx refs are valid", cs, |vc| {
-            let cond = and::expr([
-                vc.query_fixed(
-                    wasm_export_section_body_chip.config.is_exportdesc_type,
+                ) * q_enable_expr;
+                let sn_expr = vc.query_advice(
+                    wasm_start_section_body_chip.config.leb128_chip.config.sn,
                     Rotation::cur(),
-                ),
-                wasm_export_section_body_chip
-                    .config
-                    .exportdesc_type_chip
-                    .config
-                    .value_equals(ExportDescType::Tableidx, Rotation::cur())(vc),
-            ]);
-            let cond = cond
-                * Self::get_selector_expr_enriched_with_error_processing(
-                    vc,
-                    q_enable,
-                    &shared_state.borrow(),
-                    error_code,
                 );
-            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
-
-            LookupArgsParams {
-                cond,
-                bytecode_number: bytecode_number_expr,
-                index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
-                tag: Tag::TableIndex.expr(),
-                is_terminator: false.expr(),
-            }
-        });
-        dynamic_indexes_chip.lookup_args("export section: memidx refs are valid", cs, |vc| {
-            let cond = and::expr([
-                vc.query_fixed(
-                    wasm_export_section_body_chip.config.is_exportdesc_type,
-                    Rotation::cur(),
-                ),
-                wasm_export_section_body_chip
-                    .config
-                    .exportdesc_type_chip
-                    .config
-                    .value_equals(ExportDescType::Memidx, Rotation::cur())(vc),
-            ]);
-            let cond = cond
-                * Self::get_selector_expr_enriched_with_error_processing(
-                    vc,
-                    q_enable,
-                    &shared_state.borrow(),
-                    error_code,
-                );
-            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
-
-            LookupArgsParams {
-                cond,
-                bytecode_number: bytecode_number_expr,
-                index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
-                tag: Tag::MemIndex.expr(),
-                is_terminator: false.expr(),
-            }
-        });
-        dynamic_indexes_chip.lookup_args("export section: globalidx refs are valid", cs, |vc| {
-            let cond = and::expr([
-                vc.query_fixed(
-                    wasm_export_section_body_chip.config.is_exportdesc_type,
-                    Rotation::cur(),
-                ),
-                wasm_export_section_body_chip
-                    .config
-                    .exportdesc_type_chip
-                    .config
-                    .value_equals(ExportDescType::Globalidx, Rotation::cur())(vc),
-            ]);
-            let cond = cond
-                * Self::get_selector_expr_enriched_with_error_processing(
-                    vc,
-                    q_enable,
-                    &shared_state.borrow(),
-                    error_code,
-                );
-            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
-
-            LookupArgsParams {
-                cond,
-                bytecode_number: bytecode_number_expr,
-                index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
-                tag: Tag::GlobalIndex.expr(),
-                is_terminator: false.expr(),
+                let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+
+                LookupArgsParams {
+                    cond,
+                    bytecode_number: bytecode_number_expr,
+                    index: sn_expr,
+                    tag: Tag::FuncIndex.expr(),
+                    is_terminator: false.expr(),
+                }
+            });
+        }
+        // import section crosschecks
+        if let Some(wasm_import_section_body_chip) = &wasm_import_section_body_chip {
+            dynamic_indexes_chip.lookup_args("import section: typeidx refs are valid", cs, |vc| {
+                let cond = and::expr([
+                    vc.query_fixed(
+                        wasm_import_section_body_chip.config.is_importdesc_type,
+                        Rotation::cur(),
+                    ),
+                    wasm_import_section_body_chip
+                        .config
+                        .importdesc_type_chip
+                        .config
+                        .value_equals(ImportDescType::Typeidx, Rotation::cur())(vc),
+                ]);
+                let cond = cond
+                    * Self::get_selector_expr_enriched_with_error_processing(
+                        vc,
+                        q_enable,
+                        &shared_state.read(),
+                        error_code,
+                    );
+                let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+
+                LookupArgsParams {
+                    cond,
+                    bytecode_number: bytecode_number_expr,
+                    index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
+                    tag: Tag::TypeIndex.expr(),
+                    is_terminator: false.expr(),
+                }
+            });
+        }
+        // export section crosschecks
+        if let Some(wasm_export_section_body_chip) = &wasm_export_section_body_chip {
+            dynamic_indexes_chip.lookup_args("export section: funcidx refs are valid", cs, |vc| {
+                let cond = and::expr([
+                    vc.query_fixed(
+                        wasm_export_section_body_chip.config.is_exportdesc_type,
+                        Rotation::cur(),
+                    ),
+                    wasm_export_section_body_chip
+                        .config
+                        .exportdesc_type_chip
+                        .config
+                        .value_equals(ExportDescType::Funcidx, Rotation::cur())(vc),
+                ]);
+                let cond = cond
+                    * Self::get_selector_expr_enriched_with_error_processing(
+                        vc,
+                        q_enable,
+                        &shared_state.read(),
+                        error_code,
+                    );
+                let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+
+                LookupArgsParams {
+                    cond,
+                    bytecode_number: bytecode_number_expr,
+                    index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
+                    tag: Tag::TypeIndex.expr(),
+                    is_terminator: false.expr(),
+                }
+            });
+        }
+        if let Some(wasm_export_section_body_chip) = &wasm_export_section_body_chip {
+            dynamic_indexes_chip.lookup_args("export section: tableidx refs are valid", cs, |vc| {
+                let cond = and::expr([
+                    vc.query_fixed(
+                        wasm_export_section_body_chip.config.is_exportdesc_type,
+                        Rotation::cur(),
+                    ),
+                    wasm_export_section_body_chip
+                        .config
+                        .exportdesc_type_chip
+                        .config
+                        .value_equals(ExportDescType::Tableidx, Rotation::cur())(vc),
+                ]);
+                let cond = cond
+                    * Self::get_selector_expr_enriched_with_error_processing(
+                        vc,
+                        q_enable,
+                        &shared_state.read(),
+                        error_code,
+                    );
+                let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+
+                LookupArgsParams {
+                    cond,
+                    bytecode_number: bytecode_number_expr,
+                    index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
+                    tag: Tag::TableIndex.expr(),
+                    is_terminator: false.expr(),
+                }
+            });
+        }
+        if let Some(wasm_export_section_body_chip) = &wasm_export_section_body_chip {
+            dynamic_indexes_chip.lookup_args("export section: memidx refs are valid", cs, |vc| {
+                let cond = and::expr([
+                    vc.query_fixed(
+                        wasm_export_section_body_chip.config.is_exportdesc_type,
+                        Rotation::cur(),
+                    ),
+                    wasm_export_section_body_chip
+                        .config
+                        .exportdesc_type_chip
+                        .config
+                        .value_equals(ExportDescType::Memidx, Rotation::cur())(vc),
+                ]);
+                let cond = cond
+                    * Self::get_selector_expr_enriched_with_error_processing(
+                        vc,
+                        q_enable,
+                        &shared_state.read(),
+                        error_code,
+                    );
+                let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+
+                LookupArgsParams {
+                    cond,
+                    bytecode_number: bytecode_number_expr,
+                    index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
+                    tag: Tag::MemIndex.expr(),
+                    is_terminator: false.expr(),
+                }
+            });
+        }
+        if let Some(wasm_export_section_body_chip) = &wasm_export_section_body_chip {
+            dynamic_indexes_chip.lookup_args("export section: globalidx refs are valid", cs, |vc| {
+                let cond = and::expr([
+                    vc.query_fixed(
+                        wasm_export_section_body_chip.config.is_exportdesc_type,
+                        Rotation::cur(),
+                    ),
+                    wasm_export_section_body_chip
+                        .config
+                        .exportdesc_type_chip
+                        .config
+                        .value_equals(ExportDescType::Globalidx, Rotation::cur())(vc),
+                ]);
+                let cond = cond
+                    * Self::get_selector_expr_enriched_with_error_processing(
+                        vc,
+                        q_enable,
+                        &shared_state.read(),
+                        error_code,
+                    );
+                let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+
+                LookupArgsParams {
+                    cond,
+                    bytecode_number: bytecode_number_expr,
+                    index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
+                    tag: Tag::GlobalIndex.expr(),
+                    is_terminator: false.expr(),
+                }
+            });
+        }
+        if let (Some(wasm_export_section_body_chip), Some(wasm_global_section_body_chip)) =
+            (&wasm_export_section_body_chip, &wasm_global_section_body_chip)
+        {
+            if wasm_export_section_body_chip.config.reject_exported_mutable_globals {
+                cs.lookup_any("export section: exported globals are immutable", |vc| {
+                    let cond = and::expr([
+                        vc.query_fixed(
+                            wasm_export_section_body_chip.config.is_exportdesc_type,
+                            Rotation::cur(),
+                        ),
+                        wasm_export_section_body_chip
+                            .config
+                            .exportdesc_type_chip
+                            .config
+                            .value_equals(ExportDescType::Globalidx, Rotation::cur())(vc),
+                    ]);
+                    let cond = cond
+                        * Self::get_selector_expr_enriched_with_error_processing(
+                            vc,
+                            q_enable,
+                            &shared_state.read(),
+                            error_code,
+                        );
+                    // mutability byte of a const global
+                    let is_const_global_expr = vc.query_fixed(
+                        wasm_global_section_body_chip.config.selectors.q_enable,
+                        Rotation::cur(),
+                    ) * vc.query_fixed(
+                        wasm_global_section_body_chip.config.is_mut_prop,
+                        Rotation::cur(),
+                    ) * (Mutability::Var.expr()
+                        - vc.query_advice(wb_table.value, Rotation::cur()));
+                    let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+
+                    vec![
+                        (cond.clone(), is_const_global_expr.clone()),
+                        (
+                            cond.clone() * bytecode_number_expr.clone(),
+                            is_const_global_expr.clone() * bytecode_number_expr,
+                        ),
+                        (
+                            cond * vc.query_advice(leb128_chip.config.sn, Rotation::next()),
+                            is_const_global_expr
+                                * vc.query_advice(
+                                    wasm_global_section_body_chip.config.global_index,
+                                    Rotation::cur(),
+                                ),
+                        ),
+                    ]
+                });
             }
-        });
+        }
         // func section crosschecks
-        dynamic_indexes_chip.lookup_args("function section: funcidx refs are valid", cs, |vc| {
-            let cond = and::expr([vc.query_fixed(
-                wasm_function_section_body_chip.config.is_typeidx,
-                Rotation::cur(),
-            )]);
-            let cond = cond
-                * Self::get_selector_expr_enriched_with_error_processing(
-                    vc,
-                    q_enable,
-                    &shared_state.borrow(),
-                    error_code,
-                );
-            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
-
-            LookupArgsParams {
-                cond,
-                bytecode_number: bytecode_number_expr,
-                index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
-                tag: Tag::TypeIndex.expr(),
-                is_terminator: false.expr(),
-            }
-        });
+        if let Some(wasm_function_section_body_chip) = &wasm_function_section_body_chip {
+            dynamic_indexes_chip.lookup_args("function section: funcidx refs are valid", cs, |vc| {
+                let cond = and::expr([vc.query_fixed(
+                    wasm_function_section_body_chip.config.is_typeidx,
+                    Rotation::cur(),
+                )]);
+                let cond = cond
+                    * Self::get_selector_expr_enriched_with_error_processing(
+                        vc,
+                        q_enable,
+                        &shared_state.read(),
+                        error_code,
+                    );
+                let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+
+                LookupArgsParams {
+                    cond,
+                    bytecode_number: bytecode_number_expr,
+                    index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
+                    tag: Tag::TypeIndex.expr(),
+                    is_terminator: false.expr(),
+                }
+            });
+        }
         // data section crosschecks
-        dynamic_indexes_chip.lookup_args("data section: memidx refs are valid", cs, |vc| {
-            let cond = vc.query_fixed(
-                wasm_data_section_body_chip.config.is_memidx,
-                Rotation::cur(),
-            );
-            let cond = cond
-                * Self::get_selector_expr_enriched_with_error_processing(
-                    vc,
-                    q_enable,
-                    &shared_state.borrow(),
-                    error_code,
+        if let Some(wasm_data_section_body_chip) = &wasm_data_section_body_chip {
+            dynamic_indexes_chip.lookup_args("data section: memidx refs are valid", cs, |vc| {
+                let cond = vc.query_fixed(
+                    wasm_data_section_body_chip.config.is_memidx,
+                    Rotation::cur(),
                 );
-            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
-
-            LookupArgsParams {
-                cond,
-                bytecode_number: bytecode_number_expr,
-                index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
-                tag: Tag::MemIndex.expr(),
-                is_terminator: false.expr(),
-            }
-        });
+                let cond = cond
+                    * Self::get_selector_expr_enriched_with_error_processing(
+                        vc,
+                        q_enable,
+                        &shared_state.read(),
+                        error_code,
+                    );
+                let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+
+                LookupArgsParams {
+                    cond,
+                    bytecode_number: bytecode_number_expr,
+                    index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
+                    tag: Tag::MemIndex.expr(),
+                    is_terminator: false.expr(),
+                }
+            });
+        }
         // code section crosschecks
         dynamic_indexes_chip.lookup_args(
             "code section has valid setup for func indexes",
@@ -1224,7 +1587,7 @@ impl<F: Field> WasmChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
@@ -1240,35 +1603,75 @@ impl<F: Field> WasmChip<F> {
                 }
             },
         );
-        dynamic_indexes_chip.lookup_args("code section: call opcode param is valid", cs, |vc| {
-            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
-            let cond = and::expr([
-                vc.query_fixed(
-                    wasm_code_section_body_chip.config.is_control_instruction,
+        if let Some(wasm_code_section_body_chip) = &wasm_code_section_body_chip {
+            // func_count already includes the declared bodies, so the body with body_item_rev_count
+            // N is the func index func_count-1-N
+            dynamic_indexes_chip.lookup_args("code section: func bodies have func indexes", cs, |vc| {
+                let cond = and::expr([
+                    vc.query_fixed(
+                        wasm_code_section_body_chip.config.is_func_body_len,
+                        Rotation::cur(),
+                    ),
+                    vc.query_fixed(
+                        wasm_code_section_body_chip.config.leb128_chip.config.is_first_byte,
+                        Rotation::cur(),
+                    ),
+                ]);
+                let cond = cond
+                    * Self::get_selector_expr_enriched_with_error_processing(
+                        vc,
+                        q_enable,
+                        &shared_state.read(),
+                        error_code,
+                    );
+                let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+                let body_item_rev_count_expr = vc.query_advice(
+                    wasm_code_section_body_chip.config.body_item_rev_count,
                     Rotation::cur(),
-                ),
-                wasm_code_section_body_chip
-                    .config
-                    .control_instruction_chip
-                    .config
-                    .value_equals(ControlInstruction::Call, Rotation::cur())(vc),
-            ]);
-            let cond = cond
-                * Self::get_selector_expr_enriched_with_error_processing(
-                    vc,
-                    q_enable,
-                    &shared_state.borrow(),
-                    error_code,
                 );
 
-            LookupArgsParams {
-                cond,
-                bytecode_number: bytecode_number_expr,
-                index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
-                tag: Tag::FuncIndex.expr(),
-                is_terminator: false.expr(),
-            }
-        });
+                LookupArgsParams {
+                    cond,
+                    bytecode_number: bytecode_number_expr,
+                    index: vc.query_advice(func_count, Rotation::cur())
+                        - 1.expr()
+                        - body_item_rev_count_expr,
+                    tag: Tag::FuncIndex.expr(),
+                    is_terminator: false.expr(),
+                }
+            });
+            dynamic_indexes_chip.lookup_args("code section: call opcode param is valid", cs, |vc| {
+                let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+                let cond = and::expr([
+                    vc.query_fixed(
+                        wasm_code_section_body_chip.config.is_control_instruction,
+                        Rotation::cur(),
+                    ),
+                    wasm_code_section_body_chip
+                        .config
+                        .control_instruction_chip
+                        .config
+                        .value_equals(ControlInstruction::Call, Rotation::cur())(vc),
+                ]);
+                let cond = cond
+                    * Self::get_selector_expr_enriched_with_error_processing(
+                        vc,
+                        q_enable,
+                        &shared_state.read(),
+                        error_code,
+                    );
+
+                LookupArgsParams {
+                    cond,
+                    bytecode_number: bytecode_number_expr,
+                    index: vc.query_advice(leb128_chip.config.sn, Rotation::next()),
+                    tag: Tag::FuncIndex.expr(),
+                    is_terminator: false.expr(),
+                }
+            });
+        }
+
+        cost_report.track("DynamicIndexesChip", cs);
 
         let config = WasmConfig {
             _marker: PhantomData,
@@ -1302,29 +1705,190 @@ impl<F: Field> WasmChip<F> {
             wasm_table_section_body_chip,
             wasm_element_section_body_chip,
             section_id_lt_chip,
-            range_table_config_0_128,
+            section_len_lt_chip,
+            section_id_is_custom_chip,
+            utf8_transition_table_config,
+            byte_set_table_config,
+            opcode_table_config,
             dynamic_indexes_chip,
             shared_state,
+            options,
             func_count,
             block_depth_level,
             body_byte_rev_index_l1,
             body_byte_rev_index_l2,
             body_item_rev_count_l1,
             body_item_rev_count_l2,
+            bytecode_rev_index,
             error_code,
+            cost_report,
         };
 
         config
     }
 
+    /// Poseidon code hash is the same over the bytecode and is a hash id of the poseidon table
+    fn configure_poseidon_code_hash(
+        cs: &mut ConstraintSystem<F>,
+        q_enable: Column<Fixed>,
+        q_first: Column<Fixed>,
+        poseidon_code_hash: Column<Advice>,
+        poseidon_table: &PoseidonTable,
+    ) {
+        cs.create_gate("poseidon code hash gate", |vc| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_enable_expr = vc.query_fixed(q_enable, Rotation::cur());
+            let q_enable_prev_expr = vc.query_fixed(q_enable, Rotation::prev());
+            let not_q_first_expr = not::expr(vc.query_fixed(q_first, Rotation::cur()));
+            let poseidon_code_hash_expr = vc.query_advice(poseidon_code_hash, Rotation::cur());
+            let poseidon_code_hash_prev_expr =
+                vc.query_advice(poseidon_code_hash, Rotation::prev());
+
+            cb.condition(not_q_first_expr, |cb| {
+                cb.require_equal(
+                    "not_q_first => poseidon_code_hash=prev.poseidon_code_hash",
+                    q_enable_prev_expr.clone() * poseidon_code_hash_expr,
+                    q_enable_prev_expr * poseidon_code_hash_prev_expr,
+                );
+            });
+
+            cb.gate(q_enable_expr)
+        });
+        cs.lookup_any("poseidon code hash is a poseidon table hash id", |vc| {
+            let q_first_expr = vc.query_fixed(q_enable, Rotation::cur())
+                * vc.query_fixed(q_first, Rotation::cur());
+            vec![
+                (
+                    q_first_expr.clone() * vc.query_advice(poseidon_code_hash, Rotation::cur()),
+                    vc.query_advice(poseidon_table.hash_id, Rotation::cur()),
+                ),
+                (
+                    q_first_expr,
+                    vc.query_advice(poseidon_table.heading_mark, Rotation::cur()),
+                ),
+            ]
+        });
+    }
+
+    /// Constrains every byte of the wasm bytecode table to be present in the unified bytecode
+    /// table shared with the evm circuit. Bytes are anchored whether the module is valid or not.
+    pub fn configure_unified_bytecode_lookup(
+        cs: &mut ConstraintSystem<F>,
+        config: &WasmConfig<F>,
+        unified_bytecode_table: &UnifiedBytecodeTable,
+    ) {
+        let wb_table = config.wb_table.clone();
+        let q_enable = config.q_enable;
+        cs.lookup_any("wasm bytecode in unified bytecode table", |vc| {
+            let q_enable_expr = vc.query_fixed(q_enable, Rotation::cur());
+            vec![
+                (
+                    q_enable_expr.clone() * BytecodeKind::Wasm.expr(),
+                    vc.query_advice(unified_bytecode_table.kind, Rotation::cur()),
+                ),
+                (
+                    q_enable_expr.clone() * vc.query_advice(wb_table.code_hash, Rotation::cur()),
+                    vc.query_advice(unified_bytecode_table.code_hash, Rotation::cur()),
+                ),
+                (
+                    q_enable_expr.clone() * vc.query_advice(wb_table.index, Rotation::cur()),
+                    vc.query_advice(unified_bytecode_table.index, Rotation::cur()),
+                ),
+                (
+                    q_enable_expr * vc.query_advice(wb_table.value, Rotation::cur()),
+                    vc.query_advice(unified_bytecode_table.value, Rotation::cur()),
+                ),
+            ]
+        });
+    }
+
     pub fn construct(config: WasmConfig<F>) -> Self {
         let instance = Self {
             config,
+            section_assignments: vec![],
+            module_outputs: vec![],
             _marker: PhantomData,
         };
         instance
     }
 
+    pub fn section_assignments_reset(&mut self) {
+        self.section_assignments.clear();
+    }
+
+    pub fn module_outputs_reset(&mut self) {
+        self.module_outputs.clear();
+    }
+
+    fn module_output_push(
+        &mut self,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) {
+        let shared_state = self.config.shared_state.read();
+        self.module_outputs.push(WasmModuleOutput {
+            bytecode_number: shared_state.bytecode_number,
+            code_hash: wb.code_hash,
+            error_code: shared_state.error_code,
+            error_reason: shared_state.error_reason.filter(|_| shared_state.error_code != 0),
+            q_last_assign_offset: wb_offset + wb.bytes.len() - 1 + assign_delta,
+        });
+    }
+
+    /// Reassigns `bytecode_number`, `code_hash` and `error_code` cells at the last row of each
+    /// assigned bytecode, returned cells are meant to be exposed as public outputs
+    pub fn assign_module_outputs(
+        &self,
+        region: &mut Region<F>,
+    ) -> Result<Vec<[AssignedCell<F, F>; WASM_MODULE_OUTPUT_LEN]>, Error> {
+        let mut cells = Vec::with_capacity(self.module_outputs.len());
+        for output in &self.module_outputs {
+            let assign_offset = output.q_last_assign_offset;
+            let bytecode_number = region
+                .assign_advice(
+                    || format!("assign 'bytecode_number' output at {}", assign_offset),
+                    self.config.bytecode_number,
+                    assign_offset,
+                    || Value::known(F::from(output.bytecode_number)),
+                )
+                .map_err(remap_error_to_assign_at(assign_offset))?;
+            let code_hash = region
+                .assign_advice(
+                    || format!("assign 'code_hash' output at {}", assign_offset),
+                    self.config.wb_table.code_hash,
+                    assign_offset,
+                    || Value::known(output.code_hash.to_scalar().unwrap()),
+                )
+                .map_err(remap_error_to_assign_at(assign_offset))?;
+            let error_code = region
+                .assign_advice(
+                    || format!("assign 'error_code' output at {}", assign_offset),
+                    self.config.error_code.code,
+                    assign_offset,
+                    || Value::known(F::from(output.error_code)),
+                )
+                .map_err(remap_error_to_assign_at(assign_offset))?;
+            cells.push([bytecode_number, code_hash, error_code]);
+        }
+        Ok(cells)
+    }
+
+    /// Builds serializable plan from bytecodes and the section ranges recorded so far
+    pub fn assignment_plan(
+        &self,
+        wbs: &[WasmBytecode],
+        assign_delta_base: AssignDeltaType,
+    ) -> WasmAssignmentPlan {
+        WasmAssignmentPlan {
+            wbs: wbs.to_vec(),
+            assign_delta_base,
+            shared_state: *self.config.shared_state.read(),
+            section_assignments: self.section_assignments.clone(),
+        }
+    }
+
     pub fn assign_auto(
         &mut self,
         region: &mut Region<F>,
@@ -1332,6 +1896,7 @@ impl<F: Field> WasmChip<F> {
         wb_offset: usize,
         assign_delta: AssignDeltaType,
     ) -> Result<NewWbOffsetType, Error> {
+        self.config.shared_state.write().module_begin();
         let result = self.assign_auto_internal(region, wb, wb_offset, assign_delta);
         let assign_delta = assign_delta
             + if self.config.wb_table.zero_row_enabled {
@@ -1342,22 +1907,18 @@ impl<F: Field> WasmChip<F> {
 
         if let Err(e) = result {
             return if is_recoverable_error(&e)
-                & self.config.shared_state.borrow().error_processing_enabled
+                & self.config.shared_state.read().error_processing_enabled
             {
                 debug!("detected recoverable error: {:?}", e);
                 match e {
-                    Error::IndexOutOfBoundsAt(offset) |
-                    Error::AssignAt(offset) |
-                    Error::ParseOpcodeFailedAt(offset) |
-                    Error::InvalidByteValueAt(offset) |
-                    Error::InvalidEnumValueAt(offset) |
-                    Error::ComputeValueAt(offset) => {
-                        debug!("recoverable error offset: {}", offset);
-                        self.shared_state().borrow_mut().error_code = ErrorCode::Error as u64;
+                    _ if error_offset(&e).is_some() => {
+                        debug!("recoverable error offset: {:?}", error_offset(&e));
+                        self.shared_state().write().error_code_turn_on(error_reason(&e, None));
+                        let error_code = self.shared_state().read().error_code;
                         // cannot use offset received from error because of forward checks 
                         // and also structure markups happen after return with error 
                         for offset in 0..wb.bytes.len() {
-                            self.assign(region, wb, offset, assign_delta, &[AssignType::ErrorCode], ErrorCode::Error as u64, None)?;
+                            self.assign(region, wb, offset, assign_delta, &[AssignType::ErrorCode], error_code, None)?;
                         }
                     }
 
@@ -1375,16 +1936,247 @@ impl<F: Field> WasmChip<F> {
                     _ => return Err(e)
                 }
 
+                self.module_output_push(wb, wb_offset, assign_delta);
+                self.config.shared_state.write().module_end();
                 Ok(wb.bytes.len() + assign_delta)
             } else {
                 Err(e)
             };
         }
 
-        self.config.shared_state.borrow_mut().bytecode_number_inc();
+        self.module_output_push(wb, wb_offset, assign_delta);
+        self.config.shared_state.write().module_end();
         return Ok(wb.bytes.len() + assign_delta);
     }
 
+    /// Cursor of the first bytecode of a region, bytecodes are assigned after `assign_delta_base`
+    pub fn region_cursor(&self, assign_delta_base: AssignDeltaType) -> RegionCursor {
+        RegionCursor::new(assign_delta_base, self.config.wb_table.zero_row_enabled)
+    }
+
+    /// Loads and assigns `wb` at `cursor`, then moves the cursor past it
+    pub fn assign_next(
+        &mut self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        cursor: &mut RegionCursor,
+    ) -> Result<(), Error> {
+        self.load(region, wb, cursor.assign_delta())?;
+        self.assign_auto(region, wb, wb_offset, cursor.assign_delta())?;
+        cursor.advance(wb);
+        Ok(())
+    }
+
+    /// [`Self::assign_next`] journaling every assignment into `journal`, which can be replayed
+    /// into a fresh region with [`AssignJournal::replay`]
+    pub fn assign_next_journaled(
+        &mut self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        cursor: &mut RegionCursor,
+        journal: &mut AssignJournal<F>,
+    ) -> Result<(), Error> {
+        with_journal(region, journal, |region| {
+            self.assign_next(region, wb, wb_offset, cursor)
+        })
+    }
+
+    fn reference_section_check<'a>(
+        reference_module: &'a ReferenceModule,
+        section_start_offset: usize,
+        section_body_start_offset: usize,
+        section_end_offset: usize,
+    ) -> Result<&'a ReferenceSection, Error> {
+        let reference_section = reference_module
+            .section_at(section_start_offset)
+            .ok_or_else(|| {
+                Error::FatalReferenceDecoderMismatch(format!(
+                    "no section starts at {}",
+                    section_start_offset
+                ))
+            })?;
+        if reference_section.body.start != section_body_start_offset
+            || reference_section.range.end != section_end_offset + 1
+        {
+            return Err(Error::FatalReferenceDecoderMismatch(format!(
+                "section at {} has body {}..={} but reference body is {:?}",
+                section_start_offset,
+                section_body_start_offset,
+                section_end_offset,
+                reference_section.body
+            )));
+        }
+        Ok(reference_section)
+    }
+
+    /// Assigns the check of a section len (at its last leb byte) against the bytes remaining after
+    /// it
+    fn assign_section_len_check(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        section_len_end_offset: usize,
+        assign_delta: AssignDeltaType,
+        section_len: usize,
+    ) -> Result<(), Error> {
+        let assign_offset = section_len_end_offset + assign_delta;
+        let bytes_remaining = wb.bytes.len() - 1 - section_len_end_offset;
+        self.config
+            .section_len_lt_chip
+            .assign(
+                region,
+                assign_offset,
+                F::from(section_len as u64),
+                F::from(bytes_remaining as u64 + 1),
+            )
+            .map_err(remap_error_to_assign_at(assign_offset))?;
+        if section_len > bytes_remaining {
+            return Err(Error::SectionLenExceededAt(assign_offset));
+        }
+        Ok(())
+    }
+
+    /// Assigns a section body by its section chip, returns the offset right after the body
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wasm_section: WasmSection,
+        wb_offset: usize,
+        section_body_offset: usize,
+        section_end_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error> {
+        let next_section_offset = match wasm_section {
+            WasmSection::Type => {
+                self
+                    .config
+                    .wasm_type_section_body_chip
+                    .as_ref()
+                    .ok_or(Error::InvalidEnumValueAt(wb_offset + assign_delta))?
+                    .assign_auto(region, wb, section_body_offset, assign_delta)
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?
+            }
+            WasmSection::Import => {
+                self
+                    .config
+                    .wasm_import_section_body_chip
+                    .as_ref()
+                    .ok_or(Error::InvalidEnumValueAt(wb_offset + assign_delta))?
+                    .assign_auto(region, wb, section_body_offset, assign_delta)
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?
+            }
+            WasmSection::Function => {
+                self
+                    .config
+                    .wasm_function_section_body_chip
+                    .as_ref()
+                    .ok_or(Error::InvalidEnumValueAt(wb_offset + assign_delta))?
+                    .assign_auto(region, wb, section_body_offset, assign_delta)
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?
+            }
+            WasmSection::Table => {
+                self
+                    .config
+                    .wasm_table_section_body_chip
+                    .as_ref()
+                    .ok_or(Error::InvalidEnumValueAt(wb_offset + assign_delta))?
+                    .assign_auto(region, wb, section_body_offset, assign_delta)
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?
+            }
+            WasmSection::Memory => {
+                self
+                    .config
+                    .wasm_memory_section_body_chip
+                    .as_ref()
+                    .ok_or(Error::InvalidEnumValueAt(wb_offset + assign_delta))?
+                    .assign_auto(region, wb, section_body_offset, assign_delta)
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?
+            }
+            WasmSection::Global => {
+                self
+                    .config
+                    .wasm_global_section_body_chip
+                    .as_ref()
+                    .ok_or(Error::InvalidEnumValueAt(wb_offset + assign_delta))?
+                    .assign_auto(region, wb, section_body_offset, assign_delta)
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?
+            }
+            WasmSection::Export => {
+                self
+                    .config
+                    .wasm_export_section_body_chip
+                    .as_ref()
+                    .ok_or(Error::InvalidEnumValueAt(wb_offset + assign_delta))?
+                    .assign_auto(region, wb, section_body_offset, assign_delta)
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?
+            }
+            WasmSection::Start => {
+                self
+                    .config
+                    .wasm_start_section_body_chip
+                    .as_ref()
+                    .ok_or(Error::InvalidEnumValueAt(wb_offset + assign_delta))?
+                    .assign_auto(region, wb, section_body_offset, assign_delta)
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?
+            }
+            WasmSection::Element => {
+                self
+                    .config
+                    .wasm_element_section_body_chip
+                    .as_ref()
+                    .ok_or(Error::InvalidEnumValueAt(wb_offset + assign_delta))?
+                    .assign_auto(region, wb, section_body_offset, assign_delta)
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?
+            }
+            WasmSection::Code => {
+                self
+                    .config
+                    .wasm_code_section_body_chip
+                    .as_ref()
+                    .ok_or(Error::InvalidEnumValueAt(wb_offset + assign_delta))?
+                    .assign_auto(region, wb, section_body_offset, assign_delta)
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?
+            }
+            WasmSection::Data => {
+                self
+                    .config
+                    .wasm_data_section_body_chip
+                    .as_ref()
+                    .ok_or(Error::InvalidEnumValueAt(wb_offset + assign_delta))?
+                    .assign_auto(region, wb, section_body_offset, assign_delta)
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?
+            }
+            WasmSection::Custom => {
+                let name =
+                    wasm_custom_section_name(&wb.bytes[..=section_end_offset], section_body_offset)
+                        .map_err(remap_error_to_compute_value_at(
+                            section_body_offset + assign_delta,
+                        ))?;
+                if name == PRODUCERS_CUSTOM_SECTION_NAME {
+                    wasm_producers_section(&wb.bytes[..=section_end_offset], section_body_offset)?;
+                } else if !is_pass_through_custom_section_name(name) {
+                    return Err(Error::FatalUnsupportedValue(format!(
+                        "unsupported custom section '{}'",
+                        String::from_utf8_lossy(name)
+                    )));
+                }
+                // body bytes are marked only by 'is_section_body' below
+                section_end_offset + 1
+            }
+            _ => {
+                return Err(Error::FatalUnsupportedValue(format!(
+                    "unsupported section value '{:x?}'",
+                    wasm_section
+                )))
+            }
+        };
+
+        Ok(next_section_offset)
+    }
+
     fn assign_auto_internal(
         &mut self,
         region: &mut Region<F>,
@@ -1393,6 +2185,12 @@ impl<F: Field> WasmChip<F> {
         assign_delta: AssignDeltaType,
     ) -> Result<OffsetType, Error> {
         debug!("wb.bytes {:x?}", wb.bytes);
+        // invalid bytecodes are left to the hand-rolled walking, it sets the error code
+        let reference_module = if self.config.shared_state.read().reference_decoder_enabled {
+            decode_module(&wb.bytes).ok()
+        } else {
+            None
+        };
         self.assign(
             region,
             wb,
@@ -1402,8 +2200,6 @@ impl<F: Field> WasmChip<F> {
             1,
             None,
         )?;
-        // TODO needed for multibytecode assignments, refactor
-        self.config.shared_state.borrow_mut().func_count = 0;
         self.assign_func_count(region, wb_offset + assign_delta)?;
         let assign_delta = assign_delta
             + if self.config.wb_table.zero_row_enabled {
@@ -1420,6 +2216,18 @@ impl<F: Field> WasmChip<F> {
             1,
             None,
         )?;
+        let wb_offset_end = wb_offset + wb.bytes.len();
+        for offset in wb_offset..wb_offset_end {
+            self.assign(
+                region,
+                wb,
+                offset,
+                assign_delta,
+                &[AssignType::BytecodeRevIndex],
+                (wb_offset_end - 1 - offset) as u64,
+                None,
+            )?;
+        }
 
         // check magic prefix and version
         let assign_offset_start = wb_offset + assign_delta + WASM_MAGIC_PREFIX_START_INDEX;
@@ -1453,6 +2261,7 @@ impl<F: Field> WasmChip<F> {
 
         let mut wb_offset = WASM_SECTIONS_START_INDEX;
         let mut section_id_prev: i64 = SECTION_ID_DEFAULT as i64;
+        let mut is_custom_section_seen = false;
         while wb_offset < wb.bytes.len() {
             let section_start_offset = wb_offset;
             let section_len_start_offset = section_start_offset + 1;
@@ -1469,15 +2278,37 @@ impl<F: Field> WasmChip<F> {
             let section_body_start_offset =
                 section_len_start_offset + section_len_leb_bytes_count as usize;
             let section_len_end_offset = section_body_start_offset - 1;
+            self.assign_section_len_check(
+                region,
+                wb,
+                section_len_end_offset,
+                assign_delta,
+                section_len,
+            )?;
             let section_body_end_offset =
                 section_start_offset + section_len_leb_bytes_count as usize + section_len;
             let section_end_offset = section_body_end_offset;
+            let reference_section = match &reference_module {
+                Some(reference_module) => Some(Self::reference_section_check(
+                    reference_module,
+                    section_start_offset,
+                    section_body_start_offset,
+                    section_end_offset,
+                )?),
+                None => None,
+            };
 
             for wb_offset in section_start_offset..=section_end_offset {
                 if wb_offset == section_start_offset {
                     let wasm_section: WasmSection = (section_id as i32).try_into().map_err(
                         remap_error_to_invalid_enum_value_at(wb_offset + assign_delta),
                     )?;
+                    // custom sections are accepted only after all the other sections
+                    if wasm_section == WasmSection::Custom {
+                        is_custom_section_seen = true;
+                    } else if is_custom_section_seen {
+                        return Err(Error::InvalidByteValueAt(wb_offset + assign_delta));
+                    }
                     debug!(
                         "wasm_section {:?}(id={}) at offset {} (assign_offset {}) offset_end {} (assign_offset {}) section_len {} bytecode(hex) {:x?}",
                         wasm_section,
@@ -1491,7 +2322,6 @@ impl<F: Field> WasmChip<F> {
                     );
                     self.assign_func_count(region, wb_offset + assign_delta)?;
 
-                    let mut next_section_offset = 0;
                     let section_body_offset = wb_offset + 1; // skip section_id
                     let section_len_last_byte_offset =
                         leb128_compute_last_byte_offset(&wb.bytes[..], section_body_offset)
@@ -1513,89 +2343,32 @@ impl<F: Field> WasmChip<F> {
                         self.assign_func_count(region, offset + assign_delta)?;
                     }
                     let section_body_offset = section_len_last_byte_offset + 1;
-                    match wasm_section {
-                        WasmSection::Type => {
-                            next_section_offset = self
-                                .config
-                                .wasm_type_section_body_chip
-                                .assign_auto(region, wb, section_body_offset, assign_delta)
-                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
-                        }
-                        WasmSection::Import => {
-                            next_section_offset = self
-                                .config
-                                .wasm_import_section_body_chip
-                                .assign_auto(region, wb, section_body_offset, assign_delta)
-                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
-                        }
-                        WasmSection::Function => {
-                            next_section_offset = self
-                                .config
-                                .wasm_function_section_body_chip
-                                .assign_auto(region, wb, section_body_offset, assign_delta)
-                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
-                        }
-                        WasmSection::Table => {
-                            next_section_offset = self
-                                .config
-                                .wasm_table_section_body_chip
-                                .assign_auto(region, wb, section_body_offset, assign_delta)
-                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
-                        }
-                        WasmSection::Memory => {
-                            next_section_offset = self
-                                .config
-                                .wasm_memory_section_body_chip
-                                .assign_auto(region, wb, section_body_offset, assign_delta)
-                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
-                        }
-                        WasmSection::Global => {
-                            next_section_offset = self
-                                .config
-                                .wasm_global_section_body_chip
-                                .assign_auto(region, wb, section_body_offset, assign_delta)
-                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
-                        }
-                        WasmSection::Export => {
-                            next_section_offset = self
-                                .config
-                                .wasm_export_section_body_chip
-                                .assign_auto(region, wb, section_body_offset, assign_delta)
-                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
-                        }
-                        WasmSection::Start => {
-                            next_section_offset = self
-                                .config
-                                .wasm_start_section_body_chip
-                                .assign_auto(region, wb, section_body_offset, assign_delta)
-                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
-                        }
-                        WasmSection::Element => {
-                            next_section_offset = self
-                                .config
-                                .wasm_element_section_body_chip
-                                .assign_auto(region, wb, section_body_offset, assign_delta)
-                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
-                        }
-                        WasmSection::Code => {
-                            next_section_offset = self
-                                .config
-                                .wasm_code_section_body_chip
-                                .assign_auto(region, wb, section_body_offset, assign_delta)
-                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
-                        }
-                        WasmSection::Data => {
-                            next_section_offset = self
-                                .config
-                                .wasm_data_section_body_chip
-                                .assign_auto(region, wb, section_body_offset, assign_delta)
-                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
-                        }
-                        _ => {
-                            return Err(Error::FatalUnsupportedValue(format!(
-                                "unsupported section value '{:x?}'",
-                                wasm_section
-                            )))
+                    // counters are rolled back so a failed section does not poison the next ones
+                    let shared_state_snapshot = self.config.shared_state.read().snapshot();
+                    let next_section_offset = self
+                        .assign_section_body(
+                            region,
+                            wb,
+                            wasm_section,
+                            wb_offset,
+                            section_body_offset,
+                            section_end_offset,
+                            assign_delta,
+                        )
+                        .map_err(|e| {
+                            let mut shared_state = self.config.shared_state.write();
+                            shared_state.restore(shared_state_snapshot);
+                            shared_state
+                                .error_reason
+                                .get_or_insert(error_reason(&e, Some(wasm_section)));
+                            e
+                        })?;
+                    if let Some(reference_section) = reference_section {
+                        if next_section_offset != reference_section.body.end {
+                            return Err(Error::FatalReferenceDecoderMismatch(format!(
+                                "{:?} section body chip stopped at {} but body ends at {}",
+                                wasm_section, next_section_offset, reference_section.body.end
+                            )));
                         }
                     }
                     debug!(
@@ -1604,6 +2377,14 @@ impl<F: Field> WasmChip<F> {
                         section_body_offset,
                         next_section_offset,
                     );
+                    self.section_assignments.push(WasmSectionAssignment {
+                        bytecode_number: self.config.shared_state.read().bytecode_number,
+                        section: wasm_section,
+                        section_start_offset,
+                        section_body_start_offset,
+                        section_end_offset,
+                        assign_delta,
+                    });
                 }
                 region
                     .assign_advice(
@@ -1622,6 +2403,10 @@ impl<F: Field> WasmChip<F> {
                         F::from(section_id),
                     )
                     .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
+                self.config
+                    .section_id_is_custom_chip
+                    .assign(region, wb_offset + assign_delta, Value::known(F::from(section_id)))
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
                 section_id_prev = section_id as i64;
             }
 
@@ -1659,13 +2444,449 @@ impl<F: Field> WasmChip<F> {
 
         let dynamic_indexes_offset = self.config.dynamic_indexes_chip.assign_auto(
             region,
-            self.config.shared_state.borrow().dynamic_indexes_offset,
+            self.config.shared_state.read().dynamic_indexes_offset,
             assign_delta,
-            self.config.shared_state.borrow().func_count,
+            self.config.shared_state.read().func_count,
             Tag::FuncIndex,
         )?;
-        self.config.shared_state.borrow_mut().dynamic_indexes_offset = dynamic_indexes_offset;
+        self.config.shared_state.write().dynamic_indexes_offset = dynamic_indexes_offset;
 
         Ok(wb_offset + assign_delta)
     }
 }
+
+/// Circuit proving a batch of bytecodes with error processing enabled (invalid bytecodes are
+/// assigned with error code set instead of failing synthesis). Bytecodes are assigned back-to-back
+/// and each one exposes its (bytecode_number, code_hash, error_code) in the instance column. Runs
+/// standalone or as a sub-circuit of the super circuit, where it also loads the wasm function
+/// table looked up by the evm circuit.
+#[derive(Clone, Debug)]
+pub struct WasmCircuit<F> {
+    pub wbs: Vec<WasmBytecode>,
+    pub assign_delta_base: AssignDeltaType,
+    /// cross-check witness generation against the wasmparser based reference decoder
+    pub reference_decoder_enabled: bool,
+    /// degree the circuit is synthesized at, the bytecodes are checked to fit it before anything
+    /// is assigned
+    pub k: u32,
+    module_outputs: RefCell<Vec<WasmModuleOutput>>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Default for WasmCircuit<F> {
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WasmCircuitConfig<F: Field> {
+    pub wasm_config: WasmConfig<F>,
+    pub instance: Column<Instance>,
+    /// exposed to the evm circuit when the wasm circuit is a sub-circuit of the super circuit
+    pub wasm_function_table: Option<WasmFunctionTable>,
+}
+
+pub struct WasmCircuitConfigArgs {
+    /// Function table loaded from the proven bytecodes, `None` for the standalone circuit
+    pub wasm_function_table: Option<WasmFunctionTable>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for WasmCircuitConfig<F> {
+    type ConfigArgs = WasmCircuitConfigArgs;
+
+    fn new(
+        cs: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            wasm_function_table,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let shared_state = SharedStateRef::default();
+        shared_state.write().error_processing_enabled = true;
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, true));
+        cs.enable_equality(wb_table.code_hash);
+
+        let wasm_config = WasmChip::configure(cs, wb_table, shared_state);
+        cs.enable_equality(wasm_config.error_code.code);
+        cs.enable_equality(wasm_config.bytecode_number);
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+
+        // code hashes of the function table only match the bytecode table ones with poseidon
+        // code hashes
+        #[cfg(feature = "poseidon-codehash")]
+        if let (Some(wasm_function_table), Some(code_section_chip)) = (
+            wasm_function_table.as_ref(),
+            wasm_config.wasm_code_section_body_chip.as_ref(),
+        ) {
+            wasm_function_table.configure_code_section_lookup(
+                cs,
+                &wasm_config.wb_table,
+                &code_section_chip.config,
+            );
+        }
+        #[cfg(feature = "poseidon-codehash")]
+        if let (Some(wasm_function_table), Some(function_section_chip)) = (
+            wasm_function_table.as_ref(),
+            wasm_config.wasm_function_section_body_chip.as_ref(),
+        ) {
+            wasm_function_table.configure_function_section_lookup(
+                cs,
+                &wasm_config.wb_table,
+                &function_section_chip.config,
+            );
+        }
+
+        Self {
+            wasm_config,
+            instance,
+            wasm_function_table,
+        }
+    }
+}
+
+impl<F: Field> WasmCircuitConfig<F> {
+    /// Layout of `cs` configured with this config, columns of the bytecode table and `WasmChip`
+    /// are named after their fields
+    pub fn layout(&self, cs: &ConstraintSystem<F>) -> CircuitLayout {
+        let wb_table = &self.wasm_config.wb_table;
+        let config = &self.wasm_config;
+        let mut roles = vec![
+            (ColumnKind::Advice, wb_table.index.index(), "wb_table.index"),
+            (ColumnKind::Advice, wb_table.value.index(), "wb_table.value"),
+            (ColumnKind::Advice, wb_table.code_hash.index(), "wb_table.code_hash"),
+            (ColumnKind::Advice, config.bytecode_number.index(), "bytecode_number"),
+            (ColumnKind::Fixed, config.q_enable.index(), "q_enable"),
+            (ColumnKind::Fixed, config.q_first.index(), "q_first"),
+            (ColumnKind::Fixed, config.q_last.index(), "q_last"),
+            (ColumnKind::Fixed, config.is_section_id.index(), "is_section_id"),
+            (ColumnKind::Fixed, config.is_section_len.index(), "is_section_len"),
+            (ColumnKind::Fixed, config.is_section_body.index(), "is_section_body"),
+            (ColumnKind::Advice, config.section_id.index(), "section_id"),
+            (ColumnKind::Advice, config.func_count.index(), "func_count"),
+            (ColumnKind::Advice, config.block_depth_level.index(), "block_depth_level"),
+            (ColumnKind::Advice, config.body_byte_rev_index_l1.index(), "body_byte_rev_index_l1"),
+            (ColumnKind::Advice, config.body_byte_rev_index_l2.index(), "body_byte_rev_index_l2"),
+            (ColumnKind::Advice, config.body_item_rev_count_l1.index(), "body_item_rev_count_l1"),
+            (ColumnKind::Advice, config.body_item_rev_count_l2.index(), "body_item_rev_count_l2"),
+            (ColumnKind::Advice, config.bytecode_rev_index.index(), "bytecode_rev_index"),
+            (ColumnKind::Advice, config.error_code.code.index(), "error_code"),
+            (ColumnKind::Advice, config.error_code.code_inv.index(), "error_code_inv"),
+            (ColumnKind::Instance, self.instance.index(), "module_outputs"),
+        ];
+        if let Some(poseidon_code_hash) = wb_table.poseidon_code_hash {
+            roles.push((
+                ColumnKind::Advice,
+                poseidon_code_hash.index(),
+                "wb_table.poseidon_code_hash",
+            ));
+        }
+        CircuitLayout::new(
+            cs,
+            &config.cost_report,
+            &roles,
+            vec![InstanceLayout {
+                column: self.instance.index(),
+                values: ["bytecode_number", "code_hash", "error_code"]
+                    .map(str::to_string)
+                    .to_vec(),
+            }],
+        )
+    }
+}
+
+impl<F: Field> WasmCircuit<F> {
+    /// Layout of the standalone circuit, meant for verifier codegen and debuggers
+    pub fn layout() -> CircuitLayout {
+        let mut cs = ConstraintSystem::default();
+        let config = Self::configure(&mut cs);
+        config.layout(&cs)
+    }
+
+    pub fn new(wbs: Vec<WasmBytecode>) -> Self {
+        let k = Self::estimate_k(&wbs, 0);
+        Self {
+            wbs,
+            assign_delta_base: 0,
+            reference_decoder_enabled: false,
+            k,
+            module_outputs: RefCell::new(vec![]),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Wasm modules executed by calls of the block, pulled from the code db. Each bytecode is
+    /// tied to the code hash the calls were executed with.
+    pub fn new_from_block(block: &circuit_input_builder::Block, code_db: &CodeDB) -> Self {
+        Self::new(
+            circuit_input_builder::wasm_codes(block, code_db)
+                .into_iter()
+                .map(|(_, code)| WasmBytecode::new(code))
+                .collect(),
+        )
+    }
+
+    pub fn with_reference_decoder(mut self) -> Self {
+        self.reference_decoder_enabled = true;
+        self
+    }
+
+    /// Synthesizes at degree `k` instead of the estimated one, e.g. the degree of the params
+    pub fn with_k(mut self, k: u32) -> Self {
+        self.k = k;
+        self
+    }
+
+    /// Outputs collected by the last synthesis
+    pub fn module_outputs(&self) -> Vec<WasmModuleOutput> {
+        self.module_outputs.borrow().clone()
+    }
+
+    /// Runs witness generation only, to get outputs needed to build the instance before proving
+    pub fn compute_module_outputs(
+        &self,
+        k: u32,
+    ) -> Result<Vec<WasmModuleOutput>, halo2_proofs::plonk::Error> {
+        self.check_capacity(k)
+            .map_err(|_| halo2_proofs::plonk::Error::NotEnoughRowsAvailable { current_k: k })?;
+        MockProver::run(k, self, vec![vec![]])?;
+        Ok(self.module_outputs())
+    }
+
+    /// Rows of the dynamic indexes assigned for a bytecode: the indexes declared by its type,
+    /// table, memory, global and data sections and its func indexes, each followed by a
+    /// terminator row. Sections which can not be parsed add no rows.
+    pub fn dynamic_indexes_num_rows(wb: &WasmBytecode) -> usize {
+        let mut num_rows = 0;
+        let mut func_count = 0;
+        for report in wb.sections() {
+            let items_count = report.items_count.unwrap_or(0) as usize;
+            num_rows += match report.section {
+                WasmSection::Type | WasmSection::Memory | WasmSection::Global | WasmSection::Data => {
+                    items_count + 1
+                }
+                WasmSection::Table => 2,
+                _ => 0,
+            };
+            if let Some(body) =
+                wb.bytes.get(report.section_body_start_offset..=report.section_end_offset)
+            {
+                func_count += section_func_count(report.section, body).unwrap_or(0);
+            }
+        }
+        num_rows + func_count + 1
+    }
+
+    /// Rows of the wasm chip region taken by the bytecodes (a zero row before each of them) and
+    /// their dynamic indexes, which are assigned at `dynamic_indexes_offset` shifted by the
+    /// assign delta of the bytecode
+    pub fn num_rows_required(wbs: &[WasmBytecode], assign_delta_base: AssignDeltaType) -> usize {
+        let mut cursor = RegionCursor::new(assign_delta_base, true);
+        let mut dynamic_indexes_offset = 0;
+        let mut dynamic_indexes_rows_end = 0;
+        for wb in wbs {
+            dynamic_indexes_offset += Self::dynamic_indexes_num_rows(wb);
+            dynamic_indexes_rows_end = dynamic_indexes_rows_end
+                .max(cursor.bytecode_assign_delta() + dynamic_indexes_offset + 1);
+            cursor.advance(wb);
+        }
+        cursor.assign_delta().max(dynamic_indexes_rows_end)
+    }
+
+    /// Fails with [`Error::FatalCapacityExceeded`] when the bytecodes do not fit a circuit of
+    /// 2^k rows, checked before anything is assigned
+    pub fn check_capacity(&self, k: u32) -> Result<(), Error> {
+        let required_rows = Self::num_rows_required(&self.wbs, self.assign_delta_base)
+            .max(Self::fixed_tables_num_rows());
+        let available_rows = (1usize << k).saturating_sub(unusable_rows::<F, Self>());
+        if required_rows > available_rows {
+            return Err(Error::FatalCapacityExceeded {
+                required_rows,
+                available_rows,
+            });
+        }
+        Ok(())
+    }
+
+    /// Rows taken by the bytecodes with their dynamic indexes and the fixed lookup tables loaded
+    /// by [`WasmChip::load_once`]
+    pub fn min_num_rows(wbs: &[WasmBytecode]) -> usize {
+        Self::num_rows_required(wbs, 0).max(Self::fixed_tables_num_rows())
+    }
+
+    /// Smallest degree the bytecodes assigned after `assign_delta_base` fit in
+    pub fn estimate_k(wbs: &[WasmBytecode], assign_delta_base: AssignDeltaType) -> u32 {
+        let required_rows =
+            Self::num_rows_required(wbs, assign_delta_base).max(Self::fixed_tables_num_rows());
+        log2_ceil(unusable_rows::<F, Self>() + required_rows)
+    }
+
+    /// Degree of the circuit, fails with [`Error::FatalCapacityExceeded`] when the bytecodes do
+    /// not fit it
+    pub fn min_k(&self) -> Result<u32, Error> {
+        self.check_capacity(self.k)?;
+        Ok(self.k)
+    }
+
+    /// Rows of the fixed lookup tables loaded by [`WasmChip::load_once`]
+    fn fixed_tables_num_rows() -> usize {
+        [
+            256,
+            utf8_transitions().len(),
+            byte_set_rows().len(),
+            opcode_rows().len(),
+        ]
+        .into_iter()
+        .max()
+        .unwrap()
+    }
+
+    pub fn instance_from_outputs(outputs: &[WasmModuleOutput]) -> Vec<Vec<F>> {
+        vec![outputs
+            .iter()
+            .flat_map(|output| {
+                [
+                    F::from(output.bytecode_number),
+                    output.code_hash.to_scalar().unwrap(),
+                    F::from(output.error_code),
+                ]
+            })
+            .collect()]
+    }
+}
+
+impl<F: Field> Circuit<F> for WasmCircuit<F> {
+    type Config = WasmCircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+        WasmCircuitConfig::new(
+            cs,
+            WasmCircuitConfigArgs {
+                wasm_function_table: None,
+            },
+        )
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), halo2_proofs::plonk::Error> {
+        self.check_capacity(self.k)
+            .map_err(|_| halo2_proofs::plonk::Error::NotEnoughRowsAvailable { current_k: self.k })?;
+        self.assign_modules(&config, &mut layouter)
+    }
+}
+
+impl<F: Field> WasmCircuit<F> {
+    fn assign_modules(
+        &self,
+        config: &WasmCircuitConfig<F>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), halo2_proofs::plonk::Error> {
+        let mut wasm_chip = WasmChip::construct(config.wasm_config.clone());
+
+        wasm_chip
+            .load_once(layouter)
+            .map_err(|_| halo2_proofs::plonk::Error::Synthesis)?;
+        let output_cells = layouter.assign_region(
+            || "wasm_chip region",
+            |mut region| {
+                wasm_chip.config.shared_state.write().reset();
+                wasm_chip.config.shared_state.write().reference_decoder_enabled =
+                    self.reference_decoder_enabled;
+                wasm_chip.section_assignments_reset();
+                wasm_chip.module_outputs_reset();
+                let mut cursor = wasm_chip.region_cursor(self.assign_delta_base);
+                for wb in &self.wbs {
+                    wasm_chip
+                        .assign_next(&mut region, wb, 0, &mut cursor)
+                        .map_err(|_| halo2_proofs::plonk::Error::Synthesis)?;
+                }
+
+                wasm_chip
+                    .assign_module_outputs(&mut region)
+                    .map_err(|_| halo2_proofs::plonk::Error::Synthesis)
+            },
+        )?;
+        for (idx, cells) in output_cells.iter().enumerate() {
+            for (column_idx, cell) in cells.iter().enumerate() {
+                layouter.constrain_instance(
+                    cell.cell(),
+                    config.instance,
+                    WASM_MODULE_OUTPUT_LEN * idx + column_idx,
+                )?;
+            }
+        }
+        *self.module_outputs.borrow_mut() = wasm_chip.module_outputs.clone();
+
+        Ok(())
+    }
+}
+
+/// Instances are only known after [`WasmCircuit::compute_module_outputs`] ran
+impl<F: Field> CircuitExt<F> for WasmCircuit<F> {
+    fn num_instance(&self) -> Vec<usize> {
+        vec![WASM_MODULE_OUTPUT_LEN * self.wbs.len()]
+    }
+
+    fn instances(&self) -> Vec<Vec<F>> {
+        Self::instance_from_outputs(&self.module_outputs())
+    }
+}
+
+impl<F: Field> SubCircuit<F> for WasmCircuit<F> {
+    type Config = WasmCircuitConfig<F>;
+
+    /// Wasm modules of the block bytecodes, module outputs are computed right away so the
+    /// instance is available before synthesis
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        let circuit = Self::new(block_wasm_bytecodes(block));
+        if !circuit.wbs.is_empty() {
+            let k = circuit.min_k().expect("wasm circuit capacity exceeded");
+            circuit
+                .compute_module_outputs(k)
+                .expect("wasm circuit witness generation failed");
+        }
+        circuit
+    }
+
+    fn instance(&self) -> Vec<Vec<F>> {
+        Self::instance_from_outputs(&self.module_outputs())
+    }
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), halo2_proofs::plonk::Error> {
+        self.check_capacity(self.k)
+            .map_err(|_| halo2_proofs::plonk::Error::NotEnoughRowsAvailable { current_k: self.k })?;
+        self.assign_modules(config, layouter)?;
+        if let Some(wasm_function_table) = config.wasm_function_table {
+            wasm_function_table.dev_load(layouter, &self.wbs, challenges)?;
+        }
+        Ok(())
+    }
+
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let wbs = block_wasm_bytecodes(block);
+        let bytecode_rows = wbs.iter().map(|wb| wb.bytes.len() + 1).sum();
+        (bytecode_rows, Self::min_num_rows(&wbs))
+    }
+}
+
+fn block_wasm_bytecodes<F: Field>(block: &witness::Block<F>) -> Vec<WasmBytecode> {
+    block
+        .bytecodes
+        .values()
+        .filter(|bytecode| CodeDB::is_wasm(&bytecode.bytes))
+        .map(|bytecode| WasmBytecode::new(bytecode.bytes.clone()))
+        .collect()
+}
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, rc::Rc};
+use std::sync::Arc;
 
 use halo2_proofs::{
     circuit::{Chip, Region, Value},
@@ -6,7 +6,6 @@ use halo2_proofs::{
     poly::Rotation,
 };
 use log::debug;
-use num_traits::checked_pow;
 use wabt::wat2wasm;
 use wasmbin::{
     io::{DecodeError, Encode},
@@ -18,7 +17,7 @@ use wasmbin::{
 use eth_types::Field;
 use gadgets::{
     binary_number::BinaryNumberChip,
-    less_than::LtChip,
+    less_than::{LtChip, LtInstruction},
     util::{and, not, or, Expr},
 };
 
@@ -26,10 +25,9 @@ use crate::{
     evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
-        consts::MAX_LEB128_BYTES,
         error::{
-            error_index_out_of_bounds, remap_error_to_assign_at, remap_error_to_compute_value_at,
-            validate_wb_offset, Error,
+            error_index_out_of_bounds, remap_error, remap_error_to_assign_at,
+            remap_error_to_compute_value_at, validate_wb_offset, Error,
         },
         leb128::{
             circuit::LEB128Chip,
@@ -38,25 +36,60 @@ use crate::{
                 leb128_compute_sn_recovered_at_position,
             },
         },
+        recorder::recorder,
         sections::consts::LebParams,
+        tables::{
+            byte_set::{config::ByteSetTableConfig, types::ByteSetTag},
+            dynamic_indexes::circuit::DynamicIndexesChip,
+            opcode::config::OpcodeTableConfig,
+        },
         types::{
-            AssignDeltaType, AssignValueType, Leb128BytesCountType, Leb128LengthType, LimitType,
-            NewWbOffsetType, SectionLengthType, SharedState, Sn, WbOffsetType,
+            AssignDeltaType, AssignValueType, Leb128LengthType, LimitType, NewWbOffsetType,
+            SharedState, SharedStateRef, Sn, WasmSection, WbOffsetType,
         },
+        utf8::circuit::UTF8Chip,
     },
 };
 
+pub use wasm_witness::common::*;
+
 #[derive(Debug, Clone)]
 pub struct LimitTypeFields<F> {
     pub is_limit_type: Column<Fixed>,
     pub is_limit_min: Column<Fixed>,
     pub is_limit_max: Column<Fixed>,
-    pub limit_type_params_lt_chip: Rc<LtChip<F, 4>>,
+    pub limit_type_params_lt_chip: Arc<LtChip<F, 4>>,
     pub limit_type: Column<Advice>,
-    pub limit_type_chip: Rc<BinaryNumberChip<F, LimitType, 8>>,
+    pub limit_type_chip: Arc<BinaryNumberChip<F, LimitType, 2>>,
     pub is_limit_type_ctx: Column<Fixed>,
 }
 
+/// Row flags driving the block_level transitions of [`WasmBlockLevelAwareChip`]
+pub struct BlockLevelTransitions<F> {
+    pub q_enable: Expression<F>,
+    pub q_first: Expression<F>,
+    pub q_last: Expression<F>,
+    /// first row of a func body, the func body itself is a block
+    pub is_func_body_start: Expression<F>,
+    /// block/loop/if opcode rows
+    pub is_block_open: Expression<F>,
+    /// end opcode rows
+    pub is_block_end: Expression<F>,
+}
+
+/// 1 if the current limit type is followed by a max
+pub fn limit_type_has_max_expr<F: Field>(
+    limit_type_chip: &BinaryNumberChip<F, LimitType, 2>,
+    vc: &mut VirtualCells<F>,
+) -> Expression<F> {
+    limit_type_chip
+        .config
+        .value_equals(LimitType::MinMax, Rotation::cur())(vc)
+        + limit_type_chip
+            .config
+            .value_equals(LimitType::SharedMinMax, Rotation::cur())(vc)
+}
+
 pub fn configure_constraints_for_q_first_and_q_last<F: Field>(
     cb: &mut BaseConstraintBuilder<F>,
     vc: &mut VirtualCells<F>,
@@ -149,6 +182,93 @@ pub fn configure_constraints_for_q_first_and_q_last<F: Field>(
     );
 }
 
+/// `q_enable`/`q_first`/`q_last` selectors of a section chip
+#[derive(Debug, Clone, Copy)]
+pub struct SectionSelectors {
+    pub q_enable: Column<Fixed>,
+    pub q_first: Column<Fixed>,
+    pub q_last: Column<Fixed>,
+}
+
+impl SectionSelectors {
+    pub fn configure<F: Field>(cs: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            q_enable: cs.fixed_column(),
+            q_first: cs.fixed_column(),
+            q_last: cs.fixed_column(),
+        }
+    }
+
+    /// `q_first_column_selectors`/`q_last_column_selectors` are the markers one of which must be
+    /// active at the first/last row
+    pub fn configure_constraints<F: Field>(
+        &self,
+        cb: &mut BaseConstraintBuilder<F>,
+        vc: &mut VirtualCells<F>,
+        q_first_column_selectors: &[Column<Fixed>],
+        q_last_column_selectors: &[Column<Fixed>],
+    ) {
+        cb.require_boolean(
+            "q_enable is boolean",
+            vc.query_fixed(self.q_enable, Rotation::cur()),
+        );
+        configure_constraints_for_q_first_and_q_last(
+            cb,
+            vc,
+            &self.q_enable,
+            &self.q_first,
+            q_first_column_selectors,
+            &self.q_last,
+            q_last_column_selectors,
+        );
+    }
+
+    pub fn assign_q_enable<F: Field>(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        q_enable: bool,
+    ) -> Result<(), Error> {
+        Self::assign_selector(region, "q_enable", self.q_enable, offset, q_enable as u64)
+    }
+
+    pub fn assign_q_first<F: Field>(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        value: AssignValueType,
+    ) -> Result<(), Error> {
+        Self::assign_selector(region, "q_first", self.q_first, offset, value)
+    }
+
+    pub fn assign_q_last<F: Field>(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        value: AssignValueType,
+    ) -> Result<(), Error> {
+        Self::assign_selector(region, "q_last", self.q_last, offset, value)
+    }
+
+    fn assign_selector<F: Field>(
+        region: &mut Region<F>,
+        name: &str,
+        column: Column<Fixed>,
+        offset: usize,
+        value: AssignValueType,
+    ) -> Result<(), Error> {
+        region
+            .assign_fixed(
+                || format!("assign '{}' val {} at {}", name, value, offset),
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )
+            .map_err(remap_error_to_assign_at(offset))?;
+        Ok(())
+    }
+}
+
 /// `is_check_next` is check next or prev
 pub fn configure_transition_check<F: Field>(
     cb: &mut BaseConstraintBuilder<F>,
@@ -171,6 +291,98 @@ pub fn configure_transition_check<F: Field>(
     });
 }
 
+/// Allowed next rows of the rows marked by `from`
+#[derive(Clone, Debug)]
+pub struct SectionTransition<F> {
+    name: &'static str,
+    from: Column<Fixed>,
+    next: Vec<Column<Fixed>>,
+    is_leb: bool,
+    condition: Option<Expression<F>>,
+}
+
+impl<F: Field> SectionTransition<F> {
+    /// `from -> next`
+    pub fn new(name: &'static str, from: Column<Fixed>, next: &[Column<Fixed>]) -> Self {
+        Self {
+            name,
+            from,
+            next: next.to_vec(),
+            is_leb: false,
+            condition: None,
+        }
+    }
+
+    /// `from+ -> next`: `from` stays active up to the last leb byte, which is followed by `next`
+    pub fn leb(name: &'static str, from: Column<Fixed>, next: &[Column<Fixed>]) -> Self {
+        Self {
+            is_leb: true,
+            ..Self::new(name, from, next)
+        }
+    }
+
+    /// Checks the transition only where `condition` holds
+    pub fn when(mut self, condition: Expression<F>) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+}
+
+pub trait WasmSectionTransitionsAwareChip<F: Field> {
+    /// Checks the next row of every row of the section body but the last one against
+    /// `transitions`
+    fn configure_transitions(
+        cb: &mut BaseConstraintBuilder<F>,
+        vc: &mut VirtualCells<F>,
+        q_last: Column<Fixed>,
+        leb128_is_last_byte: Column<Fixed>,
+        transitions: Vec<SectionTransition<F>>,
+    ) {
+        let not_q_last_expr = not::expr(vc.query_fixed(q_last, Rotation::cur()));
+        let leb128_is_last_byte_expr = vc.query_fixed(leb128_is_last_byte, Rotation::cur());
+        for transition in transitions {
+            let mut condition = and::expr([
+                not_q_last_expr.clone(),
+                vc.query_fixed(transition.from, Rotation::cur()),
+            ]);
+            if let Some(c) = transition.condition {
+                condition = and::expr([condition, c]);
+            }
+            if !transition.is_leb {
+                configure_transition_check(
+                    cb,
+                    vc,
+                    transition.name,
+                    condition,
+                    true,
+                    &transition.next,
+                );
+                continue;
+            }
+            let mut next_within_leb = transition.next.clone();
+            if !next_within_leb.contains(&transition.from) {
+                next_within_leb.insert(0, transition.from);
+            }
+            configure_transition_check(
+                cb,
+                vc,
+                transition.name,
+                condition.clone(),
+                true,
+                &next_within_leb,
+            );
+            configure_transition_check(
+                cb,
+                vc,
+                transition.name,
+                and::expr([condition, leb128_is_last_byte_expr.clone()]),
+                true,
+                &transition.next,
+            );
+        }
+    }
+}
+
 pub trait WasmLenPrefixedBytesSpanAwareChip<F: Field> {
     fn configure_len_prefixed_bytes_span_checks(
         cs: &mut ConstraintSystem<F>,
@@ -315,16 +527,14 @@ pub trait WasmLimitTypeAwareChip<F: Field> {
         let is_limit_type_ctx = cs.fixed_column();
         let limit_type = cs.advice_column();
         let config = BinaryNumberChip::configure(cs, is_limit_type_ctx, Some(limit_type.into()));
-        let limit_type_chip = Rc::new(BinaryNumberChip::construct(config));
+        let limit_type_chip = Arc::new(BinaryNumberChip::construct(config));
 
         let limit_type_params_lt_chip_config = LtChip::configure(
             cs,
             |vc| {
                 and::expr([
                     vc.query_fixed(q_enable, Rotation::cur()),
-                    limit_type_chip
-                        .config
-                        .value_equals(LimitType::MinMax, Rotation::cur())(vc),
+                    limit_type_has_max_expr(&limit_type_chip, vc),
                     vc.query_fixed(is_limit_min, Rotation::prev()),
                     vc.query_fixed(is_limit_max, Rotation::cur()),
                 ])
@@ -333,7 +543,7 @@ pub trait WasmLimitTypeAwareChip<F: Field> {
             |vc| vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
         );
         let limit_type_params_lt_chip =
-            Rc::new(LtChip::construct(limit_type_params_lt_chip_config));
+            Arc::new(LtChip::construct(limit_type_params_lt_chip_config));
 
         LimitTypeFields {
             is_limit_type,
@@ -352,6 +562,7 @@ pub trait WasmLimitTypeAwareChip<F: Field> {
         q_enable: Column<Fixed>,
         leb128_chip: &LEB128Chip<F>,
         limit_type_fields: &LimitTypeFields<F>,
+        byte_set_table_config: &ByteSetTableConfig<F>,
     ) {
         let LimitTypeFields {
             is_limit_type,
@@ -362,6 +573,18 @@ pub trait WasmLimitTypeAwareChip<F: Field> {
             limit_type_chip,
             is_limit_type_ctx,
         } = limit_type_fields;
+        byte_set_table_config.lookup_byte(
+            cs,
+            "limit_type => byte value is valid",
+            ByteSetTag::LimitType,
+            |vc| {
+                (
+                    vc.query_fixed(q_enable, Rotation::cur())
+                        * vc.query_fixed(*is_limit_type, Rotation::cur()),
+                    vc.query_advice(bytecode_table.value, Rotation::cur()),
+                )
+            },
+        );
         cs.create_gate("limit_type structure gate", |vc| {
             let mut cb = BaseConstraintBuilder::default();
 
@@ -383,13 +606,6 @@ pub trait WasmLimitTypeAwareChip<F: Field> {
                 is_limit_type_ctx_expr.clone(),
             );
 
-            cb.condition(is_limit_type_expr.clone(), |cb| {
-                cb.require_in_set(
-                    "limit_type => byte value is valid",
-                    byte_val_expr.clone(),
-                    vec![LimitType::MinOnly.expr(), LimitType::MinMax.expr()],
-                )
-            });
             cb.require_equal(
                 "is_limit_type_ctx active on a specific flags only",
                 is_limit_type_expr.clone() + is_limit_min_expr.clone() + is_limit_max_expr.clone(),
@@ -424,9 +640,7 @@ pub trait WasmLimitTypeAwareChip<F: Field> {
             cb.condition(
                 and::expr([
                     vc.query_fixed(q_enable, Rotation::cur()),
-                    limit_type_chip
-                        .config
-                        .value_equals(LimitType::MinMax, Rotation::cur())(vc),
+                    limit_type_has_max_expr(limit_type_chip, vc),
                     vc.query_fixed(*is_limit_min, Rotation::prev()),
                     vc.query_fixed(*is_limit_max, Rotation::cur()),
                 ]),
@@ -445,15 +659,22 @@ pub trait WasmLimitTypeAwareChip<F: Field> {
 }
 
 pub trait WasmSharedStateAwareChip<F: Field> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>>;
+    fn shared_state(&self) -> SharedStateRef;
 }
 
 pub trait WasmFuncCountAwareChip<F: Field>: WasmSharedStateAwareChip<F> {
     fn func_count_col(&self) -> Column<Advice>;
 
     fn assign_func_count(&self, region: &mut Region<F>, assign_offset: usize) -> Result<(), Error> {
-        let func_count = self.shared_state().borrow().func_count;
+        let func_count = self.shared_state().read().func_count;
         debug!("assign at {} func_count val {}", assign_offset, func_count);
+        recorder::record(
+            std::any::type_name::<Self>(),
+            "func_count",
+            assign_offset,
+            func_count as u64,
+            || format!("func_count val {}", func_count),
+        );
         region
             .assign_advice(
                 || {
@@ -472,15 +693,37 @@ pub trait WasmFuncCountAwareChip<F: Field>: WasmSharedStateAwareChip<F> {
     }
 }
 
+/// Error code of the failure of the bytecode ([`ErrorCode`](super::types::ErrorCode), 0 if it
+/// did not fail) along with its inverse, their product flags the rows of a failed bytecode
+#[derive(Copy, Clone, Debug)]
+pub struct ErrorCodeColumns {
+    pub code: Column<Advice>,
+    pub code_inv: Column<Advice>,
+}
+
+impl ErrorCodeColumns {
+    pub fn configure<F: Field>(cs: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            code: cs.advice_column(),
+            code_inv: cs.advice_column(),
+        }
+    }
+
+    /// 1 at the rows of a failed bytecode, 0 otherwise
+    pub fn is_error_expr<F: Field>(&self, vc: &mut VirtualCells<F>, at: Rotation) -> Expression<F> {
+        vc.query_advice(self.code, at) * vc.query_advice(self.code_inv, at)
+    }
+}
+
 pub trait WasmErrorAwareChip<F: Field>: WasmSharedStateAwareChip<F> {
-    fn error_code_col(&self) -> Column<Advice>;
+    fn error_code_col(&self) -> ErrorCodeColumns;
 
     fn configure_error_code(
         cs: &mut ConstraintSystem<F>,
         q_enable: Column<Fixed>,
         q_first: Column<Fixed>,
         q_last: Column<Fixed>,
-        error_code: Column<Advice>,
+        error_code: ErrorCodeColumns,
     ) {
         cs.create_gate("ErrorCode gate", |vc| {
             let mut cb = BaseConstraintBuilder::default();
@@ -490,27 +733,27 @@ pub trait WasmErrorAwareChip<F: Field>: WasmSharedStateAwareChip<F> {
             let not_q_first_expr = not::expr(q_first_expr.clone());
             let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
             let not_q_last_expr = not::expr(q_last_expr.clone());
-            let error_code_expr = vc.query_advice(error_code, Rotation::cur());
+            let error_code_expr = vc.query_advice(error_code.code, Rotation::cur());
+            let is_error_expr = error_code.is_error_expr(vc, Rotation::cur());
 
-            cb.require_boolean("error_code is bool", error_code_expr.clone());
+            cb.require_zero(
+                "error_code!=0 => is_error=1",
+                error_code_expr.clone() * not::expr(is_error_expr.clone()),
+            );
 
             cb.condition(
-                and::expr([not_q_first_expr.clone(), not::expr(error_code_expr.clone())]),
+                and::expr([not_q_first_expr.clone(), not::expr(is_error_expr.clone())]),
                 |cb| {
-                    let error_code_prev_expr = vc.query_advice(error_code, Rotation::prev());
-                    cb.require_equal(
-                        "error_code=0 => prev.error_code=0",
-                        error_code_expr.clone(),
-                        error_code_prev_expr.clone(),
-                    );
+                    let error_code_prev_expr = vc.query_advice(error_code.code, Rotation::prev());
+                    cb.require_zero("error_code=0 => prev.error_code=0", error_code_prev_expr);
                 },
             );
             cb.condition(
-                and::expr([not_q_last_expr.clone(), error_code_expr.clone()]),
+                and::expr([not_q_last_expr.clone(), is_error_expr.clone()]),
                 |cb| {
-                    let error_code_next_expr = vc.query_advice(error_code, Rotation::next());
+                    let error_code_next_expr = vc.query_advice(error_code.code, Rotation::next());
                     cb.require_equal(
-                        "error_code=1 => next.error_code=1",
+                        "error_code!=0 => next.error_code=error_code",
                         error_code_expr.clone(),
                         error_code_next_expr.clone(),
                     );
@@ -527,8 +770,26 @@ pub trait WasmErrorAwareChip<F: Field>: WasmSharedStateAwareChip<F> {
         assign_offset: usize,
         error_code_replacer: Option<u64>,
     ) -> Result<(), Error> {
-        let error_code = error_code_replacer.unwrap_or(self.shared_state().borrow().error_code);
+        let error_code = error_code_replacer.unwrap_or(self.shared_state().read().error_code);
         debug!("assign at {} error_code val {}", assign_offset, error_code);
+        recorder::record(
+            std::any::type_name::<Self>(),
+            recorder::ERROR_CODE_COLUMN,
+            assign_offset,
+            error_code,
+            || format!("error_code val {}", error_code),
+        );
+        self.assign_error_code_cells(region, assign_offset, error_code)
+    }
+
+    /// Assigns `error_code` with its inverse at `assign_offset`
+    fn assign_error_code_cells(
+        &self,
+        region: &mut Region<F>,
+        assign_offset: usize,
+        error_code: u64,
+    ) -> Result<(), Error> {
+        let error_code_col = self.error_code_col();
         region
             .assign_advice(
                 || {
@@ -537,11 +798,19 @@ pub trait WasmErrorAwareChip<F: Field>: WasmSharedStateAwareChip<F> {
                         error_code, assign_offset
                     )
                 },
-                self.error_code_col(),
+                error_code_col.code,
                 assign_offset,
                 || Value::known(F::from(error_code)),
             )
             .map_err(remap_error_to_assign_at(assign_offset))?;
+        region
+            .assign_advice(
+                || format!("assign 'error_code_inv' at {}", assign_offset),
+                error_code_col.code_inv,
+                assign_offset,
+                || Value::known(F::from(error_code).invert().unwrap_or(F::zero())),
+            )
+            .map_err(remap_error_to_assign_at(assign_offset))?;
         Ok(())
     }
 
@@ -552,17 +821,21 @@ pub trait WasmErrorAwareChip<F: Field>: WasmSharedStateAwareChip<F> {
         len: usize,
         explicit_error_code: Option<u64>,
     ) -> Result<(), Error> {
-        let error_code = explicit_error_code.unwrap_or(self.shared_state().borrow().error_code);
+        let error_code = explicit_error_code.unwrap_or(self.shared_state().read().error_code);
+        let error_reason = self.shared_state().read().error_reason;
         for offset in assign_offset..assign_offset + len {
-            debug!("assign at {} error_code val {}", offset, error_code);
-            region
-                .assign_advice(
-                    || format!("assign 'error_code' val {} at {}", error_code, offset),
-                    self.error_code_col(),
-                    offset,
-                    || Value::known(F::from(error_code)),
-                )
-                .map_err(remap_error_to_assign_at(offset))?;
+            debug!(
+                "assign at {} error_code val {} reason {:?}",
+                offset, error_code, error_reason
+            );
+            recorder::record(
+                std::any::type_name::<Self>(),
+                recorder::ERROR_CODE_COLUMN,
+                offset,
+                error_code,
+                || format!("error_code val {} (rest, reason {:?})", error_code, error_reason),
+            );
+            self.assign_error_code_cells(region, offset, error_code)?;
         }
         Ok(())
     }
@@ -571,15 +844,13 @@ pub trait WasmErrorAwareChip<F: Field>: WasmSharedStateAwareChip<F> {
         vc: &mut VirtualCells<F>,
         q_enable: Column<Fixed>,
         shared_state: &SharedState,
-        error_code: Column<Advice>,
+        error_code: ErrorCodeColumns,
     ) -> Expression<F> {
         let q_enable_expr = vc.query_fixed(q_enable, Rotation::cur());
-        let error_code_expr = vc.query_advice(error_code, Rotation::cur());
-        let not_error_code_expr = not::expr(error_code_expr);
 
         q_enable_expr
             * if shared_state.error_processing_enabled {
-                not_error_code_expr
+                not::expr(error_code.is_error_expr(vc, Rotation::cur()))
             } else {
                 1.expr()
             }
@@ -638,11 +909,18 @@ pub trait WasmBytecodeNumberAwareChip<F: Field>: WasmSharedStateAwareChip<F> {
         explicit_bytecode_number: Option<u64>,
     ) -> Result<(), Error> {
         let bytecode_number =
-            explicit_bytecode_number.unwrap_or(self.shared_state().borrow().bytecode_number);
+            explicit_bytecode_number.unwrap_or(self.shared_state().read().bytecode_number);
         debug!(
             "assign at {} bytecode_number val {}",
             assign_offset, bytecode_number
         );
+        recorder::record(
+            std::any::type_name::<Self>(),
+            "bytecode_number",
+            assign_offset,
+            bytecode_number,
+            || format!("bytecode_number val {}", bytecode_number),
+        );
         region
             .assign_advice(
                 || {
@@ -664,16 +942,129 @@ pub trait WasmBytecodeNumberAwareChip<F: Field>: WasmSharedStateAwareChip<F> {
 pub trait WasmBlockLevelAwareChip<F: Field>: WasmSharedStateAwareChip<F> {
     fn block_level_col(&self) -> Column<Advice>;
 
+    /// block_level is 0 on the first and last rows, 1 on a func body start, grows on block
+    /// opens, drops on block ends and is carried over on the other rows
+    fn configure_block_level(
+        cs: &mut ConstraintSystem<F>,
+        block_level: Column<Advice>,
+        transitions: impl FnOnce(&mut VirtualCells<F>) -> BlockLevelTransitions<F>,
+    ) {
+        cs.create_gate("block_level gate", |vc| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let BlockLevelTransitions {
+                q_enable,
+                q_first,
+                q_last,
+                is_func_body_start,
+                is_block_open,
+                is_block_end,
+            } = transitions(vc);
+            let block_level_expr = vc.query_advice(block_level, Rotation::cur());
+            let block_level_prev_expr = vc.query_advice(block_level, Rotation::prev());
+
+            cb.condition(q_first.clone(), |cb| {
+                cb.require_zero("q_first => block_level=0", block_level_expr.clone());
+            });
+            cb.condition(q_last.clone(), |cb| {
+                cb.require_zero("q_last => block_level=0", block_level_expr.clone());
+            });
+            cb.condition(is_func_body_start.clone(), |cb| {
+                cb.require_equal(
+                    "is_func_body_start => block_level=1",
+                    block_level_expr.clone(),
+                    1.expr(),
+                );
+            });
+            cb.condition(is_block_open.clone(), |cb| {
+                cb.require_equal(
+                    "is_block_open => block_level=prev.block_level+1",
+                    block_level_expr.clone(),
+                    block_level_prev_expr.clone() + 1.expr(),
+                );
+            });
+            cb.condition(is_block_end.clone(), |cb| {
+                cb.require_equal(
+                    "is_block_end => block_level=prev.block_level-1",
+                    block_level_expr.clone(),
+                    block_level_prev_expr.clone() - 1.expr(),
+                );
+            });
+            cb.condition(
+                and::expr([
+                    not::expr(q_first),
+                    not::expr(q_last),
+                    not::expr(is_func_body_start),
+                    not::expr(is_block_open),
+                    not::expr(is_block_end),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "block_level=prev.block_level",
+                        block_level_expr.clone(),
+                        block_level_prev_expr.clone(),
+                    );
+                },
+            );
+
+            cb.gate(q_enable)
+        });
+    }
+
+    /// Configures the `label < block_level` check of branch instructions on rows enabled by
+    /// `q_enable`
+    fn configure_branch_depth_lt_chip(
+        cs: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<F>) -> Expression<F>,
+        label: impl FnOnce(&mut VirtualCells<F>) -> Expression<F>,
+        block_level: Column<Advice>,
+    ) -> Arc<LtChip<F, 2>> {
+        let config = LtChip::configure(cs, q_enable, label, |vc| {
+            vc.query_advice(block_level, Rotation::cur())
+        });
+        Arc::new(LtChip::construct(config))
+    }
+
+    /// Assigns the branch depth check of `label` against the current block_level
+    fn assign_branch_depth(
+        &self,
+        region: &mut Region<F>,
+        branch_depth_lt_chip: &LtChip<F, 2>,
+        assign_offset: usize,
+        label: u64,
+    ) -> Result<(), Error> {
+        let block_level = self.shared_state().read().block_level;
+        debug!(
+            "assign at {} branch depth label {} block_level {}",
+            assign_offset, label, block_level,
+        );
+        branch_depth_lt_chip
+            .assign(
+                region,
+                assign_offset,
+                F::from(label),
+                F::from(block_level as u64),
+            )
+            .map_err(remap_error(Error::FatalAssignExternalChip))
+    }
+
     fn assign_block_level(
         &self,
         region: &mut Region<F>,
         assign_offset: usize,
     ) -> Result<(), Error> {
-        let block_level = self.shared_state().borrow().block_level;
+        let block_level = self.shared_state().read().block_level;
         debug!(
             "assign at {} block_level val {}",
             assign_offset, block_level
         );
+        recorder::record(
+            std::any::type_name::<Self>(),
+            "block_level",
+            assign_offset,
+            block_level as u64,
+            || format!("block_level val {}", block_level),
+        );
         region
             .assign_advice(
                 || {
@@ -692,7 +1083,7 @@ pub trait WasmBlockLevelAwareChip<F: Field>: WasmSharedStateAwareChip<F> {
 }
 
 pub trait WasmAssignAwareChip<F: Field> {
-    type AssignType;
+    type AssignType: std::fmt::Debug;
 
     fn assign(
         &self,
@@ -705,6 +1096,17 @@ pub trait WasmAssignAwareChip<F: Field> {
         leb_params: Option<LebParams>,
     ) -> Result<(), Error> {
         validate_wb_offset(wb, wb_offset)?;
+        if recorder::is_recording() {
+            for assign_type in assign_types {
+                recorder::record(
+                    std::any::type_name::<Self>(),
+                    &format!("{:?}", assign_type),
+                    wb_offset + assign_delta,
+                    assign_value,
+                    || format!("wb_offset {} leb_params {:?}", wb_offset, leb_params),
+                );
+            }
+        }
         self.assign_internal(
             region,
             wb,
@@ -728,6 +1130,54 @@ pub trait WasmAssignAwareChip<F: Field> {
     ) -> Result<(), Error>;
 }
 
+/// Chips and columns shared between section body chips (`WasmChip` owns them in the full circuit)
+#[derive(Debug, Clone)]
+pub struct SectionBodyChipResources<F: Field> {
+    pub wb_table: Arc<WasmBytecodeTable>,
+    pub leb128_chip: Arc<LEB128Chip<F>>,
+    pub utf8_chip: Arc<UTF8Chip<F>>,
+    pub byte_set_table_config: Arc<ByteSetTableConfig<F>>,
+    pub opcode_table_config: Arc<OpcodeTableConfig<F>>,
+    pub dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
+    pub shared_state: SharedStateRef,
+    pub func_count: Column<Advice>,
+    pub body_byte_rev_index_l2: Column<Advice>,
+    pub body_item_rev_count_l1: Column<Advice>,
+    pub body_item_rev_count_l2: Column<Advice>,
+    pub error_code: ErrorCodeColumns,
+    /// assigned by `WasmChip` only, section body chips query it for their lookups so all the rows
+    /// of a bytecode agree on its bytecode_number
+    pub bytecode_number: Column<Advice>,
+    /// memory limits above it are rejected, see
+    /// [`WasmCircuitOptions::max_memory_pages`](crate::wasm_circuit::builder::WasmCircuitOptions)
+    pub max_memory_pages: u64,
+}
+
+/// Uniform configure/assign entry points of section body chips, lets generic code (e.g. test
+/// harnesses) drive any of them
+pub trait SectionBodyChip<F: Field>: WasmSharedStateAwareChip<F> + Sized {
+    const SECTION: WasmSection;
+
+    fn configure_with_resources(
+        cs: &mut ConstraintSystem<F>,
+        resources: &SectionBodyChipResources<F>,
+    ) -> Self;
+
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: WbOffsetType,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error>;
+
+    /// Growth of `func_count` at the current row relative to the previous one, zero for the
+    /// sections not declaring functions
+    fn func_count_increment_expr(&self, _vc: &mut VirtualCells<F>) -> Expression<F> {
+        0.expr()
+    }
+}
+
 pub trait WasmMarkupLeb128SectionAwareChip<F: Field>: WasmAssignAwareChip<F> {
     fn markup_leb_section(
         &self,
@@ -833,33 +1283,6 @@ pub trait WasmNameAwareChip<F: Field>: WasmAssignAwareChip<F> {
     }
 }
 
-pub fn digit_char_to_number(ch: &char) -> u8 {
-    *ch as u8 - 48
-}
-
-pub fn wasm_compute_section_len(
-    wb: &[u8],
-    len_start_index: usize,
-) -> Result<(SectionLengthType, Leb128BytesCountType), Error> {
-    let mut section_len: usize = 0;
-    let mut i = len_start_index;
-    loop {
-        let byte = wb.get(i).ok_or(Error::IndexOutOfBoundsSimple)?;
-        let mut byte_val: u32 = (byte & 0b1111111) as u32;
-        let pow = checked_pow(0b10000000, i - len_start_index).ok_or(Error::ComputationFailed)?;
-        byte_val = byte_val * pow;
-        section_len += byte_val as usize;
-        if byte & 0b10000000 == 0 {
-            break;
-        }
-        i += 1;
-        if i - len_start_index >= MAX_LEB128_BYTES {
-            return Err(Error::Leb128MaxBytes);
-        }
-    }
-    Ok((section_len, (i - len_start_index + 1) as u8))
-}
-
 #[cfg(any(feature = "test", test))]
 pub fn wat_extract_section_bytecode(path_to_file: &str, kind: Kind) -> Vec<u8> {
     let wat: Vec<u8> = std::fs::read(path_to_file).unwrap();
@@ -0,0 +1,3 @@
+pub mod generator;
+#[cfg(any(feature = "test", test))]
+pub mod tests;
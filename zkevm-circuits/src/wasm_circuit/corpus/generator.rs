@@ -0,0 +1,124 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::wasm_circuit::{
+    error::Error,
+    leb128::consts::EIGHT_MS_BIT_MASK,
+    validation::{types::SectionReport, validate_module},
+};
+
+pub const CORPUS_MANIFEST_FILE_NAME: &str = "manifest.json";
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MutationKind {
+    /// bytecode is cut in the middle of a section body
+    Truncation,
+    /// continuation bit of the section length first byte is flipped
+    FlippedContinuationBit,
+    /// ids of two neighbour sections are swapped
+    SwappedSectionIds,
+}
+
+/// Corrupted variant of a fixture
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CorpusVariant {
+    pub name: String,
+    pub fixture: String,
+    pub mutation: MutationKind,
+    pub bytes: Vec<u8>,
+}
+
+/// Manifest entry of a committed variant. `error_class` is the class of the error (variant name
+/// of [`Error`] without its offset) the variant must be rejected with. The manifest is written
+/// by hand from the mutation and the fixture layout, the generator doesn't touch it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CorpusEntry {
+    pub name: String,
+    pub fixture: String,
+    pub mutation: MutationKind,
+    pub error_class: String,
+}
+
+pub fn error_class(error: &Error) -> String {
+    let repr = format!("{:?}", error);
+    repr.split('(').next().unwrap_or_default().to_string()
+}
+
+fn mutate(bytes: &[u8], sections: &[SectionReport], kind: MutationKind) -> Vec<Vec<u8>> {
+    let mut variants = vec![];
+    match kind {
+        MutationKind::Truncation => {
+            for s in sections {
+                let len = (s.section_body_start_offset + s.section_end_offset + 1) / 2;
+                variants.push(bytes[..len].to_vec());
+            }
+        }
+        MutationKind::FlippedContinuationBit => {
+            for s in sections {
+                let mut variant = bytes.to_vec();
+                variant[s.section_start_offset + 1] ^= EIGHT_MS_BIT_MASK;
+                variants.push(variant);
+            }
+        }
+        MutationKind::SwappedSectionIds => {
+            for pair in sections.windows(2) {
+                let mut variant = bytes.to_vec();
+                variant.swap(pair[0].section_start_offset, pair[1].section_start_offset);
+                variants.push(variant);
+            }
+        }
+    }
+    variants
+}
+
+/// Systematically corrupts a valid fixture, every section gives a truncated variant and a
+/// flipped continuation bit one, every pair of neighbour sections a swapped ids one
+pub fn generate_variants(fixture: &str, bytes: &[u8]) -> Result<Vec<CorpusVariant>, Error> {
+    let report = validate_module(bytes);
+    if let Some(e) = report.error {
+        return Err(Error::FatalInvalidArgumentValue(format!(
+            "fixture '{}' must be valid but has error {:?}",
+            fixture, e
+        )));
+    }
+    let mut res = vec![];
+    for kind in [
+        MutationKind::Truncation,
+        MutationKind::FlippedContinuationBit,
+        MutationKind::SwappedSectionIds,
+    ] {
+        for (idx, variant) in mutate(bytes, &report.sections, kind).into_iter().enumerate() {
+            res.push(CorpusVariant {
+                name: format!("{}_{:?}_{}", fixture, kind, idx),
+                fixture: fixture.to_string(),
+                mutation: kind,
+                bytes: variant,
+            });
+        }
+    }
+    Ok(res)
+}
+
+/// Writes variants as `<name>.wasm` files into `dir`, entries of new variants have to be added
+/// to the manifest by hand
+pub fn write_corpus(dir: &Path, variants: &[CorpusVariant]) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for variant in variants {
+        std::fs::write(dir.join(format!("{}.wasm", variant.name)), &variant.bytes)?;
+    }
+    Ok(())
+}
+
+pub fn read_corpus(dir: &Path) -> std::io::Result<Vec<(CorpusEntry, Vec<u8>)>> {
+    let json = std::fs::read_to_string(dir.join(CORPUS_MANIFEST_FILE_NAME))?;
+    let entries: Vec<CorpusEntry> = serde_json::from_str(&json)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    entries
+        .into_iter()
+        .map(|entry| {
+            let bytes = std::fs::read(dir.join(format!("{}.wasm", entry.name)))?;
+            Ok((entry, bytes))
+        })
+        .collect()
+}
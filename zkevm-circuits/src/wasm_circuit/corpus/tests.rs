@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod wasm_corpus_tests {
+    use std::path::Path;
+
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use wabt::wat2wasm;
+
+    use crate::wasm_circuit::{
+        bytecode::bytecode::WasmBytecode,
+        circuit::WasmCircuit,
+        corpus::generator::{
+            error_class, generate_variants, read_corpus, write_corpus, CorpusVariant,
+        },
+        validation::validate_module,
+    };
+
+    const CORPUS_DIR: &str = "./test_files/corpus";
+    const FIXTURES: &[&str] = &["func_export", "start_data"];
+
+    fn generate_all() -> Vec<CorpusVariant> {
+        let mut variants = vec![];
+        for fixture in FIXTURES {
+            let path = format!("{}/fixtures/{}.wat", CORPUS_DIR, fixture);
+            let bytes = wat2wasm(std::fs::read(path).unwrap()).unwrap();
+            variants.extend(generate_variants(fixture, &bytes).unwrap());
+        }
+        variants
+    }
+
+    /// for dev only: rewrites the variants after fixtures or the generator change, the manifest
+    /// is kept up to date by hand
+    #[ignore]
+    #[test]
+    pub fn regenerate_corpus() {
+        write_corpus(Path::new(CORPUS_DIR), &generate_all()).unwrap();
+    }
+
+    #[test]
+    pub fn corpus_is_up_to_date() {
+        let corpus = read_corpus(Path::new(CORPUS_DIR)).expect("corpus must be committed");
+        let committed = corpus
+            .iter()
+            .map(|(entry, bytes)| (&entry.name, &entry.fixture, entry.mutation, bytes))
+            .collect::<Vec<_>>();
+        let variants = generate_all();
+        let generated = variants
+            .iter()
+            .map(|v| (&v.name, &v.fixture, v.mutation, &v.bytes))
+            .collect::<Vec<_>>();
+        assert_eq!(committed, generated);
+    }
+
+    #[test]
+    pub fn corpus_variants_rejected() {
+        let variants = read_corpus(Path::new(CORPUS_DIR)).expect("corpus must be committed");
+        assert!(!variants.is_empty());
+        for (entry, bytes) in variants {
+            let error = validate_module(&bytes).error;
+            assert_eq!(
+                error.as_ref().map(error_class),
+                Some(entry.error_class.clone()),
+                "variant '{}'",
+                entry.name
+            );
+
+            let circuit = WasmCircuit::<Fr>::new(vec![WasmBytecode::new(bytes)]);
+            let outputs = circuit.compute_module_outputs(9).unwrap();
+            let reason = outputs[0].error_reason.expect("corpus variants fail");
+            assert_eq!(outputs[0].error_code, reason.error_code as u64, "variant '{}'", entry.name);
+            let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+            let prover = MockProver::run(9, &circuit, instance).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+}
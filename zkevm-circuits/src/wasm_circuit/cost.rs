@@ -0,0 +1,4 @@
+pub mod cost;
+pub mod layout;
+#[cfg(any(feature = "test", test))]
+pub mod tests;
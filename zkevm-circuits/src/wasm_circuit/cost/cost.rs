@@ -0,0 +1,205 @@
+use std::{fmt, ops::Range};
+
+use halo2_proofs::plonk::ConstraintSystem;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use eth_types::Field;
+
+/// Sizes of a constraint system (or of the part of it allocated by a single chip)
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CsCounts {
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub selectors: usize,
+    pub gates: usize,
+    pub constraints: usize,
+    pub lookups: usize,
+}
+
+impl CsCounts {
+    pub fn of<F: Field>(cs: &ConstraintSystem<F>) -> Self {
+        Self {
+            advice_columns: cs.num_advice_columns(),
+            fixed_columns: cs.num_fixed_columns(),
+            selectors: cs.num_selectors(),
+            gates: cs.gates().len(),
+            constraints: cs.gates().iter().map(|g| g.polynomials().len()).sum(),
+            lookups: cs.lookups().len(),
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.advice_columns += other.advice_columns;
+        self.fixed_columns += other.fixed_columns;
+        self.selectors += other.selectors;
+        self.gates += other.gates;
+        self.constraints += other.constraints;
+        self.lookups += other.lookups;
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        Self {
+            advice_columns: self.advice_columns - other.advice_columns,
+            fixed_columns: self.fixed_columns - other.fixed_columns,
+            selectors: self.selectors - other.selectors,
+            gates: self.gates - other.gates,
+            constraints: self.constraints - other.constraints,
+            lookups: self.lookups - other.lookups,
+        }
+    }
+}
+
+/// Index ranges of the columns, selectors, gates and lookups allocated by a chip, one range per
+/// [`CostReport::track`] call that allocated any
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CsRanges {
+    pub advice_columns: Vec<Range<usize>>,
+    pub fixed_columns: Vec<Range<usize>>,
+    pub selectors: Vec<Range<usize>>,
+    pub gates: Vec<Range<usize>>,
+    pub lookups: Vec<Range<usize>>,
+}
+
+impl CsRanges {
+    fn push(&mut self, from: &CsCounts, to: &CsCounts) {
+        for (ranges, start, end) in [
+            (&mut self.advice_columns, from.advice_columns, to.advice_columns),
+            (&mut self.fixed_columns, from.fixed_columns, to.fixed_columns),
+            (&mut self.selectors, from.selectors, to.selectors),
+            (&mut self.gates, from.gates, to.gates),
+            (&mut self.lookups, from.lookups, to.lookups),
+        ] {
+            if start < end {
+                ranges.push(start..end);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChipCost {
+    pub chip: String,
+    pub counts: CsCounts,
+    #[serde(default)]
+    pub ranges: CsRanges,
+}
+
+/// Per chip cost of a constraint system. Everything allocated since the previous
+/// [`Self::track`] call is attributed to the tracked chip, repeated tracks of the same chip are
+/// summed up. Allocations made before [`Self::new`] (e.g. bytecode table) are not included.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CostReport {
+    pub chips: Vec<ChipCost>,
+    #[serde(skip)]
+    last: CsCounts,
+}
+
+impl CostReport {
+    pub fn new<F: Field>(cs: &ConstraintSystem<F>) -> Self {
+        Self {
+            chips: vec![],
+            last: CsCounts::of(cs),
+        }
+    }
+
+    pub fn track<F: Field>(&mut self, chip: &str, cs: &ConstraintSystem<F>) {
+        let current = CsCounts::of(cs);
+        let previous = std::mem::replace(&mut self.last, current);
+        let delta = current.sub(&previous);
+        let chip_cost = match self.chips.iter().position(|c| c.chip == chip) {
+            Some(idx) => &mut self.chips[idx],
+            None => {
+                self.chips.push(ChipCost {
+                    chip: chip.to_string(),
+                    ..Default::default()
+                });
+                self.chips.last_mut().unwrap()
+            }
+        };
+        chip_cost.counts.add(&delta);
+        chip_cost.ranges.push(&previous, &current);
+    }
+
+    /// Chip which allocated the item at `idx`, `ranges` picks the kind of item
+    pub fn owner(
+        &self,
+        idx: usize,
+        ranges: impl Fn(&CsRanges) -> &[Range<usize>],
+    ) -> Option<&str> {
+        self.chips
+            .iter()
+            .find(|c| ranges(&c.ranges).iter().any(|r| r.contains(&idx)))
+            .map(|c| c.chip.as_str())
+    }
+
+    pub fn chip(&self, chip: &str) -> Option<&CsCounts> {
+        self.chips.iter().find(|c| c.chip == chip).map(|c| &c.counts)
+    }
+
+    pub fn total(&self) -> CsCounts {
+        let mut total = CsCounts::default();
+        for chip_cost in &self.chips {
+            total.add(&chip_cost.counts);
+        }
+        total
+    }
+}
+
+impl fmt::Display for CostReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<32} {:>8} {:>8} {:>9} {:>8} {:>11} {:>8}",
+            "chip", "advice", "fixed", "selectors", "gates", "constraints", "lookups"
+        )?;
+        let total = self.total();
+        let rows = self
+            .chips
+            .iter()
+            .map(|c| (c.chip.as_str(), &c.counts))
+            .chain(std::iter::once(("total", &total)));
+        for (chip, c) in rows {
+            writeln!(
+                f,
+                "{:<32} {:>8} {:>8} {:>9} {:>8} {:>11} {:>8}",
+                chip,
+                c.advice_columns,
+                c.fixed_columns,
+                c.selectors,
+                c.gates,
+                c.constraints,
+                c.lookups
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn digest(repr: &str) -> String {
+    hex::encode(&Keccak256::digest(repr.as_bytes())[..8])
+}
+
+/// Structure of the constraint system as lines of `<kind> <name> <digest>` (one per gate and
+/// lookup, digests cover the whole expressions and queried columns) ended by the column counts
+/// and a digest of everything above. Any change of gates or lookups changes the shape.
+pub fn cs_shape<F: Field>(cs: &ConstraintSystem<F>) -> Vec<String> {
+    let mut lines = vec![];
+    for gate in cs.gates() {
+        lines.push(format!("gate '{}' {}", gate.name(), digest(&format!("{:?}", gate))));
+    }
+    for (idx, lookup) in cs.lookups().iter().enumerate() {
+        lines.push(format!("lookup #{} {}", idx, digest(&format!("{:?}", lookup))));
+    }
+    let counts = CsCounts::of(cs);
+    lines.push(format!(
+        "columns advice {} fixed {} instance {} selectors {}",
+        counts.advice_columns,
+        counts.fixed_columns,
+        cs.num_instance_columns(),
+        counts.selectors
+    ));
+    let total = digest(&lines.join("\n"));
+    lines.push(format!("total {}", total));
+    lines
+}
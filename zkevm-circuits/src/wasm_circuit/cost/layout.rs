@@ -0,0 +1,191 @@
+use std::collections::BTreeSet;
+
+use halo2_proofs::plonk::{ConstraintSystem, Expression};
+use serde::{Deserialize, Serialize};
+
+use eth_types::Field;
+
+use crate::wasm_circuit::cost::cost::{cs_shape, CostReport};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnKind {
+    Advice,
+    Fixed,
+    Instance,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ColumnLayout {
+    pub kind: ColumnKind,
+    pub index: usize,
+    /// chip which allocated the column, `None` for columns allocated outside of the tracked chips
+    pub chip: Option<String>,
+    pub role: Option<String>,
+}
+
+/// Cell queried by a gate or lookup expression
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct ColumnQuery {
+    pub kind: ColumnKind,
+    pub index: usize,
+    pub rotation: i32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GateLayout {
+    pub name: String,
+    pub chip: Option<String>,
+    pub constraints: Vec<String>,
+    pub queries: Vec<ColumnQuery>,
+}
+
+/// Cells queried by each input and table expression of a lookup
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LookupLayout {
+    pub index: usize,
+    pub chip: Option<String>,
+    pub inputs: Vec<Vec<ColumnQuery>>,
+    pub table: Vec<Vec<ColumnQuery>>,
+}
+
+/// Public values of an instance column, `values` repeat once per proven item (e.g. module)
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct InstanceLayout {
+    pub column: usize,
+    pub values: Vec<String>,
+}
+
+/// Machine readable layout of a constraint system: column roles and owners, gates, lookups and
+/// instance layout. Selectors are listed as they were configured, before keygen compresses them
+/// into fixed columns. `shape_digest` is the total digest of [`cs_shape`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CircuitLayout {
+    pub degree: usize,
+    pub blinding_factors: usize,
+    pub selectors: usize,
+    pub columns: Vec<ColumnLayout>,
+    pub gates: Vec<GateLayout>,
+    pub lookups: Vec<LookupLayout>,
+    pub instances: Vec<InstanceLayout>,
+    pub shape_digest: String,
+}
+
+fn queries<F: Field>(expr: &Expression<F>) -> Vec<ColumnQuery> {
+    let query = |kind, index, rotation| BTreeSet::from([ColumnQuery {
+        kind,
+        index,
+        rotation,
+    }]);
+    let union = |mut a: BTreeSet<ColumnQuery>, b: BTreeSet<ColumnQuery>| {
+        a.extend(b);
+        a
+    };
+    expr.evaluate(
+        &|_| BTreeSet::new(),
+        &|_| BTreeSet::new(),
+        &|q| query(ColumnKind::Fixed, q.column_index(), q.rotation().0),
+        &|q| query(ColumnKind::Advice, q.column_index(), q.rotation().0),
+        &|q| query(ColumnKind::Instance, q.column_index(), q.rotation().0),
+        &|_| BTreeSet::new(),
+        &|a| a,
+        &union,
+        &union,
+        &|a, _| a,
+    )
+    .into_iter()
+    .collect()
+}
+
+impl CircuitLayout {
+    /// `roles` name columns, owners come from the ranges tracked by `cost_report`
+    pub fn new<F: Field>(
+        cs: &ConstraintSystem<F>,
+        cost_report: &CostReport,
+        roles: &[(ColumnKind, usize, &str)],
+        instances: Vec<InstanceLayout>,
+    ) -> Self {
+        let role = |kind, index| {
+            roles
+                .iter()
+                .find(|(k, i, _)| *k == kind && *i == index)
+                .map(|(_, _, role)| role.to_string())
+        };
+        let owned = |owner: Option<&str>| owner.map(str::to_string);
+
+        let mut columns = vec![];
+        for index in 0..cs.num_advice_columns() {
+            columns.push(ColumnLayout {
+                kind: ColumnKind::Advice,
+                index,
+                chip: owned(cost_report.owner(index, |r| r.advice_columns.as_slice())),
+                role: role(ColumnKind::Advice, index),
+            });
+        }
+        for index in 0..cs.num_fixed_columns() {
+            columns.push(ColumnLayout {
+                kind: ColumnKind::Fixed,
+                index,
+                chip: owned(cost_report.owner(index, |r| r.fixed_columns.as_slice())),
+                role: role(ColumnKind::Fixed, index),
+            });
+        }
+        for index in 0..cs.num_instance_columns() {
+            columns.push(ColumnLayout {
+                kind: ColumnKind::Instance,
+                index,
+                chip: None,
+                role: role(ColumnKind::Instance, index),
+            });
+        }
+
+        let gates = cs
+            .gates()
+            .iter()
+            .enumerate()
+            .map(|(idx, gate)| GateLayout {
+                name: gate.name().to_string(),
+                chip: owned(cost_report.owner(idx, |r| r.gates.as_slice())),
+                constraints: (0..gate.polynomials().len())
+                    .map(|i| gate.constraint_name(i).to_string())
+                    .collect(),
+                queries: gate
+                    .polynomials()
+                    .iter()
+                    .flat_map(queries)
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect(),
+            })
+            .collect();
+        let lookups = cs
+            .lookups()
+            .iter()
+            .enumerate()
+            .map(|(index, lookup)| LookupLayout {
+                index,
+                chip: owned(cost_report.owner(index, |r| r.lookups.as_slice())),
+                inputs: lookup.input_expressions().iter().map(queries).collect(),
+                table: lookup.table_expressions().iter().map(queries).collect(),
+            })
+            .collect();
+
+        Self {
+            degree: cs.degree(),
+            blinding_factors: cs.blinding_factors(),
+            selectors: cs.num_selectors(),
+            columns,
+            gates,
+            lookups,
+            instances,
+            shape_digest: cs_shape(cs)
+                .pop()
+                .and_then(|total| total.strip_prefix("total ").map(str::to_string))
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("layout is serializable")
+    }
+}
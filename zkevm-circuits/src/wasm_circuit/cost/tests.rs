@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod wasm_cost_tests {
+    use std::sync::Arc;
+
+    use halo2_proofs::{
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+
+    use crate::wasm_circuit::{
+        builder::WasmCircuitBuilder,
+        bytecode::bytecode_table::WasmBytecodeTable,
+        circuit::{WasmChip, WasmCircuit},
+        consts::WASM_MODULE_OUTPUT_LEN,
+        cost::{
+            cost::CsCounts,
+            layout::{CircuitLayout, ColumnKind},
+        },
+        types::{SharedStateRef, WasmSection},
+    };
+
+    /// for dev only
+    #[ignore]
+    #[test]
+    pub fn print_cost_report() {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        let wb_table = Arc::new(WasmBytecodeTable::construct(&mut cs, false));
+        let shared_state = SharedStateRef::default();
+        let config = WasmChip::configure(&mut cs, wb_table, shared_state);
+        println!("{}", config.cost_report);
+    }
+
+    #[test]
+    pub fn report_sums_up_to_cs() {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        let wb_table = Arc::new(WasmBytecodeTable::construct(&mut cs, false));
+        let before = CsCounts::of(&cs);
+        let shared_state = SharedStateRef::default();
+        let config = WasmChip::configure(&mut cs, wb_table, shared_state);
+        let after = CsCounts::of(&cs);
+
+        let total = config.cost_report.total();
+        assert_eq!(total.advice_columns, after.advice_columns - before.advice_columns);
+        assert_eq!(total.fixed_columns, after.fixed_columns - before.fixed_columns);
+        assert_eq!(total.gates, after.gates - before.gates);
+        assert_eq!(total.lookups, after.lookups - before.lookups);
+        assert!(config.cost_report.chip("WasmCodeSectionBodyChip").unwrap().advice_columns > 0);
+
+        let table = config.cost_report.to_string();
+        assert!(table.contains("LEB128Chip"));
+        assert!(table.lines().last().unwrap().starts_with("total"));
+    }
+
+    #[test]
+    pub fn layout_covers_cs() {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        WasmCircuit::<Fr>::configure(&mut cs);
+        let layout = WasmCircuit::<Fr>::layout();
+
+        let count = |kind| layout.columns.iter().filter(|c| c.kind == kind).count();
+        assert_eq!(count(ColumnKind::Advice), cs.num_advice_columns());
+        assert_eq!(count(ColumnKind::Fixed), cs.num_fixed_columns());
+        assert_eq!(count(ColumnKind::Instance), 1);
+        assert_eq!(layout.gates.len(), cs.gates().len());
+        assert_eq!(layout.lookups.len(), cs.lookups().len());
+        assert_eq!(layout.instances[0].values.len(), WASM_MODULE_OUTPUT_LEN);
+
+        // only the bytecode table and the instance are allocated outside of the tracked chips
+        let unowned = layout
+            .columns
+            .iter()
+            .filter(|c| c.chip.is_none())
+            .map(|c| c.role.as_deref().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            unowned,
+            ["wb_table.index", "wb_table.value", "wb_table.code_hash", "module_outputs"]
+        );
+        let func_count = layout
+            .columns
+            .iter()
+            .find(|c| c.role.as_deref() == Some("func_count"))
+            .unwrap();
+        assert_eq!(func_count.chip.as_deref(), Some("WasmChip"));
+        assert!(layout
+            .gates
+            .iter()
+            .all(|g| g.chip.is_some() && !g.queries.is_empty()));
+        assert!(layout
+            .lookups
+            .iter()
+            .any(|l| l.chip.as_deref() == Some("LEB128Chip")));
+
+        let restored: CircuitLayout = serde_json::from_str(&layout.to_json()).unwrap();
+        assert_eq!(restored, layout);
+    }
+
+    #[test]
+    pub fn disabled_section_has_no_cost() {
+        let options = WasmCircuitBuilder::new()
+            .without_section(WasmSection::Code)
+            .build();
+        let mut cs = ConstraintSystem::<Fr>::default();
+        let wb_table = Arc::new(WasmBytecodeTable::construct(&mut cs, false));
+        let shared_state = SharedStateRef::default();
+        let config = WasmChip::configure_with_options(&mut cs, wb_table, shared_state, options);
+        assert!(config.cost_report.chip("WasmCodeSectionBodyChip").is_none());
+    }
+}
+
+/// Snapshot of the wasm circuit constraint system shape, stored in
+/// `./test_files/golden/cs_shape.txt`. A missing snapshot is a failure, set
+/// `WASM_GOLDEN_BLESS=1` to write it after an intended change of `configure()`.
+#[cfg(test)]
+mod wasm_cs_shape_tests {
+    use std::path::Path;
+
+    use halo2_proofs::{
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+
+    use crate::wasm_circuit::{circuit::WasmCircuit, cost::cost::cs_shape};
+
+    const SNAPSHOT_PATH: &str = "./test_files/golden/cs_shape.txt";
+
+    #[test]
+    pub fn cs_shape_matches_snapshot() {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        WasmCircuit::<Fr>::configure(&mut cs);
+        let actual = cs_shape(&cs).join("\n");
+
+        let path = Path::new(SNAPSHOT_PATH);
+        if std::env::var("WASM_GOLDEN_BLESS").is_ok() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, &actual).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(path).unwrap_or_else(|_| {
+            panic!("snapshot {:?} is missing, bless it with WASM_GOLDEN_BLESS=1", path)
+        });
+        let changed = expected
+            .lines()
+            .zip(actual.lines())
+            .filter(|(e, a)| e != a)
+            .map(|(e, a)| format!("- {}\n+ {}", e, a))
+            .collect::<Vec<_>>();
+        assert!(
+            expected == actual,
+            "wasm circuit constraint system shape changed, review the diff and rerun with \
+             WASM_GOLDEN_BLESS=1 if the change is intended:\n{}",
+            changed.join("\n")
+        );
+    }
+
+    #[test]
+    pub fn cs_shape_is_deterministic() {
+        let shape = || {
+            let mut cs = ConstraintSystem::<Fr>::default();
+            WasmCircuit::<Fr>::configure(&mut cs);
+            cs_shape(&cs)
+        };
+        assert_eq!(shape(), shape());
+    }
+}
@@ -0,0 +1,3 @@
+pub use wasm_witness::decoder;
+#[cfg(any(feature = "test", test))]
+pub mod tests;
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod wasm_decoder_tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use wabt::wat2wasm;
+
+    use crate::wasm_circuit::{
+        bytecode::bytecode::WasmBytecode,
+        circuit::WasmCircuit,
+        decoder::decoder::decode_module,
+        types::WasmSection,
+        validation::validate_module,
+    };
+
+    const PATHS: [&str; 3] = [
+        "./test_files/cc1.wat",
+        "./test_files/cc2.wat",
+        "./test_files/cc3.wat",
+    ];
+
+    fn wat_file_to_bytes(path: &str) -> Vec<u8> {
+        let data: Vec<u8> = std::fs::read(path).unwrap();
+        wat2wasm(data).unwrap()
+    }
+
+    #[test]
+    pub fn files_match_hand_rolled_walking() {
+        for path in PATHS {
+            let bytes = wat_file_to_bytes(path);
+            let module = decode_module(&bytes).unwrap();
+            let report = validate_module(&bytes);
+            assert_eq!(module.sections.len(), report.sections.len(), "file '{}'", path);
+            for (section, expected) in module.sections.iter().zip(report.sections.iter()) {
+                assert_eq!(section.section, expected.section);
+                assert_eq!(section.range.start, expected.section_start_offset);
+                assert_eq!(section.body.start, expected.section_body_start_offset);
+                assert_eq!(section.range.end, expected.section_end_offset + 1);
+                if section.section != WasmSection::Start {
+                    assert_eq!(Some(section.items.len() as u64), expected.items_count);
+                }
+            }
+            let code_items = module
+                .sections
+                .iter()
+                .find(|s| s.section == WasmSection::Code)
+                .map(|s| s.items.clone())
+                .unwrap_or_default();
+            assert_eq!(code_items, WasmBytecode::new(bytes).function_bodies());
+        }
+    }
+
+    #[test]
+    pub fn truncated_module_fails() {
+        let mut bytes = wat_file_to_bytes(PATHS[0]);
+        bytes.truncate(bytes.len() - 1);
+        assert!(decode_module(&bytes).is_err());
+    }
+
+    #[test]
+    pub fn files_with_reference_decoder_ok() {
+        let wbs = PATHS
+            .iter()
+            .map(|path| WasmBytecode::new(wat_file_to_bytes(path)))
+            .collect::<Vec<_>>();
+        let circuit = WasmCircuit::<Fr>::new(wbs).with_reference_decoder();
+        let outputs = circuit.compute_module_outputs(13).unwrap();
+        assert!(outputs.iter().all(|o| o.error_code == 0));
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+        let prover = MockProver::run(13, &circuit, instance).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn invalid_module_with_reference_decoder_sets_error_code() {
+        let mut bytes = wat_file_to_bytes(PATHS[0]);
+        bytes[8] = WasmSection::DataCount as u8 + 1;
+        let circuit =
+            WasmCircuit::<Fr>::new(vec![WasmBytecode::new(bytes)]).with_reference_decoder();
+        let outputs = circuit.compute_module_outputs(9).unwrap();
+        assert_eq!(outputs[0].error_code, 1);
+    }
+}
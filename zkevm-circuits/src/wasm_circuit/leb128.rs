@@ -1,5 +1,4 @@
 #[cfg(any(feature = "test", test))]
 pub mod tests;
-pub mod consts;
+pub use wasm_witness::leb128::{consts, helpers};
 pub mod circuit;
-pub mod helpers;
@@ -121,6 +121,7 @@ mod leb128_circuit_tests {
 
     use eth_types::Field;
 
+    use crate::test_rng::{test_rng, TestRng};
     use crate::wasm_circuit::error::Error;
     use crate::wasm_circuit::leb128::consts::{EIGHT_LS_BITS_MASK, EIGHT_MS_BIT_MASK, SEVEN_LS_BITS_MASK};
     use crate::wasm_circuit::leb128::tests::TestCircuit;
@@ -213,12 +214,12 @@ mod leb128_circuit_tests {
         )?)
     }
 
-    pub fn leb128_break_continuation_bit(rng: &mut rand::prelude::ThreadRng, leb128: &mut Vec<u8>) {
+    pub fn leb128_break_continuation_bit(rng: &mut TestRng, leb128: &mut Vec<u8>) {
         let byte_number = rng.gen::<usize>() % leb128.len();
         break_bit_by_mask(&mut leb128[byte_number], EIGHT_MS_BIT_MASK);
     }
 
-    pub fn leb_break_random_bit(rng: &mut rand::prelude::ThreadRng, leb128: &mut Vec<u8>) {
+    pub fn leb_break_random_bit(rng: &mut TestRng, leb128: &mut Vec<u8>) {
         let byte_to_break_number = rng.gen::<usize>() % leb128.len();
         let bit_to_break_number = rng.gen::<u64>() % 8;
         let bit_to_break_mask = 1 << bit_to_break_number;
@@ -303,7 +304,7 @@ mod leb128_circuit_tests {
     }
 
     pub fn eligible_numbers<const LEB_BYTES_N: usize, const IS_SIGNED: bool>() {
-        let mut rng = rand::thread_rng();
+        let mut rng = test_rng();
         let mut numbers_to_check = Vec::<(bool, u64)>::new();
         if !IS_SIGNED { // 0 cannot be SIGNED
             numbers_to_check.push((IS_SIGNED, 0));
@@ -375,7 +376,7 @@ mod leb128_circuit_tests {
     }
 
     pub fn leb_broken_continuation_bit<const LEB_BYTES_N: usize, const IS_SIGNED: bool>() {
-        let mut rng = rand::thread_rng();
+        let mut rng = test_rng();
         let mut solid_numbers_to_check = Vec::<u64>::new();
         solid_numbers_to_check.push(1);
         for i in 0..leb128_bytes_n_to_max_bit_depth(IS_SIGNED, LEB_BYTES_N).unwrap() - 1 {
@@ -448,7 +449,7 @@ mod leb128_circuit_tests {
     }
 
     pub fn leb_broken_random_bit<const LEB_BYTES_N: usize, const IS_SIGNED: bool>() {
-        let mut rng = rand::thread_rng();
+        let mut rng = test_rng();
         let mut solid_numbers_to_check = Vec::<u64>::new();
         solid_numbers_to_check.push(0);
         solid_numbers_to_check.push(1);
@@ -519,4 +520,179 @@ mod leb128_circuit_tests {
         leb_broken_random_bit::<8, IS_SIGNED>();
         leb_broken_random_bit::<9, IS_SIGNED>();
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod leb128_proptests {
+    use std::marker::PhantomData;
+
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use proptest::{collection::vec, prelude::*};
+
+    use crate::wasm_circuit::leb128::{
+        consts::{EIGHT_LS_BITS_MASK, EIGHT_MS_BIT_MASK, LEB128_MAX_BYTES_COUNT},
+        helpers::{leb128_compute_sn, leb128_compute_sn_recovered_at_position, leb128_encode},
+        tests::TestCircuit,
+    };
+
+    const UNSIGNED_BOUNDARY_VALUES: &[u64] = &[
+        0,
+        1,
+        63,
+        64,
+        127,
+        128,
+        u8::MAX as u64,
+        u16::MAX as u64,
+        u32::MAX as u64,
+        u32::MAX as u64 + 1,
+        i64::MAX as u64,
+        u64::MAX,
+    ];
+    const SIGNED_BOUNDARY_VALUES: &[i64] = &[
+        -1,
+        -63,
+        -64,
+        -65,
+        -128,
+        i32::MIN as i64,
+        i32::MIN as i64 - 1,
+        i64::MIN + 1,
+        i64::MIN,
+    ];
+
+    /// LEB128 of the value, extended with `padding` redundant continuation bytes (non-canonical
+    /// but valid encoding). `value` must be non-negative for unsigned and negative for signed.
+    fn leb128_encode_padded(is_signed: bool, value: i64, padding: usize) -> Vec<u8> {
+        let mut bytes = if is_signed {
+            leb128_encode(true, value as i128).unwrap()
+        } else {
+            leb128_encode(false, value as u64 as i128).unwrap()
+        };
+        if padding == 0 {
+            return bytes;
+        }
+        let fill = if is_signed { EIGHT_LS_BITS_MASK } else { EIGHT_MS_BIT_MASK };
+        *bytes.last_mut().unwrap() |= EIGHT_MS_BIT_MASK;
+        bytes.extend(std::iter::repeat(fill).take(padding - 1));
+        bytes.push(fill & !EIGHT_MS_BIT_MASK);
+        bytes
+    }
+
+    /// runs LEB128Chip over `leb_bytes` aligned to `aligned_len` with unused bytes
+    fn chip_verify(is_signed: bool, leb_bytes: &[u8], aligned_len: usize, sn: u64) -> bool {
+        let mut bytes = leb_bytes.to_vec();
+        let fill = if is_signed { EIGHT_LS_BITS_MASK } else { 0 };
+        bytes.resize(aligned_len.max(leb_bytes.len()), fill);
+        let leb_bytes_last_byte_index = leb_bytes.len() as u64 - 1;
+        let prover = if is_signed {
+            let circuit = TestCircuit::<Fr, true> {
+                leb_bytes: &bytes,
+                leb_bytes_last_byte_index,
+                is_signed,
+                sn,
+                offset_shift: 0,
+                _marker: PhantomData,
+            };
+            MockProver::run(5, &circuit, vec![]).unwrap()
+        } else {
+            let circuit = TestCircuit::<Fr, false> {
+                leb_bytes: &bytes,
+                leb_bytes_last_byte_index,
+                is_signed,
+                sn,
+                offset_shift: 0,
+                _marker: PhantomData,
+            };
+            MockProver::run(5, &circuit, vec![]).unwrap()
+        };
+        prover.verify().is_ok()
+    }
+
+    fn check_roundtrip(is_signed: bool, value: i64, padding: usize, prefix: &[u8]) {
+        let leb_bytes = leb128_encode_padded(is_signed, value, padding);
+        let sn = if is_signed { value.unsigned_abs() } else { value as u64 };
+
+        let mut bytes = prefix.to_vec();
+        bytes.extend_from_slice(&leb_bytes);
+        let (sn_computed, last_byte_offset) =
+            leb128_compute_sn(&bytes, is_signed, prefix.len()).unwrap();
+        assert_eq!(sn_computed, sn, "leb128 {:x?}", leb_bytes);
+        assert_eq!(last_byte_offset, bytes.len() - 1);
+
+        let last_byte_rel_offset = leb_bytes.len() - 1;
+        let sn_recovered = leb_bytes.iter().enumerate().fold(0, |acc, (i, &b)| {
+            leb128_compute_sn_recovered_at_position(acc, is_signed, i, last_byte_rel_offset, b)
+        });
+        assert_eq!(sn_recovered, sn);
+    }
+
+    #[test]
+    pub fn boundary_values_roundtrip_ok() {
+        for &value in UNSIGNED_BOUNDARY_VALUES {
+            check_roundtrip(false, value as i64, 0, &[]);
+            let leb_bytes = leb128_encode_padded(false, value as i64, 0);
+            assert!(chip_verify(false, &leb_bytes, leb_bytes.len(), value));
+        }
+        for &value in SIGNED_BOUNDARY_VALUES {
+            check_roundtrip(true, value, 0, &[]);
+            let leb_bytes = leb128_encode_padded(true, value, 0);
+            assert!(chip_verify(true, &leb_bytes, leb_bytes.len(), value.unsigned_abs()));
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn unsigned_roundtrip_ok(
+            value in any::<u64>(),
+            padding in 0..LEB128_MAX_BYTES_COUNT,
+            prefix in vec(any::<u8>(), 0..4),
+        ) {
+            let len = leb128_encode_padded(false, value as i64, 0).len();
+            prop_assume!(len + padding <= LEB128_MAX_BYTES_COUNT);
+            check_roundtrip(false, value as i64, padding, &prefix);
+        }
+
+        #[test]
+        fn signed_roundtrip_ok(
+            value in i64::MIN..0i64,
+            padding in 0..LEB128_MAX_BYTES_COUNT,
+            prefix in vec(any::<u8>(), 0..4),
+        ) {
+            let len = leb128_encode_padded(true, value, 0).len();
+            prop_assume!(len + padding <= LEB128_MAX_BYTES_COUNT);
+            check_roundtrip(true, value, padding, &prefix);
+        }
+
+        #[test]
+        fn chip_unsigned_ok(
+            value in any::<u64>(),
+            padding in 0..LEB128_MAX_BYTES_COUNT,
+            alignment in 0..LEB128_MAX_BYTES_COUNT,
+        ) {
+            let leb_bytes = leb128_encode_padded(false, value as i64, 0);
+            prop_assume!(leb_bytes.len() + padding <= LEB128_MAX_BYTES_COUNT);
+            let leb_bytes = leb128_encode_padded(false, value as i64, padding);
+            prop_assert!(chip_verify(false, &leb_bytes, alignment, value));
+        }
+
+        #[test]
+        fn chip_signed_ok(
+            value in i64::MIN..0i64,
+            padding in 0..LEB128_MAX_BYTES_COUNT,
+            alignment in 0..LEB128_MAX_BYTES_COUNT,
+        ) {
+            let leb_bytes = leb128_encode_padded(true, value, 0);
+            prop_assume!(leb_bytes.len() + padding <= LEB128_MAX_BYTES_COUNT);
+            let leb_bytes = leb128_encode_padded(true, value, padding);
+            prop_assert!(chip_verify(true, &leb_bytes, alignment, value.unsigned_abs()));
+        }
+
+        #[test]
+        fn chip_wrong_sn_fails(value in 1..u32::MAX as u64, delta in 1..u32::MAX as u64) {
+            let leb_bytes = leb128_encode_padded(false, value as i64, 0);
+            prop_assert!(!chip_verify(false, &leb_bytes, leb_bytes.len(), value + delta));
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod circuit;
+#[cfg(any(feature = "test", test))]
+pub mod tests;
@@ -0,0 +1,504 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Expression, Fixed, Instance},
+    poly::Rotation,
+};
+use log::debug;
+
+use bus_mapping::wasm::{
+    memory_tree::WASM_MEMORY_TREE_NODE_DOMAIN, WasmMemoryTreeAccess, WASM_MEMORY_CELL_SIZE,
+    WASM_MEMORY_TREE_DEPTH,
+};
+use eth_types::Field;
+use gadgets::util::{and, not, Expr};
+
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    table::PoseidonTable,
+    wasm_circuit::{
+        common::configure_constraints_for_q_first_and_q_last,
+        error::{remap_error_to_assign_at, Error},
+        tables::fixed_range::config::RangeTableConfig,
+        types::AssignDeltaType,
+    },
+};
+
+/// Rows of an access, from the leaf up to the root
+pub const ROWS_PER_ACCESS: usize = WASM_MEMORY_TREE_DEPTH + 1;
+
+/// Openings and updates of memory tree cells. Every access takes [`ROWS_PER_ACCESS`] rows, one
+/// per node on the path of the cell, and every node is the poseidon hash of the node below and
+/// its sibling. Accesses are chained: each one starts at the root the previous one ended at.
+/// The cell values of the `q_leaf` rows are split into bytes, so they fit 64 bits.
+///
+/// The chip is standalone: nothing looks up its rows yet and wasm load/store steps still read
+/// and write memory byte by byte (`memory_read_cell`/`memory_write_cell` push a memory op per
+/// byte of the cell). Looking up the openings needs the load/store execution gadgets, which are
+/// not part of the evm circuit yet (see `evm_circuit/wasm.rs`), and a cell memory tag in the
+/// state circuit so the byte ops can be dropped.
+#[derive(Debug, Clone)]
+pub struct MemoryTreeConfig<F: Field> {
+    pub q_enable: Column<Fixed>,
+    pub q_first: Column<Fixed>,
+    pub q_last: Column<Fixed>,
+    pub q_leaf: Column<Fixed>,
+    pub q_root: Column<Fixed>,
+    /// index of the cell shifted right by the level of the row
+    pub cell_index: Column<Advice>,
+    /// low bit of `cell_index`, set when the node is a right child
+    pub path_bit: Column<Advice>,
+    pub sibling: Column<Advice>,
+    /// node before and after the access
+    pub old_node: Column<Advice>,
+    pub new_node: Column<Advice>,
+    pub is_write: Column<Advice>,
+    /// little endian bytes of the cell value before and after the access on `q_leaf` rows
+    pub old_value_bytes: [Column<Advice>; WASM_MEMORY_CELL_SIZE as usize],
+    pub new_value_bytes: [Column<Advice>; WASM_MEMORY_CELL_SIZE as usize],
+    /// roots before and after the access, the same on all its rows
+    pub old_root: Column<Advice>,
+    pub new_root: Column<Advice>,
+
+    _marker: PhantomData<F>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemoryTreeChip<F: Field> {
+    pub config: MemoryTreeConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> MemoryTreeChip<F> {
+    pub fn construct(config: MemoryTreeConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        poseidon_table: &PoseidonTable,
+        range_table_config_0_256: &RangeTableConfig<F, 0, 256>,
+    ) -> MemoryTreeConfig<F> {
+        let q_enable = cs.fixed_column();
+        let q_first = cs.fixed_column();
+        let q_last = cs.fixed_column();
+        let q_leaf = cs.fixed_column();
+        let q_root = cs.fixed_column();
+        let cell_index = cs.advice_column();
+        let path_bit = cs.advice_column();
+        let sibling = cs.advice_column();
+        let old_node = cs.advice_column();
+        let new_node = cs.advice_column();
+        let is_write = cs.advice_column();
+        let old_value_bytes = std::array::from_fn(|_| cs.advice_column());
+        let new_value_bytes = std::array::from_fn(|_| cs.advice_column());
+        let old_root = cs.advice_column();
+        let new_root = cs.advice_column();
+        cs.enable_equality(old_root);
+        cs.enable_equality(new_root);
+
+        cs.create_gate("MemoryTree gate", |vc| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_enable_expr = vc.query_fixed(q_enable, Rotation::cur());
+            let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
+            let q_leaf_expr = vc.query_fixed(q_leaf, Rotation::cur());
+            let q_root_expr = vc.query_fixed(q_root, Rotation::cur());
+
+            let cell_index_expr = vc.query_advice(cell_index, Rotation::cur());
+            let path_bit_expr = vc.query_advice(path_bit, Rotation::cur());
+            let old_node_expr = vc.query_advice(old_node, Rotation::cur());
+            let new_node_expr = vc.query_advice(new_node, Rotation::cur());
+            let is_write_expr = vc.query_advice(is_write, Rotation::cur());
+            let old_root_expr = vc.query_advice(old_root, Rotation::cur());
+            let new_root_expr = vc.query_advice(new_root, Rotation::cur());
+
+            let cell_index_next_expr = vc.query_advice(cell_index, Rotation::next());
+            let is_write_next_expr = vc.query_advice(is_write, Rotation::next());
+            let old_root_next_expr = vc.query_advice(old_root, Rotation::next());
+            let new_root_next_expr = vc.query_advice(new_root, Rotation::next());
+
+            let mut value_expr = |bytes: [Column<Advice>; WASM_MEMORY_CELL_SIZE as usize]| {
+                bytes.iter().rev().fold(0.expr(), |value, byte| {
+                    value * 256.expr() + vc.query_advice(*byte, Rotation::cur())
+                })
+            };
+            let old_value_expr = value_expr(old_value_bytes);
+            let new_value_expr = value_expr(new_value_bytes);
+
+            configure_constraints_for_q_first_and_q_last(
+                &mut cb,
+                vc,
+                &q_enable,
+                &q_first,
+                &[q_leaf],
+                &q_last,
+                &[q_root],
+            );
+
+            cb.condition(q_leaf_expr, |cb| {
+                cb.require_boolean("is_write is boolean", is_write_expr.clone());
+                cb.require_equal(
+                    "old cell value is its bytes",
+                    old_node_expr.clone(),
+                    old_value_expr,
+                );
+                cb.require_equal(
+                    "new cell value is its bytes",
+                    new_node_expr.clone(),
+                    new_value_expr,
+                );
+                cb.require_zero(
+                    "reads keep the cell value",
+                    not::expr(is_write_expr.clone())
+                        * (new_node_expr.clone() - old_node_expr.clone()),
+                );
+            });
+            cb.condition(not::expr(q_root_expr.clone()), |cb| {
+                cb.require_boolean("path_bit is boolean", path_bit_expr.clone());
+                cb.require_equal(
+                    "cell_index = 2 * next.cell_index + path_bit",
+                    cell_index_expr.clone(),
+                    2.expr() * cell_index_next_expr + path_bit_expr.clone(),
+                );
+                cb.require_equal(
+                    "is_write is the same on the rows of an access",
+                    is_write_next_expr,
+                    is_write_expr.clone(),
+                );
+                cb.require_equal(
+                    "old_root is the same on the rows of an access",
+                    old_root_next_expr.clone(),
+                    old_root_expr.clone(),
+                );
+                cb.require_equal(
+                    "new_root is the same on the rows of an access",
+                    new_root_next_expr,
+                    new_root_expr.clone(),
+                );
+            });
+            cb.condition(q_root_expr.clone(), |cb| {
+                cb.require_zero("cell index fits the tree", cell_index_expr.clone());
+                cb.require_equal("root is old_root", old_node_expr.clone(), old_root_expr);
+                cb.require_equal("root is new_root", new_node_expr.clone(), new_root_expr.clone());
+            });
+            cb.condition(and::expr([q_root_expr, not::expr(q_last_expr)]), |cb| {
+                cb.require_equal(
+                    "access starts at the root the previous one ended at",
+                    old_root_next_expr,
+                    new_root_expr,
+                );
+            });
+
+            cb.gate(q_enable_expr)
+        });
+
+        for (name, node) in [
+            ("memory tree old node is the hash of its children", old_node),
+            ("memory tree new node is the hash of its children", new_node),
+        ] {
+            cs.lookup_any(name, |vc| {
+                let cond = vc.query_fixed(q_enable, Rotation::cur())
+                    * not::expr(vc.query_fixed(q_root, Rotation::cur()));
+                let node_expr = vc.query_advice(node, Rotation::cur());
+                let sibling_expr = vc.query_advice(sibling, Rotation::cur());
+                let path_bit_expr = vc.query_advice(path_bit, Rotation::cur());
+                let left_expr = node_expr.clone()
+                    + path_bit_expr.clone() * (sibling_expr.clone() - node_expr.clone());
+                let right_expr = sibling_expr.clone() + path_bit_expr * (node_expr - sibling_expr);
+                let inputs: [(Expression<F>, Column<Advice>); 5] = [
+                    (vc.query_advice(node, Rotation::next()), poseidon_table.hash_id),
+                    (left_expr, poseidon_table.input0),
+                    (right_expr, poseidon_table.input1),
+                    (
+                        Expression::Constant(F::from_u128(WASM_MEMORY_TREE_NODE_DOMAIN)),
+                        poseidon_table.control,
+                    ),
+                    (1.expr(), poseidon_table.heading_mark),
+                ];
+                inputs
+                    .into_iter()
+                    .map(|(input, column)| {
+                        (cond.clone() * input, vc.query_advice(column, Rotation::cur()))
+                    })
+                    .collect()
+            });
+        }
+
+        for byte in old_value_bytes.into_iter().chain(new_value_bytes) {
+            cs.lookup("memory tree cell value byte is in range", |vc| {
+                vec![(
+                    vc.query_fixed(q_enable, Rotation::cur())
+                        * vc.query_fixed(q_leaf, Rotation::cur())
+                        * vc.query_advice(byte, Rotation::cur()),
+                    range_table_config_0_256.value,
+                )]
+            });
+        }
+
+        MemoryTreeConfig {
+            q_enable,
+            q_first,
+            q_last,
+            q_leaf,
+            q_root,
+            cell_index,
+            path_bit,
+            sibling,
+            old_node,
+            new_node,
+            is_write,
+            old_value_bytes,
+            new_value_bytes,
+            old_root,
+            new_root,
+            _marker: PhantomData,
+        }
+    }
+
+    fn assign_advice(
+        &self,
+        region: &mut Region<F>,
+        name: &str,
+        column: Column<Advice>,
+        assign_offset: usize,
+        value: F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        region
+            .assign_advice(
+                || format!("assign '{}' val {:?} at {}", name, value, assign_offset),
+                column,
+                assign_offset,
+                || Value::known(value),
+            )
+            .map_err(remap_error_to_assign_at(assign_offset))
+    }
+
+    fn assign_fixed(
+        &self,
+        region: &mut Region<F>,
+        name: &str,
+        column: Column<Fixed>,
+        assign_offset: usize,
+    ) -> Result<(), Error> {
+        region
+            .assign_fixed(
+                || format!("assign '{}' val 1 at {}", name, assign_offset),
+                column,
+                assign_offset,
+                || Value::known(F::one()),
+            )
+            .map_err(remap_error_to_assign_at(assign_offset))?;
+        Ok(())
+    }
+
+    /// Assigns [`ROWS_PER_ACCESS`] rows per access starting at `assign_delta`, returns the
+    /// `old_root` cell of the first access and the `new_root` cell of the last one
+    pub fn assign(
+        &self,
+        region: &mut Region<F>,
+        accesses: &[WasmMemoryTreeAccess<F>],
+        assign_delta: AssignDeltaType,
+    ) -> Result<Option<(AssignedCell<F, F>, AssignedCell<F, F>)>, Error> {
+        let mut root_cells = None;
+        for (access_index, access) in accesses.iter().enumerate() {
+            if access.siblings.len() != WASM_MEMORY_TREE_DEPTH {
+                return Err(Error::FatalInvalidArgumentValue(format!(
+                    "memory tree: {} siblings for a tree of depth {}",
+                    access.siblings.len(),
+                    WASM_MEMORY_TREE_DEPTH
+                )));
+            }
+            debug!(
+                "assign at {} memory tree access {} cell {} {} -> {} is_write {}",
+                assign_delta + access_index * ROWS_PER_ACCESS,
+                access_index,
+                access.cell_index,
+                access.old_value,
+                access.new_value,
+                access.is_write,
+            );
+            let old_path = access.path(access.old_value);
+            let new_path = access.path(access.new_value);
+            for level in 0..ROWS_PER_ACCESS {
+                let assign_offset = assign_delta + access_index * ROWS_PER_ACCESS + level;
+                let is_root = level == WASM_MEMORY_TREE_DEPTH;
+                self.assign_fixed(region, "q_enable", self.config.q_enable, assign_offset)?;
+                if access_index == 0 && level == 0 {
+                    self.assign_fixed(region, "q_first", self.config.q_first, assign_offset)?;
+                }
+                if access_index + 1 == accesses.len() && is_root {
+                    self.assign_fixed(region, "q_last", self.config.q_last, assign_offset)?;
+                }
+                if level == 0 {
+                    self.assign_fixed(region, "q_leaf", self.config.q_leaf, assign_offset)?;
+                }
+                if is_root {
+                    self.assign_fixed(region, "q_root", self.config.q_root, assign_offset)?;
+                }
+
+                let cell_index = access.cell_index >> level;
+                let (path_bit, sibling) = if is_root {
+                    (0, F::zero())
+                } else {
+                    (cell_index & 1, access.siblings[level])
+                };
+                for (name, column, value) in [
+                    ("cell_index", self.config.cell_index, F::from(cell_index)),
+                    ("path_bit", self.config.path_bit, F::from(path_bit)),
+                    ("sibling", self.config.sibling, sibling),
+                    ("old_node", self.config.old_node, old_path[level]),
+                    ("new_node", self.config.new_node, new_path[level]),
+                    ("is_write", self.config.is_write, F::from(access.is_write as u64)),
+                ] {
+                    self.assign_advice(region, name, column, assign_offset, value)?;
+                }
+                // only the leaf row holds the cell values
+                let (old_value, new_value) = if level == 0 {
+                    (access.old_value, access.new_value)
+                } else {
+                    (0, 0)
+                };
+                for (name, columns, value) in [
+                    ("old_value_byte", self.config.old_value_bytes, old_value),
+                    ("new_value_byte", self.config.new_value_bytes, new_value),
+                ] {
+                    for (column, byte) in columns.into_iter().zip(value.to_le_bytes()) {
+                        let value = F::from(byte as u64);
+                        self.assign_advice(region, name, column, assign_offset, value)?;
+                    }
+                }
+                let old_root_cell = self.assign_advice(
+                    region,
+                    "old_root",
+                    self.config.old_root,
+                    assign_offset,
+                    access.old_root,
+                )?;
+                let new_root_cell = self.assign_advice(
+                    region,
+                    "new_root",
+                    self.config.new_root,
+                    assign_offset,
+                    access.new_root,
+                )?;
+                if is_root {
+                    let first_old_root_cell = root_cells
+                        .take()
+                        .map_or(old_root_cell, |(first_old_root_cell, _)| first_old_root_cell);
+                    root_cells = Some((first_old_root_cell, new_root_cell));
+                }
+            }
+        }
+        Ok(root_cells)
+    }
+
+    /// Poseidon table rows of the node hashes on the paths of `accesses`
+    pub fn poseidon_rows(accesses: &[WasmMemoryTreeAccess<F>]) -> Vec<[Value<F>; 5]> {
+        let mut rows = vec![];
+        for access in accesses {
+            for value in [access.old_value, access.new_value] {
+                let path = access.path(value);
+                for (level, sibling) in access.siblings.iter().enumerate() {
+                    let (left, right) = if (access.cell_index >> level) & 1 == 0 {
+                        (path[level], *sibling)
+                    } else {
+                        (*sibling, path[level])
+                    };
+                    rows.push(
+                        [
+                            path[level + 1],
+                            left,
+                            right,
+                            F::from_u128(WASM_MEMORY_TREE_NODE_DOMAIN),
+                            F::one(),
+                        ]
+                        .map(Value::known),
+                    );
+                }
+            }
+        }
+        rows
+    }
+}
+
+/// Proves a sequence of memory tree accesses moves the root from the first instance value to
+/// the second one
+#[derive(Clone, Debug, Default)]
+pub struct MemoryTreeCircuit<F> {
+    pub accesses: Vec<WasmMemoryTreeAccess<F>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MemoryTreeCircuitConfig<F: Field> {
+    pub poseidon_table: PoseidonTable,
+    pub range_table_config_0_256: RangeTableConfig<F, 0, 256>,
+    pub chip: MemoryTreeChip<F>,
+    pub instance: Column<Instance>,
+}
+
+impl<F: Field> MemoryTreeCircuit<F> {
+    pub fn new(accesses: Vec<WasmMemoryTreeAccess<F>>) -> Self {
+        Self { accesses }
+    }
+
+    pub fn instance(&self) -> Vec<Vec<F>> {
+        match (self.accesses.first(), self.accesses.last()) {
+            (Some(first), Some(last)) => vec![vec![first.old_root, last.new_root]],
+            _ => vec![vec![]],
+        }
+    }
+}
+
+impl<F: Field> Circuit<F> for MemoryTreeCircuit<F> {
+    type Config = MemoryTreeCircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+        let poseidon_table = PoseidonTable::dev_construct(cs);
+        let range_table_config_0_256 = RangeTableConfig::configure(cs);
+        let config = MemoryTreeChip::configure(cs, &poseidon_table, &range_table_config_0_256);
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+        MemoryTreeCircuitConfig {
+            poseidon_table,
+            range_table_config_0_256,
+            chip: MemoryTreeChip::construct(config),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), halo2_proofs::plonk::Error> {
+        config.range_table_config_0_256.load(&mut layouter)?;
+        let poseidon_rows = MemoryTreeChip::poseidon_rows(&self.accesses);
+        config
+            .poseidon_table
+            .load(&mut layouter, poseidon_rows.iter().map(|row| row.as_slice()))?;
+        let root_cells = layouter.assign_region(
+            || "memory tree region",
+            |mut region| {
+                config
+                    .chip
+                    .assign(&mut region, &self.accesses, 0)
+                    .map_err(|_| halo2_proofs::plonk::Error::Synthesis)
+            },
+        )?;
+        if let Some((old_root_cell, new_root_cell)) = root_cells {
+            layouter.constrain_instance(old_root_cell.cell(), config.instance, 0)?;
+            layouter.constrain_instance(new_root_cell.cell(), config.instance, 1)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod memory_tree_tests {
+    use bus_mapping::wasm::{WasmMemory, WasmMemoryTree, WasmMemoryTreeAccess};
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    use crate::wasm_circuit::memory_tree::circuit::MemoryTreeCircuit;
+
+    /// A store spanning two cells followed by loads of both, on a memory with data
+    fn accesses() -> Vec<WasmMemoryTreeAccess<Fr>> {
+        let mut tree = WasmMemoryTree::from_memory(&WasmMemory(vec![0x0102, 0, 7]));
+        vec![
+            tree.read_cell(2),
+            tree.read_cell(3),
+            tree.write_cell(3, 0xaabb << 48),
+            tree.write_cell(4, 0xccdd),
+            tree.read_cell(3),
+            tree.read_cell(1 << 20),
+        ]
+    }
+
+    fn verifies(circuit: &MemoryTreeCircuit<Fr>, instance: Vec<Vec<Fr>>) -> bool {
+        MockProver::run(10, circuit, instance)
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    pub fn accesses_ok() {
+        let circuit = MemoryTreeCircuit::new(accesses());
+        let prover = MockProver::run(10, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn max_cell_value_ok() {
+        let mut tree = WasmMemoryTree::<Fr>::new();
+        let accesses = vec![tree.write_cell(5, u64::MAX), tree.read_cell(5)];
+        let circuit = MemoryTreeCircuit::new(accesses);
+        let prover = MockProver::run(10, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn no_accesses_ok() {
+        let circuit = MemoryTreeCircuit::<Fr>::default();
+        let prover = MockProver::run(10, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn read_changing_the_cell_fails() {
+        let mut accesses = accesses();
+        let mut tree = WasmMemoryTree::from_memory(&WasmMemory(vec![0x0102, 0, 7]));
+        // a write passed off as a read
+        accesses[0] = WasmMemoryTreeAccess {
+            is_write: false,
+            ..tree.write_cell(2, 8)
+        };
+        let circuit = MemoryTreeCircuit::new(accesses);
+        assert!(!verifies(&circuit, circuit.instance()));
+    }
+
+    #[test]
+    pub fn wrong_value_fails() {
+        let mut accesses = accesses();
+        accesses[4].old_value += 1;
+        accesses[4].new_value += 1;
+        let circuit = MemoryTreeCircuit::new(accesses);
+        assert!(!verifies(&circuit, circuit.instance()));
+    }
+
+    #[test]
+    pub fn wrong_cell_index_fails() {
+        let mut accesses = accesses();
+        accesses[1].cell_index = 5;
+        let circuit = MemoryTreeCircuit::new(accesses);
+        assert!(!verifies(&circuit, circuit.instance()));
+    }
+
+    #[test]
+    pub fn unchained_accesses_fail() {
+        let mut accesses = accesses();
+        // the first write is dropped, the next access starts at a root never reached
+        accesses.remove(2);
+        let circuit = MemoryTreeCircuit::new(accesses);
+        assert!(!verifies(&circuit, circuit.instance()));
+    }
+
+    #[test]
+    pub fn other_roots_fail() {
+        let circuit = MemoryTreeCircuit::new(accesses());
+        let mut instance = circuit.instance();
+        instance[0][1] += Fr::from(1);
+        assert!(!verifies(&circuit, instance));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod module_builder;
+#[cfg(any(feature = "test", test))]
+pub mod tests;
@@ -0,0 +1,162 @@
+use crate::wasm_circuit::{
+    consts::{WASM_BLOCK_END, WASM_MAGIC_PREFIX, WASM_VERSION_PREFIX},
+    leb128::consts::{EIGHT_MS_BIT_MASK, SEVEN_LS_BITS_MASK},
+    sections::r#type::item::consts::Type,
+    types::{MemSegmentType, NumType, NumericInstruction, WasmSection},
+};
+
+/// Unsigned LEB128 of `value` stretched to `width` bytes with redundant continuation bytes.
+/// `None` (or a width too small for the value) gives the minimal encoding.
+pub fn leb128_unsigned(value: u64, width: Option<usize>) -> Vec<u8> {
+    let mut bytes = vec![];
+    let mut rest = value;
+    loop {
+        bytes.push(rest as u8 & SEVEN_LS_BITS_MASK);
+        rest >>= 7;
+        if rest == 0 {
+            break;
+        }
+    }
+    while bytes.len() < width.unwrap_or_default() {
+        bytes.push(0);
+    }
+    let last_idx = bytes.len() - 1;
+    for byte in &mut bytes[..last_idx] {
+        *byte |= EIGHT_MS_BIT_MASK;
+    }
+    bytes
+}
+
+/// Signed LEB128 of `value`, minimal encoding
+pub fn leb128_signed(value: i64) -> Vec<u8> {
+    let mut bytes = vec![];
+    leb128::write::signed(&mut bytes, value).unwrap();
+    bytes
+}
+
+/// Byte sequence of a single section: items prefixed with their count, section length and id.
+/// `count_width`/`len_width` choose the LEB128 widths of the count and the length.
+#[derive(Clone, Debug)]
+pub struct SectionBuilder {
+    section: WasmSection,
+    items: Vec<Vec<u8>>,
+    /// start section has no count, its single item is the func index
+    without_count: bool,
+    count_width: Option<usize>,
+    len_width: Option<usize>,
+}
+
+impl SectionBuilder {
+    pub fn new(section: WasmSection) -> Self {
+        Self {
+            section,
+            items: vec![],
+            without_count: section == WasmSection::Start,
+            count_width: None,
+            len_width: None,
+        }
+    }
+
+    pub fn count_width(mut self, width: usize) -> Self {
+        self.count_width = Some(width);
+        self
+    }
+
+    pub fn len_width(mut self, width: usize) -> Self {
+        self.len_width = Some(width);
+        self
+    }
+
+    /// Raw item bytes
+    pub fn item(mut self, bytes: Vec<u8>) -> Self {
+        self.items.push(bytes);
+        self
+    }
+
+    /// Type section item: `func` type with the given params and results
+    pub fn func_type(self, params: &[NumType], results: &[NumType]) -> Self {
+        let mut bytes = vec![Type::FuncType as u8];
+        for types in [params, results] {
+            bytes.extend(leb128_unsigned(types.len() as u64, None));
+            bytes.extend(types.iter().map(|t| *t as u8));
+        }
+        self.item(bytes)
+    }
+
+    /// Function section item, `idx_width` is the LEB128 width of the type index
+    pub fn func(self, type_idx: u64, idx_width: Option<usize>) -> Self {
+        self.item(leb128_unsigned(type_idx, idx_width))
+    }
+
+    /// Code section item: locals as (count, type) groups, then raw instructions. The body size
+    /// and the terminating `end` are added automatically.
+    pub fn code(self, locals: &[(u64, NumType)], instructions: &[u8]) -> Self {
+        let mut body = leb128_unsigned(locals.len() as u64, None);
+        for (count, t) in locals {
+            body.extend(leb128_unsigned(*count, None));
+            body.push(*t as u8);
+        }
+        body.extend_from_slice(instructions);
+        body.push(WASM_BLOCK_END);
+        let mut bytes = leb128_unsigned(body.len() as u64, None);
+        bytes.extend(body);
+        self.item(bytes)
+    }
+
+    /// Data section item: active segment of memory 0 placed at `offset` (`i32.const` init expr)
+    pub fn active_data(self, offset: i32, data: &[u8], len_width: Option<usize>) -> Self {
+        let mut bytes = vec![MemSegmentType::Active as u8, NumericInstruction::I32Const as u8];
+        bytes.extend(leb128_signed(offset as i64));
+        bytes.push(WASM_BLOCK_END);
+        bytes.extend(leb128_unsigned(data.len() as u64, len_width));
+        bytes.extend_from_slice(data);
+        self.item(bytes)
+    }
+
+    /// Section body: items count (if section has one) followed by the items
+    pub fn build_body(&self) -> Vec<u8> {
+        let mut body = vec![];
+        if !self.without_count {
+            body.extend(leb128_unsigned(self.items.len() as u64, self.count_width));
+        }
+        for item in &self.items {
+            body.extend_from_slice(item);
+        }
+        body
+    }
+
+    /// Whole section: id, body length and body
+    pub fn build(&self) -> Vec<u8> {
+        let body = self.build_body();
+        let mut bytes = vec![self.section as u8];
+        bytes.extend(leb128_unsigned(body.len() as u64, self.len_width));
+        bytes.extend(body);
+        bytes
+    }
+}
+
+/// Builds module bytecode from sections, kept in the order they were added
+#[derive(Clone, Debug, Default)]
+pub struct ModuleBuilder {
+    sections: Vec<SectionBuilder>,
+}
+
+impl ModuleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn section(mut self, section: SectionBuilder) -> Self {
+        self.sections.push(section);
+        self
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let mut bytes = WASM_MAGIC_PREFIX.as_bytes().to_vec();
+        bytes.extend(WASM_VERSION_PREFIX.chars().map(|ch| ch.to_digit(10).unwrap() as u8));
+        for section in &self.sections {
+            bytes.extend(section.build());
+        }
+        bytes
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod module_builder_tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    use crate::wasm_circuit::{
+        bytecode::bytecode::WasmBytecode,
+        circuit::WasmCircuit,
+        module_builder::module_builder::{leb128_unsigned, ModuleBuilder, SectionBuilder},
+        sections::{
+            function::body::circuit::WasmFunctionSectionBodyChip,
+            harness::circuit::SectionTestCircuit,
+        },
+        types::{NumType, NumericInstruction, WasmSection},
+        validation::validate_module,
+    };
+
+    fn module(count_width: Option<usize>) -> ModuleBuilder {
+        let mut functions = SectionBuilder::new(WasmSection::Function).func(0, Some(2));
+        let mut code = SectionBuilder::new(WasmSection::Code);
+        if let Some(width) = count_width {
+            functions = functions.count_width(width).len_width(width);
+            code = code.count_width(width);
+        }
+        let add = [
+            NumericInstruction::I32Const as u8,
+            1,
+            NumericInstruction::I32Const as u8,
+            2,
+            NumericInstruction::I32Add as u8,
+        ];
+        ModuleBuilder::new()
+            .section(
+                SectionBuilder::new(WasmSection::Type)
+                    .func_type(&[NumType::I32, NumType::I64], &[NumType::I32]),
+            )
+            .section(functions)
+            .section(SectionBuilder::new(WasmSection::Memory).item(vec![0, 1]))
+            .section(code.code(&[(1, NumType::I32)], &add))
+            .section(SectionBuilder::new(WasmSection::Data).active_data(0, b"Hello", Some(3)))
+    }
+
+    #[test]
+    pub fn leb128_widths_ok() {
+        assert_eq!(leb128_unsigned(5, None), vec![0x05]);
+        assert_eq!(leb128_unsigned(5, Some(3)), vec![0x85, 0x80, 0x00]);
+        assert_eq!(leb128_unsigned(300, None), vec![0xac, 0x02]);
+        assert_eq!(leb128_unsigned(300, Some(1)), vec![0xac, 0x02]);
+    }
+
+    #[test]
+    pub fn module_is_valid() {
+        for count_width in [None, Some(1), Some(3), Some(5)] {
+            let bytes = module(count_width).build();
+            wasmparser::validate(&bytes).unwrap();
+            assert!(validate_module(&bytes).is_ok());
+        }
+    }
+
+    #[test]
+    pub fn multi_byte_leb_count_section_body_ok() {
+        let body = SectionBuilder::new(WasmSection::Function)
+            .count_width(4)
+            .func(0, Some(3))
+            .func(1, None)
+            .build_body();
+        let circuit = SectionTestCircuit::<Fr, WasmFunctionSectionBodyChip<Fr>>::new(body);
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn multi_byte_leb_module_ok() {
+        let circuit = WasmCircuit::<Fr>::new(vec![WasmBytecode::new(module(Some(3)).build())]);
+        let outputs = circuit.compute_module_outputs(9).unwrap();
+        assert_eq!(outputs[0].error_code, 0);
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+        let prover = MockProver::run(9, &circuit, instance).unwrap();
+        prover.assert_satisfied();
+    }
+}
@@ -0,0 +1,7 @@
+pub mod aggregation;
+pub mod evm;
+pub mod params_store;
+pub mod prover;
+pub mod recursion;
+#[cfg(any(feature = "test", test))]
+pub mod tests;
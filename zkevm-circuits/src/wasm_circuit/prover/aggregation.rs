@@ -0,0 +1,175 @@
+use std::{collections::HashMap, marker::PhantomData};
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{Error, ProvingKey},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rand::RngCore;
+use snark_verifier::loader::evm::encode_calldata;
+use snark_verifier_sdk::{
+    evm::{gen_evm_proof_shplonk, gen_evm_verifier_shplonk},
+    gen_pk,
+    halo2::{aggregation::AggregationCircuit, gen_snark_shplonk},
+    CircuitExt, Snark,
+};
+
+use crate::wasm_circuit::{
+    bytecode::bytecode::WasmBytecode,
+    circuit::WasmCircuit,
+    prover::{
+        evm::deploy_and_call,
+        prover::{wasm_keys_id, WasmKeysId},
+    },
+    types::WasmModuleOutput,
+};
+
+/// Proof of a block for rollup settlement: one outer snark verifying the wasm circuit proof of the
+/// modules executed by the block and the execution circuit proof of the block.
+///
+/// The outer instance is the accumulator followed by the re-exposed inner instances, the wasm
+/// circuit ones (`(bytecode_number, code_hash, error_code)` per module) first.
+#[derive(Clone, Debug)]
+pub struct BlockProof {
+    pub keys_id: WasmKeysId,
+    pub outputs: Vec<WasmModuleOutput>,
+    pub execution_instances: Vec<Vec<Fr>>,
+    pub instances: Vec<Vec<Fr>>,
+    /// proof of the outer snark, encoded for the evm verifier
+    pub bytes: Vec<u8>,
+}
+
+/// Aggregates the wasm circuit and the execution circuit `C` of the same block. Inner proofs use
+/// `params`, the outer one `agg_params`. Keys of the wasm circuit depend on the bytecodes, so
+/// they and the outer keys are cached by [`WasmKeysId`] like in
+/// [`WasmProver`](super::prover::WasmProver).
+pub struct BlockAggregator<C> {
+    params: ParamsKZG<Bn256>,
+    agg_params: ParamsKZG<Bn256>,
+    execution_pk: Option<ProvingKey<G1Affine>>,
+    wasm_pks: HashMap<WasmKeysId, ProvingKey<G1Affine>>,
+    /// outer keys and number of instances
+    agg_pks: HashMap<WasmKeysId, (ProvingKey<G1Affine>, Vec<usize>)>,
+    _marker: PhantomData<C>,
+}
+
+impl<C: CircuitExt<Fr>> BlockAggregator<C> {
+    /// Unsafe setup meant for tests and local tooling, real deployments use
+    /// [`Self::from_params`]
+    pub fn setup(k: u32, agg_k: u32, mut rng: impl RngCore) -> Self {
+        let params = ParamsKZG::<Bn256>::setup(k, &mut rng);
+        let agg_params = ParamsKZG::<Bn256>::setup(agg_k, &mut rng);
+        Self::from_params(params, agg_params)
+    }
+
+    pub fn from_params(params: ParamsKZG<Bn256>, agg_params: ParamsKZG<Bn256>) -> Self {
+        Self {
+            params,
+            agg_params,
+            execution_pk: None,
+            wasm_pks: HashMap::new(),
+            agg_pks: HashMap::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn inner_snarks(
+        &mut self,
+        wbs: &[WasmBytecode],
+        execution: C,
+        rng: &mut (impl RngCore + Send),
+    ) -> Result<(Vec<WasmModuleOutput>, [Snark; 2]), Error> {
+        let wasm = WasmCircuit::<Fr>::new(wbs.to_vec()).with_k(self.params.k());
+        let outputs = wasm.compute_module_outputs(wasm.k)?;
+        let params = &self.params;
+        let wasm_pk = self
+            .wasm_pks
+            .entry(wasm_keys_id(wbs))
+            .or_insert_with(|| gen_pk(params, &wasm, None));
+        let wasm_snark = gen_snark_shplonk(params, wasm_pk, wasm, rng, None::<String>);
+
+        let execution_pk = self
+            .execution_pk
+            .get_or_insert_with(|| gen_pk(params, &execution, None));
+        let execution_snark =
+            gen_snark_shplonk(params, execution_pk, execution, rng, None::<String>);
+        Ok((outputs, [wasm_snark, execution_snark]))
+    }
+
+    pub fn prove(
+        &mut self,
+        wbs: &[WasmBytecode],
+        execution: C,
+        mut rng: impl RngCore + Send,
+    ) -> Result<BlockProof, Error> {
+        let (outputs, snarks) = self.inner_snarks(wbs, execution, &mut rng)?;
+        let execution_instances = snarks[1].instances.clone();
+
+        let circuit = AggregationCircuit::public(&self.agg_params, snarks, false, &mut rng);
+        let instances = circuit.instances();
+        let agg_params = &self.agg_params;
+        let keys_id = wasm_keys_id(wbs);
+        let (pk, _) = self.agg_pks.entry(keys_id.clone()).or_insert_with(|| {
+            (gen_pk(agg_params, &circuit, None), circuit.num_instance())
+        });
+        let bytes = gen_evm_proof_shplonk(agg_params, pk, circuit, instances.clone(), &mut rng);
+
+        Ok(BlockProof {
+            keys_id,
+            outputs,
+            execution_instances,
+            instances,
+            bytes,
+        })
+    }
+
+    /// Verifier of the blocks executing `wbs`, `None` until one of them was proven since the
+    /// outer keys are generated with the first proof
+    pub fn verifier(&self, wbs: &[WasmBytecode]) -> Option<BlockVerifier> {
+        let keys_id = wasm_keys_id(wbs);
+        let (pk, num_instance) = self.agg_pks.get(&keys_id)?;
+        let deployment_code = gen_evm_verifier_shplonk::<AggregationCircuit>(
+            &self.agg_params,
+            pk.get_vk(),
+            num_instance.clone(),
+            None,
+        );
+        Some(BlockVerifier {
+            keys_id,
+            deployment_code,
+        })
+    }
+}
+
+/// Settlement side of a [`BlockProof`]: the bytecode of the evm contract verifying the outer
+/// snark, accumulator included.
+pub struct BlockVerifier {
+    keys_id: WasmKeysId,
+    deployment_code: Vec<u8>,
+}
+
+impl BlockVerifier {
+    pub fn deployment_code(&self) -> &[u8] {
+        &self.deployment_code
+    }
+
+    /// Runs the verifier contract in an evm, a revert rejects the proof
+    pub fn verify(&self, proof: &BlockProof) -> Result<(), Error> {
+        if proof.keys_id != self.keys_id || proof.outputs.len() != self.keys_id.len() {
+            return Err(Error::ConstraintSystemFailure);
+        }
+        // the outputs and execution instances must be the ones re-exposed by the outer snark
+        let inner_instances = WasmCircuit::<Fr>::instance_from_outputs(&proof.outputs)
+            .into_iter()
+            .chain(proof.execution_instances.iter().cloned())
+            .flatten()
+            .collect::<Vec<_>>();
+        if !proof.instances[0].ends_with(&inner_instances) {
+            return Err(Error::ConstraintSystemFailure);
+        }
+        deploy_and_call(
+            self.deployment_code.clone(),
+            encode_calldata(&proof.instances, &proof.bytes),
+        )
+    }
+}
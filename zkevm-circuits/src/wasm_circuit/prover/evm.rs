@@ -0,0 +1,144 @@
+use std::path::Path;
+
+use halo2_proofs::{halo2curves::bn256::Fr, plonk::Error};
+use rand::RngCore;
+use snark_verifier::loader::evm::{
+    encode_calldata as encode_instances_calldata, Address, ExecutorBuilder,
+};
+use snark_verifier_sdk::{
+    evm::{gen_evm_proof_shplonk, gen_evm_verifier_shplonk},
+    CircuitExt,
+};
+
+use eth_types::Word;
+
+use crate::wasm_circuit::{
+    bytecode::bytecode::WasmBytecode,
+    circuit::WasmCircuit,
+    consts::WASM_MODULE_OUTPUT_LEN,
+    prover::prover::{wasm_keys_id, Kzg, WasmKeysId, WasmProof, WasmProver},
+    types::WasmModuleOutput,
+};
+
+/// Calldata of the verifier contract: the public outputs as 32 bytes big endian words
+/// (`bytecode_number, code_hash, error_code` per module) followed by the proof
+pub fn encode_calldata(outputs: &[WasmModuleOutput], proof: &[u8]) -> Vec<u8> {
+    encode_instances_calldata(&WasmCircuit::<Fr>::instance_from_outputs(outputs), proof)
+}
+
+/// Splits calldata of `num_modules` modules into the public outputs and the proof. Offsets of the
+/// outputs are not part of the calldata and are left at their default.
+pub fn decode_calldata(
+    calldata: &[u8],
+    num_modules: usize,
+) -> Option<(Vec<WasmModuleOutput>, Vec<u8>)> {
+    let instances_len = 32 * WASM_MODULE_OUTPUT_LEN * num_modules;
+    if calldata.len() < instances_len {
+        return None;
+    }
+    let (instances, proof) = calldata.split_at(instances_len);
+    let outputs = instances
+        .chunks(32 * WASM_MODULE_OUTPUT_LEN)
+        .map(|output| {
+            let words = output
+                .chunks(32)
+                .map(Word::from_big_endian)
+                .collect::<Vec<_>>();
+            Some(WasmModuleOutput {
+                bytecode_number: u64::try_from(words[0]).ok()?,
+                code_hash: words[1],
+                error_code: u64::try_from(words[2]).ok()?,
+                ..Default::default()
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some((outputs, proof.to_vec()))
+}
+
+/// Deploys a verifier contract in a fresh evm and calls it, a failed deployment or a reverted
+/// call rejects the proof
+pub(crate) fn deploy_and_call(deployment_code: Vec<u8>, calldata: Vec<u8>) -> Result<(), Error> {
+    let mut evm = ExecutorBuilder::default()
+        .with_gas_limit(u64::MAX.into())
+        .build();
+    let caller = Address::from_low_u64_be(0xfe);
+    let verifier = evm
+        .deploy(caller, deployment_code.into(), 0.into())
+        .address
+        .ok_or(Error::ConstraintSystemFailure)?;
+    if evm
+        .call_raw(caller, verifier, calldata.into(), 0.into())
+        .reverted
+    {
+        return Err(Error::ConstraintSystemFailure);
+    }
+    Ok(())
+}
+
+impl WasmProver<Kzg> {
+    /// Same as [`Self::prove`] with the proof encoded for the evm verifier (keccak transcript),
+    /// it only verifies with [`WasmEvmVerifier`]
+    pub fn prove_evm(
+        &mut self,
+        wbs: &[WasmBytecode],
+        mut rng: impl RngCore + Send,
+    ) -> Result<WasmProof, Error> {
+        let circuit = WasmCircuit::<Fr>::new(wbs.to_vec()).with_k(self.k());
+        let outputs = circuit.compute_module_outputs(circuit.k)?;
+        let instances = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+        let (params, pk) = self.params_and_proving_key(wbs)?;
+        let bytes = gen_evm_proof_shplonk(params, pk, circuit, instances, &mut rng);
+
+        Ok(WasmProof {
+            keys_id: wasm_keys_id(wbs),
+            outputs,
+            bytes,
+        })
+    }
+
+    /// Verifier contract of `wbs`, its yul source is written to `yul_path` if given
+    pub fn evm_verifier(
+        &mut self,
+        wbs: &[WasmBytecode],
+        yul_path: Option<&Path>,
+    ) -> Result<WasmEvmVerifier, Error> {
+        let num_instance = WasmCircuit::<Fr>::new(wbs.to_vec()).num_instance();
+        let (params, pk) = self.params_and_proving_key(wbs)?;
+        let deployment_code = gen_evm_verifier_shplonk::<WasmCircuit<Fr>>(
+            params,
+            pk.get_vk(),
+            num_instance,
+            yul_path,
+        );
+        Ok(WasmEvmVerifier {
+            keys_id: wasm_keys_id(wbs),
+            deployment_code,
+        })
+    }
+}
+
+/// Settlement side of a [`WasmProver::prove_evm`] proof: the bytecode of the evm contract
+/// verifying the wasm circuit of a fixed set of bytecodes.
+pub struct WasmEvmVerifier {
+    keys_id: WasmKeysId,
+    deployment_code: Vec<u8>,
+}
+
+impl WasmEvmVerifier {
+    pub fn deployment_code(&self) -> &[u8] {
+        &self.deployment_code
+    }
+
+    /// Calldata of the verifier contract for `proof`
+    pub fn calldata(&self, proof: &WasmProof) -> Vec<u8> {
+        encode_calldata(&proof.outputs, &proof.bytes)
+    }
+
+    /// Runs the verifier contract in an evm, a revert rejects the proof
+    pub fn verify(&self, proof: &WasmProof) -> Result<(), Error> {
+        if proof.keys_id != self.keys_id || proof.outputs.len() != self.keys_id.len() {
+            return Err(Error::ConstraintSystemFailure);
+        }
+        deploy_and_call(self.deployment_code.clone(), self.calldata(proof))
+    }
+}
@@ -0,0 +1,302 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs, io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{keygen_pk, keygen_vk, Error, ProvingKey, VerifyingKey},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    SerdeFormat,
+};
+use rand::RngCore;
+use sha3::{Digest, Keccak256};
+
+use eth_types::ToBigEndian;
+
+use crate::wasm_circuit::{
+    bytecode::bytecode::WasmBytecode,
+    circuit::WasmCircuit,
+    prover::prover::{wasm_keys_id, Kzg, WasmKeysId, WasmProver},
+};
+
+/// Degrees the store serves parameters and keys for
+pub const SUPPORTED_K: RangeInclusive<u32> = 9..=26;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug)]
+pub enum ParamsStoreError {
+    UnsupportedK(u32),
+    /// no parameters file for k and no larger one to downsize
+    MissingParams(u32),
+    /// file content does not hash to the pinned or recorded hash
+    HashMismatch {
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+    Io(io::Error),
+    Plonk(Error),
+}
+
+impl fmt::Display for ParamsStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedK(k) => write!(f, "k={} is not supported", k),
+            Self::MissingParams(k) => write!(f, "no parameters for k={}", k),
+            Self::HashMismatch {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} hashes to {}, expected {}",
+                path.display(),
+                actual,
+                expected
+            ),
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Plonk(e) => write!(f, "{:?}", e),
+        }
+    }
+}
+
+impl From<io::Error> for ParamsStoreError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<Error> for ParamsStoreError {
+    fn from(e: Error) -> Self {
+        Self::Plonk(e)
+    }
+}
+
+fn file_hash(bytes: &[u8]) -> String {
+    hex::encode(Keccak256::digest(bytes))
+}
+
+/// Short name of a set of bytecodes, used in key file names
+fn keys_name(keys_id: &WasmKeysId) -> String {
+    let mut hasher = Keccak256::new();
+    for code_hash in keys_id {
+        hasher.update(code_hash.to_be_bytes());
+    }
+    hex::encode(&hasher.finalize()[..8])
+}
+
+/// Directory of KZG parameters (`kzg_bn254_{k}.srs`) and wasm circuit keys
+/// (`wasm_{k}_{bytecodes}.pk/.vk`) with an in-memory cache in front of it.
+///
+/// Hashes of the files written by the store are recorded in `manifest.json` and checked on load,
+/// parameters downloaded from a ceremony are checked against hashes pinned with
+/// [`Self::pin_params_hash`]. Missing parameters are downsized from a larger file, missing keys
+/// are generated and written.
+pub struct ParamsStore {
+    dir: PathBuf,
+    pinned_hashes: HashMap<u32, String>,
+    manifest: BTreeMap<String, String>,
+    params: HashMap<u32, ParamsKZG<Bn256>>,
+    pks: HashMap<(u32, WasmKeysId), ProvingKey<G1Affine>>,
+}
+
+impl ParamsStore {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, ParamsStoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let manifest = match fs::read(dir.join(MANIFEST_FILE)) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            dir,
+            pinned_hashes: HashMap::new(),
+            manifest,
+            params: HashMap::new(),
+            pks: HashMap::new(),
+        })
+    }
+
+    /// Expected keccak256 (hex) of the parameters file of `k`, takes precedence over the manifest
+    pub fn pin_params_hash(mut self, k: u32, hash: impl Into<String>) -> Self {
+        self.pinned_hashes.insert(k, hash.into());
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn params_file(k: u32) -> String {
+        format!("kzg_bn254_{}.srs", k)
+    }
+
+    fn key_file(k: u32, keys_id: &WasmKeysId, ext: &str) -> String {
+        format!("wasm_{}_{}.{}", k, keys_name(keys_id), ext)
+    }
+
+    fn check_k(k: u32) -> Result<(), ParamsStoreError> {
+        if !SUPPORTED_K.contains(&k) {
+            return Err(ParamsStoreError::UnsupportedK(k));
+        }
+        Ok(())
+    }
+
+    /// Content of `file`, `None` if missing. Checked against `expected` or the manifest.
+    fn read_checked(
+        &self,
+        file: &str,
+        expected: Option<&String>,
+    ) -> Result<Option<Vec<u8>>, ParamsStoreError> {
+        let path = self.dir.join(file);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(expected) = expected.or_else(|| self.manifest.get(file)) {
+            let actual = file_hash(&bytes);
+            if &actual != expected {
+                return Err(ParamsStoreError::HashMismatch {
+                    path,
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+        Ok(Some(bytes))
+    }
+
+    /// Writes `file` and records its hash in the manifest
+    fn write_recorded(&mut self, file: String, bytes: &[u8]) -> Result<(), ParamsStoreError> {
+        fs::write(self.dir.join(&file), bytes)?;
+        self.manifest.insert(file, file_hash(bytes));
+        let manifest = serde_json::to_vec_pretty(&self.manifest)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(self.dir.join(MANIFEST_FILE), manifest)?;
+        Ok(())
+    }
+
+    fn read_params_file(&self, k: u32) -> Result<Option<ParamsKZG<Bn256>>, ParamsStoreError> {
+        let bytes = self.read_checked(&Self::params_file(k), self.pinned_hashes.get(&k))?;
+        bytes
+            .map(|bytes| ParamsKZG::<Bn256>::read(&mut bytes.as_slice()))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    pub fn params(&mut self, k: u32) -> Result<&ParamsKZG<Bn256>, ParamsStoreError> {
+        Self::check_k(k)?;
+        if !self.params.contains_key(&k) {
+            let params = match self.read_params_file(k)? {
+                Some(params) => params,
+                None => {
+                    let mut larger = None;
+                    for larger_k in k + 1..=*SUPPORTED_K.end() {
+                        if let Some(params) = self.read_params_file(larger_k)? {
+                            larger = Some(params);
+                            break;
+                        }
+                    }
+                    let mut params = larger.ok_or(ParamsStoreError::MissingParams(k))?;
+                    params.downsize(k);
+                    params
+                }
+            };
+            self.params.insert(k, params);
+        }
+        Ok(&self.params[&k])
+    }
+
+    /// Writes `params` as the parameters of their k
+    pub fn insert_params(&mut self, params: ParamsKZG<Bn256>) -> Result<(), ParamsStoreError> {
+        let k = params.k();
+        Self::check_k(k)?;
+        let mut bytes = vec![];
+        params.write(&mut bytes)?;
+        self.write_recorded(Self::params_file(k), &bytes)?;
+        self.params.insert(k, params);
+        Ok(())
+    }
+
+    /// Unsafe setup meant for tests and local tooling, real deployments load the parameters of
+    /// a ceremony
+    pub fn setup(&mut self, k: u32, rng: impl RngCore) -> Result<(), ParamsStoreError> {
+        self.insert_params(ParamsKZG::<Bn256>::setup(k, rng))
+    }
+
+    /// Proving key of the wasm circuit of `wbs`, generated and written on first use
+    pub fn proving_key(
+        &mut self,
+        k: u32,
+        wbs: &[WasmBytecode],
+    ) -> Result<&ProvingKey<G1Affine>, ParamsStoreError> {
+        let keys_id = wasm_keys_id(wbs);
+        let cache_key = (k, keys_id.clone());
+        if !self.pks.contains_key(&cache_key) {
+            let pk_file = Self::key_file(k, &keys_id, "pk");
+            let pk = match self.read_checked(&pk_file, None)? {
+                Some(bytes) => ProvingKey::<G1Affine>::read::<_, WasmCircuit<Fr>>(
+                    &mut bytes.as_slice(),
+                    SerdeFormat::RawBytes,
+                )?,
+                None => {
+                    let circuit = WasmCircuit::<Fr>::new(wbs.to_vec()).with_k(k);
+                    let params = self.params(k)?;
+                    let vk = keygen_vk(params, &circuit)?;
+                    let pk = keygen_pk(params, vk, &circuit)?;
+
+                    let mut bytes = vec![];
+                    pk.write(&mut bytes, SerdeFormat::RawBytes)?;
+                    self.write_recorded(pk_file, &bytes)?;
+                    let mut bytes = vec![];
+                    pk.get_vk().write(&mut bytes, SerdeFormat::RawBytes)?;
+                    self.write_recorded(Self::key_file(k, &keys_id, "vk"), &bytes)?;
+                    pk
+                }
+            };
+            self.pks.insert(cache_key.clone(), pk);
+        }
+        Ok(&self.pks[&cache_key])
+    }
+
+    /// Verifying key of the wasm circuit of `wbs`, read without the proving key when it was
+    /// written before
+    pub fn verifying_key(
+        &mut self,
+        k: u32,
+        wbs: &[WasmBytecode],
+    ) -> Result<VerifyingKey<G1Affine>, ParamsStoreError> {
+        let keys_id = wasm_keys_id(wbs);
+        if let Some(pk) = self.pks.get(&(k, keys_id.clone())) {
+            return Ok(pk.get_vk().clone());
+        }
+        match self.read_checked(&Self::key_file(k, &keys_id, "vk"), None)? {
+            Some(bytes) => Ok(VerifyingKey::<G1Affine>::read::<_, WasmCircuit<Fr>>(
+                &mut bytes.as_slice(),
+                SerdeFormat::RawBytes,
+            )?),
+            None => Ok(self.proving_key(k, wbs)?.get_vk().clone()),
+        }
+    }
+
+    /// Prover at `k` holding the proving keys of `wbs_sets`
+    pub fn prover(
+        &mut self,
+        k: u32,
+        wbs_sets: &[&[WasmBytecode]],
+    ) -> Result<WasmProver<Kzg>, ParamsStoreError> {
+        let mut prover = WasmProver::from_params(self.params(k)?.clone());
+        for wbs in wbs_sets {
+            let pk = self.proving_key(k, wbs)?.clone();
+            prover.insert_proving_key(wasm_keys_id(wbs), pk);
+        }
+        Ok(prover)
+    }
+}
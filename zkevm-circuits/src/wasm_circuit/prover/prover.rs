@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Error, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::{CommitmentScheme, Params, ParamsProver},
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA, ParamsVerifierIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::SingleStrategy as SingleStrategyIPA,
+        },
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG, ParamsVerifierKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use eth_types::Word;
+
+use crate::wasm_circuit::{
+    bytecode::bytecode::WasmBytecode, circuit::WasmCircuit, types::WasmModuleOutput,
+};
+
+/// Fixed selectors of the wasm circuit are assigned from the bytecode layout, so proving and
+/// verifying keys are bound to the exact set of bytecodes and are cached by their code hashes.
+pub type WasmKeysId = Vec<Word>;
+
+pub fn wasm_keys_id(wbs: &[WasmBytecode]) -> WasmKeysId {
+    wbs.iter().map(|wb| wb.code_hash).collect()
+}
+
+pub type ProverParams<B> = <<B as WasmBackend>::Scheme as CommitmentScheme>::ParamsProver;
+pub type VerifierParams<B> = <<B as WasmBackend>::Scheme as CommitmentScheme>::ParamsVerifier;
+
+/// Commitment scheme the wasm circuit is proven with. Backends commit over bn256 so all of them
+/// prove the same [`WasmCircuit<Fr>`].
+pub trait WasmBackend {
+    type Scheme: CommitmentScheme<Scalar = Fr, Curve = G1Affine>;
+
+    /// Unsafe for schemes with a trusted setup, meant for tests and local tooling
+    fn setup(k: u32, rng: impl RngCore) -> ProverParams<Self>;
+
+    fn verifier_params(params: &ProverParams<Self>) -> VerifierParams<Self>;
+
+    fn create_proof(
+        params: &ProverParams<Self>,
+        pk: &ProvingKey<G1Affine>,
+        circuit: WasmCircuit<Fr>,
+        instance: &[&[Fr]],
+    ) -> Result<Vec<u8>, Error>;
+
+    fn verify_proof(
+        params: &VerifierParams<Self>,
+        vk: &VerifyingKey<G1Affine>,
+        instance: &[&[Fr]],
+        proof: &[u8],
+    ) -> Result<(), Error>;
+}
+
+/// KZG with SHPLONK multiopen, the backend of on-chain verification
+#[derive(Clone, Copy, Debug)]
+pub struct Kzg;
+
+impl WasmBackend for Kzg {
+    type Scheme = KZGCommitmentScheme<Bn256>;
+
+    fn setup(k: u32, rng: impl RngCore) -> ParamsKZG<Bn256> {
+        ParamsKZG::<Bn256>::setup(k, rng)
+    }
+
+    fn verifier_params(params: &ParamsKZG<Bn256>) -> ParamsVerifierKZG<Bn256> {
+        params.verifier_params().clone()
+    }
+
+    fn create_proof(
+        params: &ParamsKZG<Bn256>,
+        pk: &ProvingKey<G1Affine>,
+        circuit: WasmCircuit<Fr>,
+        instance: &[&[Fr]],
+    ) -> Result<Vec<u8>, Error> {
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof::<
+            KZGCommitmentScheme<Bn256>,
+            ProverSHPLONK<'_, Bn256>,
+            Challenge255<G1Affine>,
+            OsRng,
+            Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+            WasmCircuit<Fr>,
+        >(params, pk, &[circuit], &[instance], OsRng, &mut transcript)?;
+        Ok(transcript.finalize())
+    }
+
+    fn verify_proof(
+        params: &ParamsVerifierKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+        instance: &[&[Fr]],
+        proof: &[u8],
+    ) -> Result<(), Error> {
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+        let strategy = SingleStrategy::new(params);
+        verify_proof::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<'_, Bn256>,
+            Challenge255<G1Affine>,
+            Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+            SingleStrategy<'_, Bn256>,
+        >(params, vk, strategy, &[instance], &mut transcript)
+    }
+}
+
+/// Inner product argument, no trusted setup, meant for recursion experiments
+#[derive(Clone, Copy, Debug)]
+pub struct Ipa;
+
+impl WasmBackend for Ipa {
+    type Scheme = IPACommitmentScheme<G1Affine>;
+
+    fn setup(k: u32, _rng: impl RngCore) -> ParamsIPA<G1Affine> {
+        ParamsIPA::<G1Affine>::new(k)
+    }
+
+    fn verifier_params(params: &ParamsIPA<G1Affine>) -> ParamsVerifierIPA<G1Affine> {
+        params.verifier_params().clone()
+    }
+
+    fn create_proof(
+        params: &ParamsIPA<G1Affine>,
+        pk: &ProvingKey<G1Affine>,
+        circuit: WasmCircuit<Fr>,
+        instance: &[&[Fr]],
+    ) -> Result<Vec<u8>, Error> {
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof::<
+            IPACommitmentScheme<G1Affine>,
+            ProverIPA<'_, G1Affine>,
+            Challenge255<G1Affine>,
+            OsRng,
+            Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+            WasmCircuit<Fr>,
+        >(params, pk, &[circuit], &[instance], OsRng, &mut transcript)?;
+        Ok(transcript.finalize())
+    }
+
+    fn verify_proof(
+        params: &ParamsVerifierIPA<G1Affine>,
+        vk: &VerifyingKey<G1Affine>,
+        instance: &[&[Fr]],
+        proof: &[u8],
+    ) -> Result<(), Error> {
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+        let strategy = SingleStrategyIPA::new(params);
+        verify_proof::<
+            IPACommitmentScheme<G1Affine>,
+            VerifierIPA<'_, G1Affine>,
+            Challenge255<G1Affine>,
+            Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+            SingleStrategyIPA<'_, G1Affine>,
+        >(params, vk, strategy, &[instance], &mut transcript)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WasmProof {
+    pub keys_id: WasmKeysId,
+    /// (bytecode_number, code_hash, error_code) public outputs, one per bytecode in assignment order
+    pub outputs: Vec<WasmModuleOutput>,
+    pub bytes: Vec<u8>,
+}
+
+pub struct WasmProver<B: WasmBackend = Kzg> {
+    params: ProverParams<B>,
+    pks: HashMap<WasmKeysId, ProvingKey<G1Affine>>,
+}
+
+impl<B: WasmBackend> WasmProver<B> {
+    /// Unsafe setup meant for tests and local tooling, real deployments use [`Self::from_params`]
+    pub fn setup(k: u32, rng: impl RngCore) -> Self {
+        Self::from_params(B::setup(k, rng))
+    }
+
+    pub fn from_params(params: ProverParams<B>) -> Self {
+        Self {
+            params,
+            pks: HashMap::new(),
+        }
+    }
+
+    pub fn params(&self) -> &ProverParams<B> {
+        &self.params
+    }
+
+    pub fn k(&self) -> u32 {
+        self.params.k()
+    }
+
+    /// Generates keys on first use, later calls for the same bytecodes hit the cache
+    pub fn proving_key(&mut self, wbs: &[WasmBytecode]) -> Result<&ProvingKey<G1Affine>, Error> {
+        let keys_id = wasm_keys_id(wbs);
+        if !self.pks.contains_key(&keys_id) {
+            let circuit = WasmCircuit::<Fr>::new(wbs.to_vec()).with_k(self.k());
+            let vk = keygen_vk(&self.params, &circuit)?;
+            let pk = keygen_pk(&self.params, vk, &circuit)?;
+            self.pks.insert(keys_id.clone(), pk);
+        }
+        Ok(&self.pks[&keys_id])
+    }
+
+    /// Parameters along with the proving key of `wbs`, for provers using another transcript
+    pub(super) fn params_and_proving_key(
+        &mut self,
+        wbs: &[WasmBytecode],
+    ) -> Result<(&ProverParams<B>, &ProvingKey<G1Affine>), Error> {
+        self.proving_key(wbs)?;
+        Ok((&self.params, &self.pks[&wasm_keys_id(wbs)]))
+    }
+
+    /// Adds a proving key generated elsewhere, e.g. by a
+    /// [`ParamsStore`](super::params_store::ParamsStore)
+    pub fn insert_proving_key(&mut self, keys_id: WasmKeysId, pk: ProvingKey<G1Affine>) {
+        self.pks.insert(keys_id, pk);
+    }
+
+    pub fn prove(&mut self, wbs: &[WasmBytecode]) -> Result<WasmProof, Error> {
+        self.proving_key(wbs)?;
+        let keys_id = wasm_keys_id(wbs);
+        let pk = &self.pks[&keys_id];
+        let circuit = WasmCircuit::<Fr>::new(wbs.to_vec()).with_k(self.k());
+        let outputs = circuit.compute_module_outputs(circuit.k)?;
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+        let instance = instance.iter().map(|v| v.as_slice()).collect::<Vec<_>>();
+        let bytes = B::create_proof(&self.params, pk, circuit, &instance)?;
+
+        Ok(WasmProof {
+            keys_id,
+            outputs,
+            bytes,
+        })
+    }
+
+    /// Same as [`Self::prove`] with the rayon parallelism of halo2 running on `pool` instead of
+    /// the global pool. Only the threads are picked, MSMs and FFTs are still halo2's own.
+    #[cfg(feature = "prover-thread-pool")]
+    pub fn prove_on_pool(
+        &mut self,
+        wbs: &[WasmBytecode],
+        pool: &rayon::ThreadPool,
+    ) -> Result<WasmProof, Error>
+    where
+        Self: Send,
+    {
+        pool.install(|| self.prove(wbs))
+    }
+
+    pub fn verifier(&mut self, wbs: &[WasmBytecode]) -> Result<WasmVerifier<B>, Error> {
+        let vk = self.proving_key(wbs)?.get_vk().clone();
+        Ok(WasmVerifier {
+            params: B::verifier_params(&self.params),
+            keys_id: wasm_keys_id(wbs),
+            vk,
+        })
+    }
+}
+
+pub struct WasmVerifier<B: WasmBackend = Kzg> {
+    params: VerifierParams<B>,
+    keys_id: WasmKeysId,
+    vk: VerifyingKey<G1Affine>,
+}
+
+impl<B: WasmBackend> WasmVerifier<B> {
+    pub fn new(
+        params: VerifierParams<B>,
+        keys_id: WasmKeysId,
+        vk: VerifyingKey<G1Affine>,
+    ) -> Self {
+        Self {
+            params,
+            keys_id,
+            vk,
+        }
+    }
+
+    pub fn vk(&self) -> &VerifyingKey<G1Affine> {
+        &self.vk
+    }
+
+    /// Checks the proof against its public outputs, callers inspect `proof.outputs` afterwards
+    pub fn verify(&self, proof: &WasmProof) -> Result<(), Error> {
+        if proof.keys_id != self.keys_id || proof.outputs.len() != self.keys_id.len() {
+            return Err(Error::ConstraintSystemFailure);
+        }
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&proof.outputs);
+        let instance = instance.iter().map(|v| v.as_slice()).collect::<Vec<_>>();
+        B::verify_proof(&self.params, &self.vk, &instance, &proof.bytes)
+    }
+}
@@ -0,0 +1,787 @@
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use gadgets::util::Expr;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, ProvingKey,
+        Selector,
+    },
+    poly::{commitment::Params, kzg::commitment::ParamsKZG, Rotation},
+};
+use itertools::Itertools;
+use mpt_zktrie::hash::{PoseidonHashChip, PoseidonHashTable};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use snark_verifier::loader::evm::encode_calldata;
+use snark_verifier_sdk::{
+    evm::{gen_evm_proof_shplonk, gen_evm_verifier_shplonk},
+    gen_pk,
+    halo2::{aggregation::AggregationCircuit, gen_snark_shplonk},
+    CircuitExt, Snark,
+};
+
+use eth_types::{Field, ToScalar, Word};
+
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    poseidon_circuit::{PoseidonCircuitConfig, PoseidonCircuitConfigArgs, HASH_BLOCK_STEP_SIZE},
+    table::PoseidonTable,
+    util::SubCircuitConfig,
+    wasm_circuit::{
+        bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+        common::{
+            section_func_count, ErrorCodeColumns, SectionBodyChip, SectionBodyChipResources,
+            WasmSharedStateAwareChip,
+        },
+        error::Error as WasmError,
+        leb128::circuit::LEB128Chip,
+        prover::{
+            evm::deploy_and_call,
+            prover::{wasm_keys_id, WasmKeysId},
+        },
+        sections::{
+            code::body::circuit::WasmCodeSectionBodyChip,
+            data::body::circuit::WasmDataSectionBodyChip,
+            element::body::circuit::WasmElementSectionBodyChip,
+            export::body::circuit::WasmExportSectionBodyChip,
+            function::body::circuit::WasmFunctionSectionBodyChip,
+            global::body::circuit::WasmGlobalSectionBodyChip,
+            import::body::circuit::WasmImportSectionBodyChip,
+            memory::body::circuit::WasmMemorySectionBodyChip,
+            r#type::body::circuit::WasmTypeSectionBodyChip,
+            start::body::circuit::WasmStartSectionBodyChip,
+            table::body::circuit::WasmTableSectionBodyChip,
+        },
+        tables::{
+            byte_set::config::ByteSetTableConfig, dynamic_indexes::circuit::DynamicIndexesChip,
+            opcode::config::OpcodeTableConfig, utf8_transition::config::Utf8TransitionTableConfig,
+        },
+        types::{SharedStateRef, WasmSection},
+        utf8::circuit::UTF8Chip,
+    },
+};
+
+/// Values exposed per section proof: commitment and func_count before and after the body
+pub const SECTION_OUTPUT_LEN: usize = 4;
+
+/// Indices of the values a section proof ends with and of the ones the next one starts with
+const SECTION_BOUNDARIES: [(usize, usize); 2] = [(1, 0), (3, 2)];
+
+/// Input of the hash of a body byte, tagged with the id of its section
+fn section_commitment_input(section: WasmSection, byte: u8) -> u64 {
+    byte as u64 + 256 * section as u64
+}
+
+/// Commitment of a section body chained after `commitment_in`: every byte of the body is hashed
+/// with the commitment of the bytes before it. The commitment of a module is the one its last
+/// section ends with, chained from 0.
+pub fn section_commitment<F: Field>(commitment_in: F, section: WasmSection, body: &[u8]) -> F {
+    body.iter().fold(commitment_in, |commitment, byte| {
+        F::hash_with_domain(
+            [commitment, F::from(section_commitment_input(section, *byte))],
+            F::zero(),
+        )
+    })
+}
+
+fn field_to_word<F: Field>(value: F) -> Word {
+    Word::from_little_endian(value.to_repr().as_ref())
+}
+
+/// Public outputs of the proof of one section body. A section starts with the commitment and
+/// func_count the previous one ended with, the first section starts at 0. The code hash only
+/// keys the proving keys and is not exposed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionOutput {
+    pub section: WasmSection,
+    pub code_hash: Word,
+    pub commitment_in: Word,
+    pub commitment_out: Word,
+    pub func_count_in: usize,
+    pub func_count_out: usize,
+}
+
+impl SectionOutput {
+    pub fn instance<F: Field>(&self) -> Vec<F> {
+        vec![
+            self.commitment_in.to_scalar().unwrap(),
+            self.commitment_out.to_scalar().unwrap(),
+            F::from(self.func_count_in as u64),
+            F::from(self.func_count_out as u64),
+        ]
+    }
+}
+
+/// Outputs of the sections of `wb` in module order, custom and data count sections are not
+/// proven in this mode
+pub fn section_outputs(wb: &WasmBytecode) -> Result<Vec<SectionOutput>, WasmError> {
+    let mut func_count = 0;
+    let mut commitment = Fr::zero();
+    let mut outputs = vec![];
+    for report in wb.sections() {
+        if matches!(report.section, WasmSection::Custom | WasmSection::DataCount) {
+            continue;
+        }
+        let body = &wb.bytes[report.section_body_start_offset..=report.section_end_offset];
+        let func_count_out = func_count + section_func_count(report.section, body)?;
+        let commitment_out = section_commitment(commitment, report.section, body);
+        outputs.push(SectionOutput {
+            section: report.section,
+            code_hash: WasmBytecode::new(body.to_vec()).code_hash,
+            commitment_in: field_to_word(commitment),
+            commitment_out: field_to_word(commitment_out),
+            func_count_in: func_count,
+            func_count_out,
+        });
+        func_count = func_count_out;
+        commitment = commitment_out;
+    }
+    Ok(outputs)
+}
+
+/// Circuit proving a single section body with chip `C`, wired like `WasmChip` wires the section
+/// chips. Row 0 holds `func_count_in` and `commitment_in`, body bytes are assigned from row 1 on
+/// and the last of them holds `func_count_out` and `commitment_out`. Every body byte is hashed
+/// into the commitment through the poseidon table, which the circuit proves itself.
+pub struct SectionCircuit<F, C> {
+    pub wb: WasmBytecode,
+    pub func_count_in: usize,
+    pub func_count_out: usize,
+    pub commitment_in: Word,
+    pub commitment_out: Word,
+    _marker: PhantomData<(F, C)>,
+}
+
+impl<F, C> Clone for SectionCircuit<F, C> {
+    fn clone(&self) -> Self {
+        Self {
+            wb: self.wb.clone(),
+            func_count_in: self.func_count_in,
+            func_count_out: self.func_count_out,
+            commitment_in: self.commitment_in,
+            commitment_out: self.commitment_out,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, C: SectionBodyChip<F>> SectionCircuit<F, C> {
+    /// `body` is a non empty body of `C::SECTION` (without section id and length)
+    pub fn new(
+        body: Vec<u8>,
+        func_count_in: usize,
+        commitment_in: Word,
+    ) -> Result<Self, WasmError> {
+        let func_count_out = func_count_in + section_func_count(C::SECTION, &body)?;
+        let commitment_out =
+            section_commitment::<F>(commitment_in.to_scalar().unwrap(), C::SECTION, &body);
+        Ok(Self {
+            wb: WasmBytecode::new(body),
+            func_count_in,
+            func_count_out,
+            commitment_in,
+            commitment_out: field_to_word(commitment_out),
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn output(&self) -> SectionOutput {
+        SectionOutput {
+            section: C::SECTION,
+            code_hash: self.wb.code_hash,
+            commitment_in: self.commitment_in,
+            commitment_out: self.commitment_out,
+            func_count_in: self.func_count_in,
+            func_count_out: self.func_count_out,
+        }
+    }
+
+    /// Commitments on the rows of the circuit, `commitment_in` first
+    fn commitments(&self) -> Vec<F> {
+        let mut commitments = vec![self.commitment_in.to_scalar().unwrap()];
+        for byte in self.wb.bytes.iter() {
+            commitments.push(section_commitment(
+                *commitments.last().unwrap(),
+                C::SECTION,
+                &[*byte],
+            ));
+        }
+        commitments
+    }
+}
+
+pub struct SectionCircuitConfig<F: Field, C> {
+    pub body_chip: Arc<C>,
+    pub resources: SectionBodyChipResources<F>,
+    q_body: Column<Fixed>,
+    commitment: Column<Advice>,
+    instance: Column<Instance>,
+    utf8_transition_table_config: Arc<Utf8TransitionTableConfig<F>>,
+    poseidon_config: PoseidonCircuitConfig<F>,
+}
+
+impl<F: Field, C> Clone for SectionCircuitConfig<F, C> {
+    fn clone(&self) -> Self {
+        Self {
+            body_chip: self.body_chip.clone(),
+            resources: self.resources.clone(),
+            q_body: self.q_body,
+            commitment: self.commitment,
+            instance: self.instance,
+            utf8_transition_table_config: self.utf8_transition_table_config.clone(),
+            poseidon_config: self.poseidon_config.clone(),
+        }
+    }
+}
+
+impl<F: Field, C: SectionBodyChip<F>> Circuit<F> for SectionCircuit<F, C> {
+    type Config = SectionCircuitConfig<F, C>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    /// The body is the shape of the circuit: chips assign their selectors from its layout, so
+    /// keys are per body. The section boundaries are the witness.
+    fn without_witnesses(&self) -> Self {
+        Self {
+            wb: self.wb.clone(),
+            func_count_in: 0,
+            func_count_out: self.func_count_out - self.func_count_in,
+            commitment_in: Word::zero(),
+            commitment_out: Word::zero(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, false));
+        let func_count = cs.advice_column();
+        let error_code = ErrorCodeColumns::configure(cs);
+        let bytecode_number = cs.advice_column();
+        let body_byte_rev_index_l2 = cs.advice_column();
+        let body_item_rev_count_l1 = cs.advice_column();
+        let body_item_rev_count_l2 = cs.advice_column();
+        let q_body = cs.fixed_column();
+        let commitment = cs.advice_column();
+        let instance = cs.instance_column();
+        cs.enable_equality(func_count);
+        cs.enable_equality(commitment);
+        cs.enable_equality(instance);
+
+        let poseidon_table = PoseidonTable::dev_construct(cs);
+        let poseidon_config =
+            PoseidonCircuitConfig::new(cs, PoseidonCircuitConfigArgs { poseidon_table });
+
+        let shared_state = SharedStateRef::default();
+
+        let utf8_transition_table_config = Arc::new(Utf8TransitionTableConfig::configure(cs));
+        let byte_set_table_config = Arc::new(ByteSetTableConfig::configure(cs));
+        let opcode_table_config = Arc::new(OpcodeTableConfig::configure(cs));
+
+        let config = DynamicIndexesChip::configure(cs, shared_state.clone());
+        let dynamic_indexes_chip = Arc::new(DynamicIndexesChip::construct(config));
+
+        let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
+        let leb128_chip = Arc::new(LEB128Chip::construct(leb128_config));
+
+        let utf8_config =
+            UTF8Chip::<F>::configure(cs, utf8_transition_table_config.clone(), &wb_table.value);
+        let utf8_chip = Arc::new(UTF8Chip::construct(utf8_config));
+
+        let resources = SectionBodyChipResources {
+            wb_table,
+            leb128_chip,
+            utf8_chip,
+            byte_set_table_config,
+            opcode_table_config,
+            dynamic_indexes_chip,
+            shared_state,
+            func_count,
+            body_byte_rev_index_l2,
+            body_item_rev_count_l1,
+            body_item_rev_count_l2,
+            error_code,
+            bytecode_number,
+        };
+        let body_chip = Arc::new(C::configure_with_resources(cs, &resources));
+
+        cs.create_gate("section body func_count transition", |vc| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_body_expr = vc.query_fixed(q_body, Rotation::cur());
+            let func_count_expr = vc.query_advice(func_count, Rotation::cur());
+            let func_count_prev_expr = vc.query_advice(func_count, Rotation::prev());
+            let func_count_increment_expr = body_chip.func_count_increment_expr(vc);
+
+            cb.require_equal(
+                "q_body => func_count=prev.func_count+func_count_increment",
+                func_count_expr,
+                func_count_prev_expr + func_count_increment_expr,
+            );
+
+            cb.gate(q_body_expr)
+        });
+
+        let wb_table = resources.wb_table.clone();
+        cs.lookup_any("section body byte is hashed into the commitment", |vc| {
+            let q_body_expr = vc.query_fixed(q_body, Rotation::cur());
+            let input_expr = vc.query_advice(wb_table.value, Rotation::cur())
+                + Expression::Constant(F::from(section_commitment_input(C::SECTION, 0)));
+            let inputs: [(Expression<F>, Column<Advice>); 5] = [
+                (vc.query_advice(commitment, Rotation::cur()), poseidon_table.hash_id),
+                (vc.query_advice(commitment, Rotation::prev()), poseidon_table.input0),
+                (input_expr, poseidon_table.input1),
+                (0.expr(), poseidon_table.control),
+                (1.expr(), poseidon_table.heading_mark),
+            ];
+            inputs
+                .into_iter()
+                .map(|(input, column)| {
+                    (q_body_expr.clone() * input, vc.query_advice(column, Rotation::cur()))
+                })
+                .collect()
+        });
+
+        SectionCircuitConfig {
+            body_chip,
+            resources,
+            q_body,
+            commitment,
+            instance,
+            utf8_transition_table_config,
+            poseidon_config,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.utf8_transition_table_config.load(&mut layouter)?;
+        config.resources.byte_set_table_config.load(&mut layouter)?;
+        config.resources.opcode_table_config.load(&mut layouter)?;
+
+        let wb = &self.wb;
+        let body_len = wb.bytes.len();
+        if body_len == 0 {
+            return Err(Error::Synthesis);
+        }
+        let commitments = self.commitments();
+        let mut hash_table = PoseidonHashTable::default();
+        hash_table.constant_inputs_with_check(
+            &wb.bytes
+                .iter()
+                .zip(commitments.iter().tuple_windows())
+                .map(|(byte, (prev, cur))| {
+                    (*prev, F::from(section_commitment_input(C::SECTION, *byte)), *cur)
+                })
+                .collect_vec(),
+        );
+        PoseidonHashChip::<_, HASH_BLOCK_STEP_SIZE>::construct(
+            config.poseidon_config.0.clone(),
+            &hash_table,
+            body_len,
+            false,
+            None,
+        )
+        .load(&mut layouter)?;
+
+        let assign_delta = 1;
+        let cells = layouter.assign_region(
+            || "section body region",
+            |mut region| {
+                config.resources.wb_table.load(&mut region, wb, assign_delta)?;
+                {
+                    let shared_state = config.body_chip.shared_state();
+                    let mut shared_state = shared_state.write();
+                    shared_state.reset();
+                    shared_state.error_processing_enabled = false;
+                    shared_state.func_count = self.func_count_in;
+                }
+                let func_count_in = region.assign_advice(
+                    || "func_count_in",
+                    config.resources.func_count,
+                    0,
+                    || Value::known(F::from(self.func_count_in as u64)),
+                )?;
+
+                let mut wb_offset = 0;
+                while wb_offset < body_len {
+                    wb_offset = config
+                        .body_chip
+                        .assign_section_body(&mut region, wb, wb_offset, assign_delta)
+                        .map_err(|_| Error::Synthesis)?;
+                }
+                for offset in assign_delta..assign_delta + body_len {
+                    region.assign_fixed(
+                        || format!("assign 'q_body' val 1 at {}", offset),
+                        config.q_body,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                }
+                let mut commitment_cells = vec![];
+                for (offset, commitment) in commitments.iter().enumerate() {
+                    commitment_cells.push(region.assign_advice(
+                        || format!("assign 'commitment' val {:?} at {}", commitment, offset),
+                        config.commitment,
+                        offset,
+                        || Value::known(*commitment),
+                    )?);
+                }
+
+                // reassigned with the value the chip put there to get the cell
+                let func_count_out = region.assign_advice(
+                    || "func_count_out",
+                    config.resources.func_count,
+                    body_len,
+                    || Value::known(F::from(self.func_count_out as u64)),
+                )?;
+
+                Ok([
+                    commitment_cells[0].clone(),
+                    commitment_cells[body_len].clone(),
+                    func_count_in,
+                    func_count_out,
+                ])
+            },
+        )?;
+        for (idx, cell) in cells.iter().enumerate() {
+            layouter.constrain_instance(cell.cell(), config.instance, idx)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<F: Field, C: SectionBodyChip<F>> CircuitExt<F> for SectionCircuit<F, C> {
+    fn num_instance(&self) -> Vec<usize> {
+        vec![SECTION_OUTPUT_LEN]
+    }
+
+    fn instances(&self) -> Vec<Vec<F>> {
+        vec![self.output().instance()]
+    }
+}
+
+/// Outer circuit of [`RecursiveWasmProver`]: aggregates the section proofs, re-exposes their
+/// instances after the accumulator and constrains every section to start with the commitment and
+/// func_count the previous one ended with.
+#[derive(Clone)]
+pub struct SectionAggregationCircuit {
+    aggregation: AggregationCircuit,
+    section_instances: Vec<Vec<Fr>>,
+}
+
+impl SectionAggregationCircuit {
+    pub fn new(
+        params: &ParamsKZG<Bn256>,
+        snarks: Vec<Snark>,
+        rng: &mut (impl RngCore + Send),
+    ) -> Self {
+        let section_instances = snarks
+            .iter()
+            .map(|snark| snark.instances.concat())
+            .collect();
+        Self {
+            aggregation: AggregationCircuit::new(params, snarks, rng),
+            section_instances,
+        }
+    }
+}
+
+impl Circuit<Fr> for SectionAggregationCircuit {
+    type Config = <AggregationCircuit as Circuit<Fr>>::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            aggregation: self.aggregation.without_witnesses(),
+            section_instances: self
+                .section_instances
+                .iter()
+                .map(|instances| vec![Fr::zero(); instances.len()])
+                .collect(),
+        }
+    }
+
+    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self::Config {
+        AggregationCircuit::configure(cs)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.range().load_lookup_table(&mut layouter)?;
+        let (accumulator, sections) =
+            self.aggregation
+                .synthesize_proof(config.clone(), &mut layouter, vec![])?;
+        layouter.assign_region(
+            || "section boundaries",
+            |mut region| {
+                for (prev, next) in sections.iter().tuple_windows() {
+                    for (out_idx, in_idx) in SECTION_BOUNDARIES {
+                        region.constrain_equal(prev[out_idx], next[in_idx])?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+        for (idx, cell) in accumulator
+            .into_iter()
+            .chain(sections.into_iter().flatten())
+            .enumerate()
+        {
+            layouter.constrain_instance(cell, config.instance, idx)?;
+        }
+        Ok(())
+    }
+}
+
+impl CircuitExt<Fr> for SectionAggregationCircuit {
+    fn num_instance(&self) -> Vec<usize> {
+        vec![self.instances()[0].len()]
+    }
+
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        vec![CircuitExt::instances(&self.aggregation)[0]
+            .iter()
+            .chain(self.section_instances.iter().flatten())
+            .copied()
+            .collect()]
+    }
+
+    fn accumulator_indices() -> Option<Vec<(usize, usize)>> {
+        <AggregationCircuit as CircuitExt<Fr>>::accumulator_indices()
+    }
+
+    fn selectors(config: &Self::Config) -> Vec<Selector> {
+        <AggregationCircuit as CircuitExt<Fr>>::selectors(config)
+    }
+}
+
+/// Proof of a module proven section by section. Meant for modules too large for a single
+/// [`WasmCircuit`](crate::wasm_circuit::circuit::WasmCircuit): the module commitment covers the
+/// section bodies and ids, the header, section lengths and the index references between sections
+/// are not proven, nor are invalid modules (whose error code only the wasm circuit proves).
+#[derive(Clone, Debug)]
+pub struct RecursiveWasmProof {
+    pub keys_id: WasmKeysId,
+    pub sections: Vec<SectionOutput>,
+    /// accumulator followed by the re-exposed instances of the section proofs
+    pub instances: Vec<Vec<Fr>>,
+    /// proof of the outer snark, encoded for the evm verifier
+    pub bytes: Vec<u8>,
+}
+
+/// Proves every section body of a module in its own [`SectionCircuit`] and links the section
+/// proofs in one outer snark. Section keys are cached by section and body code hash, outer keys
+/// by module like in [`BlockAggregator`](super::aggregation::BlockAggregator).
+pub struct RecursiveWasmProver {
+    params: ParamsKZG<Bn256>,
+    agg_params: ParamsKZG<Bn256>,
+    section_pks: HashMap<(WasmSection, Word), ProvingKey<G1Affine>>,
+    /// outer keys and number of instances
+    agg_pks: HashMap<WasmKeysId, (ProvingKey<G1Affine>, Vec<usize>)>,
+}
+
+impl RecursiveWasmProver {
+    /// Unsafe setup meant for tests and local tooling, real deployments use
+    /// [`Self::from_params`]
+    pub fn setup(k: u32, agg_k: u32, mut rng: impl RngCore) -> Self {
+        let params = ParamsKZG::<Bn256>::setup(k, &mut rng);
+        let agg_params = ParamsKZG::<Bn256>::setup(agg_k, &mut rng);
+        Self::from_params(params, agg_params)
+    }
+
+    pub fn from_params(params: ParamsKZG<Bn256>, agg_params: ParamsKZG<Bn256>) -> Self {
+        Self {
+            params,
+            agg_params,
+            section_pks: HashMap::new(),
+            agg_pks: HashMap::new(),
+        }
+    }
+
+    fn section_snark<C: SectionBodyChip<Fr>>(
+        &mut self,
+        body: &[u8],
+        boundary: &SectionOutput,
+        rng: &mut (impl RngCore + Send),
+    ) -> Result<(SectionOutput, Snark), Error> {
+        let circuit = SectionCircuit::<Fr, C>::new(
+            body.to_vec(),
+            boundary.func_count_out,
+            boundary.commitment_out,
+        )
+        .map_err(|_| Error::Synthesis)?;
+        let output = circuit.output();
+        let params = &self.params;
+        let pk = self
+            .section_pks
+            .entry((C::SECTION, output.code_hash))
+            .or_insert_with(|| gen_pk(params, &circuit, None));
+        let snark = gen_snark_shplonk(params, pk, circuit, rng, None::<String>);
+        Ok((output, snark))
+    }
+
+    fn section_snarks(
+        &mut self,
+        wb: &WasmBytecode,
+        rng: &mut (impl RngCore + Send),
+    ) -> Result<(Vec<SectionOutput>, Vec<Snark>), Error> {
+        let mut outputs = vec![];
+        let mut snarks = vec![];
+        // outputs of the sections before the first one
+        let mut boundary = SectionOutput {
+            section: WasmSection::Custom,
+            code_hash: Word::zero(),
+            commitment_in: Word::zero(),
+            commitment_out: Word::zero(),
+            func_count_in: 0,
+            func_count_out: 0,
+        };
+        for report in wb.sections() {
+            let body = &wb.bytes[report.section_body_start_offset..=report.section_end_offset];
+            let (output, snark) = match report.section {
+                WasmSection::Custom | WasmSection::DataCount => continue,
+                WasmSection::Type => {
+                    self.section_snark::<WasmTypeSectionBodyChip<Fr>>(body, &boundary, rng)?
+                }
+                WasmSection::Import => {
+                    self.section_snark::<WasmImportSectionBodyChip<Fr>>(body, &boundary, rng)?
+                }
+                WasmSection::Function => {
+                    self.section_snark::<WasmFunctionSectionBodyChip<Fr>>(body, &boundary, rng)?
+                }
+                WasmSection::Table => {
+                    self.section_snark::<WasmTableSectionBodyChip<Fr>>(body, &boundary, rng)?
+                }
+                WasmSection::Memory => {
+                    self.section_snark::<WasmMemorySectionBodyChip<Fr>>(body, &boundary, rng)?
+                }
+                WasmSection::Global => {
+                    self.section_snark::<WasmGlobalSectionBodyChip<Fr>>(body, &boundary, rng)?
+                }
+                WasmSection::Export => {
+                    self.section_snark::<WasmExportSectionBodyChip<Fr>>(body, &boundary, rng)?
+                }
+                WasmSection::Start => {
+                    self.section_snark::<WasmStartSectionBodyChip<Fr>>(body, &boundary, rng)?
+                }
+                WasmSection::Element => {
+                    self.section_snark::<WasmElementSectionBodyChip<Fr>>(body, &boundary, rng)?
+                }
+                WasmSection::Code => {
+                    self.section_snark::<WasmCodeSectionBodyChip<Fr>>(body, &boundary, rng)?
+                }
+                WasmSection::Data => {
+                    self.section_snark::<WasmDataSectionBodyChip<Fr>>(body, &boundary, rng)?
+                }
+            };
+            boundary = output.clone();
+            outputs.push(output);
+            snarks.push(snark);
+        }
+        Ok((outputs, snarks))
+    }
+
+    pub fn prove(
+        &mut self,
+        wb: &WasmBytecode,
+        mut rng: impl RngCore + Send,
+    ) -> Result<RecursiveWasmProof, Error> {
+        let (sections, snarks) = self.section_snarks(wb, &mut rng)?;
+        if snarks.is_empty() {
+            return Err(Error::Synthesis);
+        }
+
+        let circuit = SectionAggregationCircuit::new(&self.agg_params, snarks, &mut rng);
+        let instances = circuit.instances();
+        let agg_params = &self.agg_params;
+        let keys_id = wasm_keys_id(std::slice::from_ref(wb));
+        let (pk, _) = self.agg_pks.entry(keys_id.clone()).or_insert_with(|| {
+            (gen_pk(agg_params, &circuit, None), circuit.num_instance())
+        });
+        let bytes = gen_evm_proof_shplonk(agg_params, pk, circuit, instances.clone(), &mut rng);
+
+        Ok(RecursiveWasmProof {
+            keys_id,
+            sections,
+            instances,
+            bytes,
+        })
+    }
+
+    /// Verifier of the proofs of `wb`, `None` until it was proven since the outer keys are
+    /// generated with the first proof
+    pub fn verifier(&self, wb: &WasmBytecode) -> Option<RecursiveWasmVerifier> {
+        let keys_id = wasm_keys_id(std::slice::from_ref(wb));
+        let (pk, num_instance) = self.agg_pks.get(&keys_id)?;
+        let deployment_code = gen_evm_verifier_shplonk::<SectionAggregationCircuit>(
+            &self.agg_params,
+            pk.get_vk(),
+            num_instance.clone(),
+            None,
+        );
+        Some(RecursiveWasmVerifier {
+            keys_id,
+            sections: section_outputs(wb).ok()?,
+            deployment_code,
+        })
+    }
+}
+
+/// Settlement side of a [`RecursiveWasmProof`]: the section outputs expected for the module and
+/// the bytecode of the evm contract verifying the outer snark.
+pub struct RecursiveWasmVerifier {
+    keys_id: WasmKeysId,
+    sections: Vec<SectionOutput>,
+    deployment_code: Vec<u8>,
+}
+
+impl RecursiveWasmVerifier {
+    pub fn deployment_code(&self) -> &[u8] {
+        &self.deployment_code
+    }
+
+    pub fn sections(&self) -> &[SectionOutput] {
+        &self.sections
+    }
+
+    /// Commitment of the module, the one its last section ends with
+    pub fn module_commitment(&self) -> Word {
+        self.sections
+            .last()
+            .map(|section| section.commitment_out)
+            .unwrap_or_default()
+    }
+
+    /// Runs the verifier contract in an evm, a revert rejects the proof. The outer snark chains
+    /// the section boundaries, the first section must start at 0.
+    pub fn verify(&self, proof: &RecursiveWasmProof) -> Result<(), Error> {
+        if proof.keys_id != self.keys_id || proof.sections != self.sections {
+            return Err(Error::ConstraintSystemFailure);
+        }
+        if proof.sections.first().map_or(true, |section| {
+            !section.commitment_in.is_zero() || section.func_count_in != 0
+        }) {
+            return Err(Error::ConstraintSystemFailure);
+        }
+        let section_instances = proof
+            .sections
+            .iter()
+            .flat_map(SectionOutput::instance::<Fr>)
+            .collect::<Vec<_>>();
+        if !proof.instances[0].ends_with(&section_instances) {
+            return Err(Error::ConstraintSystemFailure);
+        }
+        deploy_and_call(
+            self.deployment_code.clone(),
+            encode_calldata(&proof.instances, &proof.bytes),
+        )
+    }
+}
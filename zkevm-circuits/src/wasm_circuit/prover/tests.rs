@@ -0,0 +1,335 @@
+#[cfg(test)]
+mod wasm_prover_tests {
+    use rand::{rngs::OsRng, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+    use wabt::wat2wasm;
+
+    use halo2_proofs::{
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        poly::commitment::{Params, ParamsProver},
+    };
+    use snark_verifier_sdk::CircuitExt;
+
+    use eth_types::ToScalar;
+
+    use crate::wasm_circuit::{
+        bytecode::bytecode::WasmBytecode,
+        circuit::WasmCircuit,
+        prover::{
+            aggregation::BlockAggregator,
+            evm::{decode_calldata, encode_calldata},
+            params_store::{ParamsStore, ParamsStoreError},
+            prover::{
+                wasm_keys_id, Ipa, Kzg, WasmBackend, WasmProof, WasmProver, WasmVerifier,
+            },
+            recursion::{section_commitment, section_outputs, RecursiveWasmProver, SectionCircuit},
+        },
+        sections::{
+            code::body::circuit::WasmCodeSectionBodyChip,
+            import::body::circuit::WasmImportSectionBodyChip,
+        },
+        types::{ErrorCode, WasmSection},
+    };
+
+    fn wat_file_to_wb(path: &str) -> WasmBytecode {
+        let data: Vec<u8> = std::fs::read(path).unwrap();
+        WasmBytecode::new(wat2wasm(data).unwrap())
+    }
+
+    fn test_prover<B: WasmBackend>(k: u32) -> WasmProver<B> {
+        let rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        WasmProver::setup(k, rng)
+    }
+
+    #[test]
+    pub fn file1_prove_verify_ok() {
+        let wbs = vec![wat_file_to_wb("./test_files/cc1.wat")];
+        let mut prover = test_prover::<Kzg>(9);
+
+        let proof = prover.prove(&wbs).unwrap();
+        let verifier = prover.verifier(&wbs).unwrap();
+        verifier.verify(&proof).unwrap();
+
+        // keys are cached, second proof reuses them
+        let proof = prover.prove(&wbs).unwrap();
+        verifier.verify(&proof).unwrap();
+    }
+
+    #[test]
+    pub fn proof_for_other_bytecode_fails() {
+        let wbs1 = vec![wat_file_to_wb("./test_files/cc1.wat")];
+        let wbs3 = vec![wat_file_to_wb("./test_files/cc3.wat")];
+        let mut prover = test_prover::<Kzg>(9);
+
+        let mut proof = prover.prove(&wbs3).unwrap();
+        let verifier = prover.verifier(&wbs1).unwrap();
+        assert!(verifier.verify(&proof).is_err());
+
+        // pretend the proof was made for the verifier's bytecode
+        proof.keys_id = wasm_keys_id(&wbs1);
+        assert!(verifier.verify(&proof).is_err());
+    }
+
+    #[test]
+    pub fn multiple_modules_tampered_outputs_fail() {
+        let mut wb_broken = wat_file_to_wb("./test_files/cc1.wat");
+        wb_broken.bytes[8] = WasmSection::DataCount as u8 + 1;
+        let wbs = vec![wb_broken, wat_file_to_wb("./test_files/cc2.wat")];
+        let mut prover = test_prover::<Kzg>(11);
+
+        let mut proof = prover.prove(&wbs).unwrap();
+        let error_codes = proof.outputs.iter().map(|o| o.error_code).collect::<Vec<_>>();
+        assert_eq!(error_codes, vec![ErrorCode::InvalidEnumValue as u64, 0]);
+        let verifier = prover.verifier(&wbs).unwrap();
+        verifier.verify(&proof).unwrap();
+
+        proof.outputs[0].error_code = 0;
+        assert!(verifier.verify(&proof).is_err());
+    }
+
+    #[cfg(feature = "prover-thread-pool")]
+    #[test]
+    pub fn file1_prove_on_thread_pool_ok() {
+        let wbs = vec![wat_file_to_wb("./test_files/cc1.wat")];
+        let mut prover = test_prover::<Kzg>(9);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+
+        let proof = prover.prove_on_pool(&wbs, &pool).unwrap();
+        prover.verifier(&wbs).unwrap().verify(&proof).unwrap();
+    }
+
+    fn prove_verify<B: WasmBackend>(wbs: &[WasmBytecode]) -> WasmProof {
+        let mut prover = test_prover::<B>(9);
+        let proof = prover.prove(wbs).unwrap();
+        prover.verifier(wbs).unwrap().verify(&proof).unwrap();
+        proof
+    }
+
+    #[test]
+    pub fn file1_prove_verify_kzg_and_ipa_ok() {
+        let wbs = vec![wat_file_to_wb("./test_files/cc1.wat")];
+        let kzg_proof = prove_verify::<Kzg>(&wbs);
+        let ipa_proof = prove_verify::<Ipa>(&wbs);
+        assert_eq!(kzg_proof.outputs, ipa_proof.outputs);
+
+        // proofs are bound to the backend they were made with
+        let kzg_verifier = test_prover::<Kzg>(9).verifier(&wbs).unwrap();
+        assert!(kzg_verifier.verify(&ipa_proof).is_err());
+        let ipa_verifier = test_prover::<Ipa>(9).verifier(&wbs).unwrap();
+        assert!(ipa_verifier.verify(&kzg_proof).is_err());
+    }
+
+    #[ignore = "Due to high memory requirement"]
+    #[test]
+    pub fn block_aggregation_prove_verify_ok() {
+        let wbs = vec![wat_file_to_wb("./test_files/cc1.wat")];
+        // a wasm circuit of other modules stands in for the execution circuit of the block
+        let execution = || {
+            let circuit = WasmCircuit::<Fr>::new(vec![wat_file_to_wb("./test_files/cc2.wat")]);
+            circuit.compute_module_outputs(11).unwrap();
+            circuit
+        };
+        let mut aggregator = BlockAggregator::<WasmCircuit<Fr>>::setup(11, 22, OsRng);
+        assert!(aggregator.verifier(&wbs).is_none());
+
+        let mut proof = aggregator.prove(&wbs, execution(), OsRng).unwrap();
+        let verifier = aggregator.verifier(&wbs).unwrap();
+        verifier.verify(&proof).unwrap();
+
+        // a proof rejected by the contract is an error, not a panic
+        let mut tampered = proof.clone();
+        tampered.bytes[0] ^= 1;
+        assert!(verifier.verify(&tampered).is_err());
+
+        proof.outputs[0].error_code = 1;
+        assert!(verifier.verify(&proof).is_err());
+    }
+
+    #[test]
+    pub fn calldata_round_trips_outputs() {
+        let wbs = vec![
+            wat_file_to_wb("./test_files/cc1.wat"),
+            wat_file_to_wb("./test_files/cc2.wat"),
+        ];
+        let circuit = WasmCircuit::<Fr>::new(wbs);
+        let outputs = circuit.compute_module_outputs(13).unwrap();
+        let proof = vec![0xab; 64];
+
+        let calldata = encode_calldata(&outputs, &proof);
+        assert_eq!(calldata.len(), 32 * circuit.num_instance()[0] + proof.len());
+        let (decoded, decoded_proof) = decode_calldata(&calldata, outputs.len()).unwrap();
+        assert_eq!(decoded_proof, proof);
+        for (decoded, output) in decoded.iter().zip(&outputs) {
+            assert_eq!(decoded.bytecode_number, output.bytecode_number);
+            assert_eq!(decoded.code_hash, output.code_hash);
+            assert_eq!(decoded.error_code, output.error_code);
+        }
+        assert!(decode_calldata(&calldata[..32], outputs.len()).is_none());
+    }
+
+    #[ignore = "Requires solc"]
+    #[test]
+    pub fn file1_evm_prove_verify_ok() {
+        let wbs = vec![wat_file_to_wb("./test_files/cc1.wat")];
+        let mut prover = test_prover::<Kzg>(9);
+
+        let mut proof = prover.prove_evm(&wbs, OsRng).unwrap();
+        let verifier = prover.evm_verifier(&wbs, None).unwrap();
+        assert!(!verifier.deployment_code().is_empty());
+        verifier.verify(&proof).unwrap();
+        assert_eq!(
+            decode_calldata(&verifier.calldata(&proof), 1).unwrap().1,
+            proof.bytes
+        );
+
+        // a proof rejected by the contract is an error, not a panic
+        let mut tampered = proof.clone();
+        tampered.bytes[0] ^= 1;
+        assert!(verifier.verify(&tampered).is_err());
+        tampered.bytes[0] ^= 1;
+        tampered.outputs[0].error_code ^= 1;
+        assert!(verifier.verify(&tampered).is_err());
+
+        // proofs of other bytecodes and truncated outputs are rejected before running the evm
+        let other_wbs = vec![wat_file_to_wb("./test_files/cc2.wat")];
+        assert!(verifier.verify(&prover.prove(&other_wbs).unwrap()).is_err());
+        proof.outputs.pop();
+        assert!(verifier.verify(&proof).is_err());
+    }
+
+    #[test]
+    pub fn params_store_reloads_and_checks_files() {
+        let dir = std::env::temp_dir().join(format!("wasm_params_store_{}", std::process::id()));
+        let wbs = vec![wat_file_to_wb("./test_files/cc1.wat")];
+        let mut store = ParamsStore::open(&dir).unwrap();
+        store.setup(10, OsRng).unwrap();
+        let vk = store.verifying_key(9, &wbs).unwrap();
+
+        // a fresh store downsizes the k=10 parameters and reads the written keys back
+        let mut store = ParamsStore::open(&dir).unwrap();
+        assert_eq!(store.params(9).unwrap().k(), 9);
+        assert!(matches!(
+            store.params(8),
+            Err(ParamsStoreError::UnsupportedK(8))
+        ));
+        assert_eq!(
+            store.verifying_key(9, &wbs).unwrap().transcript_repr(),
+            vk.transcript_repr()
+        );
+        let mut prover = store.prover(9, &[&wbs]).unwrap();
+        let proof = prover.prove(&wbs).unwrap();
+        let verifier = WasmVerifier::<Kzg>::new(
+            store.params(9).unwrap().verifier_params().clone(),
+            wasm_keys_id(&wbs),
+            vk,
+        );
+        verifier.verify(&proof).unwrap();
+
+        // files not matching the pinned or recorded hashes are rejected
+        let mut store = ParamsStore::open(&dir).unwrap().pin_params_hash(10, "00");
+        assert!(matches!(
+            store.params(10),
+            Err(ParamsStoreError::HashMismatch { .. })
+        ));
+        let params_file = dir.join("kzg_bn254_10.srs");
+        let mut bytes = std::fs::read(&params_file).unwrap();
+        bytes[64] ^= 1;
+        std::fs::write(&params_file, bytes).unwrap();
+        let mut store = ParamsStore::open(&dir).unwrap();
+        assert!(matches!(
+            store.params(10),
+            Err(ParamsStoreError::HashMismatch { .. })
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    pub fn section_circuits_chain_func_count() {
+        let wb = wat_file_to_wb("./test_files/cc1.wat");
+        let body = |section| wb.bytes[wb.section_body(section).unwrap()].to_vec();
+
+        let outputs = section_outputs(&wb).unwrap();
+        let output = |section| {
+            outputs
+                .iter()
+                .find(|output| output.section == section)
+                .unwrap()
+        };
+
+        let import = SectionCircuit::<Fr, WasmImportSectionBodyChip<Fr>>::new(
+            body(WasmSection::Import),
+            0,
+            output(WasmSection::Import).commitment_in,
+        )
+        .unwrap();
+        assert_eq!(import.func_count_out, 3);
+        let code_input = output(WasmSection::Code);
+        let code = SectionCircuit::<Fr, WasmCodeSectionBodyChip<Fr>>::new(
+            body(WasmSection::Code),
+            code_input.func_count_in,
+            code_input.commitment_in,
+        )
+        .unwrap();
+        assert_eq!(&import.output(), output(WasmSection::Import));
+        assert_eq!(&code.output(), code_input);
+        // the module commitment chains the commitments of the sections
+        let module_commitment = outputs.iter().fold(Fr::zero(), |commitment, output| {
+            section_commitment(commitment, output.section, &body(output.section))
+        });
+        assert_eq!(
+            outputs.last().unwrap().commitment_out.to_scalar(),
+            Some(module_commitment)
+        );
+
+        let k = 15;
+        let prover = MockProver::run(k, &import, import.instances()).unwrap();
+        prover.assert_satisfied();
+        let prover = MockProver::run(k, &code, code.instances()).unwrap();
+        prover.assert_satisfied();
+
+        // func_count_out and the commitment must follow from the body
+        for idx in [1, 3] {
+            let mut instances = code.instances();
+            instances[0][idx] += Fr::from(1);
+            let prover = MockProver::run(k, &code, instances).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[ignore = "Due to high memory requirement"]
+    #[test]
+    pub fn recursive_prove_verify_ok() {
+        let wb = wat_file_to_wb("./test_files/cc1.wat");
+        let mut prover = RecursiveWasmProver::setup(15, 22, OsRng);
+        assert!(prover.verifier(&wb).is_none());
+
+        let mut proof = prover.prove(&wb, OsRng).unwrap();
+        let verifier = prover.verifier(&wb).unwrap();
+        verifier.verify(&proof).unwrap();
+        assert_eq!(
+            verifier.module_commitment(),
+            proof.sections.last().unwrap().commitment_out
+        );
+
+        // a proof rejected by the contract is an error, not a panic
+        let mut tampered = proof.clone();
+        tampered.bytes[0] ^= 1;
+        assert!(verifier.verify(&tampered).is_err());
+
+        // sections of another module do not link
+        let other = wat_file_to_wb("./test_files/cc2.wat");
+        let other_proof = prover.prove(&other, OsRng).unwrap();
+        assert!(verifier.verify(&other_proof).is_err());
+
+        proof.sections.last_mut().unwrap().func_count_in += 1;
+        assert!(verifier.verify(&proof).is_err());
+    }
+}
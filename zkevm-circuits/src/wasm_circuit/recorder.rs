@@ -0,0 +1,4 @@
+pub mod journal;
+pub mod recorder;
+#[cfg(any(feature = "test", test))]
+pub mod tests;
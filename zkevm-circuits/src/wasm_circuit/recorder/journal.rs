@@ -0,0 +1,324 @@
+use std::{collections::HashMap, fmt};
+
+use halo2_proofs::{
+    circuit::{layouter::RegionLayouter, Cell, Region, Value},
+    plonk::{Advice, Any, Assigned, Column, Error, Fixed, Instance, Selector},
+};
+
+use eth_types::Field;
+
+use crate::wasm_circuit::types::AssignOffsetType;
+
+/// Single journaled assignment. `value` is `None` when the value was unknown (keygen).
+#[derive(Clone, Debug, PartialEq)]
+pub enum JournalEntry<F: Field> {
+    Advice {
+        column: Column<Advice>,
+        assign_offset: AssignOffsetType,
+        value: Option<F>,
+    },
+    Fixed {
+        column: Column<Fixed>,
+        assign_offset: AssignOffsetType,
+        value: Option<F>,
+    },
+    Selector {
+        selector: Selector,
+        assign_offset: AssignOffsetType,
+    },
+}
+
+/// Cell assigned twice with different values
+#[derive(Clone, Debug, PartialEq)]
+pub struct JournalConflict<F: Field> {
+    pub column: Column<Any>,
+    pub assign_offset: AssignOffsetType,
+    pub prev_value: Option<F>,
+    pub value: Option<F>,
+}
+
+impl<F: Field> JournalEntry<F> {
+    /// (column, assign_offset) of the assigned cell, selectors are not cells
+    fn cell(&self) -> Option<(Column<Any>, AssignOffsetType)> {
+        match self {
+            JournalEntry::Advice {
+                column,
+                assign_offset,
+                ..
+            } => Some(((*column).into(), *assign_offset)),
+            JournalEntry::Fixed {
+                column,
+                assign_offset,
+                ..
+            } => Some(((*column).into(), *assign_offset)),
+            JournalEntry::Selector { .. } => None,
+        }
+    }
+
+    fn value(&self) -> Option<F> {
+        match self {
+            JournalEntry::Advice { value, .. } | JournalEntry::Fixed { value, .. } => *value,
+            JournalEntry::Selector { .. } => Some(F::one()),
+        }
+    }
+}
+
+/// Cell values assigned in a region, in assignment order. Only the latest value of a cell is
+/// replayed, copy constraints are not journaled and must be re-created by the caller.
+#[derive(Clone, Debug, Default)]
+pub struct AssignJournal<F: Field> {
+    entries: Vec<JournalEntry<F>>,
+    /// (column, assign_offset) -> index of the latest entry of the cell
+    cells: HashMap<(Column<Any>, AssignOffsetType), usize>,
+    conflicts: Vec<JournalConflict<F>>,
+}
+
+impl<F: Field> AssignJournal<F> {
+    pub fn entries(&self) -> &[JournalEntry<F>] {
+        &self.entries
+    }
+
+    pub fn conflicts(&self) -> &[JournalConflict<F>] {
+        &self.conflicts
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Latest value assigned to the cell
+    pub fn value_at(&self, column: Column<Any>, assign_offset: AssignOffsetType) -> Option<F> {
+        self.cells
+            .get(&(column, assign_offset))
+            .and_then(|&idx| self.entries[idx].value())
+    }
+
+    fn push(&mut self, entry: JournalEntry<F>) {
+        if let Some((column, assign_offset)) = entry.cell() {
+            if let Some(&idx) = self.cells.get(&(column, assign_offset)) {
+                let prev_value = self.entries[idx].value();
+                if prev_value != entry.value() {
+                    self.conflicts.push(JournalConflict {
+                        column,
+                        assign_offset,
+                        prev_value,
+                        value: entry.value(),
+                    });
+                }
+            }
+            self.cells.insert((column, assign_offset), self.entries.len());
+        }
+        self.entries.push(entry);
+    }
+
+    /// Re-applies the latest value of every journaled cell (and the enabled selectors) to
+    /// `region`
+    pub fn replay(&self, region: &mut Region<F>) -> Result<(), Error> {
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if let Some(cell) = entry.cell() {
+                if self.cells.get(&cell) != Some(&idx) {
+                    continue;
+                }
+            }
+            match entry {
+                JournalEntry::Advice {
+                    column,
+                    assign_offset,
+                    value,
+                } => {
+                    region.assign_advice(
+                        || format!("replay advice at {}", assign_offset),
+                        *column,
+                        *assign_offset,
+                        || Value::known(value.unwrap_or_default()),
+                    )?;
+                }
+                JournalEntry::Fixed {
+                    column,
+                    assign_offset,
+                    value,
+                } => {
+                    region.assign_fixed(
+                        || format!("replay fixed at {}", assign_offset),
+                        *column,
+                        *assign_offset,
+                        || Value::known(value.unwrap_or_default()),
+                    )?;
+                }
+                JournalEntry::Selector {
+                    selector,
+                    assign_offset,
+                } => {
+                    region.enable_selector(
+                        || format!("replay selector at {}", assign_offset),
+                        selector,
+                        *assign_offset,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn known_value<F: Field>(value: &Value<Assigned<F>>) -> Option<F> {
+    let mut known = None;
+    value.as_ref().map(|v| known = Some(v.evaluate()));
+    known
+}
+
+/// Region layouter journaling the assignments it forwards to the wrapped region
+struct JournalingRegion<'a, 'r, F: Field> {
+    region: &'a mut Region<'r, F>,
+    journal: &'a mut AssignJournal<F>,
+}
+
+impl<'a, 'r, F: Field> fmt::Debug for JournalingRegion<'a, 'r, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JournalingRegion")
+            .field("entries", &self.journal.len())
+            .finish()
+    }
+}
+
+impl<'a, 'r, F: Field> RegionLayouter<F> for JournalingRegion<'a, 'r, F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.region.enable_selector(annotation, selector, offset)?;
+        self.journal.push(JournalEntry::Selector {
+            selector: *selector,
+            assign_offset: offset,
+        });
+        Ok(())
+    }
+
+    fn name_column<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Any>,
+    ) {
+        self.region.name_column(annotation, column);
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        let mut value = None;
+        let cell = self
+            .region
+            .assign_advice(annotation, column, offset, || {
+                let assigned = to();
+                value = known_value(&assigned);
+                assigned
+            })?
+            .cell();
+        self.journal.push(JournalEntry::Advice {
+            column,
+            assign_offset: offset,
+            value,
+        });
+        Ok(cell)
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        let cell = self
+            .region
+            .assign_advice_from_constant(annotation, column, offset, constant)?
+            .cell();
+        self.journal.push(JournalEntry::Advice {
+            column,
+            assign_offset: offset,
+            value: Some(constant.evaluate()),
+        });
+        Ok(cell)
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        instance: Column<Instance>,
+        row: usize,
+        advice: Column<Advice>,
+        offset: usize,
+    ) -> Result<(Cell, Value<F>), Error> {
+        let assigned_cell =
+            self.region
+                .assign_advice_from_instance(annotation, instance, row, advice, offset)?;
+        let value = assigned_cell.value().copied();
+        self.journal.push(JournalEntry::Advice {
+            column: advice,
+            assign_offset: offset,
+            value: known_value(&value.map(Assigned::from)),
+        });
+        Ok((assigned_cell.cell(), value))
+    }
+
+    fn instance_value(
+        &mut self,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<Value<F>, Error> {
+        self.region.instance_value(instance, row)
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        let mut value = None;
+        let cell = self
+            .region
+            .assign_fixed(annotation, column, offset, || {
+                let assigned = to();
+                value = known_value(&assigned);
+                assigned
+            })?
+            .cell();
+        self.journal.push(JournalEntry::Fixed {
+            column,
+            assign_offset: offset,
+            value,
+        });
+        Ok(cell)
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.region.constrain_constant(cell, constant)
+    }
+
+    fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error> {
+        self.region.constrain_equal(left, right)
+    }
+}
+
+/// Runs `assign` on a region journaling every assignment made to `region` into `journal`
+pub fn with_journal<F: Field, T>(
+    region: &mut Region<F>,
+    journal: &mut AssignJournal<F>,
+    assign: impl FnOnce(&mut Region<F>) -> T,
+) -> T {
+    let mut journaling_region = JournalingRegion { region, journal };
+    let mut region = Region::from(&mut journaling_region as &mut dyn RegionLayouter<F>);
+    assign(&mut region)
+}
@@ -0,0 +1,94 @@
+use std::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+
+use crate::wasm_circuit::types::{AssignOffsetType, AssignValueType};
+
+/// Single recorded assignment. `column` is the assign type (or helper column name) and `chip` is
+/// the type name of the chip which made the assignment.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AssignRecord {
+    pub chip: String,
+    pub column: String,
+    pub assign_offset: AssignOffsetType,
+    pub value: AssignValueType,
+    pub annotation: String,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AssignTrace {
+    pub records: Vec<AssignRecord>,
+}
+
+impl AssignTrace {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn write_json(&self, path: &str) -> std::io::Result<()> {
+        let json = self
+            .to_json()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        std::fs::write(path, json)
+    }
+
+    /// (assign_offset, error_code) pairs at which the assigned error code changes its value
+    pub fn error_code_timeline(&self) -> Vec<(AssignOffsetType, AssignValueType)> {
+        let mut timeline: Vec<(AssignOffsetType, AssignValueType)> = vec![];
+        let mut error_codes = self
+            .records
+            .iter()
+            .filter(|r| r.column == ERROR_CODE_COLUMN)
+            .map(|r| (r.assign_offset, r.value))
+            .collect::<Vec<_>>();
+        error_codes.sort_by_key(|(offset, _)| *offset);
+        for (offset, error_code) in error_codes {
+            match timeline.last() {
+                Some((_, last_error_code)) if *last_error_code == error_code => {}
+                _ => timeline.push((offset, error_code)),
+            }
+        }
+        timeline
+    }
+}
+
+pub const ERROR_CODE_COLUMN: &str = "error_code";
+
+thread_local! {
+    static RECORDER: RefCell<Option<AssignTrace>> = RefCell::new(None);
+}
+
+/// Opt-in recorder of `assign*` calls made by wasm chips on the current thread. Recording is off
+/// unless [`start`] was called, so regular witness generation is not affected.
+pub fn start() {
+    RECORDER.with(|r| *r.borrow_mut() = Some(AssignTrace::default()));
+}
+
+/// Stops recording and returns everything recorded since [`start`]
+pub fn stop() -> AssignTrace {
+    RECORDER.with(|r| r.borrow_mut().take().unwrap_or_default())
+}
+
+pub fn is_recording() -> bool {
+    RECORDER.with(|r| r.borrow().is_some())
+}
+
+pub fn record(
+    chip: &str,
+    column: &str,
+    assign_offset: AssignOffsetType,
+    value: AssignValueType,
+    annotation: impl FnOnce() -> String,
+) {
+    RECORDER.with(|r| {
+        if let Some(trace) = r.borrow_mut().as_mut() {
+            trace.records.push(AssignRecord {
+                chip: chip.to_string(),
+                column: column.to_string(),
+                assign_offset,
+                value,
+                annotation: annotation(),
+            });
+        }
+    });
+}
@@ -0,0 +1,235 @@
+#[cfg(test)]
+mod wasm_recorder_tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use wabt::wat2wasm;
+
+    use crate::wasm_circuit::{
+        bytecode::bytecode::WasmBytecode,
+        circuit::WasmCircuit,
+        recorder::recorder::{self, AssignTrace},
+        types::{ErrorCode, WasmSection},
+    };
+
+    fn wat_file_to_wb(path: &str) -> WasmBytecode {
+        let data: Vec<u8> = std::fs::read(path).unwrap();
+        WasmBytecode::new(wat2wasm(data).unwrap())
+    }
+
+    fn record(wbs: Vec<WasmBytecode>, k: u32) -> AssignTrace {
+        let circuit = WasmCircuit::<Fr>::new(wbs);
+        let outputs = circuit.compute_module_outputs(k).unwrap();
+        recorder::start();
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+        let prover = MockProver::run(k, &circuit, instance).unwrap();
+        let trace = recorder::stop();
+        prover.assert_satisfied();
+        trace
+    }
+
+    #[test]
+    pub fn recording_is_off_by_default() {
+        assert!(!recorder::is_recording());
+        let circuit = WasmCircuit::<Fr>::new(vec![wat_file_to_wb("./test_files/cc1.wat")]);
+        circuit.compute_module_outputs(9).unwrap();
+        assert!(!recorder::is_recording());
+        assert!(recorder::stop().records.is_empty());
+    }
+
+    #[test]
+    pub fn file1_trace_json_roundtrip_ok() {
+        let trace = record(vec![wat_file_to_wb("./test_files/cc1.wat")], 9);
+        assert!(!recorder::is_recording());
+        assert!(!trace.records.is_empty());
+        assert!(trace.records.iter().any(|r| r.column == "bytecode_number"));
+
+        let json = trace.to_json().unwrap();
+        let trace_restored: AssignTrace = serde_json::from_str(&json).unwrap();
+        assert_eq!(trace, trace_restored);
+
+        let timeline = trace.error_code_timeline();
+        assert!(timeline.iter().all(|(_, error_code)| *error_code == 0));
+    }
+
+    #[test]
+    pub fn file1_invalid_section_id_timeline_ok() {
+        let mut wb = wat_file_to_wb("./test_files/cc1.wat");
+        wb.bytes[8] = WasmSection::DataCount as u8 + 1;
+        let trace = record(vec![wb], 9);
+
+        let timeline = trace.error_code_timeline();
+        assert_eq!(
+            timeline.last().map(|(_, error_code)| *error_code),
+            Some(ErrorCode::InvalidEnumValue as u64)
+        );
+    }
+}
+
+#[cfg(test)]
+mod wasm_journal_tests {
+    use std::cell::RefCell;
+
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+    use wabt::wat2wasm;
+
+    use eth_types::Field;
+
+    use crate::wasm_circuit::{
+        bytecode::bytecode::WasmBytecode,
+        circuit::{WasmChip, WasmCircuit, WasmCircuitConfig},
+        recorder::journal::{with_journal, AssignJournal},
+        types::WasmSection,
+    };
+
+    #[derive(Default)]
+    struct JournalCircuit<F: Field> {
+        wbs: Vec<WasmBytecode>,
+        /// replayed instead of assigning `wbs` when set
+        replayed_journal: Option<AssignJournal<F>>,
+        journal: RefCell<AssignJournal<F>>,
+    }
+
+    impl<F: Field> Circuit<F> for JournalCircuit<F> {
+        type Config = WasmCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            WasmCircuit::<F>::configure(cs)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let mut wasm_chip = WasmChip::construct(config.wasm_config.clone());
+            wasm_chip.load_once(&mut layouter).map_err(|_| Error::Synthesis)?;
+            layouter.assign_region(
+                || "wasm_chip region",
+                |mut region| {
+                    if let Some(journal) = &self.replayed_journal {
+                        return journal.replay(&mut region);
+                    }
+                    let mut journal = AssignJournal::default();
+                    wasm_chip.config.shared_state.write().reset();
+                    let mut cursor = wasm_chip.region_cursor(0);
+                    for wb in &self.wbs {
+                        wasm_chip
+                            .assign_next_journaled(&mut region, wb, 0, &mut cursor, &mut journal)
+                            .map_err(|_| Error::Synthesis)?;
+                    }
+                    *self.journal.borrow_mut() = journal;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn wat_file_to_wb(path: &str) -> WasmBytecode {
+        let data: Vec<u8> = std::fs::read(path).unwrap();
+        WasmBytecode::new(wat2wasm(data).unwrap())
+    }
+
+    fn journal(wbs: Vec<WasmBytecode>, k: u32) -> AssignJournal<Fr> {
+        let circuit = JournalCircuit::<Fr> {
+            wbs,
+            ..Default::default()
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+        prover.assert_satisfied();
+        circuit.journal.into_inner()
+    }
+
+    #[test]
+    pub fn file1_journal_replay_ok() {
+        let journal = journal(vec![wat_file_to_wb("./test_files/cc1.wat")], 9);
+        assert!(!journal.is_empty());
+
+        let circuit = JournalCircuit::<Fr> {
+            replayed_journal: Some(journal),
+            ..Default::default()
+        };
+        let prover = MockProver::run(9, &circuit, vec![vec![]]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn file1_invalid_section_id_journal_replay_ok() {
+        let mut wb = wat_file_to_wb("./test_files/cc1.wat");
+        wb.bytes[8] = WasmSection::DataCount as u8 + 1;
+        let journal = journal(vec![wb], 9);
+
+        let circuit = JournalCircuit::<Fr> {
+            replayed_journal: Some(journal),
+            ..Default::default()
+        };
+        let prover = MockProver::run(9, &circuit, vec![vec![]]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[derive(Default)]
+    struct DoubleAssignCircuit {
+        journal: RefCell<AssignJournal<Fr>>,
+    }
+
+    impl Circuit<Fr> for DoubleAssignCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fr>) -> Self::Config {
+            cs.advice_column()
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "double assign region",
+                |mut region| {
+                    let mut journal = AssignJournal::default();
+                    with_journal(&mut region, &mut journal, |region| {
+                        for value in [1, 1, 2] {
+                            region.assign_advice(
+                                || "value",
+                                config,
+                                0,
+                                || Value::known(Fr::from(value)),
+                            )?;
+                        }
+                        Ok::<_, Error>(())
+                    })?;
+                    *self.journal.borrow_mut() = journal;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    pub fn double_assignment_with_different_values_is_a_conflict() {
+        let circuit = DoubleAssignCircuit::default();
+        MockProver::run(4, &circuit, vec![]).unwrap();
+        let journal = circuit.journal.into_inner();
+        assert_eq!(journal.len(), 3);
+        assert_eq!(journal.conflicts().len(), 1);
+        let conflict = &journal.conflicts()[0];
+        assert_eq!(conflict.assign_offset, 0);
+        assert_eq!(conflict.prev_value, Some(Fr::from(1)));
+        assert_eq!(conflict.value, Some(Fr::from(2)));
+        assert_eq!(journal.value_at(conflict.column, 0), Some(Fr::from(2)));
+    }
+}
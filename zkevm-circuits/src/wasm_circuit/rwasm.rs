@@ -0,0 +1,6 @@
+pub mod circuit;
+pub mod flattener;
+pub mod translator;
+#[cfg(any(feature = "test", test))]
+pub mod tests;
+pub mod types;
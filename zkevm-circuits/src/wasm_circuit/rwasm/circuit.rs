@@ -0,0 +1,933 @@
+use std::{collections::HashMap, marker::PhantomData};
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Expression, Fixed, Instance},
+    poly::Rotation,
+};
+use log::debug;
+
+use eth_types::{Field, ToScalar};
+use gadgets::{
+    is_zero::{IsZeroChip, IsZeroInstruction},
+    less_than::{LtChip, LtInstruction},
+    util::{and, not, Expr},
+};
+
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    wasm_circuit::{
+        bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+        common::configure_constraints_for_q_first_and_q_last,
+        consts::WASM_BLOCK_END,
+        error::{error_index_out_of_bounds, remap_error, remap_error_to_assign_at, Error},
+        rwasm::flattener::{flatten, FlattenedModule},
+        tables::{
+            fixed_range::config::RangeTableConfig,
+            opcode::{
+                config::OpcodeTableConfig,
+                types::{opcode_rows, ImmediateKind, OpcodeClass},
+            },
+        },
+        types::{AssignDeltaType, ControlInstruction},
+    },
+};
+
+/// Opcodes told apart by the equivalence gate, in `opcode_chips` order
+const TRACKED_OPCODES: [u8; 7] = [
+    ControlInstruction::Block as u8,
+    ControlInstruction::Loop as u8,
+    ControlInstruction::If as u8,
+    ControlInstruction::Else as u8,
+    WASM_BLOCK_END,
+    ControlInstruction::Br as u8,
+    ControlInstruction::BrIf as u8,
+];
+const BLOCK: usize = 0;
+const LOOP: usize = 1;
+const IF: usize = 2;
+const ELSE: usize = 3;
+const END: usize = 4;
+const BR: usize = 5;
+const BR_IF: usize = 6;
+
+/// Bytes of the longest immediate, the leb128 of a 64-bit const
+const MAX_IMMEDIATE_BYTES: usize = 10;
+
+/// Rows of a flattened module checked against the wasm bytecode table it was translated from.
+/// Every instruction is in the opcode table and keeps the opcode found at its bytecode offset
+/// and the bytes of its immediate, within a function the next instruction starts right after
+/// them so no instruction is skipped or repeated. Every row belongs to the innermost open block, named by the index of
+/// its first instruction (the entry for the function body) and the index of its `end`. The
+/// relocated operand of `br`/`br_if` points to the `end` (to the `loop` itself for loops) of the
+/// block enclosing the branch at the label depth, the one of `if`/`else` to the `else`/`end` of
+/// their own block. The bytes between two functions (body size and locals) are not checked.
+#[derive(Debug, Clone)]
+pub struct RwasmEquivalenceConfig<F: Field> {
+    pub q_enable: Column<Fixed>,
+    pub q_first: Column<Fixed>,
+    pub q_last: Column<Fixed>,
+    pub code_hash: Column<Advice>,
+    /// instruction index in the flattened stream
+    pub index: Column<Advice>,
+    /// bytecode offset of the opcode the instruction was translated from
+    pub wb_offset: Column<Advice>,
+    pub opcode: Column<Advice>,
+    pub operand: Column<Advice>,
+    /// opcode table row of the opcode
+    pub opcode_class: Column<Advice>,
+    pub immediate_kind: Column<Advice>,
+    /// bytes following the opcode, their low 7 bits and whether they belong to its immediate
+    pub imm_bytes: [Column<Advice>; MAX_IMMEDIATE_BYTES],
+    pub imm_lows: [Column<Advice>; MAX_IMMEDIATE_BYTES],
+    pub is_imm: [Column<Advice>; MAX_IMMEDIATE_BYTES],
+    /// open blocks before the instruction, the function body included
+    pub block_level: Column<Advice>,
+    /// index of the first instruction and of the `end` of the innermost open block
+    pub block_id: Column<Advice>,
+    pub block_end: Column<Advice>,
+    pub is_func_start: Column<Advice>,
+    /// opcode and block level of the instruction a relocated operand points to
+    pub target_opcode: Column<Advice>,
+    pub target_block_level: Column<Advice>,
+    /// block of the `end`/`else` a relocated operand points to, of the body of a `loop`
+    pub target_block_id: Column<Advice>,
+    pub target_block_end: Column<Advice>,
+
+    pub opcode_chips: Vec<IsZeroChip<F>>,
+    pub wb_offset_lt_chip: LtChip<F, 4>,
+    /// `index < block_end + 1`
+    pub block_end_lt_chip: LtChip<F, 4>,
+    /// `target_block_id < index + 1`
+    pub target_block_id_lt_chip: LtChip<F, 4>,
+    /// `index < target_block_end`
+    pub target_block_end_lt_chip: LtChip<F, 4>,
+
+    _marker: PhantomData<F>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RwasmEquivalenceChip<F: Field> {
+    pub config: RwasmEquivalenceConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> RwasmEquivalenceChip<F> {
+    pub fn construct(config: RwasmEquivalenceConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        wb_table: &WasmBytecodeTable,
+        opcode_table: &OpcodeTableConfig<F>,
+        range_table_config_0_128: &RangeTableConfig<F, 0, 128>,
+    ) -> RwasmEquivalenceConfig<F> {
+        let q_enable = cs.fixed_column();
+        let q_first = cs.fixed_column();
+        let q_last = cs.fixed_column();
+        let code_hash = cs.advice_column();
+        let index = cs.advice_column();
+        let wb_offset = cs.advice_column();
+        let opcode = cs.advice_column();
+        let operand = cs.advice_column();
+        let opcode_class = cs.advice_column();
+        let immediate_kind = cs.advice_column();
+        let imm_bytes = std::array::from_fn(|_| cs.advice_column());
+        let imm_lows = std::array::from_fn(|_| cs.advice_column());
+        let is_imm = std::array::from_fn(|_| cs.advice_column());
+        let block_level = cs.advice_column();
+        let block_id = cs.advice_column();
+        let block_end = cs.advice_column();
+        let is_func_start = cs.advice_column();
+        let target_opcode = cs.advice_column();
+        let target_block_level = cs.advice_column();
+        let target_block_id = cs.advice_column();
+        let target_block_end = cs.advice_column();
+        cs.enable_equality(code_hash);
+
+        let opcode_chips = TRACKED_OPCODES
+            .iter()
+            .map(|&tracked_opcode| {
+                let value_inv = cs.advice_column();
+                let config = IsZeroChip::configure(
+                    cs,
+                    |vc| vc.query_fixed(q_enable, Rotation::cur()),
+                    |vc| vc.query_advice(opcode, Rotation::cur()) - tracked_opcode.expr(),
+                    value_inv,
+                );
+                IsZeroChip::construct(config)
+            })
+            .collect::<Vec<_>>();
+        let config = LtChip::configure(
+            cs,
+            |vc| {
+                and::expr([
+                    vc.query_fixed(q_enable, Rotation::cur()),
+                    not::expr(vc.query_fixed(q_last, Rotation::cur())),
+                ])
+            },
+            |vc| vc.query_advice(wb_offset, Rotation::cur()),
+            |vc| vc.query_advice(wb_offset, Rotation::next()),
+        );
+        let wb_offset_lt_chip = LtChip::construct(config);
+        let config = LtChip::configure(
+            cs,
+            |vc| vc.query_fixed(q_enable, Rotation::cur()),
+            |vc| vc.query_advice(index, Rotation::cur()),
+            |vc| vc.query_advice(block_end, Rotation::cur()) + 1.expr(),
+        );
+        let block_end_lt_chip = LtChip::construct(config);
+        let config = LtChip::configure(
+            cs,
+            |vc| vc.query_fixed(q_enable, Rotation::cur()),
+            |vc| vc.query_advice(target_block_id, Rotation::cur()),
+            |vc| vc.query_advice(index, Rotation::cur()) + 1.expr(),
+        );
+        let target_block_id_lt_chip = LtChip::construct(config);
+        let config = LtChip::configure(
+            cs,
+            |vc| vc.query_fixed(q_enable, Rotation::cur()),
+            |vc| vc.query_advice(index, Rotation::cur()),
+            |vc| vc.query_advice(target_block_end, Rotation::cur()),
+        );
+        let target_block_end_lt_chip = LtChip::construct(config);
+
+        let is = |position: usize| opcode_chips[position].config().expr();
+
+        cs.create_gate("RwasmEquivalence gate", |vc| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_enable_expr = vc.query_fixed(q_enable, Rotation::cur());
+            let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
+            let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
+            let not_q_last_expr = not::expr(q_last_expr.clone());
+
+            let code_hash_expr = vc.query_advice(code_hash, Rotation::cur());
+            let index_expr = vc.query_advice(index, Rotation::cur());
+            let wb_offset_expr = vc.query_advice(wb_offset, Rotation::cur());
+            let immediate_kind_expr = vc.query_advice(immediate_kind, Rotation::cur());
+            let block_level_expr = vc.query_advice(block_level, Rotation::cur());
+            let block_id_expr = vc.query_advice(block_id, Rotation::cur());
+            let block_end_expr = vc.query_advice(block_end, Rotation::cur());
+            let is_func_start_expr = vc.query_advice(is_func_start, Rotation::cur());
+            let target_opcode_expr = vc.query_advice(target_opcode, Rotation::cur());
+            let target_block_level_expr = vc.query_advice(target_block_level, Rotation::cur());
+            let target_block_id_expr = vc.query_advice(target_block_id, Rotation::cur());
+            // a br label is the single byte of its immediate
+            let label_expr = vc.query_advice(imm_bytes[0], Rotation::cur());
+
+            let is_block_open_expr = is(BLOCK) + is(LOOP) + is(IF);
+            let block_level_after_expr =
+                block_level_expr.clone() + is_block_open_expr.clone() - is(END);
+
+            configure_constraints_for_q_first_and_q_last(
+                &mut cb,
+                vc,
+                &q_enable,
+                &q_first,
+                &[],
+                &q_last,
+                &[],
+            );
+
+            cb.require_boolean("is_func_start is boolean", is_func_start_expr.clone());
+            cb.condition(q_first_expr.clone(), |cb| {
+                cb.require_zero("q_first => index=0", index_expr.clone());
+                cb.require_equal(
+                    "q_first => is_func_start",
+                    is_func_start_expr.clone(),
+                    1.expr(),
+                );
+            });
+            cb.condition(is_func_start_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_func_start => block_level=1",
+                    block_level_expr.clone(),
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "is_func_start => block_id=index",
+                    block_id_expr.clone(),
+                    index_expr.clone(),
+                );
+            });
+
+            let is_imm_exprs = is_imm.map(|column| vc.query_advice(column, Rotation::cur()));
+            let imm_len_expr =
+                is_imm_exprs.iter().fold(0.expr(), |len, is_imm_expr| len + is_imm_expr.clone());
+            // immediate kinds are 0, 1 or 2 (opcode table), only 0 has no immediate
+            cb.require_equal(
+                "immediate follows the opcode iff the opcode has one",
+                is_imm_exprs[0].clone() * 2.expr(),
+                immediate_kind_expr.clone() * (3.expr() - immediate_kind_expr),
+            );
+            for (position, is_imm_expr) in is_imm_exprs.iter().enumerate() {
+                let is_imm_next_expr =
+                    is_imm_exprs.get(position + 1).cloned().unwrap_or_else(|| 0.expr());
+                cb.require_boolean("is_imm is boolean", is_imm_expr.clone());
+                cb.require_zero(
+                    "immediate bytes follow each other",
+                    is_imm_next_expr.clone() * not::expr(is_imm_expr.clone()),
+                );
+                cb.condition(is_imm_expr.clone(), |cb| {
+                    cb.require_equal(
+                        "immediate byte continues iff the next byte belongs to the immediate",
+                        vc.query_advice(imm_bytes[position], Rotation::cur()),
+                        vc.query_advice(imm_lows[position], Rotation::cur())
+                            + is_imm_next_expr * 0x80.expr(),
+                    );
+                });
+            }
+
+            cb.condition(is(END), |cb| {
+                cb.require_equal(
+                    "end closes the innermost block",
+                    index_expr.clone(),
+                    block_end_expr.clone(),
+                );
+            });
+            cb.require_equal(
+                "instruction is not past the end of its block",
+                block_end_lt_chip.config().is_lt(vc, None),
+                1.expr(),
+            );
+
+            let code_hash_next_expr = vc.query_advice(code_hash, Rotation::next());
+            let index_next_expr = vc.query_advice(index, Rotation::next());
+            let wb_offset_next_expr = vc.query_advice(wb_offset, Rotation::next());
+            let block_level_next_expr = vc.query_advice(block_level, Rotation::next());
+            let block_id_next_expr = vc.query_advice(block_id, Rotation::next());
+            let block_end_next_expr = vc.query_advice(block_end, Rotation::next());
+            let is_func_start_next_expr = vc.query_advice(is_func_start, Rotation::next());
+            let wb_offset_lt_expr = wb_offset_lt_chip.config().is_lt(vc, None);
+            cb.condition(not_q_last_expr.clone(), |cb| {
+                cb.require_equal(
+                    "code_hash is the same for every instruction",
+                    code_hash_next_expr,
+                    code_hash_expr.clone(),
+                );
+                cb.require_equal("index grows +1", index_next_expr, index_expr.clone() + 1.expr());
+                cb.require_equal("wb_offset grows", wb_offset_lt_expr, 1.expr());
+            });
+            cb.condition(
+                and::expr([not_q_last_expr.clone(), is_func_start_next_expr.clone()]),
+                |cb| {
+                    cb.require_zero(
+                        "function closes its blocks before the next one starts",
+                        block_level_after_expr.clone(),
+                    );
+                },
+            );
+            cb.condition(
+                and::expr([not_q_last_expr.clone(), not::expr(is_func_start_next_expr)]),
+                |cb| {
+                    cb.require_equal(
+                        "next instruction follows the immediate in the bytecode",
+                        wb_offset_next_expr,
+                        wb_offset_expr + 1.expr() + imm_len_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "block_level follows block opens and ends",
+                        block_level_next_expr,
+                        block_level_after_expr.clone(),
+                    );
+                    cb.condition(is_block_open_expr.clone(), |cb| {
+                        cb.require_equal(
+                            "block open => next instruction starts the block",
+                            block_id_next_expr.clone(),
+                            index_expr.clone() + 1.expr(),
+                        );
+                    });
+                    // the block after an end is looked up at the opener
+                    cb.condition(not::expr(is_block_open_expr.clone() + is(END)), |cb| {
+                        cb.require_equal(
+                            "block_id is the same inside a block",
+                            block_id_next_expr,
+                            block_id_expr.clone(),
+                        );
+                        cb.require_equal(
+                            "block_end is the same inside a block",
+                            block_end_next_expr,
+                            block_end_expr.clone(),
+                        );
+                    });
+                },
+            );
+            cb.condition(q_last_expr.clone(), |cb| {
+                cb.require_zero(
+                    "q_last => last function closes its blocks",
+                    block_level_after_expr.clone(),
+                );
+            });
+
+            let target_is =
+                |opcode: usize| target_opcode_expr.clone() - TRACKED_OPCODES[opcode].expr();
+            cb.condition(is(BR) + is(BR_IF), |cb| {
+                cb.require_equal("br label is a single byte", imm_len_expr.clone(), 1.expr());
+                cb.require_zero(
+                    "br target is an end or a loop",
+                    target_is(END) * target_is(LOOP),
+                );
+                cb.require_zero(
+                    "br to end => target block_level = block_level - label",
+                    (target_block_level_expr.clone() - block_level_expr.clone()
+                        + label_expr.clone())
+                        * target_is(LOOP),
+                );
+                cb.require_zero(
+                    "br to loop => target block_level = block_level - label - 1",
+                    (target_block_level_expr.clone() - block_level_expr.clone()
+                        + label_expr.clone()
+                        + 1.expr())
+                        * target_is(END),
+                );
+                // the only block at the target level starting before and ending after the br
+                cb.require_equal(
+                    "br target block starts at or before the br",
+                    target_block_id_lt_chip.config().is_lt(vc, None),
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "br target block ends after the br",
+                    target_block_end_lt_chip.config().is_lt(vc, None),
+                    1.expr(),
+                );
+            });
+            cb.condition(is(IF), |cb| {
+                cb.require_zero(
+                    "if target is its else or end",
+                    target_is(ELSE) * target_is(END),
+                );
+                cb.require_equal(
+                    "if target is inside the if",
+                    target_block_level_expr.clone(),
+                    block_level_expr.clone() + 1.expr(),
+                );
+                cb.require_equal(
+                    "if target belongs to the block of the if",
+                    target_block_id_expr.clone(),
+                    index_expr.clone() + 1.expr(),
+                );
+            });
+            cb.condition(is(ELSE), |cb| {
+                cb.require_zero("else target is an end", target_is(END));
+                cb.require_equal(
+                    "else target is at the else block_level",
+                    target_block_level_expr.clone(),
+                    block_level_expr.clone(),
+                );
+                cb.require_equal(
+                    "else target ends the block of the else",
+                    target_block_id_expr.clone(),
+                    block_id_expr.clone(),
+                );
+            });
+
+            cb.gate(q_enable_expr)
+        });
+
+        cs.lookup_any("rwasm opcode is the wasm bytecode opcode", |vc| {
+            let q_enable_expr = vc.query_fixed(q_enable, Rotation::cur());
+            vec![
+                (
+                    q_enable_expr.clone() * vc.query_advice(code_hash, Rotation::cur()),
+                    vc.query_advice(wb_table.code_hash, Rotation::cur()),
+                ),
+                (
+                    q_enable_expr.clone() * vc.query_advice(wb_offset, Rotation::cur()),
+                    vc.query_advice(wb_table.index, Rotation::cur()),
+                ),
+                (
+                    q_enable_expr * vc.query_advice(opcode, Rotation::cur()),
+                    vc.query_advice(wb_table.value, Rotation::cur()),
+                ),
+            ]
+        });
+
+        opcode_table.lookup_opcode(cs, "rwasm opcode is a supported instruction", |vc| {
+            (
+                vc.query_fixed(q_enable, Rotation::cur()),
+                [
+                    vc.query_advice(opcode_class, Rotation::cur()),
+                    vc.query_advice(opcode, Rotation::cur()),
+                    vc.query_advice(immediate_kind, Rotation::cur()),
+                ],
+            )
+        });
+
+        for position in 0..MAX_IMMEDIATE_BYTES {
+            cs.lookup_any("rwasm immediate byte is the wasm bytecode byte", |vc| {
+                let cond = vc.query_fixed(q_enable, Rotation::cur())
+                    * vc.query_advice(is_imm[position], Rotation::cur());
+                vec![
+                    (
+                        cond.clone() * vc.query_advice(code_hash, Rotation::cur()),
+                        vc.query_advice(wb_table.code_hash, Rotation::cur()),
+                    ),
+                    (
+                        cond.clone()
+                            * (vc.query_advice(wb_offset, Rotation::cur())
+                                + (position + 1).expr()),
+                        vc.query_advice(wb_table.index, Rotation::cur()),
+                    ),
+                    (
+                        cond * vc.query_advice(imm_bytes[position], Rotation::cur()),
+                        vc.query_advice(wb_table.value, Rotation::cur()),
+                    ),
+                ]
+            });
+            cs.lookup("rwasm immediate byte low bits are in range", |vc| {
+                vec![(
+                    vc.query_fixed(q_enable, Rotation::cur())
+                        * vc.query_advice(imm_lows[position], Rotation::cur()),
+                    range_table_config_0_128.value,
+                )]
+            });
+        }
+
+        cs.lookup_any("rwasm relocated operand points to the target", |vc| {
+            let q_enable_expr = vc.query_fixed(q_enable, Rotation::cur());
+            let cond = q_enable_expr.clone() * (is(BR) + is(BR_IF) + is(IF) + is(ELSE));
+            let target: [(Expression<F>, Column<Advice>); 4] = [
+                (vc.query_advice(code_hash, Rotation::cur()), code_hash),
+                (vc.query_advice(operand, Rotation::cur()), index),
+                (vc.query_advice(target_opcode, Rotation::cur()), opcode),
+                (vc.query_advice(target_block_level, Rotation::cur()), block_level),
+            ];
+            target
+                .into_iter()
+                .map(|(input, column)| {
+                    (
+                        cond.clone() * input,
+                        q_enable_expr.clone() * vc.query_advice(column, Rotation::cur()),
+                    )
+                })
+                .collect()
+        });
+
+        cs.lookup_any("rwasm relocated operand target block", |vc| {
+            let q_enable_expr = vc.query_fixed(q_enable, Rotation::cur());
+            let cond = q_enable_expr.clone() * (is(BR) + is(BR_IF) + is(IF) + is(ELSE));
+            // br targets are an end or a loop, a loop is followed by the first row of its body
+            let loop_target_expr = (TRACKED_OPCODES[END].expr()
+                - vc.query_advice(target_opcode, Rotation::cur()))
+                * Expression::Constant(
+                    F::from((TRACKED_OPCODES[END] - TRACKED_OPCODES[LOOP]) as u64)
+                        .invert()
+                        .unwrap(),
+                );
+            let target_row_expr = vc.query_advice(operand, Rotation::cur())
+                + (is(BR) + is(BR_IF)) * loop_target_expr;
+            let target: [(Expression<F>, Column<Advice>); 4] = [
+                (vc.query_advice(code_hash, Rotation::cur()), code_hash),
+                (target_row_expr, index),
+                (vc.query_advice(target_block_id, Rotation::cur()), block_id),
+                (vc.query_advice(target_block_end, Rotation::cur()), block_end),
+            ];
+            target
+                .into_iter()
+                .map(|(input, column)| {
+                    (
+                        cond.clone() * input,
+                        q_enable_expr.clone() * vc.query_advice(column, Rotation::cur()),
+                    )
+                })
+                .collect()
+        });
+
+        cs.lookup_any("rwasm block after an end is the block of its opener", |vc| {
+            let q_enable_expr = vc.query_fixed(q_enable, Rotation::cur());
+            let cond = q_enable_expr.clone()
+                * not::expr(vc.query_fixed(q_last, Rotation::cur()))
+                * not::expr(vc.query_advice(is_func_start, Rotation::next()))
+                * is(END);
+            let target: [(Expression<F>, Column<Advice>); 4] = [
+                (vc.query_advice(code_hash, Rotation::cur()), code_hash),
+                (vc.query_advice(block_id, Rotation::cur()) - 1.expr(), index),
+                (vc.query_advice(block_id, Rotation::next()), block_id),
+                (vc.query_advice(block_end, Rotation::next()), block_end),
+            ];
+            target
+                .into_iter()
+                .map(|(input, column)| {
+                    (
+                        cond.clone() * input,
+                        q_enable_expr.clone() * vc.query_advice(column, Rotation::cur()),
+                    )
+                })
+                .collect()
+        });
+
+        cs.lookup_any("rwasm else is the target of its if", |vc| {
+            let q_enable_expr = vc.query_fixed(q_enable, Rotation::cur());
+            let cond = q_enable_expr.clone() * is(ELSE);
+            let target: [(Expression<F>, Column<Advice>); 4] = [
+                (vc.query_advice(code_hash, Rotation::cur()), code_hash),
+                (vc.query_advice(block_id, Rotation::cur()) - 1.expr(), index),
+                (TRACKED_OPCODES[IF].expr(), opcode),
+                (vc.query_advice(index, Rotation::cur()), operand),
+            ];
+            target
+                .into_iter()
+                .map(|(input, column)| {
+                    (
+                        cond.clone() * input,
+                        q_enable_expr.clone() * vc.query_advice(column, Rotation::cur()),
+                    )
+                })
+                .collect()
+        });
+
+        RwasmEquivalenceConfig {
+            q_enable,
+            q_first,
+            q_last,
+            code_hash,
+            index,
+            wb_offset,
+            opcode,
+            operand,
+            opcode_class,
+            immediate_kind,
+            imm_bytes,
+            imm_lows,
+            is_imm,
+            block_level,
+            block_id,
+            block_end,
+            is_func_start,
+            target_opcode,
+            target_block_level,
+            target_block_id,
+            target_block_end,
+            opcode_chips,
+            wb_offset_lt_chip,
+            block_end_lt_chip,
+            target_block_id_lt_chip,
+            target_block_end_lt_chip,
+            _marker: PhantomData,
+        }
+    }
+
+    fn assign_advice(
+        &self,
+        region: &mut Region<F>,
+        name: &str,
+        column: Column<Advice>,
+        assign_offset: usize,
+        value: F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        region
+            .assign_advice(
+                || format!("assign '{}' val {:?} at {}", name, value, assign_offset),
+                column,
+                assign_offset,
+                || Value::known(value),
+            )
+            .map_err(remap_error_to_assign_at(assign_offset))
+    }
+
+    fn assign_fixed(
+        &self,
+        region: &mut Region<F>,
+        name: &str,
+        column: Column<Fixed>,
+        assign_offset: usize,
+    ) -> Result<(), Error> {
+        region
+            .assign_fixed(
+                || format!("assign '{}' val 1 at {}", name, assign_offset),
+                column,
+                assign_offset,
+                || Value::known(F::one()),
+            )
+            .map_err(remap_error_to_assign_at(assign_offset))?;
+        Ok(())
+    }
+
+    /// Assigns a row per instruction of `flattened` starting at `assign_delta`, returns the
+    /// code hash cell of the first row
+    pub fn assign(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        flattened: &FlattenedModule,
+        assign_delta: AssignDeltaType,
+    ) -> Result<Option<AssignedCell<F, F>>, Error> {
+        let code = &flattened.module.code;
+        if flattened.wb_offsets.len() != code.len() {
+            return Err(Error::FatalInvalidArgumentValue(format!(
+                "rwasm: {} bytecode offsets for {} instructions",
+                flattened.wb_offsets.len(),
+                code.len()
+            )));
+        }
+        let code_hash = wb.code_hash.to_scalar().ok_or_else(|| {
+            Error::FatalInvalidArgumentValue("rwasm: code hash is not a field element".to_string())
+        })?;
+
+        // (block level, block id) of every instruction and the end of every block id
+        let mut blocks = Vec::with_capacity(code.len());
+        let mut block_ends = HashMap::new();
+        let mut open_blocks = vec![];
+        for (index, instr) in code.iter().enumerate() {
+            if flattened.module.func_entries.contains(&(index as u32)) {
+                open_blocks = vec![index];
+            }
+            let block_id = open_blocks.last().copied().unwrap_or_default();
+            blocks.push((open_blocks.len() as u64, block_id));
+            if TRACKED_OPCODES[BLOCK..=IF].contains(&instr.opcode) {
+                open_blocks.push(index + 1);
+            } else if instr.opcode == WASM_BLOCK_END {
+                open_blocks.pop();
+                block_ends.insert(block_id, index);
+            }
+        }
+        let block_end_of = |block_id: usize| block_ends.get(&block_id).copied().unwrap_or_default();
+
+        let mut code_hash_cell = None;
+        for (index, (instr, &wb_offset)) in code.iter().zip(&flattened.wb_offsets).enumerate() {
+            let assign_offset = assign_delta + index;
+            debug!(
+                "assign at {} rwasm instruction {} opcode {:#x} operand {} wb_offset {}",
+                assign_offset, index, instr.opcode, instr.operand, wb_offset,
+            );
+            self.assign_fixed(region, "q_enable", self.config.q_enable, assign_offset)?;
+            if index == 0 {
+                self.assign_fixed(region, "q_first", self.config.q_first, assign_offset)?;
+            }
+            if index + 1 == code.len() {
+                self.assign_fixed(region, "q_last", self.config.q_last, assign_offset)?;
+            }
+
+            let cell = self.assign_advice(
+                region,
+                "code_hash",
+                self.config.code_hash,
+                assign_offset,
+                code_hash,
+            )?;
+            code_hash_cell.get_or_insert(cell);
+            let is_func_start = flattened.module.func_entries.contains(&(index as u32));
+            let is_br = [BR, BR_IF]
+                .iter()
+                .any(|&position| TRACKED_OPCODES[position] == instr.opcode);
+            let is_relocated = is_br
+                || [IF, ELSE]
+                    .iter()
+                    .any(|&position| TRACKED_OPCODES[position] == instr.opcode);
+            let (opcode_class, immediate_kind) = opcode_rows()
+                .into_iter()
+                .find(|(_, opcode, _)| *opcode == instr.opcode)
+                .map(|(class, _, kind)| (class, kind))
+                .unwrap_or((OpcodeClass::Disabled, ImmediateKind::None));
+            let mut immediate = vec![];
+            if immediate_kind != ImmediateKind::None {
+                for imm_offset in wb_offset + 1..=wb_offset + MAX_IMMEDIATE_BYTES {
+                    let byte = *wb
+                        .get(imm_offset)
+                        .ok_or_else(|| error_index_out_of_bounds(imm_offset))?;
+                    immediate.push(byte);
+                    if byte & 0x80 == 0 {
+                        break;
+                    }
+                }
+            }
+            let (block_level, block_id) = blocks[index];
+            let (target_opcode, target_block_level, target_block_id) = if is_relocated {
+                let target = instr.operand as usize;
+                let target_opcode = code
+                    .get(target)
+                    .ok_or_else(|| error_index_out_of_bounds(assign_offset))?
+                    .opcode;
+                let target_row = if is_br && target_opcode == TRACKED_OPCODES[LOOP] {
+                    target + 1
+                } else {
+                    target
+                };
+                let &(_, target_block_id) = blocks
+                    .get(target_row)
+                    .ok_or_else(|| error_index_out_of_bounds(assign_offset))?;
+                (target_opcode, blocks[target].0, target_block_id)
+            } else {
+                (0, 0, 0)
+            };
+            let target_block_end = if is_relocated {
+                block_end_of(target_block_id)
+            } else {
+                0
+            };
+            for (name, column, value) in [
+                ("index", self.config.index, index as u64),
+                ("wb_offset", self.config.wb_offset, wb_offset as u64),
+                ("opcode", self.config.opcode, instr.opcode as u64),
+                ("operand", self.config.operand, instr.operand),
+                ("opcode_class", self.config.opcode_class, opcode_class as u64),
+                ("immediate_kind", self.config.immediate_kind, immediate_kind as u64),
+                ("block_level", self.config.block_level, block_level),
+                ("block_id", self.config.block_id, block_id as u64),
+                ("block_end", self.config.block_end, block_end_of(block_id) as u64),
+                ("is_func_start", self.config.is_func_start, is_func_start as u64),
+                ("target_opcode", self.config.target_opcode, target_opcode as u64),
+                (
+                    "target_block_level",
+                    self.config.target_block_level,
+                    target_block_level,
+                ),
+                (
+                    "target_block_id",
+                    self.config.target_block_id,
+                    target_block_id as u64,
+                ),
+                (
+                    "target_block_end",
+                    self.config.target_block_end,
+                    target_block_end as u64,
+                ),
+            ] {
+                self.assign_advice(region, name, column, assign_offset, F::from(value))?;
+            }
+            for (position, ((&imm_byte, &imm_low), &is_imm)) in self
+                .config
+                .imm_bytes
+                .iter()
+                .zip(&self.config.imm_lows)
+                .zip(&self.config.is_imm)
+                .enumerate()
+            {
+                let byte = immediate.get(position).copied().unwrap_or_default() as u64;
+                for (name, column, value) in [
+                    ("imm_byte", imm_byte, byte),
+                    ("imm_low", imm_low, byte & 0x7f),
+                    ("is_imm", is_imm, (position < immediate.len()) as u64),
+                ] {
+                    self.assign_advice(region, name, column, assign_offset, F::from(value))?;
+                }
+            }
+
+            for (chip, &tracked_opcode) in self.config.opcode_chips.iter().zip(&TRACKED_OPCODES) {
+                chip.assign(
+                    region,
+                    assign_offset,
+                    Value::known(F::from(instr.opcode as u64) - F::from(tracked_opcode as u64)),
+                )
+                .map_err(remap_error(Error::FatalAssignExternalChip))?;
+            }
+            if let Some(&wb_offset_next) = flattened.wb_offsets.get(index + 1) {
+                self.config
+                    .wb_offset_lt_chip
+                    .assign(
+                        region,
+                        assign_offset,
+                        F::from(wb_offset as u64),
+                        F::from(wb_offset_next as u64),
+                    )
+                    .map_err(remap_error(Error::FatalAssignExternalChip))?;
+            }
+            for (chip, lhs, rhs) in [
+                (&self.config.block_end_lt_chip, index, block_end_of(block_id) + 1),
+                (&self.config.target_block_id_lt_chip, target_block_id, index + 1),
+                (&self.config.target_block_end_lt_chip, index, target_block_end),
+            ] {
+                chip.assign(region, assign_offset, F::from(lhs as u64), F::from(rhs as u64))
+                    .map_err(remap_error(Error::FatalAssignExternalChip))?;
+            }
+        }
+        Ok(code_hash_cell)
+    }
+}
+
+/// Proves a flattened module is a translation of the wasm bytecode whose code hash is the only
+/// instance value
+#[derive(Clone, Debug, Default)]
+pub struct RwasmEquivalenceCircuit<F> {
+    pub wb: Option<WasmBytecode>,
+    pub flattened: FlattenedModule,
+    _marker: PhantomData<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RwasmEquivalenceCircuitConfig<F: Field> {
+    pub wb_table: WasmBytecodeTable,
+    pub opcode_table: OpcodeTableConfig<F>,
+    pub range_table_config_0_128: RangeTableConfig<F, 0, 128>,
+    pub chip: RwasmEquivalenceChip<F>,
+    pub instance: Column<Instance>,
+}
+
+impl<F: Field> RwasmEquivalenceCircuit<F> {
+    /// Circuit of `wb` and its translation by [`flatten`]
+    pub fn new(wb: WasmBytecode) -> Result<Self, Error> {
+        Ok(Self {
+            flattened: flatten(&wb.bytes)?,
+            wb: Some(wb),
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn instance(&self) -> Vec<Vec<F>> {
+        let code_hash = self.wb.as_ref().and_then(|wb| wb.code_hash.to_scalar());
+        vec![code_hash.into_iter().collect()]
+    }
+}
+
+impl<F: Field> Circuit<F> for RwasmEquivalenceCircuit<F> {
+    type Config = RwasmEquivalenceCircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+        let wb_table = WasmBytecodeTable::construct(cs, false);
+        let opcode_table = OpcodeTableConfig::configure(cs);
+        let range_table_config_0_128 = RangeTableConfig::configure(cs);
+        let config = RwasmEquivalenceChip::configure(
+            cs,
+            &wb_table,
+            &opcode_table,
+            &range_table_config_0_128,
+        );
+        let instance = cs.instance_column();
+        cs.enable_equality(instance);
+        RwasmEquivalenceCircuitConfig {
+            wb_table,
+            opcode_table,
+            range_table_config_0_128,
+            chip: RwasmEquivalenceChip::construct(config),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), halo2_proofs::plonk::Error> {
+        config.opcode_table.load(&mut layouter)?;
+        config.range_table_config_0_128.load(&mut layouter)?;
+        let wb = match &self.wb {
+            Some(wb) => wb,
+            None => return Ok(()),
+        };
+        let code_hash_cell = layouter.assign_region(
+            || "rwasm equivalence region",
+            |mut region| {
+                config.wb_table.load(&mut region, wb, 0)?;
+                config
+                    .chip
+                    .assign(&mut region, wb, &self.flattened, 0)
+                    .map_err(|_| halo2_proofs::plonk::Error::Synthesis)
+            },
+        )?;
+        if let Some(cell) = code_hash_cell {
+            layouter.constrain_instance(cell.cell(), config.instance, 0)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use wasmparser::{Operator, Parser, Payload};
+
+use crate::wasm_circuit::{
+    error::Error,
+    rwasm::types::{RwasmInstruction, RwasmModule},
+};
+
+/// Flattened module together with the bytecode offset of the opcode every instruction was
+/// translated from
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FlattenedModule {
+    pub module: RwasmModule,
+    pub wb_offsets: Vec<usize>,
+}
+
+fn unsupported(msg: &str, offset: usize) -> Error {
+    Error::FatalUnsupportedValue(format!("rwasm flattener: {} at {}", msg, offset))
+}
+
+fn decode_error(e: impl std::fmt::Display) -> Error {
+    Error::FatalInvalidArgumentValue(format!("rwasm flattener: {}", e))
+}
+
+/// Instruction index of the `else` (if any) and of the `end` of the block opened at every
+/// index, indexes are relative to the function
+fn block_ends(ops: &[(usize, Operator)]) -> Vec<(Option<usize>, usize)> {
+    let mut ends = vec![(None, 0); ops.len()];
+    let mut opened: Vec<usize> = vec![];
+    for (index, (_, op)) in ops.iter().enumerate() {
+        match op {
+            Operator::Block { .. } | Operator::Loop { .. } | Operator::If { .. } => {
+                opened.push(index)
+            }
+            Operator::Else => {
+                if let Some(&start) = opened.last() {
+                    ends[start].0 = Some(index);
+                }
+            }
+            Operator::End => {
+                if let Some(start) = opened.pop() {
+                    ends[start].1 = index;
+                }
+            }
+            _ => {}
+        }
+    }
+    ends
+}
+
+/// Instruction index a branch of `depth` continues at
+fn branch_target(
+    ops: &[(usize, Operator)],
+    ends: &[(Option<usize>, usize)],
+    labels: &[Option<usize>],
+    depth: u32,
+    offset: usize,
+) -> Result<usize, Error> {
+    let label = labels
+        .len()
+        .checked_sub(depth as usize + 1)
+        .map(|position| labels[position])
+        .ok_or_else(|| unsupported("branch depth above the open blocks", offset))?;
+    Ok(match label {
+        Some(start) if matches!(ops[start].1, Operator::Loop { .. }) => start,
+        Some(start) => ends[start].1,
+        None => ops.len() - 1,
+    })
+}
+
+/// Translates a function body (operators with their bytecode offsets) starting at instruction
+/// `entry` of the flattened stream
+fn flatten_function(
+    bytes: &[u8],
+    ops: &[(usize, Operator)],
+    entry: usize,
+) -> Result<Vec<RwasmInstruction>, Error> {
+    let ends = block_ends(ops);
+    // opener index of every open block, `None` for the function body
+    let mut labels: Vec<Option<usize>> = vec![None];
+    let mut code = Vec::with_capacity(ops.len());
+    for (index, (offset, op)) in ops.iter().enumerate() {
+        let opcode = bytes[*offset];
+        let operand = match op {
+            Operator::Block { .. } | Operator::Loop { .. } => {
+                labels.push(Some(index));
+                0
+            }
+            Operator::If { .. } => {
+                labels.push(Some(index));
+                let (else_index, end_index) = ends[index];
+                (entry + else_index.unwrap_or(end_index)) as u64
+            }
+            Operator::Else => {
+                let start = labels
+                    .last()
+                    .copied()
+                    .flatten()
+                    .ok_or_else(|| unsupported("else outside of if", *offset))?;
+                (entry + ends[start].1) as u64
+            }
+            Operator::End => {
+                labels.pop();
+                0
+            }
+            Operator::Br { relative_depth } | Operator::BrIf { relative_depth } => {
+                // labels are decoded from a single leb byte by the equivalence circuit
+                if *relative_depth >= 0x80 {
+                    return Err(unsupported("branch depth above 127", *offset));
+                }
+                (entry + branch_target(ops, &ends, &labels, *relative_depth, *offset)?) as u64
+            }
+            Operator::BrTable { .. } => return Err(unsupported("br_table", *offset)),
+            Operator::I32Const { value } => *value as u32 as u64,
+            Operator::I64Const { value } => *value as u64,
+            Operator::LocalGet { local_index }
+            | Operator::LocalSet { local_index }
+            | Operator::LocalTee { local_index } => *local_index as u64,
+            Operator::GlobalGet { global_index } | Operator::GlobalSet { global_index } => {
+                *global_index as u64
+            }
+            Operator::Call { function_index } => *function_index as u64,
+            _ if opcode >= 0xFC => return Err(unsupported("prefixed instruction", *offset)),
+            _ => 0,
+        };
+        code.push(RwasmInstruction::new(opcode, operand));
+    }
+    Ok(code)
+}
+
+/// Flattens the functions of `bytes` into a single instruction stream. Every wasm instruction
+/// becomes one instruction with the same opcode, branch operands are relocated:
+///
+/// - `br`/`br_if`: index of the `end` of the target block, of the `loop` itself for loops
+/// - `if`: index of its `else`, of its `end` without `else`
+/// - `else`: index of the `end` of the `if`
+///
+/// Other operands carry the const value or the local/global/function index, zero otherwise.
+/// Memory is left empty.
+pub fn flatten(bytes: &[u8]) -> Result<FlattenedModule, Error> {
+    let mut flattened = FlattenedModule::default();
+    for payload in Parser::new(0).parse_all(bytes) {
+        if let Payload::CodeSectionEntry(body) = payload.map_err(decode_error)? {
+            let mut reader = body.get_operators_reader().map_err(decode_error)?;
+            let mut ops = vec![];
+            while !reader.eof() {
+                let (op, offset) = reader.read_with_offset().map_err(decode_error)?;
+                ops.push((offset, op));
+            }
+            let entry = flattened.module.code.len();
+            flattened.module.func_entries.push(entry as u32);
+            flattened
+                .module
+                .code
+                .extend(flatten_function(bytes, &ops, entry)?);
+            flattened.wb_offsets.extend(ops.iter().map(|(offset, _)| *offset));
+        }
+    }
+    Ok(flattened)
+}
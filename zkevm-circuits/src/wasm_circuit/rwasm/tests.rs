@@ -0,0 +1,233 @@
+#[cfg(test)]
+mod rwasm_tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use wasm_encoder::{
+        BlockType, CodeSection, Function, FunctionSection, Instruction, Module, TypeSection,
+        ValType,
+    };
+
+    use crate::wasm_circuit::{
+        bytecode::bytecode::WasmBytecode,
+        circuit::WasmCircuit,
+        rwasm::{
+            circuit::RwasmEquivalenceCircuit,
+            flattener::flatten,
+            translator::{translate, wasm_bytecode_from_rwasm},
+            types::{RwasmInstruction, RwasmModule},
+        },
+        types::{ControlInstruction, NumericInstruction, ParametricInstruction, VariableInstruction},
+    };
+
+    fn module() -> RwasmModule {
+        let instr = RwasmInstruction::new;
+        RwasmModule {
+            code: vec![
+                instr(NumericInstruction::I32Const as u8, (-7i32) as u32 as u64),
+                instr(NumericInstruction::I64Const as u8, u64::MAX),
+                instr(ParametricInstruction::Drop as u8, 0),
+                instr(ParametricInstruction::Drop as u8, 0),
+                instr(ControlInstruction::Call as u8, 1),
+                instr(NumericInstruction::I32Const as u8, 300),
+                instr(NumericInstruction::I32Const as u8, 5),
+                instr(NumericInstruction::I32Add as u8, 0),
+                instr(ParametricInstruction::Drop as u8, 0),
+            ],
+            memory: b"rwasm memory".to_vec(),
+            func_entries: vec![0, 5],
+        }
+    }
+
+    #[test]
+    pub fn parse_roundtrip() {
+        let module = module();
+        assert_eq!(RwasmModule::parse(&module.to_bytes()).unwrap(), module);
+        let funcs = module.funcs().map(|f| f.len()).collect::<Vec<_>>();
+        assert_eq!(funcs, vec![5, 4]);
+    }
+
+    #[test]
+    pub fn parse_rejects_malformed() {
+        let bytes = module().to_bytes();
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = 0;
+        assert!(RwasmModule::parse(&bad_magic).is_err());
+        assert!(RwasmModule::parse(&bytes[..bytes.len() - 1]).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(RwasmModule::parse(&trailing).is_err());
+        let mut unordered = module();
+        unordered.func_entries = vec![0, 5, 5];
+        assert!(RwasmModule::parse(&unordered.to_bytes()).is_err());
+    }
+
+    #[test]
+    pub fn variable_instruction_operand_is_leb() {
+        let mut module = module();
+        module.code[0] = RwasmInstruction::new(VariableInstruction::LocalGet as u8, 200);
+        let bytes = translate(&module).unwrap();
+        let local_get = [VariableInstruction::LocalGet as u8, 0xc8, 0x01];
+        assert!(bytes.windows(3).any(|w| w == local_get));
+    }
+
+    #[test]
+    pub fn branches_are_not_translated() {
+        let mut module = module();
+        module.code[2] = RwasmInstruction::new(ControlInstruction::BrIf as u8, 2);
+        assert!(translate(&module).is_err());
+    }
+
+    #[test]
+    pub fn translated_module_ok() {
+        let module = module();
+        let bytes = translate(&module).unwrap();
+        wasmparser::validate(&bytes).unwrap();
+
+        let wb = wasm_bytecode_from_rwasm(&module.to_bytes()).unwrap();
+        assert_eq!(wb.bytes, bytes);
+        let circuit = WasmCircuit::<Fr>::new(vec![wb]);
+        let outputs = circuit.compute_module_outputs(9).unwrap();
+        assert_eq!(outputs[0].error_code, 0);
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+        let prover = MockProver::run(9, &circuit, instance).unwrap();
+        prover.assert_satisfied();
+    }
+
+    /// Two functions, the first with nested blocks and branches out of an `if` and to a loop
+    fn branching_module() -> Vec<u8> {
+        let mut types = TypeSection::new();
+        types.function(vec![ValType::I32], vec![]);
+        let mut functions = FunctionSection::new();
+        functions.function(0);
+        functions.function(0);
+        let mut codes = CodeSection::new();
+        for body in [
+            vec![
+                Instruction::Block(BlockType::Empty),
+                Instruction::Loop(BlockType::Empty),
+                Instruction::LocalGet(0),
+                Instruction::BrIf(1),
+                Instruction::LocalGet(0),
+                Instruction::If(BlockType::Empty),
+                Instruction::Br(2),
+                Instruction::Else,
+                Instruction::Nop,
+                Instruction::End,
+                Instruction::Br(0),
+                Instruction::End,
+                Instruction::End,
+                Instruction::End,
+            ],
+            vec![
+                Instruction::I32Const(1),
+                Instruction::If(BlockType::Empty),
+                Instruction::Nop,
+                Instruction::End,
+                Instruction::End,
+            ],
+        ] {
+            let mut f = Function::new(vec![]);
+            body.iter().for_each(|instruction| {
+                f.instruction(instruction);
+            });
+            codes.function(&f);
+        }
+        let mut module = Module::new();
+        module.section(&types);
+        module.section(&functions);
+        module.section(&codes);
+        module.finish()
+    }
+
+    fn equivalence_circuit() -> RwasmEquivalenceCircuit<Fr> {
+        RwasmEquivalenceCircuit::new(WasmBytecode::new(branching_module())).unwrap()
+    }
+
+    fn equivalence_verifies(circuit: &RwasmEquivalenceCircuit<Fr>) -> bool {
+        MockProver::run(9, circuit, circuit.instance())
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    pub fn flatten_relocates_branch_targets() {
+        let bytes = branching_module();
+        wasmparser::validate(&bytes).unwrap();
+        let flattened = flatten(&bytes).unwrap();
+        assert_eq!(flattened.module.func_entries, vec![0, 14]);
+        for (instr, wb_offset) in flattened.module.code.iter().zip(&flattened.wb_offsets) {
+            assert_eq!(instr.opcode, bytes[*wb_offset]);
+        }
+        let operands = flattened
+            .module
+            .code
+            .iter()
+            .map(|instr| instr.operand)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            operands,
+            vec![0, 0, 0, 12, 0, 7, 12, 9, 0, 0, 1, 0, 0, 0, 1, 17, 0, 0, 0]
+        );
+        assert_eq!(
+            RwasmModule::parse(&flattened.module.to_bytes()).unwrap(),
+            flattened.module
+        );
+    }
+
+    #[test]
+    pub fn equivalence_ok() {
+        let circuit = equivalence_circuit();
+        let prover = MockProver::run(9, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn equivalence_changed_opcode_fails() {
+        let mut circuit = equivalence_circuit();
+        // nop -> unreachable
+        circuit.flattened.module.code[8].opcode = ControlInstruction::Unreachable as u8;
+        assert!(!equivalence_verifies(&circuit));
+    }
+
+    #[test]
+    pub fn equivalence_wrong_branch_target_fails() {
+        let mut circuit = equivalence_circuit();
+        // `br 2` pointed to the end of the loop instead of the end of the block
+        circuit.flattened.module.code[6].operand = 11;
+        assert!(!equivalence_verifies(&circuit));
+
+        let mut circuit = equivalence_circuit();
+        // `br 0` pointed to the block instead of the loop
+        circuit.flattened.module.code[10].operand = 0;
+        assert!(!equivalence_verifies(&circuit));
+
+        let mut circuit = equivalence_circuit();
+        // `br 2` pointed to the end at the same block level in the second function
+        circuit.flattened.module.code[6].operand = 17;
+        assert!(!equivalence_verifies(&circuit));
+
+        let mut circuit = equivalence_circuit();
+        // `if` pointed past its `else` to its `end`
+        circuit.flattened.module.code[5].operand = 9;
+        assert!(!equivalence_verifies(&circuit));
+    }
+
+    #[test]
+    pub fn equivalence_skipped_instruction_fails() {
+        let mut circuit = equivalence_circuit();
+        // the `nop` of the second function dropped, the `if` relocated to the `end` left
+        circuit.flattened.module.code.remove(16);
+        circuit.flattened.wb_offsets.remove(16);
+        circuit.flattened.module.code[15].operand = 16;
+        assert!(!equivalence_verifies(&circuit));
+    }
+
+    #[test]
+    pub fn equivalence_other_code_hash_fails() {
+        let circuit = equivalence_circuit();
+        let mut instance = circuit.instance();
+        instance[0][0] += Fr::from(1);
+        let prover = MockProver::run(9, &circuit, instance).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::wasm_circuit::{
+    bytecode::bytecode::WasmBytecode,
+    consts::{WASM_BLOCK_END, WASM_MAGIC_PREFIX, WASM_VERSION_PREFIX},
+    error::Error,
+    leb128::helpers::leb128_encode,
+    rwasm::types::{RwasmInstruction, RwasmModule},
+    sections::r#type::item::consts::Type,
+    types::{
+        ControlInstruction, LimitType, MemSegmentType, NumericInstruction, ParametricInstruction,
+        VariableInstruction, WasmSection,
+    },
+};
+
+pub const WASM_PAGE_SIZE: usize = 0x10000;
+
+fn unsupported(instr: &RwasmInstruction, index: usize) -> Error {
+    Error::FatalUnsupportedValue(format!(
+        "rwasm: opcode {:#x} at instruction {} has no canonical wasm translation",
+        instr.opcode, index
+    ))
+}
+
+fn leb_unsigned(value: u64) -> Result<Vec<u8>, Error> {
+    leb128_encode(false, value as i128)
+}
+
+fn section(section: WasmSection, body: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![section as u8];
+    bytes.extend(leb_unsigned(body.len() as u64)?);
+    bytes.extend(body);
+    Ok(bytes)
+}
+
+fn translate_instruction(instr: &RwasmInstruction, index: usize) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![instr.opcode];
+    if let Ok(opcode) = NumericInstruction::try_from(instr.opcode) {
+        match opcode {
+            NumericInstruction::I32Const => {
+                bytes.extend(leb128_encode(true, instr.operand as u32 as i32 as i128)?)
+            }
+            NumericInstruction::I64Const => {
+                bytes.extend(leb128_encode(true, instr.operand as i64 as i128)?)
+            }
+            _ => {}
+        }
+        return Ok(bytes);
+    }
+    if VariableInstruction::try_from(instr.opcode).is_ok() {
+        bytes.extend(leb_unsigned(instr.operand as u32 as u64)?);
+        return Ok(bytes);
+    }
+    if ParametricInstruction::try_from(instr.opcode).is_ok() {
+        return Ok(bytes);
+    }
+    // branches carry the instruction index of their target in the flattened stream (see
+    // `flatten`), they have no structured (block-label) counterpart, so only calls are
+    // translated
+    match ControlInstruction::try_from(instr.opcode) {
+        Ok(ControlInstruction::Call) => {
+            bytes.extend(leb_unsigned(instr.operand as u32 as u64)?);
+            Ok(bytes)
+        }
+        Ok(ControlInstruction::Unreachable) => Ok(bytes),
+        _ => Err(unsupported(instr, index)),
+    }
+}
+
+/// Translates a flattened module into canonical wasm processed by the section chips. Every
+/// function gets the `[] -> []` type, memory is placed by a single active data segment at
+/// address 0. The translation happens during witness generation and is not constrained by the
+/// circuit: it must be replayed by the verifier side to bind the proven wasm to the rwasm
+/// artifact.
+pub fn translate(module: &RwasmModule) -> Result<Vec<u8>, Error> {
+    let funcs_count = module.func_entries.len() as u64;
+
+    let mut bytes = WASM_MAGIC_PREFIX.as_bytes().to_vec();
+    bytes.extend(WASM_VERSION_PREFIX.chars().map(|ch| ch.to_digit(10).unwrap() as u8));
+    if funcs_count > 0 {
+        let mut body = leb_unsigned(1)?;
+        body.extend([Type::FuncType as u8, 0, 0]);
+        bytes.extend(section(WasmSection::Type, body)?);
+
+        let mut body = leb_unsigned(funcs_count)?;
+        body.extend(std::iter::repeat(0).take(funcs_count as usize));
+        bytes.extend(section(WasmSection::Function, body)?);
+    }
+    if !module.memory.is_empty() {
+        let pages = (module.memory.len() + WASM_PAGE_SIZE - 1) / WASM_PAGE_SIZE;
+        let mut body = leb_unsigned(1)?;
+        body.push(LimitType::MinOnly as u8);
+        body.extend(leb_unsigned(pages as u64)?);
+        bytes.extend(section(WasmSection::Memory, body)?);
+    }
+    if funcs_count > 0 {
+        let mut body = leb_unsigned(funcs_count)?;
+        let mut index = 0;
+        for func in module.funcs() {
+            // no locals
+            let mut func_body = leb_unsigned(0)?;
+            for instr in func {
+                func_body.extend(translate_instruction(instr, index)?);
+                index += 1;
+            }
+            func_body.push(WASM_BLOCK_END);
+            body.extend(leb_unsigned(func_body.len() as u64)?);
+            body.extend(func_body);
+        }
+        bytes.extend(section(WasmSection::Code, body)?);
+    }
+    if !module.memory.is_empty() {
+        let mut body = leb_unsigned(1)?;
+        body.push(MemSegmentType::Active as u8);
+        body.extend([NumericInstruction::I32Const as u8, 0, WASM_BLOCK_END]);
+        body.extend(leb_unsigned(module.memory.len() as u64)?);
+        body.extend(&module.memory);
+        bytes.extend(section(WasmSection::Data, body)?);
+    }
+
+    Ok(bytes)
+}
+
+/// Bytecode of a flattened (rwasm) module translated into canonical wasm, see [`translate`]
+pub fn wasm_bytecode_from_rwasm(bytes: &[u8]) -> Result<WasmBytecode, Error> {
+    Ok(WasmBytecode::new(translate(&RwasmModule::parse(bytes)?)?))
+}
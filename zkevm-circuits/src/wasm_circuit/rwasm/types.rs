@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+
+use crate::wasm_circuit::error::Error;
+
+pub const RWASM_MAGIC: [u8; 2] = [0xef, 0x52];
+pub const RWASM_VERSION: u8 = 0x01;
+/// opcode byte followed by a little-endian u64 operand (zero for opcodes without one)
+pub const RWASM_INSTRUCTION_LEN: usize = 9;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RwasmInstruction {
+    pub opcode: u8,
+    pub operand: u64,
+}
+
+impl RwasmInstruction {
+    pub fn new(opcode: u8, operand: u64) -> Self {
+        Self { opcode, operand }
+    }
+}
+
+/// Flattened module: a single instruction stream split into functions by entry indexes and the
+/// initial linear memory. Layout (all integers little-endian):
+///
+/// ```text
+/// magic(2) version(1)
+/// code_len(u32)   instructions, RWASM_INSTRUCTION_LEN bytes each
+/// memory_len(u32) memory bytes, loaded at address 0
+/// funcs_count(u32) entry instruction index(u32) of every function, ascending, first is 0
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RwasmModule {
+    pub code: Vec<RwasmInstruction>,
+    pub memory: Vec<u8>,
+    pub func_entries: Vec<u32>,
+}
+
+fn invalid(msg: &str, offset: usize) -> Error {
+    Error::FatalInvalidArgumentValue(format!("rwasm: {} at {}", msg, offset))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| invalid("unexpected end", self.offset))?;
+        let bytes = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+impl RwasmModule {
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(RWASM_MAGIC.len())? != RWASM_MAGIC {
+            return Err(invalid("bad magic", 0));
+        }
+        if reader.take(1)?[0] != RWASM_VERSION {
+            return Err(invalid("unsupported version", RWASM_MAGIC.len()));
+        }
+
+        let code_len = reader.u32()? as usize;
+        if code_len % RWASM_INSTRUCTION_LEN != 0 {
+            return Err(invalid("code length is not a multiple of instruction length", 3));
+        }
+        let code = reader
+            .take(code_len)?
+            .chunks(RWASM_INSTRUCTION_LEN)
+            .map(|c| RwasmInstruction::new(c[0], u64::from_le_bytes(c[1..].try_into().unwrap())))
+            .collect::<Vec<_>>();
+
+        let memory_len = reader.u32()? as usize;
+        let memory = reader.take(memory_len)?.to_vec();
+
+        let funcs_count = reader.u32()? as usize;
+        let mut func_entries = Vec::with_capacity(funcs_count.min(code.len()));
+        for _ in 0..funcs_count {
+            let offset = reader.offset;
+            let entry = reader.u32()?;
+            let expected_min = func_entries.last().map_or(0, |prev| prev + 1);
+            if entry as usize >= code.len()
+                || entry < expected_min
+                || (func_entries.is_empty() && entry != 0)
+            {
+                return Err(invalid("bad function entry", offset));
+            }
+            func_entries.push(entry);
+        }
+        if reader.offset != bytes.len() {
+            return Err(invalid("trailing bytes", reader.offset));
+        }
+
+        Ok(Self {
+            code,
+            memory,
+            func_entries,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = RWASM_MAGIC.to_vec();
+        bytes.push(RWASM_VERSION);
+        bytes.extend(((self.code.len() * RWASM_INSTRUCTION_LEN) as u32).to_le_bytes());
+        for instr in &self.code {
+            bytes.push(instr.opcode);
+            bytes.extend(instr.operand.to_le_bytes());
+        }
+        bytes.extend((self.memory.len() as u32).to_le_bytes());
+        bytes.extend(&self.memory);
+        bytes.extend((self.func_entries.len() as u32).to_le_bytes());
+        for entry in &self.func_entries {
+            bytes.extend(entry.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Instructions of every function
+    pub fn funcs(&self) -> impl Iterator<Item = &[RwasmInstruction]> {
+        let ends = self
+            .func_entries
+            .iter()
+            .skip(1)
+            .map(|e| *e as usize)
+            .chain(std::iter::once(self.code.len()));
+        self.func_entries
+            .iter()
+            .zip(ends)
+            .map(|(start, end)| &self.code[*start as usize..end])
+    }
+}
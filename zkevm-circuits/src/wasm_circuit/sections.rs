@@ -10,3 +10,5 @@ pub mod consts;
 pub mod start;
 pub mod table;
 pub mod element;
+#[cfg(any(feature = "test", test))]
+pub mod harness;
@@ -1,8 +1,8 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, ops::Range, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Chip, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Fixed},
+    plonk::{Advice, Column, ConstraintSystem, Expression, Fixed, VirtualCells},
     poly::Rotation,
 };
 use itertools::Itertools;
@@ -20,36 +20,43 @@ use crate::{
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
-            configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            WasmAssignAwareChip, WasmBlockLevelAwareChip, WasmCountPrefixedItemsAwareChip,
-            WasmErrorAwareChip, WasmFuncCountAwareChip, WasmLenPrefixedBytesSpanAwareChip,
+            configure_transition_check, BlockLevelTransitions, ErrorCodeColumns, SectionBodyChip,
+            SectionBodyChipResources, SectionSelectors, WasmAssignAwareChip,
+            WasmBlockLevelAwareChip, WasmCountPrefixedItemsAwareChip, WasmErrorAwareChip,
+            WasmFuncCountAwareChip, WasmLenPrefixedBytesSpanAwareChip,
             WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
         },
-        consts::{WASM_BLOCKTYPE_DELIMITER, WASM_BLOCK_END},
+        consts::{
+            MAX_BLOCK_LEVEL, MAX_FUNC_BODY_SIZE, MAX_FUNC_LOCALS, WASM_BLOCKTYPE_DELIMITER,
+            WASM_BLOCK_END, WASM_SIMD_INSTRUCTION_PREFIX,
+        },
         error::{
             remap_error, remap_error_to_assign_at, remap_error_to_invalid_enum_value_at, Error,
         },
         leb128::circuit::LEB128Chip,
         sections::{code::body::types::AssignType, consts::LebParams},
         tables::{
-            code_blocks, code_blocks::circuit::CodeBlocksChip,
+            code_blocks,
+            code_blocks::circuit::CodeBlocksChip,
             dynamic_indexes::circuit::DynamicIndexesChip,
+            opcode::{
+                config::OpcodeTableConfig,
+                types::{ImmediateKind, OpcodeClass},
+            },
         },
         types::{
-            AssignDeltaType, AssignValueType, ControlInstruction, NumericInstruction,
-            ParametricInstruction, SharedState, VariableInstruction, CONTROL_INSTRUCTION_BLOCK,
-            CONTROL_INSTRUCTION_WITHOUT_ARGS, CONTROL_INSTRUCTION_WITH_LEB_ARG,
-            NUMERIC_INSTRUCTIONS_WITHOUT_ARGS, NUMERIC_INSTRUCTION_WITH_LEB_ARG,
-            PARAMETRIC_INSTRUCTIONS_WITHOUT_ARGS, VARIABLE_INSTRUCTION_WITH_LEB_ARG,
+            simd_instruction_len, AssignDeltaType, AssignValueType, ControlInstruction,
+            NewWbOffsetType, NumericInstruction, ParametricInstruction, SharedStateRef,
+            VariableInstruction, WasmSection, CONTROL_INSTRUCTION_BLOCK,
+            CONTROL_INSTRUCTION_WITH_LEB_ARG, NUMERIC_INSTRUCTION_WITH_LEB_ARG,
+            VARIABLE_INSTRUCTION_WITH_LEB_ARG,
         },
     },
 };
 
 #[derive(Debug, Clone)]
 pub struct WasmCodeSectionBodyConfig<F: Field> {
-    pub q_enable: Column<Fixed>,
-    pub q_first: Column<Fixed>,
-    pub q_last: Column<Fixed>,
+    pub selectors: SectionSelectors,
     pub is_funcs_count: Column<Fixed>,
     pub is_func_body_len: Column<Fixed>,
     pub is_local_type_transitions_count: Column<Fixed>,
@@ -63,28 +70,43 @@ pub struct WasmCodeSectionBodyConfig<F: Field> {
     pub is_control_instruction: Column<Fixed>,
     pub is_control_instruction_leb_arg: Column<Fixed>,
     pub is_parametric_instruction: Column<Fixed>,
+    /// `0xFD` prefix of a skipped SIMD instruction
+    pub is_simd_instruction: Column<Fixed>,
+    /// sub-opcode and immediates of a skipped SIMD instruction
+    pub is_simd_instruction_operand: Column<Fixed>,
     pub is_blocktype_delimiter: Column<Fixed>,
     pub is_block_end: Column<Fixed>,
 
-    pub leb128_chip: Rc<LEB128Chip<F>>,
-    pub numeric_instructions_chip: Rc<BinaryNumberChip<F, NumericInstruction, 8>>,
-    pub variable_instruction_chip: Rc<BinaryNumberChip<F, VariableInstruction, 8>>,
-    pub control_instruction_chip: Rc<BinaryNumberChip<F, ControlInstruction, 8>>,
-    pub parametric_instruction_chip: Rc<BinaryNumberChip<F, ParametricInstruction, 8>>,
-    pub dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+    pub leb128_chip: Arc<LEB128Chip<F>>,
+    pub control_instruction_chip: Arc<BinaryNumberChip<F, ControlInstruction, 8>>,
+    pub dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
 
-    pub code_blocks_chip: Rc<CodeBlocksChip<F>>,
+    pub code_blocks_chip: Arc<CodeBlocksChip<F>>,
     block_opcode_number: Column<Advice>,
 
     pub func_count: Column<Advice>,
     pub block_level: Column<Advice>,
-    pub block_level_lt_chip: Rc<LtChip<F, 2>>,
+    pub block_level_lt_chip: Arc<LtChip<F, 2>>,
+    /// block_level must not exceed `max_block_level`
+    pub max_block_level: u64,
+    pub block_level_max_lt_chip: Arc<LtChip<F, 2>>,
+    pub block_level_positive_lt_chip: Arc<LtChip<F, 2>>,
+    /// declared func body size must not exceed `max_func_body_size`
+    pub max_func_body_size: u64,
+    pub func_body_size_lt_chip: Arc<LtChip<F, 4>>,
+    /// number of locals declared so far by the current func body
+    pub locals_count: Column<Advice>,
+    /// locals count of a func body must not exceed `max_func_locals`
+    pub max_func_locals: u64,
+    pub locals_count_lt_chip: Arc<LtChip<F, 5>>,
+    /// SIMD instructions are skipped instead of setting the error code
+    pub skip_simd_instructions: bool,
     body_byte_rev_index: Column<Advice>,
-    body_item_rev_count: Column<Advice>,
+    pub body_item_rev_count: Column<Advice>,
 
-    error_code: Column<Advice>,
+    error_code: ErrorCodeColumns,
 
-    pub shared_state: Rc<RefCell<SharedState>>,
+    pub shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -104,17 +126,60 @@ impl<F: Field> WasmLenPrefixedBytesSpanAwareChip<F> for WasmCodeSectionBodyChip<
 impl<F: Field> WasmCountPrefixedItemsAwareChip<F> for WasmCodeSectionBodyChip<F> {}
 
 impl<F: Field> WasmErrorAwareChip<F> for WasmCodeSectionBodyChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmCodeSectionBodyChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
 
+impl<F: Field> SectionBodyChip<F> for WasmCodeSectionBodyChip<F> {
+    const SECTION: WasmSection = WasmSection::Code;
+
+    fn configure_with_resources(
+        cs: &mut ConstraintSystem<F>,
+        resources: &SectionBodyChipResources<F>,
+    ) -> Self {
+        let config = Self::configure(
+            cs,
+            resources.wb_table.clone(),
+            resources.leb128_chip.clone(),
+            &resources.opcode_table_config,
+            resources.dynamic_indexes_chip.clone(),
+            resources.func_count,
+            resources.shared_state.clone(),
+            resources.body_byte_rev_index_l2,
+            resources.body_item_rev_count_l1,
+            resources.error_code,
+            resources.bytecode_number,
+            MAX_BLOCK_LEVEL,
+            MAX_FUNC_BODY_SIZE,
+            MAX_FUNC_LOCALS,
+            false,
+        );
+        Self::construct(config)
+    }
+
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error> {
+        self.assign_auto(region, wb, wb_offset, assign_delta)
+    }
+
+    fn func_count_increment_expr(&self, vc: &mut VirtualCells<F>) -> Expression<F> {
+        vc.query_fixed(self.config.selectors.q_first, Rotation::cur())
+            * vc.query_advice(self.config.leb128_chip.config.sn, Rotation::cur())
+    }
+}
+
 impl<F: Field> WasmFuncCountAwareChip<F> for WasmCodeSectionBodyChip<F> {
     fn func_count_col(&self) -> Column<Advice> {
         self.config.func_count
@@ -146,16 +211,12 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmCodeSectionBodyChip<F> {
             "assign at {} q_enable {} assign_types {:?} assign_value {} byte_val {:x?}",
             assign_offset, q_enable, assign_types, assign_value, wb.bytes[wb_offset],
         );
-        region
-            .assign_fixed(
-                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
-                self.config.q_enable,
-                assign_offset,
-                || Value::known(F::from(q_enable as u64)),
-            )
-            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.config
+            .selectors
+            .assign_q_enable(region, assign_offset, q_enable)?;
         self.assign_func_count(region, assign_offset)?;
         self.assign_block_level(region, assign_offset)?;
+        self.assign_block_level_bounds(region, assign_offset)?;
 
         for assign_type in assign_types {
             if [
@@ -181,29 +242,14 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmCodeSectionBodyChip<F> {
                     ))
                 }
                 AssignType::QFirst => {
-                    region
-                        .assign_fixed(
-                            || {
-                                format!(
-                                    "assign 'q_first' val {} at {}",
-                                    assign_value, assign_offset
-                                )
-                            },
-                            self.config.q_first,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_first(region, assign_offset, assign_value)?;
                 }
                 AssignType::QLast => {
-                    region
-                        .assign_fixed(
-                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
-                            self.config.q_last,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_last(region, assign_offset, assign_value)?;
                 }
                 AssignType::IsFuncsCount => {
                     region
@@ -294,15 +340,6 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmCodeSectionBodyChip<F> {
                             || Value::known(F::from(assign_value)),
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
-                    if assign_value == 1 {
-                        let opcode: NumericInstruction = wb.bytes[wb_offset]
-                            .try_into()
-                            .map_err(remap_error_to_invalid_enum_value_at(assign_offset))?;
-                        self.config
-                            .numeric_instructions_chip
-                            .assign(region, assign_offset, &opcode)
-                            .map_err(remap_error(Error::FatalAssignExternalChip))?;
-                    }
                 }
                 AssignType::IsNumericInstructionLebArg => {
                     region
@@ -333,15 +370,6 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmCodeSectionBodyChip<F> {
                             || Value::known(F::from(assign_value)),
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
-                    if assign_value == 1 {
-                        let opcode = wb.bytes[wb_offset]
-                            .try_into()
-                            .map_err(remap_error_to_invalid_enum_value_at(assign_offset))?;
-                        self.config
-                            .variable_instruction_chip
-                            .assign(region, assign_offset, &opcode)
-                            .map_err(remap_error(Error::FatalAssignExternalChip))?;
-                    }
                 }
                 AssignType::IsVariableInstructionLebArg => {
                     region
@@ -411,15 +439,36 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmCodeSectionBodyChip<F> {
                             || Value::known(F::from(assign_value)),
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
-                    if assign_value == 1 {
-                        let opcode = wb.bytes[wb_offset]
-                            .try_into()
-                            .map_err(remap_error_to_invalid_enum_value_at(assign_offset))?;
-                        self.config
-                            .parametric_instruction_chip
-                            .assign(region, assign_offset, &opcode)
-                            .map_err(remap_error(Error::FatalAssignExternalChip))?;
-                    }
+                }
+                AssignType::IsSimdInstruction => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_simd_instruction' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_simd_instruction,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::IsSimdInstructionOperand => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_simd_instruction_operand' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_simd_instruction_operand,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
                 AssignType::IsBlocktypeDelimiter => {
                     region
@@ -481,6 +530,21 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmCodeSectionBodyChip<F> {
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
+                AssignType::LocalsCount => {
+                    region
+                        .assign_advice(
+                            || {
+                                format!(
+                                    "assign 'locals_count' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.locals_count,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
                 AssignType::BlockOpcodeIndex => {
                     region
                         .assign_advice(
@@ -516,19 +580,23 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        wb_table: Rc<WasmBytecodeTable>,
-        leb128_chip: Rc<LEB128Chip<F>>,
-        dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+        wb_table: Arc<WasmBytecodeTable>,
+        leb128_chip: Arc<LEB128Chip<F>>,
+        opcode_table_config: &OpcodeTableConfig<F>,
+        dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
         func_count: Column<Advice>,
-        shared_state: Rc<RefCell<SharedState>>,
+        shared_state: SharedStateRef,
         body_byte_rev_index: Column<Advice>,
         body_item_rev_count: Column<Advice>,
-        error_code: Column<Advice>,
+        error_code: ErrorCodeColumns,
         bytecode_number: Column<Advice>,
+        max_block_level: u64,
+        max_func_body_size: u64,
+        max_func_locals: u64,
+        skip_simd_instructions: bool,
     ) -> WasmCodeSectionBodyConfig<F> {
-        let q_enable = cs.fixed_column();
-        let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        let selectors = SectionSelectors::configure(cs);
+        let SectionSelectors { q_enable, q_first, q_last } = selectors;
         let is_funcs_count = cs.fixed_column();
         let is_func_body_len = cs.fixed_column();
         let is_local_type_transitions_count = cs.fixed_column();
@@ -537,6 +605,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
 
         let block_level = cs.advice_column();
         let block_opcode_number = cs.advice_column();
+        let locals_count = cs.advice_column();
 
         let is_numeric_instruction = cs.fixed_column();
         let is_numeric_instruction_leb_arg = cs.fixed_column();
@@ -545,35 +614,25 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
         let is_control_instruction = cs.fixed_column();
         let is_control_instruction_leb_arg = cs.fixed_column();
         let is_parametric_instruction = cs.fixed_column();
+        let is_simd_instruction = cs.fixed_column();
+        let is_simd_instruction_operand = cs.fixed_column();
         let is_blocktype_delimiter = cs.fixed_column();
         let is_block_end = cs.fixed_column();
 
         let config = CodeBlocksChip::configure(cs, shared_state.clone());
-        let code_blocks_chip = Rc::new(CodeBlocksChip::construct(config));
-
-        let config =
-            BinaryNumberChip::configure(cs, is_numeric_instruction, Some(wb_table.value.into()));
-        let numeric_instructions_chip = Rc::new(BinaryNumberChip::construct(config));
+        let code_blocks_chip = Arc::new(CodeBlocksChip::construct(config));
 
         let config =
             BinaryNumberChip::configure(cs, is_control_instruction, Some(wb_table.value.into()));
-        let control_instruction_chip = Rc::new(BinaryNumberChip::construct(config));
-
-        let config =
-            BinaryNumberChip::configure(cs, is_parametric_instruction, Some(wb_table.value.into()));
-        let parametric_instruction_chip = Rc::new(BinaryNumberChip::construct(config));
-
-        let config =
-            BinaryNumberChip::configure(cs, is_variable_instruction, Some(wb_table.value.into()));
-        let variable_instruction_chip = Rc::new(BinaryNumberChip::construct(config));
+        let control_instruction_chip = Arc::new(BinaryNumberChip::construct(config));
 
-        let config = LtChip::configure(
+        let block_level_lt_chip = Self::configure_branch_depth_lt_chip(
             cs,
             |vc| {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
@@ -596,9 +655,64 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 ])
             },
             |vc| vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
+            block_level,
+        );
+
+        let config = LtChip::configure(
+            cs,
+            |vc| {
+                Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                )
+            },
             |vc| vc.query_advice(block_level, Rotation::cur()),
+            |_| (max_block_level + 1).expr(),
         );
-        let block_level_lt_chip = Rc::new(LtChip::construct(config));
+        let block_level_max_lt_chip = Arc::new(LtChip::construct(config));
+        let config = LtChip::configure(
+            cs,
+            |vc| {
+                Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                )
+            },
+            |_| 0.expr(),
+            |vc| vc.query_advice(block_level, Rotation::cur()),
+        );
+        let block_level_positive_lt_chip = Arc::new(LtChip::construct(config));
+
+        let config = LtChip::configure(
+            cs,
+            |vc| {
+                and::expr([
+                    vc.query_fixed(q_enable, Rotation::cur()),
+                    vc.query_fixed(is_func_body_len, Rotation::cur()),
+                    vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur()),
+                ])
+            },
+            |vc| vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
+            |_| (max_func_body_size + 1).expr(),
+        );
+        let func_body_size_lt_chip = Arc::new(LtChip::construct(config));
+        let config = LtChip::configure(
+            cs,
+            |vc| {
+                and::expr([
+                    vc.query_fixed(q_enable, Rotation::cur()),
+                    vc.query_fixed(is_local_repetition_count, Rotation::cur()),
+                    vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur()),
+                ])
+            },
+            |vc| vc.query_advice(locals_count, Rotation::cur()),
+            |_| (max_func_locals + 1).expr(),
+        );
+        let locals_count_lt_chip = Arc::new(LtChip::construct(config));
 
         Self::configure_len_prefixed_bytes_span_checks(
             cs,
@@ -616,6 +730,8 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                         is_control_instruction,
                         is_control_instruction_leb_arg,
                         is_parametric_instruction,
+                        is_simd_instruction,
+                        is_simd_instruction_operand,
                         is_blocktype_delimiter,
                         is_block_end,
                     ]
@@ -658,7 +774,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let is_funcs_count_expr = vc.query_fixed(is_funcs_count, Rotation::cur());
@@ -742,14 +858,64 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             ]
         });
 
+        opcode_table_config.lookup_opcode(
+            cs,
+            "code section: instruction opcode and its immediate are valid",
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                );
+                let is_numeric_instruction_expr =
+                    vc.query_fixed(is_numeric_instruction, Rotation::cur());
+                let is_variable_instruction_expr =
+                    vc.query_fixed(is_variable_instruction, Rotation::cur());
+                let is_control_instruction_expr =
+                    vc.query_fixed(is_control_instruction, Rotation::cur());
+                let is_parametric_instruction_expr =
+                    vc.query_fixed(is_parametric_instruction, Rotation::cur());
+                let is_instruction_leb_arg_next_expr = or::expr(
+                    [
+                        is_numeric_instruction_leb_arg,
+                        is_variable_instruction_leb_arg,
+                        is_control_instruction_leb_arg,
+                    ]
+                    .map(|c| vc.query_fixed(c, Rotation::next())),
+                );
+                let is_blocktype_delimiter_next_expr =
+                    vc.query_fixed(is_blocktype_delimiter, Rotation::next());
+                let byte_val_expr = vc.query_advice(wb_table.value, Rotation::cur());
+
+                let class_expr = is_numeric_instruction_expr.clone() * OpcodeClass::Numeric.expr()
+                    + is_variable_instruction_expr.clone() * OpcodeClass::Variable.expr()
+                    + is_control_instruction_expr.clone() * OpcodeClass::Control.expr()
+                    + is_parametric_instruction_expr.clone() * OpcodeClass::Parametric.expr();
+                let immediate_kind_expr = is_instruction_leb_arg_next_expr
+                    * ImmediateKind::Leb.expr()
+                    + is_blocktype_delimiter_next_expr * ImmediateKind::Blocktype.expr();
+
+                (
+                    q_enable_expr
+                        * or::expr([
+                            is_numeric_instruction_expr,
+                            is_variable_instruction_expr,
+                            is_control_instruction_expr,
+                            is_parametric_instruction_expr,
+                        ]),
+                    [class_expr, byte_val_expr, immediate_kind_expr],
+                )
+            },
+        );
+
         cs.create_gate("WasmCodeSectionBody gate", |vc| {
             let mut cb = BaseConstraintBuilder::default();
 
-            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.read(), error_code);
             let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
             let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
             let not_q_last_expr = not::expr(q_last_expr.clone());
-            let is_funcs_count_prev_expr = vc.query_fixed(is_funcs_count, Rotation::prev());
             let is_funcs_count_expr = vc.query_fixed(is_funcs_count, Rotation::cur());
             let is_func_body_len_expr = vc.query_fixed(is_func_body_len, Rotation::cur());
             let is_local_type_transitions_count_expr = vc.query_fixed(is_local_type_transitions_count, Rotation::cur());
@@ -762,9 +928,11 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             let is_control_instruction_expr = vc.query_fixed(is_control_instruction, Rotation::cur());
             let is_control_instruction_leb_arg_expr = vc.query_fixed(is_control_instruction_leb_arg, Rotation::cur());
             let is_parametric_instruction_expr = vc.query_fixed(is_parametric_instruction, Rotation::cur());
+            let is_simd_instruction_expr = vc.query_fixed(is_simd_instruction, Rotation::cur());
+            let is_simd_instruction_operand_expr = vc.query_fixed(is_simd_instruction_operand, Rotation::cur());
             let is_blocktype_delimiter_expr = vc.query_fixed(is_blocktype_delimiter, Rotation::cur());
-            let is_block_end_prev_expr = vc.query_fixed(is_block_end, Rotation::prev());
             let is_block_end_expr = vc.query_fixed(is_block_end, Rotation::cur());
+            let is_func_body_len_next_expr = vc.query_fixed(is_func_body_len, Rotation::next());
 
             let leb128_q_enable_expr = vc.query_fixed(leb128_chip.config.q_enable, Rotation::cur());
 
@@ -780,7 +948,6 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             let block_opcode_number_prev_expr = vc.query_advice(block_opcode_number, Rotation::prev());
             let block_opcode_number_expr = vc.query_advice(block_opcode_number, Rotation::cur());
 
-            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_funcs_count is boolean", is_funcs_count_expr.clone());
             cb.require_boolean("is_func_body_len is boolean", is_func_body_len_expr.clone());
             cb.require_boolean("is_local_type_transitions_count is boolean", is_local_type_transitions_count_expr.clone());
@@ -793,16 +960,10 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             cb.require_boolean("is_control_instruction is boolean", is_control_instruction_expr.clone());
             cb.require_boolean("is_control_instruction_leb_arg is boolean", is_control_instruction_leb_arg_expr.clone());
             cb.require_boolean("is_parametric_instruction is boolean", is_parametric_instruction_expr.clone());
+            cb.require_boolean("is_simd_instruction is boolean", is_simd_instruction_expr.clone());
+            cb.require_boolean("is_simd_instruction_operand is boolean", is_simd_instruction_operand_expr.clone());
 
-            configure_constraints_for_q_first_and_q_last(
-                &mut cb,
-                vc,
-                &q_enable,
-                &q_first,
-                &[is_funcs_count],
-                &q_last,
-                &[is_block_end],
-            );
+            selectors.configure_constraints(&mut cb, vc, &[is_funcs_count], &[is_block_end]);
 
             let control_opcode_is_block_expr = control_instruction_chip.config.value_equals(ControlInstruction::Block, Rotation::cur())(vc);
             let control_opcode_is_loop_expr = control_instruction_chip.config.value_equals(ControlInstruction::Loop, Rotation::cur())(vc);
@@ -866,48 +1027,12 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 }
             );
 
-            let is_numeric_opcode_without_params_expr = or::expr(
-                NUMERIC_INSTRUCTIONS_WITHOUT_ARGS.iter()
-                    .map(|v| {
-                        numeric_instructions_chip.config.value_equals(*v, Rotation::cur())(vc)
-                    }).collect_vec()
-            );
-            let is_numeric_opcode_with_leb_param_expr = or::expr(
-                NUMERIC_INSTRUCTION_WITH_LEB_ARG.iter()
-                    .map(|v| {
-                        numeric_instructions_chip.config.value_equals(*v, Rotation::cur())(vc)
-                    }).collect_vec()
-            );
-            let is_variable_opcode_with_leb_param_expr = or::expr(
-                VARIABLE_INSTRUCTION_WITH_LEB_ARG.iter()
-                    .map(|v| {
-                        variable_instruction_chip.config.value_equals(*v, Rotation::cur())(vc)
-                    }).collect_vec()
-            );
-            let is_control_opcode_without_params_expr = or::expr(
-                CONTROL_INSTRUCTION_WITHOUT_ARGS.iter()
-                    .map(|v| {
-                        control_instruction_chip.config.value_equals(*v, Rotation::cur())(vc)
-                    }).collect_vec()
-            );
-            let is_control_opcode_with_leb_param_expr = or::expr(
-                CONTROL_INSTRUCTION_WITH_LEB_ARG.iter()
-                    .map(|v| {
-                        control_instruction_chip.config.value_equals(*v, Rotation::cur())(vc)
-                    }).collect_vec()
-            );
             let is_control_opcode_block_expr = or::expr(
                 CONTROL_INSTRUCTION_BLOCK.iter()
                     .map(|v| {
                         control_instruction_chip.config.value_equals(*v, Rotation::cur())(vc)
                     }).collect_vec()
             );
-            let is_parametric_opcode_without_params_expr = or::expr(
-                PARAMETRIC_INSTRUCTIONS_WITHOUT_ARGS.iter()
-                    .map(|v| {
-                        parametric_instruction_chip.config.value_equals(*v, Rotation::cur())(vc)
-                    }).collect_vec()
-            );
 
             let is_instruction_leb_arg_expr = or::expr([
                 is_numeric_instruction_leb_arg_expr.clone(),
@@ -915,81 +1040,96 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 is_control_instruction_leb_arg_expr.clone(),
             ]);
 
-            // block_level constraints
+            cb.require_equal(
+                "block_level <= max_block_level",
+                block_level_max_lt_chip.config().is_lt(vc, None),
+                1.expr(),
+            );
             cb.condition(
-                q_first_expr.clone(),
+                and::expr([
+                    is_block_end_expr.clone(),
+                    not_q_last_expr.clone(),
+                    is_func_body_len_next_expr.clone(),
+                ]),
                 |cb| {
                     cb.require_zero(
-                        "q_first => block_level=0",
+                        "is_block_end && next.is_func_body_len => block_level=0",
                         block_level_expr.clone(),
                     );
                 }
             );
             cb.condition(
-                q_last_expr.clone(),
+                and::expr([
+                    is_block_end_expr.clone(),
+                    not_q_last_expr.clone(),
+                    not::expr(is_func_body_len_next_expr.clone()),
+                ]),
                 |cb| {
-                    cb.require_zero(
-                        "q_last => block_level=0",
-                        block_level_expr.clone(),
+                    cb.require_equal(
+                        "is_block_end inside func body => block_level>0",
+                        block_level_positive_lt_chip.config().is_lt(vc, None),
+                        1.expr(),
                     );
                 }
             );
+
+            // func body limits
             cb.condition(
                 and::expr([
                     is_func_body_len_expr.clone(),
-                    or::expr([
-                        is_funcs_count_prev_expr.clone(),
-                        is_block_end_prev_expr.clone(),
-                    ]),
+                    leb128_is_last_byte_expr.clone(),
                 ]),
                 |cb| {
-                    cb.require_zero(
-                        "block_level=1 on is_func_body_len transition",
-                        block_level_expr.clone() - 1.expr(),
+                    cb.require_equal(
+                        "is_func_body_len => func body size <= max_func_body_size",
+                        func_body_size_lt_chip.config().is_lt(vc, None),
+                        1.expr(),
                     );
                 }
             );
+            let locals_count_expr = vc.query_advice(locals_count, Rotation::cur());
+            let locals_count_prev_expr = vc.query_advice(locals_count, Rotation::prev());
+            let is_local_repetition_count_last_byte_expr = and::expr([
+                is_local_repetition_count_expr.clone(),
+                leb128_is_last_byte_expr.clone(),
+            ]);
             cb.condition(
-                is_control_opcode_block_expr.clone(),
+                is_func_body_len_expr.clone(),
                 |cb| {
-                    let block_level_prev_expr = vc.query_advice(block_level, Rotation::prev());
-                    cb.require_equal(
-                        "is_control_opcode_block => prev.block_level+1=block_level",
-                        block_level_prev_expr + 1.expr(),
-                        block_level_expr.clone(),
+                    cb.require_zero(
+                        "is_func_body_len => locals_count=0",
+                        locals_count_expr.clone(),
                     );
                 }
             );
             cb.condition(
-                is_block_end_expr.clone(),
+                is_local_repetition_count_last_byte_expr.clone(),
                 |cb| {
-                    let block_level_prev_expr = vc.query_advice(block_level, Rotation::prev());
+                    let local_repetition_count_expr = vc.query_advice(leb128_chip.config.sn, Rotation::cur());
                     cb.require_equal(
-                        "is_block_end => prev.block_level-1=block_level",
-                        block_level_prev_expr - 1.expr(),
-                        block_level_expr.clone(),
+                        "is_local_repetition_count last byte => locals_count=prev.locals_count+local_repetition_count",
+                        locals_count_expr.clone(),
+                        locals_count_prev_expr.clone() + local_repetition_count_expr,
+                    );
+                    cb.require_equal(
+                        "is_local_repetition_count last byte => locals_count <= max_func_locals",
+                        locals_count_lt_chip.config().is_lt(vc, None),
+                        1.expr(),
                     );
                 }
             );
             cb.condition(
                 and::expr([
-                    not::expr(q_first_expr.clone()),
-                    not::expr(q_last_expr.clone()),
-                    not::expr(and::expr([
-                        is_func_body_len_expr.clone(),
-                        or::expr([
-                            is_funcs_count_prev_expr.clone(),
-                            is_block_end_prev_expr.clone(),
-                        ]),
-                    ])),
-                    not::expr(is_control_opcode_block_expr.clone()),
-                    not::expr(is_block_end_expr.clone()),
+                    not_q_first_expr.clone(),
+                    not::expr(is_funcs_count_expr.clone()),
+                    not::expr(is_func_body_len_expr.clone()),
+                    not::expr(is_local_repetition_count_last_byte_expr.clone()),
                 ]),
                 |cb| {
-                    let block_level_prev_expr = vc.query_advice(block_level, Rotation::prev());
-                    cb.require_zero(
-                        "prev.block_level_expr=block_level",
-                        block_level_expr.clone() - block_level_prev_expr,
+                    cb.require_equal(
+                        "locals_count=prev.locals_count",
+                        locals_count_expr.clone(),
+                        locals_count_prev_expr.clone(),
                     );
                 }
             );
@@ -1008,6 +1148,8 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     + is_control_instruction_expr.clone()
                     + is_control_instruction_leb_arg_expr.clone()
                     + is_parametric_instruction_expr.clone()
+                    + is_simd_instruction_expr.clone()
+                    + is_simd_instruction_operand_expr.clone()
                     + is_blocktype_delimiter_expr.clone()
                     + is_block_end_expr.clone(),
                 1.expr(),
@@ -1038,7 +1180,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 true,
                 &[
                     is_local_type_transitions_count, is_local_repetition_count,
-                    is_numeric_instruction, is_variable_instruction, is_control_instruction, is_parametric_instruction, is_block_end,
+                    is_numeric_instruction, is_variable_instruction, is_control_instruction, is_parametric_instruction, is_simd_instruction, is_block_end,
                 ],
             );
             configure_transition_check(
@@ -1055,69 +1197,11 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 "check next: ... is_local_type(1))) -> is_func_body_code+",
                 is_local_type_expr.clone(),
                 true,
-                &[is_local_repetition_count, is_numeric_instruction, is_variable_instruction, is_control_instruction, is_parametric_instruction, ],
+                &[is_local_repetition_count, is_numeric_instruction, is_variable_instruction, is_control_instruction, is_parametric_instruction, is_simd_instruction, is_block_end, ],
             );
 
             // BASIC CONSTRAINTS:
 
-            cb.condition(
-                is_numeric_instruction_expr.clone(),
-                |cb| {
-                    cb.require_equal(
-                        "is_numeric_instruction(1) -> opcode is valid",
-                        or::expr([
-                            is_numeric_opcode_without_params_expr.clone(),
-                            is_numeric_opcode_with_leb_param_expr.clone(),
-                        ]),
-                        1.expr(),
-                    );
-                }
-            );
-
-            cb.condition(
-                is_variable_instruction_expr.clone(),
-                |cb| {
-                    cb.require_equal(
-                        "is_variable_instruction(1) => opcode is valid",
-                        or::expr(
-                            VARIABLE_INSTRUCTION_WITH_LEB_ARG.iter()
-                                .map(|v| {
-                                    variable_instruction_chip.config.value_equals(*v, Rotation::cur())(vc)
-                                }).collect_vec()
-                        ),
-                        1.expr(),
-                    );
-                }
-            );
-
-            cb.condition(
-                is_control_instruction_expr.clone(),
-                |cb| {
-                    cb.require_equal(
-                        "is_control_instruction(1) -> opcode is valid",
-                        or::expr([
-                            is_control_opcode_without_params_expr.clone(),
-                            is_control_opcode_with_leb_param_expr.clone(),
-                            is_control_opcode_block_expr.clone(),
-                        ]),
-                        1.expr(),
-                    );
-                }
-            );
-
-            cb.condition(
-                is_parametric_instruction_expr.clone(),
-                |cb| {
-                    cb.require_equal(
-                        "is_parametric_instruction(1) -> opcode is valid",
-                        or::expr([
-                            is_parametric_opcode_without_params_expr.clone(),
-                        ]),
-                        1.expr(),
-                    );
-                }
-            );
-
             cb.condition(
                 or::expr([
                     is_funcs_count_expr.clone(),
@@ -1157,57 +1241,26 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 }
             );
 
-            // SIMPLE RELATIONS CONSTRAINTS:
-
-            // is_numeric_opcode_with_leb_param{1} -> is_numeric_instruction_leb_arg+
+            // is_simd_instruction{1} => WASM_SIMD_INSTRUCTION_PREFIX
             cb.condition(
-                is_numeric_opcode_with_leb_param_expr.clone(),
+                is_simd_instruction_expr.clone(),
                 |cb| {
-                    let is_numeric_instruction_leb_arg_next_expr = vc.query_fixed(is_numeric_instruction_leb_arg, Rotation::next());
                     cb.require_equal(
-                        "is_numeric_opcode_with_leb_param(1) -> is_numeric_instruction_leb_arg_next",
-                        is_numeric_instruction_leb_arg_next_expr.clone(),
-                        1.expr(),
-                    );
-                }
-            );
-
-            // is_variable_opcode_with_leb_param{1} -> is_variable_instruction_leb_arg+
-            cb.condition(
-                is_variable_opcode_with_leb_param_expr.clone(),
-                |cb| {
-                    let is_variable_instruction_leb_arg_next_expr = vc.query_fixed(is_variable_instruction_leb_arg, Rotation::next());
-                    cb.require_equal(
-                        "is_variable_opcode_with_leb_param(1) -> is_variable_instruction_leb_arg+",
-                        is_variable_instruction_leb_arg_next_expr.clone(),
-                        1.expr(),
+                        "is_simd_instruction(1) => WASM_SIMD_INSTRUCTION_PREFIX",
+                        byte_val_expr.clone(),
+                        WASM_SIMD_INSTRUCTION_PREFIX.expr(),
                     );
                 }
             );
+            if !skip_simd_instructions {
+                cb.require_zero(
+                    "SIMD instructions are not skipped => !is_simd_instruction",
+                    is_simd_instruction_expr.clone(),
+                );
+            }
 
-            // is_control_opcode_with_leb_param{1} -> is_control_instruction_leb_arg+
-            cb.condition(
-                is_control_opcode_with_leb_param_expr.clone(),
-                |cb| {
-                    let is_control_instruction_leb_arg_next_expr = vc.query_fixed(is_control_instruction_leb_arg, Rotation::next());
-                    cb.require_equal(
-                        "is_control_opcode_with_leb_param(1) -> is_control_instruction_leb_arg+",
-                        is_control_instruction_leb_arg_next_expr.clone(),
-                        1.expr(),
-                    );
-                }
-            );
-            // is_control_opcode_block{1} -> is_blocktype_delimiter{1}
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "is_control_opcode_block(1) -> is_blocktype_delimiter(1)",
-                and::expr([
-                    is_control_opcode_block_expr.clone(),
-                ]),
-                true,
-                &[is_blocktype_delimiter],
-            );
+            // SIMPLE RELATIONS CONSTRAINTS:
+            // opcode -> immediate relations are checked by the opcode table lookup
 
             // COMPLEX RELATIONS CONSTRAINTS:
 
@@ -1221,6 +1274,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_simd_instruction_next_expr = vc.query_fixed(is_simd_instruction, Rotation::next());
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1232,6 +1286,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                             + is_variable_instruction_next_expr
                             + is_control_instruction_next_expr
                             + is_parametric_instruction_next_expr
+                            + is_simd_instruction_next_expr
 
                             + is_block_end_next_expr
                         ,
@@ -1250,6 +1305,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_simd_instruction_next_expr = vc.query_fixed(is_simd_instruction, Rotation::next());
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1261,6 +1317,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                             + is_variable_instruction_next_expr
                             + is_control_instruction_next_expr
                             + is_parametric_instruction_next_expr
+                            + is_simd_instruction_next_expr
 
                             + is_block_end_next_expr
                         ,
@@ -1282,6 +1339,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_simd_instruction_next_expr = vc.query_fixed(is_simd_instruction, Rotation::next());
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1293,6 +1351,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                             + is_variable_instruction_next_expr
                             + is_control_instruction_next_expr
                             + is_parametric_instruction_next_expr
+                            + is_simd_instruction_next_expr
 
                             + is_block_end_next_expr
                         ,
@@ -1314,11 +1373,13 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_simd_instruction_next_expr = vc.query_fixed(is_simd_instruction, Rotation::next());
 
                     let is_instruction_next_expr = is_numeric_instruction_next_expr
                         + is_variable_instruction_next_expr
                         + is_control_instruction_next_expr
-                        + is_parametric_instruction_next_expr;
+                        + is_parametric_instruction_next_expr
+                        + is_simd_instruction_next_expr;
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1348,11 +1409,13 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_simd_instruction_next_expr = vc.query_fixed(is_simd_instruction, Rotation::next());
 
                     let is_instruction_next_expr = is_numeric_instruction_next_expr
                         + is_variable_instruction_next_expr
                         + is_control_instruction_next_expr
-                        + is_parametric_instruction_next_expr;
+                        + is_parametric_instruction_next_expr
+                        + is_simd_instruction_next_expr;
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1382,11 +1445,13 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_simd_instruction_next_expr = vc.query_fixed(is_simd_instruction, Rotation::next());
 
                     let is_instruction_next_expr = is_numeric_instruction_next_expr
                         + is_variable_instruction_next_expr
                         + is_control_instruction_next_expr
-                        + is_parametric_instruction_next_expr;
+                        + is_parametric_instruction_next_expr
+                        + is_simd_instruction_next_expr;
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1403,6 +1468,54 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 }
             );
 
+            // is_simd_instruction{1} -> is_simd_instruction_operand
+            cb.condition(
+                is_simd_instruction_expr.clone(),
+                |cb| {
+                    let is_simd_instruction_operand_next_expr = vc.query_fixed(is_simd_instruction_operand, Rotation::next());
+
+                    cb.require_equal(
+                        "check next: is_simd_instruction(1) -> is_simd_instruction_operand",
+                        is_simd_instruction_operand_next_expr,
+                        1.expr(),
+                    );
+                }
+            );
+
+            // is_simd_instruction_operand -> is_simd_instruction_operand || is_instruction || is_block_end
+            // immediate layouts are not checked: SIMD semantics are not proven
+            cb.condition(
+                is_simd_instruction_operand_expr.clone(),
+                |cb| {
+                    let is_simd_instruction_operand_next_expr = vc.query_fixed(is_simd_instruction_operand, Rotation::next());
+
+                    let is_numeric_instruction_next_expr = vc.query_fixed(is_numeric_instruction, Rotation::next());
+                    let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
+                    let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
+                    let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_simd_instruction_next_expr = vc.query_fixed(is_simd_instruction, Rotation::next());
+
+                    let is_instruction_next_expr = is_numeric_instruction_next_expr
+                        + is_variable_instruction_next_expr
+                        + is_control_instruction_next_expr
+                        + is_parametric_instruction_next_expr
+                        + is_simd_instruction_next_expr;
+
+                    let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
+
+                    cb.require_equal(
+                        "check next: is_simd_instruction_operand -> is_simd_instruction_operand || is_instruction || is_block_end",
+                        is_simd_instruction_operand_next_expr
+
+                            + is_instruction_next_expr
+
+                            + is_block_end_next_expr
+                        ,
+                        1.expr(),
+                    );
+                }
+            );
+
             // is_block_end && !not_q_last -> is_instruction || is_block_end
             cb.condition(
                 and::expr([
@@ -1416,11 +1529,13 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_simd_instruction_next_expr = vc.query_fixed(is_simd_instruction, Rotation::next());
 
                     let is_instruction_next_expr = is_numeric_instruction_next_expr
                         + is_variable_instruction_next_expr
                         + is_control_instruction_next_expr
-                        + is_parametric_instruction_next_expr;
+                        + is_parametric_instruction_next_expr
+                        + is_simd_instruction_next_expr;
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1457,12 +1572,45 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             cb.gate(q_enable_expr.clone())
         });
 
+        Self::configure_block_level(cs, block_level, |vc| {
+            let is_func_body_len_expr = vc.query_fixed(is_func_body_len, Rotation::cur());
+            let is_funcs_count_prev_expr = vc.query_fixed(is_funcs_count, Rotation::prev());
+            let is_block_end_prev_expr = vc.query_fixed(is_block_end, Rotation::prev());
+            // control instruction bits are only bound to the byte value on control opcode rows
+            let is_block_open_expr = vc.query_fixed(is_control_instruction, Rotation::cur())
+                * or::expr(
+                    CONTROL_INSTRUCTION_BLOCK
+                        .iter()
+                        .map(|v| {
+                            control_instruction_chip
+                                .config
+                                .value_equals(*v, Rotation::cur())(vc)
+                        })
+                        .collect_vec(),
+                );
+
+            BlockLevelTransitions {
+                q_enable: Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                ),
+                q_first: vc.query_fixed(q_first, Rotation::cur()),
+                q_last: vc.query_fixed(q_last, Rotation::cur()),
+                is_func_body_start: and::expr([
+                    is_func_body_len_expr,
+                    or::expr([is_funcs_count_prev_expr, is_block_end_prev_expr]),
+                ]),
+                is_block_open: is_block_open_expr,
+                is_block_end: vc.query_fixed(is_block_end, Rotation::cur()),
+            }
+        });
+
         let config = WasmCodeSectionBodyConfig::<F> {
             _marker: PhantomData,
 
-            q_enable,
-            q_first,
-            q_last,
+            selectors,
             is_funcs_count,
             is_func_body_len,
             is_local_type_transitions_count,
@@ -1475,19 +1623,27 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             is_control_instruction,
             is_control_instruction_leb_arg,
             is_parametric_instruction,
+            is_simd_instruction,
+            is_simd_instruction_operand,
             is_blocktype_delimiter,
             is_block_end,
             leb128_chip,
-            numeric_instructions_chip,
-            variable_instruction_chip,
             control_instruction_chip,
-            parametric_instruction_chip,
             dynamic_indexes_chip,
             code_blocks_chip,
             block_opcode_number,
             func_count,
             block_level,
             block_level_lt_chip,
+            max_block_level,
+            block_level_max_lt_chip,
+            block_level_positive_lt_chip,
+            max_func_body_size,
+            func_body_size_lt_chip,
+            locals_count,
+            max_func_locals,
+            locals_count_lt_chip,
+            skip_simd_instructions,
             body_byte_rev_index,
             body_item_rev_count,
             error_code,
@@ -1497,6 +1653,150 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
         config
     }
 
+    /// Assigns the checks of block_level against `max_block_level` and zero
+    fn assign_block_level_bounds(
+        &self,
+        region: &mut Region<F>,
+        assign_offset: usize,
+    ) -> Result<(), Error> {
+        let block_level = self.config.shared_state.read().block_level as u64;
+        self.config
+            .block_level_max_lt_chip
+            .assign(
+                region,
+                assign_offset,
+                F::from(block_level),
+                F::from(self.config.max_block_level + 1),
+            )
+            .map_err(remap_error(Error::FatalAssignExternalChip))?;
+        self.config
+            .block_level_positive_lt_chip
+            .assign(region, assign_offset, F::zero(), F::from(block_level))
+            .map_err(remap_error(Error::FatalAssignExternalChip))?;
+        Ok(())
+    }
+
+    /// Assigns the check of the declared func body size (at its last leb byte) against
+    /// `max_func_body_size`
+    fn assign_func_body_size(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        assign_delta: AssignDeltaType,
+        func_body_size: u64,
+    ) -> Result<(), Error> {
+        self.config
+            .func_body_size_lt_chip
+            .assign(
+                region,
+                offset + assign_delta,
+                F::from(func_body_size),
+                F::from(self.config.max_func_body_size + 1),
+            )
+            .map_err(remap_error(Error::FatalAssignExternalChip))?;
+        if func_body_size > self.config.max_func_body_size {
+            return Err(Error::FuncBodySizeExceededAt(offset));
+        }
+        Ok(())
+    }
+
+    /// Assigns `locals_count` to the rows of `offsets`
+    fn assign_locals_count(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        offsets: Range<usize>,
+        assign_delta: AssignDeltaType,
+        locals_count: u64,
+    ) -> Result<(), Error> {
+        for offset in offsets {
+            self.assign(
+                region,
+                wb,
+                offset,
+                assign_delta,
+                &[AssignType::LocalsCount],
+                locals_count,
+                None,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Assigns the check of the locals count (at the last leb byte of a local repetition count)
+    /// against `max_func_locals`
+    fn assign_locals_count_limit(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        offset: usize,
+        assign_delta: AssignDeltaType,
+        locals_count: u64,
+    ) -> Result<(), Error> {
+        self.assign_locals_count(region, wb, offset..offset + 1, assign_delta, locals_count)?;
+        self.config
+            .locals_count_lt_chip
+            .assign(
+                region,
+                offset + assign_delta,
+                F::from(locals_count),
+                F::from(self.config.max_func_locals + 1),
+            )
+            .map_err(remap_error(Error::FatalAssignExternalChip))?;
+        if locals_count > self.config.max_func_locals {
+            return Err(Error::FuncLocalsExceededAt(offset));
+        }
+        Ok(())
+    }
+
+    /// Skips the SIMD instruction at `wb_offset` (validation-only) or returns
+    /// [`Error::SimdInstructionAt`], returns new offset
+    fn markup_simd_instruction(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+        block_opcode_number: u64,
+    ) -> Result<usize, Error> {
+        let simd_instruction_len = simd_instruction_len(&wb.bytes, wb_offset)
+            .map_err(|_| Error::ParseOpcodeFailedAt(wb_offset))?;
+        if !self.config.skip_simd_instructions {
+            return Err(Error::SimdInstructionAt(wb_offset));
+        }
+        self.assign(
+            region,
+            wb,
+            wb_offset,
+            assign_delta,
+            &[AssignType::IsSimdInstruction],
+            1,
+            None,
+        )?;
+        for offset in wb_offset + 1..wb_offset + simd_instruction_len {
+            self.assign(
+                region,
+                wb,
+                offset,
+                assign_delta,
+                &[AssignType::IsSimdInstructionOperand],
+                1,
+                None,
+            )?;
+        }
+        self.markup_code_blocks(
+            region,
+            &wb,
+            wb_offset,
+            assign_delta,
+            simd_instruction_len,
+            block_opcode_number,
+            None,
+        )?;
+
+        Ok(wb_offset + simd_instruction_len)
+    }
+
     /// returns new offset
     fn markup_instruction_section(
         &self,
@@ -1510,8 +1810,19 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
 
         let opcode = wb.bytes[offset];
 
+        if opcode == WASM_SIMD_INSTRUCTION_PREFIX {
+            return self.markup_simd_instruction(
+                region,
+                wb,
+                offset,
+                assign_delta,
+                *block_opcode_number,
+            );
+        }
+
         let mut assign_type = AssignType::Unknown;
         let mut assign_type_argument = AssignType::Unknown;
+        let mut is_branch = false;
 
         if let Ok(opcode) = <u8 as TryInto<NumericInstruction>>::try_into(opcode) {
             assign_type = AssignType::IsNumericInstruction;
@@ -1531,11 +1842,15 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             assign_type = AssignType::IsControlInstruction;
             if CONTROL_INSTRUCTION_BLOCK.contains(&opcode) {
                 assign_type_argument = AssignType::IsBlocktypeDelimiter;
-                self.shared_state().borrow_mut().block_level_inc();
+                self.shared_state().write().block_level_inc();
+                if self.shared_state().read().block_level as u64 > self.config.max_block_level {
+                    return Err(Error::BlockLevelExceededAt(offset));
+                }
             }
             if CONTROL_INSTRUCTION_WITH_LEB_ARG.contains(&opcode) {
                 assign_type_argument = AssignType::IsControlInstructionLebArg
             }
+            is_branch = [ControlInstruction::Br, ControlInstruction::BrIf].contains(&opcode);
 
             match opcode {
                 ControlInstruction::Block => {
@@ -1596,7 +1911,10 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
 
         if opcode == WASM_BLOCK_END {
             assign_type = AssignType::IsBlockEnd;
-            self.shared_state().borrow_mut().block_level_dec();
+            if self.shared_state().read().block_level == 0 {
+                return Err(Error::ParseOpcodeFailedAt(offset));
+            }
+            self.shared_state().write().block_level_dec();
 
             *block_opcode_number += 1;
             self.markup_code_blocks(
@@ -1672,22 +1990,16 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 *block_opcode_number,
                 None,
             )?;
-            let block_level = self.config.shared_state.borrow().block_level;
-            debug!(
-                "assign at {} block_level_lt_chip instr_arg_val {} block_level {}",
+            let block_level = self.config.shared_state.read().block_level;
+            if is_branch && instr_arg_val >= block_level as u64 {
+                return Err(Error::BranchDepthExceededAt(offset));
+            }
+            self.assign_branch_depth(
+                region,
+                &self.config.block_level_lt_chip,
                 offset + assign_delta,
                 instr_arg_val,
-                block_level,
-            );
-            self.config
-                .block_level_lt_chip
-                .assign(
-                    region,
-                    offset + assign_delta,
-                    F::from(instr_arg_val),
-                    F::from(block_level as u64),
-                )
-                .map_err(remap_error(Error::FatalAssignExternalChip))?;
+            )?;
             offset += inst_arg_leb_len;
         }
 
@@ -1795,7 +2107,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             body_item_rev_count,
             None,
         )?;
-        self.config.shared_state.borrow_mut().func_count += funcs_count as usize;
+        self.config.shared_state.write().func_count += funcs_count as usize;
         self.assign(
             region,
             &wb,
@@ -1810,7 +2122,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
         for _func_index in 0..funcs_count {
             body_item_rev_count -= 1;
             // is_func_body_len+
-            self.config.shared_state.borrow_mut().block_level_inc();
+            self.config.shared_state.write().block_level_inc();
             let (func_body_len, func_body_len_leb_len) = self.markup_leb_section(
                 region,
                 wb,
@@ -1827,6 +2139,13 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 block_opcode_number,
                 None,
             )?;
+            let func_body_len_last_byte_offset = offset + func_body_len_leb_len - 1;
+            self.assign_func_body_size(
+                region,
+                func_body_len_last_byte_offset,
+                assign_delta,
+                func_body_len,
+            )?;
             let func_body_end_offset =
                 offset + func_body_len_leb_len + (func_body_len as usize) - 1;
             for offset in offset..=func_body_end_offset {
@@ -1840,7 +2159,6 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     None,
                 )?;
             }
-            let func_body_len_last_byte_offset = offset + func_body_len_leb_len - 1;
             for offset in func_body_len_last_byte_offset..=func_body_end_offset {
                 self.assign(
                     region,
@@ -1852,6 +2170,8 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     None,
                 )?;
             }
+            let mut locals_count: u64 = 0;
+            let mut locals_count_from_offset = offset;
             offset += func_body_len_leb_len;
 
             //  locals{1}(is_local_type_transitions_count+ ...
@@ -1876,7 +2196,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
 
             for _is_valtype_transition_index in 0..is_local_type_transitions_count {
                 // -> local_var_descriptor+(is_local_repetition_count+ ...
-                let (_is_local_repetition_count, is_local_repetition_count_leb_len) = self
+                let (local_repetition_count, is_local_repetition_count_leb_len) = self
                     .markup_leb_section(
                         region,
                         wb,
@@ -1893,6 +2213,24 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     block_opcode_number,
                     None,
                 )?;
+                let local_repetition_count_last_byte_offset =
+                    offset + is_local_repetition_count_leb_len - 1;
+                self.assign_locals_count(
+                    region,
+                    wb,
+                    locals_count_from_offset..local_repetition_count_last_byte_offset,
+                    assign_delta,
+                    locals_count,
+                )?;
+                locals_count += local_repetition_count;
+                self.assign_locals_count_limit(
+                    region,
+                    wb,
+                    local_repetition_count_last_byte_offset,
+                    assign_delta,
+                    locals_count,
+                )?;
+                locals_count_from_offset = local_repetition_count_last_byte_offset;
                 offset += is_local_repetition_count_leb_len;
 
                 // is_local_type{1}
@@ -1917,7 +2255,18 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 offset += 1;
             }
 
+            self.assign_locals_count(
+                region,
+                wb,
+                locals_count_from_offset..func_body_end_offset + 1,
+                assign_delta,
+                locals_count,
+            )?;
+
             while offset <= func_body_end_offset {
+                if self.shared_state().read().block_level == 0 {
+                    return Err(Error::ParseOpcodeFailedAt(offset));
+                }
                 offset = self.markup_instruction_section(
                     region,
                     wb,
@@ -1926,6 +2275,10 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     &mut block_opcode_number,
                 )?;
             }
+            // every block opened inside the func body must be closed by its own End
+            if self.shared_state().read().block_level != 0 {
+                return Err(Error::ParseOpcodeFailedAt(func_body_end_offset));
+            }
         }
 
         if offset != wb_offset {
@@ -1,18 +1,26 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
-    circuit::{Layouter, SimpleFloorPlanner},
+    circuit::{Layouter, SimpleFloorPlanner, Value},
     plonk::{Circuit, ConstraintSystem, Error},
 };
 
-use eth_types::{Field, Hash, ToWord};
+use eth_types::{Field, Hash, ToScalar, ToWord};
 
-use crate::wasm_circuit::{
-    bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
-    leb128::circuit::LEB128Chip,
-    sections::code::body::circuit::WasmCodeSectionBodyChip,
-    tables::dynamic_indexes::circuit::DynamicIndexesChip,
-    types::SharedState,
+use crate::{
+    table::WasmFunctionTable,
+    wasm_circuit::{
+        bytecode::{
+            bytecode::{decode_function_locals, WasmBytecode},
+            bytecode_table::WasmBytecodeTable,
+        },
+        common::ErrorCodeColumns,
+        consts::{MAX_BLOCK_LEVEL, MAX_FUNC_BODY_SIZE, MAX_FUNC_LOCALS},
+        leb128::circuit::LEB128Chip,
+        sections::code::body::circuit::WasmCodeSectionBodyChip,
+        tables::{dynamic_indexes::circuit::DynamicIndexesChip, opcode::config::OpcodeTableConfig},
+        types::SharedStateRef,
+    },
 };
 
 #[derive(Default)]
@@ -20,13 +28,19 @@ struct TestCircuit<'a, F> {
     code_hash: Hash,
     bytecode: &'a [u8],
     offset_start: usize,
+    /// added to the locals count of the first function loaded into the function table
+    num_locals_delta: u64,
+    /// (offset, value) of a block_level assigned over the computed one
+    block_level_override: Option<(usize, u64)>,
     _marker: PhantomData<F>,
 }
 
 #[derive(Clone)]
 struct TestCircuitConfig<F: Field> {
-    body_chip: Rc<WasmCodeSectionBodyChip<F>>,
-    wb_table: Rc<WasmBytecodeTable>,
+    body_chip: Arc<WasmCodeSectionBodyChip<F>>,
+    wb_table: Arc<WasmBytecodeTable>,
+    opcode_table_config: Arc<OpcodeTableConfig<F>>,
+    function_table: WasmFunctionTable,
     _marker: PhantomData<F>,
 }
 
@@ -39,25 +53,28 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
     }
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, false));
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, false));
         let func_count = cs.advice_column();
-        let error_code = cs.advice_column();
+        let error_code = ErrorCodeColumns::configure(cs);
         let bytecode_number = cs.advice_column();
         let body_byte_rev_index = cs.advice_column();
         let body_item_rev_count = cs.advice_column();
 
-        let shared_state = Rc::new(RefCell::new(SharedState::default()));
+        let shared_state = SharedStateRef::default();
 
         let dynamic_indexes_config = DynamicIndexesChip::configure(cs, shared_state.clone());
-        let dynamic_indexes_chip = Rc::new(DynamicIndexesChip::construct(dynamic_indexes_config));
+        let dynamic_indexes_chip = Arc::new(DynamicIndexesChip::construct(dynamic_indexes_config));
 
         let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
-        let leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
+        let leb128_chip = Arc::new(LEB128Chip::construct(leb128_config));
+
+        let opcode_table_config = Arc::new(OpcodeTableConfig::configure(cs));
 
         let wasm_code_section_body_config = WasmCodeSectionBodyChip::configure(
             cs,
             wb_table.clone(),
             leb128_chip.clone(),
+            &opcode_table_config,
             dynamic_indexes_chip.clone(),
             func_count,
             shared_state.clone(),
@@ -65,12 +82,24 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
             body_item_rev_count,
             error_code,
             bytecode_number,
+            MAX_BLOCK_LEVEL,
+            MAX_FUNC_BODY_SIZE,
+            MAX_FUNC_LOCALS,
+            false,
+        );
+        let function_table = WasmFunctionTable::construct(cs);
+        function_table.configure_code_section_lookup(
+            cs,
+            &wb_table,
+            &wasm_code_section_body_config,
         );
         let wasm_code_section_body_chip =
             WasmCodeSectionBodyChip::construct(wasm_code_section_body_config);
         let test_circuit_config = TestCircuitConfig {
-            body_chip: Rc::new(wasm_code_section_body_chip),
+            body_chip: Arc::new(wasm_code_section_body_chip),
             wb_table: wb_table.clone(),
+            opcode_table_config,
+            function_table,
             _marker: Default::default(),
         };
 
@@ -93,6 +122,7 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
                 },
             )
             .unwrap();
+        config.opcode_table_config.load(&mut layouter)?;
         layouter.assign_region(
             || "wasm_code_section_body region",
             |mut region| {
@@ -103,10 +133,34 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
                         .assign_auto(&mut region, &wb, offset_start, assign_delta)
                         .unwrap();
                 }
+                if let Some((offset, block_level)) = self.block_level_override {
+                    region.assign_advice(
+                        || format!("override block_level at {}", offset),
+                        config.body_chip.config.block_level,
+                        offset,
+                        || Value::known(F::from(block_level)),
+                    )?;
+                }
 
                 Ok(())
             },
         )?;
+        let mut function_locals = if wb.bytes.is_empty() {
+            vec![]
+        } else {
+            decode_function_locals(&wb.bytes, self.offset_start, 0).unwrap()
+        };
+        if let Some((_, num_locals)) = function_locals.first_mut() {
+            *num_locals += self.num_locals_delta;
+        }
+        let code_hash = Value::known(wb.code_hash.to_scalar().unwrap());
+        // the type index is only constrained by the function section lookup
+        config.function_table.load(
+            &mut layouter,
+            function_locals
+                .into_iter()
+                .map(|(function_index, num_locals)| (code_hash, function_index, num_locals, 0)),
+        )?;
 
         Ok(())
     }
@@ -149,7 +203,7 @@ mod wasm_code_section_body_tests {
             code_hash,
             bytecode: &bytecode,
             offset_start: 0,
-            _marker: Default::default(),
+            ..Default::default()
         };
         test(test_circuit, true);
     }
@@ -168,7 +222,7 @@ mod wasm_code_section_body_tests {
             code_hash,
             bytecode: &bytecode,
             offset_start: 0,
-            _marker: Default::default(),
+            ..Default::default()
         };
         test(test_circuit, true);
     }
@@ -187,8 +241,94 @@ mod wasm_code_section_body_tests {
             code_hash,
             bytecode: &bytecode,
             offset_start: 0,
-            _marker: Default::default(),
+            ..Default::default()
         };
         test(test_circuit, true);
     }
+
+    #[cfg(feature = "tail-call")]
+    #[test]
+    pub fn return_call_instruction_ok() {
+        // one function: no locals, return_call 0, end
+        let bytecode = vec![0x01, 0x04, 0x00, 0x12, 0x00, 0x0b];
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            ..Default::default()
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn empty_body_ok() {
+        // one function: no locals, end
+        let bytecode = vec![0x01, 0x02, 0x00, 0x0b];
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            ..Default::default()
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn function_locals_ok() {
+        // one function: 2 i32 locals and an otherwise empty body
+        let bytecode = vec![0x01, 0x04, 0x01, 0x02, 0x7f, 0x0b];
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            ..Default::default()
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn nested_blocks_ok() {
+        // one function: block loop end end
+        let bytecode = vec![0x01, 0x08, 0x00, 0x02, 0x40, 0x03, 0x40, 0x0b, 0x0b, 0x0b];
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            ..Default::default()
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn block_level_change_off_block_opcode_fails() {
+        let bytecode = vec![0x01, 0x08, 0x00, 0x02, 0x40, 0x03, 0x40, 0x0b, 0x0b, 0x0b];
+        let code_hash = CodeDB::hash(&bytecode);
+        // the blocktype delimiter of the block opcode keeps block_level 2
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            block_level_override: Some((4, 3)),
+            ..Default::default()
+        };
+        test(test_circuit, false);
+    }
+
+    #[test]
+    pub fn wrong_function_locals_fails() {
+        let bytecode = vec![0x01, 0x04, 0x01, 0x02, 0x7f, 0x0b];
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            num_locals_delta: 1,
+            ..Default::default()
+        };
+        test(test_circuit, false);
+    }
 }
@@ -16,11 +16,14 @@ pub enum AssignType {
     IsControlInstruction,
     IsControlInstructionLebArg,
     IsParametricInstruction,
+    IsSimdInstruction,
+    IsSimdInstructionOperand,
     IsBlocktypeDelimiter,
     IsBlockEnd,
 
     BodyByteRevIndex,
     BodyItemRevCount,
+    LocalsCount,
 
     BlockOpcodeIndex,
 
@@ -1,4 +1,6 @@
-#[derive(Copy, Clone, Default, Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Default, Debug, Serialize, Deserialize)]
 pub struct LebParams {
     pub is_signed: bool,
     pub byte_rel_offset: usize,
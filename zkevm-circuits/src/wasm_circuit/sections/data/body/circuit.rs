@@ -1,16 +1,16 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, ops::Range, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Region, Value},
     plonk::{Advice, Column, ConstraintSystem, Fixed},
     poly::Rotation,
 };
-use itertools::Itertools;
 use log::debug;
 
 use eth_types::Field;
 use gadgets::{
     binary_number::BinaryNumberChip,
+    less_than::{LtChip, LtInstruction},
     util::{and, not, or, Expr},
 };
 
@@ -19,33 +19,35 @@ use crate::{
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
-            configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip, WasmErrorAwareChip,
-            WasmFuncCountAwareChip, WasmLenPrefixedBytesSpanAwareChip,
-            WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
+            ErrorCodeColumns, SectionBodyChip, SectionBodyChipResources, SectionSelectors,
+            SectionTransition, WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip,
+            WasmErrorAwareChip, WasmFuncCountAwareChip, WasmLenPrefixedBytesSpanAwareChip,
+            WasmMarkupLeb128SectionAwareChip, WasmSectionTransitionsAwareChip,
+            WasmSharedStateAwareChip,
         },
-        consts::WASM_BLOCK_END,
+        consts::{MAX_DATA_SEGMENT_SIZE, MAX_DATA_SIZE, WASM_BLOCK_END},
         error::{
             remap_error, remap_error_to_assign_at, remap_error_to_invalid_enum_value_at, Error,
         },
         leb128::circuit::LEB128Chip,
         sections::{consts::LebParams, data::body::types::AssignType},
-        tables::dynamic_indexes::{
-            circuit::DynamicIndexesChip,
-            types::{LookupArgsParams, Tag},
+        tables::{
+            byte_set::{config::ByteSetTableConfig, types::ByteSetTag},
+            dynamic_indexes::{
+                circuit::DynamicIndexesChip,
+                types::{LookupArgsParams, Tag},
+            },
         },
         types::{
             AssignDeltaType, AssignValueType, MemSegmentType, NewWbOffsetType, NumericInstruction,
-            SharedState,
+            SharedStateRef, WasmSection,
         },
     },
 };
 
 #[derive(Debug, Clone)]
 pub struct WasmDataSectionBodyConfig<F: Field> {
-    pub q_enable: Column<Fixed>,
-    pub q_first: Column<Fixed>,
-    pub q_last: Column<Fixed>,
+    pub selectors: SectionSelectors,
     pub is_items_count: Column<Fixed>,
     pub is_memidx: Column<Fixed>,
     pub is_mem_segment_type: Column<Fixed>,
@@ -57,18 +59,26 @@ pub struct WasmDataSectionBodyConfig<F: Field> {
 
     pub is_mem_segment_type_ctx: Column<Fixed>,
 
-    pub leb128_chip: Rc<LEB128Chip<F>>,
-    pub dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+    pub leb128_chip: Arc<LEB128Chip<F>>,
+    pub dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
     pub mem_segment_type: Column<Advice>,
-    pub mem_segment_type_chip: Rc<BinaryNumberChip<F, MemSegmentType, 8>>,
+    pub mem_segment_type_chip: Arc<BinaryNumberChip<F, MemSegmentType, 2>>,
+    /// segment len must not exceed `max_data_segment_size`
+    pub max_data_segment_size: u64,
+    pub mem_segment_len_lt_chip: Arc<LtChip<F, 4>>,
+    /// total len of the segments declared so far
+    pub data_segments_size: Column<Advice>,
+    /// total len of the segments must not exceed `max_data_size`
+    pub max_data_size: u64,
+    pub data_segments_size_lt_chip: Arc<LtChip<F, 5>>,
 
     func_count: Column<Advice>,
     body_byte_rev_index: Column<Advice>,
     body_item_rev_count: Column<Advice>,
 
-    error_code: Column<Advice>,
+    error_code: ErrorCodeColumns,
 
-    shared_state: Rc<RefCell<SharedState>>,
+    shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -87,18 +97,56 @@ impl<F: Field> WasmCountPrefixedItemsAwareChip<F> for WasmDataSectionBodyChip<F>
 
 impl<F: Field> WasmLenPrefixedBytesSpanAwareChip<F> for WasmDataSectionBodyChip<F> {}
 
+impl<F: Field> WasmSectionTransitionsAwareChip<F> for WasmDataSectionBodyChip<F> {}
+
 impl<F: Field> WasmErrorAwareChip<F> for WasmDataSectionBodyChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmDataSectionBodyChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
 
+impl<F: Field> SectionBodyChip<F> for WasmDataSectionBodyChip<F> {
+    const SECTION: WasmSection = WasmSection::Data;
+
+    fn configure_with_resources(
+        cs: &mut ConstraintSystem<F>,
+        resources: &SectionBodyChipResources<F>,
+    ) -> Self {
+        let config = Self::configure(
+            cs,
+            resources.wb_table.clone(),
+            resources.leb128_chip.clone(),
+            &resources.byte_set_table_config,
+            resources.dynamic_indexes_chip.clone(),
+            resources.func_count,
+            resources.shared_state.clone(),
+            resources.body_byte_rev_index_l2,
+            resources.body_item_rev_count_l1,
+            resources.error_code,
+            resources.bytecode_number,
+            MAX_DATA_SEGMENT_SIZE,
+            MAX_DATA_SIZE,
+        );
+        Self::construct(config)
+    }
+
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error> {
+        self.assign_auto(region, wb, wb_offset, assign_delta)
+    }
+}
+
 impl<F: Field> WasmFuncCountAwareChip<F> for WasmDataSectionBodyChip<F> {
     fn func_count_col(&self) -> Column<Advice> {
         self.config.func_count
@@ -129,14 +177,9 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmDataSectionBodyChip<F> {
             wb.bytes[wb_offset],
             leb_params,
         );
-        region
-            .assign_fixed(
-                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
-                self.config.q_enable,
-                assign_offset,
-                || Value::known(F::from(q_enable as u64)),
-            )
-            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.config
+            .selectors
+            .assign_q_enable(region, assign_offset, q_enable)?;
         self.assign_func_count(region, assign_offset)?;
 
         for assign_type in assign_types {
@@ -154,29 +197,14 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmDataSectionBodyChip<F> {
             }
             match assign_type {
                 AssignType::QFirst => {
-                    region
-                        .assign_fixed(
-                            || {
-                                format!(
-                                    "assign 'q_first' val {} at {}",
-                                    assign_value, assign_offset
-                                )
-                            },
-                            self.config.q_first,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_first(region, assign_offset, assign_value)?;
                 }
                 AssignType::QLast => {
-                    region
-                        .assign_fixed(
-                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
-                            self.config.q_last,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_last(region, assign_offset, assign_value)?;
                 }
                 AssignType::IsItemsCount => {
                     region
@@ -365,6 +393,21 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmDataSectionBodyChip<F> {
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
+                AssignType::DataSegmentsSize => {
+                    region
+                        .assign_advice(
+                            || {
+                                format!(
+                                    "assign 'data_segments_size' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.data_segments_size,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
                 AssignType::ErrorCode => {
                     self.assign_error_code(region, assign_offset, None)?;
                 }
@@ -385,19 +428,21 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        wb_table: Rc<WasmBytecodeTable>,
-        leb128_chip: Rc<LEB128Chip<F>>,
-        dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+        wb_table: Arc<WasmBytecodeTable>,
+        leb128_chip: Arc<LEB128Chip<F>>,
+        byte_set_table_config: &ByteSetTableConfig<F>,
+        dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
         func_count: Column<Advice>,
-        shared_state: Rc<RefCell<SharedState>>,
+        shared_state: SharedStateRef,
         body_byte_rev_index: Column<Advice>,
         body_item_rev_count: Column<Advice>,
-        error_code: Column<Advice>,
+        error_code: ErrorCodeColumns,
         bytecode_number: Column<Advice>,
+        max_data_segment_size: u64,
+        max_data_size: u64,
     ) -> WasmDataSectionBodyConfig<F> {
-        let q_enable = cs.fixed_column();
-        let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        let selectors = SectionSelectors::configure(cs);
+        let SectionSelectors { q_enable, q_last, .. } = selectors;
         let is_items_count = cs.fixed_column();
         let is_memidx = cs.fixed_column();
         let is_mem_segment_type = cs.fixed_column();
@@ -412,7 +457,35 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
 
         let config =
             BinaryNumberChip::configure(cs, is_mem_segment_type_ctx, Some(mem_segment_type.into()));
-        let mem_segment_type_chip = Rc::new(BinaryNumberChip::construct(config));
+        let mem_segment_type_chip = Arc::new(BinaryNumberChip::construct(config));
+
+        let data_segments_size = cs.advice_column();
+        let config = LtChip::configure(
+            cs,
+            |vc| {
+                and::expr([
+                    vc.query_fixed(q_enable, Rotation::cur()),
+                    vc.query_fixed(is_mem_segment_len, Rotation::cur()),
+                    vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur()),
+                ])
+            },
+            |vc| vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
+            |_| (max_data_segment_size + 1).expr(),
+        );
+        let mem_segment_len_lt_chip = Arc::new(LtChip::construct(config));
+        let config = LtChip::configure(
+            cs,
+            |vc| {
+                and::expr([
+                    vc.query_fixed(q_enable, Rotation::cur()),
+                    vc.query_fixed(is_mem_segment_len, Rotation::cur()),
+                    vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur()),
+                ])
+            },
+            |vc| vc.query_advice(data_segments_size, Rotation::cur()),
+            |_| (max_data_size + 1).expr(),
+        );
+        let data_segments_size_lt_chip = Arc::new(LtChip::construct(config));
 
         dynamic_indexes_chip.lookup_args(
             "data section has valid setup for data indexes",
@@ -423,7 +496,7 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
                     * Self::get_selector_expr_enriched_with_error_processing(
                         vc,
                         q_enable,
-                        &shared_state.borrow(),
+                        &shared_state.read(),
                         error_code,
                     );
                 LookupArgsParams {
@@ -475,7 +548,7 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
@@ -486,12 +559,28 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
             |vc| vc.query_fixed(q_last, Rotation::cur()),
         );
 
+        byte_set_table_config.lookup_byte(
+            cs,
+            "is_mem_segment_type -> byte value is valid",
+            ByteSetTag::MemSegmentType,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                );
+                (
+                    q_enable_expr * vc.query_fixed(is_mem_segment_type, Rotation::cur()),
+                    vc.query_advice(wb_table.value, Rotation::cur()),
+                )
+            },
+        );
+
         cs.create_gate("WasmDataSectionBody gate", |vc| {
             let mut cb = BaseConstraintBuilder::default();
 
-            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
-            let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
-            let not_q_last_expr = not::expr(q_last_expr.clone());
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.read(), error_code);
             let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
             let is_mem_segment_type_expr = vc.query_fixed(is_mem_segment_type, Rotation::cur());
             let is_mem_index_expr = vc.query_fixed(is_memidx, Rotation::cur());
@@ -507,9 +596,6 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
             let byte_val_expr = vc.query_advice(wb_table.value, Rotation::cur());
             let mem_segment_type_expr = vc.query_advice(mem_segment_type, Rotation::cur());
 
-            let leb128_is_last_byte_expr = vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
-
-            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_items_count is boolean", is_items_count_expr.clone());
             cb.require_boolean("is_mem_segment_type is boolean", is_mem_segment_type_expr.clone());
             cb.require_boolean("is_mem_index is boolean", is_mem_index_expr.clone());
@@ -549,13 +635,10 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
                 }
             );
 
-            configure_constraints_for_q_first_and_q_last(
+            selectors.configure_constraints(
                 &mut cb,
                 vc,
-                &q_enable,
-                &q_first,
                 &[is_items_count],
-                &q_last,
                 &[is_mem_segment_len, is_mem_segment_bytes],
             );
 
@@ -605,371 +688,141 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
             let mem_segment_type_is_active_expr = mem_segment_type_chip.config.value_equals(MemSegmentType::Active, Rotation::cur())(vc);
             let mem_segment_type_is_passive_expr = mem_segment_type_chip.config.value_equals(MemSegmentType::Passive, Rotation::cur())(vc);
             let mem_segment_type_is_active_variadic_expr = mem_segment_type_chip.config.value_equals(MemSegmentType::ActiveVariadic, Rotation::cur())(vc);
-            // constraints for is_mem_segment_type{1}=MemSegmentType::Active:
-            // is_items_count+ -> item+ (is_mem_segment_type{1} -> is_mem_segment_size_opcode{1} -> is_mem_segment_size+ -> is_block_end{1} -> is_mem_segment_len+ -> is_mem_segment_bytes*)
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_items_count+ -> item+ (is_mem_segment_type{1} ...",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_items_count_expr.clone(),
-                    mem_segment_type_is_active_expr.clone(),
-                ]),
-                true,
-                &[is_items_count, is_mem_segment_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_items_count+ -> item+ (is_mem_segment_type{1} ...",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_items_count_expr.clone(),
-                    mem_segment_type_is_active_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_segment_type{1} -> is_mem_segment_size_opcode{1}",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_type_expr.clone(),
-                    mem_segment_type_is_active_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_size_opcode],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_segment_size_opcode{1} -> is_mem_segment_size+",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_size_opcode_expr.clone(),
-                    mem_segment_type_is_active_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_size],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_segment_size+ -> is_block_end{1}",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_size_expr.clone(),
-                    mem_segment_type_is_active_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_size, is_block_end],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_mem_segment_size+ -> is_block_end{1}",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_size_expr.clone(),
-                    mem_segment_type_is_active_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                ]),
-                true,
-                &[is_block_end],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_block_end{1} -> is_mem_segment_len+",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_block_end_expr.clone(),
-                    mem_segment_type_is_active_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_len],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_segment_len+ -> is_mem_segment_bytes*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_len_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_len, is_mem_segment_bytes, is_mem_segment_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_mem_segment_len+ -> is_mem_segment_bytes*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_len_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_bytes, is_mem_segment_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_segment_bytes*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_bytes_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_bytes, is_mem_segment_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_mem_segment_bytes*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_bytes_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_type],
-            );
-            // constraints for is_mem_segment_type{1}=MemSegmentType::Passive:
-            // is_items_count+ -> item+ (is_mem_segment_len{1} -> is_mem_segment_bytes*
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_items_count+ -> item+ (is_mem_segment_len{1} ...",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_items_count_expr.clone(),
-                    mem_segment_type_is_passive_expr.clone(),
-                ]),
-                true,
-                &[is_items_count, is_mem_segment_len],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_items_count+ -> item+ (is_mem_segment_len{1} ...",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_items_count_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                    mem_segment_type_is_passive_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_len],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_segment_len+ -> is_mem_segment_bytes*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_len_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_len, is_mem_segment_bytes, is_mem_segment_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_mem_segment_len+ -> is_mem_segment_bytes*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                    is_mem_segment_len_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_bytes, is_mem_segment_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_segment_bytes*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_bytes_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_bytes, is_mem_segment_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_mem_segment_bytes*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                    is_mem_segment_bytes_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_type],
-            );
-            // constraints for is_mem_segment_type{1}=MemSegmentType::ActiveVariadic:
-            //  is_items_count+ -> item+ (is_mem_segment_type{1} -> is_mem_index+ -> is_mem_segment_size_opcode{1} -> is_mem_segment_size+ -> is_block_end{1} -> is_mem_segment_len+ -> is_mem_segment_bytes*)
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_items_count+ -> item+ (is_mem_segment_type{1} ...",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_items_count_expr.clone(),
-                    mem_segment_type_is_active_variadic_expr.clone(),
-                ]),
-                true,
-                &[is_items_count, is_mem_segment_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_items_count+ -> item+ (is_mem_segment_type{1} ...",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                    is_items_count_expr.clone(),
-                    mem_segment_type_is_active_variadic_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_segment_type{1} -> is_mem_index+",
-                and::expr([
-                    is_mem_segment_type_expr.clone(),
-                    mem_segment_type_is_active_variadic_expr.clone(),
-                    not_q_last_expr.clone(),
-                ]),
-                true,
-                &[is_memidx],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_index+ -> is_mem_segment_size_opcode{1}",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_index_expr.clone(),
-                    mem_segment_type_is_active_variadic_expr.clone(),
-                ]),
-                true,
-                &[is_memidx, is_mem_segment_size_opcode],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_mem_index+ -> is_mem_segment_size_opcode{1}",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                    is_mem_index_expr.clone(),
-                    mem_segment_type_is_active_variadic_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_size_opcode],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_segment_size_opcode{1} -> is_mem_segment_size+",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_size_opcode_expr.clone(),
-                    mem_segment_type_is_active_variadic_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_size],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_segment_size+ -> is_block_end{1}",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_size_expr.clone(),
-                    mem_segment_type_is_active_variadic_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_size, is_block_end],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_mem_segment_size+ -> is_block_end{1}",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                    is_mem_segment_size_expr.clone(),
-                    mem_segment_type_is_active_variadic_expr.clone(),
-                ]),
-                true,
-                &[is_block_end],
-            );
-            configure_transition_check(
+            Self::configure_transitions(
                 &mut cb,
                 vc,
-                "check next: is_block_end{1} -> is_mem_segment_len+",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_block_end_expr.clone(),
-                    mem_segment_type_is_active_variadic_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_len],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_segment_len+ -> is_mem_segment_bytes*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_len_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_len, is_mem_segment_bytes, is_mem_segment_type],
+                q_last,
+                leb128_chip.config.is_last_byte,
+                vec![
+                    // constraints for is_mem_segment_type{1}=MemSegmentType::Active:
+                    // is_items_count+ -> item+ (is_mem_segment_type{1} -> is_mem_segment_size_opcode{1} -> is_mem_segment_size+ -> is_block_end{1} -> is_mem_segment_len+ -> is_mem_segment_bytes*)
+                    SectionTransition::leb(
+                        "check next: is_items_count+ -> item+ (is_mem_segment_type{1} ...",
+                        is_items_count,
+                        &[is_mem_segment_type],
+                    ).when(mem_segment_type_is_active_expr.clone()),
+                    SectionTransition::new(
+                        "check next: is_mem_segment_type{1} -> is_mem_segment_size_opcode{1}",
+                        is_mem_segment_type,
+                        &[is_mem_segment_size_opcode],
+                    ).when(mem_segment_type_is_active_expr.clone()),
+                    SectionTransition::new(
+                        "check next: is_mem_segment_size_opcode{1} -> is_mem_segment_size+",
+                        is_mem_segment_size_opcode,
+                        &[is_mem_segment_size],
+                    ).when(mem_segment_type_is_active_expr.clone()),
+                    SectionTransition::leb(
+                        "check next: is_mem_segment_size+ -> is_block_end{1}",
+                        is_mem_segment_size,
+                        &[is_block_end],
+                    ).when(mem_segment_type_is_active_expr.clone()),
+                    SectionTransition::new(
+                        "check next: is_block_end{1} -> is_mem_segment_len+",
+                        is_block_end,
+                        &[is_mem_segment_len],
+                    ).when(mem_segment_type_is_active_expr.clone()),
+                    // constraints for is_mem_segment_type{1}=MemSegmentType::Passive:
+                    // is_items_count+ -> item+ (is_mem_segment_len{1} -> is_mem_segment_bytes*
+                    SectionTransition::leb(
+                        "check next: is_items_count+ -> item+ (is_mem_segment_len{1} ...",
+                        is_items_count,
+                        &[is_mem_segment_len],
+                    ).when(mem_segment_type_is_passive_expr.clone()),
+                    // constraints for is_mem_segment_type{1}=MemSegmentType::ActiveVariadic:
+                    //  is_items_count+ -> item+ (is_mem_segment_type{1} -> is_mem_index+ -> is_mem_segment_size_opcode{1} -> is_mem_segment_size+ -> is_block_end{1} -> is_mem_segment_len+ -> is_mem_segment_bytes*)
+                    SectionTransition::leb(
+                        "check next: is_items_count+ -> item+ (is_mem_segment_type{1} ...",
+                        is_items_count,
+                        &[is_mem_segment_type],
+                    ).when(mem_segment_type_is_active_variadic_expr.clone()),
+                    SectionTransition::new(
+                        "check next: is_mem_segment_type{1} -> is_mem_index+",
+                        is_mem_segment_type,
+                        &[is_memidx],
+                    ).when(mem_segment_type_is_active_variadic_expr.clone()),
+                    SectionTransition::leb(
+                        "check next: is_mem_index+ -> is_mem_segment_size_opcode{1}",
+                        is_memidx,
+                        &[is_mem_segment_size_opcode],
+                    ).when(mem_segment_type_is_active_variadic_expr.clone()),
+                    SectionTransition::new(
+                        "check next: is_mem_segment_size_opcode{1} -> is_mem_segment_size+",
+                        is_mem_segment_size_opcode,
+                        &[is_mem_segment_size],
+                    ).when(mem_segment_type_is_active_variadic_expr.clone()),
+                    SectionTransition::leb(
+                        "check next: is_mem_segment_size+ -> is_block_end{1}",
+                        is_mem_segment_size,
+                        &[is_block_end],
+                    ).when(mem_segment_type_is_active_variadic_expr.clone()),
+                    SectionTransition::new(
+                        "check next: is_block_end{1} -> is_mem_segment_len+",
+                        is_block_end,
+                        &[is_mem_segment_len],
+                    ).when(mem_segment_type_is_active_variadic_expr.clone()),
+                    // the tail of an item is the same for all the segment types
+                    SectionTransition::leb(
+                        "check next: is_mem_segment_len+ -> is_mem_segment_bytes*",
+                        is_mem_segment_len,
+                        &[is_mem_segment_bytes, is_mem_segment_type],
+                    ),
+                    SectionTransition::leb(
+                        "check next: is_mem_segment_bytes*",
+                        is_mem_segment_bytes,
+                        &[is_mem_segment_type],
+                    ),
+                ],
             );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_mem_segment_len+ -> is_mem_segment_bytes*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                    is_mem_segment_len_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_bytes, is_mem_segment_type],
+
+            // data size limits
+            let data_segments_size_expr = vc.query_advice(data_segments_size, Rotation::cur());
+            let data_segments_size_prev_expr = vc.query_advice(data_segments_size, Rotation::prev());
+            let is_mem_segment_len_last_byte_expr = and::expr([
+                is_mem_segment_len_expr.clone(),
+                vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur()),
+            ]);
+            cb.condition(
+                is_items_count_expr.clone(),
+                |cb| {
+                    cb.require_zero(
+                        "is_items_count => data_segments_size=0",
+                        data_segments_size_expr.clone(),
+                    );
+                }
             );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_mem_segment_bytes*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_mem_segment_bytes_expr.clone(),
-                ]),
-                true,
-                &[is_mem_segment_bytes, is_mem_segment_type],
+            cb.condition(
+                is_mem_segment_len_last_byte_expr.clone(),
+                |cb| {
+                    let mem_segment_len_expr = vc.query_advice(leb128_chip.config.sn, Rotation::cur());
+                    cb.require_equal(
+                        "is_mem_segment_len last byte => mem_segment_len <= max_data_segment_size",
+                        mem_segment_len_lt_chip.config().is_lt(vc, None),
+                        1.expr(),
+                    );
+                    cb.require_equal(
+                        "is_mem_segment_len last byte => data_segments_size=prev.data_segments_size+mem_segment_len",
+                        data_segments_size_expr.clone(),
+                        data_segments_size_prev_expr.clone() + mem_segment_len_expr,
+                    );
+                    cb.require_equal(
+                        "is_mem_segment_len last byte => data_segments_size <= max_data_size",
+                        data_segments_size_lt_chip.config().is_lt(vc, None),
+                        1.expr(),
+                    );
+                }
             );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_mem_segment_bytes*",
+            cb.condition(
                 and::expr([
-                    not_q_last_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                    is_mem_segment_bytes_expr.clone(),
+                    not::expr(is_items_count_expr.clone()),
+                    not::expr(is_mem_segment_len_last_byte_expr.clone()),
                 ]),
-                true,
-                &[is_mem_segment_type],
+                |cb| {
+                    cb.require_equal(
+                        "data_segments_size=prev.data_segments_size",
+                        data_segments_size_expr.clone(),
+                        data_segments_size_prev_expr.clone(),
+                    );
+                }
             );
 
             cb.condition(
@@ -983,20 +836,6 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
                 }
             );
 
-            cb.condition(
-                is_mem_segment_type_expr.clone(),
-                |cb| {
-                    cb.require_in_set(
-                        "is_mem_segment_type -> byte value is valid",
-                        byte_val_expr.clone(),
-                        vec![
-                            MemSegmentType::Active.expr(),
-                            MemSegmentType::Passive.expr(),
-                            MemSegmentType::ActiveVariadic.expr(),
-                        ],
-                    )
-                }
-            );
 
             cb.condition(
                 is_mem_segment_size_opcode_expr.clone(),
@@ -1017,9 +856,7 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
         let config = WasmDataSectionBodyConfig::<F> {
             _marker: PhantomData,
 
-            q_enable,
-            q_first,
-            q_last,
+            selectors,
             is_items_count,
             is_memidx,
             is_mem_segment_type,
@@ -1033,6 +870,11 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
             dynamic_indexes_chip,
             mem_segment_type,
             mem_segment_type_chip,
+            max_data_segment_size,
+            mem_segment_len_lt_chip,
+            data_segments_size,
+            max_data_size,
+            data_segments_size_lt_chip,
             func_count,
             body_byte_rev_index,
             body_item_rev_count,
@@ -1043,6 +885,91 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
         config
     }
 
+    /// Assigns `data_segments_size` to the rows of `offsets`
+    fn assign_data_segments_size(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        offsets: Range<usize>,
+        assign_delta: AssignDeltaType,
+        data_segments_size: u64,
+    ) -> Result<(), Error> {
+        for offset in offsets {
+            self.assign(
+                region,
+                wb,
+                offset,
+                assign_delta,
+                &[AssignType::DataSegmentsSize],
+                data_segments_size,
+                None,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Marks up is_mem_segment_len+ and assigns the checks (at its last leb byte) of the segment
+    /// len against `max_data_segment_size` and of the data segments size against `max_data_size`
+    fn markup_mem_segment_len(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        offset: usize,
+        assign_delta: AssignDeltaType,
+        data_segments_size: &mut u64,
+        data_segments_size_from_offset: &mut usize,
+    ) -> Result<(u64, usize), Error> {
+        let (mem_segment_len, mem_segment_len_leb_len) = self.markup_leb_section(
+            region,
+            wb,
+            offset,
+            assign_delta,
+            &[AssignType::IsMemSegmentLen, AssignType::IsMemSegmentTypeCtx],
+        )?;
+        let last_byte_offset = offset + mem_segment_len_leb_len - 1;
+        self.assign_data_segments_size(
+            region,
+            wb,
+            *data_segments_size_from_offset..last_byte_offset,
+            assign_delta,
+            *data_segments_size,
+        )?;
+        *data_segments_size += mem_segment_len;
+        *data_segments_size_from_offset = last_byte_offset;
+        self.assign_data_segments_size(
+            region,
+            wb,
+            last_byte_offset..last_byte_offset + 1,
+            assign_delta,
+            *data_segments_size,
+        )?;
+        self.config
+            .mem_segment_len_lt_chip
+            .assign(
+                region,
+                last_byte_offset + assign_delta,
+                F::from(mem_segment_len),
+                F::from(self.config.max_data_segment_size + 1),
+            )
+            .map_err(remap_error(Error::FatalAssignExternalChip))?;
+        self.config
+            .data_segments_size_lt_chip
+            .assign(
+                region,
+                last_byte_offset + assign_delta,
+                F::from(*data_segments_size),
+                F::from(self.config.max_data_size + 1),
+            )
+            .map_err(remap_error(Error::FatalAssignExternalChip))?;
+        if mem_segment_len > self.config.max_data_segment_size
+            || *data_segments_size > self.config.max_data_size
+        {
+            return Err(Error::DataSizeExceededAt(last_byte_offset));
+        }
+
+        Ok((mem_segment_len, mem_segment_len_leb_len))
+    }
+
     pub fn assign_auto(
         &self,
         region: &mut Region<F>,
@@ -1051,6 +978,8 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
         assign_delta: AssignDeltaType,
     ) -> Result<NewWbOffsetType, Error> {
         let mut offset = wb_offset;
+        let mut data_segments_size: u64 = 0;
+        let mut data_segments_size_from_offset = offset;
 
         // items_count+
         self.assign(
@@ -1083,12 +1012,12 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
         }
         let dynamic_indexes_offset = self.config.dynamic_indexes_chip.assign_auto(
             region,
-            self.config.shared_state.borrow().dynamic_indexes_offset,
+            self.config.shared_state.read().dynamic_indexes_offset,
             assign_delta,
             items_count as usize,
             Tag::DataIndex,
         )?;
-        self.config.shared_state.borrow_mut().dynamic_indexes_offset = dynamic_indexes_offset;
+        self.config.shared_state.write().dynamic_indexes_offset = dynamic_indexes_offset;
         offset += items_count_leb_len;
 
         for _item_index in 0..items_count {
@@ -1195,12 +1124,13 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
                     offset += 1;
 
                     // is_mem_segment_len+
-                    let (mem_segment_len, mem_segment_len_leb_len) = self.markup_leb_section(
+                    let (mem_segment_len, mem_segment_len_leb_len) = self.markup_mem_segment_len(
                         region,
                         wb,
                         offset,
                         assign_delta,
-                        &[AssignType::IsMemSegmentLen, AssignType::IsMemSegmentTypeCtx],
+                        &mut data_segments_size,
+                        &mut data_segments_size_from_offset,
                     )?;
                     let mem_segment_len_last_byte_offset = offset + mem_segment_len_leb_len - 1;
                     let mem_segment_last_byte_offset =
@@ -1259,12 +1189,13 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
                 }
                 MemSegmentType::Passive => {
                     // is_mem_segment_len+
-                    let (mem_segment_len, mem_segment_len_leb_len) = self.markup_leb_section(
+                    let (mem_segment_len, mem_segment_len_leb_len) = self.markup_mem_segment_len(
                         region,
                         wb,
                         offset,
                         assign_delta,
-                        &[AssignType::IsMemSegmentLen, AssignType::IsMemSegmentTypeCtx],
+                        &mut data_segments_size,
+                        &mut data_segments_size_from_offset,
                     )?;
                     let mem_segment_len_last_byte_offset = offset + mem_segment_len_leb_len - 1;
                     let mem_segment_last_byte_offset =
@@ -1411,12 +1342,13 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
                     offset += 1;
 
                     // is_mem_segment_len+
-                    let (mem_segment_len, mem_segment_len_leb_len) = self.markup_leb_section(
+                    let (mem_segment_len, mem_segment_len_leb_len) = self.markup_mem_segment_len(
                         region,
                         wb,
                         offset,
                         assign_delta,
-                        &[AssignType::IsMemSegmentLen, AssignType::IsMemSegmentTypeCtx],
+                        &mut data_segments_size,
+                        &mut data_segments_size_from_offset,
                     )?;
                     for offset in offset..offset + mem_segment_len_leb_len {
                         self.assign(
@@ -1474,6 +1406,14 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
             }
         }
 
+        self.assign_data_segments_size(
+            region,
+            wb,
+            data_segments_size_from_offset..offset,
+            assign_delta,
+            data_segments_size,
+        )?;
+
         if offset != wb_offset {
             self.assign(
                 region,
@@ -18,6 +18,7 @@ pub enum AssignType {
 
     BodyByteRevIndex,
     BodyItemRevCount,
+    DataSegmentsSize,
 
     ErrorCode,
 }
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Region, Value},
@@ -18,25 +18,29 @@ use crate::{
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
-            configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip, WasmErrorAwareChip,
-            WasmFuncCountAwareChip, WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
+            configure_transition_check, ErrorCodeColumns, SectionBodyChip, SectionBodyChipResources,
+            SectionSelectors, WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip,
+            WasmErrorAwareChip, WasmFuncCountAwareChip, WasmMarkupLeb128SectionAwareChip,
+            WasmSharedStateAwareChip,
         },
+        consts::WASM_BLOCK_END,
         error::{remap_error_to_assign_at, remap_error_to_invalid_enum_value_at, Error},
         leb128::circuit::LEB128Chip,
         sections::{
             consts::LebParams,
             element::body::{consts::ElementType, types::AssignType},
         },
-        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedState},
+        tables::byte_set::{config::ByteSetTableConfig, types::ByteSetTag},
+        types::{
+            AssignDeltaType, AssignValueType, NewWbOffsetType, NumericInstruction,
+            ReferenceInstruction, SharedStateRef, WasmSection,
+        },
     },
 };
 
 #[derive(Debug, Clone)]
 pub struct WasmElementSectionBodyConfig<F: Field> {
-    pub q_enable: Column<Fixed>,
-    pub q_first: Column<Fixed>,
-    pub q_last: Column<Fixed>,
+    pub selectors: SectionSelectors,
     pub is_items_count: Column<Fixed>,
     pub is_elem_type: Column<Fixed>,
     pub is_elem_type_ctx: Column<Fixed>,
@@ -46,19 +50,22 @@ pub struct WasmElementSectionBodyConfig<F: Field> {
     pub is_funcs_idx_count: Column<Fixed>,
     pub is_func_idx: Column<Fixed>,
     pub is_elem_kind: Column<Fixed>,
+    pub is_table_idx: Column<Fixed>,
+    pub is_ref_type: Column<Fixed>,
+    pub is_elem_expr_opcode: Column<Fixed>,
 
     pub elem_type: Column<Advice>,
 
-    pub elem_type_chip: Rc<BinaryNumberChip<F, ElementType, 8>>,
+    pub elem_type_chip: Arc<BinaryNumberChip<F, ElementType, 8>>,
 
-    pub leb128_chip: Rc<LEB128Chip<F>>,
+    pub leb128_chip: Arc<LEB128Chip<F>>,
 
     pub func_count: Column<Advice>,
     body_item_rev_count: Column<Advice>,
 
-    error_code: Column<Advice>,
+    error_code: ErrorCodeColumns,
 
-    shared_state: Rc<RefCell<SharedState>>,
+    shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -76,17 +83,48 @@ impl<F: Field> WasmMarkupLeb128SectionAwareChip<F> for WasmElementSectionBodyChi
 impl<F: Field> WasmCountPrefixedItemsAwareChip<F> for WasmElementSectionBodyChip<F> {}
 
 impl<F: Field> WasmErrorAwareChip<F> for WasmElementSectionBodyChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmElementSectionBodyChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
 
+impl<F: Field> SectionBodyChip<F> for WasmElementSectionBodyChip<F> {
+    const SECTION: WasmSection = WasmSection::Element;
+
+    fn configure_with_resources(
+        cs: &mut ConstraintSystem<F>,
+        resources: &SectionBodyChipResources<F>,
+    ) -> Self {
+        let config = Self::configure(
+            cs,
+            resources.wb_table.clone(),
+            resources.leb128_chip.clone(),
+            &resources.byte_set_table_config,
+            resources.func_count,
+            resources.shared_state.clone(),
+            resources.body_item_rev_count_l1,
+            resources.error_code,
+        );
+        Self::construct(config)
+    }
+
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error> {
+        self.assign_auto(region, wb, wb_offset, assign_delta)
+    }
+}
+
 impl<F: Field> WasmFuncCountAwareChip<F> for WasmElementSectionBodyChip<F> {
     fn func_count_col(&self) -> Column<Advice> {
         self.config.func_count
@@ -113,14 +151,9 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmElementSectionBodyChip<F> {
             "assign at {} q_enable {} assign_types {:?} assign_value {} byte_val {:x?}",
             assign_offset, q_enable, assign_types, assign_value, byte_val,
         );
-        region
-            .assign_fixed(
-                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
-                self.config.q_enable,
-                assign_offset,
-                || Value::known(F::from(q_enable as u64)),
-            )
-            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.config
+            .selectors
+            .assign_q_enable(region, assign_offset, q_enable)?;
         self.assign_func_count(region, assign_offset)?;
 
         for assign_type in assign_types {
@@ -129,6 +162,7 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmElementSectionBodyChip<F> {
                 AssignType::IsNumericInstructionLebArg,
                 AssignType::IsFuncsIdxCount,
                 AssignType::IsFuncIdx,
+                AssignType::IsTableIdx,
             ]
             .contains(&assign_type)
             {
@@ -139,29 +173,14 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmElementSectionBodyChip<F> {
             }
             match assign_type {
                 AssignType::QFirst => {
-                    region
-                        .assign_fixed(
-                            || {
-                                format!(
-                                    "assign 'q_first' val {} at {}",
-                                    assign_value, assign_offset
-                                )
-                            },
-                            self.config.q_first,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_first(region, assign_offset, assign_value)?;
                 }
                 AssignType::QLast => {
-                    region
-                        .assign_fixed(
-                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
-                            self.config.q_last,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_last(region, assign_offset, assign_value)?;
                 }
                 AssignType::IsItemsCount => {
                     region
@@ -293,6 +312,51 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmElementSectionBodyChip<F> {
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
+                AssignType::IsTableIdx => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_table_idx' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_table_idx,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::IsRefType => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_ref_type' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_ref_type,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::IsElemExprOpcode => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_elem_expr_opcode' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_elem_expr_opcode,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
                 AssignType::ElemType => {
                     region
                         .assign_advice(
@@ -348,16 +412,16 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        wb_table: Rc<WasmBytecodeTable>,
-        leb128_chip: Rc<LEB128Chip<F>>,
+        wb_table: Arc<WasmBytecodeTable>,
+        leb128_chip: Arc<LEB128Chip<F>>,
+        byte_set_table_config: &ByteSetTableConfig<F>,
         func_count: Column<Advice>,
-        shared_state: Rc<RefCell<SharedState>>,
+        shared_state: SharedStateRef,
         body_item_rev_count: Column<Advice>,
-        error_code: Column<Advice>,
+        error_code: ErrorCodeColumns,
     ) -> WasmElementSectionBodyConfig<F> {
-        let q_enable = cs.fixed_column();
-        let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        let selectors = SectionSelectors::configure(cs);
+        let SectionSelectors { q_enable, q_last, .. } = selectors;
         let is_items_count = cs.fixed_column();
         let is_elem_type = cs.fixed_column();
         let is_elem_type_ctx = cs.fixed_column();
@@ -367,10 +431,13 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
         let is_funcs_idx_count = cs.fixed_column();
         let is_func_idx = cs.fixed_column();
         let is_elem_kind = cs.fixed_column();
+        let is_table_idx = cs.fixed_column();
+        let is_ref_type = cs.fixed_column();
+        let is_elem_expr_opcode = cs.fixed_column();
 
         let elem_type = cs.advice_column();
         let config = BinaryNumberChip::configure(cs, is_elem_type_ctx, Some(elem_type.into()));
-        let elem_type_chip = Rc::new(BinaryNumberChip::construct(config));
+        let elem_type_chip = Arc::new(BinaryNumberChip::construct(config));
 
         Self::configure_count_prefixed_items_checks(
             cs,
@@ -381,7 +448,7 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
@@ -392,10 +459,45 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
             |vc| vc.query_fixed(q_last, Rotation::cur()),
         );
 
+        byte_set_table_config.lookup_byte(
+            cs,
+            "is_elem_type -> byte_val is valid",
+            ByteSetTag::ElementType,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                );
+                (
+                    q_enable_expr * vc.query_fixed(is_elem_type, Rotation::cur()),
+                    vc.query_advice(wb_table.value, Rotation::cur()),
+                )
+            },
+        );
+        byte_set_table_config.lookup_byte(
+            cs,
+            "is_ref_type -> byte_val is valid",
+            ByteSetTag::RefType,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                );
+                (
+                    q_enable_expr * vc.query_fixed(is_ref_type, Rotation::cur()),
+                    vc.query_advice(wb_table.value, Rotation::cur()),
+                )
+            },
+        );
+
         cs.create_gate("WasmElementSectionBody gate", |vc| {
             let mut cb = BaseConstraintBuilder::default();
 
-            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.read(), error_code);
             let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
             let not_q_last_expr = not::expr(q_last_expr.clone());
             let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
@@ -407,6 +509,9 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
             let is_funcs_idx_count_expr = vc.query_fixed(is_funcs_idx_count, Rotation::cur());
             let is_func_idx_expr = vc.query_fixed(is_func_idx, Rotation::cur());
             let is_elem_kind_expr = vc.query_fixed(is_elem_kind, Rotation::cur());
+            let is_table_idx_expr = vc.query_fixed(is_table_idx, Rotation::cur());
+            let is_ref_type_expr = vc.query_fixed(is_ref_type, Rotation::cur());
+            let is_elem_expr_opcode_expr = vc.query_fixed(is_elem_expr_opcode, Rotation::cur());
 
             let byte_val_expr = vc.query_advice(wb_table.value, Rotation::cur());
 
@@ -416,11 +521,19 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
             let elem_type_is_1_expr = elem_type_chip.config.value_equals(ElementType::_1, Rotation::cur())(vc);
             // let elem_type_is_0_next_expr = elem_type_chip.config.value_equals(ElementType::_0, Rotation::next())(vc);
             let elem_type_is_1_next_expr = elem_type_chip.config.value_equals(ElementType::_1, Rotation::next())(vc);
+            let elem_type_is_2_expr = elem_type_chip.config.value_equals(ElementType::_2, Rotation::cur())(vc);
+            let elem_type_is_4_expr = elem_type_chip.config.value_equals(ElementType::_4, Rotation::cur())(vc);
+            let elem_type_is_5_expr = elem_type_chip.config.value_equals(ElementType::_5, Rotation::cur())(vc);
+            let elem_type_is_6_expr = elem_type_chip.config.value_equals(ElementType::_6, Rotation::cur())(vc);
+            let elem_type_is_7_expr = elem_type_chip.config.value_equals(ElementType::_7, Rotation::cur())(vc);
+            // element types are exclusive, sums of their flags are flags too
+            let elem_type_has_offset_expr_expr = elem_type_is_0_expr.clone() + elem_type_is_2_expr.clone() + elem_type_is_4_expr.clone() + elem_type_is_6_expr.clone();
+            let elem_type_uses_elem_exprs_expr = elem_type_is_4_expr.clone() + elem_type_is_5_expr.clone() + elem_type_is_6_expr.clone() + elem_type_is_7_expr.clone();
+            let elem_type_has_elem_kind_expr = elem_type_is_1_expr.clone() + elem_type_is_2_expr.clone();
 
             let leb128_sn_expr = vc.query_advice(leb128_chip.config.sn, Rotation::cur());
             let leb128_is_last_byte_expr = vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
 
-            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_items_count is boolean", is_items_count_expr.clone());
             cb.require_boolean("is_elem_type is boolean", is_elem_type_expr.clone());
             cb.require_boolean("is_elem_type_ctx is boolean", is_elem_type_ctx_expr.clone());
@@ -430,15 +543,15 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
             cb.require_boolean("is_funcs_idx_count is boolean", is_funcs_idx_count_expr.clone());
             cb.require_boolean("is_func_idx is boolean", is_func_idx_expr.clone());
             cb.require_boolean("is_elem_kind is boolean", is_elem_kind_expr.clone());
+            cb.require_boolean("is_table_idx is boolean", is_table_idx_expr.clone());
+            cb.require_boolean("is_ref_type is boolean", is_ref_type_expr.clone());
+            cb.require_boolean("is_elem_expr_opcode is boolean", is_elem_expr_opcode_expr.clone());
 
-            configure_constraints_for_q_first_and_q_last(
+            selectors.configure_constraints(
                 &mut cb,
                 vc,
-                &q_enable,
-                &q_first,
                 &[is_items_count],
-                &q_last,
-                &[is_funcs_idx_count, is_func_idx],
+                &[is_funcs_idx_count, is_func_idx, is_block_end],
             );
 
             cb.require_equal(
@@ -450,7 +563,10 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                     is_block_end_expr.clone() +
                     is_funcs_idx_count_expr.clone() +
                     is_func_idx_expr.clone() +
-                    is_elem_kind_expr.clone(),
+                    is_elem_kind_expr.clone() +
+                    is_table_idx_expr.clone() +
+                    is_ref_type_expr.clone() +
+                    is_elem_expr_opcode_expr.clone(),
                 1.expr(),
             );
 
@@ -460,37 +576,17 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                     is_funcs_idx_count_expr.clone(),
                     is_func_idx_expr.clone(),
                     is_numeric_instruction_leb_arg_expr.clone(),
+                    is_table_idx_expr.clone(),
                 ]),
                 |cb| {
                     cb.require_equal(
-                        "is_items_count || is_funcs_idx_count || is_func_idx || is_numeric_instruction_leb_arg => leb128",
+                        "is_items_count || is_funcs_idx_count || is_func_idx || is_numeric_instruction_leb_arg || is_table_idx => leb128",
                         vc.query_fixed(leb128_chip.config.q_enable, Rotation::cur()),
                         1.expr(),
                     )
                 }
             );
 
-            cb.condition(
-                is_elem_type_expr.clone(),
-                |cb| {
-                    cb.require_in_set(
-                        "is_elem_type -> byte_val is valid",
-                        byte_val_expr.clone(),
-                        vec![
-                            ElementType::_0.expr(),
-                            ElementType::_1.expr(),
-                            // TODO
-                            // ElementType::_2.expr(),
-                            // ElementType::_3.expr(),
-                            // ElementType::_4.expr(),
-                            // ElementType::_5.expr(),
-                            // ElementType::_6.expr(),
-                            // ElementType::_7.expr(),
-                        ],
-                    );
-                }
-            );
-
             cb.require_equal(
                 "check relation of is_elem_type_ctx with other flags",
                 is_elem_type_expr.clone()
@@ -499,7 +595,10 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                     + is_block_end_expr.clone()
                     + is_funcs_idx_count_expr.clone()
                     + is_func_idx_expr.clone()
-                    + is_elem_kind_expr.clone(),
+                    + is_elem_kind_expr.clone()
+                    + is_table_idx_expr.clone()
+                    + is_ref_type_expr.clone()
+                    + is_elem_expr_opcode_expr.clone(),
                 is_elem_type_ctx_expr.clone()
             );
             cb.condition(
@@ -555,11 +654,11 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
             configure_transition_check(
                 &mut cb,
                 vc,
-                "check next: is_elem_type{1}=0 -> is_numeric_instruction{1}",
+                "check next: is_elem_type{1}=0|4 -> is_numeric_instruction{1}",
                 and::expr([
                     not_q_last_expr.clone(),
                     is_elem_type_expr.clone(),
-                    elem_type_is_0_expr.clone(),
+                    elem_type_is_0_expr.clone() + elem_type_is_4_expr.clone(),
                 ]),
                 true,
                 &[is_numeric_instruction, ],
@@ -571,7 +670,7 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                 and::expr([
                     not_q_last_expr.clone(),
                     is_numeric_instruction_expr.clone(),
-                    elem_type_is_0_expr.clone(),
+                    elem_type_has_offset_expr_expr.clone(),
                 ]),
                 true,
                 &[is_numeric_instruction_leb_arg, ],
@@ -583,7 +682,7 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                 and::expr([
                     not_q_last_expr.clone(),
                     is_numeric_instruction_leb_arg_expr.clone(),
-                    elem_type_is_0_expr.clone(),
+                    elem_type_has_offset_expr_expr.clone(),
                 ]),
                 true,
                 &[is_numeric_instruction_leb_arg, is_block_end, ],
@@ -596,7 +695,7 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                     not_q_last_expr.clone(),
                     leb128_is_last_byte_expr.clone(),
                     is_numeric_instruction_leb_arg_expr.clone(),
-                    elem_type_is_0_expr.clone(),
+                    elem_type_has_offset_expr_expr.clone(),
                 ]),
                 true,
                 &[is_block_end],
@@ -683,7 +782,7 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                 and::expr([
                     not_q_last_expr.clone(),
                     is_elem_kind_expr.clone(),
-                    elem_type_is_1_expr.clone(),
+                    elem_type_has_elem_kind_expr.clone(),
                 ]),
                 true,
                 &[is_funcs_idx_count, ],
@@ -695,7 +794,7 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                 and::expr([
                     not_q_last_expr.clone(),
                     is_funcs_idx_count_expr.clone(),
-                    elem_type_is_1_expr.clone(),
+                    elem_type_has_elem_kind_expr.clone(),
                 ]) * leb128_sn_expr.clone(),
                 true,
                 &[is_funcs_idx_count, is_func_idx],
@@ -708,7 +807,7 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                     not_q_last_expr.clone(),
                     leb128_is_last_byte_expr.clone(),
                     is_funcs_idx_count_expr.clone(),
-                    elem_type_is_1_expr.clone(),
+                    elem_type_has_elem_kind_expr.clone(),
                 ]) * leb128_sn_expr.clone(),
                 true,
                 &[is_func_idx],
@@ -720,7 +819,7 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                 and::expr([
                     not_q_last_expr.clone(),
                     is_func_idx_expr.clone(),
-                    elem_type_is_1_expr.clone(),
+                    elem_type_has_elem_kind_expr.clone(),
                 ]),
                 true,
                 &[is_func_idx, is_elem_type],
@@ -733,21 +832,262 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                     not_q_last_expr.clone(),
                     leb128_is_last_byte_expr.clone(),
                     is_func_idx_expr.clone(),
-                    elem_type_is_1_expr.clone(),
+                    elem_type_has_elem_kind_expr.clone(),
                 ]),
                 true,
                 &[is_func_idx, is_elem_type],
             );
 
+            // elem_body+(is_elem_type{1}=2 -> is_table_idx+ -> is_numeric_instruction{1} -> is_numeric_instruction_leb_arg+ -> is_block_end{1} -> is_elem_kind{1} -> is_funcs_idx_count+ -> is_func_idx*)
+            // elem_body+(is_elem_type{1}=4 -> is_numeric_instruction{1} -> is_numeric_instruction_leb_arg+ -> is_block_end{1} -> is_funcs_idx_count+ -> elem_expr*)
+            // elem_body+(is_elem_type{1}=5|7 -> is_ref_type{1} -> is_funcs_idx_count+ -> elem_expr*)
+            // elem_body+(is_elem_type{1}=6 -> is_table_idx+ -> is_numeric_instruction{1} -> is_numeric_instruction_leb_arg+ -> is_block_end{1} -> is_ref_type{1} -> is_funcs_idx_count+ -> elem_expr*)
+            // elem_expr(is_elem_expr_opcode{1} -> (is_func_idx+ | is_ref_type{1}) -> is_block_end{1})
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_elem_type{1}=2|6 -> is_table_idx+",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    is_elem_type_expr.clone(),
+                    elem_type_is_2_expr.clone() + elem_type_is_6_expr.clone(),
+                ]),
+                true,
+                &[is_table_idx],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_table_idx+ -> is_numeric_instruction{1}",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    is_table_idx_expr.clone(),
+                ]),
+                true,
+                &[is_table_idx, is_numeric_instruction],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next (last leb byte): is_table_idx+ -> is_numeric_instruction{1}",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    leb128_is_last_byte_expr.clone(),
+                    is_table_idx_expr.clone(),
+                ]),
+                true,
+                &[is_numeric_instruction],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_block_end{1}=2 -> is_elem_kind{1}",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    is_block_end_expr.clone(),
+                    elem_type_is_2_expr.clone(),
+                ]),
+                true,
+                &[is_elem_kind],
+            );
+            let is_numeric_instruction_leb_arg_prev_expr = vc.query_fixed(is_numeric_instruction_leb_arg, Rotation::prev());
+            let is_block_end_prev_expr = vc.query_fixed(is_block_end, Rotation::prev());
+            let is_elem_expr_opcode_prev_expr = vc.query_fixed(is_elem_expr_opcode, Rotation::prev());
+            let is_func_idx_prev_expr = vc.query_fixed(is_func_idx, Rotation::prev());
+            let is_ref_type_prev_expr = vc.query_fixed(is_ref_type, Rotation::prev());
+            let is_elem_type_prev_expr = vc.query_fixed(is_elem_type, Rotation::prev());
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_elem_type{1}=5|7 -> is_ref_type{1}",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    is_elem_type_expr.clone(),
+                    elem_type_is_5_expr.clone() + elem_type_is_7_expr.clone(),
+                ]),
+                true,
+                &[is_ref_type],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_block_end{1}=4 (offset expression end) -> is_funcs_idx_count+",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    is_block_end_expr.clone(),
+                    elem_type_is_4_expr.clone(),
+                    is_numeric_instruction_leb_arg_prev_expr.clone(),
+                ]),
+                true,
+                &[is_funcs_idx_count],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_block_end{1}=6 (offset expression end) -> is_ref_type{1}",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    is_block_end_expr.clone(),
+                    elem_type_is_6_expr.clone(),
+                    is_numeric_instruction_leb_arg_prev_expr.clone(),
+                ]),
+                true,
+                &[is_ref_type],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_ref_type{1}=5|6|7 (segment type) -> is_funcs_idx_count+",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    is_ref_type_expr.clone(),
+                    elem_type_uses_elem_exprs_expr.clone(),
+                    is_block_end_prev_expr.clone() + is_elem_type_prev_expr.clone(),
+                ]),
+                true,
+                &[is_funcs_idx_count],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_funcs_idx_count+ -> elem_expr*",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    is_funcs_idx_count_expr.clone(),
+                    elem_type_uses_elem_exprs_expr.clone(),
+                ]) * leb128_sn_expr.clone(),
+                true,
+                &[is_funcs_idx_count, is_elem_expr_opcode],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next (last leb byte): is_funcs_idx_count+ -> elem_expr*",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    leb128_is_last_byte_expr.clone(),
+                    is_funcs_idx_count_expr.clone(),
+                    elem_type_uses_elem_exprs_expr.clone(),
+                ]) * leb128_sn_expr.clone(),
+                true,
+                &[is_elem_expr_opcode],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_elem_expr_opcode{1} -> is_func_idx+ | is_ref_type{1}",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    is_elem_expr_opcode_expr.clone(),
+                ]),
+                true,
+                &[is_func_idx, is_ref_type],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_func_idx+=4..7 -> is_block_end{1}",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    is_func_idx_expr.clone(),
+                    elem_type_uses_elem_exprs_expr.clone(),
+                ]),
+                true,
+                &[is_func_idx, is_block_end],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next (last leb byte): is_func_idx+=4..7 -> is_block_end{1}",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    leb128_is_last_byte_expr.clone(),
+                    is_func_idx_expr.clone(),
+                    elem_type_uses_elem_exprs_expr.clone(),
+                ]),
+                true,
+                &[is_block_end],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_ref_type{1}=4..7 (ref.null arg) -> is_block_end{1}",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    is_ref_type_expr.clone(),
+                    elem_type_uses_elem_exprs_expr.clone(),
+                    is_elem_expr_opcode_prev_expr.clone(),
+                ]),
+                true,
+                &[is_block_end],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_block_end{1}=4..7 (elem_expr end) -> elem_expr* | is_elem_type{1}",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    is_block_end_expr.clone(),
+                    elem_type_uses_elem_exprs_expr.clone(),
+                    is_func_idx_prev_expr.clone() + is_ref_type_prev_expr.clone(),
+                ]),
+                true,
+                &[is_elem_expr_opcode, is_elem_type],
+            );
+
+            cb.condition(
+                is_numeric_instruction_expr.clone(),
+                |cb| {
+                    cb.require_in_set(
+                        "is_numeric_instruction -> byte value is valid",
+                        byte_val_expr.clone(),
+                        vec![
+                            NumericInstruction::I32Const.expr(),
+                        ],
+                    )
+                }
+            );
+            cb.condition(
+                is_block_end_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "is_block_end -> byte value = WASM_BLOCK_END",
+                        byte_val_expr.clone(),
+                        WASM_BLOCK_END.expr(),
+                    )
+                }
+            );
+            cb.condition(
+                is_elem_expr_opcode_expr.clone(),
+                |cb| {
+                    cb.require_in_set(
+                        "is_elem_expr_opcode -> byte value is valid",
+                        byte_val_expr.clone(),
+                        vec![
+                            ReferenceInstruction::RefFunc.expr(),
+                            ReferenceInstruction::RefNull.expr(),
+                        ],
+                    );
+                    cb.require_zero(
+                        "is_elem_expr_opcode && next.is_func_idx -> byte value = ref.func",
+                        vc.query_fixed(is_func_idx, Rotation::next())
+                            * (byte_val_expr.clone() - ReferenceInstruction::RefFunc.expr()),
+                    );
+                    cb.require_zero(
+                        "is_elem_expr_opcode && next.is_ref_type -> byte value = ref.null",
+                        vc.query_fixed(is_ref_type, Rotation::next())
+                            * (byte_val_expr.clone() - ReferenceInstruction::RefNull.expr()),
+                    );
+                }
+            );
+
             cb.gate(q_enable_expr.clone())
         });
 
         let config = WasmElementSectionBodyConfig::<F> {
             _marker: PhantomData,
 
-            q_enable,
-            q_first,
-            q_last,
+            selectors,
             is_items_count,
             is_elem_type,
             is_elem_type_ctx,
@@ -757,6 +1097,9 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
             is_funcs_idx_count,
             is_func_idx,
             is_elem_kind,
+            is_table_idx,
+            is_ref_type,
+            is_elem_expr_opcode,
             elem_type,
             elem_type_chip,
             leb128_chip,
@@ -769,6 +1112,70 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
         config
     }
 
+    /// Marks the byte at `offset` as `assign_type` of an element of `elem_type_val`, returns the
+    /// offset after it
+    fn markup_elem_byte(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        offset: usize,
+        assign_delta: AssignDeltaType,
+        assign_type: AssignType,
+        elem_type_val: u64,
+    ) -> Result<NewWbOffsetType, Error> {
+        self.assign(
+            region,
+            wb,
+            offset,
+            assign_delta,
+            &[assign_type, AssignType::IsElemTypeCtx],
+            1,
+            None,
+        )?;
+        self.assign(
+            region,
+            wb,
+            offset,
+            assign_delta,
+            &[AssignType::ElemType],
+            elem_type_val,
+            None,
+        )?;
+        Ok(offset + 1)
+    }
+
+    /// Marks the leb128 at `offset` as `assign_type` of an element of `elem_type_val`, returns its
+    /// value and the offset after it
+    fn markup_elem_leb(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        offset: usize,
+        assign_delta: AssignDeltaType,
+        assign_type: AssignType,
+        elem_type_val: u64,
+    ) -> Result<(u64, NewWbOffsetType), Error> {
+        let (value, leb_len) = self.markup_leb_section(
+            region,
+            wb,
+            offset,
+            assign_delta,
+            &[assign_type, AssignType::IsElemTypeCtx],
+        )?;
+        for offset in offset..offset + leb_len {
+            self.assign(
+                region,
+                wb,
+                offset,
+                assign_delta,
+                &[AssignType::ElemType],
+                elem_type_val,
+                None,
+            )?;
+        }
+        Ok((value, offset + leb_len))
+    }
+
     pub fn assign_auto(
         &self,
         region: &mut Region<F>,
@@ -840,190 +1247,149 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
             offset += 1;
 
             match elem_type {
-                ElementType::_0 => {
-                    // numeric_instruction{1}
-                    self.assign(
-                        region,
-                        wb,
-                        offset,
-                        assign_delta,
-                        &[AssignType::IsNumericInstruction, AssignType::IsElemTypeCtx],
-                        1,
-                        None,
-                    )?;
-                    self.assign(
+                ElementType::_0 | ElementType::_2 | ElementType::_4 | ElementType::_6 => {
+                    if elem_type == ElementType::_2 || elem_type == ElementType::_6 {
+                        // table_idx+
+                        offset = self
+                            .markup_elem_leb(
+                                region,
+                                wb,
+                                offset,
+                                assign_delta,
+                                AssignType::IsTableIdx,
+                                elem_type_val,
+                            )?
+                            .1;
+                    }
+                    // offset expression: numeric_instruction{1} -> numeric_instruction_leb_arg+ ->
+                    // numeric_instruction_block_end{1}
+                    offset = self.markup_elem_byte(
                         region,
                         wb,
                         offset,
                         assign_delta,
-                        &[AssignType::ElemType],
+                        AssignType::IsNumericInstruction,
                         elem_type_val,
-                        None,
                     )?;
-                    offset += 1;
-
-                    // numeric_instruction_leb_arg+
-                    let (_numeric_instruction_leb_arg, numeric_instruction_leb_arg_leb_len) = self
-                        .markup_leb_section(
+                    offset = self
+                        .markup_elem_leb(
                             region,
                             wb,
                             offset,
                             assign_delta,
-                            &[
-                                AssignType::IsNumericInstructionLebArg,
-                                AssignType::IsElemTypeCtx,
-                            ],
-                        )?;
-                    for offset in offset..offset + numeric_instruction_leb_arg_leb_len {
-                        self.assign(
-                            region,
-                            wb,
-                            offset,
-                            assign_delta,
-                            &[AssignType::ElemType],
+                            AssignType::IsNumericInstructionLebArg,
                             elem_type_val,
-                            None,
-                        )?;
-                    }
-                    offset += numeric_instruction_leb_arg_leb_len;
-
-                    // numeric_instruction_block_end{1}
-                    self.assign(
+                        )?
+                        .1;
+                    offset = self.markup_elem_byte(
                         region,
                         wb,
                         offset,
                         assign_delta,
-                        &[AssignType::IsBlockEnd, AssignType::IsElemTypeCtx],
-                        1,
-                        None,
+                        AssignType::IsBlockEnd,
+                        elem_type_val,
                     )?;
-                    self.assign(
+                }
+                ElementType::_1 | ElementType::_5 | ElementType::_7 => {}
+                _ => {
+                    return Err(Error::FatalUnsupportedTypeValue(format!(
+                        "unsupported element type '{:?}'",
+                        elem_type
+                    )))
+                }
+            }
+            match elem_type {
+                // elem_kind{1}
+                ElementType::_1 | ElementType::_2 => {
+                    offset = self.markup_elem_byte(
                         region,
                         wb,
                         offset,
                         assign_delta,
-                        &[AssignType::ElemType],
+                        AssignType::IsElemKind,
                         elem_type_val,
-                        None,
                     )?;
-                    offset += 1;
-
-                    // funcs_idx_count+
-                    let (funcs_idx_count, funcs_idx_count_leb_len) = self.markup_leb_section(
+                }
+                // ref_type{1}
+                ElementType::_5 | ElementType::_6 | ElementType::_7 => {
+                    offset = self.markup_elem_byte(
                         region,
                         wb,
                         offset,
                         assign_delta,
-                        &[AssignType::IsFuncsIdxCount, AssignType::IsElemTypeCtx],
+                        AssignType::IsRefType,
+                        elem_type_val,
                     )?;
-                    for offset in offset..offset + funcs_idx_count_leb_len {
-                        self.assign(
+                }
+                _ => {}
+            }
+
+            // funcs_idx_count+, counts element expressions for segments using them
+            let (funcs_idx_count, new_offset) = self.markup_elem_leb(
+                region,
+                wb,
+                offset,
+                assign_delta,
+                AssignType::IsFuncsIdxCount,
+                elem_type_val,
+            )?;
+            offset = new_offset;
+            for _funcs_idx_index in 0..funcs_idx_count {
+                if elem_type < ElementType::_4 {
+                    // func_idx+
+                    offset = self
+                        .markup_elem_leb(
                             region,
                             wb,
                             offset,
                             assign_delta,
-                            &[AssignType::ElemType],
+                            AssignType::IsFuncIdx,
                             elem_type_val,
-                            None,
-                        )?;
-                    }
-                    offset += funcs_idx_count_leb_len;
-
-                    for _funcs_idx_index in 0..funcs_idx_count {
-                        // func_idx+
-                        let (_func_idx, func_idx_leb_len) = self.markup_leb_section(
+                        )?
+                        .1;
+                    continue;
+                }
+                // elem_expr_opcode{1} -> (func_idx+ | ref_type{1}) -> block_end{1}
+                let opcode: ReferenceInstruction = wb.bytes[offset]
+                    .try_into()
+                    .map_err(remap_error_to_invalid_enum_value_at(offset + assign_delta))?;
+                offset = self.markup_elem_byte(
+                    region,
+                    wb,
+                    offset,
+                    assign_delta,
+                    AssignType::IsElemExprOpcode,
+                    elem_type_val,
+                )?;
+                offset = match opcode {
+                    ReferenceInstruction::RefFunc => {
+                        self.markup_elem_leb(
                             region,
                             wb,
                             offset,
                             assign_delta,
-                            &[AssignType::IsFuncIdx, AssignType::IsElemTypeCtx],
-                        )?;
-                        for offset in offset..offset + func_idx_leb_len {
-                            self.assign(
-                                region,
-                                wb,
-                                offset,
-                                assign_delta,
-                                &[AssignType::ElemType],
-                                elem_type_val,
-                                None,
-                            )?;
-                        }
-                        offset += func_idx_leb_len;
+                            AssignType::IsFuncIdx,
+                            elem_type_val,
+                        )?
+                        .1
                     }
-                }
-                ElementType::_1 => {
-                    // elem_kind{1}
-                    self.assign(
-                        region,
-                        wb,
-                        offset,
-                        assign_delta,
-                        &[AssignType::IsElemKind, AssignType::IsElemTypeCtx],
-                        1,
-                        None,
-                    )?;
-                    self.assign(
+                    ReferenceInstruction::RefNull => self.markup_elem_byte(
                         region,
                         wb,
                         offset,
                         assign_delta,
-                        &[AssignType::ElemType],
+                        AssignType::IsRefType,
                         elem_type_val,
-                        None,
-                    )?;
-                    offset += 1;
-
-                    // funcs_idx_count+
-                    let (funcs_idx_count, funcs_idx_count_leb_len) = self.markup_leb_section(
-                        region,
-                        wb,
-                        offset,
-                        assign_delta,
-                        &[AssignType::IsFuncsIdxCount, AssignType::IsElemTypeCtx],
-                    )?;
-                    for offset in offset..offset + funcs_idx_count_leb_len {
-                        self.assign(
-                            region,
-                            wb,
-                            offset,
-                            assign_delta,
-                            &[AssignType::ElemType],
-                            elem_type_val,
-                            None,
-                        )?;
-                    }
-                    offset += funcs_idx_count_leb_len;
-
-                    for _funcs_idx_index in 0..funcs_idx_count {
-                        // func_idxs+
-                        let (_func_idxs, func_idxs_leb_len) = self.markup_leb_section(
-                            region,
-                            wb,
-                            offset,
-                            assign_delta,
-                            &[AssignType::IsFuncIdx, AssignType::IsElemTypeCtx],
-                        )?;
-                        for offset in offset..offset + func_idxs_leb_len {
-                            self.assign(
-                                region,
-                                wb,
-                                offset,
-                                assign_delta,
-                                &[AssignType::ElemType],
-                                elem_type_val,
-                                None,
-                            )?;
-                        }
-                        offset += func_idxs_leb_len;
-                    }
-                }
-                _ => {
-                    return Err(Error::FatalUnsupportedTypeValue(format!(
-                        "unsupported element type '{:?}'",
-                        elem_type
-                    )))
-                }
+                    )?,
+                };
+                offset = self.markup_elem_byte(
+                    region,
+                    wb,
+                    offset,
+                    assign_delta,
+                    AssignType::IsBlockEnd,
+                    elem_type_val,
+                )?;
             }
 
             for offset in item_start_offset..offset {
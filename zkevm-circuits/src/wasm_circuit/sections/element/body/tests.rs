@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
@@ -9,9 +9,11 @@ use eth_types::{Field, Hash, ToWord};
 
 use crate::wasm_circuit::{
     bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+    common::ErrorCodeColumns,
     leb128::circuit::LEB128Chip,
     sections::element::body::circuit::WasmElementSectionBodyChip,
-    types::SharedState,
+    tables::byte_set::config::ByteSetTableConfig,
+    types::SharedStateRef,
 };
 
 #[derive(Default)]
@@ -24,8 +26,9 @@ struct TestCircuit<'a, F> {
 
 #[derive(Clone)]
 struct TestCircuitConfig<F: Field> {
-    body_chip: Rc<WasmElementSectionBodyChip<F>>,
-    wb_table: Rc<WasmBytecodeTable>,
+    body_chip: Arc<WasmElementSectionBodyChip<F>>,
+    wb_table: Arc<WasmBytecodeTable>,
+    byte_set_table_config: ByteSetTableConfig<F>,
     _marker: PhantomData<F>,
 }
 
@@ -38,20 +41,22 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
     }
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, false));
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, false));
         let func_count = cs.advice_column();
-        let error_code = cs.advice_column();
+        let error_code = ErrorCodeColumns::configure(cs);
         let body_item_rev_count = cs.advice_column();
 
-        let shared_state = Rc::new(RefCell::new(SharedState::default()));
+        let shared_state = SharedStateRef::default();
 
         let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
-        let leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
+        let leb128_chip = Arc::new(LEB128Chip::construct(leb128_config));
+        let byte_set_table_config = ByteSetTableConfig::configure(cs);
 
         let wasm_element_section_body_config = WasmElementSectionBodyChip::configure(
             cs,
             wb_table.clone(),
             leb128_chip.clone(),
+            &byte_set_table_config,
             func_count,
             shared_state.clone(),
             body_item_rev_count,
@@ -60,8 +65,9 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
         let wasm_element_section_body_chip =
             WasmElementSectionBodyChip::construct(wasm_element_section_body_config);
         let test_circuit_config = TestCircuitConfig {
-            body_chip: Rc::new(wasm_element_section_body_chip),
+            body_chip: Arc::new(wasm_element_section_body_chip),
             wb_table: wb_table.clone(),
+            byte_set_table_config,
             _marker: Default::default(),
         };
 
@@ -84,6 +90,7 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
                 },
             )
             .unwrap();
+        config.byte_set_table_config.load(&mut layouter)?;
         layouter.assign_region(
             || "wasm_element_section_body region",
             |mut region| {
@@ -168,4 +175,59 @@ mod wasm_element_section_body_tests {
         };
         test(test_circuit, true);
     }
+
+    fn test_body(bytecode: &[u8], is_ok: bool) {
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash: CodeDB::hash(bytecode),
+            bytecode,
+            offset_start: 0,
+            _marker: Default::default(),
+        };
+        test(test_circuit, is_ok);
+    }
+
+    #[test]
+    pub fn table_idx_and_func_idxs_ok() {
+        // flag 2: table 1, offset (i32.const 0), elemkind 0, funcs [0, 1]
+        test_body(&[1, 2, 1, 0x41, 0, 0xB, 0, 2, 0, 1], true);
+    }
+
+    #[test]
+    pub fn table_idx_and_elem_exprs_ok() {
+        // flag 6: table 1, offset (i32.const 5), funcref, exprs [ref.func 0, ref.null func]
+        test_body(
+            &[1, 6, 1, 0x41, 5, 0xB, 0x70, 2, 0xD2, 0, 0xB, 0xD0, 0x70, 0xB],
+            true,
+        );
+    }
+
+    #[test]
+    pub fn offset_and_elem_exprs_ok() {
+        // flag 4: offset (i32.const 0), exprs [ref.func 0, ref.null func]
+        test_body(
+            &[1, 4, 0x41, 0, 0xB, 2, 0xD2, 0, 0xB, 0xD0, 0x70, 0xB],
+            true,
+        );
+    }
+
+    #[test]
+    pub fn passive_and_declarative_elem_exprs_ok() {
+        // flag 5: funcref, exprs [ref.func 1]; flag 7: funcref, exprs [ref.null func]
+        test_body(
+            &[2, 5, 0x70, 1, 0xD2, 1, 0xB, 7, 0x70, 1, 0xD0, 0x70, 0xB],
+            true,
+        );
+    }
+
+    #[test]
+    pub fn elem_expr_without_end_fails() {
+        // flag 5 with the ref.func expression terminated by a non end byte
+        test_body(&[1, 5, 0x70, 1, 0xD2, 1, 0xA], false);
+    }
+
+    #[test]
+    pub fn offset_expr_other_than_i32_const_fails() {
+        // flag 2 with an i64.const offset
+        test_body(&[1, 2, 1, 0x42, 0, 0xB, 0, 2, 0, 1], false);
+    }
 }
@@ -14,6 +14,9 @@ pub enum AssignType {
     IsFuncsIdxCount,
     IsFuncIdx,
     IsElemKind,
+    IsTableIdx,
+    IsRefType,
+    IsElemExprOpcode,
 
     BodyItemRevCount,
 
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Region, Value},
@@ -18,25 +18,29 @@ use crate::{
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
-            configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip, WasmErrorAwareChip,
-            WasmFuncCountAwareChip, WasmLenPrefixedBytesSpanAwareChip,
+            configure_transition_check, ErrorCodeColumns, SectionBodyChip, SectionBodyChipResources,
+            SectionSelectors, WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip,
+            WasmErrorAwareChip, WasmFuncCountAwareChip, WasmLenPrefixedBytesSpanAwareChip,
             WasmMarkupLeb128SectionAwareChip, WasmNameAwareChip, WasmSharedStateAwareChip,
         },
         error::{
-            remap_error, remap_error_to_assign_at, remap_error_to_invalid_enum_value_at, Error,
+            remap_error, remap_error_to_assign_at, remap_error_to_compute_value_at,
+            remap_error_to_invalid_enum_value_at, Error,
         },
         leb128::circuit::LEB128Chip,
         sections::{consts::LebParams, export::body::types::AssignType},
-        types::{AssignDeltaType, AssignValueType, ExportDescType, NewWbOffsetType, SharedState},
+        tables::byte_set::{config::ByteSetTableConfig, types::ByteSetTag},
+        types::{
+            AssignDeltaType, AssignValueType, ExportDescType, NewWbOffsetType, SharedStateRef,
+            WasmSection,
+        },
+        utf8::circuit::UTF8Chip,
     },
 };
 
 #[derive(Debug, Clone)]
 pub struct WasmExportSectionBodyConfig<F: Field> {
-    pub q_enable: Column<Fixed>,
-    pub q_first: Column<Fixed>,
-    pub q_last: Column<Fixed>,
+    pub selectors: SectionSelectors,
     pub is_items_count: Column<Fixed>,
     pub is_export_name_len: Column<Fixed>,
     pub is_export_name: Column<Fixed>,
@@ -44,17 +48,22 @@ pub struct WasmExportSectionBodyConfig<F: Field> {
     pub is_exportdesc_type_ctx: Column<Fixed>,
     pub is_exportdesc_val: Column<Fixed>,
 
-    pub leb128_chip: Rc<LEB128Chip<F>>,
+    pub leb128_chip: Arc<LEB128Chip<F>>,
+    pub utf8_chip: Arc<UTF8Chip<F>>,
     pub exportdesc_type: Column<Advice>,
-    pub exportdesc_type_chip: Rc<BinaryNumberChip<F, ExportDescType, 8>>,
+    pub exportdesc_type_chip: Arc<BinaryNumberChip<F, ExportDescType, 8>>,
 
     pub func_count: Column<Advice>,
     body_byte_rev_index: Column<Advice>,
     body_item_rev_count: Column<Advice>,
 
-    error_code: Column<Advice>,
+    error_code: ErrorCodeColumns,
+
+    /// exports of mutable globals set the error code, the lookup into the global section is
+    /// configured by the circuit
+    pub reject_exported_mutable_globals: bool,
 
-    shared_state: Rc<RefCell<SharedState>>,
+    shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -76,17 +85,51 @@ impl<F: Field> WasmLenPrefixedBytesSpanAwareChip<F> for WasmExportSectionBodyChi
 impl<F: Field> WasmNameAwareChip<F> for WasmExportSectionBodyChip<F> {}
 
 impl<F: Field> WasmErrorAwareChip<F> for WasmExportSectionBodyChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmExportSectionBodyChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
 
+impl<F: Field> SectionBodyChip<F> for WasmExportSectionBodyChip<F> {
+    const SECTION: WasmSection = WasmSection::Export;
+
+    fn configure_with_resources(
+        cs: &mut ConstraintSystem<F>,
+        resources: &SectionBodyChipResources<F>,
+    ) -> Self {
+        let config = Self::configure(
+            cs,
+            resources.wb_table.clone(),
+            resources.leb128_chip.clone(),
+            &resources.byte_set_table_config,
+            resources.utf8_chip.clone(),
+            resources.func_count,
+            resources.shared_state.clone(),
+            resources.body_byte_rev_index_l2,
+            resources.body_item_rev_count_l1,
+            resources.error_code,
+            false,
+        );
+        Self::construct(config)
+    }
+
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error> {
+        self.assign_auto(region, wb, wb_offset, assign_delta)
+    }
+}
+
 impl<F: Field> WasmFuncCountAwareChip<F> for WasmExportSectionBodyChip<F> {
     fn func_count_col(&self) -> Column<Advice> {
         self.config.func_count
@@ -112,14 +155,9 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmExportSectionBodyChip<F> {
             "assign at {} q_enable {} assign_types {:?} assign_value {} byte_val {:x?}",
             assign_offset, q_enable, assign_types, assign_value, wb.bytes[wb_offset],
         );
-        region
-            .assign_fixed(
-                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
-                self.config.q_enable,
-                assign_offset,
-                || Value::known(F::from(q_enable as u64)),
-            )
-            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.config
+            .selectors
+            .assign_q_enable(region, assign_offset, q_enable)?;
         self.assign_func_count(region, assign_offset)?;
 
         for assign_type in assign_types {
@@ -137,29 +175,14 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmExportSectionBodyChip<F> {
             }
             match assign_type {
                 AssignType::QFirst => {
-                    region
-                        .assign_fixed(
-                            || {
-                                format!(
-                                    "assign 'q_first' val {} at {}",
-                                    assign_value, assign_offset
-                                )
-                            },
-                            self.config.q_first,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_first(region, assign_offset, assign_value)?;
                 }
                 AssignType::QLast => {
-                    region
-                        .assign_fixed(
-                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
-                            self.config.q_last,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_last(region, assign_offset, assign_value)?;
                 }
                 AssignType::IsItemsCount => {
                     region
@@ -316,17 +339,19 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        wb_table: Rc<WasmBytecodeTable>,
-        leb128_chip: Rc<LEB128Chip<F>>,
+        wb_table: Arc<WasmBytecodeTable>,
+        leb128_chip: Arc<LEB128Chip<F>>,
+        byte_set_table_config: &ByteSetTableConfig<F>,
+        utf8_chip: Arc<UTF8Chip<F>>,
         func_count: Column<Advice>,
-        shared_state: Rc<RefCell<SharedState>>,
+        shared_state: SharedStateRef,
         body_byte_rev_index: Column<Advice>,
         body_item_rev_count: Column<Advice>,
-        error_code: Column<Advice>,
+        error_code: ErrorCodeColumns,
+        reject_exported_mutable_globals: bool,
     ) -> WasmExportSectionBodyConfig<F> {
-        let q_enable = cs.fixed_column();
-        let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        let selectors = SectionSelectors::configure(cs);
+        let SectionSelectors { q_enable, q_first, q_last } = selectors;
         let is_items_count = cs.fixed_column();
         let is_export_name_len = cs.fixed_column();
         let is_export_name = cs.fixed_column();
@@ -339,7 +364,7 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
 
         let config =
             BinaryNumberChip::configure(cs, is_exportdesc_type_ctx, Some(exportdesc_type.into()));
-        let exportdesc_type_chip = Rc::new(BinaryNumberChip::construct(config));
+        let exportdesc_type_chip = Arc::new(BinaryNumberChip::construct(config));
 
         Self::configure_len_prefixed_bytes_span_checks(
             cs,
@@ -375,7 +400,7 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
@@ -398,10 +423,28 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
             |vc| vc.query_fixed(q_last, Rotation::cur()),
         );
 
+        byte_set_table_config.lookup_byte(
+            cs,
+            "is_exportdesc_type -> byte_val has valid value",
+            ByteSetTag::ExportDescType,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                );
+                (
+                    q_enable_expr * vc.query_fixed(is_exportdesc_type, Rotation::cur()),
+                    vc.query_advice(wb_table.value, Rotation::cur()),
+                )
+            },
+        );
+
         cs.create_gate("WasmExportSectionBody gate", |vc| {
             let mut cb = BaseConstraintBuilder::default();
 
-            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.read(), error_code);
             // let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
             let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
             let not_q_last_expr = not::expr(q_last_expr.clone());
@@ -414,29 +457,19 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
             let is_exportdesc_type_ctx_prev_expr = vc.query_fixed(is_exportdesc_type_ctx, Rotation::prev());
             let is_exportdesc_type_ctx_expr = vc.query_fixed(is_exportdesc_type_ctx, Rotation::cur());
 
-            let byte_val_expr = vc.query_advice(wb_table.value, Rotation::cur());
-
             let exportdesc_type_prev_expr = vc.query_advice(exportdesc_type, Rotation::prev());
             let exportdesc_type_expr = vc.query_advice(exportdesc_type, Rotation::cur());
 
+            let utf8_chip_q_enabled_expr = vc.query_fixed(utf8_chip.config.q_enable, Rotation::cur());
             let leb128_is_last_byte_expr = vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
 
-            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_items_count is boolean", is_items_count_expr.clone());
             cb.require_boolean("is_export_name_len is boolean", is_export_name_len_expr.clone());
             cb.require_boolean("is_export_name is boolean", is_export_name_expr.clone());
             cb.require_boolean("is_exportdesc_type is boolean", is_exportdesc_type_expr.clone());
             cb.require_boolean("is_exportdesc_val is boolean", is_exportdesc_val_expr.clone());
 
-            configure_constraints_for_q_first_and_q_last(
-                &mut cb,
-                vc,
-                &q_enable,
-                &q_first,
-                &[is_items_count],
-                &q_last,
-                &[is_exportdesc_val],
-            );
+            selectors.configure_constraints(&mut cb, vc, &[is_items_count], &[is_exportdesc_val]);
 
             cb.require_equal(
                 "exactly one mark flag active at the same time",
@@ -463,6 +496,12 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
                 }
             );
 
+            cb.require_equal(
+                "is_export_name -> utf8",
+                is_export_name_expr.clone(),
+                utf8_chip_q_enabled_expr.clone(),
+            );
+
             cb.condition(
                 or::expr([
                     is_exportdesc_type_expr.clone(),
@@ -583,31 +622,13 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
                 &[is_export_name_len],
             );
 
-            cb.condition(
-                is_exportdesc_type_expr.clone(),
-                |cb| {
-                    cb.require_in_set(
-                        "is_exportdesc_type -> byte_val has valid value",
-                        byte_val_expr.clone(),
-                        vec![
-                            ExportDescType::Funcidx.expr(),
-                            ExportDescType::Tableidx.expr(),
-                            ExportDescType::Memidx.expr(),
-                            ExportDescType::Globalidx.expr(),
-                        ],
-                    );
-                }
-            );
-
             cb.gate(q_enable_expr.clone())
         });
 
         let config = WasmExportSectionBodyConfig::<F> {
             _marker: PhantomData,
 
-            q_enable,
-            q_first,
-            q_last,
+            selectors,
             is_items_count,
             is_export_name_len,
             is_export_name,
@@ -615,12 +636,14 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
             is_exportdesc_type_ctx,
             is_exportdesc_val,
             leb128_chip,
+            utf8_chip,
             exportdesc_type,
             exportdesc_type_chip,
             func_count,
             body_byte_rev_index,
             body_item_rev_count,
             error_code,
+            reject_exported_mutable_globals,
             shared_state,
         };
 
@@ -702,6 +725,13 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
                 export_name_len as usize,
                 1,
             )?;
+            self.config.utf8_chip.assign_auto(
+                region,
+                wb,
+                export_name_len as usize,
+                offset,
+                offset + assign_delta,
+            )?;
             offset = export_name_new_offset;
 
             let exportdesc_type_val = wb.bytes.as_slice()[offset];
@@ -740,7 +770,7 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
                 | ExportDescType::Tableidx
                 | ExportDescType::Memidx
                 | ExportDescType::Globalidx => {
-                    let (_exportdesc_val, exportdesc_val_leb_len) = self.markup_leb_section(
+                    let (exportdesc_val, exportdesc_val_leb_len) = self.markup_leb_section(
                         region,
                         wb,
                         offset,
@@ -762,6 +792,20 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
                             .assign(region, offset + assign_delta, &exportdesc_type)
                             .map_err(remap_error(Error::FatalAssignExternalChip))?;
                     }
+                    if exportdesc_type == ExportDescType::Globalidx
+                        && self.config.reject_exported_mutable_globals
+                    {
+                        let global_mutabilities = wb
+                            .global_mutabilities()
+                            .map_err(remap_error_to_compute_value_at(offset))?;
+                        if global_mutabilities
+                            .get(exportdesc_val as usize)
+                            .copied()
+                            .unwrap_or_default()
+                        {
+                            return Err(Error::ExportedMutableGlobalAt(offset));
+                        }
+                    }
                     offset += exportdesc_val_leb_len;
                 }
             }
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
@@ -9,9 +9,14 @@ use eth_types::{Field, Hash, ToWord};
 
 use crate::wasm_circuit::{
     bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+    common::ErrorCodeColumns,
     leb128::circuit::LEB128Chip,
     sections::export::body::circuit::WasmExportSectionBodyChip,
-    types::SharedState,
+    tables::{
+        byte_set::config::ByteSetTableConfig, utf8_transition::config::Utf8TransitionTableConfig,
+    },
+    types::SharedStateRef,
+    utf8::circuit::UTF8Chip,
 };
 
 #[derive(Default)]
@@ -24,8 +29,10 @@ struct TestCircuit<'a, F> {
 
 #[derive(Clone)]
 struct TestCircuitConfig<F: Field> {
-    body_chip: Rc<WasmExportSectionBodyChip<F>>,
-    wb_table: Rc<WasmBytecodeTable>,
+    body_chip: Arc<WasmExportSectionBodyChip<F>>,
+    wb_table: Arc<WasmBytecodeTable>,
+    byte_set_table_config: ByteSetTableConfig<F>,
+    utf8_transition_table_config: Arc<Utf8TransitionTableConfig<F>>,
     _marker: PhantomData<F>,
 }
 
@@ -38,32 +45,43 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
     }
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, false));
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, false));
         let func_count = cs.advice_column();
-        let error_code = cs.advice_column();
+        let error_code = ErrorCodeColumns::configure(cs);
         let body_byte_rev_index = cs.advice_column();
         let body_item_rev_count = cs.advice_column();
 
-        let shared_state = Rc::new(RefCell::new(SharedState::default()));
+        let shared_state = SharedStateRef::default();
 
         let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
-        let leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
+        let leb128_chip = Arc::new(LEB128Chip::construct(leb128_config));
+        let byte_set_table_config = ByteSetTableConfig::configure(cs);
+
+        let utf8_transition_table_config = Arc::new(Utf8TransitionTableConfig::configure(cs));
+        let utf8_config =
+            UTF8Chip::<F>::configure(cs, utf8_transition_table_config.clone(), &wb_table.value);
+        let utf8_chip = Arc::new(UTF8Chip::construct(utf8_config));
 
         let wasm_export_section_body_config = WasmExportSectionBodyChip::configure(
             cs,
             wb_table.clone(),
             leb128_chip.clone(),
+            &byte_set_table_config,
+            utf8_chip.clone(),
             func_count,
             shared_state,
             body_byte_rev_index,
             body_item_rev_count,
             error_code,
+            false,
         );
         let wasm_export_section_body_chip =
             WasmExportSectionBodyChip::construct(wasm_export_section_body_config);
         let test_circuit_config = TestCircuitConfig {
-            body_chip: Rc::new(wasm_export_section_body_chip),
+            body_chip: Arc::new(wasm_export_section_body_chip),
             wb_table: wb_table.clone(),
+            byte_set_table_config,
+            utf8_transition_table_config,
             _marker: Default::default(),
         };
 
@@ -86,6 +104,8 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
                 },
             )
             .unwrap();
+        config.utf8_transition_table_config.load(&mut layouter)?;
+        config.byte_set_table_config.load(&mut layouter)?;
         layouter.assign_region(
             || "wasm_export_section_body region",
             |mut region| {
@@ -119,7 +139,7 @@ mod wasm_export_section_body_tests {
     };
 
     fn test<'a, F: Field>(test_circuit: TestCircuit<'_, F>, is_ok: bool) {
-        let k = 8;
+        let k = 9;
         let prover = MockProver::run(k, &test_circuit, vec![]).unwrap();
         if is_ok {
             prover.assert_satisfied();
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Region, Value},
@@ -15,33 +15,34 @@ use crate::{
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
-            configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip, WasmErrorAwareChip,
-            WasmFuncCountAwareChip, WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
+            ErrorCodeColumns, SectionBodyChip, SectionBodyChipResources, SectionSelectors,
+            SectionTransition, WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip,
+            WasmErrorAwareChip, WasmFuncCountAwareChip, WasmMarkupLeb128SectionAwareChip,
+            WasmSectionTransitionsAwareChip, WasmSharedStateAwareChip,
         },
         error::{remap_error_to_assign_at, Error},
         leb128::circuit::LEB128Chip,
         sections::{consts::LebParams, function::body::types::AssignType},
-        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedState},
+        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedStateRef, WasmSection},
     },
 };
 
 #[derive(Debug, Clone)]
 pub struct WasmFunctionSectionBodyConfig<F: Field> {
-    pub q_enable: Column<Fixed>,
-    pub q_first: Column<Fixed>,
-    pub q_last: Column<Fixed>,
+    pub selectors: SectionSelectors,
     pub is_items_count: Column<Fixed>,
     pub is_typeidx: Column<Fixed>,
 
-    pub leb128_chip: Rc<LEB128Chip<F>>,
+    pub leb128_chip: Arc<LEB128Chip<F>>,
 
-    func_count: Column<Advice>,
-    body_item_rev_count: Column<Advice>,
+    pub func_count: Column<Advice>,
+    pub body_item_rev_count: Column<Advice>,
+    /// items count of the section, on every row of the body
+    pub items_count: Column<Advice>,
 
-    error_code: Column<Advice>,
+    error_code: ErrorCodeColumns,
 
-    shared_state: Rc<RefCell<SharedState>>,
+    shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -58,18 +59,50 @@ impl<F: Field> WasmMarkupLeb128SectionAwareChip<F> for WasmFunctionSectionBodyCh
 
 impl<F: Field> WasmCountPrefixedItemsAwareChip<F> for WasmFunctionSectionBodyChip<F> {}
 
+impl<F: Field> WasmSectionTransitionsAwareChip<F> for WasmFunctionSectionBodyChip<F> {}
+
 impl<F: Field> WasmErrorAwareChip<F> for WasmFunctionSectionBodyChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmFunctionSectionBodyChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
 
+impl<F: Field> SectionBodyChip<F> for WasmFunctionSectionBodyChip<F> {
+    const SECTION: WasmSection = WasmSection::Function;
+
+    fn configure_with_resources(
+        cs: &mut ConstraintSystem<F>,
+        resources: &SectionBodyChipResources<F>,
+    ) -> Self {
+        let config = Self::configure(
+            cs,
+            resources.wb_table.clone(),
+            resources.leb128_chip.clone(),
+            resources.func_count,
+            resources.shared_state.clone(),
+            resources.body_item_rev_count_l1,
+            resources.error_code,
+        );
+        Self::construct(config)
+    }
+
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error> {
+        self.assign_auto(region, wb, wb_offset, assign_delta)
+    }
+}
+
 impl<F: Field> WasmFuncCountAwareChip<F> for WasmFunctionSectionBodyChip<F> {
     fn func_count_col(&self) -> Column<Advice> {
         self.config.func_count
@@ -95,14 +128,9 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmFunctionSectionBodyChip<F> {
             "assign at {} q_enable {} assign_types {:?} assign_value {} byte_val {:x?}",
             assign_offset, q_enable, assign_types, assign_value, wb.bytes[wb_offset],
         );
-        region
-            .assign_fixed(
-                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
-                self.config.q_enable,
-                assign_offset,
-                || Value::known(F::from(q_enable as u64)),
-            )
-            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.config
+            .selectors
+            .assign_q_enable(region, assign_offset, q_enable)?;
         self.assign_func_count(region, assign_offset)?;
 
         for assign_type in assign_types {
@@ -114,29 +142,14 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmFunctionSectionBodyChip<F> {
             }
             match assign_type {
                 AssignType::QFirst => {
-                    region
-                        .assign_fixed(
-                            || {
-                                format!(
-                                    "assign 'q_first' val {} at {}",
-                                    assign_value, assign_offset
-                                )
-                            },
-                            self.config.q_first,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_first(region, assign_offset, assign_value)?;
                 }
                 AssignType::QLast => {
-                    region
-                        .assign_fixed(
-                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
-                            self.config.q_last,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_last(region, assign_offset, assign_value)?;
                 }
                 AssignType::IsItemsCount => {
                     region
@@ -183,6 +196,21 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmFunctionSectionBodyChip<F> {
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
+                AssignType::ItemsCount => {
+                    region
+                        .assign_advice(
+                            || {
+                                format!(
+                                    "assign 'items_count' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.items_count,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
                 AssignType::ErrorCode => {
                     self.assign_error_code(region, assign_offset, None)?;
                 }
@@ -203,18 +231,18 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        _wb_table: Rc<WasmBytecodeTable>,
-        leb128_chip: Rc<LEB128Chip<F>>,
+        _wb_table: Arc<WasmBytecodeTable>,
+        leb128_chip: Arc<LEB128Chip<F>>,
         func_count: Column<Advice>,
-        shared_state: Rc<RefCell<SharedState>>,
+        shared_state: SharedStateRef,
         body_item_rev_count: Column<Advice>,
-        error_code: Column<Advice>,
+        error_code: ErrorCodeColumns,
     ) -> WasmFunctionSectionBodyConfig<F> {
-        let q_enable = cs.fixed_column();
-        let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        let selectors = SectionSelectors::configure(cs);
+        let SectionSelectors { q_enable, q_last, .. } = selectors;
         let is_items_count = cs.fixed_column();
         let is_typeidx = cs.fixed_column();
+        let items_count = cs.advice_column();
 
         Self::configure_count_prefixed_items_checks(
             cs,
@@ -225,7 +253,7 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
@@ -248,30 +276,18 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
             let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                 vc,
                 q_enable,
-                &shared_state.borrow(),
+                &shared_state.read(),
                 error_code,
             );
-            let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
-            let not_q_last_expr = not::expr(q_last_expr.clone());
             let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
             let is_typeidx_expr = vc.query_fixed(is_typeidx, Rotation::cur());
+            let q_first_expr = vc.query_fixed(selectors.q_first, Rotation::cur());
+            let items_count_expr = vc.query_advice(items_count, Rotation::cur());
 
-            let leb128_is_last_byte_expr =
-                vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
-
-            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_items_count is boolean", is_items_count_expr.clone());
             cb.require_boolean("is_typeidx is boolean", is_typeidx_expr.clone());
 
-            configure_constraints_for_q_first_and_q_last(
-                &mut cb,
-                vc,
-                &q_enable,
-                &q_first,
-                &[is_items_count],
-                &q_last,
-                &[is_typeidx],
-            );
+            selectors.configure_constraints(&mut cb, vc, &[is_items_count], &[is_typeidx]);
 
             cb.require_equal(
                 "exactly one mark flag active at the same time",
@@ -290,46 +306,34 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
                 },
             );
 
-            // is_items_count+ -> is_typeidx+
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_items_count+ -> is_typeidx+",
-                and::expr([not_q_last_expr.clone(), is_items_count_expr.clone()]),
-                true,
-                &[is_items_count, is_typeidx],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_items_count+ -> is_typeidx+",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                    is_items_count_expr.clone(),
-                ]),
-                true,
-                &[is_typeidx],
-            );
-            configure_transition_check(
+            cb.condition(is_items_count_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_items_count -> items_count=leb128.sn",
+                    items_count_expr.clone(),
+                    vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
+                )
+            });
+            cb.condition(not::expr(q_first_expr), |cb| {
+                cb.require_equal(
+                    "!q_first -> items_count=prev.items_count",
+                    items_count_expr.clone(),
+                    vc.query_advice(items_count, Rotation::prev()),
+                )
+            });
+
+            Self::configure_transitions(
                 &mut cb,
                 vc,
-                "check next: is_typeidx+",
-                and::expr([not_q_last_expr.clone(), is_typeidx_expr.clone()]),
-                true,
-                &[is_typeidx],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_typeidx+",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                    is_typeidx_expr.clone(),
-                ]),
-                true,
-                &[is_typeidx],
+                q_last,
+                leb128_chip.config.is_last_byte,
+                vec![
+                    SectionTransition::leb(
+                        "check next: is_items_count+ -> is_typeidx+",
+                        is_items_count,
+                        &[is_typeidx],
+                    ),
+                    SectionTransition::leb("check next: is_typeidx+", is_typeidx, &[is_typeidx]),
+                ],
             );
 
             cb.gate(q_enable_expr.clone())
@@ -338,14 +342,13 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
         let config = WasmFunctionSectionBodyConfig::<F> {
             _marker: PhantomData,
 
-            q_enable,
-            q_first,
-            q_last,
+            selectors,
             is_items_count,
             is_typeidx,
             leb128_chip,
             func_count,
             body_item_rev_count,
+            items_count,
             error_code,
             shared_state,
         };
@@ -380,6 +383,15 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
                 body_item_rev_count,
                 None,
             )?;
+            self.assign(
+                region,
+                &wb,
+                offset,
+                assign_delta,
+                &[AssignType::ItemsCount],
+                items_count,
+                None,
+            )?;
         }
         self.assign(
             region,
@@ -415,6 +427,15 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
                     body_item_rev_count,
                     None,
                 )?;
+                self.assign(
+                    region,
+                    &wb,
+                    offset,
+                    assign_delta,
+                    &[AssignType::ItemsCount],
+                    items_count,
+                    None,
+                )?;
             }
         }
 
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
@@ -9,9 +9,10 @@ use eth_types::{Field, Hash, ToWord};
 
 use crate::wasm_circuit::{
     bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+    common::ErrorCodeColumns,
     leb128::circuit::LEB128Chip,
     sections::function::body::circuit::WasmFunctionSectionBodyChip,
-    types::SharedState,
+    types::SharedStateRef,
 };
 
 #[derive(Default)]
@@ -24,8 +25,8 @@ struct TestCircuit<'a, F> {
 
 #[derive(Clone)]
 struct TestCircuitConfig<F: Field> {
-    body_chip: Rc<WasmFunctionSectionBodyChip<F>>,
-    wb_table: Rc<WasmBytecodeTable>,
+    body_chip: Arc<WasmFunctionSectionBodyChip<F>>,
+    wb_table: Arc<WasmBytecodeTable>,
     _marker: PhantomData<F>,
 }
 
@@ -38,15 +39,15 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
     }
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, false));
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, false));
         let func_count = cs.advice_column();
-        let error_code = cs.advice_column();
+        let error_code = ErrorCodeColumns::configure(cs);
         let body_item_rev_count = cs.advice_column();
 
-        let shared_state = Rc::new(RefCell::new(SharedState::default()));
+        let shared_state = SharedStateRef::default();
 
         let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
-        let leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
+        let leb128_chip = Arc::new(LEB128Chip::construct(leb128_config));
 
         let wasm_function_section_body_config = WasmFunctionSectionBodyChip::configure(
             cs,
@@ -60,7 +61,7 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
         let wasm_function_section_body_chip =
             WasmFunctionSectionBodyChip::construct(wasm_function_section_body_config);
         let test_circuit_config = TestCircuitConfig {
-            body_chip: Rc::new(wasm_function_section_body_chip),
+            body_chip: Arc::new(wasm_function_section_body_chip),
             wb_table: wb_table.clone(),
             _marker: Default::default(),
         };
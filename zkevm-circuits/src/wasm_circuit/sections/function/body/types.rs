@@ -7,6 +7,7 @@ pub enum AssignType {
     IsTypeidx,
 
     BodyItemRevCount,
+    ItemsCount,
 
     ErrorCode,
 }
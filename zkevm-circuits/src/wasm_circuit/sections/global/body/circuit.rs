@@ -1,11 +1,10 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Region, Value},
     plonk::{Advice, Column, ConstraintSystem, Fixed},
     poly::Rotation,
 };
-use itertools::Itertools;
 use log::debug;
 
 use eth_types::Field;
@@ -19,30 +18,32 @@ use crate::{
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
-            configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip, WasmErrorAwareChip,
-            WasmFuncCountAwareChip, WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
+            configure_transition_check, ErrorCodeColumns, SectionBodyChip, SectionBodyChipResources,
+            SectionSelectors, WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip,
+            WasmErrorAwareChip, WasmFuncCountAwareChip, WasmMarkupLeb128SectionAwareChip,
+            WasmSharedStateAwareChip,
         },
         consts::WASM_BLOCK_END,
         error::{remap_error_to_assign_at, remap_error_to_invalid_enum_value_at, Error},
         leb128::circuit::LEB128Chip,
         sections::{consts::LebParams, global::body::types::AssignType},
-        tables::dynamic_indexes::{
-            circuit::DynamicIndexesChip,
-            types::{LookupArgsParams, Tag},
+        tables::{
+            byte_set::{config::ByteSetTableConfig, types::ByteSetTag},
+            dynamic_indexes::{
+                circuit::DynamicIndexesChip,
+                types::{LookupArgsParams, Tag},
+            },
         },
         types::{
             AssignDeltaType, AssignValueType, NewWbOffsetType, NumType, NumericInstruction,
-            SharedState, NUM_TYPE_VALUES,
+            SharedStateRef, WasmSection,
         },
     },
 };
 
 #[derive(Debug, Clone)]
 pub struct WasmGlobalSectionBodyConfig<F: Field> {
-    pub q_enable: Column<Fixed>,
-    pub q_first: Column<Fixed>,
-    pub q_last: Column<Fixed>,
+    pub selectors: SectionSelectors,
     pub is_items_count: Column<Fixed>,
     pub is_global_type: Column<Fixed>,
     pub is_global_type_ctx: Column<Fixed>,
@@ -52,17 +53,19 @@ pub struct WasmGlobalSectionBodyConfig<F: Field> {
     pub is_expr_delimiter: Column<Fixed>,
 
     pub global_type: Column<Advice>,
+    /// position of the item in the section, shared by all the rows of the item
+    pub global_index: Column<Advice>,
 
-    pub leb128_chip: Rc<LEB128Chip<F>>,
-    pub dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
-    pub global_type_chip: Rc<BinaryNumberChip<F, NumType, 8>>,
+    pub leb128_chip: Arc<LEB128Chip<F>>,
+    pub dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
+    pub global_type_chip: Arc<BinaryNumberChip<F, NumType, 8>>,
 
     func_count: Column<Advice>,
     body_item_rev_count: Column<Advice>,
 
-    error_code: Column<Advice>,
+    error_code: ErrorCodeColumns,
 
-    shared_state: Rc<RefCell<SharedState>>,
+    shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -80,17 +83,50 @@ impl<F: Field> WasmMarkupLeb128SectionAwareChip<F> for WasmGlobalSectionBodyChip
 impl<F: Field> WasmCountPrefixedItemsAwareChip<F> for WasmGlobalSectionBodyChip<F> {}
 
 impl<F: Field> WasmErrorAwareChip<F> for WasmGlobalSectionBodyChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmGlobalSectionBodyChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
 
+impl<F: Field> SectionBodyChip<F> for WasmGlobalSectionBodyChip<F> {
+    const SECTION: WasmSection = WasmSection::Global;
+
+    fn configure_with_resources(
+        cs: &mut ConstraintSystem<F>,
+        resources: &SectionBodyChipResources<F>,
+    ) -> Self {
+        let config = Self::configure(
+            cs,
+            resources.wb_table.clone(),
+            resources.leb128_chip.clone(),
+            &resources.byte_set_table_config,
+            resources.dynamic_indexes_chip.clone(),
+            resources.func_count,
+            resources.shared_state.clone(),
+            resources.body_item_rev_count_l1,
+            resources.error_code,
+            resources.bytecode_number,
+        );
+        Self::construct(config)
+    }
+
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error> {
+        self.assign_auto(region, wb, wb_offset, assign_delta)
+    }
+}
+
 impl<F: Field> WasmFuncCountAwareChip<F> for WasmGlobalSectionBodyChip<F> {
     fn func_count_col(&self) -> Column<Advice> {
         self.config.func_count
@@ -116,14 +152,9 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmGlobalSectionBodyChip<F> {
             "assign at {} q_enable {} assign_types {:?} assign_values {} byte_val {:x?}",
             assign_offset, q_enable, assign_types, assign_value, wb.bytes[wb_offset],
         );
-        region
-            .assign_fixed(
-                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
-                self.config.q_enable,
-                assign_offset,
-                || Value::known(F::from(q_enable as u64)),
-            )
-            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.config
+            .selectors
+            .assign_q_enable(region, assign_offset, q_enable)?;
         self.assign_func_count(region, assign_offset)?;
 
         for assign_type in assign_types {
@@ -135,29 +166,14 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmGlobalSectionBodyChip<F> {
             }
             match assign_type {
                 AssignType::QFirst => {
-                    region
-                        .assign_fixed(
-                            || {
-                                format!(
-                                    "assign 'q_first' val {} at {}",
-                                    assign_value, assign_offset
-                                )
-                            },
-                            self.config.q_first,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_first(region, assign_offset, assign_value)?;
                 }
                 AssignType::QLast => {
-                    region
-                        .assign_fixed(
-                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
-                            self.config.q_last,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_last(region, assign_offset, assign_value)?;
                 }
                 AssignType::IsItemsCount => {
                     region
@@ -301,6 +317,21 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmGlobalSectionBodyChip<F> {
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
+                AssignType::GlobalIndex => {
+                    region
+                        .assign_advice(
+                            || {
+                                format!(
+                                    "assign 'global_index' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.global_index,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
                 AssignType::ErrorCode => {
                     self.assign_error_code(region, assign_offset, None)?;
                 }
@@ -321,18 +352,18 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        wb_table: Rc<WasmBytecodeTable>,
-        leb128_chip: Rc<LEB128Chip<F>>,
-        dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+        wb_table: Arc<WasmBytecodeTable>,
+        leb128_chip: Arc<LEB128Chip<F>>,
+        byte_set_table_config: &ByteSetTableConfig<F>,
+        dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
         func_count: Column<Advice>,
-        shared_state: Rc<RefCell<SharedState>>,
+        shared_state: SharedStateRef,
         body_item_rev_count: Column<Advice>,
-        error_code: Column<Advice>,
+        error_code: ErrorCodeColumns,
         bytecode_number: Column<Advice>,
     ) -> WasmGlobalSectionBodyConfig<F> {
-        let q_enable = cs.fixed_column();
-        let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        let selectors = SectionSelectors::configure(cs);
+        let SectionSelectors { q_enable, q_last, .. } = selectors;
         let is_items_count = cs.fixed_column();
         let is_global_type = cs.fixed_column();
         let is_global_type_ctx = cs.fixed_column();
@@ -342,8 +373,9 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
         let is_expr_delimiter = cs.fixed_column();
 
         let global_type = cs.advice_column();
+        let global_index = cs.advice_column();
         let config = BinaryNumberChip::configure(cs, is_global_type_ctx, Some(global_type.into()));
-        let global_type_chip = Rc::new(BinaryNumberChip::construct(config));
+        let global_type_chip = Arc::new(BinaryNumberChip::construct(config));
 
         dynamic_indexes_chip.lookup_args(
             "global section has valid setup for mem indexes",
@@ -354,7 +386,7 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
                     * Self::get_selector_expr_enriched_with_error_processing(
                         vc,
                         q_enable,
-                        &shared_state.borrow(),
+                        &shared_state.read(),
                         error_code,
                     );
                 LookupArgsParams {
@@ -376,7 +408,7 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
@@ -387,10 +419,32 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
             |vc| vc.query_fixed(q_last, Rotation::cur()),
         );
 
+        for (name, tag, is_tagged_byte) in [
+            ("is_global_type has eligible byte value", ByteSetTag::NumType, is_global_type),
+            (
+                "is_init_opcode has eligible byte value",
+                ByteSetTag::GlobalInitOpcode,
+                is_init_opcode,
+            ),
+        ] {
+            byte_set_table_config.lookup_byte(cs, name, tag, |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                );
+                (
+                    q_enable_expr * vc.query_fixed(is_tagged_byte, Rotation::cur()),
+                    vc.query_advice(wb_table.value, Rotation::cur()),
+                )
+            });
+        }
+
         cs.create_gate("WasmGlobalSectionBody gate", |vc| {
             let mut cb = BaseConstraintBuilder::default();
 
-            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.read(), error_code);
             // let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
             let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
             let not_q_last_expr = not::expr(q_last_expr.clone());
@@ -406,9 +460,11 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
 
             let global_type_expr = vc.query_advice(global_type, Rotation::cur());
 
+            let global_index_expr = vc.query_advice(global_index, Rotation::cur());
+            let global_index_prev_expr = vc.query_advice(global_index, Rotation::prev());
+
             let leb128_is_last_byte_expr = vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
 
-            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_items_count is boolean", is_items_count_expr.clone());
             cb.require_boolean("is_global_type is boolean", is_global_type_expr.clone());
             cb.require_boolean("is_mut_prop is boolean", is_mut_prop_expr.clone());
@@ -416,15 +472,7 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
             cb.require_boolean("is_init_val is boolean", is_init_val_expr.clone());
             cb.require_boolean("is_expr_delimiter is boolean", is_expr_delimiter_expr.clone());
 
-            configure_constraints_for_q_first_and_q_last(
-                &mut cb,
-                vc,
-                &q_enable,
-                &q_first,
-                &[is_items_count],
-                &q_last,
-                &[is_expr_delimiter],
-            );
+            selectors.configure_constraints(&mut cb, vc, &[is_items_count], &[is_expr_delimiter]);
 
             cb.require_equal(
                 "exactly one mark flag active at the same time",
@@ -470,6 +518,38 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
                 }
             );
 
+            cb.condition(
+                is_items_count_expr.clone(),
+                |cb| {
+                    cb.require_zero(
+                        "is_items_count => global_index=0",
+                        global_index_expr.clone(),
+                    );
+                }
+            );
+            cb.condition(
+                is_global_type_expr.clone(),
+                |cb| {
+                    // previous row is either the items count or the delimiter of the previous item
+                    cb.require_equal(
+                        "is_global_type => global_index=prev.global_index+prev.is_expr_delimiter",
+                        global_index_expr.clone(),
+                        global_index_prev_expr.clone()
+                            + vc.query_fixed(is_expr_delimiter, Rotation::prev()),
+                    );
+                }
+            );
+            cb.condition(
+                not::expr(is_items_count_expr.clone() + is_global_type_expr.clone()),
+                |cb| {
+                    cb.require_equal(
+                        "global_index is the same for all the rows of an item",
+                        global_index_expr.clone(),
+                        global_index_prev_expr.clone(),
+                    );
+                }
+            );
+
             cb.condition(
                 or::expr([
                     is_items_count_expr.clone(),
@@ -576,17 +656,6 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
                 &[is_global_type],
             );
 
-            cb.condition(
-                is_global_type_expr.clone(),
-                |cb| {
-                    cb.require_in_set(
-                        "is_global_type has eligible byte value",
-                        byte_val_expr.clone(),
-                        NUM_TYPE_VALUES.iter().map(|&v| v.expr()).collect_vec(),
-                    )
-                }
-            );
-
             cb.condition(
                 is_mut_prop_expr.clone(),
                 |cb| {
@@ -600,17 +669,6 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
             cb.condition(
                 is_init_opcode_expr.clone(),
                 |cb| {
-                    cb.require_in_set(
-                        "is_init_opcode has eligible byte value",
-                        byte_val_expr.clone(),
-                        vec![
-                            NumericInstruction::I32Const.expr(),
-                            NumericInstruction::I64Const.expr(),
-                            // add support for float types?
-                            // F32Const,
-                            // F64Const,
-                        ],
-                    );
                     let global_type_is_i32_expr = global_type_chip.config.value_equals(NumType::I32, Rotation::cur())(vc);
                     cb.require_zero(
                         "is_init_opcode && global_type_is_i32 => global type corresponds to init opcode",
@@ -641,9 +699,7 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
         let config = WasmGlobalSectionBodyConfig::<F> {
             _marker: PhantomData,
 
-            q_enable,
-            q_first,
-            q_last,
+            selectors,
             is_items_count,
             is_global_type,
             is_global_type_ctx,
@@ -652,6 +708,7 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
             is_init_val,
             is_expr_delimiter,
             global_type,
+            global_index,
             leb128_chip,
             dynamic_indexes_chip,
             global_type_chip,
@@ -691,15 +748,24 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
                 body_item_rev_count,
                 None,
             )?;
+            self.assign(
+                region,
+                &wb,
+                offset,
+                assign_delta,
+                &[AssignType::GlobalIndex],
+                0,
+                None,
+            )?;
         }
         let dynamic_indexes_offset = self.config.dynamic_indexes_chip.assign_auto(
             region,
-            self.config.shared_state.borrow().dynamic_indexes_offset,
+            self.config.shared_state.read().dynamic_indexes_offset,
             assign_delta,
             items_count as usize,
             Tag::GlobalIndex,
         )?;
-        self.config.shared_state.borrow_mut().dynamic_indexes_offset = dynamic_indexes_offset;
+        self.config.shared_state.write().dynamic_indexes_offset = dynamic_indexes_offset;
         self.assign(
             region,
             &wb,
@@ -711,7 +777,7 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
         )?;
         offset += items_count_leb_len;
 
-        for _item_index in 0..items_count {
+        for item_index in 0..items_count {
             body_item_rev_count -= 1;
             let item_start_offset = offset;
 
@@ -825,6 +891,15 @@ impl<F: Field> WasmGlobalSectionBodyChip<F> {
                     body_item_rev_count,
                     None,
                 )?;
+                self.assign(
+                    region,
+                    &wb,
+                    offset,
+                    assign_delta,
+                    &[AssignType::GlobalIndex],
+                    item_index,
+                    None,
+                )?;
             }
         }
 
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
@@ -7,12 +7,21 @@ use halo2_proofs::{
 
 use eth_types::{Field, Hash, ToWord};
 
-use crate::wasm_circuit::{
-    bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
-    leb128::circuit::LEB128Chip,
-    sections::global::body::circuit::WasmGlobalSectionBodyChip,
-    tables::dynamic_indexes::circuit::DynamicIndexesChip,
-    types::SharedState,
+use crate::{
+    table::WasmGlobalTable,
+    wasm_circuit::{
+        bytecode::{
+            bytecode::{decode_global_init_values, WasmBytecode},
+            bytecode_table::WasmBytecodeTable,
+        },
+        common::ErrorCodeColumns,
+        leb128::circuit::LEB128Chip,
+        sections::global::body::circuit::WasmGlobalSectionBodyChip,
+        tables::{
+            byte_set::config::ByteSetTableConfig, dynamic_indexes::circuit::DynamicIndexesChip,
+        },
+        types::SharedStateRef,
+    },
 };
 
 #[derive(Default)]
@@ -20,13 +29,17 @@ struct TestCircuit<'a, F> {
     code_hash: Hash,
     bytecode: &'a [u8],
     offset_start: usize,
+    /// added to the first init value loaded into the global table
+    init_value_delta: u64,
     _marker: PhantomData<F>,
 }
 
 #[derive(Clone)]
 struct TestCircuitConfig<F: Field> {
-    body_chip: Rc<WasmGlobalSectionBodyChip<F>>,
-    wb_table: Rc<WasmBytecodeTable>,
+    body_chip: Arc<WasmGlobalSectionBodyChip<F>>,
+    wb_table: Arc<WasmBytecodeTable>,
+    byte_set_table_config: ByteSetTableConfig<F>,
+    global_table: WasmGlobalTable,
     _marker: PhantomData<F>,
 }
 
@@ -39,24 +52,26 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
     }
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, false));
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, false));
         let func_count = cs.advice_column();
-        let error_code = cs.advice_column();
+        let error_code = ErrorCodeColumns::configure(cs);
         let bytecode_number = cs.advice_column();
         let body_item_rev_count = cs.advice_column();
 
-        let shared_state = Rc::new(RefCell::new(SharedState::default()));
+        let shared_state = SharedStateRef::default();
 
         let config = DynamicIndexesChip::configure(cs, shared_state.clone());
-        let dynamic_indexes_chip = Rc::new(DynamicIndexesChip::construct(config));
+        let dynamic_indexes_chip = Arc::new(DynamicIndexesChip::construct(config));
 
         let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
-        let leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
+        let leb128_chip = Arc::new(LEB128Chip::construct(leb128_config));
+        let byte_set_table_config = ByteSetTableConfig::configure(cs);
 
         let wasm_global_section_body_config = WasmGlobalSectionBodyChip::configure(
             cs,
             wb_table.clone(),
             leb128_chip.clone(),
+            &byte_set_table_config,
             dynamic_indexes_chip.clone(),
             func_count,
             shared_state.clone(),
@@ -64,11 +79,19 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
             error_code,
             bytecode_number,
         );
+        let global_table = WasmGlobalTable::construct(cs);
+        global_table.configure_global_section_lookup(
+            cs,
+            &wb_table,
+            &wasm_global_section_body_config,
+        );
         let wasm_global_section_body_chip =
             WasmGlobalSectionBodyChip::construct(wasm_global_section_body_config);
         let test_circuit_config = TestCircuitConfig {
-            body_chip: Rc::new(wasm_global_section_body_chip),
+            body_chip: Arc::new(wasm_global_section_body_chip),
             wb_table: wb_table.clone(),
+            byte_set_table_config,
+            global_table,
             _marker: Default::default(),
         };
 
@@ -91,6 +114,7 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
                 },
             )
             .unwrap();
+        config.byte_set_table_config.load(&mut layouter)?;
         layouter.assign_region(
             || "wasm_global_section_body region",
             |mut region| {
@@ -105,6 +129,21 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
                 Ok(())
             },
         )?;
+        let mut init_values = if wb.bytes.is_empty() {
+            vec![]
+        } else {
+            decode_global_init_values(&wb.bytes, self.offset_start).unwrap()
+        };
+        if let Some(init_value) = init_values.first_mut() {
+            *init_value += self.init_value_delta;
+        }
+        config.global_table.load(
+            &mut layouter,
+            init_values
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| (wb.code_hash, index as u64, value)),
+        )?;
 
         Ok(())
     }
@@ -147,7 +186,7 @@ mod wasm_global_section_body_tests {
             code_hash,
             bytecode: &bytecode,
             offset_start: 0,
-            _marker: Default::default(),
+            ..Default::default()
         };
         test(test_circuit, true);
     }
@@ -166,8 +205,22 @@ mod wasm_global_section_body_tests {
             code_hash,
             bytecode: &bytecode,
             offset_start: 0,
-            _marker: Default::default(),
+            ..Default::default()
         };
         test(test_circuit, true);
     }
+
+    #[test]
+    pub fn file2_wrong_init_value_in_global_table_fails() {
+        let bytecode = wat_extract_section_body_bytecode("./test_files/cc2.wat", Kind::Global);
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            init_value_delta: 1,
+            ..Default::default()
+        };
+        test(test_circuit, false);
+    }
 }
@@ -14,6 +14,7 @@ pub enum AssignType {
     IsExprDelimiter,
 
     BodyItemRevCount,
+    GlobalIndex,
 
     ErrorCode,
 }
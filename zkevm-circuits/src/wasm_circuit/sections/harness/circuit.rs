@@ -0,0 +1,170 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use eth_types::Field;
+
+use crate::wasm_circuit::{
+    builder::WasmCircuitOptions,
+    bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+    common::{ErrorCodeColumns, SectionBodyChip, SectionBodyChipResources, WasmSharedStateAwareChip},
+    leb128::circuit::LEB128Chip,
+    tables::{
+        byte_set::config::ByteSetTableConfig, dynamic_indexes::circuit::DynamicIndexesChip,
+        opcode::config::OpcodeTableConfig, utf8_transition::config::Utf8TransitionTableConfig,
+    },
+    types::{AssignDeltaType, SharedStateRef},
+    utf8::circuit::UTF8Chip,
+};
+
+/// Test circuit assigning a single section body with chip `C`. Bytecode table, LEB128 and UTF8
+/// chips, byte set and opcode tables, dynamic indexes and shared state are wired the same way
+/// `WasmChip` does it.
+pub struct SectionTestCircuit<F, C> {
+    pub bytecode: Vec<u8>,
+    pub offset_start: usize,
+    pub assign_delta_base: AssignDeltaType,
+    _marker: PhantomData<(F, C)>,
+}
+
+impl<F, C> Default for SectionTestCircuit<F, C> {
+    fn default() -> Self {
+        Self {
+            bytecode: vec![],
+            offset_start: 0,
+            assign_delta_base: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, C: SectionBodyChip<F>> SectionTestCircuit<F, C> {
+    /// `bytecode` is a section body (without section id and length)
+    pub fn new(bytecode: Vec<u8>) -> Self {
+        Self {
+            bytecode,
+            ..Default::default()
+        }
+    }
+
+    /// Takes the body of `C::SECTION` out of a whole module, `None` if module has no such section
+    pub fn from_module(bytes: Vec<u8>) -> Option<Self> {
+        let wb = WasmBytecode::new(bytes);
+        let section_body = wb.section_body(C::SECTION)?;
+        Some(Self::new(wb.bytes[section_body].to_vec()))
+    }
+}
+
+pub struct SectionTestCircuitConfig<F: Field, C> {
+    pub body_chip: Arc<C>,
+    pub resources: SectionBodyChipResources<F>,
+    utf8_transition_table_config: Arc<Utf8TransitionTableConfig<F>>,
+}
+
+impl<F: Field, C> Clone for SectionTestCircuitConfig<F, C> {
+    fn clone(&self) -> Self {
+        Self {
+            body_chip: self.body_chip.clone(),
+            resources: self.resources.clone(),
+            utf8_transition_table_config: self.utf8_transition_table_config.clone(),
+        }
+    }
+}
+
+impl<F: Field, C: SectionBodyChip<F>> Circuit<F> for SectionTestCircuit<F, C> {
+    type Config = SectionTestCircuitConfig<F, C>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, false));
+        let func_count = cs.advice_column();
+        let error_code = ErrorCodeColumns::configure(cs);
+        let bytecode_number = cs.advice_column();
+        let body_byte_rev_index_l2 = cs.advice_column();
+        let body_item_rev_count_l1 = cs.advice_column();
+        let body_item_rev_count_l2 = cs.advice_column();
+
+        let shared_state = SharedStateRef::default();
+
+        let utf8_transition_table_config = Arc::new(Utf8TransitionTableConfig::configure(cs));
+        let byte_set_table_config = Arc::new(ByteSetTableConfig::configure(cs));
+        let opcode_table_config = Arc::new(OpcodeTableConfig::configure(cs));
+
+        let config = DynamicIndexesChip::configure(cs, shared_state.clone());
+        let dynamic_indexes_chip = Arc::new(DynamicIndexesChip::construct(config));
+
+        let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
+        let leb128_chip = Arc::new(LEB128Chip::construct(leb128_config));
+
+        let utf8_config =
+            UTF8Chip::<F>::configure(cs, utf8_transition_table_config.clone(), &wb_table.value);
+        let utf8_chip = Arc::new(UTF8Chip::construct(utf8_config));
+
+        let resources = SectionBodyChipResources {
+            wb_table,
+            leb128_chip,
+            utf8_chip,
+            byte_set_table_config,
+            opcode_table_config,
+            dynamic_indexes_chip,
+            shared_state,
+            func_count,
+            body_byte_rev_index_l2,
+            body_item_rev_count_l1,
+            body_item_rev_count_l2,
+            error_code,
+            bytecode_number,
+            max_memory_pages: WasmCircuitOptions::default().max_memory_pages(),
+        };
+        let body_chip = Arc::new(C::configure_with_resources(cs, &resources));
+
+        SectionTestCircuitConfig {
+            body_chip,
+            resources,
+            utf8_transition_table_config,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let wb = WasmBytecode::new(self.bytecode.clone());
+        let assign_delta = self.assign_delta_base;
+        layouter.assign_region(
+            || format!("wasm bytecode table at {}", assign_delta),
+            |mut region| {
+                config.resources.wb_table.load(&mut region, &wb, assign_delta)?;
+                Ok(())
+            },
+        )?;
+        config.utf8_transition_table_config.load(&mut layouter)?;
+        config.resources.byte_set_table_config.load(&mut layouter)?;
+        config.resources.opcode_table_config.load(&mut layouter)?;
+        layouter.assign_region(
+            || "section body region",
+            |mut region| {
+                config.body_chip.shared_state().write().reset();
+                let mut wb_offset = self.offset_start;
+                while wb_offset < wb.bytes.len() {
+                    wb_offset = config
+                        .body_chip
+                        .assign_section_body(&mut region, &wb, wb_offset, assign_delta)
+                        .map_err(|_| Error::Synthesis)?;
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+}
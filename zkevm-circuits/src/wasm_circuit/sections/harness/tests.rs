@@ -0,0 +1,275 @@
+#[cfg(test)]
+mod section_test_circuit_tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use wabt::wat2wasm;
+
+    use crate::wasm_circuit::{
+        common::SectionBodyChip,
+        sections::{
+            code::body::circuit::WasmCodeSectionBodyChip,
+            data::body::circuit::WasmDataSectionBodyChip,
+            element::body::circuit::WasmElementSectionBodyChip,
+            export::body::circuit::WasmExportSectionBodyChip,
+            function::body::circuit::WasmFunctionSectionBodyChip,
+            global::body::circuit::WasmGlobalSectionBodyChip,
+            harness::circuit::SectionTestCircuit,
+            import::body::circuit::WasmImportSectionBodyChip,
+            memory::body::circuit::WasmMemorySectionBodyChip,
+            r#type::body::circuit::WasmTypeSectionBodyChip,
+            start::body::circuit::WasmStartSectionBodyChip,
+            table::body::circuit::WasmTableSectionBodyChip,
+        },
+    };
+
+    const PATHS: [&str; 3] = [
+        "./test_files/cc1.wat",
+        "./test_files/cc2.wat",
+        "./test_files/cc3.wat",
+    ];
+
+    /// Runs chip `C` over its section of every test file that has it, returns how many did
+    fn test_files_ok<C: SectionBodyChip<Fr>>() -> usize {
+        let mut tested = 0;
+        for path in PATHS {
+            let data: Vec<u8> = std::fs::read(path).unwrap();
+            let bytes = wat2wasm(data).unwrap();
+            if let Some(circuit) = SectionTestCircuit::<Fr, C>::from_module(bytes) {
+                let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+                prover.assert_satisfied();
+                tested += 1;
+            }
+        }
+        tested
+    }
+
+    #[test]
+    pub fn type_section_ok() {
+        assert!(test_files_ok::<WasmTypeSectionBodyChip<Fr>>() > 0);
+    }
+
+    #[test]
+    pub fn import_section_ok() {
+        assert!(test_files_ok::<WasmImportSectionBodyChip<Fr>>() > 0);
+    }
+
+    #[test]
+    pub fn function_section_ok() {
+        assert!(test_files_ok::<WasmFunctionSectionBodyChip<Fr>>() > 0);
+    }
+
+    #[test]
+    pub fn table_section_ok() {
+        assert!(test_files_ok::<WasmTableSectionBodyChip<Fr>>() > 0);
+    }
+
+    #[test]
+    pub fn memory_section_ok() {
+        assert!(test_files_ok::<WasmMemorySectionBodyChip<Fr>>() > 0);
+    }
+
+    #[test]
+    pub fn global_section_ok() {
+        assert!(test_files_ok::<WasmGlobalSectionBodyChip<Fr>>() > 0);
+    }
+
+    #[test]
+    pub fn export_section_ok() {
+        assert!(test_files_ok::<WasmExportSectionBodyChip<Fr>>() > 0);
+    }
+
+    #[test]
+    pub fn start_section_ok() {
+        assert!(test_files_ok::<WasmStartSectionBodyChip<Fr>>() > 0);
+    }
+
+    #[test]
+    pub fn element_section_ok() {
+        assert!(test_files_ok::<WasmElementSectionBodyChip<Fr>>() > 0);
+    }
+
+    #[test]
+    pub fn code_section_ok() {
+        assert!(test_files_ok::<WasmCodeSectionBodyChip<Fr>>() > 0);
+    }
+
+    #[test]
+    pub fn data_section_ok() {
+        assert!(test_files_ok::<WasmDataSectionBodyChip<Fr>>() > 0);
+    }
+
+    #[test]
+    pub fn broken_section_body_fails() {
+        let data: Vec<u8> = std::fs::read(PATHS[0]).unwrap();
+        let bytes = wat2wasm(data).unwrap();
+        let mut circuit =
+            SectionTestCircuit::<Fr, WasmFunctionSectionBodyChip<Fr>>::from_module(bytes).unwrap();
+        // items count claims one more item than the body has
+        circuit.bytecode[0] += 1;
+        if let Ok(prover) = MockProver::run(9, &circuit, vec![]) {
+            assert!(prover.verify().is_err());
+        }
+    }
+}
+
+/// Golden witness snapshots of section body chips. Snapshots are stored in
+/// `./test_files/golden/<section>.txt`, a missing snapshot is written on the first run, set
+/// `WASM_GOLDEN_BLESS=1` to rewrite all of them after an intended witness change.
+#[cfg(test)]
+mod section_golden_witness_tests {
+    use std::path::Path;
+
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use itertools::Itertools;
+    use wabt::wat2wasm;
+
+    use crate::wasm_circuit::{
+        common::SectionBodyChip,
+        recorder::recorder,
+        sections::{
+            code::body::circuit::WasmCodeSectionBodyChip,
+            data::body::circuit::WasmDataSectionBodyChip,
+            element::body::circuit::WasmElementSectionBodyChip,
+            export::body::circuit::WasmExportSectionBodyChip,
+            function::body::circuit::WasmFunctionSectionBodyChip,
+            global::body::circuit::WasmGlobalSectionBodyChip,
+            harness::circuit::SectionTestCircuit,
+            import::body::circuit::WasmImportSectionBodyChip,
+            memory::body::circuit::WasmMemorySectionBodyChip,
+            r#type::body::circuit::WasmTypeSectionBodyChip,
+            start::body::circuit::WasmStartSectionBodyChip,
+            table::body::circuit::WasmTableSectionBodyChip,
+        },
+    };
+
+    const GOLDEN_DIR: &str = "./test_files/golden";
+    const PATHS: [&str; 3] = [
+        "./test_files/cc1.wat",
+        "./test_files/cc2.wat",
+        "./test_files/cc3.wat",
+    ];
+
+    /// flags, LEB128 SNs and reverse counters/indexes
+    fn is_key_column(column: &str) -> bool {
+        column.starts_with('Q')
+            || column.starts_with("Is")
+            || column.contains("Leb")
+            || column.contains("RevCount")
+            || column.contains("RevIndex")
+    }
+
+    /// chip type name without module path and generics
+    fn short_chip_name(chip: &str) -> &str {
+        let name = chip.split('<').next().unwrap_or(chip);
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
+    /// Assigns the section of the first test file having it, returns the snapshot of key columns
+    fn snapshot<C: SectionBodyChip<Fr>>() -> String {
+        let circuit = PATHS
+            .iter()
+            .find_map(|path| {
+                let data: Vec<u8> = std::fs::read(path).unwrap();
+                SectionTestCircuit::<Fr, C>::from_module(wat2wasm(data).unwrap())
+            })
+            .unwrap();
+        recorder::start();
+        let prover = MockProver::run(9, &circuit, vec![]);
+        let trace = recorder::stop();
+        prover.unwrap().assert_satisfied();
+
+        // floor planner may run synthesis more than once, identical records are collapsed
+        trace
+            .records
+            .iter()
+            .filter(|r| is_key_column(&r.column))
+            .map(|r| {
+                format!(
+                    "{} {} {} {}",
+                    r.assign_offset,
+                    short_chip_name(&r.chip),
+                    r.column,
+                    r.value
+                )
+            })
+            .unique()
+            .join("\n")
+    }
+
+    fn check_golden<C: SectionBodyChip<Fr>>() {
+        let actual = snapshot::<C>();
+        let path = Path::new(GOLDEN_DIR).join(format!("{:?}.txt", C::SECTION).to_lowercase());
+        if std::env::var("WASM_GOLDEN_BLESS").is_ok() {
+            std::fs::create_dir_all(GOLDEN_DIR).unwrap();
+            std::fs::write(&path, &actual).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "golden {:?} of {:?} section is missing, bless it with WASM_GOLDEN_BLESS=1",
+                path,
+                C::SECTION
+            )
+        });
+        assert!(
+            expected == actual,
+            "witness of {:?} section differs from golden {:?}",
+            C::SECTION,
+            path
+        );
+    }
+
+    #[test]
+    pub fn type_section_golden() {
+        check_golden::<WasmTypeSectionBodyChip<Fr>>();
+    }
+
+    #[test]
+    pub fn import_section_golden() {
+        check_golden::<WasmImportSectionBodyChip<Fr>>();
+    }
+
+    #[test]
+    pub fn function_section_golden() {
+        check_golden::<WasmFunctionSectionBodyChip<Fr>>();
+    }
+
+    #[test]
+    pub fn table_section_golden() {
+        check_golden::<WasmTableSectionBodyChip<Fr>>();
+    }
+
+    #[test]
+    pub fn memory_section_golden() {
+        check_golden::<WasmMemorySectionBodyChip<Fr>>();
+    }
+
+    #[test]
+    pub fn global_section_golden() {
+        check_golden::<WasmGlobalSectionBodyChip<Fr>>();
+    }
+
+    #[test]
+    pub fn export_section_golden() {
+        check_golden::<WasmExportSectionBodyChip<Fr>>();
+    }
+
+    #[test]
+    pub fn start_section_golden() {
+        check_golden::<WasmStartSectionBodyChip<Fr>>();
+    }
+
+    #[test]
+    pub fn element_section_golden() {
+        check_golden::<WasmElementSectionBodyChip<Fr>>();
+    }
+
+    #[test]
+    pub fn code_section_golden() {
+        check_golden::<WasmCodeSectionBodyChip<Fr>>();
+    }
+
+    #[test]
+    pub fn data_section_golden() {
+        check_golden::<WasmDataSectionBodyChip<Fr>>();
+    }
+}
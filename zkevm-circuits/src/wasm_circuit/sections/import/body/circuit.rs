@@ -1,17 +1,16 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Fixed},
+    plonk::{Advice, Column, ConstraintSystem, Expression, Fixed, VirtualCells},
     poly::Rotation,
 };
-use itertools::Itertools;
 use log::debug;
 
 use eth_types::Field;
 use gadgets::{
     binary_number::BinaryNumberChip,
-    less_than::LtInstruction,
+    less_than::{LtChip, LtInstruction},
     util::{and, not, or, Expr},
 };
 
@@ -20,31 +19,49 @@ use crate::{
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
-            configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            LimitTypeFields, WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip,
-            WasmErrorAwareChip, WasmFuncCountAwareChip, WasmLenPrefixedBytesSpanAwareChip,
-            WasmLimitTypeAwareChip, WasmMarkupLeb128SectionAwareChip, WasmNameAwareChip,
-            WasmSharedStateAwareChip,
+            configure_transition_check, limit_type_has_max_expr, ErrorCodeColumns, LimitTypeFields,
+            SectionBodyChip, SectionBodyChipResources, SectionSelectors, WasmAssignAwareChip,
+            WasmCountPrefixedItemsAwareChip, WasmErrorAwareChip, WasmFuncCountAwareChip,
+            WasmLenPrefixedBytesSpanAwareChip, WasmLimitTypeAwareChip,
+            WasmMarkupLeb128SectionAwareChip, WasmNameAwareChip, WasmSharedStateAwareChip,
         },
         error::{
             remap_error, remap_error_to_assign_at, remap_error_to_invalid_enum_value_at, Error,
         },
+        consts::{MAX_IMPORT_MOD_NAME_LEN, MAX_IMPORT_NAME_LEN},
         leb128::circuit::LEB128Chip,
         sections::{consts::LebParams, import::body::types::AssignType},
-        tables::dynamic_indexes::circuit::DynamicIndexesChip,
+        tables::{
+            byte_set::{config::ByteSetTableConfig, types::ByteSetTag},
+            dynamic_indexes::circuit::DynamicIndexesChip,
+            import_whitelist::{
+                config::ImportWhitelistTableConfig,
+                types::{name_rlc, ImportWhitelist, NAME_RLC_BASE},
+            },
+        },
         types::{
             AssignDeltaType, AssignValueType, ImportDescType, LimitType, NewWbOffsetType, RefType,
-            SharedState, IMPORT_DESC_TYPE_VALUES, MUTABILITY_VALUES, REF_TYPE_VALUES,
+            SharedState, SharedStateRef, WasmSection,
         },
         utf8::circuit::UTF8Chip,
     },
 };
 
+/// Module and import names of the current item packed by
+/// [`name_rlc`](crate::wasm_circuit::tables::import_whitelist::types::name_rlc), carried from
+/// the item start to its `importdesc_type` row where function imports are looked up in the table
+#[derive(Debug, Clone)]
+pub struct ImportWhitelistFields<F: Field> {
+    pub table: ImportWhitelistTableConfig<F>,
+    pub mod_name_len: Column<Advice>,
+    pub mod_name_rlc: Column<Advice>,
+    pub import_name_len: Column<Advice>,
+    pub import_name_rlc: Column<Advice>,
+}
+
 #[derive(Debug, Clone)]
 pub struct WasmImportSectionBodyConfig<F: Field> {
-    pub q_enable: Column<Fixed>,
-    pub q_first: Column<Fixed>,
-    pub q_last: Column<Fixed>,
+    pub selectors: SectionSelectors,
     pub is_items_count: Column<Fixed>,
     pub is_mod_name_len: Column<Fixed>,
     pub is_mod_name: Column<Fixed>,
@@ -59,19 +76,24 @@ pub struct WasmImportSectionBodyConfig<F: Field> {
 
     pub is_ref_type: Column<Fixed>,
 
-    pub leb128_chip: Rc<LEB128Chip<F>>,
-    pub utf8_chip: Rc<UTF8Chip<F>>,
-    pub dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+    pub leb128_chip: Arc<LEB128Chip<F>>,
+    pub utf8_chip: Arc<UTF8Chip<F>>,
+    pub dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
     pub importdesc_type: Column<Advice>,
-    pub importdesc_type_chip: Rc<BinaryNumberChip<F, ImportDescType, 8>>,
+    pub importdesc_type_chip: Arc<BinaryNumberChip<F, ImportDescType, 8>>,
+    pub import_whitelist_fields: Option<ImportWhitelistFields<F>>,
+    /// module and field names must not be longer than `max_mod_name_len` and `max_import_name_len`
+    pub max_mod_name_len: u64,
+    pub max_import_name_len: u64,
+    pub name_len_lt_chip: Arc<LtChip<F, 4>>,
 
     func_count: Column<Advice>,
     body_byte_rev_index: Column<Advice>,
     body_item_rev_count: Column<Advice>,
 
-    error_code: Column<Advice>,
+    error_code: ErrorCodeColumns,
 
-    shared_state: Rc<RefCell<SharedState>>,
+    shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -95,17 +117,64 @@ impl<F: Field> WasmNameAwareChip<F> for WasmImportSectionBodyChip<F> {}
 impl<F: Field> WasmLimitTypeAwareChip<F> for WasmImportSectionBodyChip<F> {}
 
 impl<F: Field> WasmErrorAwareChip<F> for WasmImportSectionBodyChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmImportSectionBodyChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
 
+impl<F: Field> SectionBodyChip<F> for WasmImportSectionBodyChip<F> {
+    const SECTION: WasmSection = WasmSection::Import;
+
+    fn configure_with_resources(
+        cs: &mut ConstraintSystem<F>,
+        resources: &SectionBodyChipResources<F>,
+    ) -> Self {
+        let config = Self::configure(
+            cs,
+            resources.wb_table.clone(),
+            resources.leb128_chip.clone(),
+            &resources.byte_set_table_config,
+            resources.utf8_chip.clone(),
+            resources.dynamic_indexes_chip.clone(),
+            resources.func_count,
+            resources.shared_state.clone(),
+            resources.body_byte_rev_index_l2,
+            resources.body_item_rev_count_l1,
+            resources.error_code,
+            None,
+            MAX_IMPORT_MOD_NAME_LEN,
+            MAX_IMPORT_NAME_LEN,
+        );
+        Self::construct(config)
+    }
+
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error> {
+        self.assign_auto(region, wb, wb_offset, assign_delta)
+    }
+
+    fn func_count_increment_expr(&self, vc: &mut VirtualCells<F>) -> Expression<F> {
+        and::expr([
+            vc.query_fixed(self.config.is_importdesc_type, Rotation::cur()),
+            self.config
+                .importdesc_type_chip
+                .config
+                .value_equals(ImportDescType::Typeidx, Rotation::cur())(vc),
+        ])
+    }
+}
+
 impl<F: Field> WasmFuncCountAwareChip<F> for WasmImportSectionBodyChip<F> {
     fn func_count_col(&self) -> Column<Advice> {
         self.config.func_count
@@ -131,14 +200,9 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmImportSectionBodyChip<F> {
             "assign at {} q_enable {} assign_types {:?} assign_value {} byte_val {:x?}",
             assign_offset, q_enable, assign_types, assign_value, wb.bytes[wb_offset],
         );
-        region
-            .assign_fixed(
-                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
-                self.config.q_enable,
-                assign_offset,
-                || Value::known(F::from(q_enable as u64)),
-            )
-            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.config
+            .selectors
+            .assign_q_enable(region, assign_offset, q_enable)?;
 
         for assign_type in assign_types {
             if [
@@ -156,37 +220,16 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmImportSectionBodyChip<F> {
                     .leb128_chip
                     .assign(region, assign_offset, true, p)?;
             }
-            if [AssignType::IsModName, AssignType::IsImportName].contains(assign_type) {
-                let byte_val = wb.bytes[wb_offset];
-                self.config
-                    .utf8_chip
-                    .assign(region, assign_offset, true, byte_val)?;
-            }
             match assign_type {
                 AssignType::QFirst => {
-                    region
-                        .assign_fixed(
-                            || {
-                                format!(
-                                    "assign 'q_first' val {} at {}",
-                                    assign_value, assign_offset
-                                )
-                            },
-                            self.config.q_first,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_first(region, assign_offset, assign_value)?;
                 }
                 AssignType::QLast => {
-                    region
-                        .assign_fixed(
-                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
-                            self.config.q_last,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_last(region, assign_offset, assign_value)?;
                 }
                 AssignType::IsItemsCount => {
                     region
@@ -489,19 +532,22 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        wb_table: Rc<WasmBytecodeTable>,
-        leb128_chip: Rc<LEB128Chip<F>>,
-        utf8_chip: Rc<UTF8Chip<F>>,
-        dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+        wb_table: Arc<WasmBytecodeTable>,
+        leb128_chip: Arc<LEB128Chip<F>>,
+        byte_set_table_config: &ByteSetTableConfig<F>,
+        utf8_chip: Arc<UTF8Chip<F>>,
+        dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
         func_count: Column<Advice>,
-        shared_state: Rc<RefCell<SharedState>>,
+        shared_state: SharedStateRef,
         body_byte_rev_index: Column<Advice>,
         body_item_rev_count: Column<Advice>,
-        error_code: Column<Advice>,
+        error_code: ErrorCodeColumns,
+        import_whitelist: Option<ImportWhitelist>,
+        max_mod_name_len: u64,
+        max_import_name_len: u64,
     ) -> WasmImportSectionBodyConfig<F> {
-        let q_enable = cs.fixed_column();
-        let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        let selectors = SectionSelectors::configure(cs);
+        let SectionSelectors { q_enable, q_first, q_last } = selectors;
         let is_items_count = cs.fixed_column();
         let is_mod_name_len = cs.fixed_column();
         let is_mod_name = cs.fixed_column();
@@ -518,7 +564,7 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
 
         let config =
             BinaryNumberChip::configure(cs, is_importdesc_type_ctx, Some(importdesc_type.into()));
-        let importdesc_type_chip = Rc::new(BinaryNumberChip::construct(config));
+        let importdesc_type_chip = Arc::new(BinaryNumberChip::construct(config));
 
         let limit_type_fields =
             Self::construct_limit_type_fields(cs, q_enable, leb128_chip.as_ref());
@@ -528,6 +574,7 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
             q_enable,
             leb128_chip.as_ref(),
             &limit_type_fields,
+            byte_set_table_config,
         );
 
         let LimitTypeFields {
@@ -538,6 +585,27 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
             ..
         } = limit_type_fields.clone();
 
+        let config = LtChip::configure(
+            cs,
+            |vc| {
+                and::expr([
+                    vc.query_fixed(q_enable, Rotation::cur()),
+                    or::expr([
+                        vc.query_fixed(is_mod_name_len, Rotation::cur()),
+                        vc.query_fixed(is_import_name_len, Rotation::cur()),
+                    ]),
+                    vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur()),
+                ])
+            },
+            |vc| vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
+            |vc| {
+                vc.query_fixed(is_mod_name_len, Rotation::cur()) * (max_mod_name_len + 1).expr()
+                    + vc.query_fixed(is_import_name_len, Rotation::cur())
+                        * (max_import_name_len + 1).expr()
+            },
+        );
+        let name_len_lt_chip = Arc::new(LtChip::construct(config));
+
         Self::configure_len_prefixed_bytes_span_checks(
             cs,
             leb128_chip.as_ref(),
@@ -593,7 +661,7 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
@@ -625,10 +693,33 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
             |vc| vc.query_fixed(q_last, Rotation::cur()),
         );
 
+        for (name, tag, is_tagged_byte) in [
+            ("reference_type => byte value is valid", ByteSetTag::RefType, is_ref_type),
+            ("is_mut_prop => byte_val is valid", ByteSetTag::Mutability, is_mut_prop),
+            (
+                "is_importdesc_type => value is valid",
+                ByteSetTag::ImportDescType,
+                is_importdesc_type,
+            ),
+        ] {
+            byte_set_table_config.lookup_byte(cs, name, tag, |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                );
+                (
+                    q_enable_expr * vc.query_fixed(is_tagged_byte, Rotation::cur()),
+                    vc.query_advice(wb_table.value, Rotation::cur()),
+                )
+            });
+        }
+
         cs.create_gate("WasmImportSectionBody gate", |vc| {
             let mut cb = BaseConstraintBuilder::default();
 
-            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.read(), error_code);
             let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
             let not_q_last_expr = not::expr(q_last_expr.clone());
             let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
@@ -654,7 +745,6 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
             let utf8_chip_q_enabled_expr = vc.query_fixed(utf8_chip.config.q_enable, Rotation::cur());
             let leb128_is_last_byte_expr = vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
 
-            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_items_count is boolean", is_items_count_expr.clone());
             cb.require_boolean("is_mod_name_len is boolean", is_mod_name_len_expr.clone());
             cb.require_boolean("is_mod_name is boolean", is_mod_name_expr.clone());
@@ -668,13 +758,10 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
             cb.require_boolean("is_limit_max is boolean", is_limit_max_expr.clone());
             cb.require_boolean("is_ref_type is boolean", is_ref_type_expr.clone());
 
-            configure_constraints_for_q_first_and_q_last(
+            selectors.configure_constraints(
                 &mut cb,
                 vc,
-                &q_enable,
-                &q_first,
                 &[is_items_count],
-                &q_last,
                 &[is_importdesc_val, is_mut_prop, is_limit_min, is_limit_max],
             );
 
@@ -724,17 +811,6 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 1.expr(),
             );
 
-            cb.condition(
-                is_ref_type_expr.clone(),
-                |cb| {
-                    cb.require_in_set(
-                        "reference_type => byte value is valid",
-                        byte_val_expr.clone(),
-                        REF_TYPE_VALUES.iter().map(|&v| v.expr()).collect_vec(),
-                    )
-                }
-            );
-
             // TODO
             // cb.req(
             //     "is_items_count=1 -> first byte val is not 0",
@@ -769,17 +845,6 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 utf8_chip_q_enabled_expr.clone(),
             );
 
-            cb.condition(
-                is_mut_prop_expr.clone(),
-                |cb| {
-                    cb.require_in_set(
-                        "is_mut_prop => byte_val is valid",
-                        byte_val_expr.clone(),
-                        MUTABILITY_VALUES.iter().map(|&v| v.expr()).collect_vec(),
-                    )
-                }
-            );
-
             // is_items_count+ -> is_item+ (is_mod_name_len+ -> is_mod_name* -> is_import_name_len+ -> is_import_name* -> import_desc+)
             // let importdesc_type_is_global_type_prev_expr = importdesc_type_chip.config.value_equals(ImportDescType::GlobalType, Rotation::prev())(vc);
             let importdesc_type_is_typeidx_expr = importdesc_type_chip.config.value_equals(ImportDescType::Typeidx, Rotation::cur())(vc);
@@ -792,7 +857,7 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
             // let importdesc_type_is_global_type_next_expr = importdesc_type_chip.config.value_equals(ImportDescType::GlobalType, Rotation::next())(vc);
 
             let limit_type_is_min_only_expr = limit_type_chip.config.value_equals(LimitType::MinOnly, Rotation::cur())(vc);
-            let limit_type_is_min_max_expr = limit_type_chip.config.value_equals(LimitType::MinMax, Rotation::cur())(vc);
+            let limit_type_has_max_expr = limit_type_has_max_expr(&limit_type_chip, vc);
 
             configure_transition_check(
                 &mut cb,
@@ -1237,7 +1302,7 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 and::expr([
                     not_q_last_expr.clone(),
                     is_limit_min_expr.clone(),
-                    limit_type_is_min_max_expr.clone(),
+                    limit_type_has_max_expr.clone(),
                     importdesc_type_is_mem_type_expr.clone(),
                 ]),
                 true,
@@ -1251,7 +1316,7 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                     not_q_last_expr.clone(),
                     leb128_is_last_byte_expr.clone(),
                     is_limit_min_expr.clone(),
-                    limit_type_is_min_max_expr.clone(),
+                    limit_type_has_max_expr.clone(),
                     importdesc_type_is_mem_type_expr.clone(),
                 ]),
                 true,
@@ -1450,7 +1515,7 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 and::expr([
                     not_q_last_expr.clone(),
                     is_limit_min_expr.clone(),
-                    limit_type_is_min_max_expr.clone(),
+                    limit_type_has_max_expr.clone(),
                     importdesc_type_is_table_type_expr.clone(),
                 ]),
                 true,
@@ -1464,7 +1529,7 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                     not_q_last_expr.clone(),
                     leb128_is_last_byte_expr.clone(),
                     is_limit_min_expr.clone(),
-                    limit_type_is_min_max_expr.clone(),
+                    limit_type_has_max_expr.clone(),
                     importdesc_type_is_table_type_expr.clone(),
                 ]),
                 true,
@@ -1497,11 +1562,6 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
             cb.condition(
                 is_importdesc_type_expr.clone(),
                 |cb| {
-                    cb.require_in_set(
-                        "is_importdesc_type => value is valid",
-                        byte_val_expr.clone(),
-                        IMPORT_DESC_TYPE_VALUES.iter().map(|&v| v.expr()).collect_vec()
-                    );
                     cb.require_equal(
                         "is_importdesc_type => importdesc_type has valid value",
                         importdesc_type_expr.clone(),
@@ -1510,15 +1570,45 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 }
             );
 
+            cb.condition(
+                and::expr([
+                    or::expr([is_mod_name_len_expr.clone(), is_import_name_len_expr.clone()]),
+                    leb128_is_last_byte_expr.clone(),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "is_mod_name_len || is_import_name_len => name len <= max name len",
+                        name_len_lt_chip.config().is_lt(vc, None),
+                        1.expr(),
+                    );
+                }
+            );
+
             cb.gate(q_enable_expr.clone())
         });
 
+        let import_whitelist_fields = import_whitelist.map(|whitelist| {
+            Self::configure_import_whitelist(
+                cs,
+                whitelist,
+                wb_table.as_ref(),
+                leb128_chip.as_ref(),
+                importdesc_type_chip.as_ref(),
+                q_enable,
+                is_mod_name_len,
+                is_mod_name,
+                is_import_name_len,
+                is_import_name,
+                is_importdesc_type,
+                &shared_state.read(),
+                error_code,
+            )
+        });
+
         let config = WasmImportSectionBodyConfig::<F> {
             _marker: PhantomData,
 
-            q_enable,
-            q_first,
-            q_last,
+            selectors,
             is_items_count,
             is_mod_name_len,
             is_mod_name,
@@ -1535,6 +1625,10 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
             dynamic_indexes_chip,
             importdesc_type,
             importdesc_type_chip,
+            import_whitelist_fields,
+            max_mod_name_len,
+            max_import_name_len,
+            name_len_lt_chip,
             func_count,
             body_byte_rev_index,
             body_item_rev_count,
@@ -1545,6 +1639,261 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
         config
     }
 
+    fn configure_import_whitelist(
+        cs: &mut ConstraintSystem<F>,
+        whitelist: ImportWhitelist,
+        wb_table: &WasmBytecodeTable,
+        leb128_chip: &LEB128Chip<F>,
+        importdesc_type_chip: &BinaryNumberChip<F, ImportDescType, 8>,
+        q_enable: Column<Fixed>,
+        is_mod_name_len: Column<Fixed>,
+        is_mod_name: Column<Fixed>,
+        is_import_name_len: Column<Fixed>,
+        is_import_name: Column<Fixed>,
+        is_importdesc_type: Column<Fixed>,
+        shared_state: &SharedState,
+        error_code: ErrorCodeColumns,
+    ) -> ImportWhitelistFields<F> {
+        let fields = ImportWhitelistFields {
+            table: ImportWhitelistTableConfig::configure(cs, whitelist),
+            mod_name_len: cs.advice_column(),
+            mod_name_rlc: cs.advice_column(),
+            import_name_len: cs.advice_column(),
+            import_name_rlc: cs.advice_column(),
+        };
+
+        cs.create_gate("WasmImportSectionBody import whitelist gate", |vc| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                vc,
+                q_enable,
+                shared_state,
+                error_code,
+            );
+            let is_mod_name_len_expr = vc.query_fixed(is_mod_name_len, Rotation::cur());
+            let is_mod_name_expr = vc.query_fixed(is_mod_name, Rotation::cur());
+            let is_import_name_len_expr = vc.query_fixed(is_import_name_len, Rotation::cur());
+            let is_import_name_expr = vc.query_fixed(is_import_name, Rotation::cur());
+            let is_importdesc_type_expr = vc.query_fixed(is_importdesc_type, Rotation::cur());
+
+            let byte_val_expr = vc.query_advice(wb_table.value, Rotation::cur());
+            let leb128_sn_expr = vc.query_advice(leb128_chip.config.sn, Rotation::cur());
+
+            let mod_name_len_expr = vc.query_advice(fields.mod_name_len, Rotation::cur());
+            let mod_name_len_prev_expr = vc.query_advice(fields.mod_name_len, Rotation::prev());
+            let mod_name_rlc_expr = vc.query_advice(fields.mod_name_rlc, Rotation::cur());
+            let mod_name_rlc_prev_expr = vc.query_advice(fields.mod_name_rlc, Rotation::prev());
+            let import_name_len_expr = vc.query_advice(fields.import_name_len, Rotation::cur());
+            let import_name_len_prev_expr =
+                vc.query_advice(fields.import_name_len, Rotation::prev());
+            let import_name_rlc_expr = vc.query_advice(fields.import_name_rlc, Rotation::cur());
+            let import_name_rlc_prev_expr =
+                vc.query_advice(fields.import_name_rlc, Rotation::prev());
+
+            cb.condition(is_mod_name_len_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_mod_name_len => mod_name_len=leb128.sn",
+                    mod_name_len_expr.clone(),
+                    leb128_sn_expr.clone(),
+                );
+                cb.require_zero("is_mod_name_len => mod_name_rlc=0", mod_name_rlc_expr.clone());
+            });
+            cb.condition(
+                or::expr([
+                    is_mod_name_expr.clone(),
+                    is_import_name_len_expr.clone(),
+                    is_import_name_expr.clone(),
+                    is_importdesc_type_expr.clone(),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "is_mod_name || ... || is_importdesc_type => mod_name_len=prev.mod_name_len",
+                        mod_name_len_expr.clone(),
+                        mod_name_len_prev_expr.clone(),
+                    );
+                },
+            );
+            cb.condition(is_mod_name_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_mod_name => mod_name_rlc=prev.mod_name_rlc*base+byte_val",
+                    mod_name_rlc_expr.clone(),
+                    mod_name_rlc_prev_expr.clone() * NAME_RLC_BASE.expr() + byte_val_expr.clone(),
+                );
+            });
+            cb.condition(
+                or::expr([
+                    is_import_name_len_expr.clone(),
+                    is_import_name_expr.clone(),
+                    is_importdesc_type_expr.clone(),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "is_import_name_len || ... || is_importdesc_type => mod_name_rlc=prev.mod_name_rlc",
+                        mod_name_rlc_expr.clone(),
+                        mod_name_rlc_prev_expr.clone(),
+                    );
+                },
+            );
+            cb.condition(is_import_name_len_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_import_name_len => import_name_len=leb128.sn",
+                    import_name_len_expr.clone(),
+                    leb128_sn_expr.clone(),
+                );
+                cb.require_zero(
+                    "is_import_name_len => import_name_rlc=0",
+                    import_name_rlc_expr.clone(),
+                );
+            });
+            cb.condition(
+                or::expr([is_import_name_expr.clone(), is_importdesc_type_expr.clone()]),
+                |cb| {
+                    cb.require_equal(
+                        "is_import_name || is_importdesc_type => import_name_len=prev.import_name_len",
+                        import_name_len_expr.clone(),
+                        import_name_len_prev_expr.clone(),
+                    );
+                },
+            );
+            cb.condition(is_import_name_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_import_name => import_name_rlc=prev.import_name_rlc*base+byte_val",
+                    import_name_rlc_expr.clone(),
+                    import_name_rlc_prev_expr.clone() * NAME_RLC_BASE.expr()
+                        + byte_val_expr.clone(),
+                );
+            });
+            cb.condition(is_importdesc_type_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_importdesc_type => import_name_rlc=prev.import_name_rlc",
+                    import_name_rlc_expr.clone(),
+                    import_name_rlc_prev_expr.clone(),
+                );
+            });
+
+            cb.gate(q_enable_expr)
+        });
+
+        // lengths are part of the lookup: names are packed without overflow only up to
+        // IMPORT_WHITELIST_NAME_MAX_LEN bytes and every whitelisted name fits
+        cs.lookup("WasmImportSectionBody function import is whitelisted", |vc| {
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                vc,
+                q_enable,
+                shared_state,
+                error_code,
+            );
+            let is_importdesc_type_expr = vc.query_fixed(is_importdesc_type, Rotation::cur());
+            let importdesc_type_is_typeidx_expr = importdesc_type_chip
+                .config
+                .value_equals(ImportDescType::Typeidx, Rotation::cur())(vc);
+            let selector_expr = and::expr([
+                q_enable_expr,
+                is_importdesc_type_expr,
+                importdesc_type_is_typeidx_expr,
+            ]);
+
+            vec![
+                (
+                    selector_expr.clone() * vc.query_advice(fields.mod_name_len, Rotation::cur()),
+                    fields.table.mod_name_len,
+                ),
+                (
+                    selector_expr.clone() * vc.query_advice(fields.mod_name_rlc, Rotation::cur()),
+                    fields.table.mod_name_rlc,
+                ),
+                (
+                    selector_expr.clone()
+                        * vc.query_advice(fields.import_name_len, Rotation::cur()),
+                    fields.table.import_name_len,
+                ),
+                (
+                    selector_expr * vc.query_advice(fields.import_name_rlc, Rotation::cur()),
+                    fields.table.import_name_rlc,
+                ),
+            ]
+        });
+
+        fields
+    }
+
+    /// Assigns the check of a name length (at its last leb byte) against `max_name_len`
+    fn assign_name_len(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        assign_delta: AssignDeltaType,
+        name_len: u64,
+        max_name_len: u64,
+    ) -> Result<(), Error> {
+        self.config
+            .name_len_lt_chip
+            .assign(
+                region,
+                offset + assign_delta,
+                F::from(name_len),
+                F::from(max_name_len + 1),
+            )
+            .map_err(remap_error(Error::FatalAssignExternalChip))?;
+        if name_len > max_name_len {
+            return Err(Error::ImportNameLenExceededAt(offset));
+        }
+        Ok(())
+    }
+
+    /// Assigns whitelist fields of the item rows `item_start_offset..=importdesc_type_offset`
+    fn assign_import_whitelist_fields(
+        &self,
+        region: &mut Region<F>,
+        fields: &ImportWhitelistFields<F>,
+        wb: &WasmBytecode,
+        item_start_offset: usize,
+        mod_name_offset: usize,
+        import_name_len_offset: usize,
+        import_name_offset: usize,
+        importdesc_type_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<(), Error> {
+        let mod_name = &wb.bytes[mod_name_offset..import_name_len_offset];
+        let import_name = &wb.bytes[import_name_offset..importdesc_type_offset];
+        for offset in item_start_offset..=importdesc_type_offset {
+            let assign_offset = offset + assign_delta;
+            // bytes of `start..end` name assigned up to and including the current row
+            let prefix_len = |start: usize, end: usize| (offset + 1).clamp(start, end) - start;
+            let mod_name_rlc: F =
+                name_rlc(&mod_name[..prefix_len(mod_name_offset, import_name_len_offset)]);
+            let (import_name_len, import_name_rlc): (u64, F) = if offset < import_name_len_offset
+            {
+                (0, F::zero())
+            } else {
+                (
+                    import_name.len() as u64,
+                    name_rlc(
+                        &import_name[..prefix_len(import_name_offset, importdesc_type_offset)],
+                    ),
+                )
+            };
+            for (name, column, value) in [
+                ("mod_name_len", fields.mod_name_len, F::from(mod_name.len() as u64)),
+                ("mod_name_rlc", fields.mod_name_rlc, mod_name_rlc),
+                ("import_name_len", fields.import_name_len, F::from(import_name_len)),
+                ("import_name_rlc", fields.import_name_rlc, import_name_rlc),
+            ] {
+                region
+                    .assign_advice(
+                        || format!("assign '{}' val {:?} at {}", name, value, assign_offset),
+                        column,
+                        assign_offset,
+                        || Value::known(value),
+                    )
+                    .map_err(remap_error_to_assign_at(assign_offset))?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn assign_auto(
         &self,
         region: &mut Region<F>,
@@ -1598,6 +1947,13 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 &[AssignType::IsModNameLen, AssignType::FuncCount],
             )?;
             let mod_name_len_last_byte_offset = offset + mod_name_leb_len - 1;
+            self.assign_name_len(
+                region,
+                mod_name_len_last_byte_offset,
+                assign_delta,
+                mod_name_len,
+                self.config.max_mod_name_len,
+            )?;
             let mod_name_last_byte_offset = mod_name_len_last_byte_offset + mod_name_len as usize;
             for offset in mod_name_len_last_byte_offset..=mod_name_last_byte_offset {
                 self.assign(
@@ -1611,6 +1967,7 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 )?;
             }
             offset += mod_name_leb_len;
+            let mod_name_offset = offset;
 
             // is_mod_name*
             self.markup_name_section(
@@ -1622,7 +1979,15 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 mod_name_len as usize,
                 1,
             )?;
+            self.config.utf8_chip.assign_auto(
+                region,
+                wb,
+                mod_name_len as usize,
+                offset,
+                offset + assign_delta,
+            )?;
             offset += mod_name_len as usize;
+            let import_name_len_offset = offset;
 
             // is_import_name_len+
             let (import_name_len, import_name_leb_len) = self.markup_leb_section(
@@ -1633,6 +1998,13 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 &[AssignType::IsImportNameLen, AssignType::FuncCount],
             )?;
             let import_name_len_last_byte_offset = offset + import_name_leb_len - 1;
+            self.assign_name_len(
+                region,
+                import_name_len_last_byte_offset,
+                assign_delta,
+                import_name_len,
+                self.config.max_import_name_len,
+            )?;
             let import_name_last_byte_offset =
                 import_name_len_last_byte_offset + import_name_len as usize;
             for offset in import_name_len_last_byte_offset..=import_name_last_byte_offset {
@@ -1647,6 +2019,7 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 )?;
             }
             offset += import_name_leb_len;
+            let import_name_offset = offset;
 
             // is_import_name*
             self.markup_name_section(
@@ -1658,6 +2031,13 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 import_name_len as usize,
                 1,
             )?;
+            self.config.utf8_chip.assign_auto(
+                region,
+                wb,
+                import_name_len as usize,
+                offset,
+                offset + assign_delta,
+            )?;
             offset += import_name_len as usize;
 
             // is_importdesc_type{1}
@@ -1667,7 +2047,28 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 .map_err(remap_error_to_invalid_enum_value_at(offset))?;
             let importdesc_type_val = importdesc_type_val as u64;
             if importdesc_type == ImportDescType::Typeidx {
-                self.config.shared_state.borrow_mut().func_count += 1;
+                self.config.shared_state.write().func_count += 1;
+            }
+            if let Some(fields) = &self.config.import_whitelist_fields {
+                if importdesc_type == ImportDescType::Typeidx
+                    && !fields.table.whitelist.contains(
+                        &wb.bytes[mod_name_offset..import_name_len_offset],
+                        &wb.bytes[import_name_offset..offset],
+                    )
+                {
+                    return Err(Error::ImportNotWhitelistedAt(offset));
+                }
+                self.assign_import_whitelist_fields(
+                    region,
+                    fields,
+                    wb,
+                    item_start_offset,
+                    mod_name_offset,
+                    import_name_len_offset,
+                    import_name_offset,
+                    offset,
+                    assign_delta,
+                )?;
             }
             self.assign(
                 region,
@@ -1844,7 +2245,7 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                     offset += limit_min_leb_len;
 
                     // limit_max*
-                    if limit_type == LimitType::MinMax {
+                    if limit_type.has_max() {
                         let (_limit_max, limit_max_leb_len) = self.markup_leb_section(
                             region,
                             wb,
@@ -1944,7 +2345,7 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                     offset += limit_min_leb_len;
 
                     // limit_max*
-                    if limit_type == LimitType::MinMax {
+                    if limit_type.has_max() {
                         let (limit_max, limit_max_leb_len) = self.markup_leb_section(
                             region,
                             wb,
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
@@ -9,11 +9,15 @@ use eth_types::{Field, Hash, ToWord};
 
 use crate::wasm_circuit::{
     bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
-    common::WasmSharedStateAwareChip,
+    common::{ErrorCodeColumns, WasmSharedStateAwareChip},
+    consts::{MAX_IMPORT_MOD_NAME_LEN, MAX_IMPORT_NAME_LEN},
     leb128::circuit::LEB128Chip,
     sections::import::body::circuit::WasmImportSectionBodyChip,
-    tables::{dynamic_indexes::circuit::DynamicIndexesChip, fixed_range::config::RangeTableConfig},
-    types::SharedState,
+    tables::{
+        byte_set::config::ByteSetTableConfig, dynamic_indexes::circuit::DynamicIndexesChip,
+        utf8_transition::config::Utf8TransitionTableConfig,
+    },
+    types::SharedStateRef,
     utf8::circuit::UTF8Chip,
 };
 
@@ -27,9 +31,10 @@ struct TestCircuit<'a, F> {
 
 #[derive(Clone)]
 struct TestCircuitConfig<F: Field> {
-    body_chip: Rc<WasmImportSectionBodyChip<F>>,
-    wb_table: Rc<WasmBytecodeTable>,
-    range_table_config_0_128: Rc<RangeTableConfig<F, 0, 128>>,
+    body_chip: Arc<WasmImportSectionBodyChip<F>>,
+    wb_table: Arc<WasmBytecodeTable>,
+    byte_set_table_config: ByteSetTableConfig<F>,
+    utf8_transition_table_config: Arc<Utf8TransitionTableConfig<F>>,
     _marker: PhantomData<F>,
 }
 
@@ -42,30 +47,32 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
     }
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, false));
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, false));
         let func_count = cs.advice_column();
-        let error_code = cs.advice_column();
+        let error_code = ErrorCodeColumns::configure(cs);
         let body_byte_rev_index = cs.advice_column();
         let body_item_rev_count = cs.advice_column();
 
-        let shared_state = Rc::new(RefCell::new(SharedState::default()));
+        let shared_state = SharedStateRef::default();
 
-        let range_table_config_0_128 = Rc::new(RangeTableConfig::configure(cs));
+        let utf8_transition_table_config = Arc::new(Utf8TransitionTableConfig::configure(cs));
 
         let config = DynamicIndexesChip::configure(cs, shared_state.clone());
-        let dynamic_indexes_chip = Rc::new(DynamicIndexesChip::construct(config));
+        let dynamic_indexes_chip = Arc::new(DynamicIndexesChip::construct(config));
 
         let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
-        let leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
+        let leb128_chip = Arc::new(LEB128Chip::construct(leb128_config));
+        let byte_set_table_config = ByteSetTableConfig::configure(cs);
 
         let utf8_config =
-            UTF8Chip::<F>::configure(cs, range_table_config_0_128.clone(), &wb_table.value);
-        let utf8_chip = Rc::new(UTF8Chip::construct(utf8_config));
+            UTF8Chip::<F>::configure(cs, utf8_transition_table_config.clone(), &wb_table.value);
+        let utf8_chip = Arc::new(UTF8Chip::construct(utf8_config));
 
         let wasm_import_section_body_config = WasmImportSectionBodyChip::configure(
             cs,
             wb_table.clone(),
             leb128_chip.clone(),
+            &byte_set_table_config,
             utf8_chip.clone(),
             dynamic_indexes_chip.clone(),
             func_count,
@@ -73,13 +80,17 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
             body_byte_rev_index,
             body_item_rev_count,
             error_code,
+            None,
+            MAX_IMPORT_MOD_NAME_LEN,
+            MAX_IMPORT_NAME_LEN,
         );
         let wasm_import_section_body_chip =
             WasmImportSectionBodyChip::construct(wasm_import_section_body_config);
         let test_circuit_config = TestCircuitConfig {
-            body_chip: Rc::new(wasm_import_section_body_chip),
+            body_chip: Arc::new(wasm_import_section_body_chip),
             wb_table: wb_table.clone(),
-            range_table_config_0_128: range_table_config_0_128.clone(),
+            byte_set_table_config,
+            utf8_transition_table_config: utf8_transition_table_config.clone(),
             _marker: Default::default(),
         };
 
@@ -102,11 +113,12 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
                 },
             )
             .unwrap();
-        config.range_table_config_0_128.load(&mut layouter)?;
+        config.utf8_transition_table_config.load(&mut layouter)?;
+        config.byte_set_table_config.load(&mut layouter)?;
         layouter.assign_region(
             || "wasm_import_section_body region",
             |mut region| {
-                config.body_chip.shared_state().borrow_mut().reset();
+                config.body_chip.shared_state().write().reset();
                 let mut start = self.offset_start;
                 while start < wb.bytes.len() {
                     start = config
@@ -137,7 +149,7 @@ mod wasm_import_section_body_tests {
     };
 
     fn test<'a, F: Field>(test_circuit: TestCircuit<'_, F>, is_ok: bool) {
-        let k = 8;
+        let k = 9;
         let prover = MockProver::run(k, &test_circuit, vec![]).unwrap();
         if is_ok {
             prover.assert_satisfied();
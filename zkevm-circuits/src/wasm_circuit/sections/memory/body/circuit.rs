@@ -1,16 +1,15 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Region, Value},
     plonk::{Advice, Column, ConstraintSystem, Fixed},
     poly::Rotation,
 };
-use itertools::Itertools;
 use log::debug;
 
 use eth_types::Field;
 use gadgets::{
-    less_than::LtInstruction,
+    less_than::{LtChip, LtInstruction},
     util::{and, not, or, Expr},
 };
 
@@ -19,45 +18,49 @@ use crate::{
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
-            configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            LimitTypeFields, WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip,
-            WasmErrorAwareChip, WasmFuncCountAwareChip, WasmLimitTypeAwareChip,
-            WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
+            configure_transition_check, limit_type_has_max_expr, ErrorCodeColumns, LimitTypeFields,
+            SectionBodyChip, SectionBodyChipResources, SectionSelectors, WasmAssignAwareChip,
+            WasmCountPrefixedItemsAwareChip, WasmErrorAwareChip, WasmFuncCountAwareChip,
+            WasmLimitTypeAwareChip, WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
         },
         error::{
             remap_error, remap_error_to_assign_at, remap_error_to_invalid_enum_value_at, Error,
         },
         leb128::circuit::LEB128Chip,
         sections::{consts::LebParams, memory::body::types::AssignType},
-        tables::dynamic_indexes::{
-            circuit::DynamicIndexesChip,
-            types::{LookupArgsParams, Tag},
+        tables::{
+            byte_set::config::ByteSetTableConfig,
+            dynamic_indexes::{
+                circuit::DynamicIndexesChip,
+                types::{LookupArgsParams, Tag},
+            },
         },
         types::{
-            AssignDeltaType, AssignValueType, LimitType, NewWbOffsetType, SharedState,
-            LIMIT_TYPE_VALUES,
+            AssignDeltaType, AssignValueType, LimitType, NewWbOffsetType, SharedStateRef,
+            WasmSection,
         },
     },
 };
 
 #[derive(Debug, Clone)]
 pub struct WasmMemorySectionBodyConfig<F: Field> {
-    pub q_enable: Column<Fixed>,
-    pub q_first: Column<Fixed>,
-    pub q_last: Column<Fixed>,
+    pub selectors: SectionSelectors,
     pub is_items_count: Column<Fixed>,
 
     pub limit_type_fields: LimitTypeFields<F>,
 
-    pub leb128_chip: Rc<LEB128Chip<F>>,
-    pub dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+    pub leb128_chip: Arc<LEB128Chip<F>>,
+    pub dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
+    /// limits must not exceed `max_memory_pages`
+    pub max_memory_pages: u64,
+    pub limit_pages_lt_chip: Arc<LtChip<F, 4>>,
 
     func_count: Column<Advice>,
     body_item_rev_count: Column<Advice>,
 
-    error_code: Column<Advice>,
+    error_code: ErrorCodeColumns,
 
-    shared_state: Rc<RefCell<SharedState>>,
+    shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -77,17 +80,51 @@ impl<F: Field> WasmCountPrefixedItemsAwareChip<F> for WasmMemorySectionBodyChip<
 impl<F: Field> WasmLimitTypeAwareChip<F> for WasmMemorySectionBodyChip<F> {}
 
 impl<F: Field> WasmErrorAwareChip<F> for WasmMemorySectionBodyChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmMemorySectionBodyChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
 
+impl<F: Field> SectionBodyChip<F> for WasmMemorySectionBodyChip<F> {
+    const SECTION: WasmSection = WasmSection::Memory;
+
+    fn configure_with_resources(
+        cs: &mut ConstraintSystem<F>,
+        resources: &SectionBodyChipResources<F>,
+    ) -> Self {
+        let config = Self::configure(
+            cs,
+            resources.wb_table.clone(),
+            resources.leb128_chip.clone(),
+            &resources.byte_set_table_config,
+            resources.dynamic_indexes_chip.clone(),
+            resources.func_count,
+            resources.shared_state.clone(),
+            resources.body_item_rev_count_l1,
+            resources.error_code,
+            resources.bytecode_number,
+            resources.max_memory_pages,
+        );
+        Self::construct(config)
+    }
+
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error> {
+        self.assign_auto(region, wb, wb_offset, assign_delta)
+    }
+}
+
 impl<F: Field> WasmFuncCountAwareChip<F> for WasmMemorySectionBodyChip<F> {
     fn func_count_col(&self) -> Column<Advice> {
         self.config.func_count
@@ -113,14 +150,9 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmMemorySectionBodyChip<F> {
             "assign at {} q_enable {} assign_types {:?} assign_value {} byte_val {:x?}",
             assign_offset, q_enable, assign_types, assign_value, wb.bytes[wb_offset],
         );
-        region
-            .assign_fixed(
-                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
-                self.config.q_enable,
-                assign_offset,
-                || Value::known(F::from(q_enable as u64)),
-            )
-            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.config
+            .selectors
+            .assign_q_enable(region, assign_offset, q_enable)?;
         self.assign_func_count(region, assign_offset)?;
 
         for assign_type in assign_types {
@@ -138,29 +170,14 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmMemorySectionBodyChip<F> {
             }
             match assign_type {
                 AssignType::QFirst => {
-                    region
-                        .assign_fixed(
-                            || {
-                                format!(
-                                    "assign 'q_first' val {} at {}",
-                                    assign_value, assign_offset
-                                )
-                            },
-                            self.config.q_first,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_first(region, assign_offset, assign_value)?;
                 }
                 AssignType::QLast => {
-                    region
-                        .assign_fixed(
-                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
-                            self.config.q_last,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_last(region, assign_offset, assign_value)?;
                 }
                 AssignType::IsItemsCount => {
                     region
@@ -295,18 +312,19 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        wb_table: Rc<WasmBytecodeTable>,
-        leb128_chip: Rc<LEB128Chip<F>>,
-        dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+        wb_table: Arc<WasmBytecodeTable>,
+        leb128_chip: Arc<LEB128Chip<F>>,
+        byte_set_table_config: &ByteSetTableConfig<F>,
+        dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
         func_count: Column<Advice>,
-        shared_state: Rc<RefCell<SharedState>>,
+        shared_state: SharedStateRef,
         body_item_rev_count: Column<Advice>,
-        error_code: Column<Advice>,
+        error_code: ErrorCodeColumns,
         bytecode_number: Column<Advice>,
+        max_memory_pages: u64,
     ) -> WasmMemorySectionBodyConfig<F> {
-        let q_enable = cs.fixed_column();
-        let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        let selectors = SectionSelectors::configure(cs);
+        let SectionSelectors { q_enable, q_last, .. } = selectors;
 
         let is_items_count = cs.fixed_column();
 
@@ -319,7 +337,7 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
                     * Self::get_selector_expr_enriched_with_error_processing(
                         vc,
                         q_enable,
-                        &shared_state.borrow(),
+                        &shared_state.read(),
                         error_code,
                     );
                 LookupArgsParams {
@@ -340,6 +358,7 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
             q_enable,
             leb128_chip.as_ref(),
             &limit_type_fields,
+            byte_set_table_config,
         );
 
         let LimitTypeFields {
@@ -350,6 +369,23 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
             ..
         } = limit_type_fields.clone();
 
+        let config = LtChip::configure(
+            cs,
+            |vc| {
+                and::expr([
+                    vc.query_fixed(q_enable, Rotation::cur()),
+                    or::expr([
+                        vc.query_fixed(is_limit_min, Rotation::cur()),
+                        vc.query_fixed(is_limit_max, Rotation::cur()),
+                    ]),
+                    vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur()),
+                ])
+            },
+            |vc| vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
+            |_| (max_memory_pages + 1).expr(),
+        );
+        let limit_pages_lt_chip = Arc::new(LtChip::construct(config));
+
         Self::configure_count_prefixed_items_checks(
             cs,
             leb128_chip.as_ref(),
@@ -359,7 +395,7 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
@@ -376,7 +412,7 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
             let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                 vc,
                 q_enable,
-                &shared_state.borrow(),
+                &shared_state.read(),
                 error_code,
             );
             // let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
@@ -389,7 +425,6 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
 
             // let is_limit_type_ctx_expr = vc.query_fixed(is_limit_type_ctx, Rotation::cur());
 
-            let byte_val_expr = vc.query_advice(wb_table.value, Rotation::cur());
             // let limit_type_prev_expr = vc.query_advice(limit_type, Rotation::prev());
             // let limit_type_expr = vc.query_advice(limit_type, Rotation::cur());
 
@@ -397,24 +432,17 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
                 limit_type_chip
                     .config
                     .value_equals(LimitType::MinOnly, Rotation::cur())(vc);
-            let limit_type_is_min_max_expr =
-                limit_type_chip
-                    .config
-                    .value_equals(LimitType::MinMax, Rotation::cur())(vc);
+            let limit_type_has_max_expr = limit_type_has_max_expr(&limit_type_chip, vc);
 
             let leb128_is_last_byte_expr =
                 vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
 
-            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_items_count is boolean", is_items_count_expr.clone());
 
-            configure_constraints_for_q_first_and_q_last(
+            selectors.configure_constraints(
                 &mut cb,
                 vc,
-                &q_enable,
-                &q_first,
                 &[is_items_count],
-                &q_last,
                 &[is_limit_min, is_limit_max],
             );
 
@@ -512,7 +540,7 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
                 and::expr([
                     not_q_last_expr.clone(),
                     is_limit_min_expr.clone(),
-                    limit_type_is_min_max_expr.clone(),
+                    limit_type_has_max_expr.clone(),
                 ]),
                 true,
                 &[is_limit_min, is_limit_max],
@@ -525,7 +553,7 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
                     not_q_last_expr.clone(),
                     leb128_is_last_byte_expr.clone(),
                     is_limit_min_expr.clone(),
-                    limit_type_is_min_max_expr.clone(),
+                    limit_type_has_max_expr.clone(),
                 ]),
                 true,
                 &[is_limit_max],
@@ -537,7 +565,7 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
                 and::expr([
                     not_q_last_expr.clone(),
                     is_limit_max_expr.clone(),
-                    limit_type_is_min_max_expr.clone(),
+                    limit_type_has_max_expr.clone(),
                 ]),
                 true,
                 &[is_limit_max],
@@ -546,24 +574,30 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
                 and::expr([
                     leb128_is_last_byte_expr.clone(),
                     is_limit_max_expr.clone(),
-                    limit_type_is_min_max_expr.clone(),
+                    limit_type_has_max_expr.clone(),
                 ]),
                 |cb| {
                     cb.require_equal(
-                        "limit_type_is_min_max && is_limit_max && leb128_is_last_byte => q_last",
+                        "limit_type_has_max && is_limit_max && leb128_is_last_byte => q_last",
                         q_last_expr.clone(),
                         1.expr(),
                     );
                 },
             );
 
-            cb.condition(is_limit_type_expr.clone(), |cb| {
-                cb.require_in_set(
-                    "is_limit_type -> byte_val is valid",
-                    byte_val_expr.clone(),
-                    LIMIT_TYPE_VALUES.iter().map(|&v| v.expr()).collect_vec(),
-                );
-            });
+            cb.condition(
+                and::expr([
+                    or::expr([is_limit_min_expr.clone(), is_limit_max_expr.clone()]),
+                    leb128_is_last_byte_expr.clone(),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "is_limit_min || is_limit_max => limit <= max_memory_pages",
+                        limit_pages_lt_chip.config().is_lt(vc, None),
+                        1.expr(),
+                    );
+                },
+            );
 
             cb.gate(q_enable_expr.clone())
         });
@@ -571,13 +605,13 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
         let config = WasmMemorySectionBodyConfig::<F> {
             _marker: PhantomData,
 
-            q_enable,
-            q_first,
-            q_last,
+            selectors,
             is_items_count,
             limit_type_fields,
             leb128_chip,
             dynamic_indexes_chip,
+            max_memory_pages,
+            limit_pages_lt_chip,
             func_count,
             body_item_rev_count,
             error_code,
@@ -587,6 +621,29 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
         config
     }
 
+    /// Assigns the check of a limit (at its last leb byte) against `max_memory_pages`
+    fn assign_limit_pages(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        assign_delta: AssignDeltaType,
+        limit: u64,
+    ) -> Result<(), Error> {
+        self.config
+            .limit_pages_lt_chip
+            .assign(
+                region,
+                offset + assign_delta,
+                F::from(limit),
+                F::from(self.config.max_memory_pages + 1),
+            )
+            .map_err(remap_error(Error::FatalAssignExternalChip))?;
+        if limit > self.config.max_memory_pages {
+            return Err(Error::MemoryLimitExceededAt(offset));
+        }
+        Ok(())
+    }
+
     pub fn assign_auto(
         &self,
         region: &mut Region<F>,
@@ -617,12 +674,12 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
         }
         let dynamic_indexes_offset = self.config.dynamic_indexes_chip.assign_auto(
             region,
-            self.config.shared_state.borrow().dynamic_indexes_offset,
+            self.config.shared_state.read().dynamic_indexes_offset,
             assign_delta,
             items_count as usize,
             Tag::MemIndex,
         )?;
-        self.config.shared_state.borrow_mut().dynamic_indexes_offset = dynamic_indexes_offset;
+        self.config.shared_state.write().dynamic_indexes_offset = dynamic_indexes_offset;
         self.assign(
             region,
             &wb,
@@ -683,10 +740,11 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
                     None,
                 )?;
             }
+            self.assign_limit_pages(region, offset + limit_min_leb_len - 1, assign_delta, limit_min)?;
             offset += limit_min_leb_len;
 
             // limit_max*
-            if limit_type == LimitType::MinMax {
+            if limit_type.has_max() {
                 let (limit_max, limit_max_leb_len) = self.markup_leb_section(
                     region,
                     wb,
@@ -705,6 +763,12 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
                         None,
                     )?;
                 }
+                self.assign_limit_pages(
+                    region,
+                    offset + limit_max_leb_len - 1,
+                    assign_delta,
+                    limit_max,
+                )?;
                 self.config
                     .limit_type_fields
                     .limit_type_params_lt_chip
@@ -1,18 +1,21 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
     plonk::{Circuit, ConstraintSystem, Error},
 };
 
-use eth_types::{Field, Hash, ToWord};
+use eth_types::{evm_types::MAX_WASM_MEMORY_PAGES, Field, Hash, ToWord};
 
 use crate::wasm_circuit::{
     bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+    common::ErrorCodeColumns,
     leb128::circuit::LEB128Chip,
     sections::memory::body::circuit::WasmMemorySectionBodyChip,
-    tables::dynamic_indexes::circuit::DynamicIndexesChip,
-    types::SharedState,
+    tables::{
+        byte_set::config::ByteSetTableConfig, dynamic_indexes::circuit::DynamicIndexesChip,
+    },
+    types::SharedStateRef,
 };
 
 #[derive(Default)]
@@ -25,8 +28,9 @@ struct TestCircuit<'a, F> {
 
 #[derive(Clone)]
 struct TestCircuitConfig<F: Field> {
-    body_chip: Rc<WasmMemorySectionBodyChip<F>>,
-    wb_table: Rc<WasmBytecodeTable>,
+    body_chip: Arc<WasmMemorySectionBodyChip<F>>,
+    wb_table: Arc<WasmBytecodeTable>,
+    byte_set_table_config: ByteSetTableConfig<F>,
     _marker: PhantomData<F>,
 }
 
@@ -39,36 +43,40 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
     }
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, false));
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, false));
         let func_count = cs.advice_column();
-        let error_code = cs.advice_column();
+        let error_code = ErrorCodeColumns::configure(cs);
         let bytecode_number = cs.advice_column();
         let body_item_rev_count = cs.advice_column();
 
-        let shared_state = Rc::new(RefCell::new(SharedState::default()));
+        let shared_state = SharedStateRef::default();
 
         let config = DynamicIndexesChip::configure(cs, shared_state.clone());
-        let dynamic_indexes_chip = Rc::new(DynamicIndexesChip::construct(config));
+        let dynamic_indexes_chip = Arc::new(DynamicIndexesChip::construct(config));
 
         let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
-        let leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
+        let leb128_chip = Arc::new(LEB128Chip::construct(leb128_config));
+        let byte_set_table_config = ByteSetTableConfig::configure(cs);
 
         let wasm_memory_section_body_config = WasmMemorySectionBodyChip::configure(
             cs,
             wb_table.clone(),
             leb128_chip.clone(),
+            &byte_set_table_config,
             dynamic_indexes_chip.clone(),
             func_count,
             shared_state.clone(),
             body_item_rev_count,
             error_code,
             bytecode_number,
+            MAX_WASM_MEMORY_PAGES,
         );
         let wasm_memory_section_body_chip =
             WasmMemorySectionBodyChip::construct(wasm_memory_section_body_config);
         let test_circuit_config = TestCircuitConfig {
-            body_chip: Rc::new(wasm_memory_section_body_chip),
+            body_chip: Arc::new(wasm_memory_section_body_chip),
             wb_table: wb_table.clone(),
+            byte_set_table_config,
             _marker: Default::default(),
         };
 
@@ -91,6 +99,7 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
                 },
             )
             .unwrap();
+        config.byte_set_table_config.load(&mut layouter)?;
         layouter.assign_region(
             || "wasm_memory_section_body region",
             |mut region| {
@@ -121,6 +130,7 @@ mod wasm_memory_section_body_tests {
 
     use crate::wasm_circuit::{
         common::wat_extract_section_body_bytecode, sections::memory::body::tests::TestCircuit,
+        types::LimitType,
     };
 
     fn test<'a, F: Field>(test_circuit: TestCircuit<'_, F>, is_ok: bool) {
@@ -201,4 +211,25 @@ mod wasm_memory_section_body_tests {
         };
         test(test_circuit, true);
     }
+
+    #[test]
+    pub fn shared_limit_requires_max() {
+        assert!(LimitType::try_from(0x2).is_err());
+        assert_eq!(LimitType::try_from(0x3).is_ok(), cfg!(feature = "threads"));
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    pub fn shared_memory_ok() {
+        // 1 memory, shared, min 1, max 2
+        let bytecode = vec![0x1, 0x3, 0x1, 0x2];
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            _marker: Default::default(),
+        };
+        test(test_circuit, true);
+    }
 }
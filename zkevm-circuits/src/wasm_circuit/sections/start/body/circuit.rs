@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Region, Value},
@@ -15,32 +15,30 @@ use crate::{
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
-            configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            WasmAssignAwareChip, WasmErrorAwareChip, WasmFuncCountAwareChip,
+            configure_transition_check, ErrorCodeColumns, SectionBodyChip, SectionBodyChipResources,
+            SectionSelectors, WasmAssignAwareChip, WasmErrorAwareChip, WasmFuncCountAwareChip,
             WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
         },
         error::{remap_error_to_assign_at, Error},
         leb128::circuit::LEB128Chip,
         sections::{consts::LebParams, start::body::types::AssignType},
-        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedState},
+        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedStateRef, WasmSection},
     },
 };
 
 #[derive(Debug, Clone)]
 pub struct WasmStartSectionBodyConfig<F: Field> {
-    pub q_enable: Column<Fixed>,
-    pub q_first: Column<Fixed>,
-    pub q_last: Column<Fixed>,
+    pub selectors: SectionSelectors,
     pub is_func_index: Column<Fixed>,
 
-    pub wb_table: Rc<WasmBytecodeTable>,
-    pub leb128_chip: Rc<LEB128Chip<F>>,
+    pub wb_table: Arc<WasmBytecodeTable>,
+    pub leb128_chip: Arc<LEB128Chip<F>>,
 
     pub func_count: Column<Advice>,
 
-    pub error_code: Column<Advice>,
+    pub error_code: ErrorCodeColumns,
 
-    shared_state: Rc<RefCell<SharedState>>,
+    shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -56,17 +54,46 @@ pub struct WasmStartSectionBodyChip<F: Field> {
 impl<F: Field> WasmMarkupLeb128SectionAwareChip<F> for WasmStartSectionBodyChip<F> {}
 
 impl<F: Field> WasmErrorAwareChip<F> for WasmStartSectionBodyChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmStartSectionBodyChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
 
+impl<F: Field> SectionBodyChip<F> for WasmStartSectionBodyChip<F> {
+    const SECTION: WasmSection = WasmSection::Start;
+
+    fn configure_with_resources(
+        cs: &mut ConstraintSystem<F>,
+        resources: &SectionBodyChipResources<F>,
+    ) -> Self {
+        let config = Self::configure(
+            cs,
+            resources.wb_table.clone(),
+            resources.leb128_chip.clone(),
+            resources.func_count,
+            resources.shared_state.clone(),
+            resources.error_code,
+        );
+        Self::construct(config)
+    }
+
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error> {
+        self.assign_auto(region, wb, wb_offset, assign_delta)
+    }
+}
+
 impl<F: Field> WasmFuncCountAwareChip<F> for WasmStartSectionBodyChip<F> {
     fn func_count_col(&self) -> Column<Advice> {
         self.config.func_count
@@ -92,14 +119,9 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmStartSectionBodyChip<F> {
             "assign at {} q_enable {} assign_types {:?} assign_value {} byte_val {:x?}",
             assign_offset, q_enable, assign_types, assign_value, wb.bytes[wb_offset],
         );
-        region
-            .assign_fixed(
-                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
-                self.config.q_enable,
-                assign_offset,
-                || Value::known(F::from(q_enable as u64)),
-            )
-            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.config
+            .selectors
+            .assign_q_enable(region, assign_offset, q_enable)?;
         self.assign_func_count(region, assign_offset)?;
 
         for assign_type in assign_types {
@@ -111,29 +133,14 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmStartSectionBodyChip<F> {
             }
             match assign_type {
                 AssignType::QFirst => {
-                    region
-                        .assign_fixed(
-                            || {
-                                format!(
-                                    "assign 'q_first' val {} at {}",
-                                    assign_value, assign_offset
-                                )
-                            },
-                            self.config.q_first,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_first(region, assign_offset, assign_value)?;
                 }
                 AssignType::QLast => {
-                    region
-                        .assign_fixed(
-                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
-                            self.config.q_last,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_last(region, assign_offset, assign_value)?;
                 }
                 AssignType::IsFuncsIndex => {
                     region
@@ -170,15 +177,14 @@ impl<F: Field> WasmStartSectionBodyChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        wb_table: Rc<WasmBytecodeTable>,
-        leb128_chip: Rc<LEB128Chip<F>>,
+        wb_table: Arc<WasmBytecodeTable>,
+        leb128_chip: Arc<LEB128Chip<F>>,
         func_count: Column<Advice>,
-        shared_state: Rc<RefCell<SharedState>>,
-        error_code: Column<Advice>,
+        shared_state: SharedStateRef,
+        error_code: ErrorCodeColumns,
     ) -> WasmStartSectionBodyConfig<F> {
-        let q_enable = cs.fixed_column();
-        let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        let selectors = SectionSelectors::configure(cs);
+        let SectionSelectors { q_enable, q_last, .. } = selectors;
         let is_func_index = cs.fixed_column();
 
         cs.create_gate("WasmStartSectionBody gate", |vc| {
@@ -187,7 +193,7 @@ impl<F: Field> WasmStartSectionBodyChip<F> {
             let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                 vc,
                 q_enable,
-                &shared_state.borrow(),
+                &shared_state.read(),
                 error_code,
             );
             // let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
@@ -202,18 +208,9 @@ impl<F: Field> WasmStartSectionBodyChip<F> {
             let leb128_is_first_byte_expr =
                 vc.query_fixed(leb128_chip.config.is_first_byte, Rotation::cur());
 
-            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_func_index is boolean", is_func_index_expr.clone());
 
-            configure_constraints_for_q_first_and_q_last(
-                &mut cb,
-                vc,
-                &q_enable,
-                &q_first,
-                &[is_func_index],
-                &q_last,
-                &[is_func_index],
-            );
+            selectors.configure_constraints(&mut cb, vc, &[is_func_index], &[is_func_index]);
 
             cb.require_equal(
                 "exactly one mark flag active at the same time",
@@ -260,9 +257,7 @@ impl<F: Field> WasmStartSectionBodyChip<F> {
         let config = WasmStartSectionBodyConfig::<F> {
             _marker: PhantomData,
 
-            q_enable,
-            q_first,
-            q_last,
+            selectors,
             is_func_index,
             wb_table,
             leb128_chip,
@@ -1,17 +1,16 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Region, Value},
     plonk::{Advice, Column, ConstraintSystem, Fixed},
     poly::Rotation,
 };
-use itertools::Itertools;
 use log::debug;
 
 use eth_types::Field;
 use gadgets::{
     less_than::LtInstruction,
-    util::{and, not, or, Expr},
+    util::{and, or, Expr},
 };
 
 use crate::{
@@ -19,39 +18,42 @@ use crate::{
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
-            configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            LimitTypeFields, WasmAssignAwareChip, WasmErrorAwareChip, WasmFuncCountAwareChip,
-            WasmLimitTypeAwareChip, WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
+            limit_type_has_max_expr, ErrorCodeColumns, LimitTypeFields, SectionBodyChip,
+            SectionBodyChipResources, SectionSelectors, SectionTransition, WasmAssignAwareChip,
+            WasmErrorAwareChip, WasmFuncCountAwareChip, WasmLimitTypeAwareChip,
+            WasmMarkupLeb128SectionAwareChip, WasmSectionTransitionsAwareChip,
+            WasmSharedStateAwareChip,
         },
         error::{
             remap_error, remap_error_to_assign_at, remap_error_to_invalid_enum_value_at, Error,
         },
         leb128::circuit::LEB128Chip,
         sections::{consts::LebParams, table::body::types::AssignType},
-        tables::dynamic_indexes::{circuit::DynamicIndexesChip, types::Tag},
+        tables::{
+            byte_set::{config::ByteSetTableConfig, types::ByteSetTag},
+            dynamic_indexes::{circuit::DynamicIndexesChip, types::Tag},
+        },
         types::{
-            AssignDeltaType, AssignValueType, LimitType, NewWbOffsetType, SharedState,
-            REF_TYPE_VALUES,
+            AssignDeltaType, AssignValueType, LimitType, NewWbOffsetType, SharedStateRef,
+            WasmSection,
         },
     },
 };
 
 #[derive(Debug, Clone)]
 pub struct WasmTableSectionBodyConfig<F: Field> {
-    pub q_enable: Column<Fixed>,
-    pub q_first: Column<Fixed>,
-    pub q_last: Column<Fixed>,
+    pub selectors: SectionSelectors,
     pub is_reference_type_count: Column<Fixed>,
     pub is_reference_type: Column<Fixed>,
 
     pub limit_type_fields: LimitTypeFields<F>,
 
-    pub leb128_chip: Rc<LEB128Chip<F>>,
-    pub dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+    pub leb128_chip: Arc<LEB128Chip<F>>,
+    pub dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
 
     pub func_count: Column<Advice>,
-    pub error_code: Column<Advice>,
-    shared_state: Rc<RefCell<SharedState>>,
+    pub error_code: ErrorCodeColumns,
+    shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -68,18 +70,51 @@ impl<F: Field> WasmMarkupLeb128SectionAwareChip<F> for WasmTableSectionBodyChip<
 
 impl<F: Field> WasmLimitTypeAwareChip<F> for WasmTableSectionBodyChip<F> {}
 
+impl<F: Field> WasmSectionTransitionsAwareChip<F> for WasmTableSectionBodyChip<F> {}
+
 impl<F: Field> WasmErrorAwareChip<F> for WasmTableSectionBodyChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmTableSectionBodyChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
 
+impl<F: Field> SectionBodyChip<F> for WasmTableSectionBodyChip<F> {
+    const SECTION: WasmSection = WasmSection::Table;
+
+    fn configure_with_resources(
+        cs: &mut ConstraintSystem<F>,
+        resources: &SectionBodyChipResources<F>,
+    ) -> Self {
+        let config = Self::configure(
+            cs,
+            resources.wb_table.clone(),
+            resources.leb128_chip.clone(),
+            &resources.byte_set_table_config,
+            resources.dynamic_indexes_chip.clone(),
+            resources.func_count,
+            resources.error_code,
+            resources.shared_state.clone(),
+        );
+        Self::construct(config)
+    }
+
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error> {
+        self.assign_auto(region, wb, wb_offset, assign_delta)
+    }
+}
+
 impl<F: Field> WasmFuncCountAwareChip<F> for WasmTableSectionBodyChip<F> {
     fn func_count_col(&self) -> Column<Advice> {
         self.config.func_count
@@ -105,14 +140,9 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmTableSectionBodyChip<F> {
             "assign at {} q_enable {} assign_types {:?} assign_value {} byte_val {:x?}",
             assign_offset, q_enable, assign_types, assign_value, wb.bytes[wb_offset],
         );
-        region
-            .assign_fixed(
-                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
-                self.config.q_enable,
-                assign_offset,
-                || Value::known(F::from(q_enable as u64)),
-            )
-            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.config
+            .selectors
+            .assign_q_enable(region, assign_offset, q_enable)?;
         self.assign_func_count(region, assign_offset)?;
 
         for assign_type in assign_types {
@@ -130,29 +160,14 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmTableSectionBodyChip<F> {
             }
             match assign_type {
                 AssignType::QFirst => {
-                    region
-                        .assign_fixed(
-                            || {
-                                format!(
-                                    "assign 'q_first' val {} at {}",
-                                    assign_value, assign_offset
-                                )
-                            },
-                            self.config.q_first,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_first(region, assign_offset, assign_value)?;
                 }
                 AssignType::QLast => {
-                    region
-                        .assign_fixed(
-                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
-                            self.config.q_last,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_last(region, assign_offset, assign_value)?;
                 }
                 AssignType::IsReferenceTypeCount => {
                     region
@@ -287,16 +302,16 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        wb_table: Rc<WasmBytecodeTable>,
-        leb128_chip: Rc<LEB128Chip<F>>,
-        dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+        wb_table: Arc<WasmBytecodeTable>,
+        leb128_chip: Arc<LEB128Chip<F>>,
+        byte_set_table_config: &ByteSetTableConfig<F>,
+        dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
         func_count: Column<Advice>,
-        error_code: Column<Advice>,
-        shared_state: Rc<RefCell<SharedState>>,
+        error_code: ErrorCodeColumns,
+        shared_state: SharedStateRef,
     ) -> WasmTableSectionBodyConfig<F> {
-        let q_enable = cs.fixed_column();
-        let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        let selectors = SectionSelectors::configure(cs);
+        let SectionSelectors { q_enable, q_last, .. } = selectors;
         let is_reference_type_count = cs.fixed_column();
         let is_reference_type = cs.fixed_column();
 
@@ -308,6 +323,7 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
             q_enable,
             leb128_chip.as_ref(),
             &limit_type_fields,
+            byte_set_table_config,
         );
 
         let LimitTypeFields {
@@ -320,18 +336,35 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
             ..
         } = limit_type_fields.clone();
 
+        byte_set_table_config.lookup_byte(
+            cs,
+            "reference_type => byte value is valid",
+            ByteSetTag::RefType,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                );
+                (
+                    q_enable_expr * vc.query_fixed(is_reference_type, Rotation::cur()),
+                    vc.query_advice(wb_table.value, Rotation::cur()),
+                )
+            },
+        );
+
         cs.create_gate("WasmTableSectionBody gate", |vc| {
             let mut cb = BaseConstraintBuilder::default();
 
             let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                 vc,
                 q_enable,
-                &shared_state.borrow(),
+                &shared_state.read(),
                 error_code,
             );
             // let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
             let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
-            let not_q_last_expr = not::expr(q_last_expr.clone());
             let is_reference_type_count_expr =
                 vc.query_fixed(is_reference_type_count, Rotation::cur());
             let is_reference_type_expr = vc.query_fixed(is_reference_type, Rotation::cur());
@@ -349,15 +382,11 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
                 limit_type_chip
                     .config
                     .value_equals(LimitType::MinOnly, Rotation::cur())(vc);
-            let limit_type_is_min_max_expr =
-                limit_type_chip
-                    .config
-                    .value_equals(LimitType::MinMax, Rotation::cur())(vc);
+            let limit_type_has_max_expr = limit_type_has_max_expr(&limit_type_chip, vc);
 
             let leb128_is_last_byte_expr =
                 vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
 
-            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean(
                 "is_reference_type_count is boolean",
                 is_reference_type_count_expr.clone(),
@@ -374,13 +403,10 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
                 is_limit_type_ctx_expr.clone(),
             );
 
-            configure_constraints_for_q_first_and_q_last(
+            selectors.configure_constraints(
                 &mut cb,
                 vc,
-                &q_enable,
-                &q_first,
                 &[is_reference_type_count],
-                &q_last,
                 &[is_limit_min, is_limit_max],
             );
 
@@ -409,21 +435,6 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
                 },
             );
 
-            cb.condition(is_reference_type_expr.clone(), |cb| {
-                cb.require_in_set(
-                    "reference_type => byte value is valid",
-                    byte_val_expr.clone(),
-                    REF_TYPE_VALUES.iter().map(|&v| v.expr()).collect_vec(),
-                )
-            });
-
-            cb.condition(is_limit_type_expr.clone(), |cb| {
-                cb.require_in_set(
-                    "limit_type => byte value is valid",
-                    byte_val_expr.clone(),
-                    vec![LimitType::MinOnly.expr(), LimitType::MinMax.expr()],
-                )
-            });
             cb.require_equal(
                 "is_limit_type_ctx active on a specific flags only",
                 is_limit_type_expr.clone() + is_limit_min_expr.clone() + is_limit_max_expr.clone(),
@@ -435,6 +446,12 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
                     limit_type_expr.clone(),
                     byte_val_expr.clone(),
                 );
+                cb.require_zero(
+                    "is_limit_type => tables are never shared",
+                    limit_type_chip
+                        .config
+                        .value_equals(LimitType::SharedMinMax, Rotation::cur())(vc),
+                );
             });
             cb.condition(is_limit_type_ctx_expr.clone(), |cb| {
                 let is_limit_type_ctx_prev_expr =
@@ -448,56 +465,37 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
 
             // reference_type_count+ -> reference_type{1} -> limit_type{1} -> limit_min+ ->
             // limit_max*
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: reference_type_count+ -> reference_type(1)",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_reference_type_count_expr.clone(),
-                ]),
-                true,
-                &[is_reference_type_count, is_reference_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): reference_type_count+ -> reference_type(1)",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    is_reference_type_count_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                ]),
-                true,
-                &[is_reference_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: reference_type(1) -> limit_type(1)",
-                and::expr([not_q_last_expr.clone(), is_reference_type_expr.clone()]),
-                true,
-                &[is_limit_type],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: limit_type(1) -> limit_min+",
-                and::expr([not_q_last_expr.clone(), is_limit_type_expr.clone()]),
-                true,
-                &[is_limit_min],
-            );
-            configure_transition_check(
+            Self::configure_transitions(
                 &mut cb,
                 vc,
-                "check next: limit_min+",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    limit_type_is_min_only_expr.clone(),
-                    is_limit_min_expr.clone(),
-                ]),
-                true,
-                &[is_limit_min],
+                q_last,
+                leb128_chip.config.is_last_byte,
+                vec![
+                    SectionTransition::leb(
+                        "check next: reference_type_count+ -> reference_type(1)",
+                        is_reference_type_count,
+                        &[is_reference_type],
+                    ),
+                    SectionTransition::new(
+                        "check next: reference_type(1) -> limit_type(1)",
+                        is_reference_type,
+                        &[is_limit_type],
+                    ),
+                    SectionTransition::new(
+                        "check next: limit_type(1) -> limit_min+",
+                        is_limit_type,
+                        &[is_limit_min],
+                    ),
+                    SectionTransition::new("check next: limit_min+", is_limit_min, &[is_limit_min])
+                        .when(limit_type_is_min_only_expr.clone()),
+                    SectionTransition::leb(
+                        "check next: limit_min+ -> limit_max*",
+                        is_limit_min,
+                        &[is_limit_max],
+                    )
+                    .when(limit_type_has_max_expr.clone()),
+                    SectionTransition::new("check next: limit_max*", is_limit_max, &[is_limit_max]),
+                ],
             );
             cb.condition(
                 and::expr([
@@ -513,48 +511,15 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
                     );
                 },
             );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: limit_min+ -> limit_max*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    limit_type_is_min_max_expr.clone(),
-                    is_limit_min_expr.clone(),
-                ]),
-                true,
-                &[is_limit_min, is_limit_max],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): limit_min+ -> limit_max*",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    limit_type_is_min_max_expr.clone(),
-                    is_limit_min_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                ]),
-                true,
-                &[is_limit_max],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: limit_max*",
-                and::expr([not_q_last_expr.clone(), is_limit_max_expr.clone()]),
-                true,
-                &[is_limit_max],
-            );
             cb.condition(
                 and::expr([
-                    limit_type_is_min_max_expr.clone(),
+                    limit_type_has_max_expr.clone(),
                     is_limit_max_expr.clone(),
                     leb128_is_last_byte_expr.clone(),
                 ]),
                 |cb| {
                     cb.require_equal(
-                        "limit_type_is_min_max && is_limit_max && leb128_is_last_byte => q_last",
+                        "limit_type_has_max && is_limit_max && leb128_is_last_byte => q_last",
                         q_last_expr.clone(),
                         1.expr(),
                     );
@@ -567,9 +532,7 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
         let config = WasmTableSectionBodyConfig::<F> {
             _marker: PhantomData,
 
-            q_enable,
-            q_first,
-            q_last,
+            selectors,
             is_reference_type_count,
             is_reference_type,
             limit_type_fields,
@@ -623,12 +586,12 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
         )?;
         let dynamic_indexes_offset = self.config.dynamic_indexes_chip.assign_auto(
             region,
-            self.config.shared_state.borrow().dynamic_indexes_offset,
+            self.config.shared_state.read().dynamic_indexes_offset,
             assign_delta,
             1,
             Tag::TableIndex,
         )?;
-        self.config.shared_state.borrow_mut().dynamic_indexes_offset = dynamic_indexes_offset;
+        self.config.shared_state.write().dynamic_indexes_offset = dynamic_indexes_offset;
         offset += 1;
 
         // limit_type{1}
@@ -636,6 +599,9 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
         let limit_type: LimitType = limit_type_val
             .try_into()
             .map_err(remap_error_to_invalid_enum_value_at(offset))?;
+        if limit_type == LimitType::SharedMinMax {
+            return Err(Error::InvalidEnumValueAt(offset));
+        }
         let limit_type_val = limit_type_val as u64;
         self.assign(
             region,
@@ -679,7 +645,7 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
         offset += limit_min_leb_len;
 
         // limit_max*
-        if limit_type == LimitType::MinMax {
+        if limit_type.has_max() {
             let (limit_max, limit_max_leb_len) = self.markup_leb_section(
                 region,
                 wb,
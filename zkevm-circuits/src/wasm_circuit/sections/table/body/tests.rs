@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
@@ -9,10 +9,13 @@ use eth_types::{Field, Hash, ToWord};
 
 use crate::wasm_circuit::{
     bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+    common::ErrorCodeColumns,
     leb128::circuit::LEB128Chip,
     sections::table::body::circuit::WasmTableSectionBodyChip,
-    tables::dynamic_indexes::circuit::DynamicIndexesChip,
-    types::SharedState,
+    tables::{
+        byte_set::config::ByteSetTableConfig, dynamic_indexes::circuit::DynamicIndexesChip,
+    },
+    types::SharedStateRef,
 };
 
 #[derive(Default)]
@@ -25,8 +28,9 @@ struct TestCircuit<'a, F> {
 
 #[derive(Clone)]
 struct TestCircuitConfig<F: Field> {
-    body_chip: Rc<WasmTableSectionBodyChip<F>>,
-    wb_table: Rc<WasmBytecodeTable>,
+    body_chip: Arc<WasmTableSectionBodyChip<F>>,
+    wb_table: Arc<WasmBytecodeTable>,
+    byte_set_table_config: ByteSetTableConfig<F>,
     _marker: PhantomData<F>,
 }
 
@@ -39,34 +43,37 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
     }
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, false));
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, false));
         let func_count = cs.advice_column();
-        let error_code = cs.advice_column();
+        let error_code = ErrorCodeColumns::configure(cs);
 
-        let shared_state = Rc::new(RefCell::new(SharedState::default()));
+        let shared_state = SharedStateRef::default();
 
         let config = DynamicIndexesChip::configure(cs, shared_state.clone());
-        let dynamic_indexes_chip = Rc::new(DynamicIndexesChip::construct(config));
+        let dynamic_indexes_chip = Arc::new(DynamicIndexesChip::construct(config));
 
         let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
-        let leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
+        let leb128_chip = Arc::new(LEB128Chip::construct(leb128_config));
+        let byte_set_table_config = ByteSetTableConfig::configure(cs);
 
         let wasm_table_section_body_config = WasmTableSectionBodyChip::configure(
             cs,
             wb_table.clone(),
             leb128_chip.clone(),
+            &byte_set_table_config,
             dynamic_indexes_chip.clone(),
             func_count,
             error_code,
             shared_state.clone(),
         );
-        let wasm_table_section_body_chip = Rc::new(WasmTableSectionBodyChip::construct(
+        let wasm_table_section_body_chip = Arc::new(WasmTableSectionBodyChip::construct(
             wasm_table_section_body_config,
         ));
 
         let test_circuit_config = TestCircuitConfig {
             body_chip: wasm_table_section_body_chip,
             wb_table,
+            byte_set_table_config,
             _marker: Default::default(),
         };
 
@@ -89,6 +96,7 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
                 },
             )
             .unwrap();
+        config.byte_set_table_config.load(&mut layouter)?;
         layouter.assign_region(
             || "wasm_table_section_body region",
             |mut region| {
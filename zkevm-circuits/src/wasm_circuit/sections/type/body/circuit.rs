@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Region, Value},
@@ -16,10 +16,12 @@ use crate::{
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
-            configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip, WasmErrorAwareChip,
-            WasmFuncCountAwareChip, WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
+            ErrorCodeColumns, SectionBodyChip, SectionBodyChipResources, SectionSelectors,
+            SectionTransition, WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip,
+            WasmErrorAwareChip, WasmFuncCountAwareChip, WasmMarkupLeb128SectionAwareChip,
+            WasmSectionTransitionsAwareChip, WasmSharedStateAwareChip,
         },
+        consts::MAX_FUNC_PARAMS,
         error::{remap_error_to_assign_at, Error},
         leb128::circuit::LEB128Chip,
         sections::{
@@ -27,27 +29,25 @@ use crate::{
             r#type::{body::types::AssignType, item::circuit::WasmTypeSectionItemChip},
         },
         tables::dynamic_indexes::{circuit::DynamicIndexesChip, types::Tag},
-        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedState},
+        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedStateRef, WasmSection},
     },
 };
 
 #[derive(Debug, Clone)]
 pub struct WasmTypeSectionBodyConfig<F> {
-    pub q_enable: Column<Fixed>,
-    pub q_first: Column<Fixed>,
-    pub q_last: Column<Fixed>,
+    pub selectors: SectionSelectors,
     pub is_items_count: Column<Fixed>,
     pub is_body: Column<Fixed>,
 
     func_count: Column<Advice>,
-    error_code: Column<Advice>,
+    error_code: ErrorCodeColumns,
     body_item_rev_count: Column<Advice>,
 
-    pub section_item_chip: Rc<WasmTypeSectionItemChip<F>>,
-    pub leb128_chip: Rc<LEB128Chip<F>>,
-    pub dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+    pub section_item_chip: Arc<WasmTypeSectionItemChip<F>>,
+    pub leb128_chip: Arc<LEB128Chip<F>>,
+    pub dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
 
-    pub shared_state: Rc<RefCell<SharedState>>,
+    pub shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -64,18 +64,65 @@ impl<F: Field> WasmMarkupLeb128SectionAwareChip<F> for WasmTypeSectionBodyChip<F
 
 impl<F: Field> WasmCountPrefixedItemsAwareChip<F> for WasmTypeSectionBodyChip<F> {}
 
+impl<F: Field> WasmSectionTransitionsAwareChip<F> for WasmTypeSectionBodyChip<F> {}
+
 impl<F: Field> WasmErrorAwareChip<F> for WasmTypeSectionBodyChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmTypeSectionBodyChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
 
+impl<F: Field> SectionBodyChip<F> for WasmTypeSectionBodyChip<F> {
+    const SECTION: WasmSection = WasmSection::Type;
+
+    fn configure_with_resources(
+        cs: &mut ConstraintSystem<F>,
+        resources: &SectionBodyChipResources<F>,
+    ) -> Self {
+        let config = WasmTypeSectionItemChip::configure(
+            cs,
+            resources.wb_table.clone(),
+            resources.leb128_chip.clone(),
+            &resources.byte_set_table_config,
+            resources.func_count,
+            resources.shared_state.clone(),
+            resources.body_item_rev_count_l2,
+            resources.error_code,
+            MAX_FUNC_PARAMS,
+            1,
+        );
+        let item_chip = Arc::new(WasmTypeSectionItemChip::construct(config));
+        let config = Self::configure(
+            cs,
+            resources.wb_table.clone(),
+            resources.leb128_chip.clone(),
+            item_chip,
+            resources.dynamic_indexes_chip.clone(),
+            resources.func_count,
+            resources.shared_state.clone(),
+            resources.body_item_rev_count_l1,
+            resources.error_code,
+        );
+        Self::construct(config)
+    }
+
+    fn assign_section_body(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+    ) -> Result<NewWbOffsetType, Error> {
+        self.assign_auto(region, wb, wb_offset, assign_delta)
+    }
+}
+
 impl<F: Field> WasmFuncCountAwareChip<F> for WasmTypeSectionBodyChip<F> {
     fn func_count_col(&self) -> Column<Advice> {
         self.config.func_count
@@ -101,14 +148,9 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmTypeSectionBodyChip<F> {
             "assign at {} q_enable {} assign_types {:?} assign_value {} byte_val {:x?}",
             assign_offset, q_enable, assign_types, assign_value, wb.bytes[wb_offset],
         );
-        region
-            .assign_fixed(
-                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
-                self.config.q_enable,
-                assign_offset,
-                || Value::known(F::from(q_enable as u64)),
-            )
-            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.config
+            .selectors
+            .assign_q_enable(region, assign_offset, q_enable)?;
         self.assign_func_count(region, assign_offset)?;
 
         for assign_type in assign_types {
@@ -121,29 +163,14 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmTypeSectionBodyChip<F> {
 
             match assign_type {
                 AssignType::QFirst => {
-                    region
-                        .assign_fixed(
-                            || {
-                                format!(
-                                    "assign 'q_first' val {} at {}",
-                                    assign_value, assign_offset
-                                )
-                            },
-                            self.config.q_first,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_first(region, assign_offset, assign_value)?;
                 }
                 AssignType::QLast => {
-                    region
-                        .assign_fixed(
-                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
-                            self.config.q_last,
-                            assign_offset,
-                            || Value::known(F::from(assign_value)),
-                        )
-                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    self.config
+                        .selectors
+                        .assign_q_last(region, assign_offset, assign_value)?;
                 }
                 AssignType::IsBodyItemsCount => {
                     region
@@ -210,18 +237,17 @@ impl<F: Field> WasmTypeSectionBodyChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        _wb_table: Rc<WasmBytecodeTable>,
-        leb128_chip: Rc<LEB128Chip<F>>,
-        section_item_chip: Rc<WasmTypeSectionItemChip<F>>,
-        dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+        _wb_table: Arc<WasmBytecodeTable>,
+        leb128_chip: Arc<LEB128Chip<F>>,
+        section_item_chip: Arc<WasmTypeSectionItemChip<F>>,
+        dynamic_indexes_chip: Arc<DynamicIndexesChip<F>>,
         func_count: Column<Advice>,
-        shared_state: Rc<RefCell<SharedState>>,
+        shared_state: SharedStateRef,
         body_item_rev_count: Column<Advice>,
-        error_code: Column<Advice>,
+        error_code: ErrorCodeColumns,
     ) -> WasmTypeSectionBodyConfig<F> {
-        let q_enable = cs.fixed_column();
-        let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        let selectors = SectionSelectors::configure(cs);
+        let SectionSelectors { q_enable, q_first, q_last } = selectors;
         let is_items_count = cs.fixed_column();
         let is_body = cs.fixed_column();
 
@@ -234,14 +260,14 @@ impl<F: Field> WasmTypeSectionBodyChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
 
                 and::expr([q_enable_expr, not::expr(is_items_count_expr)])
             },
-            |vc| vc.query_fixed(section_item_chip.config.q_first, Rotation::cur()),
+            |vc| vc.query_fixed(section_item_chip.config.selectors.q_first, Rotation::cur()),
             |vc| vc.query_fixed(q_last, Rotation::cur()),
         );
 
@@ -251,33 +277,19 @@ impl<F: Field> WasmTypeSectionBodyChip<F> {
             let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                 vc,
                 q_enable,
-                &shared_state.borrow(),
+                &shared_state.read(),
                 error_code,
             );
             // let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
-            let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
-            let not_q_last_expr = not::expr(q_last_expr.clone());
             let is_items_count_expr = vc.query_fixed(is_items_count, Rotation::cur());
             let is_body_expr = vc.query_fixed(is_body, Rotation::cur());
 
             // let byte_value_expr = vc.query_advice(bytecode_table.value, Rotation::cur());
 
-            let leb128_is_last_byte_expr =
-                vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
-
-            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_items_count is boolean", is_items_count_expr.clone());
             cb.require_boolean("is_body is boolean", is_body_expr.clone());
 
-            configure_constraints_for_q_first_and_q_last(
-                &mut cb,
-                vc,
-                &q_enable,
-                &q_first,
-                &[is_items_count],
-                &q_last,
-                &[is_body],
-            );
+            selectors.configure_constraints(&mut cb, vc, &[is_items_count], &[is_body]);
 
             cb.condition(is_items_count_expr.clone(), |cb| {
                 cb.require_zero(
@@ -288,36 +300,22 @@ impl<F: Field> WasmTypeSectionBodyChip<F> {
             cb.require_equal(
                 "is_body_expr <-> wasm_type_section_item",
                 is_body_expr.clone(),
-                vc.query_fixed(section_item_chip.config.q_enable, Rotation::cur()),
+                vc.query_fixed(section_item_chip.config.selectors.q_enable, Rotation::cur()),
             );
 
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next: is_items_count+ -> is_body+",
-                and::expr([not_q_last_expr.clone(), is_items_count_expr.clone()]),
-                true,
-                &[is_items_count, is_body],
-            );
-            configure_transition_check(
-                &mut cb,
-                vc,
-                "check next (last leb byte): is_items_count+ -> is_body+",
-                and::expr([
-                    not_q_last_expr.clone(),
-                    leb128_is_last_byte_expr.clone(),
-                    is_items_count_expr.clone(),
-                ]),
-                true,
-                &[is_body],
-            );
-            configure_transition_check(
+            Self::configure_transitions(
                 &mut cb,
                 vc,
-                "check next: is_body+",
-                and::expr([not_q_last_expr.clone(), is_body_expr.clone()]),
-                true,
-                &[is_body],
+                q_last,
+                leb128_chip.config.is_last_byte,
+                vec![
+                    SectionTransition::leb(
+                        "check next: is_items_count+ -> is_body+",
+                        is_items_count,
+                        &[is_body],
+                    ),
+                    SectionTransition::new("check next: is_body+", is_body, &[is_body]),
+                ],
             );
 
             cb.gate(q_enable_expr.clone())
@@ -326,9 +324,7 @@ impl<F: Field> WasmTypeSectionBodyChip<F> {
         let config = WasmTypeSectionBodyConfig::<F> {
             _marker: PhantomData,
 
-            q_enable,
-            q_first,
-            q_last,
+            selectors,
             is_items_count,
             is_body,
             leb128_chip,
@@ -384,12 +380,12 @@ impl<F: Field> WasmTypeSectionBodyChip<F> {
 
         let dynamic_indexes_offset = self.config.dynamic_indexes_chip.assign_auto(
             region,
-            self.config.shared_state.borrow().dynamic_indexes_offset,
+            self.config.shared_state.read().dynamic_indexes_offset,
             assign_delta,
             items_count as usize,
             Tag::TypeIndex,
         )?;
-        self.config.shared_state.borrow_mut().dynamic_indexes_offset = dynamic_indexes_offset;
+        self.config.shared_state.write().dynamic_indexes_offset = dynamic_indexes_offset;
 
         for _body_item_index in 0..items_count {
             body_item_rev_count -= 1;
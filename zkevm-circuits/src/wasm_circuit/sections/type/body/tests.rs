@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
@@ -9,12 +9,16 @@ use eth_types::{Field, Hash, ToWord};
 
 use crate::wasm_circuit::{
     bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+    common::ErrorCodeColumns,
+    consts::MAX_FUNC_PARAMS,
     leb128::circuit::LEB128Chip,
     sections::r#type::{
         body::circuit::WasmTypeSectionBodyChip, item::circuit::WasmTypeSectionItemChip,
     },
-    tables::dynamic_indexes::circuit::DynamicIndexesChip,
-    types::SharedState,
+    tables::{
+        byte_set::config::ByteSetTableConfig, dynamic_indexes::circuit::DynamicIndexesChip,
+    },
+    types::SharedStateRef,
 };
 
 #[derive(Default)]
@@ -26,10 +30,11 @@ struct TestCircuit<'a, F> {
 }
 
 #[derive(Clone)]
-struct TestCircuitConfig<F> {
-    item_chip: Rc<WasmTypeSectionItemChip<F>>,
-    body_chip: Rc<WasmTypeSectionBodyChip<F>>,
-    wb_table: Rc<WasmBytecodeTable>,
+struct TestCircuitConfig<F: Field> {
+    item_chip: Arc<WasmTypeSectionItemChip<F>>,
+    body_chip: Arc<WasmTypeSectionBodyChip<F>>,
+    wb_table: Arc<WasmBytecodeTable>,
+    byte_set_table_config: ByteSetTableConfig<F>,
     _marker: PhantomData<F>,
 }
 
@@ -42,29 +47,33 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
     }
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, false));
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, false));
         let func_count = cs.advice_column();
-        let error_code = cs.advice_column();
+        let error_code = ErrorCodeColumns::configure(cs);
         let body_item_rev_count_lv1 = cs.advice_column();
         let body_item_rev_count_lv2 = cs.advice_column();
 
-        let shared_state = Rc::new(RefCell::new(SharedState::default()));
+        let shared_state = SharedStateRef::default();
 
         let config = DynamicIndexesChip::configure(cs, shared_state.clone());
-        let dynamic_indexes_chip = Rc::new(DynamicIndexesChip::construct(config));
+        let dynamic_indexes_chip = Arc::new(DynamicIndexesChip::construct(config));
 
         let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
-        let leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
+        let leb128_chip = Arc::new(LEB128Chip::construct(leb128_config));
+        let byte_set_table_config = ByteSetTableConfig::configure(cs);
         let config = WasmTypeSectionItemChip::configure(
             cs,
             wb_table.clone(),
             leb128_chip.clone(),
+            &byte_set_table_config,
             func_count,
             shared_state.clone(),
             body_item_rev_count_lv2,
             error_code,
+            MAX_FUNC_PARAMS,
+            1,
         );
-        let item_chip = Rc::new(WasmTypeSectionItemChip::construct(config));
+        let item_chip = Arc::new(WasmTypeSectionItemChip::construct(config));
         let config = WasmTypeSectionBodyChip::configure(
             cs,
             wb_table.clone(),
@@ -76,11 +85,12 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
             body_item_rev_count_lv1,
             error_code,
         );
-        let body_chip = Rc::new(WasmTypeSectionBodyChip::construct(config));
+        let body_chip = Arc::new(WasmTypeSectionBodyChip::construct(config));
         let test_circuit_config = TestCircuitConfig {
             item_chip,
             body_chip,
             wb_table,
+            byte_set_table_config,
             _marker: Default::default(),
         };
 
@@ -103,10 +113,11 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
                 },
             )
             .unwrap();
+        config.byte_set_table_config.load(&mut layouter)?;
         layouter.assign_region(
             || "wasm_type_section_body region",
             |mut region| {
-                config.body_chip.config.shared_state.borrow_mut().reset();
+                config.body_chip.config.shared_state.write().reset();
                 let assign_delta = self.assign_delta_base;
                 let mut wb_offset = 0;
                 while wb_offset < wb.bytes.len() {
@@ -128,14 +139,17 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
 mod wasm_type_section_body_tests {
     use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
     use log::debug;
-    use rand::{thread_rng, Rng};
+    use rand::Rng;
     use wasmbin::sections::Kind;
 
     use bus_mapping::state_db::CodeDB;
     use eth_types::Field;
 
-    use crate::wasm_circuit::{
-        common::wat_extract_section_body_bytecode, sections::r#type::body::tests::TestCircuit,
+    use crate::{
+        test_rng::test_rng,
+        wasm_circuit::{
+            common::wat_extract_section_body_bytecode, sections::r#type::body::tests::TestCircuit,
+        },
     };
 
     fn test<'a, F: Field>(test_circuit: TestCircuit<'_, F>, is_ok: bool, k: u32) {
@@ -172,7 +186,7 @@ mod wasm_type_section_body_tests {
         let test_circuit = TestCircuit::<Fr> {
             code_hash,
             bytecode_bytes: &bytecode,
-            assign_delta_base: thread_rng().gen_range(5..300),
+            assign_delta_base: test_rng().gen_range(5..300),
             ..Default::default()
         };
         test(test_circuit, true, 9);
@@ -199,7 +213,7 @@ mod wasm_type_section_body_tests {
         let test_circuit = TestCircuit::<Fr> {
             code_hash,
             bytecode_bytes: &bytecode,
-            assign_delta_base: thread_rng().gen_range(5..300),
+            assign_delta_base: test_rng().gen_range(5..300),
             ..Default::default()
         };
         test(test_circuit, true, 9);
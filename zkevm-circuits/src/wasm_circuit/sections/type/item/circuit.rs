@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Region, Value},
@@ -9,45 +9,58 @@ use itertools::Itertools;
 use log::debug;
 
 use eth_types::Field;
-use gadgets::util::{and, not, or, Expr};
+use gadgets::{
+    less_than::{LtChip, LtInstruction},
+    util::{and, not, or, Expr},
+};
 
 use crate::{
     evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
-            configure_constraints_for_q_first_and_q_last, configure_transition_check,
-            WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip, WasmErrorAwareChip,
-            WasmFuncCountAwareChip, WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
+            configure_transition_check, ErrorCodeColumns, SectionSelectors, WasmAssignAwareChip,
+            WasmCountPrefixedItemsAwareChip, WasmErrorAwareChip, WasmFuncCountAwareChip,
+            WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
         },
-        error::{remap_error_to_assign_at, Error},
+        error::{remap_error, remap_error_to_assign_at, Error},
         leb128::circuit::LEB128Chip,
         sections::{
             consts::LebParams,
             r#type::item::{consts::Type::FuncType, types::AssignType},
         },
-        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, NumType, SharedState},
+        tables::byte_set::{config::ByteSetTableConfig, types::ByteSetTag},
+        types::{
+            AssignDeltaType, AssignOffsetType, AssignValueType, NewWbOffsetType, SharedStateRef,
+        },
     },
 };
 
 #[derive(Debug, Clone)]
 pub struct WasmTypeSectionItemConfig<F> {
-    pub q_enable: Column<Fixed>,
-    pub q_first: Column<Fixed>,
-    pub q_last: Column<Fixed>,
+    pub selectors: SectionSelectors,
     pub is_type: Column<Fixed>,
     pub is_input_count: Column<Fixed>,
     pub is_input_type: Column<Fixed>,
     pub is_output_count: Column<Fixed>,
     pub is_output_type: Column<Fixed>,
 
-    pub leb128_chip: Rc<LEB128Chip<F>>,
+    /// number of params of the type, shared by all the rows of the item
+    pub input_count: Column<Advice>,
+    /// number of results of the type, shared by all the rows of the item
+    pub output_count: Column<Advice>,
+
+    pub leb128_chip: Arc<LEB128Chip<F>>,
+    /// param and result counts must not exceed `max_func_params` and `max_func_results`
+    pub max_func_params: u64,
+    pub max_func_results: u64,
+    pub count_lt_chip: Arc<LtChip<F, 4>>,
 
     func_count: Column<Advice>,
-    error_code: Column<Advice>,
+    error_code: ErrorCodeColumns,
     body_item_rev_count: Column<Advice>,
 
-    shared_state: Rc<RefCell<SharedState>>,
+    shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -65,13 +78,13 @@ impl<F: Field> WasmMarkupLeb128SectionAwareChip<F> for WasmTypeSectionItemChip<F
 impl<F: Field> WasmCountPrefixedItemsAwareChip<F> for WasmTypeSectionItemChip<F> {}
 
 impl<F: Field> WasmErrorAwareChip<F> for WasmTypeSectionItemChip<F> {
-    fn error_code_col(&self) -> Column<Advice> {
+    fn error_code_col(&self) -> ErrorCodeColumns {
         self.config.error_code
     }
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for WasmTypeSectionItemChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
@@ -101,14 +114,9 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmTypeSectionItemChip<F> {
             "assign at {} q_enable {} assign_types {:?} assign_value {} byte_val {:x?}",
             assign_offset, q_enable, assign_types, assign_value, wb.bytes[wb_offset],
         );
-        region
-            .assign_fixed(
-                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
-                self.config.q_enable,
-                assign_offset,
-                || Value::known(F::from(q_enable as u64)),
-            )
-            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.config
+            .selectors
+            .assign_q_enable(region, assign_offset, q_enable)?;
         self.assign_func_count(region, assign_offset)?;
 
         for assign_type in assign_types {
@@ -194,31 +202,46 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmTypeSectionItemChip<F> {
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
-                AssignType::QFirst => {
+                AssignType::InputCount => {
                     region
-                        .assign_fixed(
+                        .assign_advice(
                             || {
                                 format!(
-                                    "assign 'q_first' val {} at {}",
+                                    "assign 'input_count' val {} at {}",
                                     assign_value, assign_offset
                                 )
                             },
-                            self.config.q_first,
+                            self.config.input_count,
                             assign_offset,
                             || Value::known(F::from(assign_value)),
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
-                AssignType::QLast => {
+                AssignType::OutputCount => {
                     region
-                        .assign_fixed(
-                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
-                            self.config.q_last,
+                        .assign_advice(
+                            || {
+                                format!(
+                                    "assign 'output_count' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.output_count,
                             assign_offset,
                             || Value::known(F::from(assign_value)),
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
+                AssignType::QFirst => {
+                    self.config
+                        .selectors
+                        .assign_q_first(region, assign_offset, assign_value)?;
+                }
+                AssignType::QLast => {
+                    self.config
+                        .selectors
+                        .assign_q_last(region, assign_offset, assign_value)?;
+                }
                 AssignType::BodyItemRevCount => {
                     region
                         .assign_advice(
@@ -255,22 +278,48 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        wb_table: Rc<WasmBytecodeTable>,
-        leb128_chip: Rc<LEB128Chip<F>>,
+        wb_table: Arc<WasmBytecodeTable>,
+        leb128_chip: Arc<LEB128Chip<F>>,
+        byte_set_table_config: &ByteSetTableConfig<F>,
         func_count: Column<Advice>,
-        shared_state: Rc<RefCell<SharedState>>,
+        shared_state: SharedStateRef,
         body_item_rev_count: Column<Advice>,
-        error_code: Column<Advice>,
+        error_code: ErrorCodeColumns,
+        max_func_params: u64,
+        max_func_results: u64,
     ) -> WasmTypeSectionItemConfig<F> {
-        let q_enable = cs.fixed_column();
-        let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        let selectors = SectionSelectors::configure(cs);
+        let SectionSelectors { q_enable, q_first, q_last } = selectors;
         let is_type = cs.fixed_column();
         let is_input_count = cs.fixed_column();
         let is_input_type = cs.fixed_column();
         let is_output_count = cs.fixed_column();
         let is_output_type = cs.fixed_column();
 
+        let input_count = cs.advice_column();
+        let output_count = cs.advice_column();
+
+        let config = LtChip::configure(
+            cs,
+            |vc| {
+                and::expr([
+                    vc.query_fixed(q_enable, Rotation::cur()),
+                    or::expr([
+                        vc.query_fixed(is_input_count, Rotation::cur()),
+                        vc.query_fixed(is_output_count, Rotation::cur()),
+                    ]),
+                    vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur()),
+                ])
+            },
+            |vc| vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
+            |vc| {
+                vc.query_fixed(is_input_count, Rotation::cur()) * (max_func_params + 1).expr()
+                    + vc.query_fixed(is_output_count, Rotation::cur())
+                        * (max_func_results + 1).expr()
+            },
+        );
+        let count_lt_chip = Arc::new(LtChip::construct(config));
+
         Self::configure_count_prefixed_items_checks(
             cs,
             leb128_chip.as_ref(),
@@ -280,7 +329,7 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
                 let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                     vc,
                     q_enable,
-                    &shared_state.borrow(),
+                    &shared_state.read(),
                     error_code,
                 );
                 let is_type_expr = vc.query_fixed(is_type, Rotation::cur());
@@ -316,16 +365,35 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
             },
         );
 
+        byte_set_table_config.lookup_byte(
+            cs,
+            "type_section_item input/output type has valid value",
+            ByteSetTag::NumType,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.read(),
+                    error_code,
+                );
+                let is_input_type_expr = vc.query_fixed(is_input_type, Rotation::cur());
+                let is_output_type_expr = vc.query_fixed(is_output_type, Rotation::cur());
+                (
+                    q_enable_expr * or::expr([is_input_type_expr, is_output_type_expr]),
+                    vc.query_advice(wb_table.value, Rotation::cur()),
+                )
+            },
+        );
+
         cs.create_gate("WasmTypeSectionItem gate", |vc| {
             let mut cb = BaseConstraintBuilder::default();
 
             let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                 vc,
                 q_enable,
-                &shared_state.borrow(),
+                &shared_state.read(),
                 error_code,
             );
-            // let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
             let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
             let not_q_last_expr = not::expr(q_last_expr.clone());
             let is_type_expr = vc.query_fixed(is_type, Rotation::cur());
@@ -336,23 +404,26 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
 
             let byte_value_expr = vc.query_advice(wb_table.value, Rotation::cur());
 
+            let input_count_expr = vc.query_advice(input_count, Rotation::cur());
+            let input_count_prev_expr = vc.query_advice(input_count, Rotation::prev());
+            let output_count_expr = vc.query_advice(output_count, Rotation::cur());
+            let output_count_prev_expr = vc.query_advice(output_count, Rotation::prev());
+            let q_first_expr = vc.query_fixed(q_first, Rotation::cur());
+            let leb128_sn_expr = vc.query_advice(leb128_chip.config.sn, Rotation::cur());
+
             let leb128_is_last_byte_expr =
                 vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
 
-            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_type is boolean", is_type_expr.clone());
             cb.require_boolean("is_input_count is boolean", is_input_count_expr.clone());
             cb.require_boolean("is_input_type is boolean", is_input_type_expr.clone());
             cb.require_boolean("is_output_count is boolean", is_output_count_expr.clone());
             cb.require_boolean("is_output_type is boolean", is_output_type_expr.clone());
 
-            configure_constraints_for_q_first_and_q_last(
+            selectors.configure_constraints(
                 &mut cb,
                 vc,
-                &q_enable,
-                &q_first,
                 &[is_type],
-                &q_last,
                 &[is_output_type, is_output_count],
             );
 
@@ -364,17 +435,6 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
                 )
             });
 
-            cb.condition(
-                or::expr([is_input_type_expr.clone(), is_output_type_expr.clone()]),
-                |cb| {
-                    cb.require_in_set(
-                        "type_section_item input/output type has valid value",
-                        byte_value_expr.clone(),
-                        vec![NumType::I32.expr(), NumType::I64.expr()],
-                    )
-                },
-            );
-
             cb.condition(
                 or::expr([is_input_count_expr.clone(), is_output_count_expr.clone()]),
                 |cb| {
@@ -387,6 +447,46 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
                 },
             );
 
+            cb.condition(is_input_count_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_input_count => input_count=leb128.sn",
+                    input_count_expr.clone(),
+                    leb128_sn_expr.clone(),
+                )
+            });
+            cb.condition(is_output_count_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_output_count => output_count=leb128.sn",
+                    output_count_expr.clone(),
+                    leb128_sn_expr.clone(),
+                )
+            });
+            cb.condition(
+                and::expr([
+                    or::expr([is_input_count_expr.clone(), is_output_count_expr.clone()]),
+                    leb128_is_last_byte_expr.clone(),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "is_input/output_count => count <= max func params/results",
+                        count_lt_chip.config().is_lt(vc, None),
+                        1.expr(),
+                    );
+                },
+            );
+            cb.condition(not::expr(q_first_expr.clone()), |cb| {
+                cb.require_equal(
+                    "input_count is the same for all the rows of an item",
+                    input_count_expr.clone(),
+                    input_count_prev_expr.clone(),
+                );
+                cb.require_equal(
+                    "output_count is the same for all the rows of an item",
+                    output_count_expr.clone(),
+                    output_count_prev_expr.clone(),
+                );
+            });
+
             cb.require_equal(
                 "exactly one mark flag active at the same time",
                 is_type_expr.clone()
@@ -470,15 +570,18 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
         let config = WasmTypeSectionItemConfig::<F> {
             _marker: PhantomData,
 
-            q_enable,
-            q_first,
-            q_last,
+            selectors,
             is_type,
             is_input_count,
             is_input_type,
             is_output_count,
             is_output_type,
+            input_count,
+            output_count,
             leb128_chip,
+            max_func_params,
+            max_func_results,
+            count_lt_chip,
             func_count,
             body_item_rev_count,
             error_code,
@@ -488,6 +591,31 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
         config
     }
 
+    /// Assigns the check of a param or result count (at its last leb byte) against `max_count`
+    fn assign_count(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        assign_delta: AssignDeltaType,
+        count: u64,
+        max_count: u64,
+        exceeded_at: fn(AssignOffsetType) -> Error,
+    ) -> Result<(), Error> {
+        self.config
+            .count_lt_chip
+            .assign(
+                region,
+                offset + assign_delta,
+                F::from(count),
+                F::from(max_count + 1),
+            )
+            .map_err(remap_error(Error::FatalAssignExternalChip))?;
+        if count > max_count {
+            return Err(exceeded_at(offset));
+        }
+        Ok(())
+    }
+
     pub fn assign_auto(
         &self,
         region: &mut Region<F>,
@@ -516,6 +644,14 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
             assign_delta,
             &[AssignType::IsInputCount],
         )?;
+        self.assign_count(
+            region,
+            offset + input_count_leb_len - 1,
+            assign_delta,
+            input_count,
+            self.config.max_func_params,
+            Error::FuncParamsExceededAt,
+        )?;
         let mut body_item_rev_count = input_count;
         for offset in offset..offset + input_count_leb_len {
             self.assign(
@@ -561,6 +697,14 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
             assign_delta,
             &[AssignType::IsOutputCount],
         )?;
+        self.assign_count(
+            region,
+            offset + output_count_leb_len - 1,
+            assign_delta,
+            output_count,
+            self.config.max_func_results,
+            Error::FuncResultsExceededAt,
+        )?;
         let mut body_item_rev_count = output_count;
         for offset in offset..offset + output_count_leb_len {
             self.assign(
@@ -598,6 +742,27 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
         }
         offset += output_count as usize;
 
+        for offset in wb_offset..offset {
+            self.assign(
+                region,
+                wb,
+                offset,
+                assign_delta,
+                &[AssignType::InputCount],
+                input_count,
+                None,
+            )?;
+            self.assign(
+                region,
+                wb,
+                offset,
+                assign_delta,
+                &[AssignType::OutputCount],
+                output_count,
+                None,
+            )?;
+        }
+
         if offset != wb_offset {
             self.assign(
                 region,
@@ -9,6 +9,9 @@ pub enum AssignType {
     IsOutputCount,
     IsOutputType,
 
+    InputCount,
+    OutputCount,
+
     BodyItemRevCount,
 
     ErrorCode,
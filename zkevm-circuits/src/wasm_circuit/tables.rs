@@ -1,3 +1,7 @@
 pub mod fixed_range;
 pub mod dynamic_indexes;
 pub mod code_blocks;
+pub mod import_whitelist;
+pub mod utf8_transition;
+pub mod byte_set;
+pub mod opcode;
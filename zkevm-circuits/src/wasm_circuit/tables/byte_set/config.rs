@@ -0,0 +1,70 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, Expression, TableColumn, VirtualCells},
+};
+
+use eth_types::Field;
+use gadgets::util::Expr;
+
+use crate::wasm_circuit::tables::byte_set::types::{byte_set_rows, ByteSetTag};
+
+/// Fixed table of allowed `(tag, byte)` pairs shared by the section chips. Checking a byte with
+/// [`Self::lookup_byte`] replaces a `require_in_set` over the allowed values and keeps the degree
+/// of the section gates low.
+#[derive(Debug, Clone)]
+pub struct ByteSetTableConfig<F: Field> {
+    pub tag: TableColumn,
+    pub byte: TableColumn,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> ByteSetTableConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            tag: cs.lookup_table_column(),
+            byte: cs.lookup_table_column(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Requires the byte returned by `cond_and_byte` to be in the set of `tag` where the returned
+    /// condition is enabled
+    pub fn lookup_byte(
+        &self,
+        cs: &mut ConstraintSystem<F>,
+        name: &'static str,
+        tag: ByteSetTag,
+        cond_and_byte: impl FnOnce(&mut VirtualCells<'_, F>) -> (Expression<F>, Expression<F>),
+    ) {
+        cs.lookup(name, |vc| {
+            let (cond, byte) = cond_and_byte(vc);
+
+            vec![
+                (cond.clone() * tag.expr(), self.tag),
+                (cond * byte, self.byte),
+            ]
+        });
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load byte set table",
+            |mut table| {
+                for (offset, (tag, byte)) in byte_set_rows().into_iter().enumerate() {
+                    for (column, value) in [(self.tag, tag as u64), (self.byte, byte as u64)] {
+                        table.assign_cell(
+                            || format!("byte set row {}", offset),
+                            column,
+                            offset,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
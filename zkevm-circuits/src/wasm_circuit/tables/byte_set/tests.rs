@@ -0,0 +1,27 @@
+#[cfg(test)]
+mod byte_set_tests {
+    use strum::IntoEnumIterator;
+
+    use crate::wasm_circuit::{
+        tables::byte_set::types::{byte_set_rows, ByteSetTag},
+        types::{LimitType, RefType},
+    };
+
+    #[test]
+    pub fn rows_include_zero_row() {
+        assert!(byte_set_rows().contains(&(ByteSetTag::Disabled, 0)));
+    }
+
+    #[test]
+    pub fn every_tag_has_distinct_bytes() {
+        for tag in ByteSetTag::iter() {
+            let mut bytes = tag.allowed_bytes();
+            assert!(!bytes.is_empty());
+            bytes.sort();
+            bytes.dedup();
+            assert_eq!(bytes.len(), tag.allowed_bytes().len());
+        }
+        assert!(ByteSetTag::LimitType.allowed_bytes().contains(&(LimitType::MinMax as u8)));
+        assert!(ByteSetTag::RefType.allowed_bytes().contains(&(RefType::FuncRef as u8)));
+    }
+}
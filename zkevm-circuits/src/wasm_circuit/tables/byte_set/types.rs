@@ -0,0 +1,79 @@
+use halo2_proofs::{arithmetic::FieldExt, plonk::Expression};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use gadgets::util::Expr;
+
+use crate::wasm_circuit::{
+    sections::element::body::consts::ElementType,
+    types::{
+        NumericInstruction, EXPORT_DESC_TYPE_VALUES, IMPORT_DESC_TYPE_VALUES, LIMIT_TYPE_VALUES,
+        MEM_SEGMENT_TYPE_VALUES, MUTABILITY_VALUES, NUM_TYPE_VALUES, REF_TYPE_VALUES,
+    },
+};
+
+/// Contexts of the bytes checked by the byte set table, every context has its own set of allowed
+/// bytes
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq)]
+pub enum ByteSetTag {
+    /// disabled lookup rows, allows `0` only
+    Disabled = 0,
+    LimitType,
+    RefType,
+    Mutability,
+    ImportDescType,
+    ExportDescType,
+    NumType,
+    MemSegmentType,
+    ElementType,
+    GlobalInitOpcode,
+}
+
+impl<F: FieldExt> Expr<F> for ByteSetTag {
+    #[inline]
+    fn expr(&self) -> Expression<F> {
+        Expression::Constant(F::from(*self as u64))
+    }
+}
+
+impl ByteSetTag {
+    pub fn allowed_bytes(&self) -> Vec<u8> {
+        match self {
+            ByteSetTag::Disabled => vec![0],
+            ByteSetTag::LimitType => LIMIT_TYPE_VALUES.iter().map(|&v| v as u8).collect(),
+            ByteSetTag::RefType => REF_TYPE_VALUES.iter().map(|&v| v as u8).collect(),
+            ByteSetTag::Mutability => MUTABILITY_VALUES.iter().map(|&v| v as u8).collect(),
+            ByteSetTag::ImportDescType => {
+                IMPORT_DESC_TYPE_VALUES.iter().map(|&v| v as u8).collect()
+            }
+            ByteSetTag::ExportDescType => {
+                EXPORT_DESC_TYPE_VALUES.iter().map(|&v| v as u8).collect()
+            }
+            ByteSetTag::NumType => NUM_TYPE_VALUES.iter().map(|&v| v as u8).collect(),
+            ByteSetTag::MemSegmentType => {
+                MEM_SEGMENT_TYPE_VALUES.iter().map(|&v| v as u8).collect()
+            }
+            ByteSetTag::ElementType => vec![
+                ElementType::_0 as u8,
+                ElementType::_1 as u8,
+                ElementType::_2 as u8,
+                ElementType::_4 as u8,
+                ElementType::_5 as u8,
+                ElementType::_6 as u8,
+                ElementType::_7 as u8,
+            ],
+            // float consts are not supported yet
+            ByteSetTag::GlobalInitOpcode => vec![
+                NumericInstruction::I32Const as u8,
+                NumericInstruction::I64Const as u8,
+            ],
+        }
+    }
+}
+
+/// All `(tag, byte)` rows of the table
+pub fn byte_set_rows() -> Vec<(ByteSetTag, u8)> {
+    ByteSetTag::iter()
+        .flat_map(|tag| tag.allowed_bytes().into_iter().map(move |byte| (tag, byte)))
+        .collect()
+}
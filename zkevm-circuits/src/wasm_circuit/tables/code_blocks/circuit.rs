@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Region, Value},
@@ -22,7 +22,7 @@ use crate::{
         },
         error::{remap_error_to_assign_at, remap_error_to_invalid_enum_value_at, Error},
         tables::code_blocks::types::{AssignType, Opcode, OPCODE_VALUES},
-        types::{AssignDeltaType, AssignValueType, OffsetType, SharedState},
+        types::{AssignDeltaType, AssignValueType, OffsetType, SharedStateRef},
     },
 };
 
@@ -35,9 +35,9 @@ pub struct CodeBlocksConfig<F> {
     pub index: Column<Advice>,
     pub opcode: Column<Advice>,
 
-    pub opcode_chip: Rc<BinaryNumberChip<F, Opcode, 8>>,
+    pub opcode_chip: Arc<BinaryNumberChip<F, Opcode, 8>>,
 
-    pub shared_state: Rc<RefCell<SharedState>>,
+    pub shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -45,7 +45,7 @@ pub struct CodeBlocksConfig<F> {
 impl<'a, F: Field> CodeBlocksConfig<F> {}
 
 impl<F: Field> WasmSharedStateAwareChip<F> for CodeBlocksChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
@@ -73,7 +73,7 @@ impl<F: Field> CodeBlocksChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        shared_state: Rc<RefCell<SharedState>>,
+        shared_state: SharedStateRef,
     ) -> CodeBlocksConfig<F> {
         let q_enable = cs.fixed_column();
         let q_first = cs.fixed_column();
@@ -84,7 +84,7 @@ impl<F: Field> CodeBlocksChip<F> {
         let index = cs.advice_column();
 
         let config = BinaryNumberChip::configure(cs, q_enable, Some(opcode.into()));
-        let opcode_chip = Rc::new(BinaryNumberChip::construct(config));
+        let opcode_chip = Arc::new(BinaryNumberChip::construct(config));
 
         Self::configure_bytecode_number(cs, q_enable, q_first, q_last, bytecode_number);
 
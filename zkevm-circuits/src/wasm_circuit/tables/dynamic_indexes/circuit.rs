@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::marker::PhantomData;
 
 use halo2_proofs::{
     circuit::{Region, Value},
@@ -16,7 +16,7 @@ use crate::{
         common::{WasmBytecodeNumberAwareChip, WasmSharedStateAwareChip},
         error::{remap_error_to_assign_at, Error},
         tables::dynamic_indexes::types::{AssignType, LookupArgsParams, Tag, TAG_VALUES},
-        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedState},
+        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedStateRef},
     },
 };
 
@@ -28,7 +28,7 @@ pub struct DynamicIndexesConfig<F> {
     pub is_terminator: Column<Fixed>,
     pub tag: Column<Fixed>,
 
-    pub shared_state: Rc<RefCell<SharedState>>,
+    pub shared_state: SharedStateRef,
 
     _marker: PhantomData<F>,
 }
@@ -42,7 +42,7 @@ pub struct DynamicIndexesChip<F> {
 }
 
 impl<F: Field> WasmSharedStateAwareChip<F> for DynamicIndexesChip<F> {
-    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+    fn shared_state(&self) -> SharedStateRef {
         self.config.shared_state.clone()
     }
 }
@@ -55,7 +55,7 @@ impl<F: Field> WasmBytecodeNumberAwareChip<F> for DynamicIndexesChip<F> {
 impl<F: Field> DynamicIndexesChip<F> {
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        shared_state: Rc<RefCell<SharedState>>,
+        shared_state: SharedStateRef,
     ) -> DynamicIndexesConfig<F> {
         let q_enable = cs.fixed_column();
         let is_terminator = cs.fixed_column();
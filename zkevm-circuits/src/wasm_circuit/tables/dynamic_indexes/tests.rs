@@ -1,4 +1,4 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
@@ -8,9 +8,12 @@ use halo2_proofs::{
 use eth_types::{Field, ToWord};
 use gadgets::util::Expr;
 
-use crate::wasm_circuit::tables::dynamic_indexes::{
-    circuit::DynamicIndexesChip,
-    types::{LookupArgsParams, Tag},
+use crate::wasm_circuit::{
+    tables::dynamic_indexes::{
+        circuit::DynamicIndexesChip,
+        types::{LookupArgsParams, Tag},
+    },
+    types::SharedStateRef,
 };
 
 #[derive(Default)]
@@ -22,7 +25,7 @@ struct TestCircuit<F> {
 
 #[derive(Clone)]
 struct TestCircuitConfig<F: Field> {
-    chip: Rc<DynamicIndexesChip<F>>,
+    chip: Arc<DynamicIndexesChip<F>>,
     _marker: PhantomData<F>,
 }
 
@@ -35,12 +38,12 @@ impl<F: Field> Circuit<F> for TestCircuit<F> {
     }
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-        let shared_state = Rc::new(RefCell::new(Default::default()));
+        let shared_state = SharedStateRef::default();
         let config = DynamicIndexesChip::configure(cs, shared_state.clone());
         let chip = DynamicIndexesChip::construct(config);
 
         let test_circuit_config = TestCircuitConfig {
-            chip: Rc::new(chip),
+            chip: Arc::new(chip),
             _marker: Default::default(),
         };
 
@@ -78,7 +81,7 @@ impl<F: Field> Circuit<F> for TestCircuit<F> {
         layouter.assign_region(
             || "wasm_data_section_body region",
             |mut region| {
-                config.chip.config.shared_state.borrow_mut().reset();
+                config.chip.config.shared_state.write().reset();
                 let mut offset = 0;
                 offset = config
                     .chip
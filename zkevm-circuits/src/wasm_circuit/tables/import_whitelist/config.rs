@@ -0,0 +1,71 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+
+use eth_types::Field;
+
+use crate::wasm_circuit::tables::import_whitelist::types::{name_rlc, ImportWhitelist};
+
+/// Fixed table of whitelisted `(mod_name_len, mod_name_rlc, import_name_len, import_name_rlc)`.
+/// First row is all zeros so disabled lookup rows pass.
+#[derive(Debug, Clone)]
+pub struct ImportWhitelistTableConfig<F: Field> {
+    pub mod_name_len: TableColumn,
+    pub mod_name_rlc: TableColumn,
+    pub import_name_len: TableColumn,
+    pub import_name_rlc: TableColumn,
+    pub whitelist: ImportWhitelist,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> ImportWhitelistTableConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>, whitelist: ImportWhitelist) -> Self {
+        Self {
+            mod_name_len: cs.lookup_table_column(),
+            mod_name_rlc: cs.lookup_table_column(),
+            import_name_len: cs.lookup_table_column(),
+            import_name_rlc: cs.lookup_table_column(),
+            whitelist,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load import whitelist table",
+            |mut table| {
+                let rows = std::iter::once((F::zero(), F::zero(), F::zero(), F::zero())).chain(
+                    self.whitelist.entries().iter().map(|(module, name)| {
+                        (
+                            F::from(module.len() as u64),
+                            name_rlc(module.as_bytes()),
+                            F::from(name.len() as u64),
+                            name_rlc(name.as_bytes()),
+                        )
+                    }),
+                );
+                for (offset, row) in rows.enumerate() {
+                    let (mod_name_len, mod_name_rlc, import_name_len, import_name_rlc) = row;
+                    for (column, value) in [
+                        (self.mod_name_len, mod_name_len),
+                        (self.mod_name_rlc, mod_name_rlc),
+                        (self.import_name_len, import_name_len),
+                        (self.import_name_rlc, import_name_rlc),
+                    ] {
+                        table.assign_cell(
+                            || format!("import whitelist row {}", offset),
+                            column,
+                            offset,
+                            || Value::known(value),
+                        )?;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod import_whitelist_tests {
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    use crate::wasm_circuit::tables::import_whitelist::types::{
+        name_rlc, ImportWhitelist, IMPORT_WHITELIST_NAME_MAX_LEN, WASI_SNAPSHOT_PREVIEW1_FUNCS,
+        WASI_SNAPSHOT_PREVIEW1_MODULE,
+    };
+
+    #[test]
+    pub fn wasi_whitelist_contains_preview1_funcs() {
+        let whitelist = ImportWhitelist::wasi_snapshot_preview1();
+        assert_eq!(whitelist.entries().len(), WASI_SNAPSHOT_PREVIEW1_FUNCS.len());
+        assert!(whitelist.contains(b"wasi_snapshot_preview1", b"fd_write"));
+        assert!(!whitelist.contains(b"wasi_snapshot_preview1", b"fd_write2"));
+        assert!(!whitelist.contains(b"env", b"fd_write"));
+        for name in WASI_SNAPSHOT_PREVIEW1_FUNCS {
+            assert!(name.len() <= IMPORT_WHITELIST_NAME_MAX_LEN);
+        }
+        assert!(WASI_SNAPSHOT_PREVIEW1_MODULE.len() <= IMPORT_WHITELIST_NAME_MAX_LEN);
+    }
+
+    #[test]
+    pub fn duplicates_are_ignored() {
+        let whitelist = ImportWhitelist::new()
+            .with_import("env", "a")
+            .with_module("env", &["a", "b"]);
+        assert_eq!(whitelist.entries().len(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn too_long_name_panics() {
+        ImportWhitelist::new().with_import("env", &"a".repeat(IMPORT_WHITELIST_NAME_MAX_LEN + 1));
+    }
+
+    #[test]
+    pub fn name_rlc_packs_bytes() {
+        assert_eq!(name_rlc::<Fr>(b""), Fr::zero());
+        assert_eq!(name_rlc::<Fr>(b"ab"), Fr::from(0x6162));
+        assert_ne!(name_rlc::<Fr>(b"ab"), name_rlc::<Fr>(b"ba"));
+        let max_len_name = [0xffu8; IMPORT_WHITELIST_NAME_MAX_LEN];
+        assert_ne!(name_rlc::<Fr>(&max_len_name), name_rlc::<Fr>(&max_len_name[1..]));
+    }
+}
@@ -0,0 +1,113 @@
+use eth_types::Field;
+use serde::{Deserialize, Serialize};
+
+/// Names are packed into a single field element (`name_rlc`), packing is injective only while
+/// names fit into the field, so whitelisted names are limited to this many bytes
+pub const IMPORT_WHITELIST_NAME_MAX_LEN: usize = 31;
+pub const NAME_RLC_BASE: u64 = 256;
+
+pub const WASI_SNAPSHOT_PREVIEW1_MODULE: &str = "wasi_snapshot_preview1";
+pub const WASI_SNAPSHOT_PREVIEW1_FUNCS: &[&str] = &[
+    "args_get",
+    "args_sizes_get",
+    "environ_get",
+    "environ_sizes_get",
+    "clock_res_get",
+    "clock_time_get",
+    "fd_advise",
+    "fd_allocate",
+    "fd_close",
+    "fd_datasync",
+    "fd_fdstat_get",
+    "fd_fdstat_set_flags",
+    "fd_fdstat_set_rights",
+    "fd_filestat_get",
+    "fd_filestat_set_size",
+    "fd_filestat_set_times",
+    "fd_pread",
+    "fd_prestat_get",
+    "fd_prestat_dir_name",
+    "fd_pwrite",
+    "fd_read",
+    "fd_readdir",
+    "fd_renumber",
+    "fd_seek",
+    "fd_sync",
+    "fd_tell",
+    "fd_write",
+    "path_create_directory",
+    "path_filestat_get",
+    "path_filestat_set_times",
+    "path_link",
+    "path_open",
+    "path_readlink",
+    "path_remove_directory",
+    "path_rename",
+    "path_symlink",
+    "path_unlink_file",
+    "poll_oneoff",
+    "proc_exit",
+    "proc_raise",
+    "sched_yield",
+    "random_get",
+    "sock_accept",
+    "sock_recv",
+    "sock_send",
+    "sock_shutdown",
+];
+
+/// `(module, name)` pairs a module is allowed to import
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ImportWhitelist {
+    entries: Vec<(String, String)>,
+}
+
+impl ImportWhitelist {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Functions of the `wasi_snapshot_preview1` ABI
+    pub fn wasi_snapshot_preview1() -> Self {
+        Self::new().with_module(WASI_SNAPSHOT_PREVIEW1_MODULE, WASI_SNAPSHOT_PREVIEW1_FUNCS)
+    }
+
+    /// Panics if a name is longer than [`IMPORT_WHITELIST_NAME_MAX_LEN`]
+    pub fn with_import(mut self, module: &str, name: &str) -> Self {
+        for s in [module, name] {
+            assert!(
+                s.len() <= IMPORT_WHITELIST_NAME_MAX_LEN,
+                "import whitelist name '{}' is longer than {} bytes",
+                s,
+                IMPORT_WHITELIST_NAME_MAX_LEN
+            );
+        }
+        if !self.contains(module.as_bytes(), name.as_bytes()) {
+            self.entries.push((module.to_string(), name.to_string()));
+        }
+        self
+    }
+
+    pub fn with_module(self, module: &str, names: &[&str]) -> Self {
+        names
+            .iter()
+            .fold(self, |whitelist, name| whitelist.with_import(module, name))
+    }
+
+    pub fn contains(&self, module: &[u8], name: &[u8]) -> bool {
+        self.entries
+            .iter()
+            .any(|(m, n)| m.as_bytes() == module && n.as_bytes() == name)
+    }
+
+    pub fn entries(&self) -> &[(String, String)] {
+        &self.entries
+    }
+}
+
+/// Big-endian packing of `bytes` in base [`NAME_RLC_BASE`]
+pub fn name_rlc<F: Field>(bytes: &[u8]) -> F {
+    bytes
+        .iter()
+        .fold(F::zero(), |acc, b| acc * F::from(NAME_RLC_BASE) + F::from(*b as u64))
+}
@@ -0,0 +1,4 @@
+pub mod config;
+#[cfg(any(feature = "test", test))]
+pub mod tests;
+pub mod types;
@@ -0,0 +1,79 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, Expression, TableColumn, VirtualCells},
+};
+
+use eth_types::Field;
+
+use crate::wasm_circuit::tables::opcode::types::opcode_rows;
+
+/// Fixed table of the supported code section instructions as `(class, opcode, immediate kind)`
+/// rows. A single lookup validates an instruction byte together with the format of the bytes
+/// following it.
+#[derive(Debug, Clone)]
+pub struct OpcodeTableConfig<F: Field> {
+    pub class: TableColumn,
+    pub opcode: TableColumn,
+    pub immediate_kind: TableColumn,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> OpcodeTableConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            class: cs.lookup_table_column(),
+            opcode: cs.lookup_table_column(),
+            immediate_kind: cs.lookup_table_column(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Requires the `(class, opcode, immediate kind)` returned by `cond_and_values` to be a row of
+    /// the table where the returned condition is enabled
+    pub fn lookup_opcode(
+        &self,
+        cs: &mut ConstraintSystem<F>,
+        name: &'static str,
+        cond_and_values: impl FnOnce(
+            &mut VirtualCells<'_, F>,
+        ) -> (Expression<F>, [Expression<F>; 3]),
+    ) {
+        cs.lookup(name, |vc| {
+            let (cond, [class, opcode, immediate_kind]) = cond_and_values(vc);
+
+            vec![
+                (cond.clone() * class, self.class),
+                (cond.clone() * opcode, self.opcode),
+                (cond * immediate_kind, self.immediate_kind),
+            ]
+        });
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load opcode table",
+            |mut table| {
+                for (offset, (class, opcode, immediate_kind)) in
+                    opcode_rows().into_iter().enumerate()
+                {
+                    for (column, value) in [
+                        (self.class, class as u64),
+                        (self.opcode, opcode as u64),
+                        (self.immediate_kind, immediate_kind as u64),
+                    ] {
+                        table.assign_cell(
+                            || format!("opcode row {}", offset),
+                            column,
+                            offset,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
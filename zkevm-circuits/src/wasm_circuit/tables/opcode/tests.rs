@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod opcode_tests {
+    use crate::wasm_circuit::{
+        tables::opcode::types::{opcode_rows, ImmediateKind, OpcodeClass},
+        types::{ControlInstruction, NumericInstruction},
+    };
+
+    #[test]
+    pub fn rows_include_zero_row() {
+        assert!(opcode_rows().contains(&(OpcodeClass::Disabled, 0, ImmediateKind::None)));
+    }
+
+    #[test]
+    pub fn opcodes_are_distinct_within_class() {
+        let rows = opcode_rows();
+        let mut keys = rows
+            .iter()
+            .map(|(class, opcode, _)| (*class as u8, *opcode))
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), rows.len());
+        assert!(rows.contains(&(
+            OpcodeClass::Numeric,
+            NumericInstruction::I32Const as u8,
+            ImmediateKind::Leb
+        )));
+        assert!(rows.contains(&(
+            OpcodeClass::Control,
+            ControlInstruction::Loop as u8,
+            ImmediateKind::Blocktype
+        )));
+    }
+}
@@ -0,0 +1,89 @@
+use halo2_proofs::{arithmetic::FieldExt, plonk::Expression};
+use strum_macros::EnumIter;
+
+use gadgets::util::Expr;
+
+use crate::wasm_circuit::types::{
+    CONTROL_INSTRUCTION_BLOCK, CONTROL_INSTRUCTION_WITHOUT_ARGS, CONTROL_INSTRUCTION_WITH_LEB_ARG,
+    NUMERIC_INSTRUCTIONS_WITHOUT_ARGS, NUMERIC_INSTRUCTION_WITH_LEB_ARG,
+    PARAMETRIC_INSTRUCTIONS_WITHOUT_ARGS, VARIABLE_INSTRUCTION_WITH_LEB_ARG,
+};
+
+/// Instruction classes of the code section, every class has its own opcode flag column
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq)]
+pub enum OpcodeClass {
+    /// disabled lookup rows
+    Disabled = 0,
+    Numeric,
+    Variable,
+    Control,
+    Parametric,
+}
+
+impl<F: FieldExt> Expr<F> for OpcodeClass {
+    #[inline]
+    fn expr(&self) -> Expression<F> {
+        Expression::Constant(F::from(*self as u64))
+    }
+}
+
+/// Format of the bytes following an opcode
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq)]
+pub enum ImmediateKind {
+    None = 0,
+    /// single leb128 argument
+    Leb,
+    /// blocktype delimiter
+    Blocktype,
+}
+
+impl<F: FieldExt> Expr<F> for ImmediateKind {
+    #[inline]
+    fn expr(&self) -> Expression<F> {
+        Expression::Constant(F::from(*self as u64))
+    }
+}
+
+/// All `(class, opcode, immediate kind)` rows of the table
+pub fn opcode_rows() -> Vec<(OpcodeClass, u8, ImmediateKind)> {
+    let mut rows = vec![(OpcodeClass::Disabled, 0, ImmediateKind::None)];
+    let mut push = |class: OpcodeClass, opcodes: Vec<u8>, kind: ImmediateKind| {
+        rows.extend(opcodes.into_iter().map(|opcode| (class, opcode, kind)))
+    };
+    push(
+        OpcodeClass::Numeric,
+        NUMERIC_INSTRUCTIONS_WITHOUT_ARGS.iter().map(|&v| v as u8).collect(),
+        ImmediateKind::None,
+    );
+    push(
+        OpcodeClass::Numeric,
+        NUMERIC_INSTRUCTION_WITH_LEB_ARG.iter().map(|&v| v as u8).collect(),
+        ImmediateKind::Leb,
+    );
+    push(
+        OpcodeClass::Variable,
+        VARIABLE_INSTRUCTION_WITH_LEB_ARG.iter().map(|&v| v as u8).collect(),
+        ImmediateKind::Leb,
+    );
+    push(
+        OpcodeClass::Control,
+        CONTROL_INSTRUCTION_WITHOUT_ARGS.iter().map(|&v| v as u8).collect(),
+        ImmediateKind::None,
+    );
+    push(
+        OpcodeClass::Control,
+        CONTROL_INSTRUCTION_WITH_LEB_ARG.iter().map(|&v| v as u8).collect(),
+        ImmediateKind::Leb,
+    );
+    push(
+        OpcodeClass::Control,
+        CONTROL_INSTRUCTION_BLOCK.iter().map(|&v| v as u8).collect(),
+        ImmediateKind::Blocktype,
+    );
+    push(
+        OpcodeClass::Parametric,
+        PARAMETRIC_INSTRUCTIONS_WITHOUT_ARGS.iter().map(|&v| v as u8).collect(),
+        ImmediateKind::None,
+    );
+    rows
+}
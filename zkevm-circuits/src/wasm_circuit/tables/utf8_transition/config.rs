@@ -0,0 +1,57 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+
+use eth_types::Field;
+
+use crate::wasm_circuit::tables::utf8_transition::types::utf8_transitions;
+
+/// Fixed table of allowed `(state_prev, byte, state)` transitions of the UTF-8 automaton. The
+/// `(Accept, 0, Accept)` transition makes all-zero disabled lookup rows pass.
+#[derive(Debug, Clone)]
+pub struct Utf8TransitionTableConfig<F: Field> {
+    pub state_prev: TableColumn,
+    pub byte: TableColumn,
+    pub state: TableColumn,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Utf8TransitionTableConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            state_prev: cs.lookup_table_column(),
+            byte: cs.lookup_table_column(),
+            state: cs.lookup_table_column(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load utf8 transition table",
+            |mut table| {
+                for (offset, (state_prev, byte, state)) in
+                    utf8_transitions().into_iter().enumerate()
+                {
+                    for (column, value) in [
+                        (self.state_prev, state_prev as u64),
+                        (self.byte, byte as u64),
+                        (self.state, state as u64),
+                    ] {
+                        table.assign_cell(
+                            || format!("utf8 transition row {}", offset),
+                            column,
+                            offset,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
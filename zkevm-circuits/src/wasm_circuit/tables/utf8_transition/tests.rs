@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod utf8_transition_tests {
+    use crate::wasm_circuit::tables::utf8_transition::types::{
+        utf8_states, utf8_transitions, Utf8State,
+    };
+
+    #[test]
+    pub fn automaton_matches_std_on_short_sequences() {
+        for b0 in 0..=u8::MAX {
+            assert_eq!(utf8_states(&[b0]).is_ok(), std::str::from_utf8(&[b0]).is_ok());
+            for b1 in 0..=u8::MAX {
+                let bytes = [b0, b1];
+                assert_eq!(utf8_states(&bytes).is_ok(), std::str::from_utf8(&bytes).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    pub fn automaton_matches_std_on_samples() {
+        let samples: [&[u8]; 10] = [
+            "wasi_snapshot_preview1".as_bytes(),
+            "имя".as_bytes(),
+            "名前".as_bytes(),
+            "🦀".as_bytes(),
+            b"\xe0\x80\xaf",
+            b"\xed\xa0\x80",
+            b"\xf0\x80\x80\xaf",
+            b"\xf4\x90\x80\x80",
+            b"\xf0\x9f\xa6",
+            b"ok\xc0\xaf",
+        ];
+        for bytes in samples {
+            assert_eq!(utf8_states(bytes).is_ok(), std::str::from_utf8(bytes).is_ok());
+        }
+        assert_eq!(utf8_states(b"\xf0\x9f\xa6"), Err(2));
+        assert_eq!(utf8_states(b"ok\xc0\xaf"), Err(2));
+    }
+
+    #[test]
+    pub fn transitions_include_zero_row() {
+        let transitions = utf8_transitions();
+        assert!(transitions.contains(&(Utf8State::Accept, 0, Utf8State::Accept)));
+        assert_eq!(
+            transitions.len(),
+            128 + 30 + 1 + 14 + 1 + 1 + 3 + 1 + 64 * 3 + 32 + 32 + 48 + 16
+        );
+    }
+}
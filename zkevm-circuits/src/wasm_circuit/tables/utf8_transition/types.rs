@@ -0,0 +1,68 @@
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// States of the UTF-8 validation automaton (RFC 3629), `Accept` is both the start and the only
+/// final state
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter)]
+pub enum Utf8State {
+    Accept = 0,
+    /// one continuation byte `80..=BF` left
+    Cont1 = 1,
+    /// two continuation bytes `80..=BF` left
+    Cont2 = 2,
+    /// three continuation bytes `80..=BF` left
+    Cont3 = 3,
+    /// after `E0`, next byte is `A0..=BF` (no overlong 3-byte encodings)
+    AfterE0 = 4,
+    /// after `ED`, next byte is `80..=9F` (no surrogates)
+    AfterEd = 5,
+    /// after `F0`, next byte is `90..=BF` (no overlong 4-byte encodings)
+    AfterF0 = 6,
+    /// after `F4`, next byte is `80..=8F` (codepoints up to `U+10FFFF`)
+    AfterF4 = 7,
+}
+
+impl Utf8State {
+    /// State after `byte`, `None` if the byte is not allowed in this state
+    pub fn next(self, byte: u8) -> Option<Self> {
+        use Utf8State::*;
+        match (self, byte) {
+            (Accept, 0x00..=0x7f) => Some(Accept),
+            (Accept, 0xc2..=0xdf) => Some(Cont1),
+            (Accept, 0xe0) => Some(AfterE0),
+            (Accept, 0xe1..=0xec | 0xee..=0xef) => Some(Cont2),
+            (Accept, 0xed) => Some(AfterEd),
+            (Accept, 0xf0) => Some(AfterF0),
+            (Accept, 0xf1..=0xf3) => Some(Cont3),
+            (Accept, 0xf4) => Some(AfterF4),
+            (Cont1, 0x80..=0xbf) => Some(Accept),
+            (Cont2, 0x80..=0xbf) | (AfterE0, 0xa0..=0xbf) | (AfterEd, 0x80..=0x9f) => Some(Cont1),
+            (Cont3, 0x80..=0xbf) | (AfterF0, 0x90..=0xbf) | (AfterF4, 0x80..=0x8f) => Some(Cont2),
+            _ => None,
+        }
+    }
+}
+
+/// All allowed `(state, byte, next_state)` transitions
+pub fn utf8_transitions() -> Vec<(Utf8State, u8, Utf8State)> {
+    Utf8State::iter()
+        .flat_map(|state| {
+            (0..=u8::MAX).filter_map(move |byte| state.next(byte).map(|next| (state, byte, next)))
+        })
+        .collect()
+}
+
+/// States after every byte of `bytes`. Fails with the index of the first byte which makes
+/// `bytes` invalid UTF-8 (the last byte if `bytes` ends in the middle of a sequence).
+pub fn utf8_states(bytes: &[u8]) -> Result<Vec<Utf8State>, usize> {
+    let mut state = Utf8State::Accept;
+    let mut states = Vec::with_capacity(bytes.len());
+    for (index, &byte) in bytes.iter().enumerate() {
+        state = state.next(byte).ok_or(index)?;
+        states.push(state);
+    }
+    if state != Utf8State::Accept {
+        return Err(bytes.len() - 1);
+    }
+    Ok(states)
+}
@@ -1,28 +1,203 @@
-use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+use std::{cell::RefCell, marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
-    circuit::{Layouter, SimpleFloorPlanner},
+    circuit::{Layouter, SimpleFloorPlanner, Value},
     plonk::{Circuit, ConstraintSystem, Error},
 };
 use log::debug;
 
 use eth_types::{Field, ToWord};
 
-use crate::wasm_circuit::{
-    bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
-    circuit::{WasmChip, WasmConfig},
-    types::SharedState,
+use crate::{
+    table::{BytecodeTable, UnifiedBytecodeTable},
+    util::Challenges,
+    wasm_circuit::{
+        builder::{WasmCircuitBuilder, WasmCircuitOptions},
+        bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+        circuit::{WasmChip, WasmConfig},
+        common::WasmFuncCountAwareChip,
+        tables::import_whitelist::types::ImportWhitelist,
+        types::{SharedStateRef, WasmModuleOutput, WasmSection, WasmSectionAssignment},
+    },
+    witness::Bytecode,
 };
 
+/// Options of a [`TestCircuitWithOptions`], `Circuit::configure` takes no parameters
+trait TestCircuitOptions: Default {
+    fn options() -> WasmCircuitOptions {
+        WasmCircuitOptions::default()
+    }
+
+    fn error_processing_enabled() -> bool {
+        true
+    }
+
+    fn wb_table<F: Field>(cs: &mut ConstraintSystem<F>) -> WasmBytecodeTable {
+        WasmBytecodeTable::construct(cs, true)
+    }
+}
+
 #[derive(Default)]
-struct TestCircuitWithErrorProcessing<F> {
+struct ErrorProcessingDisabled;
+
+impl TestCircuitOptions for ErrorProcessingDisabled {
+    fn error_processing_enabled() -> bool {
+        false
+    }
+}
+
+#[derive(Default)]
+struct ErrorProcessingEnabled;
+
+impl TestCircuitOptions for ErrorProcessingEnabled {}
+
+pub fn minimal_circuit_options() -> WasmCircuitOptions {
+    WasmCircuitBuilder::new()
+        .without_section(WasmSection::Table)
+        .without_section(WasmSection::Global)
+        .without_section(WasmSection::Start)
+        .without_section(WasmSection::Element)
+        .build()
+}
+
+#[derive(Default)]
+struct MinimalOptions;
+
+impl TestCircuitOptions for MinimalOptions {
+    fn options() -> WasmCircuitOptions {
+        minimal_circuit_options()
+    }
+}
+
+pub fn import_whitelist_circuit_options() -> WasmCircuitOptions {
+    WasmCircuitBuilder::new()
+        .with_import_whitelist(
+            ImportWhitelist::wasi_snapshot_preview1()
+                .with_module("env", &["_evm_address", "_evm_balance"]),
+        )
+        .build()
+}
+
+#[derive(Default)]
+struct ImportWhitelisted;
+
+impl TestCircuitOptions for ImportWhitelisted {
+    fn options() -> WasmCircuitOptions {
+        import_whitelist_circuit_options()
+    }
+}
+
+#[derive(Default)]
+struct ExportedMutableGlobalsRejected;
+
+impl TestCircuitOptions for ExportedMutableGlobalsRejected {
+    fn options() -> WasmCircuitOptions {
+        WasmCircuitBuilder::new()
+            .with_exported_mutable_globals_rejected()
+            .build()
+    }
+}
+
+#[derive(Default)]
+struct SimdInstructionsSkipped;
+
+impl TestCircuitOptions for SimdInstructionsSkipped {
+    fn options() -> WasmCircuitOptions {
+        WasmCircuitBuilder::new()
+            .with_simd_instructions_skipped()
+            .build()
+    }
+}
+
+#[derive(Default)]
+struct SmallDataLimits;
+
+impl TestCircuitOptions for SmallDataLimits {
+    fn options() -> WasmCircuitOptions {
+        WasmCircuitBuilder::new()
+            .with_max_data_segment_size(16)
+            .with_max_data_size(24)
+            .build()
+    }
+}
+
+#[derive(Default)]
+struct PoseidonCodeHash;
+
+impl TestCircuitOptions for PoseidonCodeHash {
+    fn error_processing_enabled() -> bool {
+        false
+    }
+
+    fn wb_table<F: Field>(cs: &mut ConstraintSystem<F>) -> WasmBytecodeTable {
+        WasmBytecodeTable::construct_with_poseidon_code_hash(cs, true)
+    }
+}
+
+/// Assigns `wbs` one after another and then the `(row, value)` func_count overrides, the returned
+/// chip holds what was recorded during the assignment
+fn assign_bytecodes<F: Field>(
+    config: WasmConfig<F>,
+    layouter: &mut impl Layouter<F>,
+    wbs: &[WasmBytecode],
+    wb_offset: usize,
+    assign_delta_base: usize,
+    func_count_overrides: &[(usize, u64)],
+) -> Result<WasmChip<F>, Error> {
+    let mut wasm_chip = WasmChip::construct(config);
+
+    wasm_chip.load_once(layouter).unwrap();
+    layouter.assign_region(
+        || "wasm_chip region",
+        |mut region| {
+            wasm_chip.config.shared_state.write().reset();
+            wasm_chip.section_assignments_reset();
+            wasm_chip.module_outputs_reset();
+            let mut cursor = wasm_chip.region_cursor(assign_delta_base);
+            for wb in wbs {
+                wasm_chip
+                    .assign_next(&mut region, wb, wb_offset, &mut cursor)
+                    .unwrap();
+                debug!(
+                    "RESULT error_code {}",
+                    wasm_chip.config.shared_state.read().error_code
+                );
+            }
+            for &(row, func_count) in func_count_overrides {
+                region.assign_advice(
+                    || format!("override func_count at {}", row),
+                    wasm_chip.func_count_col(),
+                    row,
+                    || Value::known(F::from(func_count)),
+                )?;
+            }
+
+            Ok(())
+        },
+    )?;
+
+    Ok(wasm_chip)
+}
+
+#[derive(Default)]
+struct TestCircuitWithOptions<F, O> {
     wbs: Vec<WasmBytecode>,
     wb_offset: usize,
     assign_delta_base: usize,
-    _marker: PhantomData<F>,
+    /// `(row, value)` func_count cells reassigned after the bytecodes to tamper the witness
+    func_count_overrides: Vec<(usize, u64)>,
+    /// recorded by the chip during synthesis
+    section_assignments: RefCell<Vec<WasmSectionAssignment>>,
+    /// recorded by the chip during synthesis
+    module_outputs: RefCell<Vec<WasmModuleOutput>>,
+    _marker: PhantomData<(F, O)>,
 }
 
-impl<F: Field> Circuit<F> for TestCircuitWithErrorProcessing<F> {
+type TestCircuit<F> = TestCircuitWithOptions<F, ErrorProcessingDisabled>;
+
+type TestCircuitWithErrorProcessing<F> = TestCircuitWithOptions<F, ErrorProcessingEnabled>;
+
+impl<F: Field, O: TestCircuitOptions> Circuit<F> for TestCircuitWithOptions<F, O> {
     type Config = WasmConfig<F>;
     type FloorPlanner = SimpleFloorPlanner;
 
@@ -31,12 +206,10 @@ impl<F: Field> Circuit<F> for TestCircuitWithErrorProcessing<F> {
     }
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-        let mut shared_state = Rc::new(RefCell::new(SharedState::default()));
-        shared_state.borrow_mut().error_processing_enabled = true;
-        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, true));
-        let config = WasmChip::<F>::configure(cs, wb_table, shared_state);
-
-        config
+        let shared_state = SharedStateRef::default();
+        shared_state.write().error_processing_enabled = O::error_processing_enabled();
+        let wb_table = Arc::new(O::wb_table(cs));
+        WasmChip::<F>::configure_with_options(cs, wb_table, shared_state, O::options())
     }
 
     fn synthesize(
@@ -44,44 +217,33 @@ impl<F: Field> Circuit<F> for TestCircuitWithErrorProcessing<F> {
         config: Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
-        let mut wasm_chip = WasmChip::construct(config);
-
-        wasm_chip.load_once(&mut layouter).unwrap();
-
-        layouter.assign_region(
-            || "wasm_chip region",
-            |mut region| {
-                wasm_chip.config.shared_state.borrow_mut().reset();
-                let mut assign_delta = self.assign_delta_base;
-                for wb in &self.wbs {
-                    wasm_chip.load(&mut region, wb, assign_delta).unwrap();
-                    assign_delta = wasm_chip
-                        .assign_auto(&mut region, wb, self.wb_offset, assign_delta)
-                        .unwrap();
-                    // debug!(
-                    //     "RESULT error_code {}",
-                    //     wasm_chip.config.shared_state.borrow().error_code
-                    // );
-                }
-
-                Ok(())
-            },
+        let wasm_chip = assign_bytecodes(
+            config,
+            &mut layouter,
+            &self.wbs,
+            self.wb_offset,
+            self.assign_delta_base,
+            &self.func_count_overrides,
         )?;
+        *self.section_assignments.borrow_mut() = wasm_chip.section_assignments.clone();
+        *self.module_outputs.borrow_mut() = wasm_chip.module_outputs.clone();
 
         Ok(())
     }
 }
 
+/// Evm and wasm bytecodes anchored in one unified bytecode table
 #[derive(Default)]
-struct TestCircuit<F> {
+struct TestCircuitWithUnifiedBytecodeTable<F> {
     wbs: Vec<WasmBytecode>,
-    wb_offset: usize,
-    assign_delta_base: usize,
+    evm_bytecodes: Vec<Bytecode>,
+    /// wasm bytecodes loaded into the unified table
+    anchored_wbs: Vec<WasmBytecode>,
     _marker: PhantomData<F>,
 }
 
-impl<F: Field> Circuit<F> for TestCircuit<F> {
-    type Config = WasmConfig<F>;
+impl<F: Field> Circuit<F> for TestCircuitWithUnifiedBytecodeTable<F> {
+    type Config = (WasmConfig<F>, BytecodeTable, UnifiedBytecodeTable, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
@@ -89,40 +251,37 @@ impl<F: Field> Circuit<F> for TestCircuit<F> {
     }
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
-        let shared_state = Rc::new(RefCell::new(SharedState::default()));
-        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, true));
-        let config = WasmChip::<F>::configure(cs, wb_table, shared_state);
+        let shared_state = SharedStateRef::default();
+        let wb_table = Arc::new(WasmBytecodeTable::construct(cs, true));
+        let bytecode_table = BytecodeTable::construct(cs);
+        let unified_bytecode_table = UnifiedBytecodeTable::construct(cs);
+        let challenges = Challenges::construct(cs);
+        let wasm_config = WasmChip::<F>::configure(cs, wb_table, shared_state);
+        WasmChip::configure_unified_bytecode_lookup(cs, &wasm_config, &unified_bytecode_table);
+        unified_bytecode_table.configure_evm_lookup(cs, &bytecode_table);
 
-        config
+        (
+            wasm_config,
+            bytecode_table,
+            unified_bytecode_table,
+            challenges,
+        )
     }
 
     fn synthesize(
         &self,
-        config: Self::Config,
+        (wasm_config, bytecode_table, unified_bytecode_table, challenges): Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
-        let mut wasm_chip = WasmChip::construct(config);
-
-        wasm_chip.load_once(&mut layouter).unwrap();
-        layouter.assign_region(
-            || "wasm_chip region",
-            |mut region| {
-                wasm_chip.config.shared_state.borrow_mut().reset();
-                let mut assign_delta = self.assign_delta_base;
-                for wb in &self.wbs {
-                    wasm_chip.load(&mut region, wb, assign_delta).unwrap();
-                    assign_delta = wasm_chip
-                        .assign_auto(&mut region, wb, self.wb_offset, assign_delta)
-                        .unwrap();
-                    debug!(
-                        "RESULT error_code {}",
-                        wasm_chip.config.shared_state.borrow().error_code
-                    );
-                }
-
-                Ok(())
-            },
+        let challenges = challenges.values(&layouter);
+        bytecode_table.dev_load(&mut layouter, &self.evm_bytecodes, &challenges)?;
+        unified_bytecode_table.dev_load(
+            &mut layouter,
+            &self.evm_bytecodes,
+            &self.anchored_wbs,
+            &challenges,
         )?;
+        assign_bytecodes(wasm_config, &mut layouter, &self.wbs, 0, 0, &[])?;
 
         Ok(())
     }
@@ -131,23 +290,48 @@ impl<F: Field> Circuit<F> for TestCircuit<F> {
 #[cfg(test)]
 mod wasm_circuit_tests {
     use ethers_core::k256::pkcs8::der::Encode;
-    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use halo2_proofs::{
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+        plonk::ConstraintSystem,
+    };
+    use std::{marker::PhantomData, sync::Arc};
     use log::debug;
-    use rand::{random, thread_rng, Rng};
+    use rand::Rng;
     use wabt::wat2wasm;
 
-    use eth_types::Field;
+    use bus_mapping::state_db::CodeDB;
+    use eth_types::{Field, ToWord};
 
-    use crate::wasm_circuit::{
-        bytecode::bytecode::WasmBytecode,
-        consts::{
-            WASM_MAGIC_PREFIX_END_INDEX, WASM_MAGIC_PREFIX_LEN, WASM_MAGIC_PREFIX_START_INDEX,
-            WASM_VERSION_PREFIX_END_INDEX, WASM_VERSION_PREFIX_LEN,
-            WASM_VERSION_PREFIX_START_INDEX,
+    use crate::{
+        test_rng::test_rng,
+        util::{log2_ceil, SubCircuit},
+        wasm_circuit::{
+            bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+            circuit::{WasmChip, WasmCircuit},
+            consts::{
+                MAX_BLOCK_LEVEL, MAX_FUNC_LOCALS, MAX_FUNC_PARAMS, MAX_IMPORT_MOD_NAME_LEN,
+                MAX_IMPORT_NAME_LEN,
+                WASM_MAGIC_PREFIX_END_INDEX,
+                WASM_MAGIC_PREFIX_LEN, WASM_MAGIC_PREFIX_START_INDEX, WASM_MODULE_OUTPUT_LEN,
+                WASM_SECTIONS_START_INDEX, WASM_VERSION_PREFIX_END_INDEX, WASM_VERSION_PREFIX_LEN,
+                WASM_VERSION_PREFIX_START_INDEX,
+            },
+            error::Error as WasmError,
+            tests::{
+                import_whitelist_circuit_options, minimal_circuit_options,
+                ExportedMutableGlobalsRejected, ImportWhitelisted, MinimalOptions,
+                PoseidonCodeHash, SimdInstructionsSkipped, SmallDataLimits, TestCircuit,
+                TestCircuitOptions, TestCircuitWithErrorProcessing, TestCircuitWithOptions,
+                TestCircuitWithUnifiedBytecodeTable,
+            },
+            tests_helpers::{custom_section, mutate_byte, producers_payload},
+            types::{
+                ErrorCode, ErrorReason, RegionCursor, SharedStateRef, WasmAssignmentPlan,
+                WasmModuleOutput, WasmSection,
+            },
         },
-        tests::{TestCircuit, TestCircuitWithErrorProcessing},
-        tests_helpers::mutate_byte,
-        types::WasmSection,
+        witness::{Block, Bytecode},
     };
 
     fn test<'a, F: Field>(test_circuit: &TestCircuit<F>, is_ok: bool, k: u32) {
@@ -159,6 +343,23 @@ mod wasm_circuit_tests {
         }
     }
 
+    /// Constraints `test_circuit` fails, as `Constraint n ('name') in gate m ('gate name')`
+    fn failed_constraints<F: Field>(test_circuit: &TestCircuit<F>, k: u32) -> Vec<String> {
+        let prover = MockProver::run(k, test_circuit, vec![]).unwrap();
+        prover
+            .verify()
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|failure| match failure {
+                VerifyFailure::ConstraintNotSatisfied { constraint, .. } => {
+                    Some(constraint.to_string())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     fn debug_wb(wb: &WasmBytecode) {
         debug!("wb.len: {}", wb.bytes.len());
         debug!("wb.len hex: {:x?}", wb.bytes.len());
@@ -203,7 +404,7 @@ mod wasm_circuit_tests {
         debug_wb(&wb);
         let circuit = TestCircuit::<Fr> {
             wbs: vec![wb],
-            assign_delta_base: thread_rng().gen_range(5..5000),
+            assign_delta_base: test_rng().gen_range(5..5000),
             ..Default::default()
         };
         test(&circuit, true, 13);
@@ -237,6 +438,49 @@ mod wasm_circuit_tests {
         test(&circuit, true, 9);
     }
 
+    #[test]
+    pub fn files_assign_from_several_threads_ok() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SharedStateRef>();
+        assert_send_sync::<WasmChip<Fr>>();
+
+        let circuits = ["cc1", "cc2", "cc3"].map(|name| {
+            let path = format!("./test_files/{}.wat", name);
+            let data: Vec<u8> = std::fs::read(path).unwrap();
+            let bytes = wat2wasm(data).unwrap();
+            TestCircuit::<Fr> {
+                wbs: vec![WasmBytecode::new(bytes)],
+                ..Default::default()
+            }
+        });
+        std::thread::scope(|scope| {
+            for circuit in circuits {
+                scope.spawn(move || test(&circuit, true, 9));
+            }
+        });
+    }
+
+    #[test]
+    pub fn file1_assignment_plan_serde_roundtrip_ok() {
+        let path = "./test_files/cc1.wat";
+        let data: Vec<u8> = std::fs::read(path).unwrap();
+        let bytes = wat2wasm(data).unwrap();
+        let wb = WasmBytecode::new(bytes);
+        let plan = WasmAssignmentPlan::new(vec![wb], 0);
+        assert!(!plan.section_assignments.is_empty());
+
+        let plan_json = serde_json::to_string(&plan).unwrap();
+        let plan_restored: WasmAssignmentPlan = serde_json::from_str(&plan_json).unwrap();
+        assert_eq!(plan_restored, plan);
+
+        let circuit = TestCircuit::<Fr> {
+            wbs: plan_restored.wbs,
+            assign_delta_base: plan_restored.assign_delta_base,
+            ..Default::default()
+        };
+        test(&circuit, true, 9);
+    }
+
     // #[ignore]
     #[test]
     pub fn multiple_bytecodes_assignment_ok() {
@@ -260,6 +504,122 @@ mod wasm_circuit_tests {
         test(&circuit, true, 13);
     }
 
+    #[test]
+    pub fn multiple_bytecodes_planned_assignment_matches_circuit() {
+        let wbs = ["./test_files/cc1.wat", "./test_files/cc2.wat"]
+            .iter()
+            .map(|path| WasmBytecode::new(wat2wasm(std::fs::read(path).unwrap()).unwrap()))
+            .collect::<Vec<_>>();
+        let assign_delta_base = 7;
+        let circuit = TestCircuit::<Fr> {
+            wbs: wbs.clone(),
+            assign_delta_base,
+            ..Default::default()
+        };
+        test(&circuit, true, 13);
+
+        let plan = WasmAssignmentPlan::new(wbs, assign_delta_base);
+        assert_eq!(plan.section_assignments, *circuit.section_assignments.borrow());
+    }
+
+    #[test]
+    pub fn multiple_modules_public_outputs_ok() {
+        let paths = [
+            "./test_files/cc1.wat",
+            "./test_files/cc2.wat",
+            "./test_files/cc3.wat",
+        ];
+        let mut wbs = vec![];
+        for path in paths {
+            let data: Vec<u8> = std::fs::read(path).unwrap();
+            wbs.push(WasmBytecode::new(wat2wasm(data).unwrap()));
+        }
+        let circuit = WasmCircuit::<Fr>::new(wbs.clone());
+        let outputs = circuit.compute_module_outputs(13).unwrap();
+        assert_eq!(outputs.len(), wbs.len());
+        for (idx, (output, wb)) in outputs.iter().zip(wbs.iter()).enumerate() {
+            assert_eq!(output.bytecode_number, idx as u64 + 1);
+            assert_eq!(output.code_hash, wb.code_hash);
+            assert_eq!(output.error_code, 0);
+        }
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+        let prover = MockProver::run(13, &circuit, instance).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn failed_module_does_not_contaminate_next_module_outputs() {
+        let mut wbs = vec![];
+        for path in ["./test_files/cc1.wat", "./test_files/cc1.wat", "./test_files/cc2.wat"] {
+            let data: Vec<u8> = std::fs::read(path).unwrap();
+            wbs.push(WasmBytecode::new(wat2wasm(data).unwrap()));
+        }
+        // change section ID of the second module to some unknown
+        wbs[1].bytes[8] = test_rng().gen_range((WasmSection::DataCount as u8 + 1)..255);
+
+        let circuit = WasmCircuit::<Fr>::new(wbs);
+        let outputs = circuit.compute_module_outputs(13).unwrap();
+        assert_eq!(module_failures(&outputs), vec![0, 1, 0]);
+        let bytecode_numbers = outputs.iter().map(|o| o.bytecode_number).collect::<Vec<_>>();
+        assert_eq!(bytecode_numbers, vec![1, 2, 3]);
+
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+        let prover = MockProver::run(13, &circuit, instance).unwrap();
+        prover.assert_satisfied();
+
+        let mut outputs_contaminated = outputs.clone();
+        outputs_contaminated[2].error_code = 1;
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs_contaminated);
+        let prover = MockProver::run(13, &circuit, instance).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    pub fn sub_circuit_from_block_ok() {
+        let mut block = Block::<Fr>::default();
+        let wasm_bytes = wat2wasm(std::fs::read("./test_files/cc1.wat").unwrap()).unwrap();
+        let evm_bytes = vec![0x5a, 0x00];
+        for bytes in [wasm_bytes, evm_bytes] {
+            let hash = CodeDB::hash(&bytes).to_word();
+            block.bytecodes.insert(hash, Bytecode { hash, bytes });
+        }
+
+        // evm bytecodes are left to the bytecode circuit
+        let circuit = <WasmCircuit<Fr> as SubCircuit<Fr>>::new_from_block(&block);
+        assert_eq!(circuit.wbs.len(), 1);
+        let instance = SubCircuit::instance(&circuit);
+        assert_eq!(instance[0].len(), WASM_MODULE_OUTPUT_LEN);
+
+        let (_, rows) = WasmCircuit::<Fr>::min_num_rows_block(&block);
+        let k = log2_ceil(WasmCircuit::<Fr>::unusable_rows() + rows);
+        let prover = MockProver::run(k, &circuit, instance).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn module_outputs_expose_bytecode_number() {
+        let mut wbs = vec![];
+        for path in ["./test_files/cc1.wat", "./test_files/cc2.wat"] {
+            let data: Vec<u8> = std::fs::read(path).unwrap();
+            wbs.push(WasmBytecode::new(wat2wasm(data).unwrap()));
+        }
+
+        let circuit = WasmCircuit::<Fr>::new(wbs);
+        let outputs = circuit.compute_module_outputs(13).unwrap();
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+        assert_eq!(instance[0][0], Fr::from(1));
+        assert_eq!(instance[0][WASM_MODULE_OUTPUT_LEN], Fr::from(2));
+        let prover = MockProver::run(13, &circuit, instance).unwrap();
+        prover.assert_satisfied();
+
+        let mut outputs_swapped = outputs.clone();
+        outputs_swapped[0].bytecode_number = 2;
+        outputs_swapped[1].bytecode_number = 1;
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs_swapped);
+        let prover = MockProver::run(13, &circuit, instance).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
     #[test]
     pub fn invalid_bytecode_parse_error_ok() {
         let paths = [
@@ -271,7 +631,7 @@ mod wasm_circuit_tests {
             let data: Vec<u8> = std::fs::read(path).unwrap();
             let bytes = wat2wasm(data).unwrap();
             let mut wb = WasmBytecode::new(bytes);
-            let i: usize = random::<usize>() % WASM_MAGIC_PREFIX_LEN;
+            let i: usize = test_rng().gen::<usize>() % WASM_MAGIC_PREFIX_LEN;
             mutate_byte(&mut wb.bytes[i]);
             let circuit = TestCircuitWithErrorProcessing::<Fr> {
                 wbs: vec![wb],
@@ -292,7 +652,7 @@ mod wasm_circuit_tests {
             let data: Vec<u8> = std::fs::read(path).unwrap();
             let bytes = wat2wasm(data).unwrap();
             let mut wb = WasmBytecode::new(bytes);
-            let i: usize = random::<usize>() % WASM_MAGIC_PREFIX_LEN;
+            let i: usize = test_rng().gen::<usize>() % WASM_MAGIC_PREFIX_LEN;
             mutate_byte(&mut wb.bytes[i]);
             let circuit = TestCircuitWithErrorProcessing::<Fr> {
                 wbs: vec![wb],
@@ -314,8 +674,8 @@ mod wasm_circuit_tests {
             let data: Vec<u8> = std::fs::read(path).unwrap();
             let bytes = wat2wasm(data).unwrap();
             let mut wb = WasmBytecode::new(bytes);
-            let i: usize =
-                WASM_VERSION_PREFIX_START_INDEX + random::<usize>() % WASM_VERSION_PREFIX_LEN;
+            let i: usize = WASM_VERSION_PREFIX_START_INDEX
+                + test_rng().gen::<usize>() % WASM_VERSION_PREFIX_LEN;
             mutate_byte(&mut wb.bytes[i]);
             let circuit = TestCircuitWithErrorProcessing::<Fr> {
                 wbs: vec![wb],
@@ -347,7 +707,7 @@ mod wasm_circuit_tests {
 
         // mutate some data
         let idx: usize =
-            thread_rng().gen_range(WASM_MAGIC_PREFIX_START_INDEX..=WASM_MAGIC_PREFIX_END_INDEX);
+            test_rng().gen_range(WASM_MAGIC_PREFIX_START_INDEX..=WASM_MAGIC_PREFIX_END_INDEX);
         mutate_byte(&mut wb.bytes[idx]);
 
         debug!("wb (modified): {:x?}", wb.bytes);
@@ -368,7 +728,7 @@ mod wasm_circuit_tests {
 
         // mutate some data
         let idx: usize =
-            thread_rng().gen_range(WASM_VERSION_PREFIX_START_INDEX..=WASM_VERSION_PREFIX_END_INDEX);
+            test_rng().gen_range(WASM_VERSION_PREFIX_START_INDEX..=WASM_VERSION_PREFIX_END_INDEX);
         mutate_byte(&mut wb.bytes[idx]);
 
         debug!("wb (modified): {:x?}", wb.bytes);
@@ -388,7 +748,7 @@ mod wasm_circuit_tests {
         debug_wb(&wb);
 
         // change section ID to some unknown
-        wb.bytes[8] = thread_rng().gen_range((WasmSection::DataCount as u8 + 1)..255);
+        wb.bytes[8] = test_rng().gen_range((WasmSection::DataCount as u8 + 1)..255);
 
         debug!("wb (modified): {:x?}", wb.bytes);
         let circuit = TestCircuitWithErrorProcessing::<Fr> {
@@ -397,4 +757,682 @@ mod wasm_circuit_tests {
         };
         test_with_error_processing(&circuit, true, 9);
     }
+
+    #[test]
+    pub fn file1_minimal_options_ok() {
+        let path = "./test_files/cc1.wat";
+        let data: Vec<u8> = std::fs::read(path).unwrap();
+        let bytes = wat2wasm(data).unwrap();
+        let wb = WasmBytecode::new(bytes);
+        let circuit = TestCircuitWithOptions::<Fr, MinimalOptions> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn file2_minimal_options_disabled_section_parse_error_ok() {
+        let path = "./test_files/cc2.wat";
+        let data: Vec<u8> = std::fs::read(path).unwrap();
+        let bytes = wat2wasm(data).unwrap();
+        let wb = WasmBytecode::new(bytes);
+        let circuit = TestCircuitWithOptions::<Fr, MinimalOptions> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        // cc2 has a table section, which the minimal options don't support
+        let module_outputs = circuit.module_outputs.borrow();
+        assert_eq!(module_outputs.len(), 1);
+        assert_eq!(module_outputs[0].error_code, ErrorCode::InvalidEnumValue as u64);
+        assert_eq!(
+            module_outputs[0].error_reason.map(|r| r.error_code),
+            Some(ErrorCode::InvalidEnumValue),
+        );
+    }
+
+    #[test]
+    pub fn minimal_options_use_fewer_columns() {
+        let mut cs_full = ConstraintSystem::<Fr>::default();
+        let wb_table = Arc::new(WasmBytecodeTable::construct(&mut cs_full, true));
+        let shared_state = SharedStateRef::default();
+        WasmChip::configure(&mut cs_full, wb_table, shared_state);
+
+        let mut cs_minimal = ConstraintSystem::<Fr>::default();
+        let wb_table = Arc::new(WasmBytecodeTable::construct(&mut cs_minimal, true));
+        let shared_state = SharedStateRef::default();
+        WasmChip::configure_with_options(
+            &mut cs_minimal,
+            wb_table,
+            shared_state,
+            minimal_circuit_options(),
+        );
+
+        assert!(cs_minimal.num_fixed_columns() < cs_full.num_fixed_columns());
+        assert!(cs_minimal.num_advice_columns() < cs_full.num_advice_columns());
+    }
+
+    fn import_whitelist_error_codes(bytes: Vec<u8>) -> Vec<u64> {
+        error_codes_with_options::<ImportWhitelisted>(vec![WasmBytecode::new(bytes)])
+    }
+
+    #[test]
+    pub fn import_whitelist_whitelisted_imports_ok() {
+        let bytes = wat2wasm(
+            r#"(module
+                (type (func (param i32)))
+                (import "env" "_evm_address" (func (type 0)))
+                (import "wasi_snapshot_preview1" "proc_exit" (func (type 0)))
+                (import "js" "memory" (memory 1)))"#,
+        )
+        .unwrap();
+        assert_eq!(import_whitelist_error_codes(bytes), vec![0]);
+    }
+
+    #[test]
+    pub fn import_whitelist_module_without_func_imports_ok() {
+        let data: Vec<u8> = std::fs::read("./test_files/cc2.wat").unwrap();
+        let bytes = wat2wasm(data).unwrap();
+        assert_eq!(import_whitelist_error_codes(bytes), vec![0]);
+    }
+
+    #[test]
+    pub fn import_whitelist_unknown_import_sets_error_code() {
+        let bytes = wat2wasm(
+            r#"(module
+                (type (func (param i32)))
+                (import "env" "_evm_address" (func (type 0)))
+                (import "env" "_evm_selfdestruct" (func (type 0))))"#,
+        )
+        .unwrap();
+        assert_eq!(import_whitelist_error_codes(bytes), vec![1]);
+
+        // long import names can't be whitelisted
+        let data: Vec<u8> = std::fs::read("./test_files/cc1.wat").unwrap();
+        let bytes = wat2wasm(data).unwrap();
+        assert_eq!(import_whitelist_error_codes(bytes), vec![1]);
+    }
+
+    fn error_codes_with_options<O: TestCircuitOptions>(wbs: Vec<WasmBytecode>) -> Vec<u64> {
+        let circuit = TestCircuitWithOptions::<Fr, O> {
+            wbs,
+            ..Default::default()
+        };
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+        let outputs = circuit.module_outputs.borrow();
+        module_failures(&outputs)
+    }
+
+    /// 1 for the failed modules, whose error code has to be the code of their error reason
+    fn module_failures(outputs: &[WasmModuleOutput]) -> Vec<u64> {
+        outputs
+            .iter()
+            .map(|o| {
+                let reason_error_code = o.error_reason.map_or(0, |r| r.error_code as u64);
+                assert_eq!(o.error_code, reason_error_code);
+                (o.error_code != 0) as u64
+            })
+            .collect()
+    }
+
+    #[test]
+    pub fn exported_mutable_global_sets_error_code_when_rejected() {
+        let wbs = [
+            r#"(module (global i32 (i32.const 1)) (export "g" (global 0)))"#,
+            r#"(module (global (mut i32) (i32.const 1)) (export "g" (global 0)))"#,
+            r#"(module
+                (global (mut i32) (i32.const 1))
+                (global i64 (i64.const 2))
+                (export "g" (global 1)))"#,
+            r#"(module
+                (global i32 (i32.const 1))
+                (global (mut i64) (i64.const 2))
+                (export "a" (global 0))
+                (export "b" (global 1)))"#,
+        ]
+        .iter()
+        .map(|wat| WasmBytecode::new(wat2wasm(wat).unwrap()))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            error_codes_with_options::<ExportedMutableGlobalsRejected>(wbs.clone()),
+            vec![0, 1, 0, 1]
+        );
+        // accepted by default
+        assert_eq!(module_error_codes(wbs), vec![0, 0, 0, 0]);
+    }
+
+    #[test]
+    pub fn simd_instructions_skipped_or_set_error_code() {
+        let wbs = [
+            r#"(module (func (result i32) (i32.add (i32.const 1) (i32.const 2))))"#,
+            r#"(module
+                (memory 1)
+                (func (result i32)
+                    (i32x4.extract_lane 1 (v128.const i32x4 1 2 3 4)))
+                (func (param i32)
+                    (v128.store
+                        (local.get 0)
+                        (i8x16.shuffle 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+                            (v128.load offset=16 (local.get 0))
+                            (v128.load8_lane 3 (local.get 0) (v128.const i64x2 0 0)))))
+                (func (result i32) (i32.const 1)))"#,
+        ]
+        .iter()
+        .map(|wat| WasmBytecode::new(wat2wasm(wat).unwrap()))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            error_codes_with_options::<SimdInstructionsSkipped>(wbs.clone()),
+            vec![0, 0]
+        );
+        // SIMD instructions set the error code by default
+        assert_eq!(module_error_codes(wbs), vec![0, 1]);
+    }
+
+    fn module_error_codes(wbs: Vec<WasmBytecode>) -> Vec<u64> {
+        module_error_codes_with_k(wbs, 10)
+    }
+
+    fn module_error_codes_with_k(wbs: Vec<WasmBytecode>, k: u32) -> Vec<u64> {
+        let circuit = WasmCircuit::<Fr>::new(wbs);
+        let outputs = circuit.compute_module_outputs(k).unwrap();
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+        let prover = MockProver::run(k, &circuit, instance).unwrap();
+        prover.assert_satisfied();
+        module_failures(&outputs)
+    }
+
+    /// Replaces the first occurrence of `name` in the module with `patched` of the same length
+    fn patch_name(wb: &WasmBytecode, name: &[u8], patched: &[u8]) -> WasmBytecode {
+        let idx = wb.bytes.windows(name.len()).position(|w| w == name).unwrap();
+        let mut bytes = wb.bytes.clone();
+        bytes[idx..idx + name.len()].copy_from_slice(patched);
+        WasmBytecode::new(bytes)
+    }
+
+    #[test]
+    pub fn utf8_names_validation() {
+        let bytes = wat2wasm(
+            r#"(module
+                (type (func (param i32)))
+                (import "env" "имя" (func (type 0)))
+                (func (type 0))
+                (export "🦀" (func 1)))"#,
+        )
+        .unwrap();
+        let wb = WasmBytecode::new(bytes);
+        let wbs = vec![
+            wb.clone(),
+            // truncated 2-byte sequence in import name
+            patch_name(&wb, "имя".as_bytes(), &[0xd0, 0xb8, 0xd0, 0xbc, 0xd1, 0x41]),
+            // surrogate in import name
+            patch_name(&wb, "имя".as_bytes(), &[0x41, 0x41, 0x41, 0xed, 0xa0, 0x80]),
+            // overlong encoding in export name
+            patch_name(&wb, "🦀".as_bytes(), &[0xf0, 0x80, 0x80, 0xaf]),
+            // zero byte in export name
+            patch_name(&wb, "🦀".as_bytes(), &[0x41, 0x41, 0x41, 0x00]),
+        ];
+        assert_eq!(module_error_codes(wbs), vec![0, 1, 1, 1, 1]);
+    }
+
+    fn module_with_custom_sections(leading: &[Vec<u8>], trailing: &[Vec<u8>]) -> WasmBytecode {
+        let bytes = wat2wasm(
+            r#"(module
+                (func (export "main") (result i32) (i32.const 42)))"#,
+        )
+        .unwrap();
+        let mut module = bytes[..WASM_SECTIONS_START_INDEX].to_vec();
+        module.extend(leading.concat());
+        module.extend_from_slice(&bytes[WASM_SECTIONS_START_INDEX..]);
+        module.extend(trailing.concat());
+        WasmBytecode::new(module)
+    }
+
+    #[test]
+    pub fn debug_custom_sections_pass_through_ok() {
+        let wb = module_with_custom_sections(
+            &[],
+            &[
+                custom_section(".debug_info", &[0xff; 200]),
+                custom_section(".debug_line", &[0; 10]),
+                custom_section("sourceMappingURL", b"main.wasm.map"),
+            ],
+        );
+        assert_eq!(module_error_codes(vec![wb]), vec![0]);
+    }
+
+    #[test]
+    pub fn section_len_above_remaining_bytes_sets_error_code() {
+        let bytes = wat2wasm(
+            r#"(module
+                (func (export "main") (result i32) (i32.const 42)))"#,
+        )
+        .unwrap();
+        // the code section is the last one, truncation leaves its declared len unchanged
+        let wbs = [0, 1, 4]
+            .iter()
+            .map(|truncated_len| WasmBytecode::new(bytes[..bytes.len() - truncated_len].to_vec()))
+            .collect();
+        assert_eq!(module_error_codes(wbs), vec![0, 1, 1]);
+    }
+
+    #[test]
+    pub fn producers_custom_section_ok() {
+        let producers = custom_section(
+            "producers",
+            &producers_payload(&[("language", &[("Rust", "")]), ("sdk", &[("fluentbase", "0.1")])]),
+        );
+        let malformed = custom_section(
+            "producers",
+            &producers_payload(&[("language", &[]), ("language", &[])]),
+        );
+        let wbs = vec![
+            module_with_custom_sections(&[], &[producers]),
+            module_with_custom_sections(&[], &[malformed]),
+        ];
+        assert_eq!(module_error_codes(wbs), vec![0, 1]);
+    }
+
+    #[test]
+    pub fn custom_section_before_other_sections_sets_error_code() {
+        let wb = module_with_custom_sections(&[custom_section(".debug_info", &[1, 2, 3])], &[]);
+        assert_eq!(module_error_codes(vec![wb]), vec![1]);
+    }
+
+    #[test]
+    pub fn data_segments_above_max_data_size_set_error_code() {
+        let wbs = [
+            r#"(module (memory 1) (data (i32.const 0) "0123456789abcdef") (data "01234567"))"#,
+            r#"(module (memory 1) (data (i32.const 0) "0123456789abcdefg"))"#,
+            r#"(module
+                (memory 1)
+                (data (i32.const 0) "0123456789abcdef")
+                (data (i32.const 16) "012345678"))"#,
+        ]
+        .iter()
+        .map(|wat| WasmBytecode::new(wat2wasm(wat).unwrap()))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            error_codes_with_options::<SmallDataLimits>(wbs.clone()),
+            vec![0, 1, 1]
+        );
+        // accepted by the default limits
+        assert_eq!(module_error_codes(wbs), vec![0, 0, 0]);
+    }
+
+    #[test]
+    pub fn memory_limits_above_max_memory_pages_set_error_code() {
+        let wbs = [
+            "(module (memory 1 256))",
+            "(module (memory 257))",
+            "(module (memory 1 300))",
+        ]
+        .iter()
+        .map(|wat| WasmBytecode::new(wat2wasm(wat).unwrap()))
+        .collect();
+        assert_eq!(module_error_codes(wbs), vec![0, 1, 1]);
+    }
+
+    #[test]
+    pub fn functype_counts_above_limits_set_error_code() {
+        let func_type = |params_count: u64, results: &str| {
+            format!(
+                "(module (type (func (param {}) (result {}))))",
+                "i32 ".repeat(params_count as usize),
+                results,
+            )
+        };
+        let wbs = [
+            func_type(MAX_FUNC_PARAMS, "i64"),
+            func_type(MAX_FUNC_PARAMS + 1, "i64"),
+            // multi-value is disabled by default
+            func_type(1, "i32 i64"),
+        ]
+        .iter()
+        .map(|wat| WasmBytecode::new(wat2wasm(wat).unwrap()))
+        .collect();
+        assert_eq!(module_error_codes_with_k(wbs, 12), vec![0, 1, 1]);
+    }
+
+    #[test]
+    pub fn import_names_above_max_name_len_set_error_code() {
+        let import = |mod_name_len: u64, import_name_len: u64| {
+            format!(
+                r#"(module (import "{}" "{}" (memory 1)))"#,
+                "m".repeat(mod_name_len as usize),
+                "f".repeat(import_name_len as usize),
+            )
+        };
+        let wbs = [
+            import(MAX_IMPORT_MOD_NAME_LEN, MAX_IMPORT_NAME_LEN),
+            import(MAX_IMPORT_MOD_NAME_LEN + 1, 1),
+            import(1, MAX_IMPORT_NAME_LEN + 1),
+        ]
+        .iter()
+        .map(|wat| WasmBytecode::new(wat2wasm(wat).unwrap()))
+        .collect();
+        assert_eq!(module_error_codes_with_k(wbs, 11), vec![0, 1, 1]);
+    }
+
+    #[test]
+    pub fn block_level_above_max_block_level_sets_error_code() {
+        let nested_blocks = |depth: u64| {
+            let body = "block ".repeat(depth as usize) + &"end ".repeat(depth as usize);
+            format!("(module (func {}))", body)
+        };
+        let wbs = [
+            nested_blocks(1),
+            nested_blocks(MAX_BLOCK_LEVEL - 1),
+            nested_blocks(MAX_BLOCK_LEVEL),
+        ]
+        .iter()
+        .map(|wat| WasmBytecode::new(wat2wasm(wat).unwrap()))
+        .collect();
+        assert_eq!(module_error_codes_with_k(wbs, 12), vec![0, 0, 1]);
+    }
+
+    #[test]
+    pub fn branch_depth_above_block_level_sets_error_code() {
+        let wb = WasmBytecode::new(wat2wasm("(module (func block br 1 end))").unwrap());
+        // wat2wasm rejects invalid depths, so `br 1` is patched in place
+        let wbs = vec![
+            wb.clone(),
+            patch_name(&wb, &[0x0C, 0x01], &[0x0C, 0x02]),
+            patch_name(&wb, &[0x0C, 0x01], &[0x0D, 0x05]),
+        ];
+        assert_eq!(module_error_codes(wbs), vec![0, 1, 1]);
+    }
+
+    #[test]
+    pub fn error_reason_carries_error_code_section_and_offset() {
+        let wb = WasmBytecode::new(wat2wasm("(module (func block br 1 end))").unwrap());
+        let wb_invalid = patch_name(&wb, &[0x0C, 0x01], &[0x0C, 0x02]);
+        let label_offset = wb_invalid.bytes.windows(2).position(|w| w == [0x0C, 0x02]).unwrap() + 1;
+        let circuit = WasmCircuit::<Fr>::new(vec![wb_invalid, wb]);
+        let outputs = circuit.compute_module_outputs(10).unwrap();
+        assert_eq!(outputs[0].error_code, ErrorCode::BranchDepthExceeded as u64);
+        assert_eq!(
+            outputs[0].error_reason,
+            Some(ErrorReason {
+                error_code: ErrorCode::BranchDepthExceeded,
+                section: Some(WasmSection::Code),
+                wb_offset: Some(label_offset),
+            })
+        );
+        assert_eq!(outputs[1].error_reason, None);
+
+        let instance = WasmCircuit::<Fr>::instance_from_outputs(&outputs);
+        let prover = MockProver::run(10, &circuit, instance).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn func_locals_above_max_func_locals_set_error_code() {
+        // wat2wasm merges consecutive locals of the same type into one repetition count
+        let func_with_locals = |i32_count: u64, i64_count: u64| {
+            format!(
+                "(module (func (local {}) (local {})))",
+                "i32 ".repeat(i32_count as usize),
+                "i64 ".repeat(i64_count as usize),
+            )
+        };
+        let wbs = [
+            func_with_locals(MAX_FUNC_LOCALS, 0),
+            func_with_locals(MAX_FUNC_LOCALS + 1, 0),
+            func_with_locals(MAX_FUNC_LOCALS / 2 + 1, MAX_FUNC_LOCALS / 2),
+        ]
+        .iter()
+        .map(|wat| WasmBytecode::new(wat2wasm(wat).unwrap()))
+        .collect();
+        assert_eq!(module_error_codes(wbs), vec![0, 1, 1]);
+    }
+
+    #[test]
+    pub fn unbalanced_block_ends_set_error_code() {
+        let wb = WasmBytecode::new(wat2wasm("(module (func block end))").unwrap());
+        let body = [0x02, 0x40, 0x0B, 0x0B];
+        let wbs = vec![
+            wb.clone(),
+            // block unreachable end: the block is never closed
+            patch_name(&wb, &body, &[0x02, 0x40, 0x00, 0x0B]),
+            // end unreachable unreachable end: the func body is closed too early
+            patch_name(&wb, &body, &[0x0B, 0x00, 0x00, 0x0B]),
+        ];
+        assert_eq!(module_error_codes(wbs), vec![0, 1, 1]);
+    }
+
+    #[test]
+    pub fn failed_section_does_not_poison_next_module_block_level() {
+        let wb = WasmBytecode::new(wat2wasm("(module (func block end))").unwrap());
+        // block unreachable end: the code section fails with the block still open
+        let wbs = vec![
+            patch_name(&wb, &[0x02, 0x40, 0x0B, 0x0B], &[0x02, 0x40, 0x00, 0x0B]),
+            wb.clone(),
+            wb,
+        ];
+        assert_eq!(module_error_codes(wbs), vec![1, 0, 0]);
+    }
+
+    #[test]
+    pub fn func_count_is_scoped_per_module() {
+        let wat = r#"(module (import "env" "f" (func)) (func call 0) (func block end))"#;
+        let wb = WasmBytecode::new(wat2wasm(wat).unwrap());
+        // the second module fails in its code section after func_count was increased
+        let wbs = vec![
+            wb.clone(),
+            patch_name(&wb, &[0x02, 0x40, 0x0B, 0x0B], &[0x02, 0x40, 0x00, 0x0B]),
+            wb.clone(),
+            wb,
+        ];
+        assert_eq!(module_error_codes(wbs), vec![0, 1, 0, 0]);
+    }
+
+    #[test]
+    pub fn func_count_hand_off_at_section_boundary_tampered_fails() {
+        let wat = r#"(module (import "env" "f" (func)) (func call 0) (func block end))"#;
+        let wb = WasmBytecode::new(wat2wasm(wat).unwrap());
+        let code_section = WasmAssignmentPlan::new(vec![wb.clone()], 0)
+            .section_assignments
+            .into_iter()
+            .find(|assignment| assignment.section == WasmSection::Code)
+            .unwrap();
+        let mut circuit = TestCircuit::<Fr> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 10);
+
+        // the code section starts as if the imported function was not handed off to it
+        let code_section_id_row = code_section.section_start_offset + code_section.assign_delta;
+        circuit.func_count_overrides = vec![(code_section_id_row, 0)];
+        let failures = failed_constraints(&circuit, 10);
+        assert!(
+            failures.iter().any(|constraint| constraint.contains("WasmCircuit gate")
+                && constraint.contains("prev.func_count=func_count")),
+            "{:?}",
+            failures
+        );
+    }
+
+    #[test]
+    pub fn func_count_carried_over_to_the_next_module_fails() {
+        let wat = r#"(module (import "env" "f" (func)) (func call 0) (func block end))"#;
+        let wb = WasmBytecode::new(wat2wasm(wat).unwrap());
+        let mut circuit = TestCircuit::<Fr> {
+            wbs: vec![wb.clone(), wb.clone()],
+            ..Default::default()
+        };
+        test(&circuit, true, 10);
+
+        // the q_first row of the second module keeps the 3 funcs of the first one
+        let mut cursor = RegionCursor::new(0, true);
+        cursor.advance(&wb);
+        circuit.func_count_overrides = vec![(cursor.assign_delta(), 3)];
+        let failures = failed_constraints(&circuit, 10);
+        assert!(
+            failures.iter().any(|constraint| constraint.contains("WasmCircuit gate")
+                && constraint.contains("q_first => func_count=0")),
+            "{:?}",
+            failures
+        );
+    }
+
+    #[test]
+    pub fn region_cursor_stacks_bytecodes() {
+        let wb = WasmBytecode::new(wat2wasm("(module)").unwrap());
+        let mut cursor = RegionCursor::new(5, true);
+        assert_eq!(cursor.bytecode_assign_delta(), 6);
+        cursor.advance(&wb);
+        assert_eq!(cursor.assign_delta(), 5 + 1 + wb.bytes.len());
+        cursor.advance(&wb);
+        assert_eq!(cursor.assign_delta(), 5 + 2 * (1 + wb.bytes.len()));
+
+        let mut cursor = RegionCursor::new(5, false);
+        cursor.advance(&wb);
+        assert_eq!(cursor.bytecode_assign_delta(), 5 + wb.bytes.len());
+    }
+
+    #[test]
+    pub fn dynamic_indexes_num_rows_follow_parsed_counts() {
+        let wat = "(module (type (func)) (type (func (param i32))) \
+            (global i32 (i32.const 1)) (global i32 (i32.const 2)) (func (type 0)))";
+        let wb = WasmBytecode::new(wat2wasm(wat).unwrap());
+        // 2 types, 2 globals and 1 func, each with a terminator row
+        assert_eq!(WasmCircuit::<Fr>::dynamic_indexes_num_rows(&wb), 8);
+    }
+
+    #[test]
+    pub fn capacity_exceeded_before_assignment() {
+        let data: Vec<u8> = std::fs::read("./test_files/cc1.wat").unwrap();
+        let wb = WasmBytecode::new(wat2wasm(data).unwrap());
+        let circuit = WasmCircuit::<Fr>::new(vec![wb]);
+        assert_eq!(circuit.check_capacity(9), Ok(()));
+        assert!(matches!(
+            circuit.check_capacity(5),
+            Err(WasmError::FatalCapacityExceeded { .. })
+        ));
+        assert!(circuit.compute_module_outputs(5).is_err());
+    }
+
+    #[test]
+    pub fn capacity_checked_before_synthesis() {
+        let data: Vec<u8> = std::fs::read("./test_files/cc1.wat").unwrap();
+        let wb = WasmBytecode::new(wat2wasm(data).unwrap());
+        let circuit = WasmCircuit::<Fr>::new(vec![wb]);
+        let k = circuit.min_k().unwrap();
+        assert!(k <= 9);
+
+        let circuit = circuit.with_k(5);
+        assert!(matches!(
+            circuit.min_k(),
+            Err(WasmError::FatalCapacityExceeded { .. })
+        ));
+        assert!(matches!(
+            MockProver::run(9, &circuit, vec![vec![]]),
+            Err(halo2_proofs::plonk::Error::NotEnoughRowsAvailable { current_k: 5 })
+        ));
+    }
+
+    #[test]
+    pub fn import_whitelist_adds_lookup() {
+        let mut cs_default = ConstraintSystem::<Fr>::default();
+        let wb_table = Arc::new(WasmBytecodeTable::construct(&mut cs_default, true));
+        let shared_state = SharedStateRef::default();
+        WasmChip::configure(&mut cs_default, wb_table, shared_state);
+
+        let mut cs_whitelist = ConstraintSystem::<Fr>::default();
+        let wb_table = Arc::new(WasmBytecodeTable::construct(&mut cs_whitelist, true));
+        let shared_state = SharedStateRef::default();
+        WasmChip::configure_with_options(
+            &mut cs_whitelist,
+            wb_table,
+            shared_state,
+            import_whitelist_circuit_options(),
+        );
+
+        assert_eq!(cs_whitelist.lookups().len(), cs_default.lookups().len() + 1);
+    }
+
+    /// Circuit over cc2 and a small evm code, the unified table anchors `anchored_wb` (cc2 if
+    /// `None`) instead of cc2
+    fn unified_bytecode_table_circuit(
+        anchored_wb: Option<WasmBytecode>,
+    ) -> TestCircuitWithUnifiedBytecodeTable<Fr> {
+        let data: Vec<u8> = std::fs::read("./test_files/cc2.wat").unwrap();
+        let wb = WasmBytecode::new(wat2wasm(data).unwrap());
+        let evm_code = vec![0x60, 0x01, 0x60, 0x02, 0x01, 0x00];
+        TestCircuitWithUnifiedBytecodeTable {
+            wbs: vec![wb.clone()],
+            evm_bytecodes: vec![Bytecode {
+                hash: CodeDB::hash(&evm_code).to_word(),
+                bytes: evm_code,
+            }],
+            anchored_wbs: vec![anchored_wb.unwrap_or(wb)],
+            _marker: PhantomData,
+        }
+    }
+
+    #[test]
+    pub fn unified_bytecode_table_mixed_code_ok() {
+        let circuit = unified_bytecode_table_circuit(None);
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn unified_bytecode_table_missing_wasm_code_fails() {
+        let other_wb = WasmBytecode::new(wat2wasm("(module)").unwrap());
+        let circuit = unified_bytecode_table_circuit(Some(other_wb));
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    pub fn file1_poseidon_code_hash_ok() {
+        let data: Vec<u8> = std::fs::read("./test_files/cc1.wat").unwrap();
+        let circuit = TestCircuitWithOptions::<Fr, PoseidonCodeHash> {
+            wbs: vec![WasmBytecode::new(wat2wasm(data).unwrap())],
+            ..Default::default()
+        };
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    pub fn poseidon_code_hash_adds_column_and_lookup() {
+        let mut cs_default = ConstraintSystem::<Fr>::default();
+        let wb_table = Arc::new(WasmBytecodeTable::construct(&mut cs_default, true));
+        let shared_state = SharedStateRef::default();
+        WasmChip::configure(&mut cs_default, wb_table, shared_state);
+
+        let mut cs_poseidon = ConstraintSystem::<Fr>::default();
+        let wb_table = Arc::new(WasmBytecodeTable::construct_with_poseidon_code_hash(
+            &mut cs_poseidon,
+            true,
+        ));
+        let shared_state = SharedStateRef::default();
+        WasmChip::configure(&mut cs_poseidon, wb_table, shared_state);
+
+        assert_eq!(cs_poseidon.num_advice_columns(), cs_default.num_advice_columns() + 1);
+        assert_eq!(cs_poseidon.lookups().len(), cs_default.lookups().len() + 1);
+    }
+
+    #[test]
+    pub fn shared_state_is_shared_across_threads() {
+        let shared_state = SharedStateRef::default();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let shared_state = shared_state.clone();
+                std::thread::spawn(move || shared_state.write().bytecode_number_inc())
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(shared_state.read().bytecode_number, 4);
+    }
 }
@@ -1,4 +1,9 @@
-use rand::{random, Rng, thread_rng};
+use rand::Rng;
+
+use crate::{
+    test_rng::test_rng,
+    wasm_circuit::{leb128::helpers::leb128_encode, types::WasmSection},
+};
 
 pub fn break_bit_by_mask(byte_to_break: &mut u8, break_mask: u8) {
     *byte_to_break = (!*byte_to_break & break_mask) | (*byte_to_break & !break_mask);
@@ -6,5 +11,33 @@ pub fn break_bit_by_mask(byte_to_break: &mut u8, break_mask: u8) {
 
 pub fn mutate_byte(byte_to_mutate: &mut u8) {
     let mut byte_old_val = *byte_to_mutate;
-    while byte_old_val == *byte_to_mutate { *byte_to_mutate = random(); }
-}
\ No newline at end of file
+    while byte_old_val == *byte_to_mutate { *byte_to_mutate = test_rng().gen(); }
+}
+/// Encodes a whole custom section (id, length and body) with the given name and payload
+pub fn custom_section(name: &str, payload: &[u8]) -> Vec<u8> {
+    let mut body = leb128_encode(false, name.len() as i128).unwrap();
+    body.extend_from_slice(name.as_bytes());
+    body.extend_from_slice(payload);
+    let mut section = vec![WasmSection::Custom as u8];
+    section.extend(leb128_encode(false, body.len() as i128).unwrap());
+    section.extend(body);
+    section
+}
+/// Payload of a `producers` custom section with the given fields and their `(name, version)` values
+pub fn producers_payload(fields: &[(&str, &[(&str, &str)])]) -> Vec<u8> {
+    let name = |name: &str| {
+        let mut bytes = leb128_encode(false, name.len() as i128).unwrap();
+        bytes.extend_from_slice(name.as_bytes());
+        bytes
+    };
+    let mut payload = leb128_encode(false, fields.len() as i128).unwrap();
+    for (field_name, values) in fields {
+        payload.extend(name(field_name));
+        payload.extend(leb128_encode(false, values.len() as i128).unwrap());
+        for (value_name, version) in values.iter() {
+            payload.extend(name(value_name));
+            payload.extend(name(version));
+        }
+    }
+    payload
+}
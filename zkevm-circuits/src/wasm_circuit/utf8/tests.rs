@@ -1,4 +1,4 @@
-use std::{marker::PhantomData, rc::Rc};
+use std::{marker::PhantomData, sync::Arc};
 
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
@@ -9,7 +9,7 @@ use eth_types::{Field, ToWord};
 
 use crate::wasm_circuit::{
     bytecode::bytecode::WasmBytecode,
-    tables::fixed_range::config::RangeTableConfig,
+    tables::utf8_transition::{config::Utf8TransitionTableConfig, types::Utf8State},
     utf8::circuit::{UTF8Chip, UTF8Config},
 };
 
@@ -23,7 +23,7 @@ struct TestCircuit<'a, F> {
 #[derive(Clone)]
 struct TestCircuitConfig<F: Field> {
     bytes: Column<Advice>,
-    eligible_byte_vals_range_table_config: Rc<RangeTableConfig<F, 0, 128>>,
+    utf8_transition_table_config: Arc<Utf8TransitionTableConfig<F>>,
     utf8_config: UTF8Config<F>,
     _marker: PhantomData<F>,
 }
@@ -38,12 +38,12 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
         let bytes = cs.advice_column();
-        let eligible_byte_vals_range_table_config = Rc::new(RangeTableConfig::configure(cs));
+        let utf8_transition_table_config = Arc::new(Utf8TransitionTableConfig::configure(cs));
         let utf8_config =
-            UTF8Chip::<F>::configure(cs, eligible_byte_vals_range_table_config.clone(), &bytes);
+            UTF8Chip::<F>::configure(cs, utf8_transition_table_config.clone(), &bytes);
         let test_circuit_config = TestCircuitConfig {
             bytes,
-            eligible_byte_vals_range_table_config: eligible_byte_vals_range_table_config.clone(),
+            utf8_transition_table_config: utf8_transition_table_config.clone(),
             utf8_config,
             _marker: Default::default(),
         };
@@ -56,9 +56,7 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
         config: Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
-        config
-            .eligible_byte_vals_range_table_config
-            .load(&mut layouter)?;
+        config.utf8_transition_table_config.load(&mut layouter)?;
         let utf8_chip = UTF8Chip::construct(config.utf8_config);
         let wb = WasmBytecode::new(self.bytes.to_vec());
 
@@ -76,9 +74,24 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
                         )
                         .unwrap();
                 }
-                utf8_chip
-                    .assign_auto(&mut region, &wb, self.bytes.len(), 0, self.offset_shift)
-                    .unwrap();
+                let assigned =
+                    utf8_chip.assign_auto(&mut region, &wb, self.bytes.len(), 0, self.offset_shift);
+                if assigned.is_err() {
+                    // no valid witness exists, assign bytes as standalone chars to check
+                    // constraints reject them
+                    for (offset, &byte_val) in self.bytes.iter().enumerate() {
+                        utf8_chip
+                            .assign(
+                                &mut region,
+                                offset + self.offset_shift,
+                                byte_val,
+                                offset == 0,
+                                offset == self.bytes.len() - 1,
+                                Utf8State::Accept,
+                            )
+                            .unwrap();
+                    }
+                }
 
                 Ok(())
             },
@@ -138,4 +151,35 @@ mod utf8_circuit_tests {
     pub fn test_zero() {
         exact_utf8bytecode(vec![0].as_slice(), 0, false);
     }
+
+    #[test]
+    pub fn test_valid_multibyte() {
+        exact_utf8bytecode("имя".as_bytes(), 0, true);
+        exact_utf8bytecode("名前🦀".as_bytes(), 1, true);
+    }
+
+    #[test]
+    pub fn test_overlong_encoding() {
+        // '/' encoded with 2 bytes
+        exact_utf8bytecode(vec![0xc0, 0xaf].as_slice(), 0, false);
+        // '/' encoded with 3 bytes
+        exact_utf8bytecode(vec![0xe0, 0x80, 0xaf].as_slice(), 0, false);
+    }
+
+    #[test]
+    pub fn test_surrogate() {
+        // U+D800
+        exact_utf8bytecode(vec![0xed, 0xa0, 0x80].as_slice(), 0, false);
+    }
+
+    #[test]
+    pub fn test_truncated_sequence() {
+        exact_utf8bytecode(vec!['a' as u8, 0xe2, 0x82].as_slice(), 0, false);
+    }
+
+    #[test]
+    pub fn test_out_of_range_codepoint() {
+        // U+110000
+        exact_utf8bytecode(vec![0xf4, 0x90, 0x80, 0x80].as_slice(), 0, false);
+    }
 }
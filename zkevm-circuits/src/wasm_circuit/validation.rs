@@ -0,0 +1,5 @@
+pub use wasm_witness::validation::{types, validator};
+#[cfg(any(feature = "test", test))]
+pub mod tests;
+
+pub use validator::validate_module;
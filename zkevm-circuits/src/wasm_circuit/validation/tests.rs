@@ -0,0 +1,216 @@
+#[cfg(test)]
+mod wasm_validation_tests {
+    use rand::Rng;
+    use wabt::wat2wasm;
+    use wasmparser::Parser;
+
+    use crate::{
+        test_rng::test_rng,
+        wasm_circuit::{
+            consts::{WASM_MAGIC_PREFIX_END_INDEX, WASM_MAGIC_PREFIX_START_INDEX},
+            error::Error,
+            tests_helpers::{custom_section, mutate_byte, producers_payload},
+            types::{ProducersField, WasmSection},
+            validation::validate_module,
+        },
+    };
+
+    fn wat_file_to_bytes(path: &str) -> Vec<u8> {
+        let data: Vec<u8> = std::fs::read(path).unwrap();
+        wat2wasm(data).unwrap()
+    }
+
+    #[test]
+    pub fn files_ok() {
+        let paths = [
+            "./test_files/cc1.wat",
+            "./test_files/cc2.wat",
+            "./test_files/cc3.wat",
+        ];
+        for path in paths {
+            let bytes = wat_file_to_bytes(path);
+            let report = validate_module(&bytes);
+            assert!(report.is_ok(), "file '{}' error {:?}", path, report.error);
+            assert!(report.sections.len() > 0);
+        }
+    }
+
+    #[test]
+    pub fn file1_section_ranges_match_wasmparser() {
+        let bytes = wat_file_to_bytes("./test_files/cc1.wat");
+        let report = validate_module(&bytes);
+        assert!(report.is_ok());
+
+        let mut expected = vec![];
+        for payload in Parser::new(0).parse_all(&bytes) {
+            if let Some((id, range)) = payload.unwrap().as_section() {
+                expected.push((id as i32, range.start, range.end - 1));
+            }
+        }
+        let actual = report
+            .sections
+            .iter()
+            .map(|s| {
+                (
+                    s.section as i32,
+                    s.section_body_start_offset,
+                    s.section_end_offset,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    pub fn file2_start_section_func_index_ok() {
+        let bytes = wat_file_to_bytes("./test_files/cc2.wat");
+        let report = validate_module(&bytes);
+        assert!(report.is_ok());
+
+        let start_section = report.section(WasmSection::Start).unwrap();
+        assert_eq!(start_section.items_count, Some(2));
+        assert_eq!(start_section.section_body_len(), 1);
+    }
+
+    #[test]
+    pub fn file1_invalid_magic_prefix_fails() {
+        let mut bytes = wat_file_to_bytes("./test_files/cc1.wat");
+        let idx: usize =
+            test_rng().gen_range(WASM_MAGIC_PREFIX_START_INDEX..=WASM_MAGIC_PREFIX_END_INDEX);
+        mutate_byte(&mut bytes[idx]);
+
+        let report = validate_module(&bytes);
+        assert_eq!(report.error, Some(Error::InvalidByteValueAt(0)));
+        assert_eq!(report.sections.len(), 0);
+    }
+
+    #[test]
+    pub fn file1_truncated_fails() {
+        let mut bytes = wat_file_to_bytes("./test_files/cc1.wat");
+        bytes.truncate(bytes.len() - 1);
+
+        let report = validate_module(&bytes);
+        assert!(!report.is_ok());
+        assert!(matches!(report.error, Some(Error::SectionLenExceededAt(_))));
+        assert_eq!(report.error_section, None);
+    }
+
+    #[test]
+    pub fn file1_invalid_section_id_fails() {
+        let mut bytes = wat_file_to_bytes("./test_files/cc1.wat");
+        bytes[8] = test_rng().gen_range((WasmSection::DataCount as u8 + 1)..255);
+
+        let report = validate_module(&bytes);
+        assert_eq!(report.error, Some(Error::InvalidEnumValueAt(8)));
+    }
+
+    #[test]
+    pub fn invalid_section_item_fails() {
+        let mut bytes = wat2wasm("(module (func))").unwrap();
+        // type section is [id, len, count, 0x60 (func type), params, results]
+        let func_type_offset = 8 + 3;
+        assert_eq!(bytes[func_type_offset], 0x60);
+        bytes[func_type_offset] = 0x61;
+        assert!(wasmparser::validate(&bytes).is_err());
+
+        let report = validate_module(&bytes);
+        assert_eq!(report.error, Some(Error::InvalidByteValueAt(func_type_offset)));
+        assert_eq!(report.error_section, Some(WasmSection::Type));
+        assert!(report.section(WasmSection::Type).is_none());
+    }
+
+    #[test]
+    pub fn items_count_above_section_body_fails() {
+        let mut bytes = wat2wasm("(module (func) (func))").unwrap();
+        // function section follows the type section [id, len, count, 0x60, params, results]
+        let function_section_offset = 8 + 6;
+        assert_eq!(bytes[function_section_offset], WasmSection::Function as u8);
+        assert_eq!(bytes[function_section_offset + 2], 2);
+        bytes[function_section_offset + 2] = 3;
+
+        let report = validate_module(&bytes);
+        assert!(!report.is_ok());
+        assert_eq!(report.error_section, Some(WasmSection::Function));
+        assert!(report.section(WasmSection::Type).is_some());
+    }
+
+    #[test]
+    pub fn custom_sections_ok_only_when_trailing_and_known() {
+        let bytes = wat_file_to_bytes("./test_files/cc2.wat");
+        let with_custom_sections = |sections: &[Vec<u8>]| {
+            let mut bytes = bytes.clone();
+            bytes.extend(sections.concat());
+            bytes
+        };
+
+        let report = validate_module(&with_custom_sections(&[
+            custom_section(".debug_abbrev", &[7; 20]),
+            custom_section("sourceMappingURL", b"cc2.wasm.map"),
+        ]));
+        assert!(report.is_ok(), "error {:?}", report.error);
+        let custom_sections = report
+            .sections
+            .iter()
+            .filter(|s| s.section == WasmSection::Custom)
+            .collect::<Vec<_>>();
+        assert_eq!(custom_sections.len(), 2);
+        assert!(custom_sections.iter().all(|s| s.items_count.is_none()));
+
+        let report = validate_module(&with_custom_sections(&[custom_section("name", &[0])]));
+        assert!(matches!(report.error, Some(Error::FatalUnsupportedValue(_))));
+
+        // type section after custom one
+        let mut bytes = with_custom_sections(&[custom_section(".debug_info", &[])]);
+        bytes.extend_from_slice(&[WasmSection::Type as u8, 1, 0]);
+        let report = validate_module(&bytes);
+        assert!(matches!(report.error, Some(Error::InvalidByteValueAt(_))));
+    }
+
+    #[test]
+    pub fn producers_custom_section_parsed() {
+        let mut bytes = wat_file_to_bytes("./test_files/cc2.wat");
+        let section_start_offset = bytes.len();
+        bytes.extend(custom_section(
+            "producers",
+            &producers_payload(&[
+                ("language", &[("Rust", "")]),
+                ("processed-by", &[("rustc", "1.66.0"), ("wasm-opt", "111")]),
+            ]),
+        ));
+        let report = validate_module(&bytes);
+        assert!(report.is_ok(), "error {:?}", report.error);
+        let producers = report.producers.unwrap();
+        assert_eq!(
+            producers.fields,
+            vec![
+                ProducersField {
+                    name: "language".to_string(),
+                    values: vec![("Rust".to_string(), "".to_string())],
+                },
+                ProducersField {
+                    name: "processed-by".to_string(),
+                    values: vec![
+                        ("rustc".to_string(), "1.66.0".to_string()),
+                        ("wasm-opt".to_string(), "111".to_string()),
+                    ],
+                },
+            ]
+        );
+
+        // unknown field, duplicated field, trailing byte
+        let field_name_offset = section_start_offset + 2 + 1 + "producers".len() + 1;
+        for payload in [
+            producers_payload(&[("compiler", &[])]),
+            producers_payload(&[("sdk", &[]), ("sdk", &[])]),
+            [producers_payload(&[("sdk", &[])]), vec![0]].concat(),
+        ] {
+            let mut bytes = wat_file_to_bytes("./test_files/cc2.wat");
+            bytes.extend(custom_section("producers", &payload));
+            let report = validate_module(&bytes);
+            assert!(matches!(
+                report.error,
+                Some(Error::InvalidByteValueAt(offset)) if offset >= field_name_offset
+            ));
+        }
+    }
+}
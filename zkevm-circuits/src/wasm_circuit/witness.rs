@@ -0,0 +1,15 @@
+//! Witness generation inputs usable without the prover: bytecode decoding, validation, shared
+//! state and assignment plans. They live in the `wasm-witness` crate, which has no halo2
+//! dependency unless its `circuit` feature is enabled: light clients and indexers depend on that
+//! crate only.
+
+pub use wasm_witness::{
+    bytecode::WasmBytecode,
+    decoder::{decode_module, ReferenceModule, ReferenceSection},
+    error::Error,
+    types::{SharedState, WasmAssignmentPlan, WasmModuleOutput, WasmSection, WasmSectionAssignment},
+    validation::{
+        types::{SectionReport, ValidationReport},
+        validate_module,
+    },
+};
@@ -6,7 +6,7 @@ use crate::evm_circuit::{detect_fixed_table_tags, EvmCircuit};
 
 use crate::{evm_circuit::util::rlc, table::BlockContextFieldTag, util::SubCircuit};
 use bus_mapping::{
-    circuit_input_builder::{self, CircuitsParams, CopyEvent, ExpEvent},
+    circuit_input_builder::{self, ChainSpec, CircuitsParams, CopyEvent, ExpEvent},
     Error,
 };
 use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word, U256};
@@ -62,6 +62,8 @@ pub struct Block<F> {
     pub mpt_updates: MptUpdates,
     /// Chain ID
     pub chain_id: Word,
+    /// Opcodes enabled on the chain
+    pub chain_spec: ChainSpec,
 }
 
 /// ...
@@ -118,7 +120,7 @@ impl<F: Field> Block<F> {
         let num_rows_required_for_rw_table: usize = self.circuits_params.max_rws;
         let num_rows_required_for_fixed_table: usize = detect_fixed_table_tags(self)
             .iter()
-            .map(|tag| tag.build::<F>().count())
+            .map(|tag| tag.build::<F>(&self.chain_spec).count())
             .sum();
         let num_rows_required_for_bytecode_table: usize = self
             .bytecodes
@@ -425,6 +427,7 @@ pub fn block_convert<F: Field>(
         keccak_inputs: circuit_input_builder::keccak_inputs(block, code_db)?,
         mpt_updates,
         chain_id,
+        chain_spec: block.chain_spec,
     })
 }
 
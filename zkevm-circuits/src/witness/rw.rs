@@ -192,6 +192,16 @@ pub enum Rw {
         tx_id: usize,
         committed_value: Word,
     },
+    /// TransientStorage
+    TransientStorage {
+        rw_counter: usize,
+        is_write: bool,
+        tx_id: usize,
+        account_address: Address,
+        storage_key: Word,
+        value: Word,
+        value_prev: Word,
+    },
     /// CallContext
     CallContext {
         rw_counter: usize,
@@ -216,6 +226,17 @@ pub enum Rw {
         call_id: usize,
         global_index: usize,
         value: StackWord,
+        value_prev: StackWord,
+    },
+    /// Table
+    Table {
+        rw_counter: usize,
+        is_write: bool,
+        call_id: usize,
+        table_index: u32,
+        elem_index: u32,
+        value: StackWord,
+        value_prev: StackWord,
     },
     /// Memory
     Memory {
@@ -320,6 +341,24 @@ impl Rw {
         }
     }
 
+    pub fn global_value_pair(&self) -> (StackWord, StackWord) {
+        match self {
+            Self::Global {
+                value, value_prev, ..
+            } => (*value, *value_prev),
+            _ => unreachable!("{:?}", self),
+        }
+    }
+
+    pub fn table_value_pair(&self) -> (StackWord, StackWord) {
+        match self {
+            Self::Table {
+                value, value_prev, ..
+            } => (*value, *value_prev),
+            _ => unreachable!("{:?}", self),
+        }
+    }
+
     pub fn account_value_pair(&self) -> (Word, Word) {
         match self {
             Self::Account {
@@ -392,6 +431,15 @@ impl Rw {
         }
     }
 
+    pub fn transient_storage_value_pair(&self) -> (Word, Word) {
+        match self {
+            Self::TransientStorage {
+                value, value_prev, ..
+            } => (*value, *value_prev),
+            _ => unreachable!("{:?}", self),
+        }
+    }
+
     pub fn call_context_value(&self) -> Word {
         match self {
             Self::CallContext { value, .. } => *value,
@@ -496,7 +544,9 @@ impl Rw {
             | Self::Memory { rw_counter, .. }
             | Self::Stack { rw_counter, .. }
             | Self::Global { rw_counter, .. }
+            | Self::Table { rw_counter, .. }
             | Self::AccountStorage { rw_counter, .. }
+            | Self::TransientStorage { rw_counter, .. }
             | Self::TxAccessListAccount { rw_counter, .. }
             | Self::TxAccessListAccountStorage { rw_counter, .. }
             | Self::TxRefund { rw_counter, .. }
@@ -513,7 +563,9 @@ impl Rw {
             Self::Memory { is_write, .. }
             | Self::Stack { is_write, .. }
             | Self::Global { is_write, .. }
+            | Self::Table { is_write, .. }
             | Self::AccountStorage { is_write, .. }
+            | Self::TransientStorage { is_write, .. }
             | Self::TxAccessListAccount { is_write, .. }
             | Self::TxAccessListAccountStorage { is_write, .. }
             | Self::TxRefund { is_write, .. }
@@ -530,7 +582,9 @@ impl Rw {
             Self::Memory { .. } => RwTableTag::Memory,
             Self::Stack { .. } => RwTableTag::Stack,
             Self::Global { .. } => RwTableTag::Global,
+            Self::Table { .. } => RwTableTag::Table,
             Self::AccountStorage { .. } => RwTableTag::AccountStorage,
+            Self::TransientStorage { .. } => RwTableTag::TransientStorage,
             Self::TxAccessListAccount { .. } => RwTableTag::TxAccessListAccount,
             Self::TxAccessListAccountStorage { .. } => RwTableTag::TxAccessListAccountStorage,
             Self::TxRefund { .. } => RwTableTag::TxRefund,
@@ -544,6 +598,7 @@ impl Rw {
     pub fn id(&self) -> Option<usize> {
         match self {
             Self::AccountStorage { tx_id, .. }
+            | Self::TransientStorage { tx_id, .. }
             | Self::TxAccessListAccount { tx_id, .. }
             | Self::TxAccessListAccountStorage { tx_id, .. }
             | Self::TxRefund { tx_id, .. }
@@ -552,6 +607,7 @@ impl Rw {
             Self::CallContext { call_id, .. }
             | Self::Stack { call_id, .. }
             | Self::Global { call_id, .. }
+            | Self::Table { call_id, .. }
             | Self::Memory { call_id, .. } => Some(*call_id),
             Self::Start { .. } | Self::Account { .. } => None,
         }
@@ -570,6 +626,9 @@ impl Rw {
             }
             | Self::AccountStorage {
                 account_address, ..
+            }
+            | Self::TransientStorage {
+                account_address, ..
             } => Some(*account_address),
             Self::Memory { memory_address, .. } => Some(U256::from(*memory_address).to_address()),
             Self::Stack { stack_pointer, .. } => {
@@ -578,6 +637,7 @@ impl Rw {
             Self::Global { global_index, .. } => {
                 Some(Address::from_low_u64_be(*global_index as u64))
             }
+            Self::Table { elem_index, .. } => Some(Address::from_low_u64_be(*elem_index as u64)),
             Self::TxLog {
                 log_id,
                 field_tag,
@@ -599,11 +659,13 @@ impl Rw {
             Self::Account { field_tag, .. } => Some(*field_tag as u64),
             Self::CallContext { field_tag, .. } => Some(*field_tag as u64),
             Self::TxReceipt { field_tag, .. } => Some(*field_tag as u64),
+            Self::Table { table_index, .. } => Some(*table_index as u64),
             Self::Start { .. }
             | Self::Memory { .. }
             | Self::Stack { .. }
             | Self::Global { .. }
             | Self::AccountStorage { .. }
+            | Self::TransientStorage { .. }
             | Self::TxAccessListAccount { .. }
             | Self::TxAccessListAccountStorage { .. }
             | Self::TxRefund { .. }
@@ -614,11 +676,13 @@ impl Rw {
     pub fn storage_key(&self) -> Option<Word> {
         match self {
             Self::AccountStorage { storage_key, .. }
+            | Self::TransientStorage { storage_key, .. }
             | Self::TxAccessListAccountStorage { storage_key, .. } => Some(*storage_key),
             Self::Start { .. }
             | Self::CallContext { .. }
             | Self::Stack { .. }
             | Self::Global { .. }
+            | Self::Table { .. }
             | Self::Memory { .. }
             | Self::TxRefund { .. }
             | Self::Account { .. }
@@ -665,13 +729,14 @@ impl Rw {
                 | AccountFieldTag::NonExisting
                 | AccountFieldTag::CodeSize => value.to_scalar().unwrap(),
             },
-            Self::AccountStorage { value, .. } => {
+            Self::AccountStorage { value, .. }
+            | Self::TransientStorage { value, .. } => {
                 rlc::value(&value.to_le_bytes(), randomness)
             }
             Self::Stack { value, .. } => {
                 value.to_scalar().unwrap()
             }
-            Self::Global { value, .. } => {
+            Self::Global { value, .. } | Self::Table { value, .. } => {
                 value.to_scalar().unwrap()
             }
 
@@ -710,7 +775,8 @@ impl Rw {
                 | AccountFieldTag::NonExisting
                 | AccountFieldTag::CodeSize => value_prev.to_scalar().unwrap(),
             }),
-            Self::AccountStorage { value_prev, .. } => {
+            Self::AccountStorage { value_prev, .. }
+            | Self::TransientStorage { value_prev, .. } => {
                 Some(rlc::value(&value_prev.to_le_bytes(), randomness))
             }
             Self::TxAccessListAccount { is_warm_prev, .. }
@@ -718,9 +784,11 @@ impl Rw {
                 Some(F::from(*is_warm_prev as u64))
             }
             Self::TxRefund { value_prev, .. } => Some(F::from(*value_prev)),
+            Self::Global { value_prev, .. } | Self::Table { value_prev, .. } => {
+                Some(value_prev.to_scalar().unwrap())
+            }
             Self::Start { .. }
             | Self::Stack { .. }
-            | Self::Global { .. }
             | Self::Memory { .. }
             | Self::CallContext { .. }
             | Self::TxLog { .. }
@@ -835,6 +903,22 @@ impl From<&operation::OperationContainer> for RwMap {
                 })
                 .collect(),
         );
+        rws.insert(
+            RwTableTag::TransientStorage,
+            container
+                .transient_storage
+                .iter()
+                .map(|op| Rw::TransientStorage {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    tx_id: op.op().tx_id,
+                    account_address: op.op().address,
+                    storage_key: op.op().key,
+                    value: op.op().value,
+                    value_prev: op.op().value_prev,
+                })
+                .collect(),
+        );
         rws.insert(
             RwTableTag::CallContext,
             container
@@ -877,6 +961,14 @@ impl From<&operation::OperationContainer> for RwMap {
                         CallContextField::MemorySize => CallContextFieldTag::MemorySize,
                         CallContextField::ReversibleWriteCounter => CallContextFieldTag::ReversibleWriteCounter,
                         CallContextField::InternalFunctionId => CallContextFieldTag::InternalFunctionId,
+                        CallContextField::NumLocals => CallContextFieldTag::NumLocals,
+                        CallContextField::FrameId => CallContextFieldTag::FrameId,
+                        CallContextField::CallerFrameId => CallContextFieldTag::CallerFrameId,
+                        CallContextField::CallerFunctionIndex => CallContextFieldTag::CallerFunctionIndex,
+                        CallContextField::ReturnProgramCounter => CallContextFieldTag::ReturnProgramCounter,
+                        CallContextField::LocalsBase => CallContextFieldTag::LocalsBase,
+                        CallContextField::NumParams => CallContextFieldTag::NumParams,
+                        CallContextField::NumResults => CallContextFieldTag::NumResults,
                     },
                     value: op.op().value,
                 })
@@ -908,6 +1000,23 @@ impl From<&operation::OperationContainer> for RwMap {
                     call_id: op.op().call_id(),
                     global_index: op.op().address() as usize,
                     value: *op.op().value(),
+                    value_prev: *op.op().value_prev(),
+                })
+                .collect(),
+        );
+        rws.insert(
+            RwTableTag::Table,
+            container
+                .tables
+                .iter()
+                .map(|op| Rw::Table {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    call_id: op.op().call_id(),
+                    table_index: op.op().table_index(),
+                    elem_index: op.op().elem_index(),
+                    value: *op.op().value(),
+                    value_prev: *op.op().value_prev(),
                 })
                 .collect(),
         );
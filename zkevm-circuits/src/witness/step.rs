@@ -4,7 +4,7 @@ use bus_mapping::{
     circuit_input_builder,
     error::{
         ContractAddressCollisionError, DepthError, ExecError, InsufficientBalanceError,
-        NonceUintOverflowError, OogError,
+        NonceUintOverflowError, OogError, WasmTrapKind,
     },
     evm::OpcodeId,
     operation,
@@ -59,6 +59,8 @@ pub struct ExecStep {
     pub max_stack_height: u32,
     /// Num locals
     pub num_locals: u32,
+    /// Immediate operands of the WASM instruction
+    pub params: Vec<u64>,
 }
 
 impl fmt::Debug for ExecStep {
@@ -120,6 +122,15 @@ impl From<&ExecError> for ExecutionState {
                 ExecutionState::ErrorCodeStore
             }
             ExecError::PrecompileFailed => ExecutionState::ErrorPrecompileFailed,
+            ExecError::WasmTrap(trap_kind) => match trap_kind {
+                WasmTrapKind::Unreachable => ExecutionState::ErrorWasmUnreachable,
+                WasmTrapKind::MemoryOutOfBounds => ExecutionState::ErrorWasmMemoryOutOfBounds,
+                WasmTrapKind::DivisionByZero => ExecutionState::ErrorWasmDivisionByZero,
+                WasmTrapKind::IndirectCallMismatch => {
+                    ExecutionState::ErrorWasmIndirectCallMismatch
+                }
+                WasmTrapKind::StackHeightExceeded => ExecutionState::ErrorWasmStackHeightExceeded,
+            },
             ExecError::OutOfGas(oog_error) => match oog_error {
                 OogError::Constant => ExecutionState::ErrorOutOfGasConstant,
                 OogError::StaticMemoryExpansion => {
@@ -205,10 +216,17 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::SetLocal |
                     OpcodeId::TeeLocal => ExecutionState::WASM_LOCAL,
 
+                    OpcodeId::CurrentMemory |
+                    OpcodeId::GrowMemory => ExecutionState::WASM_MEMORY,
+
                     OpcodeId::Call |
                     OpcodeId::CallIndirect => ExecutionState::WASM_CALL,
 
-                    OpcodeId::Return |
+                    OpcodeId::Return => ExecutionState::WASM_RETURN,
+
+                    OpcodeId::ReturnCall |
+                    OpcodeId::ReturnCallIndirect => ExecutionState::WASM_RETURN_CALL,
+
                     OpcodeId::Br |
                     OpcodeId::BrIf |
                     OpcodeId::BrTable => ExecutionState::WASM_BREAK,
@@ -267,6 +285,9 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::SHL | OpcodeId::SHR => ExecutionState::SHL_SHR,
                     OpcodeId::SLOAD => ExecutionState::SLOAD,
                     OpcodeId::SSTORE => ExecutionState::SSTORE,
+                    OpcodeId::TLOAD => ExecutionState::TLOAD,
+                    OpcodeId::TSTORE => ExecutionState::TSTORE,
+                    OpcodeId::MCOPY => ExecutionState::MCOPY,
                     OpcodeId::CALLDATASIZE => ExecutionState::CALLDATASIZE,
                     OpcodeId::CALLDATACOPY => ExecutionState::CALLDATACOPY,
                     OpcodeId::CHAINID => ExecutionState::CHAINID,
@@ -319,7 +340,9 @@ pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u6
                     operation::Target::Memory => RwTableTag::Memory,
                     operation::Target::Stack => RwTableTag::Stack,
                     operation::Target::Global => RwTableTag::Global,
+                    operation::Target::Table => RwTableTag::Table,
                     operation::Target::Storage => RwTableTag::AccountStorage,
+                    operation::Target::TransientStorage => RwTableTag::TransientStorage,
                     operation::Target::TxAccessListAccount => RwTableTag::TxAccessListAccount,
                     operation::Target::TxAccessListAccountStorage => {
                         RwTableTag::TxAccessListAccountStorage
@@ -351,7 +374,8 @@ pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u6
         log_id: step.log_id,
         block_num,
         function_index: step.function_index,
-        max_stack_height: step.function_index,
+        max_stack_height: step.max_stack_height,
         num_locals: step.num_locals,
+        params: step.params.clone(),
     }
 }
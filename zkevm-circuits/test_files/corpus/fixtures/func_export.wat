@@ -0,0 +1,5 @@
+(module
+  (type (func (param i32) (result i32)))
+  (func (type 0) local.get 0)
+  (memory 1)
+  (export "f" (func 0)))
@@ -0,0 +1,7 @@
+(module
+  (import "env" "g" (func (param i32)))
+  (global (mut i32) (i32.const 0))
+  (func i32.const 7 call 0)
+  (start 1)
+  (memory 1)
+  (data (i32.const 0) "hi"))